@@ -0,0 +1,820 @@
+/// Agent chain definitions and execution history
+///
+/// Chains (ordered steps, each handled by a registered agent) are stored in
+/// knowledge_companion.db. A step can fan out to several agents that work on the same
+/// input concurrently, with the step's own agent merging their outputs.
+///
+/// Chains can also be written as a YAML/JSON pipeline whose steps have ids, `when`
+/// conditions on earlier outputs and `loop`s back to earlier steps, e.g.
+///
+/// ```yaml
+/// steps:
+///   - { id: draft, agent_id: writer-v1 }
+///   - id: critique
+///     agent_id: reviewer-v1
+///     loop: { to: draft, if: "critique.score < 7", max: 3 }
+///   - { id: review, pause: "Approve the draft or say what to change" }
+/// ```
+///
+/// A `pause` step runs no agent: the run waits for the user's input (see
+/// `resume_chain`), which later steps and conditions see as that step's output. A step
+/// with `reflect` has a critic review its output, which its agent then revises once.
+///
+/// Every orchestrate_agents run is recorded with its task and context, a snapshot of the
+/// chain as it was when it ran, and each agent's input, output, timing and estimated
+/// tokens, so orchestrations can be audited and repeated.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use crate::reflection::ReflectOptions;
+
+/// Upper bound on a loop's `max`, so a pipeline can't run away
+pub const MAX_LOOP_REPEATS: usize = 10;
+
+lazy_static::lazy_static! {
+    // "Score: 6/10", "score = 6.5", "**Score:** 6", "a score of 6"
+    static ref SCORE: Regex = Regex::new(r"(?i)\bscore\b\W{0,4}(?:of\s+|is\s+)?(\d+(?:\.\d+)?)").unwrap();
+}
+
+pub fn init_chain_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_chains (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            steps TEXT NOT NULL,
+            builtin INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS chain_runs (
+            run_id TEXT PRIMARY KEY,
+            chain_id TEXT NOT NULL,
+            chain TEXT NOT NULL,
+            task TEXT NOT NULL,
+            context TEXT,
+            status TEXT NOT NULL,
+            final_output TEXT,
+            error TEXT,
+            total_tokens INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL,
+            finished_at TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS chain_run_steps (
+            run_id TEXT NOT NULL,
+            step_index INTEGER NOT NULL,
+            stage INTEGER NOT NULL DEFAULT 0,
+            role TEXT NOT NULL DEFAULT 'agent',
+            agent_id TEXT NOT NULL,
+            agent_name TEXT NOT NULL,
+            input TEXT NOT NULL,
+            output TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            error TEXT,
+            started_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            estimated_tokens INTEGER NOT NULL,
+            PRIMARY KEY (run_id, step_index)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_chain_runs_chain ON chain_runs(chain_id, created_at)",
+        [],
+    )?;
+
+    for chain in builtin_chains() {
+        conn.execute(
+            "INSERT OR IGNORE INTO agent_chains (id, name, description, steps, builtin, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 1, ?5, ?5)",
+            params![chain.id, chain.name, chain.description, steps_json(&chain.steps), chain.created_at],
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainStep {
+    /// Registered agent (agents.json) that handles this step; for a parallel step, the
+    /// reducer that merges the branches' outputs. Unused by pause steps.
+    #[serde(default)]
+    pub agent_id: String,
+    /// Extra instructions for this step, added to the agent's input
+    #[serde(default)]
+    pub instructions: Option<String>,
+    /// Agents that process the step's input concurrently before `agent_id` merges them.
+    /// Branches are single agents; their own `parallel` lists aren't run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parallel: Vec<ChainStep>,
+    /// Name conditions and loops use to refer to this step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Condition (see `Condition::parse`); the step is skipped when it doesn't hold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Go back to an earlier step after this one while a condition holds
+    #[serde(default, rename = "loop", skip_serializing_if = "Option::is_none")]
+    pub loop_back: Option<LoopBack>,
+    /// Pause for human input instead of running an agent, showing the user this prompt
+    /// along with the latest output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pause: Option<String>,
+    /// Have a critic review the step's output against a rubric and the agent revise it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflect: Option<ReflectOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopBack {
+    /// Id of the step to go back to: this one or an earlier one
+    pub to: String,
+    #[serde(rename = "if")]
+    pub condition: String,
+    /// Times to go back at most
+    #[serde(default = "default_loop_max")]
+    pub max: usize,
+}
+
+fn default_loop_max() -> usize {
+    3
+}
+
+impl ChainStep {
+    pub fn agent(agent_id: &str) -> Self {
+        Self {
+            agent_id: agent_id.to_string(),
+            instructions: None,
+            parallel: Vec::new(),
+            id: None,
+            when: None,
+            loop_back: None,
+            pause: None,
+            reflect: None,
+        }
+    }
+
+    pub fn pause(prompt: &str) -> Self {
+        Self { pause: Some(prompt.to_string()), ..Self::agent("") }
+    }
+
+    /// Every agent the step uses: branches first, then the step's own agent and its
+    /// critic. Pause steps use none.
+    pub fn agent_ids(&self) -> Vec<&str> {
+        if self.pause.is_some() {
+            return Vec::new();
+        }
+        let mut ids: Vec<&str> = self.parallel.iter().map(|branch| branch.agent_id.as_str()).collect();
+        ids.push(&self.agent_id);
+        ids.extend(self.reflect.as_ref().and_then(|r| r.critic_id.as_deref()));
+        ids
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainDefinition {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub steps: Vec<ChainStep>,
+    #[serde(default)]
+    pub builtin: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ChainDefinition {
+    pub fn new(id: String, name: String, description: String, steps: Vec<ChainStep>) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self { id, name, description, steps, builtin: false, created_at: now.clone(), updated_at: now }
+    }
+}
+
+/// Chains that ship with the app; users can add their own with create_agent_chain
+pub fn builtin_chains() -> Vec<ChainDefinition> {
+    let builtin = |id: &str, name: &str, description: &str, agents: &[&str]| ChainDefinition {
+        builtin: true,
+        // Fixed so re-seeding never changes the recorded date
+        created_at: "2024-01-01T00:00:00+00:00".to_string(),
+        updated_at: "2024-01-01T00:00:00+00:00".to_string(),
+        ..ChainDefinition::new(id.to_string(), name.to_string(), description.to_string(), agents.iter().map(|a| ChainStep::agent(a)).collect())
+    };
+    vec![
+        builtin(
+            "content-creation-v1",
+            "Content Creation Pipeline",
+            "Full pipeline: Research → Plan → Write → Review",
+            &["researcher-v1", "planner-v1", "writer-v1", "reviewer-v1"],
+        ),
+        builtin(
+            "research-review-v1",
+            "Research with Review",
+            "Research with quality review",
+            &["researcher-v1", "reviewer-v1"],
+        ),
+        ChainDefinition {
+            steps: vec![
+                ChainStep { id: Some("draft".to_string()), ..ChainStep::agent("writer-v1") },
+                ChainStep {
+                    id: Some("critique".to_string()),
+                    instructions: Some("Critique the latest draft and list concrete improvements. End with \"Score: N/10\".".to_string()),
+                    loop_back: Some(LoopBack { to: "draft".to_string(), condition: "critique.score < 7".to_string(), max: 3 }),
+                    ..ChainStep::agent("reviewer-v1")
+                },
+            ],
+            ..builtin("draft-critique-loop-v1", "Draft and Critique", "Write → Critique, redrafting until the score reaches 7", &[])
+        },
+        ChainDefinition {
+            steps: vec![
+                ChainStep {
+                    instructions: Some("Merge the research and the plan into one brief, resolving any conflicts between them.".to_string()),
+                    parallel: vec![ChainStep::agent("researcher-v1"), ChainStep::agent("planner-v1")],
+                    ..ChainStep::agent("reviewer-v1")
+                },
+                ChainStep::agent("writer-v1"),
+            ],
+            ..builtin("research-plan-parallel-v1", "Parallel Research and Planning", "Research ‖ Plan → Merge → Write", &[])
+        },
+        ChainDefinition {
+            steps: vec![
+                ChainStep::agent("planner-v1"),
+                ChainStep {
+                    id: Some("review".to_string()),
+                    ..ChainStep::pause("Review the plan: approve it, or say what to change before it's written up")
+                },
+                ChainStep {
+                    instructions: Some("Revise the plan with the human review's feedback, if any.".to_string()),
+                    when: Some("review.output not contains \"approve\"".to_string()),
+                    ..ChainStep::agent("planner-v1")
+                },
+                ChainStep::agent("writer-v1"),
+            ],
+            ..builtin("plan-review-write-v1", "Plan with Human Review", "Plan → Your review → Revise → Write", &[])
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Test {
+    Score(Comparison, f64),
+    Contains(String),
+    NotContains(String),
+}
+
+/// A test on a step's output: `[step.]score <op> <number>` or `[step.]output [not] contains "text"`.
+/// Without a step it applies to the step being run (for a loop) or the latest output (for `when`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub step: Option<String>,
+    test: Test,
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let invalid = || format!("Invalid condition '{}': expected e.g. `critique.score < 7` or `output contains \"APPROVED\"`", text);
+        let end = text.find(|c: char| !(c.is_alphanumeric() || "_-.".contains(c))).ok_or_else(invalid)?;
+        let (subject, rest) = text.split_at(end);
+        let (step, field) = match subject.rsplit_once('.') {
+            Some((step, field)) => (Some(step.to_string()), field),
+            None => (None, subject),
+        };
+        let rest = rest.trim();
+
+        let test = match field.to_lowercase().as_str() {
+            "score" => {
+                let (op, value) = ["<=", ">=", "!=", "==", "<", ">"]
+                    .iter()
+                    .find_map(|op| rest.strip_prefix(op).map(|value| (*op, value)))
+                    .ok_or_else(invalid)?;
+                let value: f64 = value.trim().parse().map_err(|_| invalid())?;
+                let comparison = match op {
+                    "<" => Comparison::Lt,
+                    "<=" => Comparison::Le,
+                    ">" => Comparison::Gt,
+                    ">=" => Comparison::Ge,
+                    "==" => Comparison::Eq,
+                    _ => Comparison::Ne,
+                };
+                Test::Score(comparison, value)
+            }
+            "output" => {
+                let (negated, needle) = match rest.strip_prefix("not contains") {
+                    Some(needle) => (true, needle),
+                    None => (false, rest.strip_prefix("contains").ok_or_else(invalid)?),
+                };
+                let needle = needle.trim().trim_matches(|c| c == '"' || c == '\'').to_lowercase();
+                if needle.is_empty() {
+                    return Err(invalid());
+                }
+                if negated { Test::NotContains(needle) } else { Test::Contains(needle) }
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(Self { step, test })
+    }
+
+    /// Whether the condition holds for `output`; score tests fail when the output has no score
+    pub fn holds(&self, output: &str) -> bool {
+        match &self.test {
+            Test::Score(comparison, value) => match score(output) {
+                Some(score) => match comparison {
+                    Comparison::Lt => score < *value,
+                    Comparison::Le => score <= *value,
+                    Comparison::Gt => score > *value,
+                    Comparison::Ge => score >= *value,
+                    Comparison::Eq => score == *value,
+                    Comparison::Ne => score != *value,
+                },
+                None => false,
+            },
+            Test::Contains(needle) => output.to_lowercase().contains(needle),
+            Test::NotContains(needle) => !output.to_lowercase().contains(needle),
+        }
+    }
+}
+
+/// The last score an agent gave in its output ("Score: 6/10")
+pub fn score(output: &str) -> Option<f64> {
+    SCORE.captures_iter(output).last().and_then(|c| c[1].parse().ok())
+}
+
+/// Index of the step with id `id`
+pub fn step_index(steps: &[ChainStep], id: &str) -> Option<usize> {
+    steps.iter().position(|s| s.id.as_deref() == Some(id))
+}
+
+/// A chain written as YAML or JSON
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pipeline {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub steps: Vec<ChainStep>,
+}
+
+/// Parse a pipeline definition (YAML, so JSON works too) and check its flow
+pub fn parse_pipeline(text: &str) -> Result<Pipeline, String> {
+    let pipeline: Pipeline = serde_yaml::from_str(text).map_err(|e| format!("Invalid pipeline: {}", e))?;
+    validate_flow(&pipeline.steps)?;
+    Ok(pipeline)
+}
+
+/// Check step ids are unique, conditions parse, `when` only looks at earlier steps and
+/// loops only go back
+pub fn validate_flow(steps: &[ChainStep]) -> Result<(), String> {
+    let mut seen: Vec<&str> = Vec::new();
+    let known = |seen: &[&str], id: &Option<String>| id.as_deref().map_or(true, |id| seen.contains(&id));
+
+    for (index, step) in steps.iter().enumerate() {
+        let label = step.id.clone().unwrap_or_else(|| format!("step {}", index + 1));
+        if step.pause.is_some() && (!step.parallel.is_empty() || step.loop_back.is_some() || step.reflect.is_some()) {
+            return Err(format!("Pause {} can't have parallel branches, a loop or reflection", label));
+        }
+        if let Some(when) = &step.when {
+            let condition = Condition::parse(when)?;
+            if !known(&seen, &condition.step) {
+                return Err(format!("Condition of {} refers to a step that doesn't run before it", label));
+            }
+        }
+        if let Some(id) = &step.id {
+            if seen.contains(&id.as_str()) {
+                return Err(format!("Duplicate step id '{}'", id));
+            }
+            seen.push(id);
+        }
+        if let Some(loop_back) = &step.loop_back {
+            let condition = Condition::parse(&loop_back.condition)?;
+            if !known(&seen, &condition.step) {
+                return Err(format!("Loop condition of {} refers to an unknown or later step", label));
+            }
+            if !seen.contains(&loop_back.to.as_str()) {
+                return Err(format!("Loop of {} must go back to this or an earlier step, not '{}'", label, loop_back.to));
+            }
+            if loop_back.max == 0 || loop_back.max > MAX_LOOP_REPEATS {
+                return Err(format!("Loop max of {} must be between 1 and {}", label, MAX_LOOP_REPEATS));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One agent execution of a recorded run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainRunStep {
+    /// Position in execution order
+    pub step_index: usize,
+    /// Chain step this execution belongs to
+    pub stage: usize,
+    /// "agent", "branch" (one of a parallel step's agents) or "reducer"
+    pub role: String,
+    pub agent_id: String,
+    pub agent_name: String,
+    pub input: String,
+    pub output: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub duration_ms: u64,
+    /// Prompt plus output, estimated like deep research token budgets
+    pub estimated_tokens: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainRunSummary {
+    pub run_id: String,
+    pub chain_id: String,
+    pub chain_name: String,
+    pub task: String,
+    /// "running", "paused" (waiting for human input), "complete", "incomplete" (a parallel
+    /// branch failed), "failed" or "limited" (a run limit stopped it)
+    pub status: String,
+    pub total_tokens: usize,
+    pub duration_ms: u64,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// A run with the chain it ran and every step it executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainRunRecord {
+    pub run_id: String,
+    pub chain: ChainDefinition,
+    pub task: String,
+    pub context: Option<serde_json::Value>,
+    pub status: String,
+    pub final_output: Option<String>,
+    pub error: Option<String>,
+    pub steps: Vec<ChainRunStep>,
+    pub total_tokens: usize,
+    pub duration_ms: u64,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+fn steps_json(steps: &[ChainStep]) -> String {
+    serde_json::to_string(steps).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(text: Option<String>) -> Option<T> {
+    text.and_then(|t| serde_json::from_str(&t).ok())
+}
+
+fn chain_from_row(row: &rusqlite::Row) -> rusqlite::Result<ChainDefinition> {
+    Ok(ChainDefinition {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        steps: parse_json(row.get(3)?).unwrap_or_default(),
+        builtin: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+/// Create or replace a chain definition
+pub fn save_chain(conn: &Connection, chain: &ChainDefinition) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO agent_chains (id, name, description, steps, builtin, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, description = excluded.description,
+             steps = excluded.steps, updated_at = excluded.updated_at",
+        params![chain.id, chain.name, chain.description, steps_json(&chain.steps), chain.builtin, chain.created_at, chain.updated_at],
+    )?;
+    Ok(())
+}
+
+pub fn get_chain(conn: &Connection, id: &str) -> rusqlite::Result<Option<ChainDefinition>> {
+    conn.query_row(
+        "SELECT id, name, description, steps, builtin, created_at, updated_at FROM agent_chains WHERE id = ?1",
+        params![id],
+        chain_from_row,
+    )
+    .optional()
+}
+
+/// Built-in chains first, then the user's newest first
+pub fn list_chains(conn: &Connection) -> rusqlite::Result<Vec<ChainDefinition>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, description, steps, builtin, created_at, updated_at
+         FROM agent_chains ORDER BY builtin DESC, created_at DESC",
+    )?;
+    let rows = stmt.query_map([], chain_from_row)?;
+    rows.collect()
+}
+
+/// Record that `chain` started running on `task`
+pub fn start_run(conn: &Connection, run_id: &str, chain: &ChainDefinition, task: &str, context: Option<&serde_json::Value>) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO chain_runs (run_id, chain_id, chain, task, context, status, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'running', ?6)",
+        params![
+            run_id,
+            chain.id,
+            serde_json::to_string(chain).unwrap_or_default(),
+            task,
+            context.map(|c| c.to_string()),
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn record_step(conn: &Connection, run_id: &str, step: &ChainRunStep) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO chain_run_steps
+             (run_id, step_index, stage, role, agent_id, agent_name, input, output, success, error, started_at, duration_ms, estimated_tokens)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            run_id,
+            step.step_index as i64,
+            step.stage as i64,
+            step.role,
+            step.agent_id,
+            step.agent_name,
+            step.input,
+            step.output,
+            step.success,
+            step.error,
+            step.started_at,
+            step.duration_ms as i64,
+            step.estimated_tokens as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Mark a run paused for human input, or running again
+pub fn set_run_status(conn: &Connection, run_id: &str, status: &str) -> rusqlite::Result<()> {
+    conn.execute("UPDATE chain_runs SET status = ?2 WHERE run_id = ?1", params![run_id, status])?;
+    Ok(())
+}
+
+pub fn finish_run(
+    conn: &Connection,
+    run_id: &str,
+    status: &str,
+    final_output: Option<&str>,
+    error: Option<&str>,
+    total_tokens: usize,
+    duration_ms: u64,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE chain_runs SET status = ?2, final_output = ?3, error = ?4, total_tokens = ?5, duration_ms = ?6, finished_at = ?7
+         WHERE run_id = ?1",
+        params![run_id, status, final_output, error, total_tokens as i64, duration_ms as i64, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+fn run_steps(conn: &Connection, run_id: &str) -> rusqlite::Result<Vec<ChainRunStep>> {
+    let mut stmt = conn.prepare(
+        "SELECT step_index, stage, role, agent_id, agent_name, input, output, success, error, started_at, duration_ms, estimated_tokens
+         FROM chain_run_steps WHERE run_id = ?1 ORDER BY step_index",
+    )?;
+    let rows = stmt.query_map(params![run_id], |row| {
+        Ok(ChainRunStep {
+            step_index: row.get::<_, i64>(0)? as usize,
+            stage: row.get::<_, i64>(1)? as usize,
+            role: row.get(2)?,
+            agent_id: row.get(3)?,
+            agent_name: row.get(4)?,
+            input: row.get(5)?,
+            output: row.get(6)?,
+            success: row.get(7)?,
+            error: row.get(8)?,
+            started_at: row.get(9)?,
+            duration_ms: row.get::<_, i64>(10)? as u64,
+            estimated_tokens: row.get::<_, i64>(11)? as usize,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn get_run(conn: &Connection, run_id: &str) -> rusqlite::Result<Option<ChainRunRecord>> {
+    let run = conn
+        .query_row(
+            "SELECT run_id, chain, task, context, status, final_output, error, total_tokens, duration_ms, created_at, finished_at
+             FROM chain_runs WHERE run_id = ?1",
+            params![run_id],
+            |row| {
+                let chain: String = row.get(1)?;
+                let chain = serde_json::from_str(&chain).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e))
+                })?;
+                Ok(ChainRunRecord {
+                    run_id: row.get(0)?,
+                    chain,
+                    task: row.get(2)?,
+                    context: parse_json(row.get(3)?),
+                    status: row.get(4)?,
+                    final_output: row.get(5)?,
+                    error: row.get(6)?,
+                    steps: Vec::new(),
+                    total_tokens: row.get::<_, i64>(7)? as usize,
+                    duration_ms: row.get::<_, i64>(8)? as u64,
+                    created_at: row.get(9)?,
+                    finished_at: row.get(10)?,
+                })
+            },
+        )
+        .optional()?;
+
+    match run {
+        Some(mut run) => {
+            run.steps = run_steps(conn, run_id)?;
+            Ok(Some(run))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Runs newest first, optionally only those of one chain
+pub fn list_runs(conn: &Connection, chain_id: Option<&str>, limit: usize) -> rusqlite::Result<Vec<ChainRunSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT run_id, chain_id, chain, task, status, total_tokens, duration_ms, created_at, finished_at
+         FROM chain_runs WHERE ?1 IS NULL OR chain_id = ?1 ORDER BY created_at DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![chain_id, limit as i64], |row| {
+        let chain: Option<ChainDefinition> = parse_json(row.get(2)?);
+        Ok(ChainRunSummary {
+            run_id: row.get(0)?,
+            chain_id: row.get(1)?,
+            chain_name: chain.map(|c| c.name).unwrap_or_default(),
+            task: row.get(3)?,
+            status: row.get(4)?,
+            total_tokens: row.get::<_, i64>(5)? as usize,
+            duration_ms: row.get::<_, i64>(6)? as u64,
+            created_at: row.get(7)?,
+            finished_at: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_chain_tables(&conn).unwrap();
+        conn
+    }
+
+    fn notes_chain() -> ChainDefinition {
+        ChainDefinition::new(
+            "chain-notes-1-v1".to_string(),
+            "Notes".to_string(),
+            String::new(),
+            vec![
+                ChainStep { parallel: vec![ChainStep::agent("researcher-v1"), ChainStep::agent("planner-v1")], ..ChainStep::agent("reviewer-v1") },
+                ChainStep::agent("writer-v1"),
+            ],
+        )
+    }
+
+    /// A finished one-step run of `chain` as `run-1`
+    fn record_finished_run(conn: &Connection, chain: &ChainDefinition) {
+        start_run(conn, "run-1", chain, "Summarize Rust", Some(&serde_json::json!({ "audience": "beginners" }))).unwrap();
+        let step = ChainRunStep {
+            step_index: 0,
+            stage: 0,
+            role: "branch".to_string(),
+            agent_id: "researcher-v1".to_string(),
+            agent_name: "Researcher".to_string(),
+            input: "Task: Summarize Rust".to_string(),
+            output: "Rust is fast.".to_string(),
+            success: true,
+            error: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: 1200,
+            estimated_tokens: 10,
+        };
+        record_step(conn, "run-1", &step).unwrap();
+        finish_run(conn, "run-1", "complete", Some("Rust is fast."), None, 10, 1300).unwrap();
+    }
+
+    #[test]
+    fn test_builtin_chains_are_seeded_once() {
+        let conn = chain_db();
+        // Seeding twice keeps one copy of each built-in chain
+        init_chain_tables(&conn).unwrap();
+        assert_eq!(list_chains(&conn).unwrap().len(), builtin_chains().len());
+    }
+
+    #[test]
+    fn test_parallel_step_agent_ids() {
+        assert_eq!(notes_chain().steps[0].agent_ids(), vec!["researcher-v1", "planner-v1", "reviewer-v1"]);
+    }
+
+    #[test]
+    fn test_saved_chain_is_listed_after_the_builtins() {
+        let conn = chain_db();
+        let chain = notes_chain();
+        save_chain(&conn, &chain).unwrap();
+        let chains = list_chains(&conn).unwrap();
+        assert!(chains[0].builtin);
+        assert_eq!(chains.last().unwrap().steps, chain.steps);
+    }
+
+    #[test]
+    fn test_run_round_trip() {
+        let conn = chain_db();
+        let chain = notes_chain();
+        save_chain(&conn, &chain).unwrap();
+        record_finished_run(&conn, &chain);
+
+        let run = get_run(&conn, "run-1").unwrap().unwrap();
+        assert_eq!((run.status.as_str(), run.steps.len(), run.total_tokens), ("complete", 1, 10));
+        assert_eq!(run.chain.steps, chain.steps);
+        assert_eq!(run.steps[0].role, "branch");
+        assert_eq!(run.context.unwrap()["audience"], "beginners");
+    }
+
+    #[test]
+    fn test_list_runs_filters_by_chain() {
+        let conn = chain_db();
+        let chain = notes_chain();
+        save_chain(&conn, &chain).unwrap();
+        record_finished_run(&conn, &chain);
+
+        let runs = list_runs(&conn, Some("chain-notes-1-v1"), 10).unwrap();
+        assert_eq!(runs[0].chain_name, "Notes");
+        assert!(list_runs(&conn, Some("content-creation-v1"), 10).unwrap().is_empty());
+        assert_eq!(list_runs(&conn, None, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_pipeline_with_a_loop() {
+        let pipeline = parse_pipeline(
+            "name: Essay\n\
+             steps:\n\
+             - { id: draft, agent_id: writer-v1 }\n\
+             - id: critique\n  agent_id: reviewer-v1\n  loop: { to: draft, if: \"critique.score < 7\" }\n\
+             - { agent_id: writer-v1, when: 'output not contains \"approved\"' }\n",
+        )
+        .unwrap();
+        assert_eq!(pipeline.name.as_deref(), Some("Essay"));
+        assert_eq!(pipeline.steps[1].loop_back.as_ref().unwrap().max, 3);
+        assert_eq!(step_index(&pipeline.steps, "critique"), Some(1));
+    }
+
+    #[test]
+    fn test_score_condition_reads_the_last_score() {
+        let below = Condition::parse("critique.score < 7").unwrap();
+        assert_eq!(below.step.as_deref(), Some("critique"));
+        assert!(below.holds("Needs work.\n\n**Score:** 6/10"));
+        assert!(!below.holds("Score: 4/10 before, now Score: 8/10"));
+        assert!(!below.holds("No score given"));
+    }
+
+    #[test]
+    fn test_contains_condition_and_bad_conditions() {
+        assert!(Condition::parse("output not contains \"APPROVED\"").unwrap().holds("Rejected"));
+        assert!(Condition::parse("score about 7").is_err());
+    }
+
+    #[test]
+    fn test_loops_must_go_back() {
+        let forward = vec![
+            ChainStep { loop_back: Some(LoopBack { to: "later".to_string(), condition: "score < 7".to_string(), max: 2 }), ..ChainStep::agent("writer-v1") },
+            ChainStep { id: Some("later".to_string()), ..ChainStep::agent("reviewer-v1") },
+        ];
+        assert!(validate_flow(&forward).is_err());
+    }
+
+    #[test]
+    fn test_pause_steps_run_no_agent_and_cannot_loop() {
+        let paused = parse_pipeline("steps:\n- { agent_id: planner-v1 }\n- { id: review, pause: Approve the plan? }\n").unwrap();
+        assert_eq!(paused.steps[1].pause.as_deref(), Some("Approve the plan?"));
+        assert!(paused.steps[1].agent_ids().is_empty());
+        let looping_pause = ChainStep { loop_back: Some(LoopBack { to: "review".to_string(), condition: "score < 7".to_string(), max: 2 }), ..paused.steps[1].clone() };
+        assert!(validate_flow(&[looping_pause]).is_err());
+    }
+
+    #[test]
+    fn test_builtin_chains_are_valid() {
+        for chain in builtin_chains() {
+            assert!(validate_flow(&chain.steps).is_ok(), "{}", chain.id);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+/// Live agent state per chat session
+///
+/// `chat_with_agent` and `chat_with_agent_stream` build a new `MinimaxAgent` per request, so
+/// nothing about a conversation outlived the call: the tool messages the model produced were
+/// lost unless the frontend sent them back, the session file was re-read for its settings each
+/// time, and `stop-generation` stopped every stream at once. Requests that name a session now
+/// share one `AgentSession` in `AppState`, holding the full history, the settings restored
+/// from the session file and a cancellation flag.
+///
+/// Each session sits behind its own tokio mutex that a request holds for its whole turn, so
+/// two requests to one session run one after the other while different sessions stream side
+/// by side. The cancellation flag lives outside that lock, so `stop_agent_session` reaches a
+/// turn in progress. `get_agent_session` returns what the last turn left, which is what the
+/// frontend saves.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::commands::AppState;
+use crate::minimax_enhanced::Message;
+use crate::session::SessionSettings;
+
+#[derive(Debug, Default)]
+pub struct AgentSession {
+    /// Everything the agent has seen, tool calls and results included
+    pub history: Vec<Message>,
+    /// From the session file, read when the session was first opened
+    pub settings: Option<SessionSettings>,
+    pub turns: usize,
+}
+
+/// A session and the flag that stops its current turn
+#[derive(Clone)]
+pub struct SessionHandle {
+    pub state: Arc<tokio::sync::Mutex<AgentSession>>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub struct AgentSessions(Mutex<HashMap<String, SessionHandle>>);
+
+impl AgentSessions {
+    /// The session called `name`, and whether it was just created
+    pub fn open(&self, name: &str) -> (SessionHandle, bool) {
+        let mut sessions = self.0.lock().unwrap();
+        match sessions.get(name) {
+            Some(handle) => (handle.clone(), false),
+            None => {
+                let handle = SessionHandle {
+                    state: Arc::new(tokio::sync::Mutex::new(AgentSession::default())),
+                    cancel: Arc::new(AtomicBool::new(false)),
+                };
+                sessions.insert(name.to_string(), handle.clone());
+                (handle, true)
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<SessionHandle> {
+        self.0.lock().unwrap().get(name).cloned()
+    }
+
+    pub fn close(&self, name: &str) -> bool {
+        self.0.lock().unwrap().remove(name).is_some()
+    }
+}
+
+/// The history to run a turn on: the stored one plus whatever the frontend added to it, or
+/// the frontend's messages alone when they no longer match (a message was edited, deleted or
+/// regenerated). Matching ignores tool messages, which the frontend doesn't keep.
+pub fn merge_history(stored: &[Message], incoming: Vec<Message>) -> Vec<Message> {
+    let visible: Vec<&Message> = stored.iter().filter(|m| m.role == "user" || (m.role == "assistant" && m.tool_calls.is_none())).collect();
+    let continues = !visible.is_empty()
+        && incoming.len() > visible.len()
+        && visible.iter().zip(&incoming).all(|(s, i)| s.role == i.role && s.content.trim() == i.content.trim());
+    if !continues {
+        return incoming;
+    }
+    let mut history = stored.to_vec();
+    history.extend(incoming.into_iter().skip(visible.len()));
+    history
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentSessionInfo {
+    pub history: Vec<Message>,
+    pub settings: Option<SessionSettings>,
+    pub turns: usize,
+    /// A turn is running right now
+    pub busy: bool,
+}
+
+/// The live state of session `name`, or None when no request has opened it since launch
+#[tauri::command]
+pub async fn get_agent_session(state: State<'_, AppState>, name: String) -> Result<Option<AgentSessionInfo>, String> {
+    let handle = match state.agent_sessions.get(&name) {
+        Some(handle) => handle,
+        None => return Ok(None),
+    };
+    let busy = handle.state.try_lock().is_err();
+    let session = handle.state.lock().await;
+    Ok(Some(AgentSessionInfo {
+        history: session.history.clone(),
+        settings: session.settings.clone(),
+        turns: session.turns,
+        busy,
+    }))
+}
+
+/// Stop the turn running in session `name`; false when there is no such session
+#[tauri::command]
+pub fn stop_agent_session(state: State<'_, AppState>, name: String) -> Result<bool, String> {
+    match state.agent_sessions.get(&name) {
+        Some(handle) => {
+            tracing::info!("🛑 Stopping session '{}'", name);
+            handle.cancel.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Forget the live state of session `name` (its saved file is untouched)
+#[tauri::command]
+pub fn close_agent_session(state: State<'_, AppState>, name: String) -> Result<bool, String> {
+    Ok(state.agent_sessions.close(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message { role: role.to_string(), content: content.to_string(), tool_calls: None, tool_call_id: None, timestamp: None }
+    }
+
+    fn stored_history() -> Vec<Message> {
+        let mut tool_call = message("assistant", "");
+        tool_call.tool_calls = Some(Vec::new());
+        vec![
+            message("user", "What is a lifetime?"),
+            tool_call,
+            message("tool", "{\"success\":true}"),
+            message("assistant", "A lifetime is a scope.\n"),
+        ]
+    }
+
+    fn sent_history() -> Vec<Message> {
+        vec![message("user", "What is a lifetime?"), message("assistant", "A lifetime is a scope."), message("user", "Example?")]
+    }
+
+    #[test]
+    fn test_open_reuses_the_named_session() {
+        let sessions = AgentSessions::default();
+        let (first, created) = sessions.open("rust-study");
+        assert!(created);
+        let (again, created) = sessions.open("rust-study");
+        assert!(!created && Arc::ptr_eq(&first.state, &again.state));
+    }
+
+    #[test]
+    fn test_cancel_flag_is_shared_with_the_stored_session() {
+        let sessions = AgentSessions::default();
+        let (session, _) = sessions.open("rust-study");
+        session.cancel.store(true, Ordering::Relaxed);
+        assert!(sessions.get("rust-study").unwrap().cancel.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_close_removes_the_session() {
+        let sessions = AgentSessions::default();
+        sessions.open("rust-study");
+        assert!(sessions.close("rust-study") && sessions.get("rust-study").is_none());
+    }
+
+    #[test]
+    fn test_merge_keeps_stored_tool_turns() {
+        let merged = merge_history(&stored_history(), sent_history());
+        assert_eq!(merged.iter().map(|m| m.role.as_str()).collect::<Vec<_>>(), vec!["user", "assistant", "tool", "assistant", "user"]);
+        assert_eq!(merged[4].content, "Example?");
+    }
+
+    #[test]
+    fn test_merge_starts_over_after_an_edited_question() {
+        let mut edited = sent_history();
+        edited[0].content = "What is a borrow?".to_string();
+        assert_eq!(merge_history(&stored_history(), edited).len(), 3);
+    }
+
+    #[test]
+    fn test_merge_without_stored_history_uses_what_was_sent() {
+        assert_eq!(merge_history(&[], sent_history()).len(), 3);
+    }
+
+    #[test]
+    fn test_merge_with_a_shorter_send_uses_what_was_sent() {
+        assert_eq!(merge_history(&stored_history(), sent_history()[..2].to_vec()).len(), 2);
+    }
+}
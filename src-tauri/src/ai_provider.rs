@@ -19,6 +19,14 @@ impl AIProvider {
         }
     }
 
+    /// The API genai talks to for this provider, checked against the network policy
+    pub fn endpoint(&self) -> &str {
+        match self {
+            AIProvider::Grok => "https://api.x.ai/v1/",
+            AIProvider::Minimax => "https://api.minimax.chat/v1/",
+        }
+    }
+
     /// Get the environment variable name for the API key
     pub fn api_key_env_var(&self) -> &str {
         match self {
@@ -69,6 +77,10 @@ impl AIService {
         // Create chat request
         let chat_req = ChatRequest::new(messages);
 
+        // genai has its own HTTP client, so the network policy is checked here
+        let endpoint = url::Url::parse(self.provider.endpoint()).context("Invalid provider endpoint")?;
+        crate::net::admit(&endpoint)?;
+
         // Make the request
         let chat_res = self.client
             .exec_chat(self.provider.model_name(), chat_req, None)
@@ -0,0 +1,349 @@
+/// Export flashcards to an Anki `.apkg` deck
+///
+/// An .apkg is a zip holding `collection.anki2` (an SQLite database in Anki's
+/// schema 11 format) and a `media` manifest. Cards come from the flashcards table and,
+/// optionally, from heading/definition pairs found in the selected notes.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use rusqlite::{params, Connection};
+use sha1::{Digest, Sha1};
+use walkdir::WalkDir;
+
+use crate::flashcards::NewCard;
+
+lazy_static::lazy_static! {
+    static ref DEFINITION: Regex = Regex::new(r"^\s*(?:[-*]\s+)?\*\*([^*]+)\*\*\s*[:\u{2014}-]\s*(.+)$").unwrap();
+}
+
+const MODEL_ID: i64 = 1_607_392_319;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnkiExportReport {
+    pub path: String,
+    pub deck_name: String,
+    pub cards: usize,
+}
+
+/// Question/answer pairs from a note: each heading with its first paragraph, plus
+/// `**Term**: definition` lines
+pub fn note_pairs(content: &str) -> Vec<NewCard> {
+    let (_, body) = crate::frontmatter::split_frontmatter(content);
+    let mut pairs = Vec::new();
+
+    for section in crate::kb_index::split_sections(body) {
+        if section.heading.is_empty() {
+            continue;
+        }
+        let paragraph: Vec<&str> = section.content
+            .lines()
+            .skip(1)
+            .skip_while(|l| l.trim().is_empty())
+            .take_while(|l| !l.trim().is_empty() && !l.starts_with("```"))
+            .collect();
+        let answer = paragraph.join(" ");
+        if answer.len() >= 20 {
+            pairs.push(NewCard { question: section.heading.clone(), answer });
+        }
+    }
+
+    for line in body.lines() {
+        if let Some(caps) = DEFINITION.captures(line) {
+            pairs.push(NewCard { question: caps[1].trim().to_string(), answer: caps[2].trim().to_string() });
+        }
+    }
+
+    pairs
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('\n', "<br>")
+}
+
+/// Anki's duplicate-check checksum: first 8 hex digits of the SHA-1 of the sort field
+fn field_checksum(field: &str) -> i64 {
+    let digest = Sha1::digest(field.as_bytes());
+    i64::from(u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]))
+}
+
+fn create_collection(conn: &Connection, deck_id: i64, deck_name: &str, now: i64) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE col (
+            id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+            ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+            conf text not null, models text not null, decks text not null, dconf text not null, tags text not null
+        );
+        CREATE TABLE notes (
+            id integer primary key, guid text not null, mid integer not null, mod integer not null,
+            usn integer not null, tags text not null, flds text not null, sfld integer not null,
+            csum integer not null, flags integer not null, data text not null
+        );
+        CREATE TABLE cards (
+            id integer primary key, nid integer not null, did integer not null, ord integer not null,
+            mod integer not null, usn integer not null, type integer not null, queue integer not null,
+            due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+            lapses integer not null, left integer not null, odue integer not null, odid integer not null,
+            flags integer not null, data text not null
+        );
+        CREATE TABLE revlog (
+            id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+            ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+            type integer not null
+        );
+        CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+        CREATE INDEX ix_notes_usn on notes (usn);
+        CREATE INDEX ix_cards_usn on cards (usn);
+        CREATE INDEX ix_revlog_usn on revlog (usn);
+        CREATE INDEX ix_cards_nid on cards (nid);
+        CREATE INDEX ix_cards_sched on cards (did, queue, due);
+        CREATE INDEX ix_revlog_cid on revlog (cid);
+        CREATE INDEX ix_notes_csum on notes (csum);",
+    )?;
+
+    let models = serde_json::json!({
+        MODEL_ID.to_string(): {
+            "id": MODEL_ID,
+            "name": "ThinkSpace Basic",
+            "type": 0,
+            "mod": now,
+            "usn": -1,
+            "sortf": 0,
+            "did": deck_id,
+            "tmpls": [{
+                "name": "Card 1",
+                "ord": 0,
+                "qfmt": "{{Front}}",
+                "afmt": "{{FrontSide}}<hr id=answer>{{Back}}",
+                "did": null,
+                "bqfmt": "",
+                "bafmt": ""
+            }],
+            "flds": [
+                { "name": "Front", "ord": 0, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] },
+                { "name": "Back", "ord": 1, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] }
+            ],
+            "css": ".card { font-family: arial; font-size: 20px; text-align: center; }",
+            "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+            "latexPost": "\\end{document}",
+            "tags": [],
+            "vers": [],
+            "req": [[0, "all", [0]]]
+        }
+    });
+
+    let deck = |id: i64, name: &str| serde_json::json!({
+        "id": id, "name": name, "desc": "", "mod": now, "usn": -1, "collapsed": false, "dyn": 0,
+        "conf": 1, "extendNew": 10, "extendRev": 50,
+        "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0]
+    });
+    let decks = serde_json::json!({
+        "1": deck(1, "Default"),
+        deck_id.to_string(): deck(deck_id, deck_name),
+    });
+
+    let dconf = serde_json::json!({
+        "1": {
+            "id": 1, "name": "Default", "mod": 0, "usn": 0, "maxTaken": 60, "autoplay": true,
+            "timer": 0, "replayq": true, "dyn": false,
+            "new": { "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "perDay": 20, "bury": true, "separate": true },
+            "rev": { "perDay": 100, "ease4": 1.3, "fuzz": 0.05, "maxIvl": 36500, "bury": true, "minSpace": 1 },
+            "lapse": { "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0 }
+        }
+    });
+
+    let conf = serde_json::json!({
+        "activeDecks": [1], "curDeck": 1, "newSpread": 0, "collapseTime": 1200, "timeLim": 0,
+        "estTimes": true, "dueCounts": true, "curModel": null, "nextPos": 1, "sortType": "noteFld",
+        "sortBackwards": false, "addToCur": true
+    });
+
+    conn.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![now, now * 1000, now * 1000, conf.to_string(), models.to_string(), decks.to_string(), dconf.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Write `cards` as a new deck to `output`
+pub fn write_apkg(output: &Path, deck_name: &str, cards: &[NewCard]) -> Result<usize, String> {
+    let now = chrono::Utc::now();
+    let now_secs = now.timestamp();
+    let base_id = now.timestamp_millis();
+    let deck_id = base_id;
+
+    let scratch = tempfile_path(output);
+    let _ = std::fs::remove_file(&scratch);
+    let result = (|| -> rusqlite::Result<usize> {
+        let conn = Connection::open(&scratch)?;
+        create_collection(&conn, deck_id, deck_name, now_secs)?;
+
+        let tx = conn.unchecked_transaction()?;
+        for (i, card) in cards.iter().enumerate() {
+            let id = base_id + i as i64 + 1;
+            let front = html_escape(&card.question);
+            let back = html_escape(&card.answer);
+            let guid = uuid::Uuid::new_v4().simple().to_string()[..10].to_string();
+
+            tx.execute(
+                "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ' thinkspace ', ?5, ?6, ?7, 0, '')",
+                params![id, guid, MODEL_ID, now_secs, format!("{}\u{1f}{}", front, back), front, field_checksum(&front)],
+            )?;
+            tx.execute(
+                "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+                params![id, id, deck_id, now_secs, i as i64 + 1],
+            )?;
+        }
+        tx.commit()?;
+        Ok(cards.len())
+    })();
+
+    let count = result.map_err(|e| format!("Failed to build Anki collection: {}", e))?;
+    let collection = std::fs::read(&scratch).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&scratch);
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = std::fs::File::create(output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file("collection.anki2", options).map_err(|e| e.to_string())?;
+    zip.write_all(&collection).map_err(|e| e.to_string())?;
+    zip.start_file("media", options).map_err(|e| e.to_string())?;
+    zip.write_all(b"{}").map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+
+    Ok(count)
+}
+
+fn tempfile_path(output: &Path) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "{}-{}.anki2",
+        output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        uuid::Uuid::new_v4().simple()
+    ))
+}
+
+/// Markdown notes under `kb_root` matching a `#tag`/`tag:` selector or a folder prefix
+fn select_notes(kb_root: &Path, selector: Option<&str>) -> Vec<(String, PathBuf)> {
+    let tag = selector.and_then(|s| s.strip_prefix("tag:").or_else(|| s.strip_prefix('#'))).map(|t| t.to_lowercase());
+    let folder = if tag.is_none() { selector.map(|s| s.trim_matches('/').to_string()) } else { None };
+
+    let mut notes = Vec::new();
+    for folder_name in crate::kb_index::KB_FOLDERS {
+        let root = kb_root.join(folder_name);
+        for entry in WalkDir::new(&root).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() || path.extension().map(|e| e != "md").unwrap_or(true) {
+                continue;
+            }
+            let key = crate::kb_index::path_key(kb_root, path);
+            let selected = match (&tag, &folder) {
+                (Some(tag), _) => crate::frontmatter::read_frontmatter(path).map(|fm| fm.tags.contains(tag)).unwrap_or(false),
+                (None, Some(folder)) => key.starts_with(folder.as_str()),
+                (None, None) => true,
+            };
+            if selected {
+                notes.push((key, path.to_path_buf()));
+            }
+        }
+    }
+    notes
+}
+
+/// Export flashcards (and optionally heading/definition pairs) to an .apkg deck.
+///
+/// `folder_or_tag` is a KB folder prefix like `research/rust` or a tag like `#rust` / `tag:rust`;
+/// omit it to export every flashcard.
+#[tauri::command]
+pub async fn export_to_anki(
+    app_handle: tauri::AppHandle,
+    folder_or_tag: Option<String>,
+    output_path: Option<String>,
+    include_note_pairs: Option<bool>,
+) -> Result<AnkiExportReport, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let selector = folder_or_tag.as_deref().filter(|s| !s.trim().is_empty());
+
+    let notes = select_notes(&kb_root, selector);
+    let note_keys: std::collections::HashSet<&str> = notes.iter().map(|(k, _)| k.as_str()).collect();
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut cards: Vec<NewCard> = {
+        let mut stmt = conn.prepare("SELECT source_path, question, answer FROM flashcards ORDER BY id")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, Option<String>>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        }).map_err(|e| e.to_string())?;
+
+        rows.filter_map(|r| r.ok())
+            .filter(|(source, _, _)| selector.is_none() || source.as_deref().map(|s| note_keys.contains(s)).unwrap_or(false))
+            .map(|(_, question, answer)| NewCard { question, answer })
+            .collect()
+    };
+
+    if include_note_pairs.unwrap_or(false) {
+        for (_, path) in &notes {
+            if let Ok(content) = std::fs::read_to_string(path) {
+                cards.extend(note_pairs(&content));
+            }
+        }
+    }
+
+    if cards.is_empty() {
+        return Err("No flashcards matched; generate some or enable note pairs".to_string());
+    }
+
+    let deck_name = match selector {
+        Some(s) => format!("ThinkSpace::{}", s.trim_start_matches('#').trim_start_matches("tag:").replace('/', "::")),
+        None => "ThinkSpace".to_string(),
+    };
+    let output = match output_path {
+        Some(p) => PathBuf::from(p),
+        None => dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or("Could not determine an output folder")?
+            .join(format!("{}.apkg", deck_name.replace("::", "-"))),
+    };
+
+    let count = write_apkg(&output, &deck_name, &cards)?;
+    tracing::info!("🗂️ Exported {} card(s) to {}", count, output.display());
+
+    Ok(AnkiExportReport { path: output.to_string_lossy().to_string(), deck_name, cards: count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_pairs_from_headings_and_definitions() {
+        let note = "---\ntitle: Rust\n---\n# Rust\n\n## Ownership\nEvery value in Rust has exactly one owner at a time.\n\nMore text.\n\n- **Borrow**: a reference that does not take ownership\n";
+        let pairs = note_pairs(note);
+
+        assert!(pairs.iter().any(|p| p.question == "Ownership" && p.answer == "Every value in Rust has exactly one owner at a time."));
+        assert!(pairs.iter().any(|p| p.question == "Borrow" && p.answer == "a reference that does not take ownership"));
+    }
+
+    #[test]
+    fn test_write_apkg_contains_collection() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("deck.apkg");
+        let cards = vec![NewCard { question: "2 + 2?".to_string(), answer: "4".to_string() }];
+
+        assert_eq!(write_apkg(&output, "ThinkSpace::test", &cards).unwrap(), 1);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&output).unwrap()).unwrap();
+        let collection_path = dir.path().join("collection.anki2");
+        let mut collection = archive.by_name("collection.anki2").unwrap();
+        std::io::copy(&mut collection, &mut std::fs::File::create(&collection_path).unwrap()).unwrap();
+
+        let conn = Connection::open(&collection_path).unwrap();
+        let notes: i64 = conn.query_row("SELECT COUNT(*) FROM notes", [], |r| r.get(0)).unwrap();
+        let cards: i64 = conn.query_row("SELECT COUNT(*) FROM cards", [], |r| r.get(0)).unwrap();
+        assert_eq!((notes, cards), (1, 1));
+    }
+}
@@ -0,0 +1,283 @@
+/// Image/PDF attachments for knowledge base notes
+///
+/// Attachments are copied into an `assets/` folder next to the note that uses them and
+/// linked relatively, so notes stay portable. Files in `assets/` folders that no note
+/// links to any more can be cleaned up with `cleanup_orphaned_attachments`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use walkdir::WalkDir;
+
+lazy_static::lazy_static! {
+    /// Markdown links and images: captures the prefix up to `(` and the target
+    static ref LINK: Regex = Regex::new(r"(!?\[[^\]]*\]\()(<[^>]+>|[^)\s]+)(\))").unwrap();
+    /// Obsidian-style embeds: ![[image.png]]
+    static ref EMBED: Regex = Regex::new(r"!\[\[([^\]|#]+)(?:[|#][^\]]*)?\]\]").unwrap();
+}
+
+pub const ASSETS_DIR: &str = "assets";
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    /// Path relative to the note, as used in its links
+    pub link: String,
+    pub absolute_path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddAttachmentResult {
+    pub attachment: Attachment,
+    pub markdown: String,
+    pub links_rewritten: usize,
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Resolve `..`/`.` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn decode_target(target: &str) -> String {
+    let target = target.trim_start_matches('<').trim_end_matches('>');
+    let target = target.split('#').next().unwrap_or(target);
+    let target = target.strip_prefix("file://").unwrap_or(target);
+    urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string())
+}
+
+/// Local files a note links to, resolved against the note's directory
+pub fn referenced_files(content: &str, note_dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for caps in LINK.captures_iter(content) {
+        let target = &caps[2];
+        if target.contains("://") && !target.starts_with("file://") {
+            continue;
+        }
+        files.push(normalize(&note_dir.join(decode_target(target))));
+    }
+    for caps in EMBED.captures_iter(content) {
+        let name = caps[1].trim();
+        // Obsidian resolves bare embeds by name; check the note's assets folder first
+        files.push(normalize(&note_dir.join(ASSETS_DIR).join(name)));
+        files.push(normalize(&note_dir.join(name)));
+    }
+
+    files
+}
+
+/// Point links that reference `original` at `new_link` instead
+pub fn rewrite_links_to(content: &str, note_dir: &Path, original: &Path, new_link: &str) -> (String, usize) {
+    let original = normalize(original);
+    let mut count = 0;
+
+    let rewritten = LINK.replace_all(content, |caps: &regex::Captures| {
+        let target = decode_target(&caps[2]);
+        if normalize(&note_dir.join(&target)) == original {
+            count += 1;
+            format!("{}{}{}", &caps[1], new_link, &caps[3])
+        } else {
+            caps[0].to_string()
+        }
+    });
+
+    (rewritten.into_owned(), count)
+}
+
+/// Pick a destination in `assets_dir` for `file_name`, reusing an identical existing file
+pub fn destination_for(assets_dir: &Path, source: &Path) -> std::io::Result<PathBuf> {
+    let file_name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "attachment".to_string());
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) => (stem.to_string(), format!(".{}", ext)),
+        None => (file_name.clone(), String::new()),
+    };
+    let source_bytes = std::fs::read(source)?;
+
+    let mut candidate = assets_dir.join(&file_name);
+    let mut n = 1;
+    while candidate.exists() {
+        if std::fs::read(&candidate)? == source_bytes {
+            return Ok(candidate);
+        }
+        candidate = assets_dir.join(format!("{}-{}{}", stem, n, ext));
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+fn note_path(kb_root: &Path, note: &str) -> Result<PathBuf, String> {
+    let path = Path::new(note);
+    // An absolute path would replace `kb_root` in the join below
+    let escapes = |c: Component| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_));
+    if path.is_absolute() || path.components().any(escapes) {
+        return Err("Note path must be relative to the knowledge base".to_string());
+    }
+    Ok(kb_root.join(path))
+}
+
+/// Copy `file_path` into the note's `assets/` folder and link it from the note.
+///
+/// Links in the note that already point at the original file (absolute paths, `file://`
+/// URLs) are rewritten to the copied asset; if there were none and `insert` isn't false,
+/// an image embed or link is appended to the note.
+#[tauri::command]
+pub async fn add_attachment(
+    app_handle: tauri::AppHandle,
+    note: String,
+    file_path: String,
+    insert: Option<bool>,
+) -> Result<AddAttachmentResult, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let note_file = note_path(&kb_root, &note)?;
+    let note_dir = note_file.parent().ok_or("Invalid note path")?.to_path_buf();
+    let source = PathBuf::from(&file_path);
+    if !source.is_file() {
+        return Err(format!("Attachment not found: {}", file_path));
+    }
+
+    let assets_dir = note_dir.join(ASSETS_DIR);
+    std::fs::create_dir_all(&assets_dir).map_err(|e| e.to_string())?;
+    let dest = destination_for(&assets_dir, &source).map_err(|e| e.to_string())?;
+    if !dest.exists() {
+        std::fs::copy(&source, &dest).map_err(|e| format!("Failed to copy attachment: {}", e))?;
+    }
+
+    let file_name = dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let link = format!("{}/{}", ASSETS_DIR, file_name.replace(' ', "%20"));
+    let markdown = if is_image(&dest) {
+        format!("![{}]({})", file_name, link)
+    } else {
+        format!("[{}]({})", file_name, link)
+    };
+
+    let content = std::fs::read_to_string(&note_file).unwrap_or_default();
+    let (mut updated, links_rewritten) = rewrite_links_to(&content, &note_dir, &source, &link);
+    if links_rewritten == 0 && insert.unwrap_or(true) {
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(&format!("\n{}\n", markdown));
+    }
+
+    if updated != content {
+        crate::note_versions::record_before_write(Some(&app_handle), &kb_root, &note_file, "attachment");
+        std::fs::write(&note_file, &updated).map_err(|e| e.to_string())?;
+    }
+
+    let size = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    tracing::info!("📎 Attached {} to {}", file_name, note);
+
+    Ok(AddAttachmentResult {
+        attachment: Attachment { link, absolute_path: dest.to_string_lossy().to_string(), size },
+        markdown,
+        links_rewritten,
+    })
+}
+
+/// Attachments a note links to that exist on disk (absolute paths can be served with convertFileSrc)
+#[tauri::command]
+pub async fn list_attachments(note: String) -> Result<Vec<Attachment>, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let note_file = note_path(&kb_root, &note)?;
+    let note_dir = note_file.parent().ok_or("Invalid note path")?;
+    let content = std::fs::read_to_string(&note_file).map_err(|e| e.to_string())?;
+
+    let mut seen = HashSet::new();
+    Ok(referenced_files(&content, note_dir)
+        .into_iter()
+        .filter(|p| p.is_file() && p.extension().map(|e| e != "md").unwrap_or(true) && seen.insert(p.clone()))
+        .map(|p| Attachment {
+            link: p.strip_prefix(note_dir).unwrap_or(&p).to_string_lossy().replace('\\', "/"),
+            size: std::fs::metadata(&p).map(|m| m.len()).unwrap_or(0),
+            absolute_path: p.to_string_lossy().to_string(),
+        })
+        .collect())
+}
+
+/// Find (and unless `dry_run`, delete) files in `assets/` folders that no note references
+#[tauri::command]
+pub async fn cleanup_orphaned_attachments(dry_run: Option<bool>) -> Result<Vec<String>, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let roots: Vec<PathBuf> = crate::kb_index::KB_FOLDERS.iter().map(|f| kb_root.join(f)).filter(|r| r.exists()).collect();
+
+    let mut referenced = HashSet::new();
+    let mut assets = Vec::new();
+    for root in &roots {
+        for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            if path.extension().map(|e| e == "md").unwrap_or(false) {
+                if let (Ok(content), Some(dir)) = (std::fs::read_to_string(path), path.parent()) {
+                    referenced.extend(referenced_files(&content, dir));
+                }
+            } else if path.parent().and_then(|p| p.file_name()).map(|n| n == ASSETS_DIR).unwrap_or(false) {
+                assets.push(path.to_path_buf());
+            }
+        }
+    }
+
+    let orphans: Vec<PathBuf> = assets.into_iter().filter(|a| !referenced.contains(&normalize(a))).collect();
+    if !dry_run.unwrap_or(false) {
+        for orphan in &orphans {
+            if let Err(e) = std::fs::remove_file(orphan) {
+                tracing::warn!("⚠️ Could not remove {}: {}", orphan.display(), e);
+            }
+        }
+        tracing::info!("🧹 Removed {} orphaned attachment(s)", orphans.len());
+    }
+
+    Ok(orphans.iter().map(|p| crate::kb_index::path_key(&kb_root, p)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_links_to_a_copied_file() {
+        let content = "![chart](file:///Users/me/Desktop/chart%201.png)\n[paper](../papers/x.pdf)\n![[diagram.svg]]\n[web](https://example.com/a.png)";
+        let (rewritten, count) = rewrite_links_to(content, Path::new("/kb/research"), Path::new("/Users/me/Desktop/chart 1.png"), "assets/chart%201.png");
+        assert_eq!(count, 1);
+        assert!(rewritten.starts_with("![chart](assets/chart%201.png)"));
+    }
+
+    #[test]
+    fn test_referenced_files_resolve_against_the_note() {
+        let content = "![chart](assets/chart%201.png)\n[paper](../papers/x.pdf)\n![[diagram.svg]]\n[web](https://example.com/a.png)";
+        let refs = referenced_files(content, Path::new("/kb/research"));
+        assert!(refs.contains(&PathBuf::from("/kb/research/assets/chart 1.png")));
+        assert!(refs.contains(&PathBuf::from("/kb/papers/x.pdf")));
+        assert!(refs.contains(&PathBuf::from("/kb/research/assets/diagram.svg")));
+        assert!(!refs.iter().any(|p| p.to_string_lossy().contains("example.com")));
+    }
+
+    #[test]
+    fn test_note_path_stays_inside_the_kb() {
+        let kb = Path::new("/kb");
+        assert_eq!(note_path(kb, "research/rust.md").unwrap(), PathBuf::from("/kb/research/rust.md"));
+        assert!(note_path(kb, "../outside.md").is_err());
+        assert!(note_path(kb, "/etc/foo.md").is_err());
+        assert!(note_path(kb, "research/../../outside.md").is_err());
+    }
+}
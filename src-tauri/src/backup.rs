@@ -0,0 +1,480 @@
+/// Scheduled, encrypted backups of the data export to a cloud target
+///
+/// A backup is the archive `export_all_data` writes (see user_data.rs), encrypted with a key
+/// derived from the `[backup]` passphrase and uploaded to the configured target (see
+/// backup_targets.rs) as `thinkspace-<profile>-<time>.tsbk`. The background loop backs up
+/// every `interval_hours`, waiting an hour after a failure, and each upload is followed by
+/// deleting all but the newest `keep` backups of the profile. `restore_backup` downloads one,
+/// decrypts it and hands it to `user_data::restore_archive`; without `confirm` it only proves
+/// the passphrase and reports what the backup would change.
+///
+/// An encrypted file is `TSBK`, a format version, the Argon2id salt and the STREAM nonce,
+/// then XChaCha20-Poly1305 chunks of 64 KiB. Every chunk is authenticated together with the
+/// header, so a wrong passphrase, a damaged file or a truncated upload fails to decrypt
+/// rather than restoring garbage. The passphrase never leaves the machine: lose it and the
+/// backups can't be read. Session bundles (session_bundle.rs) are encrypted the same way.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{OsRng, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::backup_targets::{BackupTarget, RemoteBackup};
+use crate::notifications::{notify, Category};
+use crate::user_data::{ExportManifest, RestoreReport};
+
+pub const EXTENSION: &str = "tsbk";
+const MAGIC: &[u8; 4] = b"TSBK";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+/// XChaCha20's 24-byte nonce less the 5 bytes STREAM keeps for the chunk counter
+const NONCE_LEN: usize = 19;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+const CHUNK_SIZE: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+const NAME_STAMP: &str = "%Y%m%d-%H%M%S";
+const STATE_FILE: &str = "backup_state.json";
+/// How often the background loop looks at the schedule
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// How long a failed scheduled backup waits before the next try, in hours
+const RETRY_HOURS: i64 = 1;
+
+lazy_static::lazy_static! {
+    /// One backup or restore at a time, whether scheduled or asked for
+    static ref BACKUP_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// The last backup of this profile, kept in its data dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupState {
+    /// When the last backup was uploaded, and its name
+    pub last_backup: Option<String>,
+    pub last_name: Option<String>,
+    pub last_attempt: Option<String>,
+    /// Why the last attempt failed; cleared by the next success
+    pub last_error: Option<String>,
+}
+
+impl BackupState {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path).ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupStatus {
+    pub target: Option<crate::backup_targets::TargetKind>,
+    pub interval_hours: u64,
+    pub keep: usize,
+    pub has_passphrase: bool,
+    #[serde(flatten)]
+    pub state: BackupState,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupResult {
+    pub name: String,
+    pub target: String,
+    /// Size of the encrypted upload
+    pub bytes: u64,
+    pub manifest: ExportManifest,
+    /// Older backups deleted to stay within `keep`
+    pub pruned: Vec<String>,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive the encryption key: {}", e))?;
+    Ok(key)
+}
+
+/// Read until `buf` is full or the input ends; returns how much was read
+fn fill(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, String> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(filled)
+}
+
+/// Encrypt everything `reader` holds into `writer`. A chunk shorter than CHUNK_SIZE (possibly
+/// empty) is always the last, so the decryptor knows where the stream ends.
+pub fn encrypt(reader: &mut impl Read, writer: &mut impl Write, passphrase: &str) -> Result<(), String> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    let mut salt_and_nonce = [0u8; SALT_LEN + NONCE_LEN];
+    OsRng.fill_bytes(&mut salt_and_nonce);
+    header.extend_from_slice(&salt_and_nonce);
+    writer.write_all(&header).map_err(|e| e.to_string())?;
+
+    let key = derive_key(passphrase, &salt_and_nonce[..SALT_LEN])?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let mut encryptor = EncryptorBE32::from_aead(cipher, salt_and_nonce[SALT_LEN..].into());
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = fill(reader, &mut chunk)?;
+        let payload = Payload { msg: &chunk[..read], aad: &header };
+        if read < CHUNK_SIZE {
+            let sealed = encryptor.encrypt_last(payload).map_err(|_| "Failed to encrypt".to_string())?;
+            writer.write_all(&sealed).map_err(|e| e.to_string())?;
+            return writer.flush().map_err(|e| e.to_string());
+        }
+        let sealed = encryptor.encrypt_next(payload).map_err(|_| "Failed to encrypt".to_string())?;
+        writer.write_all(&sealed).map_err(|e| e.to_string())?;
+    }
+}
+
+/// Decrypt what `encrypt` wrote. Output written before a failure is incomplete and shouldn't be used.
+pub fn decrypt(reader: &mut impl Read, writer: &mut impl Write, passphrase: &str) -> Result<(), String> {
+    let mut header = [0u8; HEADER_LEN];
+    if fill(reader, &mut header)? < HEADER_LEN || &header[..MAGIC.len()] != MAGIC {
+        return Err("Not a file ThinkSpace encrypted".to_string());
+    }
+    if header[MAGIC.len()] != FORMAT_VERSION {
+        return Err(format!("This file uses format {}, which this version of ThinkSpace can't read", header[MAGIC.len()]));
+    }
+    let salt = &header[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN];
+    let key = derive_key(passphrase, salt)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let mut decryptor = DecryptorBE32::from_aead(cipher, header[HEADER_LEN - NONCE_LEN..].into());
+
+    let failed = || "Wrong passphrase, or the file is damaged or incomplete".to_string();
+    let mut chunk = vec![0u8; CHUNK_SIZE + TAG_LEN];
+    loop {
+        let read = fill(reader, &mut chunk)?;
+        let payload = Payload { msg: &chunk[..read], aad: &header };
+        if read < chunk.len() {
+            let plain = decryptor.decrypt_last(payload).map_err(|_| failed())?;
+            writer.write_all(&plain).map_err(|e| e.to_string())?;
+            return writer.flush().map_err(|e| e.to_string());
+        }
+        let plain = decryptor.decrypt_next(payload).map_err(|_| failed())?;
+        writer.write_all(&plain).map_err(|e| e.to_string())?;
+    }
+}
+
+fn encrypt_file(src: &Path, dest: &Path, passphrase: &str) -> Result<(), String> {
+    let mut reader = BufReader::new(std::fs::File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?);
+    let mut writer = BufWriter::new(std::fs::File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?);
+    encrypt(&mut reader, &mut writer, passphrase)
+}
+
+fn decrypt_file(src: &Path, dest: &Path, passphrase: &str) -> Result<(), String> {
+    let mut reader = BufReader::new(std::fs::File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?);
+    let mut writer = BufWriter::new(std::fs::File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?);
+    decrypt(&mut reader, &mut writer, passphrase)
+}
+
+fn backup_name(profile: &str, time: DateTime<Local>) -> String {
+    format!("thinkspace-{}-{}.{}", profile, time.format(NAME_STAMP), EXTENSION)
+}
+
+/// Whether `name` is a backup of `profile` (and not of a profile whose id merely starts the same)
+fn is_backup_of(name: &str, profile: &str) -> bool {
+    name.strip_prefix(&format!("thinkspace-{}-", profile))
+        .and_then(|rest| rest.strip_suffix(&format!(".{}", EXTENSION)))
+        .map_or(false, |stamp| chrono::NaiveDateTime::parse_from_str(stamp, NAME_STAMP).is_ok())
+}
+
+/// Whether a scheduled backup should run at `now`
+fn due(state: &BackupState, interval_hours: u64, now: DateTime<Utc>) -> bool {
+    let parse = |stamp: &Option<String>| stamp.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.with_timezone(&Utc));
+    let interval = chrono::Duration::hours(interval_hours as i64);
+    let elapsed = parse(&state.last_backup).map_or(true, |last| now - last >= interval);
+    let retry_wait = state.last_error.is_some() && parse(&state.last_attempt).map_or(false, |last| now - last < chrono::Duration::hours(RETRY_HOURS));
+    elapsed && !retry_wait
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().filter(|v| !v.trim().is_empty()).map(str::to_string)
+}
+
+fn configured_target(config: &crate::config::BackupConfig) -> Result<Box<dyn BackupTarget>, String> {
+    let kind = config.target.ok_or("No backup target configured; set [backup] target in thinkspace.toml")?;
+    crate::backup_targets::target(kind, config)
+}
+
+/// A scratch folder for one backup or restore, removed again by `finish`
+fn scratch_dir() -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("thinkspace-backup-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+fn finish<T>(scratch: &Path, result: Result<T, String>) -> Result<T, String> {
+    let _ = std::fs::remove_dir_all(scratch);
+    result
+}
+
+/// Delete all but the newest `keep` backups of `profile`; returns the names deleted
+async fn prune(target: &dyn BackupTarget, profile: &str, keep: usize) -> Vec<String> {
+    let mut backups: Vec<RemoteBackup> = match target.list().await {
+        Ok(backups) => backups.into_iter().filter(|b| is_backup_of(&b.name, profile)).collect(),
+        Err(e) => {
+            tracing::warn!("⚠️ Couldn't list old backups to prune: {}", e);
+            return Vec::new();
+        }
+    };
+    // Names end in their time, so newest first is reverse name order
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    let mut pruned = Vec::new();
+    for backup in backups.iter().skip(keep.max(1)) {
+        match target.delete(backup).await {
+            Ok(()) => pruned.push(backup.name.clone()),
+            Err(e) => tracing::warn!("⚠️ Couldn't delete old backup {}: {}", backup.name, e),
+        }
+    }
+    pruned
+}
+
+async fn upload_backup(app_handle: &AppHandle, config: &crate::config::BackupConfig) -> Result<BackupResult, String> {
+    let passphrase = non_empty(&config.passphrase).ok_or("Set a passphrase in [backup] to encrypt backups with")?;
+    let target = configured_target(config)?;
+    let profile = crate::profiles::active_id();
+    let name = backup_name(&profile, Local::now());
+
+    let scratch = scratch_dir()?;
+    let result = async {
+        let export = crate::user_data::export_to(app_handle, scratch.join("export.zip"), config.user_id.as_deref()).await?;
+        let encrypted = scratch.join(&name);
+        let (plain, sealed) = (PathBuf::from(&export.archive), encrypted.clone());
+        tauri::async_runtime::spawn_blocking(move || encrypt_file(&plain, &sealed, &passphrase))
+            .await
+            .map_err(|e| e.to_string())??;
+        let bytes = std::fs::metadata(&encrypted).map(|m| m.len()).unwrap_or(0);
+
+        target.upload(&name, &encrypted).await?;
+        let pruned = prune(target.as_ref(), &profile, config.keep).await;
+        Ok(BackupResult { name: name.clone(), target: target.name().to_string(), bytes, manifest: export.manifest, pruned })
+    }
+    .await;
+    finish(&scratch, result)
+}
+
+/// Export, encrypt and upload a backup now, recording the outcome for the schedule
+pub async fn back_up(app_handle: &AppHandle) -> Result<BackupResult, String> {
+    let _running = BACKUP_LOCK.lock().await;
+    let config = crate::config::current().backup.clone();
+    let result = upload_backup(app_handle, &config).await;
+
+    let state_path = crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?.join(STATE_FILE);
+    let mut state = BackupState::load(&state_path);
+    let now = crate::scheduler::stamp(Utc::now());
+    state.last_attempt = Some(now.clone());
+    match &result {
+        Ok(backup) => {
+            tracing::info!("☁️ Backed up {} to {} ({} bytes, {} pruned)", backup.name, backup.target, backup.bytes, backup.pruned.len());
+            state.last_backup = Some(now);
+            state.last_name = Some(backup.name.clone());
+            state.last_error = None;
+        }
+        Err(e) => state.last_error = Some(e.clone()),
+    }
+    if let Err(e) = state.save(&state_path) {
+        tracing::warn!("⚠️ {}", e);
+    }
+    let _ = app_handle.emit_all("backup-finished", serde_json::json!({
+        "success": result.is_ok(),
+        "name": result.as_ref().ok().map(|b| b.name.clone()),
+        "error": result.as_ref().err(),
+    }));
+    match &result {
+        Ok(backup) => notify(app_handle, Category::Backup, "☁️ Backed up", &format!("{} to {}", backup.name, backup.target)),
+        Err(e) => notify(app_handle, Category::Errors, "⚠️ Backup failed", e),
+    };
+    result
+}
+
+/// Back up on the `[backup]` schedule for as long as the app runs
+pub fn start_background(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = crate::config::current().backup.clone();
+            if config.target.is_some() && config.interval_hours > 0 {
+                let state = crate::profiles::data_dir(Some(&app_handle)).map(|dir| BackupState::load(&dir.join(STATE_FILE))).unwrap_or_default();
+                if due(&state, config.interval_hours, Utc::now()) {
+                    if let Err(e) = back_up(&app_handle).await {
+                        tracing::warn!("⚠️ Scheduled backup failed: {}", e);
+                    }
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// The backup settings (without secrets) and how the last backup went
+#[tauri::command]
+pub fn get_backup_status(app_handle: AppHandle) -> Result<BackupStatus, String> {
+    let config = crate::config::current().backup.clone();
+    let state_path = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?.join(STATE_FILE);
+    Ok(BackupStatus {
+        target: config.target,
+        interval_hours: config.interval_hours,
+        keep: config.keep,
+        has_passphrase: non_empty(&config.passphrase).is_some(),
+        state: BackupState::load(&state_path),
+    })
+}
+
+#[tauri::command]
+pub async fn backup_now(app_handle: AppHandle) -> Result<BackupResult, String> {
+    back_up(&app_handle).await
+}
+
+/// Backups at the configured target, of every profile, newest first
+#[tauri::command]
+pub async fn list_backups() -> Result<Vec<RemoteBackup>, String> {
+    let config = crate::config::current().backup.clone();
+    let mut backups: Vec<RemoteBackup> = configured_target(&config)?
+        .list()
+        .await?
+        .into_iter()
+        .filter(|b| b.name.ends_with(&format!(".{}", EXTENSION)))
+        .collect();
+    backups.sort_by(|a, b| b.modified.cmp(&a.modified));
+    Ok(backups)
+}
+
+/// Download, decrypt and restore the backup `id` (from `list_backups`). `passphrase` defaults to
+/// the configured one. Without `confirm` nothing changes: the report says what would.
+#[tauri::command]
+pub async fn restore_backup(
+    app_handle: AppHandle,
+    id: String,
+    passphrase: Option<String>,
+    confirm: Option<bool>,
+) -> Result<RestoreReport, String> {
+    let _running = BACKUP_LOCK.lock().await;
+    let config = crate::config::current().backup.clone();
+    let passphrase = non_empty(&passphrase)
+        .or_else(|| non_empty(&config.passphrase))
+        .ok_or("Enter the passphrase the backup was made with")?;
+    let target = configured_target(&config)?;
+    let backup = target
+        .list()
+        .await?
+        .into_iter()
+        .find(|b| b.id == id || b.name == id)
+        .ok_or_else(|| format!("No backup '{}' at {}", id, target.name()))?;
+
+    let scratch = scratch_dir()?;
+    let result = async {
+        let encrypted = scratch.join(&backup.name);
+        target.download(&backup, &encrypted).await?;
+        let archive = scratch.join("export.zip");
+        let plain = archive.clone();
+        tauri::async_runtime::spawn_blocking(move || decrypt_file(&encrypted, &plain, &passphrase))
+            .await
+            .map_err(|e| e.to_string())??;
+        crate::user_data::restore_archive(&app_handle, archive, !confirm.unwrap_or(false)).await
+    }
+    .await;
+    finish(&scratch, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// `data` sealed and checked to open back to itself
+    fn roundtrip(data: &[u8]) -> Vec<u8> {
+        let mut sealed = Vec::new();
+        encrypt(&mut &data[..], &mut sealed, "correct horse").unwrap();
+        let mut plain = Vec::new();
+        decrypt(&mut &sealed[..], &mut plain, "correct horse").unwrap();
+        assert_eq!(plain, data);
+        sealed
+    }
+
+    fn fails(sealed: &[u8], passphrase: &str) -> bool {
+        decrypt(&mut &sealed[..], &mut Vec::new(), passphrase).is_err()
+    }
+
+    fn data() -> Vec<u8> {
+        (0..CHUNK_SIZE * 2 + 100).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn stamp(now: DateTime<Utc>, hours_ago: i64) -> Option<String> {
+        Some(crate::scheduler::stamp(now - chrono::Duration::hours(hours_ago)))
+    }
+
+    #[test]
+    fn test_encryption_roundtrip_and_size() {
+        roundtrip(b"");
+        let data = data();
+        assert_eq!(roundtrip(&data).len(), HEADER_LEN + data.len() + 3 * TAG_LEN);
+        // A whole number of chunks ends with an empty one
+        assert_eq!(roundtrip(&data[..CHUNK_SIZE]).len(), HEADER_LEN + CHUNK_SIZE + 2 * TAG_LEN);
+    }
+
+    #[test]
+    fn test_wrong_passphrase_fails() {
+        assert!(fails(&roundtrip(&data()), "wrong horse"));
+    }
+
+    #[test]
+    fn test_cut_or_tampered_backups_fail() {
+        let sealed = roundtrip(&data());
+        // Cut at a chunk boundary, flipped in the header or in a chunk
+        assert!(fails(&sealed[..HEADER_LEN + CHUNK_SIZE + TAG_LEN], "correct horse"));
+        let mut tampered = sealed.clone();
+        tampered[MAGIC.len() + 1] ^= 1;
+        assert!(fails(&tampered, "correct horse"));
+        let mut tampered = sealed.clone();
+        tampered[HEADER_LEN + 10] ^= 1;
+        assert!(fails(&tampered, "correct horse"));
+    }
+
+    #[test]
+    fn test_plain_zip_is_not_a_backup() {
+        assert!(fails(b"PK\x03\x04 a plain zip", "correct horse"));
+    }
+
+    #[test]
+    fn test_backup_names() {
+        let time = Local.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap();
+        let name = backup_name("work", time);
+        assert_eq!(name, "thinkspace-work-20260314-093000.tsbk");
+        assert!(is_backup_of(&name, "work"));
+        assert!(!is_backup_of(&backup_name("work-2", time), "work"));
+        assert!(!is_backup_of("thinkspace-work-notes.tsbk", "work"));
+    }
+
+    #[test]
+    fn test_due_after_the_interval() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 12, 0, 0).unwrap();
+        assert!(due(&BackupState::default(), 24, now));
+        assert!(!due(&BackupState { last_backup: stamp(now, 3), ..Default::default() }, 24, now));
+        assert!(due(&BackupState { last_backup: stamp(now, 25), ..Default::default() }, 24, now));
+    }
+
+    #[test]
+    fn test_failure_holds_off_the_next_try() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 12, 0, 0).unwrap();
+        // A failure holds off the next try for a while, not until the next interval
+        let failed = BackupState { last_backup: stamp(now, 25), last_attempt: Some(crate::scheduler::stamp(now)), last_error: Some("offline".to_string()), ..Default::default() };
+        assert!(!due(&failed, 24, now));
+        assert!(due(&failed, 24, now + chrono::Duration::hours(2)));
+    }
+}
@@ -0,0 +1,334 @@
+/// Structured canvas blocks: charts, tables, task lists and kanban boards, mermaid diagrams
+///
+/// `canvas_update` with `add_block` accepts these as a `data` payload. They're checked here,
+/// so the agent gets a precise error to correct instead of a block that silently fails to
+/// render, and turned into the markdown the canvas already holds: GFM tables and task lists,
+/// a `mermaid` fence, and for charts a `chart` fence with normalized JSON that the canvas
+/// draws. Because the result is plain markdown, saved sessions and canvas exports keep it.
+
+use serde::Serialize;
+use serde_json::Value;
+
+pub const BLOCK_TYPES: [&str; 5] = ["chart", "table", "tasks", "kanban", "mermaid"];
+
+const CHART_TYPES: [&str; 4] = ["line", "bar", "area", "pie"];
+const MAX_ROWS: usize = 500;
+const MAX_COLUMNS: usize = 20;
+const MAX_SERIES: usize = 12;
+const MAX_POINTS: usize = 1000;
+const MAX_TASKS: usize = 200;
+/// First words a mermaid diagram can start with
+const MERMAID_DIAGRAMS: [&str; 20] = [
+    "graph", "flowchart", "sequenceDiagram", "classDiagram", "stateDiagram", "stateDiagram-v2", "erDiagram",
+    "journey", "gantt", "pie", "quadrantChart", "requirementDiagram", "gitGraph", "mindmap", "timeline",
+    "sankey-beta", "xychart-beta", "block-beta", "C4Context", "kanban",
+];
+
+#[derive(Debug, Serialize, PartialEq)]
+struct ChartSeries {
+    name: String,
+    values: Vec<f64>,
+}
+
+/// What the canvas's `chart` fence holds
+#[derive(Debug, Serialize, PartialEq)]
+struct Chart {
+    #[serde(rename = "type")]
+    chart_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    labels: Vec<String>,
+    series: Vec<ChartSeries>,
+}
+
+pub fn is_block_type(block_type: &str) -> bool {
+    BLOCK_TYPES.contains(&block_type)
+}
+
+/// `data` as markdown for a `block_type` block, or what's wrong with it
+pub fn block_markdown(block_type: &str, data: &Value) -> Result<String, String> {
+    match block_type {
+        "chart" => chart_block(data),
+        "table" => table_block(data),
+        "tasks" => tasks_block(data),
+        "kanban" => kanban_block(data),
+        "mermaid" => mermaid_block(data),
+        other => Err(format!("Unknown block type '{}': use one of {}", other, BLOCK_TYPES.join(", "))),
+    }
+}
+
+fn field<'a>(data: &'a Value, name: &str) -> Result<&'a Value, String> {
+    data.get(name).ok_or_else(|| format!("'data.{}' is required", name))
+}
+
+fn array<'a>(data: &'a Value, name: &str, max: usize) -> Result<&'a Vec<Value>, String> {
+    let items = field(data, name)?.as_array().ok_or_else(|| format!("'data.{}' must be an array", name))?;
+    if items.is_empty() {
+        return Err(format!("'data.{}' is empty", name));
+    }
+    if items.len() > max {
+        return Err(format!("'data.{}' has {} entries; at most {} fit on the canvas", name, items.len(), max));
+    }
+    Ok(items)
+}
+
+fn optional_text(data: &Value, name: &str) -> Option<String> {
+    data.get(name).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// A table cell or label: strings as they are, numbers and booleans as written
+fn cell_text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Null => Ok(String::new()),
+        other => Err(format!("cells must be text or numbers, not {}", other)),
+    }
+}
+
+/// Keep a cell on one line and its pipes from ending it early
+fn escape_cell(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ").trim().to_string()
+}
+
+fn number(value: &Value) -> Option<f64> {
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    };
+    number.filter(|n| n.is_finite())
+}
+
+/// `{type, title?, labels, series: [{name, values}]}`; `series` may also hold `points` of
+/// `{x, y}` in place of `values`, whose x's then become the labels
+fn chart_block(data: &Value) -> Result<String, String> {
+    let chart_type = optional_text(data, "type").or_else(|| optional_text(data, "chart_type")).unwrap_or_else(|| "line".to_string());
+    if !CHART_TYPES.contains(&chart_type.as_str()) {
+        return Err(format!("Unknown chart type '{}': use one of {}", chart_type, CHART_TYPES.join(", ")));
+    }
+    let mut labels: Vec<String> = match data.get("labels") {
+        Some(labels) => labels.as_array().ok_or("'data.labels' must be an array")?.iter().map(cell_text).collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+
+    let mut series = Vec::new();
+    for (i, entry) in array(data, "series", MAX_SERIES)?.iter().enumerate() {
+        let name = optional_text(entry, "name").unwrap_or_else(|| format!("Series {}", i + 1));
+        let values: Vec<f64> = if let Some(values) = entry.get("values").and_then(|v| v.as_array()) {
+            values
+                .iter()
+                .map(|v| number(v).ok_or_else(|| format!("Series '{}' has a value that isn't a number: {}", name, v)))
+                .collect::<Result<_, _>>()?
+        } else if let Some(points) = entry.get("points").and_then(|v| v.as_array()) {
+            let xs: Vec<String> = points.iter().map(|p| p.get("x").map(cell_text).unwrap_or(Ok(String::new()))).collect::<Result<_, _>>()?;
+            if labels.is_empty() {
+                labels = xs;
+            } else if xs != labels {
+                return Err(format!("Series '{}' has points at different x's than the others", name));
+            }
+            points
+                .iter()
+                .map(|p| p.get("y").and_then(number).ok_or_else(|| format!("Series '{}' has a point without a numeric 'y': {}", name, p)))
+                .collect::<Result<_, _>>()?
+        } else {
+            return Err(format!("Series '{}' needs 'values' (numbers) or 'points' ({{x, y}})", name));
+        };
+        if values.is_empty() || values.len() > MAX_POINTS {
+            return Err(format!("Series '{}' needs between 1 and {} values", name, MAX_POINTS));
+        }
+        series.push(ChartSeries { name, values });
+    }
+
+    if labels.is_empty() {
+        let longest = series.iter().map(|s| s.values.len()).max().unwrap_or(0);
+        labels = (1..=longest).map(|i| i.to_string()).collect();
+    }
+    if let Some(mismatched) = series.iter().find(|s| s.values.len() != labels.len()) {
+        return Err(format!("Series '{}' has {} values for {} labels", mismatched.name, mismatched.values.len(), labels.len()));
+    }
+    if chart_type == "pie" {
+        if series.len() != 1 {
+            return Err("A pie chart takes exactly one series".to_string());
+        }
+        if series[0].values.iter().any(|v| *v < 0.0) {
+            return Err("A pie chart can't have negative values".to_string());
+        }
+    }
+
+    let chart = Chart { chart_type, title: optional_text(data, "title"), labels, series };
+    let json = serde_json::to_string_pretty(&chart).map_err(|e| e.to_string())?;
+    Ok(format!("```chart\n{}\n```", json))
+}
+
+/// `{columns, rows}` with every row as long as `columns`, or `{rows}` of objects keyed by column
+fn table_block(data: &Value) -> Result<String, String> {
+    let rows = array(data, "rows", MAX_ROWS)?;
+    let columns: Vec<String> = match data.get("columns") {
+        Some(columns) => columns
+            .as_array()
+            .ok_or("'data.columns' must be an array")?
+            .iter()
+            .map(cell_text)
+            .collect::<Result<_, _>>()?,
+        // Rows given as objects name their own columns
+        None => {
+            let first = rows[0].as_object().ok_or("'data.columns' is required unless the rows are objects")?;
+            first.keys().cloned().collect()
+        }
+    };
+    if columns.is_empty() || columns.len() > MAX_COLUMNS {
+        return Err(format!("A table needs between 1 and {} columns", MAX_COLUMNS));
+    }
+
+    let mut lines = vec![
+        format!("| {} |", columns.iter().map(|c| escape_cell(c)).collect::<Vec<_>>().join(" | ")),
+        format!("|{}", " --- |".repeat(columns.len())),
+    ];
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = match row {
+            Value::Array(cells) if cells.len() == columns.len() => cells.iter().map(cell_text).collect::<Result<_, _>>()?,
+            Value::Array(cells) => return Err(format!("Row {} has {} cells for {} columns", i + 1, cells.len(), columns.len())),
+            Value::Object(fields) => columns.iter().map(|c| fields.get(c).map(cell_text).unwrap_or(Ok(String::new()))).collect::<Result<_, _>>()?,
+            other => return Err(format!("Row {} must be an array of cells or an object, not {}", i + 1, other)),
+        };
+        lines.push(format!("| {} |", cells.iter().map(|c| escape_cell(c)).collect::<Vec<_>>().join(" | ")));
+    }
+    let title = optional_text(data, "title").map(|t| format!("**{}**\n\n", t)).unwrap_or_default();
+    Ok(format!("{}{}", title, lines.join("\n")))
+}
+
+/// Task list lines for `items`: strings, or `{text, done?}`
+fn task_lines(items: &[Value], context: &str) -> Result<Vec<String>, String> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let (text, done) = match item {
+                Value::String(text) => (text.trim().to_string(), false),
+                Value::Object(_) => (
+                    optional_text(item, "text").or_else(|| optional_text(item, "title")).unwrap_or_default(),
+                    item.get("done").and_then(|d| d.as_bool()).unwrap_or(false),
+                ),
+                _ => (String::new(), false),
+            };
+            if text.is_empty() {
+                return Err(format!("{} item {} needs text", context, i + 1));
+            }
+            Ok(format!("- [{}] {}", if done { "x" } else { " " }, text.replace(['\r', '\n'], " ")))
+        })
+        .collect()
+}
+
+/// `{title?, items}`
+fn tasks_block(data: &Value) -> Result<String, String> {
+    let lines = task_lines(array(data, "items", MAX_TASKS)?, "Task")?;
+    let title = optional_text(data, "title").map(|t| format!("**{}**\n\n", t)).unwrap_or_default();
+    Ok(format!("{}{}", title, lines.join("\n")))
+}
+
+/// `{title?, columns: [{title, items}]}`, one task list per column
+fn kanban_block(data: &Value) -> Result<String, String> {
+    let columns = array(data, "columns", MAX_COLUMNS)?;
+    let total: usize = columns.iter().filter_map(|c| c.get("items")?.as_array().map(Vec::len)).sum();
+    if total > MAX_TASKS {
+        return Err(format!("The board has {} cards; at most {} fit on the canvas", total, MAX_TASKS));
+    }
+    let mut parts = Vec::new();
+    if let Some(title) = optional_text(data, "title") {
+        parts.push(format!("**{}**", title));
+    }
+    for (i, column) in columns.iter().enumerate() {
+        let name = optional_text(column, "title").ok_or_else(|| format!("Column {} needs a title", i + 1))?;
+        let items = column.get("items").and_then(|v| v.as_array()).map(Vec::as_slice).unwrap_or(&[]);
+        let cards = task_lines(items, &format!("Column '{}'", name))?;
+        let cards = if cards.is_empty() { "_Empty_".to_string() } else { cards.join("\n") };
+        parts.push(format!("#### {} ({})\n\n{}", name, items.len(), cards));
+    }
+    Ok(parts.join("\n\n"))
+}
+
+/// `{code}`, or the diagram source as a string
+fn mermaid_block(data: &Value) -> Result<String, String> {
+    let code = match data {
+        Value::String(code) => code.as_str(),
+        _ => field(data, "code")?.as_str().ok_or("'data.code' must be a string")?,
+    };
+    let code = code.trim().trim_start_matches("```mermaid").trim_end_matches("```").trim();
+    let first = code
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("%%"))
+        .and_then(|line| line.split_whitespace().next())
+        .ok_or("The mermaid diagram is empty")?;
+    if !MERMAID_DIAGRAMS.contains(&first) {
+        return Err(format!("'{}' doesn't start a mermaid diagram: begin with a diagram type like flowchart, sequenceDiagram or gantt", first));
+    }
+    if code.contains("```") {
+        return Err("The mermaid diagram can't contain ``` fences".to_string());
+    }
+    Ok(format!("```mermaid\n{}\n```", code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chart_block_normalizes_series() {
+        let chart = block_markdown("chart", &json!({
+            "type": "bar",
+            "title": "Signups",
+            "series": [
+                { "name": "Organic", "points": [{ "x": "Jan", "y": 10 }, { "x": "Feb", "y": "12.5" }] },
+                { "name": "Paid", "values": [3, 4] }
+            ]
+        }))
+        .unwrap();
+        let json: Value = serde_json::from_str(chart.strip_prefix("```chart\n").unwrap().strip_suffix("\n```").unwrap()).unwrap();
+        assert_eq!(json, json!({
+            "type": "bar",
+            "title": "Signups",
+            "labels": ["Jan", "Feb"],
+            "series": [{ "name": "Organic", "values": [10.0, 12.5] }, { "name": "Paid", "values": [3.0, 4.0] }]
+        }));
+    }
+
+    #[test]
+    fn test_chart_block_rejects_mismatched_series() {
+        assert!(block_markdown("chart", &json!({ "labels": ["a", "b"], "series": [{ "values": [1] }] })).unwrap_err().contains("1 values for 2 labels"));
+        assert!(block_markdown("chart", &json!({ "type": "pie", "series": [{ "values": [1] }, { "values": [2] }] })).is_err());
+    }
+
+    #[test]
+    fn test_table_block() {
+        let table = block_markdown("table", &json!({ "columns": ["Plan", "Price"], "rows": [["Pro | Team", 12], ["Free", null]] })).unwrap();
+        assert_eq!(table, "| Plan | Price |\n| --- | --- |\n| Pro \\| Team | 12 |\n| Free |  |");
+        assert_eq!(block_markdown("table", &json!({ "rows": [{ "a": 1 }] })).unwrap(), "| a |\n| --- |\n| 1 |");
+    }
+
+    #[test]
+    fn test_table_block_rejects_short_rows() {
+        assert!(block_markdown("table", &json!({ "columns": ["a", "b"], "rows": [[1]] })).unwrap_err().contains("Row 1 has 1 cells"));
+    }
+
+    #[test]
+    fn test_tasks_and_kanban_blocks() {
+        assert_eq!(block_markdown("tasks", &json!({ "items": ["Draft", { "text": "Ship", "done": true }] })).unwrap(), "- [ ] Draft\n- [x] Ship");
+        let board = block_markdown("kanban", &json!({ "columns": [{ "title": "Doing", "items": ["Deck"] }, { "title": "Done" }] })).unwrap();
+        assert_eq!(board, "#### Doing (1)\n\n- [ ] Deck\n\n#### Done (0)\n\n_Empty_");
+    }
+
+    #[test]
+    fn test_mermaid_block() {
+        assert_eq!(block_markdown("mermaid", &json!({ "code": "flowchart LR\n  A --> B" })).unwrap(), "```mermaid\nflowchart LR\n  A --> B\n```");
+        assert!(block_markdown("mermaid", &json!({ "code": "A --> B" })).is_err());
+    }
+
+    #[test]
+    fn test_unknown_block_kind() {
+        assert!(block_markdown("gallery", &json!({})).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+/// Export a saved session's canvases as a standalone document
+///
+/// `export_canvas` renders the canvases persisted with a session (their markdown, with embeds,
+/// mermaid and local images handled as in `render_markdown`) and the media shown beside them
+/// into one self-contained HTML page: images are inlined, generated HTML such as the harvest
+/// view keeps running in a sandboxed frame, and links, videos and 3D scenes are listed. PDF
+/// and PNG are printed from that page by a Chromium-based browser (Chrome, Chromium, Edge or
+/// Brave) run headless, when one is installed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long the headless browser gets to print the page
+const PRINT_TIMEOUT: Duration = Duration::from_secs(60);
+/// PNG screenshots are this wide; the height is estimated from the content
+const PNG_WIDTH: u32 = 1280;
+const MAX_PNG_HEIGHT: u32 = 16_384;
+
+const STYLE_CSS: &str = "body{margin:0;font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;color:#1f2328;background:#fff}
+main{max-width:900px;margin:0 auto;padding:32px 48px;line-height:1.6}
+header{border-bottom:1px solid #d0d7de;margin-bottom:24px}header p{color:#656d76;font-size:14px;margin-top:0}
+section{margin-bottom:32px}section>h2.slot{font-size:13px;text-transform:uppercase;color:#656d76;letter-spacing:.04em}
+pre{background:#f6f8fa;padding:12px;overflow-x:auto;border-radius:6px;white-space:pre-wrap}code{font-size:90%}
+blockquote{border-left:4px solid #d0d7de;margin:0;padding:0 16px;color:#424a53}
+table{border-collapse:collapse}td,th{border:1px solid #d0d7de;padding:4px 10px}img{max-width:100%}
+figure.media{margin:16px 0;border:1px solid #d0d7de;border-radius:6px;padding:12px}
+figure.media iframe{width:100%;height:640px;border:0}figure.media figcaption{font-size:13px;color:#656d76;margin-bottom:8px}
+@media print{figure.media iframe{height:900px}section{break-inside:auto}}
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasFormat {
+    Html,
+    Pdf,
+    Png,
+}
+
+impl CanvasFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.trim().to_lowercase().as_str() {
+            "" | "html" | "htm" => Ok(CanvasFormat::Html),
+            "pdf" => Ok(CanvasFormat::Pdf),
+            "png" => Ok(CanvasFormat::Png),
+            other => Err(format!("Unknown canvas export format '{}': use \"html\", \"pdf\" or \"png\"", other)),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            CanvasFormat::Html => "html",
+            CanvasFormat::Pdf => "pdf",
+            CanvasFormat::Png => "png",
+        }
+    }
+}
+
+/// The parts of a saved session the canvas export needs; the chat is skipped, not parsed
+#[derive(Debug, Deserialize)]
+struct StoredCanvas {
+    name: String,
+    #[serde(default)]
+    timestamp: String,
+    main_canvas: Option<String>,
+    left_canvas: Option<String>,
+    /// Either one `{type_, content}` or `{main, left}` with a `{type, content}` each
+    visuals: Option<serde_json::Value>,
+}
+
+/// Media shown in a canvas's side pane
+#[derive(Debug, Clone, PartialEq)]
+struct Media {
+    slot: String,
+    kind: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CanvasExport {
+    pub path: String,
+    pub format: String,
+    /// Non-fatal problems (missing embeds, unreadable images, ...)
+    pub warnings: Vec<String>,
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn media_items(visuals: &serde_json::Value) -> Vec<Media> {
+    let media = |slot: &str, value: &serde_json::Value| -> Option<Media> {
+        let kind = value.get("type").or_else(|| value.get("type_")).and_then(|t| t.as_str()).unwrap_or("url");
+        let content = value.get("content").and_then(|c| c.as_str()).filter(|c| !c.trim().is_empty())?;
+        Some(Media { slot: slot.to_string(), kind: kind.to_string(), content: content.to_string() })
+    };
+    if visuals.get("content").is_some() {
+        return media("main", visuals).into_iter().collect();
+    }
+    ["main", "left"].iter().filter_map(|slot| media(slot, visuals.get(*slot)?)).collect()
+}
+
+fn media_html(media: &Media) -> String {
+    let body = match media.kind.as_str() {
+        "html" => format!(
+            "<figcaption>Generated page</figcaption><iframe sandbox=\"allow-scripts\" srcdoc=\"{}\"></iframe>",
+            html_escape(&media.content)
+        ),
+        "threejs" | "manifold" => format!(
+            "<figcaption>3D scene (open the session in ThinkSpace to view it)</figcaption><details><summary>Scene code</summary><pre><code>{}</code></pre></details>",
+            html_escape(&media.content)
+        ),
+        "youtube" => format!("<figcaption>Video</figcaption><a href=\"{0}\">{0}</a>", html_escape(&media.content)),
+        _ => format!("<figcaption>Page</figcaption><a href=\"{0}\">{0}</a>", html_escape(&media.content)),
+    };
+    format!("<figure class=\"media\">{}</figure>", body)
+}
+
+/// The standalone page for `canvas`; returns it, whether mermaid was left for the browser,
+/// and the problems met along the way
+fn canvas_document(
+    canvas: &StoredCanvas,
+    kb_root: &Path,
+    index: Option<&rusqlite::Connection>,
+    mermaid: &dyn Fn(&str) -> Option<String>,
+) -> (String, Vec<String>) {
+    let resolve = |name: &str| -> Option<PathBuf> {
+        let relative = kb_root.join(format!("{}.md", name.trim_end_matches(".md")));
+        if relative.is_file() {
+            return Some(relative);
+        }
+        let key = crate::note_links::resolve_note_name(index?, name).ok()??;
+        Some(kb_root.join(key))
+    };
+    let media = canvas.visuals.as_ref().map(media_items).unwrap_or_default();
+
+    let mut warnings = Vec::new();
+    let mut client_mermaid = false;
+    let mut sections = Vec::new();
+    let slots = [("main", "Primary notebook", &canvas.main_canvas), ("left", "Secondary notebook", &canvas.left_canvas)];
+    for (slot, label, markdown) in slots {
+        let markdown = markdown.as_deref().unwrap_or("");
+        let slot_media: Vec<&Media> = media.iter().filter(|m| m.slot == slot).collect();
+        if markdown.trim().is_empty() && slot_media.is_empty() {
+            continue;
+        }
+        let expanded = crate::markdown_render::expand_embeds(markdown, kb_root, &resolve, 0, &mut HashSet::new(), &mut warnings);
+        let (html, needs_client_mermaid) = crate::markdown_render::to_html(&expanded, kb_root, mermaid, &mut warnings);
+        client_mermaid |= needs_client_mermaid;
+        let media_html: String = slot_media.into_iter().map(media_html).collect();
+        sections.push((label, format!("{}{}", html, media_html)));
+    }
+    if sections.is_empty() {
+        warnings.push(format!("Session '{}' has no saved canvas", canvas.name));
+    }
+
+    let body: String = match sections.as_slice() {
+        [(_, only)] => format!("<section>{}</section>", only),
+        _ => sections.iter().map(|(label, html)| format!("<section><h2 class=\"slot\">{}</h2>{}</section>", label, html)).collect(),
+    };
+    let mermaid_script = if client_mermaid {
+        "<script type=\"module\">import mermaid from 'https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs';mermaid.initialize({startOnLoad:true});</script>"
+    } else {
+        ""
+    };
+    let saved = chrono::DateTime::parse_from_rfc3339(&canvas.timestamp)
+        .map(|t| format!("Saved {}", t.format("%Y-%m-%d %H:%M")))
+        .unwrap_or_default();
+    let page = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{title}</title><style>{style}</style></head>\
+         <body><main><header><h1>{title}</h1><p>{saved}</p></header>{body}</main>{mermaid}</body></html>\n",
+        title = html_escape(&canvas.name),
+        style = STYLE_CSS,
+        saved = saved,
+        body = body,
+        mermaid = mermaid_script,
+    );
+    (page, warnings)
+}
+
+/// Rough height of the rendered page, so a PNG shows all of it
+fn png_height(canvas: &StoredCanvas) -> u32 {
+    let text: usize = [&canvas.main_canvas, &canvas.left_canvas].iter().filter_map(|c| c.as_deref()).map(|c| c.len()).sum();
+    let lines: usize = [&canvas.main_canvas, &canvas.left_canvas].iter().filter_map(|c| c.as_deref()).map(|c| c.lines().count()).sum();
+    let images = [&canvas.main_canvas, &canvas.left_canvas].iter().filter_map(|c| c.as_deref()).map(|c| c.matches("![").count()).sum::<usize>();
+    let media = canvas.visuals.as_ref().map(|v| media_items(v).len()).unwrap_or(0);
+    let height = 240 + (text / 100 + lines) * 26 + (images + media) * 680;
+    (height as u32).clamp(800, MAX_PNG_HEIGHT)
+}
+
+/// A Chromium-based browser that can print headless
+fn find_browser() -> Option<PathBuf> {
+    let on_path = ["chromium", "chromium-browser", "google-chrome", "google-chrome-stable", "microsoft-edge", "brave-browser"];
+    let installed = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Brave Browser.app/Contents/MacOS/Brave Browser",
+        r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+        r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+        r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+        r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+    ];
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    on_path
+        .iter()
+        .flat_map(|name| std::env::split_paths(&path_var).map(move |dir| dir.join(name)))
+        .chain(installed.iter().map(PathBuf::from))
+        .find(|path| path.is_file())
+}
+
+/// Print `page` (an HTML file) to `output` as a PDF or PNG with a headless browser
+fn print_page(page: &Path, output: &Path, format: CanvasFormat, png_height: u32) -> Result<(), String> {
+    let browser = find_browser()
+        .ok_or("PDF and PNG export need Chrome, Chromium, Edge or Brave installed; export as HTML instead")?;
+    // Its own profile, so a browser that's already open doesn't take the job over
+    let profile = std::env::temp_dir().join(format!("thinkspace-print-{}", uuid::Uuid::new_v4().simple()));
+    let page_url = url::Url::from_file_path(page).map_err(|_| format!("Invalid path {}", page.display()))?;
+
+    let mut command = std::process::Command::new(&browser);
+    command
+        .arg("--headless")
+        .arg("--disable-gpu")
+        .arg("--hide-scrollbars")
+        .arg(format!("--user-data-dir={}", profile.display()));
+    match format {
+        CanvasFormat::Pdf => command
+            .arg("--no-pdf-header-footer")
+            .arg("--print-to-pdf-no-header")
+            .arg(format!("--print-to-pdf={}", output.display())),
+        _ => command
+            .arg(format!("--window-size={},{}", PNG_WIDTH, png_height))
+            .arg(format!("--screenshot={}", output.display())),
+    };
+    let mut child = command
+        .arg(page_url.as_str())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", browser.display(), e))?;
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) if started.elapsed() > PRINT_TIMEOUT => {
+                let _ = child.kill();
+                break Err(format!("{} didn't finish printing within {}s", browser.display(), PRINT_TIMEOUT.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(200)),
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    let _ = std::fs::remove_dir_all(&profile);
+    let status = status?;
+    if !status.success() || !output.is_file() {
+        return Err(format!("{} failed to print the canvas ({})", browser.display(), status));
+    }
+    Ok(())
+}
+
+/// Render session `session_id`'s saved canvases to a standalone HTML page, or a PDF or PNG
+/// of it, at `dest` (a file or a folder; by default the profile's `exports/` folder)
+#[tauri::command]
+pub async fn export_canvas(
+    app_handle: tauri::AppHandle,
+    session_id: String,
+    format: String,
+    dest: Option<String>,
+) -> Result<CanvasExport, String> {
+    let format = CanvasFormat::parse(&format)?;
+    let sessions_dir = crate::session::sessions_dir(&app_handle)?;
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let exports_dir = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?.join("exports");
+    let index = crate::note_links::open_index(Some(&app_handle)).ok();
+
+    crate::db::blocking(move || {
+        let canvas: StoredCanvas = crate::session::read_session(&crate::session::find_session_file(&sessions_dir, &session_id)?)?;
+        let (page, warnings) = canvas_document(&canvas, &kb_root, index.as_ref(), &crate::markdown_render::render_mermaid);
+
+        let file_name = format!("{}-canvas.{}", crate::session::safe_name(&canvas.name), format.extension());
+        let path = match dest.as_deref().map(PathBuf::from) {
+            Some(dest) if dest.extension().is_some() => dest,
+            Some(dir) => dir.join(file_name),
+            None => exports_dir.join(file_name),
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        if format == CanvasFormat::Html {
+            std::fs::write(&path, page).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        } else {
+            let page_path = std::env::temp_dir().join(format!("thinkspace-canvas-{}.html", uuid::Uuid::new_v4().simple()));
+            std::fs::write(&page_path, page).map_err(|e| e.to_string())?;
+            let printed = print_page(&page_path, &path, format, png_height(&canvas));
+            let _ = std::fs::remove_file(&page_path);
+            printed?;
+        }
+
+        tracing::info!("🖼️ Exported canvas of '{}' to {}", canvas.name, path.display());
+        Ok(CanvasExport {
+            path: path.to_string_lossy().to_string(),
+            format: format.extension().to_string(),
+            warnings,
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn study_canvas() -> StoredCanvas {
+        serde_json::from_value(serde_json::json!({
+            "name": "Rust study",
+            "timestamp": "2026-10-16T09:30:00Z",
+            "chat": [{ "role": "user", "content": "hi" }],
+            "main_canvas": "# Notes\n\n![[Ownership]]\n\n![chart](chart.png)\n\n![[Missing]]",
+            "left_canvas": null,
+            "visuals": {
+                "main": { "type": "html", "content": "<h1 class=\"x\">Harvest</h1>" },
+                "left": { "type": "youtube", "content": "https://youtu.be/abc" }
+            }
+        }))
+        .unwrap()
+    }
+
+    /// The study canvas rendered against a knowledge base holding its note and image
+    fn rendered() -> (String, Vec<String>) {
+        let kb = tempfile::tempdir().unwrap();
+        std::fs::write(kb.path().join("Ownership.md"), "Each value has one **owner**.").unwrap();
+        std::fs::write(kb.path().join("chart.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        canvas_document(&study_canvas(), kb.path(), None, &|_| None)
+    }
+
+    #[test]
+    fn test_media_items() {
+        assert_eq!(media_items(study_canvas().visuals.as_ref().unwrap()).len(), 2);
+        assert_eq!(media_items(&serde_json::json!({ "type_": "url", "content": "https://a.example" }))[0].slot, "main");
+    }
+
+    #[test]
+    fn test_document_inlines_notes_and_images() {
+        let (page, _) = rendered();
+        assert!(page.contains("<title>Rust study</title>"));
+        assert!(page.contains("Saved 2026-10-16 09:30"));
+        assert!(page.contains("<strong>owner</strong>"));
+        assert!(page.contains("src=\"data:image/png;base64,"));
+    }
+
+    #[test]
+    fn test_document_includes_the_visuals() {
+        let (page, _) = rendered();
+        assert!(page.contains("srcdoc=\"&lt;h1 class=&quot;x&quot;&gt;Harvest&lt;/h1&gt;\""));
+        // The left slot has only a video, so both slots get a heading
+        assert!(page.contains("Secondary notebook</h2><figure class=\"media\"><figcaption>Video</figcaption><a href=\"https://youtu.be/abc\">"));
+    }
+
+    #[test]
+    fn test_document_warns_about_missing_notes() {
+        let (_, warnings) = rendered();
+        assert_eq!(warnings, vec!["Embedded note not found: Missing".to_string()]);
+    }
+
+    #[test]
+    fn test_canvas_format_parse() {
+        assert_eq!(CanvasFormat::parse("PDF").unwrap(), CanvasFormat::Pdf);
+        assert!(CanvasFormat::parse("docx").is_err());
+    }
+
+    #[test]
+    fn test_png_height() {
+        assert!(png_height(&study_canvas()) >= 800);
+    }
+}
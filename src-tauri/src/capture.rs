@@ -0,0 +1,192 @@
+/// Quick capture from anywhere with a global shortcut
+///
+/// The `[capture]` hotkey (CmdOrCtrl+Shift+Space unless configured) opens a small
+/// always-on-top window wherever the user is. What's typed there is appended to the inbox
+/// note (`dumps/Inbox.md` by default) with the time, run through WAMA's reminder check, and
+/// offered to the TKG, where WAMA decides whether it's worth remembering. Changing the
+/// hotkey in thinkspace.toml re-registers it right away; an empty one turns it off.
+
+use std::path::Path;
+use tauri::{AppHandle, GlobalShortcutManager, Manager};
+
+pub const CAPTURE_WINDOW: &str = "quick-capture";
+const INBOX_TEMPLATE: &str = "---\ntitle: Inbox\ntags: [inbox]\n---\n\n# Inbox\n\n";
+const MODIFIERS: [&str; 11] = ["cmd", "command", "super", "ctrl", "control", "cmdorctrl", "commandorcontrol", "alt", "option", "shift", "meta"];
+
+/// Reject an inbox outside the knowledge base folders or a hotkey with no key
+pub fn check_config(capture: &crate::config::CaptureConfig) -> Result<(), String> {
+    let inbox = Path::new(&capture.inbox);
+    if inbox.extension().map_or(true, |e| e != "md") {
+        return Err(format!("Invalid inbox in [capture]: '{}' is not a .md note", capture.inbox));
+    }
+    let folder = inbox.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    crate::kb_import::check_target_folder(&folder).map_err(|e| format!("Invalid inbox in [capture]: {}", e))?;
+
+    let hotkey = capture.hotkey.trim();
+    if hotkey.is_empty() {
+        return Ok(());
+    }
+    let parts: Vec<&str> = hotkey.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts.split_last().unwrap_or((&"", &[]));
+    let unknown = modifiers.iter().find(|m| !MODIFIERS.contains(&m.to_lowercase().as_str()));
+    if key.is_empty() || MODIFIERS.contains(&key.to_lowercase().as_str()) || unknown.is_some() {
+        return Err(format!("Invalid hotkey in [capture]: '{}', use modifiers and a key like \"CmdOrCtrl+Shift+Space\"", capture.hotkey));
+    }
+    Ok(())
+}
+
+/// Append `text` captured at `stamp` to the inbox note's `content`, creating the note if it's empty
+pub fn append_capture(content: &str, stamp: &str, text: &str) -> String {
+    let mut out = if content.trim().is_empty() { INBOX_TEMPLATE.to_string() } else { content.trim_end().to_string() + "\n" };
+    let mut lines = text.trim().lines();
+    out.push_str(&format!("- {} {}\n", stamp, lines.next().unwrap_or_default().trim_end()));
+    // Further lines stay with their entry
+    for line in lines {
+        out.push_str(&format!("  {}\n", line.trim_end()));
+    }
+    out
+}
+
+/// Show the capture window, making it the first time
+fn open_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_window(CAPTURE_WINDOW) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+    let built = tauri::WindowBuilder::new(app_handle, CAPTURE_WINDOW, tauri::WindowUrl::App("index.html#quick-capture".into()))
+        .title("Quick capture")
+        .inner_size(520.0, 180.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build();
+    if let Err(e) = built {
+        tracing::warn!("⚠️ Failed to open the capture window: {}", e);
+    }
+}
+
+/// Register the configured hotkey, replacing `previous` (the one registered before, if any)
+pub fn register_hotkey(app_handle: &AppHandle, previous: Option<&str>) {
+    let mut shortcuts = app_handle.global_shortcut_manager();
+    if let Some(previous) = previous.map(str::trim).filter(|p| !p.is_empty()) {
+        if let Err(e) = shortcuts.unregister(previous) {
+            tracing::warn!("⚠️ Failed to release the capture hotkey {}: {}", previous, e);
+        }
+    }
+    let hotkey = crate::config::current().capture.hotkey.trim().to_string();
+    if hotkey.is_empty() {
+        return;
+    }
+    let handle = app_handle.clone();
+    match shortcuts.register(&hotkey, move || open_window(&handle)) {
+        Ok(()) => tracing::info!("⌨️ Quick capture on {}", hotkey),
+        // Usually another app holds the same shortcut
+        Err(e) => tracing::warn!("⚠️ Failed to register the capture hotkey {}: {}", hotkey, e),
+    }
+}
+
+/// Append `text` to the inbox note and hand it to WAMA; `store` overrides `store_to_tkg`
+pub async fn capture_text(app_handle: &AppHandle, text: &str, user_id: &str, store: Option<bool>) -> Result<serde_json::Value, String> {
+    if text.trim().is_empty() {
+        return Err("Nothing to capture".to_string());
+    }
+    let config = crate::config::current();
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let inbox = kb_root.join(&config.capture.inbox);
+    let stamp = crate::config::now().format("%Y-%m-%d %H:%M").to_string();
+
+    let entry = text.to_string();
+    let handle = app_handle.clone();
+    crate::db::blocking(move || {
+        if let Some(parent) = inbox.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = std::fs::read_to_string(&inbox).unwrap_or_default();
+        crate::note_versions::record_before_write(Some(&handle), &kb_root, &inbox, "capture");
+        std::fs::write(&inbox, append_capture(&content, &stamp, &entry)).map_err(|e| format!("Failed to write the inbox: {}", e))
+    })
+    .await?;
+
+    let wama = if store.unwrap_or(config.capture.store_to_tkg) {
+        let memory = format!("Captured: {}", text.trim());
+        match crate::tkg::tkg_store_knowledge(memory, "MEMORY".to_string(), 0.5, user_id.to_string()).await {
+            Ok(result) => serde_json::from_str(&result).unwrap_or(serde_json::Value::String(result)),
+            // TKG not configured or WAMA rejected the capture - it's still in the inbox
+            Err(e) => serde_json::json!({ "success": false, "message": e }),
+        }
+    } else {
+        // Not kept in the TKG, but a "remind me ..." still becomes a reminder
+        crate::reminders::capture_memory(text.trim());
+        serde_json::Value::Null
+    };
+
+    tracing::info!("📥 Captured to {}", config.capture.inbox);
+    Ok(serde_json::json!({
+        "success": true,
+        "path": config.capture.inbox,
+        "wama": wama
+    }))
+}
+
+#[tauri::command]
+pub async fn quick_capture(
+    app_handle: AppHandle,
+    text: String,
+    user_id: Option<String>,
+    store: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    capture_text(&app_handle, &text, user_id.as_deref().unwrap_or("guest"), store).await
+}
+
+/// Hide the capture window without capturing
+#[tauri::command]
+pub fn close_quick_capture(app_handle: AppHandle) {
+    if let Some(window) = app_handle.get_window(CAPTURE_WINDOW) {
+        let _ = window.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CaptureConfig;
+
+    fn with(hotkey: &str, inbox: &str) -> CaptureConfig {
+        CaptureConfig { hotkey: hotkey.to_string(), inbox: inbox.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_valid_capture_configs() {
+        assert!(check_config(&CaptureConfig::default()).is_ok());
+        assert!(check_config(&with("", "journal/inbox.md")).is_ok());
+    }
+
+    #[test]
+    fn test_bad_hotkeys_are_rejected() {
+        assert!(check_config(&with("Alt+Shift", "dumps/Inbox.md")).is_err());
+        assert!(check_config(&with("Hyper+K", "dumps/Inbox.md")).is_err());
+    }
+
+    #[test]
+    fn test_bad_inbox_paths_are_rejected() {
+        assert!(check_config(&with("CmdOrCtrl+K", "../Inbox.md")).is_err());
+        assert!(check_config(&with("CmdOrCtrl+K", "dumps/Inbox.txt")).is_err());
+    }
+
+    #[test]
+    fn test_first_capture_starts_the_inbox() {
+        let first = append_capture("", "2026-10-16 09:30", "Call the printer\nabout the quote ");
+        assert_eq!(first, format!("{}- 2026-10-16 09:30 Call the printer\n  about the quote\n", INBOX_TEMPLATE));
+    }
+
+    #[test]
+    fn test_later_captures_follow_the_last_entry() {
+        let first = append_capture("", "2026-10-16 09:30", "Call the printer\nabout the quote ");
+        let second = append_capture(&format!("{}\n\n", first), "2026-10-16 10:00", "Idea: spaced reading list");
+        assert!(second.ends_with("  about the quote\n- 2026-10-16 10:00 Idea: spaced reading list\n"));
+    }
+}
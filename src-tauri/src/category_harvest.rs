@@ -0,0 +1,266 @@
+/// Progress of wiki category harvests, so an interrupted one can resume
+///
+/// Each harvest (one category on one wiki) records its pages in knowledge_companion.db as
+/// pending, saved or failed. Running the same category again while pages are left resumes
+/// it: saved pages are skipped and only the rest are fetched. Once every page has been
+/// tried the harvest is complete, and the next run starts over.
+///
+/// Pages are fetched `concurrency` at a time with `delay_ms` before each request, and a
+/// `wiki-harvest-progress` event is emitted after every page.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use crate::scheduler::stamp;
+
+pub const DEFAULT_CONCURRENCY: usize = 2;
+pub const MAX_CONCURRENCY: usize = 8;
+pub const DEFAULT_DELAY_MS: u64 = 200;
+const MAX_DELAY_MS: u64 = 60_000;
+pub const DEFAULT_LIMIT: usize = 10;
+pub const MAX_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HarvestOptions {
+    pub concurrency: usize,
+    /// Pause before each page request, per worker
+    pub delay_ms: u64,
+    pub limit: usize,
+    /// Continue an unfinished harvest of the category instead of starting over
+    pub resume: bool,
+}
+
+impl HarvestOptions {
+    /// Options from the harvest tool's arguments, clamped to sane bounds
+    pub fn from_args(args: &std::collections::HashMap<String, serde_json::Value>) -> Self {
+        let number = |key: &str| args.get(key).and_then(|v| v.as_u64());
+        Self {
+            concurrency: number("concurrency").map_or(DEFAULT_CONCURRENCY, |n| n as usize).clamp(1, MAX_CONCURRENCY),
+            delay_ms: number("delay_ms").unwrap_or(DEFAULT_DELAY_MS).min(MAX_DELAY_MS),
+            limit: number("limit").map_or(DEFAULT_LIMIT, |n| n as usize).clamp(1, MAX_LIMIT),
+            resume: args.get("resume").and_then(|v| v.as_bool()).unwrap_or(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryHarvest {
+    pub id: i64,
+    pub api_base: String,
+    pub category: String,
+    /// "running" or "completed"
+    pub status: String,
+    pub total: usize,
+    pub saved: usize,
+    pub failed: usize,
+    pub started_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarvestProgress {
+    pub harvest_id: i64,
+    pub category: String,
+    pub wiki: String,
+    pub title: String,
+    /// "saved" or "failed"
+    pub status: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+    /// Pages done so far, including ones saved by an earlier run
+    pub done: usize,
+    pub total: usize,
+}
+
+pub fn init_category_harvest_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS category_harvests (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            api_base TEXT NOT NULL,
+            category TEXT NOT NULL,
+            status TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(api_base, category)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS category_harvest_pages (
+            harvest_id INTEGER NOT NULL REFERENCES category_harvests(id) ON DELETE CASCADE,
+            title TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            path TEXT,
+            error TEXT,
+            PRIMARY KEY (harvest_id, title)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Start a harvest of `titles`, or resume the unfinished one of the same category.
+/// Returns its id and the titles still to fetch.
+pub fn begin(conn: &Connection, api_base: &str, category: &str, titles: &[String], resume: bool) -> rusqlite::Result<(i64, Vec<String>)> {
+    let now = stamp(chrono::Utc::now());
+    let existing: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, status FROM category_harvests WHERE api_base = ?1 AND category = ?2",
+            params![api_base, category],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let id = match existing {
+        Some((id, status)) => {
+            if !resume || status == "completed" {
+                conn.execute("DELETE FROM category_harvest_pages WHERE harvest_id = ?1", [id])?;
+                conn.execute("UPDATE category_harvests SET started_at = ?1 WHERE id = ?2", params![now, id])?;
+            }
+            conn.execute("UPDATE category_harvests SET status = 'running', updated_at = ?1 WHERE id = ?2", params![now, id])?;
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO category_harvests (api_base, category, status, started_at, updated_at) VALUES (?1, ?2, 'running', ?3, ?3)",
+                params![api_base, category, now],
+            )?;
+            conn.last_insert_rowid()
+        }
+    };
+    for title in titles {
+        conn.execute("INSERT OR IGNORE INTO category_harvest_pages (harvest_id, title) VALUES (?1, ?2)", params![id, title])?;
+    }
+    // Failed pages are retried on resume, saved ones skipped
+    let saved: std::collections::HashSet<String> = {
+        let mut stmt = conn.prepare("SELECT title FROM category_harvest_pages WHERE harvest_id = ?1 AND status = 'saved'")?;
+        let rows = stmt.query_map([id], |row| row.get(0))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    Ok((id, titles.iter().filter(|t| !saved.contains(*t)).cloned().collect()))
+}
+
+/// Record the outcome of one page
+pub fn mark_page(conn: &Connection, harvest_id: i64, title: &str, result: &Result<String, String>) -> rusqlite::Result<()> {
+    let (status, path, error) = match result {
+        Ok(path) => ("saved", Some(path.as_str()), None),
+        Err(e) => ("failed", None, Some(e.as_str())),
+    };
+    conn.execute(
+        "UPDATE category_harvest_pages SET status = ?1, path = ?2, error = ?3 WHERE harvest_id = ?4 AND title = ?5",
+        params![status, path, error, harvest_id, title],
+    )?;
+    conn.execute("UPDATE category_harvests SET updated_at = ?1 WHERE id = ?2", params![stamp(chrono::Utc::now()), harvest_id])?;
+    Ok(())
+}
+
+pub fn finish(conn: &Connection, harvest_id: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE category_harvests SET status = 'completed', updated_at = ?1 WHERE id = ?2",
+        params![stamp(chrono::Utc::now()), harvest_id],
+    )?;
+    Ok(())
+}
+
+pub fn list_harvests(conn: &Connection) -> rusqlite::Result<Vec<CategoryHarvest>> {
+    let mut stmt = conn.prepare(
+        "SELECT h.id, h.api_base, h.category, h.status, h.started_at, h.updated_at,
+                COUNT(p.title),
+                COALESCE(SUM(p.status = 'saved'), 0),
+                COALESCE(SUM(p.status = 'failed'), 0)
+         FROM category_harvests h LEFT JOIN category_harvest_pages p ON p.harvest_id = h.id
+         GROUP BY h.id ORDER BY h.updated_at DESC",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(CategoryHarvest {
+            id: row.get(0)?,
+            api_base: row.get(1)?,
+            category: row.get(2)?,
+            status: row.get(3)?,
+            started_at: row.get(4)?,
+            updated_at: row.get(5)?,
+            total: row.get::<_, i64>(6)? as usize,
+            saved: row.get::<_, i64>(7)? as usize,
+            failed: row.get::<_, i64>(8)? as usize,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn emit_progress(app_handle: Option<&tauri::AppHandle>, progress: &HarvestProgress) {
+    if let Some(handle) = app_handle {
+        let _ = handle.emit_all("wiki-harvest-progress", progress);
+    }
+}
+
+/// Category harvests, most recently active first, with how many pages are saved or failed
+#[tauri::command]
+pub fn list_category_harvests(app_handle: tauri::AppHandle) -> Result<Vec<CategoryHarvest>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_harvests(&conn).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API: &str = "https://oldschool.runescape.wiki/api.php";
+
+    fn harvest_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_category_harvest_tables(&conn).unwrap();
+        conn
+    }
+
+    fn titles() -> Vec<String> {
+        ["Zulrah", "Vorkath", "Kraken"].iter().map(|t| t.to_string()).collect()
+    }
+
+    /// A harvest interrupted after saving Zulrah and failing Vorkath
+    fn interrupted(conn: &Connection) -> i64 {
+        let (id, todo) = begin(conn, API, "Bosses", &titles(), true).unwrap();
+        assert_eq!(todo.len(), 3);
+        mark_page(conn, id, "Zulrah", &Ok("research/osrs/Bosses/Zulrah.md".to_string())).unwrap();
+        mark_page(conn, id, "Vorkath", &Err("timeout".to_string())).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_options_are_capped() {
+        let args: std::collections::HashMap<String, serde_json::Value> =
+            serde_json::from_str(r#"{"concurrency": 50, "delay_ms": 0, "limit": 1000}"#).unwrap();
+        let options = HarvestOptions::from_args(&args);
+        assert_eq!((options.concurrency, options.delay_ms, options.limit, options.resume), (MAX_CONCURRENCY, 0, MAX_LIMIT, true));
+    }
+
+    #[test]
+    fn test_resume_skips_saved_pages_and_retries_failed_ones() {
+        let conn = harvest_db();
+        let id = interrupted(&conn);
+        let (resumed, todo) = begin(&conn, API, "Bosses", &titles(), true).unwrap();
+        assert_eq!((resumed, todo), (id, vec!["Vorkath".to_string(), "Kraken".to_string()]));
+    }
+
+    #[test]
+    fn test_list_harvests_counts_pages() {
+        let conn = harvest_db();
+        interrupted(&conn);
+        let listed = &list_harvests(&conn).unwrap()[0];
+        assert_eq!((listed.total, listed.saved, listed.failed, listed.status.as_str()), (3, 1, 1, "running"));
+    }
+
+    #[test]
+    fn test_completed_harvest_starts_over() {
+        let conn = harvest_db();
+        let id = interrupted(&conn);
+        finish(&conn, id).unwrap();
+        assert_eq!(begin(&conn, API, "Bosses", &titles(), true).unwrap().1.len(), 3);
+    }
+
+    #[test]
+    fn test_resume_false_starts_over() {
+        let conn = harvest_db();
+        interrupted(&conn);
+        assert_eq!(begin(&conn, API, "Bosses", &titles(), false).unwrap().1.len(), 3);
+    }
+}
@@ -0,0 +1,513 @@
+/// A Discord or Slack bot that relays messages to the agent
+///
+/// With a bot token, channels and users under `[bridge.discord]` or `[bridge.slack]`, a
+/// background loop polls those channels and runs each new message from one of the listed
+/// users through `chat_with_agent`, as the profile's TKG user, then posts the reply in the
+/// channel. Each channel keeps its last few exchanges in memory so follow-ups work; they're
+/// gone after a restart, and messages sent while the app was closed are left unanswered.
+///
+/// Anyone who can post as a listed user can drive the agent, so it only gets the read-only
+/// tools in SAFE_TOOLS unless `[bridge] tools` names more. Like scheduled tasks, the bridge
+/// uses the keys the frontend gave `start_scheduler` and waits until it has them. Discord
+/// bots need the Message Content intent; Slack apps need `channels:history` (or
+/// `groups:history`) and `chat:write`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::config::BridgeConfig;
+use crate::minimax_enhanced::{Message, MinimaxAgent};
+use crate::net::SendChecked;
+use crate::scheduler::stamp;
+
+const DISCORD_API: &str = "https://discord.com/api/v10";
+const SLACK_API: &str = "https://slack.com/api";
+/// Discord snowflakes count milliseconds from the start of 2015
+const DISCORD_EPOCH_MS: i64 = 1_420_070_400_000;
+/// Messages fetched per channel and poll
+const FETCH_LIMIT: usize = 50;
+/// Earlier messages (user and agent) each channel's conversation keeps
+const MAX_HISTORY: usize = 20;
+const MAX_ITERATIONS: usize = 15;
+
+/// Tools the agent gets by default: they read notes, memories, code and the web, but change
+/// nothing
+pub const SAFE_TOOLS: &[&str] = &[
+    "calculate",
+    "search_knowledge",
+    "read_file",
+    "read_pdf",
+    "list_markdown_files",
+    "get_note_links",
+    "tkg_search",
+    "web_search",
+    "read_tool_result",
+    "find_symbol",
+    "grep_codebase",
+    "get_repo_stats",
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BridgeAccount {
+    /// The bot token; unset leaves this platform off
+    pub token: Option<String>,
+    /// Channel ids to answer in
+    pub channels: Vec<String>,
+    /// User ids whose messages are answered
+    pub users: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Discord,
+    Slack,
+}
+
+impl Platform {
+    const ALL: [Platform; 2] = [Platform::Discord, Platform::Slack];
+
+    fn name(self) -> &'static str {
+        match self {
+            Platform::Discord => "discord",
+            Platform::Slack => "slack",
+        }
+    }
+
+    fn account(self, config: &BridgeConfig) -> &BridgeAccount {
+        match self {
+            Platform::Discord => &config.discord,
+            Platform::Slack => &config.slack,
+        }
+    }
+
+    /// Longest message the platform takes, in characters
+    fn message_limit(self) -> usize {
+        match self {
+            Platform::Discord => 2000,
+            Platform::Slack => 4000,
+        }
+    }
+
+    /// A cursor past every message sent before `time`
+    fn cursor_at(self, time: DateTime<Utc>) -> String {
+        match self {
+            Platform::Discord => (((time.timestamp_millis() - DISCORD_EPOCH_MS).max(0) as u64) << 22).to_string(),
+            Platform::Slack => format!("{}.{:06}", time.timestamp(), time.timestamp_subsec_micros()),
+        }
+    }
+}
+
+/// Reject `[bridge]` settings that parse but can't be used
+pub fn check_config(config: &BridgeConfig) -> Result<(), String> {
+    for platform in Platform::ALL {
+        let account = platform.account(config);
+        if account.token.is_some() && (account.channels.is_empty() || account.users.is_empty()) {
+            return Err(format!("[bridge.{}] needs the channels to answer in and the users to answer", platform.name()));
+        }
+    }
+    if config.poll_seconds == 0 {
+        return Err("poll_seconds in [bridge] must be at least 1".to_string());
+    }
+    let known = MinimaxAgent::tool_names();
+    if let Some(unknown) = config.tools.iter().find(|tool| !known.contains(tool)) {
+        return Err(format!("Unknown tool '{}' in [bridge] tools", unknown));
+    }
+    Ok(())
+}
+
+/// Per-tool switches that leave the agent only SAFE_TOOLS and `extra`
+pub fn enabled_tools(all: &[String], extra: &[String]) -> HashMap<String, bool> {
+    all.iter()
+        .map(|tool| (tool.clone(), SAFE_TOOLS.contains(&tool.as_str()) || extra.contains(tool)))
+        .collect()
+}
+
+/// A message to answer
+#[derive(Debug, Clone, PartialEq)]
+struct Incoming {
+    /// Where the channel's cursor moves once it's handled
+    cursor: String,
+    text: String,
+}
+
+/// Drop leading mentions of the bot ("<@U123> what did I read about ...")
+fn strip_mentions(text: &str) -> &str {
+    let mut text = text.trim();
+    while let Some(rest) = text.strip_prefix("<@") {
+        match rest.find('>') {
+            Some(end) => text = rest[end + 1..].trim_start(),
+            None => break,
+        }
+    }
+    text
+}
+
+/// Messages from `users` in a Discord channel listing, oldest first
+fn discord_messages(listing: &serde_json::Value, users: &[String]) -> Vec<Incoming> {
+    let mut messages: Vec<(u64, Incoming)> = listing
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|message| {
+            let author = message.get("author")?;
+            if author.get("bot").and_then(|b| b.as_bool()).unwrap_or(false) {
+                return None;
+            }
+            let user = author.get("id")?.as_str()?;
+            let id = message.get("id")?.as_str()?;
+            let text = strip_mentions(message.get("content")?.as_str()?);
+            if !users.iter().any(|u| u == user) || text.is_empty() {
+                return None;
+            }
+            Some((id.parse().ok()?, Incoming { cursor: id.to_string(), text: text.to_string() }))
+        })
+        .collect();
+    messages.sort_by_key(|(id, _)| *id);
+    messages.into_iter().map(|(_, message)| message).collect()
+}
+
+/// A Slack `ts` ("1760601600.000200") as a sortable pair
+fn slack_ts(ts: &str) -> Option<(u64, u64)> {
+    let (seconds, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    Some((seconds.parse().ok()?, micros.parse().ok()?))
+}
+
+/// Messages from `users` in a Slack `conversations.history` response, oldest first
+fn slack_messages(history: &serde_json::Value, users: &[String]) -> Vec<Incoming> {
+    let mut messages: Vec<((u64, u64), Incoming)> = history
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|message| {
+            // Joins, edits and bot posts (including our replies) come with a subtype or bot_id
+            if message.get("subtype").is_some() || message.get("bot_id").is_some() {
+                return None;
+            }
+            let user = message.get("user")?.as_str()?;
+            let ts = message.get("ts")?.as_str()?;
+            let text = strip_mentions(message.get("text")?.as_str()?);
+            if !users.iter().any(|u| u == user) || text.is_empty() {
+                return None;
+            }
+            Some((slack_ts(ts)?, Incoming { cursor: ts.to_string(), text: text.to_string() }))
+        })
+        .collect();
+    messages.sort_by_key(|(ts, _)| *ts);
+    messages.into_iter().map(|(_, message)| message).collect()
+}
+
+/// Split a reply into messages of at most `limit` characters, at a line break or space when
+/// there's one in the second half
+fn split_reply(text: &str, limit: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut rest = text.trim();
+    while rest.chars().count() > limit {
+        let hard = rest.char_indices().nth(limit).map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..hard];
+        let cut = window
+            .rfind('\n')
+            .or_else(|| window.rfind(' '))
+            .filter(|&i| i >= hard / 2)
+            .unwrap_or(hard);
+        parts.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
+}
+
+async fn fetch(platform: Platform, token: &str, channel: &str, cursor: &str, users: &[String]) -> Result<Vec<Incoming>, String> {
+    let client = crate::net::client();
+    match platform {
+        Platform::Discord => {
+            let url = format!("{}/channels/{}/messages", DISCORD_API, urlencoding::encode(channel));
+            let response = client
+                .get(url)
+                .header(reqwest::header::AUTHORIZATION, format!("Bot {}", token))
+                .query(&[("after", cursor.to_string()), ("limit", FETCH_LIMIT.to_string())])
+                .send_checked()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(format!("Discord channel {} ({}): {}", channel, status, body.chars().take(200).collect::<String>()));
+            }
+            let listing: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+            Ok(discord_messages(&listing, users))
+        }
+        Platform::Slack => {
+            let response = client
+                .get(format!("{}/conversations.history", SLACK_API))
+                .bearer_auth(token)
+                .query(&[("channel", channel.to_string()), ("oldest", cursor.to_string()), ("limit", FETCH_LIMIT.to_string())])
+                .send_checked()
+                .await
+                .map_err(|e| e.to_string())?;
+            let history: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+            slack_result(&history).map_err(|e| format!("Slack channel {}: {}", channel, e))?;
+            Ok(slack_messages(&history, users))
+        }
+    }
+}
+
+/// Slack answers 200 either way; failures say `"ok": false` and why
+fn slack_result(response: &serde_json::Value) -> Result<(), String> {
+    if response.get("ok").and_then(|ok| ok.as_bool()).unwrap_or(false) {
+        return Ok(());
+    }
+    Err(response.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error").to_string())
+}
+
+async fn post(platform: Platform, token: &str, channel: &str, text: &str) -> Result<(), String> {
+    let client = crate::net::client();
+    for part in split_reply(text, platform.message_limit()) {
+        match platform {
+            Platform::Discord => {
+                let url = format!("{}/channels/{}/messages", DISCORD_API, urlencoding::encode(channel));
+                let response = client
+                    .post(url)
+                    .header(reqwest::header::AUTHORIZATION, format!("Bot {}", token))
+                    .json(&serde_json::json!({ "content": part }))
+                    .send_checked()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if !response.status().is_success() {
+                    return Err(format!("Posting to Discord channel {} failed ({})", channel, response.status()));
+                }
+            }
+            Platform::Slack => {
+                let response = client
+                    .post(format!("{}/chat.postMessage", SLACK_API))
+                    .bearer_auth(token)
+                    .json(&serde_json::json!({ "channel": channel, "text": part }))
+                    .send_checked()
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let result: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+                slack_result(&result).map_err(|e| format!("Posting to Slack channel {} failed: {}", channel, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn message(role: &str, content: &str) -> Message {
+    Message { role: role.to_string(), content: content.to_string(), tool_calls: None, tool_call_id: None, timestamp: None }
+}
+
+/// Answer `text` with the scheduler's keys, continuing `history`
+async fn answer(app_handle: &AppHandle, config: &BridgeConfig, history: &[Message], text: &str) -> Result<String, String> {
+    let (provider, keys, scheduler_user) = crate::scheduler::keys().ok_or("The scheduler hasn't been started with API keys yet")?;
+    let user_id = crate::profiles::tkg_user_id(config.user_id.as_deref().unwrap_or(&scheduler_user));
+    let mut messages = history.to_vec();
+    messages.push(message("user", text));
+    let response = crate::minimax_enhanced::chat_with_agent(
+        app_handle.clone(),
+        provider.clone(),
+        keys.primary(&provider).unwrap_or_default(),
+        keys.tavily.clone(),
+        keys.grok.clone(),
+        keys.gemini.clone(),
+        messages,
+        Some(MAX_ITERATIONS),
+        Some(enabled_tools(&MinimaxAgent::tool_names(), &config.tools)),
+        Some(user_id),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(response.content)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChannelStatus {
+    pub replies: usize,
+    /// When a message was last answered (a `stamp`)
+    pub last_reply_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    /// Keyed by "<platform>:<channel>"
+    static ref STATUS: Mutex<HashMap<String, ChannelStatus>> = Mutex::new(HashMap::new());
+}
+
+fn record(key: &str, result: Result<(), String>) {
+    let mut status = STATUS.lock().unwrap();
+    let channel = status.entry(key.to_string()).or_default();
+    match result {
+        Ok(()) => {
+            channel.replies += 1;
+            channel.last_reply_at = Some(stamp(Utc::now()));
+            channel.last_error = None;
+        }
+        Err(e) => {
+            // Only log a failure once, not on every poll until it's fixed
+            if channel.last_error.as_deref() != Some(e.as_str()) {
+                tracing::warn!("⚠️ Chat bridge: {}", e);
+            }
+            channel.last_error = Some(e);
+        }
+    }
+}
+
+/// Answer what's new in one channel, moving its cursor past each message handled
+async fn poll_channel(app_handle: &AppHandle, config: &BridgeConfig, platform: Platform, channel: &str, cursor: &mut String, history: &mut Vec<Message>) {
+    let account = platform.account(config);
+    let token = account.token.as_deref().unwrap_or_default();
+    let key = format!("{}:{}", platform.name(), channel);
+    let incoming = match fetch(platform, token, channel, cursor, &account.users).await {
+        Ok(incoming) => incoming,
+        Err(e) => return record(&key, Err(e)),
+    };
+    for message_in in incoming {
+        // Moved first, so a message that fails isn't retried forever
+        *cursor = message_in.cursor.clone();
+        tracing::info!("💬 {} message in {}", platform.name(), channel);
+        let reply = match answer(app_handle, config, history, &message_in.text).await {
+            Ok(reply) => {
+                history.push(message("user", &message_in.text));
+                history.push(message("assistant", &reply));
+                let excess = history.len().saturating_sub(MAX_HISTORY);
+                history.drain(..excess);
+                reply
+            }
+            Err(e) => format!("⚠️ {}", e),
+        };
+        record(&key, post(platform, token, channel, &reply).await);
+    }
+}
+
+pub fn start_background(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let started = Utc::now();
+        let mut cursors: HashMap<String, String> = HashMap::new();
+        let mut histories: HashMap<String, Vec<Message>> = HashMap::new();
+        loop {
+            let config = crate::config::current().bridge.clone();
+            // Without keys nothing could be answered; messages wait until the scheduler starts
+            if crate::scheduler::keys().is_some() {
+                for platform in Platform::ALL {
+                    let account = platform.account(&config);
+                    if account.token.is_none() {
+                        continue;
+                    }
+                    for channel in &account.channels {
+                        let key = format!("{}:{}", platform.name(), channel);
+                        let cursor = cursors.entry(key.clone()).or_insert_with(|| platform.cursor_at(started));
+                        let history = histories.entry(key).or_default();
+                        poll_channel(&app_handle, &config, platform, channel, cursor, history).await;
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(config.poll_seconds.max(1))).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeStatus {
+    pub platform: Platform,
+    pub channel: String,
+    #[serde(flatten)]
+    pub status: ChannelStatus,
+}
+
+/// The configured channels and how answering in each has gone since launch
+#[tauri::command]
+pub fn get_chat_bridge_status() -> Vec<BridgeStatus> {
+    let config = crate::config::current().bridge.clone();
+    let status = STATUS.lock().unwrap();
+    Platform::ALL
+        .iter()
+        .filter(|platform| platform.account(&config).token.is_some())
+        .flat_map(|&platform| {
+            platform.account(&config).channels.iter().map(move |channel| (platform, channel.clone()))
+        })
+        .map(|(platform, channel)| BridgeStatus {
+            status: status.get(&format!("{}:{}", platform.name(), channel)).cloned().unwrap_or_default(),
+            platform,
+            channel,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_discord_messages_from_allowed_users_oldest_first() {
+        let users = vec!["42".to_string()];
+        let discord = serde_json::json!([
+            { "id": "1300000000000000002", "content": "<@99> and the second?", "author": { "id": "42" } },
+            { "id": "1300000000000000003", "content": "Here you go", "author": { "id": "99", "bot": true } },
+            { "id": "1300000000000000001", "content": "What did I note on lifetimes?", "author": { "id": "42" } },
+            { "id": "1300000000000000004", "content": "hi", "author": { "id": "7" } },
+        ]);
+        let texts: Vec<String> = discord_messages(&discord, &users).into_iter().map(|m| m.text).collect();
+        assert_eq!(texts, ["What did I note on lifetimes?", "and the second?"]);
+    }
+
+    #[test]
+    fn test_slack_messages_skip_bots_and_subtypes() {
+        let users = vec!["U42".to_string()];
+        let slack = serde_json::json!({ "ok": true, "messages": [
+            { "type": "message", "user": "U42", "text": "later", "ts": "1760601600.000200" },
+            { "type": "message", "user": "U42", "text": "joined", "ts": "1760601599.000000", "subtype": "channel_join" },
+            { "type": "message", "user": "U42", "text": "first", "ts": "1760601600.000010" },
+            { "type": "message", "text": "reply", "ts": "1760601601.000000", "bot_id": "B1" },
+        ]});
+        let messages = slack_messages(&slack, &users);
+        assert_eq!(messages.iter().map(|m| m.text.as_str()).collect::<Vec<_>>(), ["first", "later"]);
+        assert_eq!(messages[1].cursor, "1760601600.000200");
+    }
+
+    #[test]
+    fn test_slack_error_result() {
+        assert_eq!(slack_result(&serde_json::json!({ "ok": false, "error": "not_in_channel" })), Err("not_in_channel".to_string()));
+    }
+
+    #[test]
+    fn test_cursor_at_start_time() {
+        let started = Utc.with_ymd_and_hms(2026, 10, 16, 8, 0, 0).unwrap();
+        assert_eq!(Platform::Slack.cursor_at(started), "1792137600.000000");
+        let snowflake: u64 = Platform::Discord.cursor_at(started).parse().unwrap();
+        assert_eq!((snowflake >> 22) as i64 + DISCORD_EPOCH_MS, started.timestamp_millis());
+    }
+
+    #[test]
+    fn test_split_reply_at_line_and_word_breaks() {
+        let reply = format!("{}\n{}", "a".repeat(1500), "b ".repeat(600));
+        let parts = split_reply(&reply, 2000);
+        assert_eq!(parts[0], "a".repeat(1500));
+        assert!(parts.iter().all(|p| p.chars().count() <= 2000));
+        assert_eq!(parts.concat().replace([' ', '\n'], ""), reply.replace([' ', '\n'], ""));
+    }
+
+    #[test]
+    fn test_split_reply_counts_chars() {
+        assert_eq!(split_reply("ü".repeat(5).as_str(), 2), ["üü", "üü", "ü"]);
+    }
+
+    #[test]
+    fn test_enabled_tools() {
+        let tools = enabled_tools(&["tkg_search".to_string(), "write_file".to_string(), "tkg_store".to_string()], &["tkg_store".to_string()]);
+        assert_eq!((tools["tkg_search"], tools["write_file"], tools["tkg_store"]), (true, false, true));
+    }
+}
@@ -0,0 +1,282 @@
+/// Citations for deep research reports
+///
+/// Sources gathered by the research agents are numbered into a `Bibliography`; the
+/// synthesizer cites them with markdown footnotes (`[^3]`). The finished report gets the
+/// bibliography appended as footnote definitions, and `check_citations` flags sections
+/// that make claims without citing anything.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use regex::Regex;
+use crate::credibility::Credibility;
+use crate::deep_research::SearchResult;
+
+lazy_static::lazy_static! {
+    static ref FOOTNOTE_REF: Regex = Regex::new(r"\[\^(\d+)\](:?)").unwrap();
+    static ref BARE_REF: Regex = Regex::new(r"\[(\d+(?:\s*,\s*\d+)*)\]").unwrap();
+    static ref FOOTNOTE_DEF: Regex = Regex::new(r"^\[\^\d+\]:").unwrap();
+    static ref REFERENCES_HEADING: Regex =
+        Regex::new(r"(?i)^(references|bibliography|sources|citations|works cited|further reading)$").unwrap();
+    static ref THINK: Regex = Regex::new(r"(?s)<think>.*?</think>").unwrap();
+}
+
+/// Sections with fewer prose words than this aren't expected to cite anything
+const MIN_CLAIM_WORDS: usize = 25;
+
+pub const CITATION_INSTRUCTIONS: &str = "Each source in the research data is numbered like [3]. \
+Cite sources inline with markdown footnote markers matching those numbers, e.g. \"Rust has no garbage collector[^3].\" \
+Every section that states facts must cite at least one source. Do not write a references or bibliography section; it is added automatically.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BibEntry {
+    pub number: usize,
+    pub title: String,
+    pub url: String,
+    /// Date the source was fetched (YYYY-MM-DD)
+    pub accessed: String,
+    #[serde(default)]
+    pub credibility: Option<Credibility>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bibliography {
+    pub entries: Vec<BibEntry>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CitationCheck {
+    /// Bibliography numbers the report cites
+    pub cited: Vec<usize>,
+    /// Numbers cited that aren't in the bibliography
+    pub unknown: Vec<usize>,
+    /// Headings of claim-bearing sections without a citation
+    pub uncited_sections: Vec<String>,
+}
+
+impl Bibliography {
+    /// Number for `url`, adding it if it's new
+    pub fn add(&mut self, title: &str, url: &str) -> usize {
+        if let Some(entry) = self.entries.iter().find(|e| e.url == url) {
+            return entry.number;
+        }
+        let number = self.entries.len() + 1;
+        self.entries.push(BibEntry {
+            number,
+            title: if title.trim().is_empty() { url.to_string() } else { title.trim().to_string() },
+            url: url.to_string(),
+            accessed: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            credibility: None,
+        });
+        number
+    }
+
+    pub fn contains(&self, number: usize) -> bool {
+        number >= 1 && number <= self.entries.len()
+    }
+
+    /// Number `sources` and format them as research data for the synthesizer, most
+    /// credible first, with each source's credibility when it was scored
+    pub fn format_sources(&mut self, sources: &[SearchResult], credibility: &HashMap<String, Credibility>) -> String {
+        let score = |s: &SearchResult| credibility.get(&s.url).map_or(0.0, |c| c.score);
+        let mut ordered: Vec<&SearchResult> = sources.iter().collect();
+        ordered.sort_by(|a, b| score(b).partial_cmp(&score(a)).unwrap_or(std::cmp::Ordering::Equal));
+
+        ordered
+            .into_iter()
+            .map(|s| {
+                let number = self.add(&s.title, &s.url);
+                match credibility.get(&s.url) {
+                    Some(c) => {
+                        self.entries[number - 1].credibility = Some(c.clone());
+                        format!("[{}] {}\nURL: {}\nCredibility: {}\nContent: {}\n\n", number, s.title, s.url, c.label(), s.content)
+                    }
+                    None => format!("[{}] {}\nURL: {}\nContent: {}\n\n", number, s.title, s.url, s.content),
+                }
+            })
+            .collect()
+    }
+
+    /// One line per source, for revision prompts
+    pub fn summary(&self) -> String {
+        self.entries.iter().map(|e| format!("[{}] {} - {}", e.number, e.title, e.url)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+fn is_references_heading(heading: &str) -> bool {
+    REFERENCES_HEADING.is_match(heading.trim_start_matches('#').trim())
+}
+
+/// Clean up a synthesized report: drop `<think>` blocks, the model's own reference list and
+/// footnote definitions, and turn bare `[3]` / `[1, 2]` citations into footnote markers
+pub fn normalize_citations(report: &str, bib: &Bibliography) -> String {
+    let report = THINK.replace_all(report, "");
+    let mut out = Vec::new();
+    let mut in_code = false;
+    let mut skipping_level: Option<usize> = None;
+
+    for line in report.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if !in_code && line.starts_with('#') {
+            let level = line.chars().take_while(|c| *c == '#').count();
+            match skipping_level {
+                Some(skip) if level > skip => continue,
+                _ => skipping_level = None,
+            }
+            if is_references_heading(line) {
+                skipping_level = Some(level);
+                continue;
+            }
+        }
+        if skipping_level.is_some() || (!in_code && FOOTNOTE_DEF.is_match(line)) {
+            continue;
+        }
+        if in_code {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let converted = BARE_REF.replace_all(line, |caps: &regex::Captures| {
+            let whole = caps.get(0).unwrap();
+            // Leave markdown links ([1](url), [text][1]), link definitions ([1]: url) and unknown numbers alone
+            let prev = line[..whole.start()].chars().last();
+            let next = line[whole.end()..].chars().next();
+            let numbers: Vec<usize> = caps[1].split(',').filter_map(|n| n.trim().parse().ok()).collect();
+            if prev == Some(']') || matches!(next, Some('(') | Some(':') | Some('[')) || !numbers.iter().all(|n| bib.contains(*n)) {
+                return whole.as_str().to_string();
+            }
+            numbers.iter().map(|n| format!("[^{}]", n)).collect()
+        });
+        out.push(converted.into_owned());
+    }
+
+    out.join("\n").trim().to_string()
+}
+
+/// Which citations the report uses and which claim-bearing sections cite nothing
+pub fn check_citations(report: &str, bib: &Bibliography) -> CitationCheck {
+    let mut cited = BTreeSet::new();
+    let mut unknown = BTreeSet::new();
+    for caps in FOOTNOTE_REF.captures_iter(report) {
+        if !caps[2].is_empty() {
+            continue;
+        }
+        if let Ok(n) = caps[1].parse::<usize>() {
+            if bib.contains(n) { cited.insert(n) } else { unknown.insert(n) };
+        }
+    }
+
+    let mut uncited_sections = Vec::new();
+    for section in crate::kb_index::split_sections(report) {
+        if is_references_heading(&section.heading) {
+            continue;
+        }
+        let mut in_code = false;
+        let prose_words: usize = section
+            .content
+            .lines()
+            .filter(|l| {
+                if l.trim_start().starts_with("```") {
+                    in_code = !in_code;
+                    return false;
+                }
+                !in_code && !l.starts_with('#')
+            })
+            .map(|l| l.split_whitespace().count())
+            .sum();
+        let has_citation = FOOTNOTE_REF.captures_iter(&section.content).any(|c| c[2].is_empty());
+        if prose_words >= MIN_CLAIM_WORDS && !has_citation {
+            uncited_sections.push(if section.heading.is_empty() { "(introduction)".to_string() } else { section.heading });
+        }
+    }
+
+    CitationCheck {
+        cited: cited.into_iter().collect(),
+        unknown: unknown.into_iter().collect(),
+        uncited_sections,
+    }
+}
+
+/// Append the bibliography as footnote definitions
+pub fn with_footnotes(report: &str, bib: &Bibliography) -> String {
+    if bib.entries.is_empty() {
+        return report.to_string();
+    }
+    let mut out = format!("{}\n\n## References\n\n", report.trim_end());
+    for entry in &bib.entries {
+        out.push_str(&format!(
+            "[^{}]: {}. <{}> (accessed {})\n",
+            entry.number,
+            entry.title.replace('\n', " "),
+            entry.url,
+            entry.accessed
+        ));
+    }
+    out
+}
+
+/// Write `report.md` and `bibliography.json` into a research run's folder
+pub fn store_report(run_dir: &Path, topic: &str, report: &str, bib: &Bibliography, check: &CitationCheck) -> std::io::Result<()> {
+    std::fs::create_dir_all(run_dir)?;
+    std::fs::write(run_dir.join("report.md"), report)?;
+    let bibliography = serde_json::json!({
+        "topic": topic,
+        "entries": bib.entries,
+        "citations": check,
+    });
+    std::fs::write(run_dir.join("bibliography.json"), serde_json::to_string_pretty(&bibliography)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bibliography() -> Bibliography {
+        let mut bib = Bibliography::default();
+        bib.add("The Rust Book", "https://doc.rust-lang.org/book/");
+        bib.add("Rustonomicon", "https://doc.rust-lang.org/nomicon/");
+        bib
+    }
+
+    /// A report citing [1, 2] in its first section and nothing in its second
+    fn normalized_report(bib: &Bibliography) -> String {
+        let filler = "word ".repeat(30);
+        let report = format!(
+            "<think>plan</think># Ownership\n\nValues have one owner [1, 2]. {filler}\n\n\
+             ## Borrowing\n\n{filler} See [the book][1] and [9].\n\n## Summary\n\nShort.\n\n\
+             ## References\n\n1. The Rust Book\n[^1]: old definition"
+        );
+        normalize_citations(&report, bib)
+    }
+
+    #[test]
+    fn test_add_reuses_a_known_url() {
+        assert_eq!(bibliography().add("Dup", "https://doc.rust-lang.org/book/"), 1);
+    }
+
+    #[test]
+    fn test_normalize_citations() {
+        let normalized = normalized_report(&bibliography());
+        assert!(normalized.starts_with("# Ownership\n\nValues have one owner [^1][^2]."));
+        assert!(normalized.contains("See [the book][1] and [9]."));
+        assert!(!normalized.contains("References") && !normalized.contains("old definition"));
+    }
+
+    #[test]
+    fn test_check_citations_finds_uncited_sections() {
+        let bib = bibliography();
+        let check = check_citations(&normalized_report(&bib), &bib);
+        assert_eq!(check.cited, vec![1, 2]);
+        assert_eq!(check.uncited_sections, vec!["Borrowing".to_string()]);
+    }
+
+    #[test]
+    fn test_with_footnotes_appends_the_references() {
+        let bib = bibliography();
+        let final_report = with_footnotes(&normalized_report(&bib), &bib);
+        assert!(final_report.contains("\n\n## References\n\n[^1]: The Rust Book. <https://doc.rust-lang.org/book/> (accessed "));
+        assert_eq!(check_citations(&final_report, &bib).uncited_sections, vec!["Borrowing".to_string()]);
+    }
+}
@@ -0,0 +1,232 @@
+/// Headless command line entry point
+///
+/// `thinkspace ask "..."`, `thinkspace research "..."` and `thinkspace harvest <url>` run the
+/// same agent, deep research and clipper code as the app, without opening a window, so
+/// recurring research and ingestion can be scripted (cron, CI, shell loops). Only the
+/// databases start; the file watcher and indexes are left to the app. Results land where the
+/// app puts them (reports in the knowledge base and research history, clips under `Clips/`).
+///
+/// Keys come from the environment: `MINIMAX_API_KEY`, `GROK_API_KEY`, `GEMINI_API_KEY` and
+/// `TAVILY_API_KEY`. The answer, report or note goes to stdout (the full result as JSON with
+/// `--json`) and logs go to stderr. The exit status is 0 on success, 1 when the job failed
+/// and 2 for a bad command line.
+
+use crate::minimax_enhanced::{AIProvider, MinimaxAgent};
+
+const MAX_ITERATIONS: usize = 20;
+
+const USAGE: &str = "Usage:
+  thinkspace ask <question> [--provider minimax|grok|gemini] [--model <model>] [--user <id>] [--json]
+  thinkspace research <topic> [--sub-topic <topic>]... [--provider <provider>] [--model <model>] [--user <id>] [--json]
+  thinkspace harvest <url> [--tag <tag>]... [--json]
+
+Keys are read from MINIMAX_API_KEY, GROK_API_KEY, GEMINI_API_KEY and TAVILY_API_KEY.";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Ask { question: String },
+    Research { topic: String, sub_topics: Vec<String> },
+    Harvest { url: String, tags: Vec<String> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    pub provider: Option<AIProvider>,
+    pub model: Option<String>,
+    pub user: Option<String>,
+    pub json: bool,
+}
+
+/// The invocation `args` (without the program name) asks for, or None when they don't start
+/// with a CLI command and the app should launch as usual
+pub fn parse(args: &[String]) -> Option<Result<Invocation, String>> {
+    let name = args.first()?.as_str();
+    if !matches!(name, "ask" | "research" | "harvest") {
+        return None;
+    }
+    Some(parse_command(name, &args[1..]))
+}
+
+fn parse_command(name: &str, args: &[String]) -> Result<Invocation, String> {
+    let mut words = Vec::new();
+    let mut repeated = Vec::new();
+    let mut invocation = Invocation { command: Command::Ask { question: String::new() }, provider: None, model: None, user: None, json: false };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--json" => invocation.json = true,
+            "--provider" => {
+                let provider = value()?;
+                invocation.provider = Some(
+                    serde_json::from_value(serde_json::Value::String(provider.to_lowercase()))
+                        .map_err(|_| format!("Unknown provider '{}'", provider))?,
+                );
+            }
+            "--model" => invocation.model = Some(value()?),
+            "--user" => invocation.user = Some(value()?),
+            "--sub-topic" if name == "research" => repeated.push(value()?),
+            "--tag" if name == "harvest" => repeated.push(value()?),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option '{}' for {}", flag, name)),
+            word => words.push(word.to_string()),
+        }
+    }
+
+    let text = words.join(" ");
+    if text.trim().is_empty() {
+        return Err(format!("{} needs {}", name, if name == "harvest" { "a URL" } else { "some text" }));
+    }
+    invocation.command = match name {
+        "ask" => Command::Ask { question: text },
+        "research" => Command::Research { topic: text, sub_topics: repeated },
+        _ => {
+            if words.len() > 1 || !(text.starts_with("http://") || text.starts_with("https://")) {
+                return Err(format!("harvest takes one http(s) URL, got '{}'", text));
+            }
+            Command::Harvest { url: text, tags: repeated }
+        }
+    };
+    Ok(invocation)
+}
+
+fn env_key(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|k| !k.trim().is_empty())
+}
+
+fn agent(invocation: &Invocation) -> Result<MinimaxAgent, String> {
+    let provider = invocation.provider.clone().unwrap_or(AIProvider::Minimax);
+    let minimax_key = env_key("MINIMAX_API_KEY");
+    let grok_key = env_key("GROK_API_KEY");
+    let gemini_key = env_key("GEMINI_API_KEY");
+    let (name, key) = match provider {
+        AIProvider::Minimax => ("MINIMAX_API_KEY", &minimax_key),
+        AIProvider::Grok => ("GROK_API_KEY", &grok_key),
+        AIProvider::Gemini => ("GEMINI_API_KEY", &gemini_key),
+    };
+    if key.is_none() {
+        return Err(format!("Set {} to use {:?}", name, provider));
+    }
+
+    let mut agent = MinimaxAgent::new(minimax_key.unwrap_or_default(), env_key("TAVILY_API_KEY"), grok_key, gemini_key)
+        .with_provider(provider)
+        .with_user_id(invocation.user.clone().unwrap_or_else(|| "cli".to_string()));
+    if let Some(model) = &invocation.model {
+        agent = agent.with_model(model.clone());
+    }
+    Ok(agent)
+}
+
+/// What to print: the readable text, and the whole result for --json
+async fn execute(invocation: &Invocation) -> Result<(String, serde_json::Value), String> {
+    match &invocation.command {
+        Command::Ask { question } => {
+            let mut agent = agent(invocation)?;
+            agent.add_user_message(question.clone());
+            let response = agent.chat(MAX_ITERATIONS).await?;
+            let json = serde_json::to_value(&response).map_err(|e| e.to_string())?;
+            Ok((response.content, json))
+        }
+        Command::Research { topic, sub_topics } => {
+            let result = agent(invocation)?.deep_research(topic.clone(), sub_topics.clone()).await;
+            if !result.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let error = result.get("error").and_then(|v| v.as_str()).unwrap_or("Research failed");
+                return Err(error.to_string());
+            }
+            let report = result.get("report").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            Ok((report, result))
+        }
+        Command::Harvest { url, tags } => {
+            let clip = crate::clipper::clip(None, url, false, tags).await?;
+            let json = serde_json::json!({ "success": true, "url": clip.url, "title": clip.title, "path": clip.path });
+            Ok((clip.path, json))
+        }
+    }
+}
+
+/// Run `invocation` to completion and return the process exit status
+pub fn run(invocation: Result<Invocation, String>) -> i32 {
+    let invocation = match invocation {
+        Ok(invocation) => invocation,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+    match crate::profiles::app_data_dir(None) {
+        Some(app_data) => {
+            if let Err(e) = std::fs::create_dir_all(&app_data) {
+                eprintln!("Failed to create {}: {}", app_data.display(), e);
+                return 1;
+            }
+            crate::startup::start_headless(&app_data);
+        }
+        None => {
+            eprintln!("Failed to get app data dir");
+            return 1;
+        }
+    }
+
+    tracing::info!("⌨️ CLI {:?}", invocation.command);
+    match tauri::async_runtime::block_on(execute(&invocation)) {
+        Ok((text, json)) => {
+            if invocation.json {
+                println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
+            } else {
+                println!("{}", text);
+            }
+            0
+        }
+        Err(e) => {
+            if invocation.json {
+                println!("{}", serde_json::json!({ "success": false, "error": e }));
+            }
+            eprintln!("❌ {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &[&str]) -> Vec<String> {
+        line.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_no_subcommand_starts_the_app() {
+        assert!(parse(&[]).is_none());
+        assert!(parse(&args(&["--devtools"])).is_none());
+    }
+
+    #[test]
+    fn test_parse_ask() {
+        let ask = parse(&args(&["ask", "What", "is", "a", "lifetime?", "--provider", "Grok", "--json"])).unwrap().unwrap();
+        assert_eq!(ask.command, Command::Ask { question: "What is a lifetime?".to_string() });
+        assert_eq!((ask.provider, ask.json), (Some(AIProvider::Grok), true));
+    }
+
+    #[test]
+    fn test_parse_research() {
+        let research = parse(&args(&["research", "Rust async", "--sub-topic", "tokio", "--sub-topic", "pinning", "--user", "sam"])).unwrap().unwrap();
+        assert_eq!(research.command, Command::Research { topic: "Rust async".to_string(), sub_topics: args(&["tokio", "pinning"]) });
+        assert_eq!(research.user.as_deref(), Some("sam"));
+    }
+
+    #[test]
+    fn test_parse_harvest() {
+        let harvest = parse(&args(&["harvest", "https://example.com/post", "--tag", "rust"])).unwrap().unwrap();
+        assert_eq!(harvest.command, Command::Harvest { url: "https://example.com/post".to_string(), tags: args(&["rust"]) });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&args(&["ask"])).unwrap().is_err());
+        assert!(parse(&args(&["ask", "hi", "--provider"])).unwrap().unwrap_err().contains("needs a value"));
+        assert!(parse(&args(&["ask", "hi", "--provider", "claude"])).unwrap().unwrap_err().contains("Unknown provider"));
+        assert!(parse(&args(&["ask", "hi", "--tag", "x"])).unwrap().unwrap_err().contains("Unknown option"));
+        assert!(parse(&args(&["harvest", "example.com"])).unwrap().is_err());
+    }
+}
@@ -0,0 +1,239 @@
+/// Opt-in clipboard watcher that offers to save what's copied
+///
+/// With `[clipboard] enabled = true` (or `set_clipboard_watch`), the clipboard is checked
+/// every `poll_ms`. A copied web address from a site on the `allow` list (any site when the
+/// list is empty), or copied text of at least `min_chars`, becomes an offer: it's emitted as
+/// `clipboard-offer` and kept until it's accepted or dismissed. Nothing is saved without
+/// that: accepting a link clips the page (see clipper.rs) and offers a note of it to the TKG;
+/// accepting text appends it to the capture inbox and hands it to WAMA (see capture.rs).
+/// What's on the clipboard when the watcher starts is never offered.
+
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, ClipboardManager, Manager};
+
+use crate::config::ClipboardConfig;
+
+/// Offers kept for the frontend; older ones are dropped
+const MAX_OFFERS: usize = 20;
+const PREVIEW_CHARS: usize = 200;
+const MIN_POLL_MS: u64 = 250;
+
+lazy_static::lazy_static! {
+    static ref OFFERS: Mutex<VecDeque<ClipboardOffer>> = Mutex::new(VecDeque::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OfferKind {
+    Url,
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardOffer {
+    pub id: String,
+    pub kind: OfferKind,
+    pub content: String,
+    pub preview: String,
+    /// The site a link points to
+    pub host: Option<String>,
+    pub at: String,
+}
+
+/// Whether `host` is `entry` or one of its subdomains; `*.` in front of an entry is optional
+fn host_allowed(host: &str, allow: &[String]) -> bool {
+    let host = host.to_lowercase();
+    allow.is_empty()
+        || allow.iter().any(|entry| {
+            let entry = entry.trim().trim_start_matches("*.").to_lowercase();
+            !entry.is_empty() && (host == entry || host.ends_with(&format!(".{}", entry)))
+        })
+}
+
+/// What `text` would be offered as, if anything
+fn classify(text: &str, config: &ClipboardConfig) -> Option<(OfferKind, Option<String>)> {
+    let trimmed = text.trim();
+    if !trimmed.contains(char::is_whitespace) {
+        if let Ok(url) = url::Url::parse(trimmed) {
+            if matches!(url.scheme(), "http" | "https") {
+                let host = url.host_str()?.to_string();
+                // A link from a site that isn't allowed isn't offered as text either
+                return host_allowed(&host, &config.allow).then_some((OfferKind::Url, Some(host)));
+            }
+        }
+    }
+    (config.long_text && trimmed.chars().count() >= config.min_chars).then_some((OfferKind::Text, None))
+}
+
+fn offer_for(text: &str, config: &ClipboardConfig) -> Option<ClipboardOffer> {
+    let (kind, host) = classify(text, config)?;
+    let content = text.trim().to_string();
+    let mut preview: String = content.chars().take(PREVIEW_CHARS).collect();
+    if preview.len() < content.len() {
+        preview.push('…');
+    }
+    Some(ClipboardOffer {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        content,
+        preview,
+        host,
+        at: crate::scheduler::stamp(chrono::Utc::now()),
+    })
+}
+
+fn fingerprint(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn push_offer(offer: ClipboardOffer) {
+    if let Ok(mut offers) = OFFERS.lock() {
+        // Copying the same thing again moves it to the front instead of repeating it
+        offers.retain(|o| o.content != offer.content);
+        offers.push_front(offer);
+        offers.truncate(MAX_OFFERS);
+    }
+}
+
+fn take_offer(id: &str) -> Option<ClipboardOffer> {
+    let mut offers = OFFERS.lock().ok()?;
+    let index = offers.iter().position(|o| o.id == id)?;
+    offers.remove(index)
+}
+
+/// Check the clipboard while the watcher is switched on
+pub fn start_background(app_handle: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last: Option<u64> = None;
+        loop {
+            let config = crate::config::current().clipboard.clone();
+            if !config.enabled {
+                last = None;
+            } else if let Ok(Some(text)) = app_handle.clipboard_manager().read_text() {
+                let seen = fingerprint(&text);
+                // The first read after switching on only notes what's already there
+                if last.map_or(false, |l| l != seen) {
+                    if let Some(offer) = offer_for(&text, &config) {
+                        tracing::info!("📋 Offering to save copied {:?}", offer.kind);
+                        let _ = app_handle.emit_all("clipboard-offer", &offer);
+                        push_offer(offer);
+                    }
+                }
+                last = Some(seen);
+            }
+            std::thread::sleep(Duration::from_millis(config.poll_ms.max(MIN_POLL_MS)));
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Offers not yet accepted or dismissed, newest first
+#[tauri::command]
+pub fn get_clipboard_offers() -> Vec<ClipboardOffer> {
+    OFFERS.lock().map(|offers| offers.iter().cloned().collect()).unwrap_or_default()
+}
+
+/// Switch the watcher on or off, saved to thinkspace.toml
+#[tauri::command]
+pub fn set_clipboard_watch(app_handle: AppHandle, enabled: bool) -> Result<bool, String> {
+    let config = crate::config::update(&app_handle, |config| config.clipboard.enabled = enabled)?;
+    if !config.clipboard.enabled {
+        if let Ok(mut offers) = OFFERS.lock() {
+            offers.clear();
+        }
+    }
+    Ok(config.clipboard.enabled)
+}
+
+/// Save an offer: a link is clipped, text goes to the capture inbox
+#[tauri::command]
+pub async fn accept_clipboard_offer(app_handle: AppHandle, id: String, user_id: Option<String>) -> Result<serde_json::Value, String> {
+    let offer = take_offer(&id).ok_or("That clipboard offer is gone; copy it again")?;
+    let user_id = user_id.unwrap_or_else(|| "guest".to_string());
+    let saved = match offer.kind {
+        OfferKind::Url => {
+            let clip = crate::clipper::clip(Some(&app_handle), &offer.content, false, &["clipboard".to_string()]).await;
+            match clip {
+                Ok(clip) => {
+                    let memory = format!("Clipped '{}' ({}) to {}", clip.title, clip.url, clip.path);
+                    let wama = match crate::tkg::tkg_store_knowledge(memory, "MEMORY".to_string(), 0.5, user_id).await {
+                        Ok(result) => serde_json::from_str(&result).unwrap_or(serde_json::Value::String(result)),
+                        // TKG not configured or WAMA rejected it - the clip is still saved
+                        Err(e) => serde_json::json!({ "success": false, "message": e }),
+                    };
+                    Ok(serde_json::json!({ "success": true, "path": clip.path, "title": clip.title, "wama": wama }))
+                }
+                Err(e) => Err(e),
+            }
+        }
+        OfferKind::Text => crate::capture::capture_text(&app_handle, &offer.content, &user_id, None).await,
+    };
+    // A failed save can be tried again
+    if saved.is_err() {
+        push_offer(offer);
+    }
+    saved
+}
+
+#[tauri::command]
+pub fn dismiss_clipboard_offer(id: String) -> bool {
+    take_offer(&id).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ClipboardConfig {
+        ClipboardConfig { enabled: true, allow: vec!["*.wikipedia.org".to_string(), "arxiv.org".to_string()], min_chars: 40, ..Default::default() }
+    }
+
+    #[test]
+    fn test_allowed_urls_are_offered() {
+        assert_eq!(classify(" https://en.wikipedia.org/wiki/Rust \n", &config()), Some((OfferKind::Url, Some("en.wikipedia.org".to_string()))));
+        assert_eq!(classify("https://arxiv.org/abs/1706.03762", &config()).map(|c| c.0), Some(OfferKind::Url));
+    }
+
+    #[test]
+    fn test_other_urls_and_short_text_are_not_offered() {
+        assert_eq!(classify("https://notarxiv.org/abs/1", &config()), None);
+        assert_eq!(classify("ftp://arxiv.org/file", &config()), None);
+        assert_eq!(classify("hunter2", &config()), None);
+    }
+
+    #[test]
+    fn test_long_text_is_offered_unless_switched_off() {
+        let long = "Spaced repetition works because retrieval strengthens memory traces.";
+        assert_eq!(classify(long, &config()), Some((OfferKind::Text, None)));
+        assert_eq!(classify(long, &ClipboardConfig { long_text: false, ..config() }), None);
+    }
+
+    #[test]
+    fn test_empty_allow_list_allows_any_host() {
+        assert!(host_allowed("example.com", &[]));
+    }
+
+    #[test]
+    fn test_offer_preview_is_cut() {
+        let offer = offer_for(&"x ".repeat(150), &config()).unwrap();
+        assert_eq!(offer.preview.chars().count(), PREVIEW_CHARS + 1);
+    }
+
+    #[test]
+    fn test_copying_again_replaces_the_offer() {
+        let offer = offer_for(&"x ".repeat(150), &config()).unwrap();
+        let id = offer.id.clone();
+        push_offer(offer);
+        push_offer(offer_for(&"x ".repeat(150), &config()).unwrap());
+        assert_eq!(get_clipboard_offers().len(), 1);
+        assert!(take_offer(&id).is_none());
+    }
+}
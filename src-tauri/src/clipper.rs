@@ -0,0 +1,161 @@
+/// Web clipper: save any web page as a clean markdown note
+///
+/// The page's main content is extracted (see web_extract) with headings, images and links
+/// kept, and written to `research/clips/<title>.md` with the source URL in frontmatter.
+/// Clipping the same page again (by canonical URL) updates its note; a different page with
+/// the same title gets a numbered file instead.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const CLIPS_FOLDER: &str = "research/clips";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Clip {
+    pub url: String,
+    pub title: String,
+    /// Note path relative to the knowledge base
+    pub path: String,
+    pub markdown: String,
+}
+
+/// The page's title, or its host when it has none
+fn clip_title(url: &str, title: Option<&str>) -> String {
+    title
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .or_else(|| url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)))
+        .unwrap_or_else(|| "Clipped page".to_string())
+}
+
+/// Where the clip of `url` goes: the note already clipped from the same page, else the first
+/// free `<slug>.md`, `<slug>-2.md`, ...
+pub fn clip_path(kb_root: &Path, title: &str, url: &str) -> PathBuf {
+    let folder = kb_root.join(CLIPS_FOLDER);
+    crate::harvests::note_path(&folder, &folder, &crate::research_notes::slugify(title), url)
+}
+
+/// The clip as a note tagged `clip` and `tags`, with harvest metadata so refresh_harvests
+/// can re-fetch it
+pub fn render_note(url: &str, title: &str, date: &str, markdown: &str, tags: &[String]) -> String {
+    let meta = crate::harvests::HarvestMeta {
+        kind: "clip".to_string(),
+        source: url.to_string(),
+        harvested: crate::scheduler::stamp(chrono::Utc::now()),
+        revision: None,
+        wiki_api: None,
+        wiki_title: None,
+        summary_only: None,
+    };
+    let body = if markdown.trim_start().starts_with("# ") {
+        markdown.trim().to_string()
+    } else {
+        format!("# {}\n\n{}", title, markdown.trim())
+    };
+    let mut all_tags = vec!["clip"];
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !all_tags.contains(&tag) {
+            all_tags.push(tag);
+        }
+    }
+    crate::harvests::render_note(title, date, &all_tags, &meta, &body)
+}
+
+/// Show the clip in the canvas split pane
+fn display(app_handle: &tauri::AppHandle, clip: &Clip) {
+    let mut warnings = Vec::new();
+    let (body, _) = crate::markdown_render::to_html(&clip.markdown, Path::new(""), &|_| None, &mut warnings);
+    let title = clip.title.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let html = format!(
+        r#"<!DOCTYPE html><html><head><style>
+        body {{ background: #09090b; color: #e4e4e7; font-family: 'Inter', system-ui, sans-serif; line-height: 1.6; margin: 0; padding: 2rem; }}
+        .container {{ max-width: 800px; margin: 0 auto; }}
+        .meta {{ color: #a1a1aa; font-size: 0.875rem; border-bottom: 1px solid rgba(255,255,255,0.1); padding-bottom: 1rem; margin-bottom: 1.5rem; }}
+        a {{ color: #8b5cf6; }} img {{ max-width: 100%; border-radius: 8px; }} pre {{ overflow-x: auto; background: #18181b; padding: 1rem; border-radius: 8px; }}
+        </style></head><body><div class="container"><h1>{}</h1>
+        <div class="meta">Clipped from <a href="{}">{}</a></div>{}</div></body></html>"#,
+        title, clip.url, clip.url, body
+    );
+    let _ = tauri::Manager::emit_all(app_handle, "canvas-split", serde_json::json!({
+        "code": html,
+        "type": "html",
+        "targetId": "main"
+    }));
+}
+
+/// Fetch `url`, save its main content as a note tagged with `tags` and optionally show it in
+/// the canvas
+pub async fn clip(app_handle: Option<&tauri::AppHandle>, url: &str, show: bool, tags: &[String]) -> Result<Clip, String> {
+    let page = crate::web_extract::fetch_page(url).await.map_err(|e| format!("{:#}", e))?;
+    if page.markdown.trim().is_empty() {
+        return Err(format!("No readable content found at {}", url));
+    }
+    let title = clip_title(url, page.title.as_deref());
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let path = clip_path(&kb_root, &title, url);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    crate::note_versions::record_before_write(app_handle, &kb_root, &path, "clip");
+    std::fs::write(&path, render_note(url, &title, &date, &page.markdown, tags)).map_err(|e| format!("Failed to save clip: {}", e))?;
+
+    let key = crate::kb_index::path_key(&kb_root, &path);
+    tracing::info!("✂️ Clipped {} to {}", url, key);
+    let clip = Clip { url: url.to_string(), title, path: key, markdown: page.markdown };
+    if let Some(handle) = app_handle {
+        crate::kb_index::apply_watch_changes(handle, &[path.clone()], None);
+        crate::events::emit(handle, "content-changed", serde_json::json!({
+            "source": kb_root.join(CLIPS_FOLDER),
+            "paths": [&clip.path],
+        }));
+        if show {
+            display(handle, &clip);
+        }
+    }
+    Ok(clip)
+}
+
+/// Save a web page as a markdown note under research/clips/
+#[tauri::command]
+pub async fn clip_url(app_handle: tauri::AppHandle, url: String, display: Option<bool>) -> Result<Clip, String> {
+    clip(Some(&app_handle), &url, display.unwrap_or(false), &[]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ownership_note() -> String {
+        render_note("https://example.com/post", "Ownership in Rust", "2026-01-01", "## Rules\n\n![diagram](https://example.com/a.png)", &[])
+    }
+
+    #[test]
+    fn test_clip_title_falls_back_to_the_host() {
+        assert_eq!(clip_title("https://example.com/post", Some("  ")), "example.com");
+    }
+
+    #[test]
+    fn test_render_note() {
+        let note = ownership_note();
+        let (fm, body) = crate::frontmatter::split_frontmatter(&note);
+        let fm = fm.expect("frontmatter should parse");
+        assert_eq!(fm.tags, vec!["clip".to_string()]);
+        assert_eq!(fm.extra["source"], serde_json::json!("https://example.com/post"));
+        assert!(body.trim_start().starts_with("# Ownership in Rust\n\n## Rules"));
+    }
+
+    #[test]
+    fn test_clip_path_replaces_only_the_same_page() {
+        let kb = tempfile::tempdir().unwrap();
+        let first = clip_path(kb.path(), "Ownership in Rust", "https://example.com/post");
+        assert!(first.ends_with("research/clips/ownership-in-rust.md"));
+        std::fs::create_dir_all(first.parent().unwrap()).unwrap();
+        std::fs::write(&first, ownership_note()).unwrap();
+        // Re-clipping the same page replaces its note; another page with that title doesn't
+        assert_eq!(clip_path(kb.path(), "Ownership in Rust", "https://example.com/post"), first);
+        assert!(clip_path(kb.path(), "Ownership in Rust", "https://other.org/").ends_with("ownership-in-rust-2.md"));
+    }
+}
@@ -0,0 +1,269 @@
+/// Ripgrep-style content search over a directory tree
+///
+/// Walks with the `ignore` crate (respecting .gitignore and hidden files) and
+/// matches line-by-line with the `grep` searcher, returning per-line hits with
+/// optional surrounding context.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use anyhow::{Result, Context};
+use grep::regex::RegexMatcherBuilder;
+use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkContextKind, SinkMatch};
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
+
+const MAX_FILE_SIZE: u64 = 2_000_000;
+const MAX_LINE_CHARS: usize = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepOptions {
+    pub pattern: String,
+    /// Glob filters in ripgrep `-g` syntax, e.g. "*.rs" or "!target/**"
+    #[serde(default)]
+    pub globs: Vec<String>,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Treat the pattern as a literal string instead of a regex
+    #[serde(default)]
+    pub fixed_strings: bool,
+    #[serde(default)]
+    pub context_lines: usize,
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+}
+
+fn default_max_results() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepMatch {
+    pub relative_path: String,
+    pub line_number: u64,
+    pub line: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrepResults {
+    pub matches: Vec<GrepMatch>,
+    pub files_searched: usize,
+    pub files_matched: usize,
+    pub truncated: bool,
+}
+
+/// Collects matches for one file, attaching before/after context lines to each hit
+struct MatchSink<'a> {
+    relative_path: String,
+    remaining: usize,
+    pending_before: Vec<String>,
+    matches: &'a mut Vec<GrepMatch>,
+    first_index: usize,
+    /// Set when a match turned up after `remaining` ran out
+    capped: bool,
+}
+
+impl<'a> Sink for MatchSink<'a> {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> std::result::Result<bool, Self::Error> {
+        if self.remaining == 0 {
+            self.capped = true;
+            return Ok(false);
+        }
+
+        self.matches.push(GrepMatch {
+            relative_path: self.relative_path.clone(),
+            line_number: mat.line_number().unwrap_or(0),
+            line: clean_line(mat.bytes()),
+            before: std::mem::take(&mut self.pending_before),
+            after: Vec::new(),
+        });
+        self.remaining -= 1;
+
+        Ok(true)
+    }
+
+    fn context(&mut self, _searcher: &Searcher, ctx: &SinkContext<'_>) -> std::result::Result<bool, Self::Error> {
+        let line = clean_line(ctx.bytes());
+        match ctx.kind() {
+            SinkContextKind::Before => self.pending_before.push(line),
+            SinkContextKind::After => {
+                // After-context belongs to the most recent match in this file
+                if self.matches.len() > self.first_index {
+                    if let Some(last) = self.matches.last_mut() {
+                        last.after.push(line);
+                    }
+                }
+            }
+            SinkContextKind::Other => {}
+        }
+        Ok(true)
+    }
+}
+
+fn clean_line(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let trimmed = text.trim_end_matches(['\r', '\n']);
+    if trimmed.chars().count() > MAX_LINE_CHARS {
+        format!("{}…", trimmed.chars().take(MAX_LINE_CHARS).collect::<String>())
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Search every non-ignored text file under `root` for `options.pattern`
+pub fn grep_directory(root: &Path, options: &GrepOptions) -> Result<GrepResults> {
+    if options.pattern.is_empty() {
+        anyhow::bail!("Search pattern must not be empty");
+    }
+
+    let pattern = if options.fixed_strings {
+        regex::escape(&options.pattern)
+    } else {
+        options.pattern.clone()
+    };
+
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(options.case_insensitive)
+        .build(&pattern)
+        .with_context(|| format!("Invalid search pattern: {}", options.pattern))?;
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.globs {
+        overrides.add(glob).with_context(|| format!("Invalid glob: {}", glob))?;
+    }
+    let overrides = overrides.build().context("Failed to build glob filters")?;
+
+    let mut searcher = SearcherBuilder::new()
+        .line_number(true)
+        .before_context(options.context_lines)
+        .after_context(options.context_lines)
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .build();
+
+    let walker = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .max_filesize(Some(MAX_FILE_SIZE))
+        .overrides(overrides)
+        .build();
+
+    let mut results = GrepResults::default();
+    let max_results = options.max_results.max(1);
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                tracing::warn!("⚠️ Skipping unreadable entry during search: {}", e);
+                continue;
+            }
+        };
+
+        if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+
+        if results.matches.len() >= max_results {
+            results.truncated = true;
+            break;
+        }
+
+        let path = entry.path();
+        let relative_path = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let before = results.matches.len();
+        let mut sink = MatchSink {
+            relative_path,
+            remaining: max_results - before,
+            pending_before: Vec::new(),
+            first_index: before,
+            matches: &mut results.matches,
+            capped: false,
+        };
+
+        if let Err(e) = searcher.search_path(&matcher, path, &mut sink) {
+            tracing::warn!("⚠️ Failed to search {}: {}", path.display(), e);
+            continue;
+        }
+        // The cap can run out inside the last file, with nothing left to walk
+        results.truncated |= sink.capped;
+
+        results.files_searched += 1;
+        if results.matches.len() > before {
+            results.files_matched += 1;
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(pattern: &str) -> GrepOptions {
+        GrepOptions {
+            pattern: pattern.to_string(),
+            globs: Vec::new(),
+            case_insensitive: false,
+            fixed_strings: false,
+            context_lines: 0,
+            max_results: default_max_results(),
+        }
+    }
+
+    #[test]
+    fn test_grep_with_context_and_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "// header\nfn chat() {}\n// footer\n").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "fn chat() in prose\n").unwrap();
+
+        let mut opts = options(r"fn \w+\(");
+        opts.globs = vec!["*.rs".to_string()];
+        opts.context_lines = 1;
+
+        let results = grep_directory(dir.path(), &opts).unwrap();
+        assert_eq!(results.matches.len(), 1);
+
+        let hit = &results.matches[0];
+        assert_eq!(hit.relative_path, "lib.rs");
+        assert_eq!(hit.line_number, 2);
+        assert_eq!(hit.before, vec!["// header".to_string()]);
+        assert_eq!(hit.after, vec!["// footer".to_string()]);
+    }
+
+    #[test]
+    fn test_grep_fixed_strings_and_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a.b\na.b\naxb\n").unwrap();
+
+        let mut opts = options("a.b");
+        opts.fixed_strings = true;
+        opts.max_results = 1;
+
+        let results = grep_directory(dir.path(), &opts).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert_eq!(results.matches[0].line, "a.b");
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn test_grep_not_truncated_when_every_match_fits() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a.b\naxb\n").unwrap();
+
+        let mut opts = options("a.b");
+        opts.fixed_strings = true;
+        opts.max_results = 1;
+
+        let results = grep_directory(dir.path(), &opts).unwrap();
+        assert_eq!(results.matches.len(), 1);
+        assert!(!results.truncated);
+    }
+}
@@ -0,0 +1,342 @@
+/// Codebase summarization pipeline
+///
+/// Groups indexed source files into modules (one per directory), summarizes each
+/// with the configured AI provider under a concurrency limit, then writes a
+/// hierarchical architecture overview to the knowledge base and stores the
+/// module summaries in the TKG for later retrieval.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use futures_util::stream::{self, StreamExt};
+
+use crate::ai_provider::{AIService, ChatContext};
+use crate::repo_indexer::{FileInfo, RepoIndex};
+use crate::repo_stats::language_for_extension;
+
+const MAX_FILE_CHARS: usize = 8_000;
+const MAX_MODULE_CHARS: usize = 40_000;
+const MAX_MODULES: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSummary {
+    pub module: String,   // Directory relative to the repo root ("." for the root)
+    pub files: Vec<String>,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub module: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodebaseSummaryReport {
+    pub repo_name: String,
+    pub modules_summarized: usize,
+    pub modules_failed: Vec<String>,
+    pub tkg_stored: usize,
+    pub output_path: String,
+}
+
+fn is_source_file(file: &FileInfo) -> bool {
+    file.is_text
+        && !matches!(
+            language_for_extension(file.extension.as_deref()),
+            "Other" | "Markdown" | "JSON" | "TOML" | "YAML"
+        )
+}
+
+/// Group source files by their containing directory
+pub fn group_modules(index: &RepoIndex) -> BTreeMap<String, Vec<&FileInfo>> {
+    let mut modules: BTreeMap<String, Vec<&FileInfo>> = BTreeMap::new();
+
+    for file in index.files.iter().filter(|f| is_source_file(f)) {
+        let module = Path::new(&file.relative_path)
+            .parent()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        modules.entry(module).or_default().push(file);
+    }
+
+    modules
+}
+
+/// Cut each file to MAX_FILE_CHARS and stop once the module has MAX_MODULE_CHARS. Both
+/// limits count chars, so multi-byte text gets the same room as ASCII.
+fn budget_contents(contents: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut file_contents = Vec::new();
+    let mut budget = MAX_MODULE_CHARS;
+
+    for (path, content) in contents {
+        if budget == 0 {
+            break;
+        }
+        let truncated: String = content.chars().take(MAX_FILE_CHARS.min(budget)).collect();
+        budget -= truncated.chars().count();
+        file_contents.push((path, truncated));
+    }
+
+    file_contents
+}
+
+fn module_context(files: &[&FileInfo]) -> ChatContext {
+    // Lazy, so files past the budget aren't read
+    let contents = files.iter().filter_map(|file| {
+        RepoIndex::read_file_content(&file.path).ok().map(|content| (file.relative_path.clone(), content))
+    });
+
+    ChatContext {
+        repo_files: files.iter().map(|f| f.relative_path.clone()).collect(),
+        file_contents: budget_contents(contents),
+    }
+}
+
+async fn summarize_module(ai: &AIService, module: &str, files: &[&FileInfo]) -> Result<String, String> {
+    let context = module_context(files);
+    let question = format!(
+        "Summarize the module `{}` in 3-6 sentences: its responsibility, the key types and functions, \
+         and how it interacts with other parts of the codebase. Be concrete and reference file names.",
+        module
+    );
+
+    ai.ask_question(&question, &context)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Run the full pipeline. `on_progress` is called after each module completes.
+pub async fn summarize_codebase<F>(
+    ai: AIService,
+    index: RepoIndex,
+    kb_root: PathBuf,
+    max_concurrency: usize,
+    user_id: String,
+    on_progress: F,
+) -> Result<CodebaseSummaryReport, String>
+where
+    F: Fn(SummarizeProgress),
+{
+    let repo_name = index.root_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repository".to_string());
+
+    let modules = group_modules(&index);
+    if modules.is_empty() {
+        return Err("No source files found in the indexed repository".to_string());
+    }
+
+    let total = modules.len().min(MAX_MODULES);
+    if modules.len() > MAX_MODULES {
+        tracing::warn!("⚠️ Summarizing the first {} of {} modules", MAX_MODULES, modules.len());
+    }
+
+    tracing::info!("🧭 Summarizing {} modules of {} (concurrency {})", total, repo_name, max_concurrency);
+
+    // Summarize modules concurrently, bounded by max_concurrency
+    let ai_ref = &ai;
+    let mut results = stream::iter(modules.into_iter().take(MAX_MODULES))
+        .map(|(module, files)| async move {
+            let summary = summarize_module(ai_ref, &module, &files).await;
+            let file_names = files.iter().map(|f| f.relative_path.clone()).collect::<Vec<_>>();
+            (module, file_names, summary)
+        })
+        .buffer_unordered(max_concurrency.max(1));
+
+    let mut summaries = Vec::new();
+    let mut failed = Vec::new();
+    let mut processed = 0;
+
+    while let Some((module, files, summary)) = results.next().await {
+        processed += 1;
+        on_progress(SummarizeProgress {
+            processed,
+            total,
+            module: module.clone(),
+        });
+
+        match summary {
+            Ok(summary) => summaries.push(ModuleSummary { module, files, summary }),
+            Err(e) => {
+                tracing::error!("❌ Failed to summarize {}: {}", module, e);
+                failed.push(module);
+            }
+        }
+    }
+    drop(results);
+
+    summaries.sort_by(|a, b| a.module.cmp(&b.module));
+
+    // Top-level overview built from the module summaries rather than raw code
+    let overview = {
+        let digest: Vec<(String, String)> = summaries
+            .iter()
+            .map(|s| (s.module.clone(), s.summary.clone()))
+            .collect();
+        let context = ChatContext {
+            repo_files: summaries.iter().map(|s| s.module.clone()).collect(),
+            file_contents: digest,
+        };
+        ai.ask_question(
+            "The 'files' above are per-module summaries of one repository. Write a concise architecture \
+             overview: the main subsystems, how data flows between them, and where a new contributor should start.",
+            &context,
+        )
+        .await
+        .unwrap_or_else(|e| format!("_Overview unavailable: {}_", e))
+    };
+
+    let markdown = render_markdown(&repo_name, &overview, &summaries);
+    let output_dir = kb_root.join("developer-reference").join("codebase-summaries");
+    std::fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
+    let output_path = output_dir.join(format!("{}.md", slugify(&repo_name)));
+    std::fs::write(&output_path, markdown).map_err(|e| e.to_string())?;
+
+    // Module summaries go to the TKG so the agent can retrieve them semantically
+    let mut tkg_stored = 0;
+    for summary in &summaries {
+        let content = format!(
+            "Codebase module summary — {} / {}\n\n{}",
+            repo_name, summary.module, summary.summary
+        );
+        match crate::tkg::tkg_store_knowledge(content, "CONCEPT".to_string(), 0.7, user_id.clone()).await {
+            Ok(response) => {
+                // WAMA may still decline to save low-value content
+                let saved = serde_json::from_str::<serde_json::Value>(&response)
+                    .ok()
+                    .and_then(|v| v.get("success").and_then(|s| s.as_bool()))
+                    .unwrap_or(false);
+                if saved {
+                    tkg_stored += 1;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ Skipping TKG storage for module summaries: {}", e);
+                break;
+            }
+        }
+    }
+
+    Ok(CodebaseSummaryReport {
+        repo_name,
+        modules_summarized: summaries.len(),
+        modules_failed: failed,
+        tkg_stored,
+        output_path: output_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Render the hierarchy: overview, then modules grouped under their top-level directory
+fn render_markdown(repo_name: &str, overview: &str, summaries: &[ModuleSummary]) -> String {
+    let mut md = format!(
+        "# {} — Architecture Summary\n\n_Generated {}_\n\n## Overview\n\n{}\n\n",
+        repo_name,
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        overview.trim()
+    );
+
+    let mut by_top_level: BTreeMap<&str, Vec<&ModuleSummary>> = BTreeMap::new();
+    for summary in summaries {
+        let top = summary.module.split('/').next().unwrap_or(".");
+        by_top_level.entry(top).or_default().push(summary);
+    }
+
+    for (top, modules) in by_top_level {
+        md.push_str(&format!("## `{}`\n\n", top));
+        for module in modules {
+            md.push_str(&format!("### `{}`\n\n{}\n\n", module.module, module.summary.trim()));
+            md.push_str(&format!("_Files: {}_\n\n", module.files.join(", ")));
+        }
+    }
+
+    md
+}
+
+fn slugify(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(relative_path: &str, is_text: bool) -> FileInfo {
+        FileInfo {
+            path: PathBuf::from("/repo").join(relative_path),
+            relative_path: relative_path.to_string(),
+            extension: Path::new(relative_path).extension().map(|e| e.to_string_lossy().to_string()),
+            size: 10,
+            is_text,
+            modified: 0,
+            hash: None,
+        }
+    }
+
+    #[test]
+    fn test_group_modules_by_directory() {
+        let mut index = RepoIndex::new(PathBuf::from("/repo"));
+        index.files = vec![
+            file("main.rs", true),
+            file("src/db.rs", true),
+            file("src/net.rs", true),
+            file("src/README.md", true),
+            file("assets/logo.rs", false),
+        ];
+
+        let modules = group_modules(&index);
+        assert_eq!(modules.keys().collect::<Vec<_>>(), vec![".", "src"]);
+        let src: Vec<&str> = modules["src"].iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(src, vec!["src/db.rs", "src/net.rs"]);
+    }
+
+    #[test]
+    fn test_budget_cuts_files_and_stops_at_the_module_limit() {
+        let files = (0..10).map(|i| (format!("f{}.rs", i), "x".repeat(MAX_FILE_CHARS + 1)));
+        let kept = budget_contents(files);
+        assert_eq!(kept.len(), MAX_MODULE_CHARS / MAX_FILE_CHARS);
+        assert!(kept.iter().all(|(_, content)| content.len() == MAX_FILE_CHARS));
+
+        // The last file gets whatever is left
+        let mut files: Vec<(String, String)> = (0..5).map(|i| (format!("f{}.rs", i), "x".repeat(MAX_MODULE_CHARS / 5 - 20))).collect();
+        files.push(("last.rs".to_string(), "y".repeat(500)));
+        let kept = budget_contents(files);
+        assert_eq!(kept[5].1.len(), 100);
+    }
+
+    #[test]
+    fn test_budget_counts_chars_not_bytes() {
+        let files = (0..10).map(|i| (format!("f{}.rs", i), "é".repeat(MAX_FILE_CHARS)));
+        let kept = budget_contents(files);
+        assert_eq!(kept.len(), MAX_MODULE_CHARS / MAX_FILE_CHARS);
+        assert!(kept.iter().all(|(_, content)| content.chars().count() == MAX_FILE_CHARS));
+    }
+
+    #[test]
+    fn test_render_groups_modules_under_top_level() {
+        let summary = |module: &str, text: &str| ModuleSummary {
+            module: module.to_string(),
+            files: vec![format!("{}/lib.rs", module)],
+            summary: format!("{}\n", text),
+        };
+        let md = render_markdown("ThinkSpace", " Tauri app. ", &[summary("src/db", "Storage."), summary("src", "Entry."), summary("ui", "Views.")]);
+
+        assert!(md.starts_with("# ThinkSpace — Architecture Summary\n\n_Generated "));
+        assert!(md.contains("## Overview\n\nTauri app.\n\n"));
+        assert!(md.contains("## `src`\n\n### `src/db`\n\nStorage.\n\n_Files: src/db/lib.rs_\n\n### `src`\n\nEntry."));
+        assert!(md.find("## `src`").unwrap() < md.find("## `ui`").unwrap());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("ThinkSpace"), "thinkspace");
+        assert_eq!(slugify("my repo (v2)"), "my-repo--v2");
+    }
+}
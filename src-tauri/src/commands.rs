@@ -3,9 +3,14 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{Manager, State};
 
-use crate::repo_indexer::{RepoIndex, FileInfo};
+use crate::repo_indexer::{RepoIndex, FileInfo, IndexDelta};
+use crate::symbols::{self, Symbol};
+use crate::code_search::{self, GrepOptions, GrepResults};
+use crate::file_watcher;
+use crate::repo_stats::{self, RepoStats};
+use crate::codebase_summary::{self, CodebaseSummaryReport};
 use crate::ai_provider::{AIService, AIProvider, ChatContext, select_relevant_files};
 
 pub mod orchestrate_agents;
@@ -14,6 +19,12 @@ pub mod orchestrate_agents;
 pub struct AppState {
     pub repo_index: Mutex<Option<RepoIndex>>,
     pub ai_service: Mutex<Option<AIService>>,
+    /// Tool permission scopes granted per conversation, by session name
+    pub tool_grants: Mutex<std::collections::HashMap<String, crate::permissions::ToolGrants>>,
+    /// Span timings recorded by the perf layer
+    pub perf: std::sync::Arc<crate::perf::PerfStats>,
+    /// Live agent state of each chat session, by session name
+    pub agent_sessions: crate::agent_sessions::AgentSessions,
 }
 
 impl AppState {
@@ -21,6 +32,9 @@ impl AppState {
         Self {
             repo_index: Mutex::new(None),
             ai_service: Mutex::new(None),
+            tool_grants: Mutex::new(std::collections::HashMap::new()),
+            perf: crate::perf::stats(),
+            agent_sessions: crate::agent_sessions::AgentSessions::default(),
         }
     }
 }
@@ -30,6 +44,7 @@ pub struct IndexProgress {
     pub total_files: usize,
     pub total_size: u64,
     pub indexed: bool,
+    pub delta: IndexDelta,
 }
 
 // Initialize AI provider with API key
@@ -52,6 +67,7 @@ pub async fn init_ai_provider(
 // Index a repository directory
 #[tauri::command]
 pub async fn index_repository(
+    app_handle: tauri::AppHandle,
     repo_path: String,
     state: State<'_, AppState>,
 ) -> Result<IndexProgress, String> {
@@ -61,14 +77,37 @@ pub async fn index_repository(
         return Err(format!("Invalid repository path: {}", repo_path));
     }
 
-    // Index the repository
-    let index = RepoIndex::index_directory(&path)
-        .map_err(|e| format!("Failed to index repository: {}", e))?;
+    let db_path = RepoIndex::index_db_path(Some(&app_handle))
+        .ok_or("Failed to get app data dir")?;
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    // Index the repository, re-hashing only files that changed since the last run
+    let emitter = app_handle.clone();
+    let root = path.clone();
+    let (index, delta) = crate::db::blocking(move || {
+        let conn = RepoIndex::open_index_db(&db_path)
+            .map_err(|e| format!("Failed to open index database: {}", e))?;
+        RepoIndex::index_directory_incremental(&root, &conn, move |event| {
+            let _ = emitter.emit_all("index-progress", event);
+        })
+        .map_err(|e| format!("Failed to index repository: {}", e))
+    })
+    .await?;
+
+    tracing::info!("📇 Indexed {}: {:?}", repo_path, delta);
+
+    // Invalidate persisted entries as files change from now on
+    if let Err(e) = file_watcher::watch_repository(&app_handle, &path) {
+        tracing::warn!("⚠️ Failed to watch repository {}: {}", repo_path, e);
+    }
 
     let progress = IndexProgress {
         total_files: index.total_files,
         total_size: index.total_size,
         indexed: true,
+        delta,
     };
 
     // Store the index in state
@@ -78,6 +117,65 @@ pub async fn index_repository(
     Ok(progress)
 }
 
+// Drop the persisted index for a repository (defaults to the current one)
+#[tauri::command]
+pub async fn invalidate_index(
+    app_handle: tauri::AppHandle,
+    repo_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let current_root = state.repo_index.lock().unwrap()
+        .as_ref()
+        .map(|index| index.root_path.clone());
+
+    let root = match repo_path {
+        Some(p) => PathBuf::from(p),
+        None => current_root.clone().ok_or("No repository indexed")?,
+    };
+
+    if let Some(db_path) = RepoIndex::index_db_path(Some(&app_handle)).filter(|p| p.exists()) {
+        let root = root.clone();
+        crate::db::blocking(move || {
+            let conn = RepoIndex::open_index_db(&db_path)
+                .map_err(|e| format!("Failed to open index database: {}", e))?;
+            RepoIndex::invalidate(&conn, &root).map_err(|e| e.to_string())
+        })
+        .await?;
+    }
+
+    if current_root.as_deref() == Some(root.as_path()) {
+        file_watcher::unwatch_repository(&app_handle);
+        *state.repo_index.lock().unwrap() = None;
+    }
+
+    tracing::info!("🗑️ Invalidated index for {:?}", root);
+    Ok(())
+}
+
+/// Load the most recently indexed repository from disk at startup
+pub fn restore_repo_index(app_handle: &tauri::AppHandle) {
+    let db_path = match RepoIndex::index_db_path(Some(app_handle)) {
+        Some(p) if p.exists() => p,
+        _ => return,
+    };
+
+    let loaded = RepoIndex::open_index_db(&db_path)
+        .and_then(|conn| RepoIndex::load_persisted(&conn, None));
+
+    match loaded {
+        Ok(Some(index)) => {
+            tracing::info!("📇 Restored index for {:?} ({} files)", index.root_path, index.total_files);
+            if let Err(e) = file_watcher::watch_repository(app_handle, &index.root_path) {
+                tracing::warn!("⚠️ Failed to watch repository: {}", e);
+            }
+            let state = app_handle.state::<AppState>();
+            *state.repo_index.lock().unwrap() = Some(index);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("⚠️ Failed to restore repository index: {}", e),
+    }
+}
+
 // Get the list of files in the current repository
 #[tauri::command]
 pub async fn get_repo_files(
@@ -111,6 +209,114 @@ pub async fn search_files(
     }
 }
 
+// Find where a function, struct or class is defined (e.g. "MinimaxAgent::chat")
+#[tauri::command]
+pub async fn find_symbol(
+    app_handle: tauri::AppHandle,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SymbolMatch>, String> {
+    // Scope to the currently indexed repository when there is one
+    let repo_root = state.repo_index.lock().unwrap()
+        .as_ref()
+        .map(|index| index.root_path.to_string_lossy().to_string());
+
+    let db_path = RepoIndex::index_db_path(Some(&app_handle))
+        .ok_or("Failed to get app data dir")?;
+    if !db_path.exists() {
+        return Err("No repository indexed".to_string());
+    }
+
+    let conn = RepoIndex::open_index_db(&db_path)
+        .map_err(|e| format!("Failed to open index database: {}", e))?;
+
+    let matches = symbols::find_symbols(&conn, &name, repo_root.as_deref(), 50)
+        .map_err(|e| e.to_string())?;
+
+    Ok(matches
+        .into_iter()
+        .map(|(repo_root, symbol)| SymbolMatch { repo_root, symbol })
+        .collect())
+}
+
+// Summarize every module of the indexed repository with the configured provider
+#[tauri::command]
+pub async fn summarize_codebase(
+    app_handle: tauri::AppHandle,
+    max_concurrency: Option<usize>,
+    user_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CodebaseSummaryReport, String> {
+    let index = state.repo_index.lock().unwrap()
+        .clone()
+        .ok_or("No repository indexed")?;
+
+    let ai_service = state.ai_service.lock().unwrap()
+        .clone()
+        .ok_or("AI provider not initialized. Please set up your API key first.")?;
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+
+    let emitter = app_handle.clone();
+    let report = codebase_summary::summarize_codebase(
+        ai_service,
+        index,
+        kb_root,
+        max_concurrency.unwrap_or(4),
+        user_id.unwrap_or_else(|| "guest".to_string()),
+        move |progress| {
+            let _ = emitter.emit_all("summarize-progress", progress);
+        },
+    )
+    .await?;
+
+    tracing::info!("🧭 Codebase summary written to {}", report.output_path);
+    crate::events::emit(&app_handle, "content-changed", ());
+
+    Ok(report)
+}
+
+// Language, LOC and hot-spot overview of the indexed repository
+#[tauri::command]
+pub async fn get_repo_stats(
+    top_n: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<RepoStats, String> {
+    let index = state.repo_index.lock().unwrap()
+        .clone()
+        .ok_or("No repository indexed")?;
+
+    // Counting lines reads every text file; keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || repo_stats::compute_repo_stats(&index, top_n.unwrap_or(10)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Regex search over the contents of the indexed repository
+#[tauri::command]
+pub async fn grep_codebase(
+    options: GrepOptions,
+    state: State<'_, AppState>,
+) -> Result<GrepResults, String> {
+    let root = state.repo_index.lock().unwrap()
+        .as_ref()
+        .map(|index| index.root_path.clone())
+        .ok_or("No repository indexed")?;
+
+    // Searching a large repo is blocking file IO; keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || code_search::grep_directory(&root, &options))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolMatch {
+    pub repo_root: String,
+    #[serde(flatten)]
+    pub symbol: Symbol,
+}
+
 // Read file content
 #[tauri::command]
 pub async fn read_file(
@@ -1,6 +1,21 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use regex::Regex;
+use tauri::Manager;
+use crate::agent_chains::{ChainDefinition, ChainRunRecord, ChainRunStep, ChainRunSummary, ChainStep, Condition};
+use crate::consensus::{Ballot, ConsensusMode};
+use crate::deep_research::estimate_tokens;
+use crate::minimax_enhanced::{AIProvider, MinimaxAgent};
+use crate::reflection::Reflection;
+use crate::run_limits::{LimitHit, RunBudget, RunLimits, RunUsage};
+
+lazy_static::lazy_static! {
+    static ref THINK: Regex = Regex::new(r"(?s)<think>.*?</think>").unwrap();
+    /// Chain runs waiting at a pause step, by run id, for resume_chain's input
+    static ref PAUSED_RUNS: Mutex<HashMap<String, tokio::sync::oneshot::Sender<String>>> = Mutex::new(HashMap::new());
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrchestrateAgentRequest {
@@ -8,9 +23,20 @@ pub struct OrchestrateAgentRequest {
     pub task: String,
     pub api_key: String,
     pub context: Option<HashMap<String, serde_json::Value>>,
+    /// Provider `api_key` belongs to: "minimax" (default), "grok" or "gemini"
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Keys for agents whose preferredProvider differs from `provider`
+    #[serde(default)]
+    pub grok_api_key: Option<String>,
+    #[serde(default)]
+    pub gemini_api_key: Option<String>,
+    /// Caps on tokens, agent calls and time; the run stops early when one is reached
+    #[serde(default)]
+    pub limits: RunLimits,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentExecution {
     pub agent_id: String,
     pub agent_name: String,
@@ -18,6 +44,13 @@ pub struct AgentExecution {
     pub content: String,
     pub error: Option<String>,
     pub timestamp: u64,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub estimated_tokens: usize,
+    /// The draft and critique, when the step reflected before producing `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<Reflection>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,41 +59,506 @@ pub struct OrchestrateAgentResponse {
     pub chain_name: String,
     pub executions: Vec<AgentExecution>,
     pub total_duration_ms: u64,
+    /// Execution record id, for get_chain_run
+    #[serde(default)]
+    pub run_id: String,
+    /// "complete", "incomplete" (a parallel branch failed but the chain finished),
+    /// "failed" (the chain stopped at a failed step) or "limited" (a run limit stopped it;
+    /// `executions` holds the partial results)
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub total_tokens: usize,
+    #[serde(default)]
+    pub usage: RunUsage,
+}
+
+/// API keys an orchestration can use, so each agent can run on its preferred provider
+#[derive(Debug, Clone, Default)]
+pub struct ProviderKeys {
+    pub minimax: Option<String>,
+    pub grok: Option<String>,
+    pub gemini: Option<String>,
+    pub tavily: Option<String>,
+}
+
+pub fn parse_provider(name: Option<&str>) -> AIProvider {
+    match name {
+        Some("grok") => AIProvider::Grok,
+        Some("gemini") => AIProvider::Gemini,
+        _ => AIProvider::Minimax,
+    }
+}
+
+impl ProviderKeys {
+    /// `api_key` belongs to `provider`; the other keys are optional extras
+    pub fn new(provider: &AIProvider, api_key: &str, grok: Option<String>, gemini: Option<String>, tavily: Option<String>) -> Self {
+        let mut keys = Self { minimax: None, grok, gemini, tavily };
+        let key = Some(api_key.to_string()).filter(|k| !k.is_empty());
+        match provider {
+            AIProvider::Minimax => keys.minimax = key,
+            AIProvider::Grok => keys.grok = key.or(keys.grok),
+            AIProvider::Gemini => keys.gemini = key.or(keys.gemini),
+        }
+        keys
+    }
+
+    fn has_key(&self, provider: &AIProvider) -> bool {
+        match provider {
+            AIProvider::Minimax => self.minimax.is_some(),
+            AIProvider::Grok => self.grok.is_some(),
+            AIProvider::Gemini => self.gemini.is_some(),
+        }
+    }
+
+    /// The preferred provider when there's a key for it, otherwise `fallback`
+    pub fn pick(&self, preferred: Option<&str>, fallback: &AIProvider) -> AIProvider {
+        match preferred.map(|p| parse_provider(Some(p))) {
+            Some(provider) if self.has_key(&provider) => provider,
+            _ => fallback.clone(),
+        }
+    }
+
+    /// The key for the agent's primary slot on `provider`; Grok is OpenAI-compatible and
+    /// uses it, Gemini has its own
+    pub fn primary(&self, provider: &AIProvider) -> Option<String> {
+        match provider {
+            AIProvider::Grok => self.grok.clone(),
+            AIProvider::Gemini => None,
+            AIProvider::Minimax => self.minimax.clone(),
+        }
+    }
+
+    /// A tool-less agent on `provider` with `system_prompt`
+    pub fn agent(&self, provider: AIProvider, system_prompt: String) -> MinimaxAgent {
+        MinimaxAgent::new(self.primary(&provider).unwrap_or_default(), self.tavily.clone(), self.grok.clone(), self.gemini.clone())
+            .with_provider(provider)
+            .with_enabled_tools(HashMap::new())
+            // After with_provider, which swaps in the Grok persona
+            .with_system_prompt(system_prompt)
+    }
+}
+
+/// An agent from the registry (agents.json)
+#[derive(Debug, Clone)]
+pub struct RegisteredAgent {
+    pub id: String,
+    pub name: String,
+    pub system_prompt: String,
+    pub preferred_provider: Option<String>,
+}
+
+pub fn load_registered_agents(app_handle: Option<&tauri::AppHandle>) -> Result<Vec<RegisteredAgent>, String> {
+    let mut loader = MinimaxAgent::new(String::new(), None, None, None);
+    if let Some(handle) = app_handle {
+        loader = loader.with_app_handle(handle.clone());
+    }
+    let registry = loader.load_agents_registry()?;
+    let agents = registry.get("agents").and_then(|a| a.as_array()).ok_or("No agents array in registry")?;
+    Ok(agents
+        .iter()
+        .filter_map(|a| {
+            let id = a.get("id")?.as_str()?.to_string();
+            let text = |key: &str| a.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+            Some(RegisteredAgent {
+                name: text("name").unwrap_or_else(|| id.clone()),
+                system_prompt: text("systemPrompt").unwrap_or_else(|| "You are a helpful assistant.".to_string()),
+                preferred_provider: text("preferredProvider"),
+                id,
+            })
+        })
+        .collect())
+}
+
+fn find_agent<'a>(agents: &'a [RegisteredAgent], id: &str) -> Result<&'a RegisteredAgent, String> {
+    agents.iter().find(|a| a.id == id).ok_or_else(|| format!("Agent '{}' not found in registry", id))
+}
+
+/// The registered agent that critiques reflected outputs, or the built-in critic
+pub fn find_critic(agents: &[RegisteredAgent], critic_id: Option<&str>) -> Result<RegisteredAgent, String> {
+    match critic_id {
+        Some(id) => find_agent(agents, id).cloned(),
+        None => Ok(RegisteredAgent {
+            id: "critic".to_string(),
+            name: "Critic".to_string(),
+            system_prompt: crate::reflection::CRITIC_PROMPT.to_string(),
+            preferred_provider: None,
+        }),
+    }
+}
+
+/// Check every agent a chain uses exists, parallel branches aren't nested and the
+/// chain's conditions and loops make sense
+fn validate_steps(steps: &[ChainStep], agents: &[RegisteredAgent]) -> Result<(), String> {
+    crate::agent_chains::validate_flow(steps)?;
+    for step in steps {
+        if step.parallel.iter().any(|branch| !branch.parallel.is_empty()) {
+            return Err(format!("Parallel branches of '{}' can't have branches of their own", step.agent_id));
+        }
+        for id in step.agent_ids() {
+            find_agent(agents, id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Input for a chain step: the task, step instructions, caller context and the latest
+/// output of each earlier step
+fn step_input(task: &str, step: &ChainStep, context: Option<&HashMap<String, serde_json::Value>>, previous: &[(usize, AgentExecution)]) -> String {
+    let mut prompt = format!("Task: {}\n\n", task);
+    if let Some(instructions) = &step.instructions {
+        prompt.push_str(&format!("Instructions: {}\n\n", instructions));
+    }
+    // API keys passed in context are for the orchestrator, not the agents
+    let context: Vec<_> = context
+        .into_iter()
+        .flatten()
+        .filter(|(key, _)| !key.ends_with("api_key"))
+        .collect();
+    if !context.is_empty() {
+        prompt.push_str("Context:\n");
+        for (key, value) in context {
+            prompt.push_str(&format!("- {}: {}\n", key, value));
+        }
+        prompt.push('\n');
+    }
+    if !previous.is_empty() {
+        prompt.push_str("Previous Agent Outputs:\n");
+        for (_, output) in previous {
+            prompt.push_str(&format!("\n=== {} ===\n{}\n", output.agent_name, output.content));
+        }
+    }
+    prompt.trim_end().to_string()
+}
+
+/// Input for the reducer of a parallel step: the branches' outputs to merge
+fn reducer_input(task: &str, step: &ChainStep, branches: &[AgentExecution]) -> String {
+    let mut prompt = format!(
+        "Task: {}\n\n{} agents worked on this task in parallel. Merge their outputs into one result, \
+         keeping what each does best and resolving contradictions.\n\n",
+        task,
+        branches.len()
+    );
+    if let Some(instructions) = &step.instructions {
+        prompt.push_str(&format!("Instructions: {}\n\n", instructions));
+    }
+    prompt.push_str("Outputs to merge:\n");
+    for branch in branches {
+        prompt.push_str(&format!("\n=== {} ===\n{}\n", branch.agent_name, branch.content));
+    }
+    prompt.trim_end().to_string()
+}
+
+/// Evaluate a pipeline condition against the latest step outputs. A condition that names
+/// no step applies to `current`, or to the latest output when that's None.
+fn condition_holds(steps: &[ChainStep], condition: &str, current: Option<usize>, outputs: &[(usize, AgentExecution)]) -> bool {
+    let condition = match Condition::parse(condition) {
+        Ok(condition) => condition,
+        Err(e) => {
+            tracing::warn!("⚠️ {}", e);
+            return false;
+        }
+    };
+    let output = match (&condition.step, current) {
+        (Some(id), _) => crate::agent_chains::step_index(steps, id).and_then(|i| outputs.iter().find(|(step, _)| *step == i)),
+        (None, Some(i)) => outputs.iter().find(|(step, _)| *step == i),
+        (None, None) => outputs.last(),
+    };
+    output.map_or(false, |(_, execution)| condition.holds(&execution.content))
+}
+
+fn failed_execution(agent_id: String, agent_name: String, error: String) -> AgentExecution {
+    AgentExecution {
+        agent_id,
+        agent_name,
+        success: false,
+        content: String::new(),
+        error: Some(error),
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        duration_ms: 0,
+        estimated_tokens: 0,
+        reflection: None,
+    }
+}
+
+/// Run one agent on `input`, timing it and estimating its tokens. The agent isn't called
+/// once the run has reached a limit, and is cut off when the time limit runs out.
+async fn execute_agent(
+    mut agent: MinimaxAgent,
+    agent_id: &str,
+    agent_name: &str,
+    system_prompt: &str,
+    input: String,
+    budget: &RunBudget,
+) -> (AgentExecution, String) {
+    if let Err(hit) = budget.try_call() {
+        tracing::info!("🛑 {} not run: {}", agent_name, hit.message());
+        return (failed_execution(agent_id.to_string(), agent_name.to_string(), hit.message().to_string()), input);
+    }
+    let started = std::time::Instant::now();
+    let result = budget
+        .within_time(agent.run_autonomous_task(input.clone()))
+        .await
+        .unwrap_or_else(|| Err(LimitHit::Duration.message().to_string()));
+    let (content, error) = match result {
+        Ok(output) => (THINK.replace_all(&output, "").trim().to_string(), None),
+        Err(e) => (String::new(), Some(e)),
+    };
+    let execution = AgentExecution {
+        agent_id: agent_id.to_string(),
+        agent_name: agent_name.to_string(),
+        success: error.is_none(),
+        estimated_tokens: estimate_tokens(system_prompt) + estimate_tokens(&input) + estimate_tokens(&content),
+        content,
+        error,
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        duration_ms: started.elapsed().as_millis() as u64,
+        reflection: None,
+    };
+    budget.record_tokens(execution.estimated_tokens);
+    (execution, input)
+}
+
+/// A reflected step's final execution: the revision if it succeeded, otherwise the draft,
+/// carrying the draft and critique and the tokens and time of all of them
+fn reflected(draft: AgentExecution, critique: AgentExecution, revision: Option<AgentExecution>) -> AgentExecution {
+    let estimated_tokens = draft.estimated_tokens + critique.estimated_tokens + revision.as_ref().map_or(0, |r| r.estimated_tokens);
+    let duration_ms = draft.duration_ms + critique.duration_ms + revision.as_ref().map_or(0, |r| r.duration_ms);
+    if !critique.success {
+        tracing::warn!("⚠️ Critique failed, keeping the draft: {}", critique.error.as_deref().unwrap_or(""));
+        return AgentExecution { estimated_tokens, duration_ms, ..draft };
+    }
+    let revision = revision.filter(|r| {
+        if !r.success {
+            tracing::warn!("⚠️ Revision failed, keeping the draft: {}", r.error.as_deref().unwrap_or(""));
+        }
+        r.success
+    });
+    let revised = revision.is_some();
+    let result = revision.unwrap_or_else(|| draft.clone());
+    AgentExecution {
+        estimated_tokens,
+        duration_ms,
+        reflection: Some(Reflection { draft: draft.content, critique: critique.content, revised }),
+        ..result
+    }
+}
+
+/// Run registered agents concurrently, each on its own input, the way deep research runs
+/// sub-topics; results come back in job order
+async fn run_concurrently(
+    keys: &ProviderKeys,
+    provider: &AIProvider,
+    jobs: Vec<(RegisteredAgent, String)>,
+    budget: &RunBudget,
+) -> Vec<(AgentExecution, String)> {
+    let handles: Vec<_> = jobs
+        .into_iter()
+        .map(|(registered, input)| {
+            let agent = keys.agent(keys.pick(registered.preferred_provider.as_deref(), provider), registered.system_prompt.clone());
+            let job = (registered.id.clone(), registered.name.clone(), input.clone());
+            let budget = budget.clone();
+            let handle = tokio::spawn(async move {
+                execute_agent(agent, &registered.id, &registered.name, &registered.system_prompt, input, &budget).await
+            });
+            (job, handle)
+        })
+        .collect();
+
+    let mut results = Vec::new();
+    for ((agent_id, agent_name, input), handle) in handles {
+        match handle.await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                tracing::error!("❌ {} task panicked: {}", agent_name, e);
+                results.push((failed_execution(agent_id, agent_name, format!("Task panicked: {}", e)), input));
+            }
+        }
+    }
+    results
+}
+
+/// Payload of the `chain-paused` event, emitted when a run reaches a pause step
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainPausedEvent {
+    pub run_id: String,
+    pub chain_id: String,
+    pub chain_name: String,
+    /// Chain step that is waiting
+    pub step_index: usize,
+    pub prompt: String,
+    /// The latest step output, for the user to review
+    pub output: String,
+    pub output_from: Option<String>,
+}
+
+/// Wait at a pause step until resume_chain delivers the user's input, which becomes the
+/// step's output. None if the run's time limit runs out first.
+async fn wait_for_human(
+    app_handle: &tauri::AppHandle,
+    chain: &ChainDefinition,
+    run_id: &str,
+    step_index: usize,
+    prompt: &str,
+    latest: Option<&AgentExecution>,
+    budget: &RunBudget,
+) -> Option<AgentExecution> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    PAUSED_RUNS.lock().unwrap().insert(run_id.to_string(), sender);
+    let event = ChainPausedEvent {
+        run_id: run_id.to_string(),
+        chain_id: chain.id.clone(),
+        chain_name: chain.name.clone(),
+        step_index,
+        prompt: prompt.to_string(),
+        output: latest.map(|e| e.content.clone()).unwrap_or_default(),
+        output_from: latest.map(|e| e.agent_name.clone()),
+    };
+    if let Err(e) = app_handle.emit_all("chain-paused", event) {
+        tracing::warn!("⚠️ Failed to emit chain pause: {}", e);
+    }
+    tracing::info!("⏸️ Chain '{}' paused at step {}: {}", chain.name, step_index + 1, prompt);
+
+    let started = std::time::Instant::now();
+    let input = budget.within_time(receiver).await;
+    PAUSED_RUNS.lock().unwrap().remove(run_id);
+    match input {
+        Some(Ok(input)) => {
+            tracing::info!("▶️ Chain '{}' resumed", chain.name);
+            Some(AgentExecution {
+                agent_id: "human".to_string(),
+                agent_name: "Human Input".to_string(),
+                success: true,
+                content: input.trim().to_string(),
+                error: None,
+                timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                duration_ms: started.elapsed().as_millis() as u64,
+                estimated_tokens: 0,
+                reflection: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Records a chain run in the database; recording failures are logged, never fatal
+struct RunRecorder {
+    conn: Option<crate::db::PooledConnection>,
+    run_id: String,
+}
+
+impl RunRecorder {
+    fn start(app_handle: &tauri::AppHandle, chain: &ChainDefinition, request: &OrchestrateAgentRequest) -> Self {
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let context = request.context.as_ref().map(|ctx| {
+            serde_json::Value::Object(ctx.iter().filter(|(k, _)| !k.ends_with("api_key")).map(|(k, v)| (k.clone(), v.clone())).collect())
+        });
+        let conn = crate::minimax_api::open_kc_database(Some(app_handle))
+            .and_then(|conn| {
+                crate::agent_chains::start_run(&conn, &run_id, chain, &request.task, context.as_ref()).map_err(|e| e.to_string())?;
+                Ok(conn)
+            })
+            .map_err(|e| tracing::warn!("⚠️ Chain run {} not recorded: {}", run_id, e))
+            .ok();
+        Self { conn, run_id }
+    }
+
+    fn set_status(&self, status: &str) {
+        if let Some(conn) = &self.conn {
+            if let Err(e) = crate::agent_chains::set_run_status(conn, &self.run_id, status) {
+                tracing::warn!("⚠️ Failed to mark chain run {} {}: {}", self.run_id, status, e);
+            }
+        }
+    }
+
+    /// Record an execution; returns the next execution index
+    fn step(&self, step_index: usize, stage: usize, role: &str, input: &str, execution: &AgentExecution) -> usize {
+        let step = ChainRunStep {
+            step_index,
+            stage,
+            role: role.to_string(),
+            agent_id: execution.agent_id.clone(),
+            agent_name: execution.agent_name.clone(),
+            input: input.to_string(),
+            output: execution.content.clone(),
+            success: execution.success,
+            error: execution.error.clone(),
+            started_at: (chrono::Utc::now() - chrono::Duration::milliseconds(execution.duration_ms as i64)).to_rfc3339(),
+            duration_ms: execution.duration_ms,
+            estimated_tokens: execution.estimated_tokens,
+        };
+        if let Some(conn) = &self.conn {
+            if let Err(e) = crate::agent_chains::record_step(conn, &self.run_id, &step) {
+                tracing::warn!("⚠️ Failed to record step {} of chain run {}: {}", step_index, self.run_id, e);
+            }
+        }
+        step_index + 1
+    }
+
+    /// `stopped` means the chain didn't reach its end, because a step failed or a run limit
+    /// was reached; a limited run keeps its last successful output as a partial result
+    fn finish(self, chain: &ChainDefinition, executions: Vec<AgentExecution>, stopped: bool, usage: RunUsage) -> OrchestrateAgentResponse {
+        let failed = executions.iter().find(|e| !e.success);
+        let limited = stopped && usage.limit_hit.is_some();
+        let status = match (stopped, failed.is_some()) {
+            (true, _) if limited => "limited",
+            (true, _) => "failed",
+            (false, true) => "incomplete",
+            (false, false) => "complete",
+        };
+        let total_tokens = executions.iter().map(|e| e.estimated_tokens).sum();
+        let final_output = if limited {
+            executions.iter().rev().find(|e| e.success).map(|e| e.content.as_str())
+        } else {
+            executions.last().filter(|_| !stopped).map(|e| e.content.as_str())
+        };
+        let duration_ms = usage.duration_ms;
+        if let Some(conn) = &self.conn {
+            let error = match usage.limit_hit.filter(|_| limited) {
+                Some(hit) => Some(hit.message()),
+                None => failed.and_then(|e| e.error.as_deref()),
+            };
+            if let Err(e) = crate::agent_chains::finish_run(conn, &self.run_id, status, final_output, error, total_tokens, duration_ms) {
+                tracing::warn!("⚠️ Failed to record chain run {}: {}", self.run_id, e);
+            }
+        }
+        OrchestrateAgentResponse {
+            chain_id: chain.id.clone(),
+            chain_name: chain.name.clone(),
+            executions,
+            total_duration_ms: duration_ms,
+            run_id: self.run_id,
+            status: status.to_string(),
+            total_tokens,
+            usage,
+        }
+    }
 }
 
 #[tauri::command]
 pub async fn orchestrate_agents(
+    app_handle: tauri::AppHandle,
     request: OrchestrateAgentRequest,
 ) -> Result<OrchestrateAgentResponse, String> {
-    let start_time = std::time::Instant::now();
+    let budget = RunBudget::new(request.limits.clone());
 
-    let start_time = std::time::Instant::now();
+    let tavily_key = request.context.as_ref()
+        .and_then(|ctx| ctx.get("tavily_api_key"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let provider = parse_provider(request.provider.as_deref());
+    let keys = ProviderKeys::new(&provider, &request.api_key, request.grok_api_key.clone(), request.gemini_api_key.clone(), tavily_key);
 
     // Check for Deep Research Chain
     if request.chain_id == "deep-research-v1" {
-        eprintln!("🚀 Starting Deep Research Agent Chain");
-        
-        // Initialize Minimax Agent
-        // Note: We need to get the Tavily key from the request or environment
-        // For now, we'll assume it might be passed in context or we need to fetch it
-        // But the request struct has api_key (Minimax). 
-        // We'll try to get Tavily key from context if available, otherwise empty (which might fail search)
-        let tavily_key = request.context.as_ref()
-            .and_then(|ctx| ctx.get("tavily_api_key"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
-
-        let mut agent = crate::minimax_enhanced::MinimaxAgent::new(
-            request.api_key.clone(),
-            tavily_key,
-            None, // No Grok key for now
-            None  // No Gemini key for now
+        tracing::info!("🚀 Starting Deep Research Agent Chain");
+        let chain = ChainDefinition::new(
+            request.chain_id.clone(),
+            "Deep Research Agent".to_string(),
+            "Autonomous web research".to_string(),
+            vec![ChainStep::agent("deep-researcher")],
         );
-
-        // Enable Web Search tool
-        let mut enabled_tools = std::collections::HashMap::new();
-        enabled_tools.insert("web_search".to_string(), true);
-        agent = agent.with_enabled_tools(enabled_tools);
+        let recorder = RunRecorder::start(&app_handle, &chain, &request);
 
         // Set System Prompt for Deep Research
         let system_prompt = r#"You are a Deep Research Agent.
@@ -71,80 +569,213 @@ Your goal is to thoroughly research the user's topic using the 'web_search' tool
 4. Iterate: If you need more info, search again with refined queries.
 5. Final Report: Produce a comprehensive markdown report citing your sources.
 Always use the <think> tag to explain your reasoning before taking actions."#.to_string();
-        
-        agent = agent.with_system_prompt(system_prompt);
-
-        // Run the task
-        let result = agent.run_autonomous_task(request.task.clone()).await?;
-
-        let response = OrchestrateAgentResponse {
-            chain_id: request.chain_id,
-            chain_name: "Deep Research Agent".to_string(),
-            executions: vec![
-                AgentExecution {
-                    agent_id: "deep-researcher".to_string(),
-                    agent_name: "Deep Research Agent".to_string(),
-                    success: true,
-                    content: result,
-                    error: None,
-                    timestamp: chrono::Utc::now().timestamp_millis() as u64,
-                },
-            ],
-            total_duration_ms: start_time.elapsed().as_millis() as u64,
-        };
 
-        return Ok(response);
+        // Enable Web Search tool
+        let mut enabled_tools = HashMap::new();
+        enabled_tools.insert("web_search".to_string(), true);
+        let agent = keys.agent(provider, system_prompt.clone()).with_enabled_tools(enabled_tools);
+
+        let (execution, input) = execute_agent(agent, "deep-researcher", "Deep Research Agent", &system_prompt, request.task.clone(), &budget).await;
+        recorder.step(0, 0, "agent", &input, &execution);
+        let stopped = !execution.success;
+        return Ok(recorder.finish(&chain, vec![execution], stopped, budget.usage()));
     }
 
-    // Fallback for other chains (Mock)
-    let mock_response = OrchestrateAgentResponse {
-        chain_id: request.chain_id,
-        chain_name: "Content Creation Pipeline".to_string(),
-        executions: vec![
-            AgentExecution {
-                agent_id: "researcher-v1".to_string(),
-                agent_name: "Research Specialist".to_string(),
-                success: true,
-                content: "# Research Results\n\n[Mock research output]".to_string(),
-                error: None,
-                timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            },
-            AgentExecution {
-                agent_id: "planner-v1".to_string(),
-                agent_name: "Strategic Planner".to_string(),
-                success: true,
-                content: "# Content Plan\n\n[Mock planning output]".to_string(),
-                error: None,
-                timestamp: chrono::Utc::now().timestamp_millis() as u64,
-            },
-        ],
-        total_duration_ms: start_time.elapsed().as_millis() as u64,
-    };
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let chain = crate::agent_chains::get_chain(&conn, &request.chain_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Agent chain '{}' not found", request.chain_id))?;
+    drop(conn);
+    let agents = load_registered_agents(Some(&app_handle))?;
+    // Fail before running anything if a step names an unknown agent
+    validate_steps(&chain.steps, &agents)?;
+
+    tracing::info!("🔗 Running agent chain '{}' ({} steps)", chain.name, chain.steps.len());
+    let recorder = RunRecorder::start(&app_handle, &chain, &request);
+    let mut executions: Vec<AgentExecution> = Vec::new();
+    // Latest output of each step that has run (a parallel step's merged result), by step
+    // index; later steps and conditions see these
+    let mut step_outputs: Vec<(usize, AgentExecution)> = Vec::new();
+    let mut loops_taken: HashMap<usize, usize> = HashMap::new();
+    let mut recorded = 0;
+    let mut stopped = false;
+    let mut index = 0;
+    while index < chain.steps.len() {
+        if let Some(hit) = budget.check() {
+            tracing::info!("🛑 Chain '{}' stopped before step {}: {}", chain.name, index + 1, hit.message());
+            stopped = true;
+            break;
+        }
+        let step = &chain.steps[index];
+        if let Some(when) = &step.when {
+            if !condition_holds(&chain.steps, when, None, &step_outputs) {
+                tracing::info!("⏭️ Step {}/{} skipped: '{}' doesn't hold", index + 1, chain.steps.len(), when);
+                index += 1;
+                continue;
+            }
+        }
+
+        if let Some(prompt) = &step.pause {
+            recorder.set_status("paused");
+            let latest = step_outputs.last().map(|(_, e)| e);
+            let execution = match wait_for_human(&app_handle, &chain, &recorder.run_id, index, prompt, latest, &budget).await {
+                Some(execution) => execution,
+                None => {
+                    tracing::info!("🛑 Chain '{}' stopped while waiting for input at step {}", chain.name, index + 1);
+                    stopped = true;
+                    break;
+                }
+            };
+            recorder.set_status("running");
+            recorded = recorder.step(recorded, index, "human", prompt, &execution);
+            step_outputs.retain(|(step, _)| *step != index);
+            step_outputs.push((index, execution.clone()));
+            executions.push(execution);
+            index += 1;
+            continue;
+        }
+
+        let mut input = step_input(&request.task, step, request.context.as_ref(), &step_outputs);
 
-    Ok(mock_response)
+        if !step.parallel.is_empty() {
+            tracing::info!("🚀 Step {}/{}: fanning out to {} agents", index + 1, chain.steps.len(), step.parallel.len());
+            let jobs = step
+                .parallel
+                .iter()
+                .map(|branch| {
+                    let registered = find_agent(&agents, &branch.agent_id)?.clone();
+                    Ok((registered, step_input(&request.task, branch, request.context.as_ref(), &step_outputs)))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            let mut merged = Vec::new();
+            for (execution, branch_input) in run_concurrently(&keys, &provider, jobs, &budget).await {
+                recorded = recorder.step(recorded, index, "branch", &branch_input, &execution);
+                if execution.success {
+                    merged.push(execution.clone());
+                } else {
+                    tracing::error!("❌ Branch {} failed: {}", execution.agent_name, execution.error.as_deref().unwrap_or(""));
+                }
+                executions.push(execution);
+            }
+            if merged.is_empty() {
+                tracing::error!("❌ Chain '{}' stopped at step {}: every branch failed", chain.name, index + 1);
+                stopped = true;
+                break;
+            }
+            input = reducer_input(&request.task, step, &merged);
+        }
+
+        let registered = find_agent(&agents, &step.agent_id)?;
+        let step_provider = keys.pick(registered.preferred_provider.as_deref(), &provider);
+        tracing::info!("🤖 Step {}/{}: {} ({:?})", index + 1, chain.steps.len(), registered.name, step_provider);
+
+        let agent = keys.agent(step_provider, registered.system_prompt.clone());
+        let (execution, input) = execute_agent(agent, &registered.id, &registered.name, &registered.system_prompt, input, &budget).await;
+        let role = if step.parallel.is_empty() { "agent" } else { "reducer" };
+        recorded = recorder.step(recorded, index, role, &input, &execution);
+        // Reflection: a critic reviews the output and the agent revises it once
+        let execution = match &step.reflect {
+            Some(options) if execution.success => {
+                let critic = find_critic(&agents, options.critic_id.as_deref())?;
+                tracing::info!("🪞 {} is critiquing {}'s output", critic.name, registered.name);
+                let agent = keys.agent(keys.pick(critic.preferred_provider.as_deref(), &provider), critic.system_prompt.clone());
+                let prompt = crate::reflection::critique_prompt(&input, &execution.content, options.rubric());
+                let (critique, critique_input) = execute_agent(agent, &critic.id, &critic.name, &critic.system_prompt, prompt, &budget).await;
+                recorded = recorder.step(recorded, index, "critic", &critique_input, &critique);
+
+                let mut revision = None;
+                if critique.success && !crate::reflection::approves(&critique.content) {
+                    let agent = keys.agent(keys.pick(registered.preferred_provider.as_deref(), &provider), registered.system_prompt.clone());
+                    let prompt = crate::reflection::revision_prompt(Some(&input), &execution.content, &critique.content);
+                    let (revised, revision_input) = execute_agent(agent, &registered.id, &registered.name, &registered.system_prompt, prompt, &budget).await;
+                    recorded = recorder.step(recorded, index, "revision", &revision_input, &revised);
+                    revision = Some(revised);
+                }
+                reflected(execution, critique, revision)
+            }
+            _ => execution,
+        };
+
+        let failed = !execution.success;
+        // A rerun (after a loop) replaces the step's earlier output
+        step_outputs.retain(|(step, _)| *step != index);
+        step_outputs.push((index, execution.clone()));
+        executions.push(execution);
+        // Later steps build on this one's output, so stop here
+        if failed {
+            tracing::error!("❌ Chain '{}' stopped at step {}", chain.name, index + 1);
+            stopped = true;
+            break;
+        }
+
+        index = match &step.loop_back {
+            Some(loop_back)
+                if loops_taken.get(&index).copied().unwrap_or(0) < loop_back.max
+                    && condition_holds(&chain.steps, &loop_back.condition, Some(index), &step_outputs) =>
+            {
+                let taken = loops_taken.entry(index).or_insert(0);
+                *taken += 1;
+                tracing::info!("🔁 '{}' holds, back to step '{}' ({}/{})", loop_back.condition, loop_back.to, taken, loop_back.max);
+                crate::agent_chains::step_index(&chain.steps, &loop_back.to).unwrap_or(index + 1)
+            }
+            _ => index + 1,
+        };
+    }
+
+    Ok(recorder.finish(&chain, executions, stopped, budget.usage()))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateChainRequest {
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
     pub agent_ids: Vec<String>,
+    /// Steps with per-step instructions; used instead of `agent_ids` when given
+    #[serde(default)]
+    pub steps: Option<Vec<ChainStep>>,
+    /// YAML or JSON pipeline with conditions and loops (see agent_chains); used instead of
+    /// `steps` when given. Its name and description fill in empty request fields.
+    #[serde(default)]
+    pub pipeline: Option<String>,
 }
 
 #[tauri::command]
 pub async fn create_agent_chain(
+    app_handle: tauri::AppHandle,
     request: CreateChainRequest,
 ) -> Result<String, String> {
-    // In a real implementation, this would:
-    // 1. Store the chain configuration
-    // 2. Return a chain ID
-    // 3. Validate that all agent IDs exist
+    let mut name = request.name;
+    let mut description = request.description;
+    let steps = match &request.pipeline {
+        Some(text) => {
+            let pipeline = crate::agent_chains::parse_pipeline(text)?;
+            if name.trim().is_empty() {
+                name = pipeline.name.unwrap_or_default();
+            }
+            description = description.filter(|d| !d.is_empty()).or(pipeline.description);
+            pipeline.steps
+        }
+        None => request.steps.unwrap_or_else(|| request.agent_ids.iter().map(|id| ChainStep::agent(id)).collect()),
+    };
+    if name.trim().is_empty() {
+        return Err("A chain needs a name".to_string());
+    }
+    if steps.is_empty() {
+        return Err("A chain needs at least one agent".to_string());
+    }
+    let agents = load_registered_agents(Some(&app_handle))?;
+    validate_steps(&steps, &agents)?;
 
     let chain_id = format!("chain-{}-{}-v1",
-        request.name.to_lowercase().replace(" ", "-"),
+        name.to_lowercase().replace(" ", "-"),
         chrono::Utc::now().timestamp()
     );
+    let chain = ChainDefinition::new(chain_id.clone(), name, description.unwrap_or_default(), steps);
 
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    crate::agent_chains::save_chain(&conn, &chain).map_err(|e| e.to_string())?;
     Ok(chain_id)
 }
 
@@ -160,175 +791,658 @@ pub struct AgentChainInfo {
     pub description: String,
     pub agent_count: usize,
     pub created_at: u64,
+    #[serde(default)]
+    pub steps: Vec<ChainStep>,
+    #[serde(default)]
+    pub builtin: bool,
 }
 
 #[tauri::command]
-pub async fn list_agent_chains() -> Result<ListChainsResponse, String> {
-    // Return all registered chains
-
-    let chains = vec![
-        AgentChainInfo {
-            id: "content-creation-v1".to_string(),
-            name: "Content Creation Pipeline".to_string(),
-            description: "Full pipeline: Research → Plan → Write → Review".to_string(),
-            agent_count: 4,
-            created_at: chrono::Utc::now().timestamp_millis() as u64,
-        },
-        AgentChainInfo {
-            id: "research-review-v1".to_string(),
-            name: "Research with Review".to_string(),
-            description: "Research with quality review".to_string(),
-            agent_count: 2,
-            created_at: chrono::Utc::now().timestamp_millis() as u64,
-        },
-    ];
+pub async fn list_agent_chains(app_handle: tauri::AppHandle) -> Result<ListChainsResponse, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let chains = crate::agent_chains::list_chains(&conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|chain| AgentChainInfo {
+            agent_count: chain.steps.len(),
+            created_at: chrono::DateTime::parse_from_rfc3339(&chain.created_at)
+                .map(|d| d.timestamp_millis() as u64)
+                .unwrap_or(0),
+            id: chain.id,
+            name: chain.name,
+            description: chain.description,
+            steps: chain.steps,
+            builtin: chain.builtin,
+        })
+        .collect();
 
     Ok(ListChainsResponse { chains })
 }
 
+/// A recorded chain run with every step's input, output, timing and tokens
+#[tauri::command]
+pub fn get_chain_run(app_handle: tauri::AppHandle, run_id: String) -> Result<ChainRunRecord, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    crate::agent_chains::get_run(&conn, &run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Chain run '{}' not found", run_id))
+}
+
+/// Chain runs newest first, optionally for one chain
+#[tauri::command]
+pub fn list_chain_runs(app_handle: tauri::AppHandle, chain_id: Option<String>, limit: Option<usize>) -> Result<Vec<ChainRunSummary>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    crate::agent_chains::list_runs(&conn, chain_id.as_deref(), limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// Continue a chain run that is paused for human input; `user_input` becomes the pause
+/// step's output
+#[tauri::command]
+pub fn resume_chain(run_id: String, user_input: String) -> Result<(), String> {
+    let sender = PAUSED_RUNS
+        .lock()
+        .unwrap()
+        .remove(&run_id)
+        .ok_or_else(|| format!("Chain run '{}' isn't waiting for input", run_id))?;
+    sender.send(user_input).map_err(|_| format!("Chain run '{}' is no longer running", run_id))
+}
+
+const JUDGE_PROMPT: &str = r#"You are an impartial Judge.
+You compare answers to the same question on correctness, depth and practicality.
+Explain your reasoning briefly and be decisive."#;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsensusRequest {
+    pub question: String,
+    pub api_key: String,
+    /// Constructs that answer the question independently (at least two)
+    pub agent_ids: Vec<String>,
+    /// "judge" (default), "majority" or "score"
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Construct that judges in judge mode; a built-in impartial judge otherwise
+    #[serde(default)]
+    pub judge_id: Option<String>,
+    #[serde(default)]
+    pub context: Option<HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub grok_api_key: Option<String>,
+    #[serde(default)]
+    pub gemini_api_key: Option<String>,
+    #[serde(default)]
+    pub limits: RunLimits,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ConsensusResponse {
+    pub question: String,
+    pub mode: ConsensusMode,
+    /// Every agent's independent answer, including failed ones
+    pub candidates: Vec<AgentExecution>,
+    /// Index into `candidates` of the chosen answer; None when the judge merged them
+    pub winner: Option<usize>,
+    pub answer: String,
+    pub rationale: String,
+    /// Votes cast in majority and score modes, by candidate index
+    pub ballots: Vec<Ballot>,
+    /// Votes (majority) or average score (score) per candidate
+    pub tallies: Vec<f64>,
+    /// The judge's or voters' executions
+    pub judging: Vec<AgentExecution>,
+    pub total_tokens: usize,
+    pub total_duration_ms: u64,
+    #[serde(default)]
+    pub usage: RunUsage,
+}
+
+/// Several agents answer a question independently, then a judge picks or merges the best
+/// answer, or the agents vote on each other's answers
+#[tauri::command]
+pub async fn orchestrate_consensus(
+    app_handle: tauri::AppHandle,
+    request: ConsensusRequest,
+) -> Result<ConsensusResponse, String> {
+    let budget = RunBudget::new(request.limits.clone());
+    let mode = match request.mode.as_deref() {
+        Some(name) => ConsensusMode::from_name(name).ok_or_else(|| format!("Unknown consensus mode '{}'", name))?,
+        None => ConsensusMode::default(),
+    };
+    if request.agent_ids.len() < 2 {
+        return Err("Consensus needs at least two agents".to_string());
+    }
+    let provider = parse_provider(request.provider.as_deref());
+    let keys = ProviderKeys::new(&provider, &request.api_key, request.grok_api_key.clone(), request.gemini_api_key.clone(), None);
+    let agents = load_registered_agents(Some(&app_handle))?;
+    let answerers = request
+        .agent_ids
+        .iter()
+        .map(|id| find_agent(&agents, id).cloned())
+        .collect::<Result<Vec<_>, _>>()?;
+    let judge = request.judge_id.as_deref().map(|id| find_agent(&agents, id).cloned()).transpose()?;
+
+    tracing::info!("🗳️ Consensus ({:?}) across {} agents", mode, answerers.len());
+    let step = ChainStep {
+        instructions: Some("Answer on your own; other agents are answering the same question independently.".to_string()),
+        ..ChainStep::agent("")
+    };
+    let input = step_input(&request.question, &step, request.context.as_ref(), &[]);
+    let jobs = answerers.iter().map(|a| (a.clone(), input.clone())).collect();
+    let candidates: Vec<AgentExecution> = run_concurrently(&keys, &provider, jobs, &budget).await.into_iter().map(|(e, _)| e).collect();
+
+    // Judges and voters only see the answers that came back
+    let answered: Vec<usize> = (0..candidates.len()).filter(|&i| candidates[i].success).collect();
+    if answered.is_empty() {
+        let error = candidates.iter().find_map(|c| c.error.clone()).unwrap_or_default();
+        return Err(format!("Every agent failed to answer: {}", error));
+    }
+    let contents: Vec<&str> = answered.iter().map(|&i| candidates[i].content.as_str()).collect();
+
+    let mut judging = Vec::new();
+    let mut ballots = Vec::new();
+    let mut tallies = Vec::new();
+    let (winner, answer, rationale) = if answered.len() == 1 {
+        let only = answered[0];
+        (Some(only), candidates[only].content.clone(), "Only one agent answered, so its answer stands.".to_string())
+    } else if mode == ConsensusMode::Judge {
+        let judge = judge.unwrap_or_else(|| RegisteredAgent {
+            id: "judge".to_string(),
+            name: "Judge".to_string(),
+            system_prompt: JUDGE_PROMPT.to_string(),
+            preferred_provider: None,
+        });
+        let agent = keys.agent(keys.pick(judge.preferred_provider.as_deref(), &provider), judge.system_prompt.clone());
+        let prompt = crate::consensus::judge_prompt(&request.question, &contents);
+        let (execution, _) = execute_agent(agent, &judge.id, &judge.name, &judge.system_prompt, prompt, &budget).await;
+        if !execution.success {
+            return Err(format!("{} failed: {}", judge.name, execution.error.unwrap_or_default()));
+        }
+        let verdict = crate::consensus::parse_verdict(&execution.content, contents.len());
+        let winner = verdict.winner.map(|w| answered[w]);
+        let answer = match winner {
+            Some(w) => candidates[w].content.clone(),
+            None => verdict.answer.unwrap_or_else(|| execution.content.clone()),
+        };
+        judging.push(execution);
+        (winner, answer, verdict.rationale)
+    } else {
+        let jobs = answered
+            .iter()
+            .enumerate()
+            .map(|(own, &i)| (answerers[i].clone(), crate::consensus::ballot_prompt(mode, &request.question, &contents, own)))
+            .collect();
+        for (own, (execution, _)) in run_concurrently(&keys, &provider, jobs, &budget).await.into_iter().enumerate() {
+            if execution.success {
+                ballots.push(crate::consensus::parse_ballot(mode, &execution.agent_name, &execution.content, contents.len(), own));
+            }
+            judging.push(execution);
+        }
+        let totals = crate::consensus::tally(mode, contents.len(), &ballots);
+        let names: Vec<&str> = answered.iter().map(|&i| candidates[i].agent_name.as_str()).collect();
+        let rationale = crate::consensus::rationale(mode, &names, &totals, &ballots);
+
+        // Report votes and tallies by candidate index
+        tallies = vec![0.0; candidates.len()];
+        for (k, &i) in answered.iter().enumerate() {
+            tallies[i] = totals[k];
+        }
+        for vote in ballots.iter_mut().flat_map(|b| b.votes.iter_mut()) {
+            vote.candidate = answered[vote.candidate];
+        }
+        match crate::consensus::winner(&totals) {
+            Some(w) => (Some(answered[w]), contents[w].to_string(), rationale),
+            None => (Some(answered[0]), contents[0].to_string(), "No valid votes were cast, so the first answer stands.".to_string()),
+        }
+    };
+
+    let total_tokens = candidates.iter().chain(&judging).map(|e| e.estimated_tokens).sum();
+    let usage = budget.usage();
+    tracing::info!("✅ Consensus reached: {}", winner.map_or("merged answer".to_string(), |w| candidates[w].agent_name.clone()));
+    Ok(ConsensusResponse {
+        question: request.question,
+        mode,
+        candidates,
+        winner,
+        answer,
+        rationale,
+        ballots,
+        tallies,
+        judging,
+        total_tokens,
+        total_duration_ms: usage.duration_ms,
+        usage,
+    })
+}
+
+const ARCHITECT_PROMPT: &str = r#"You are The Architect.
+Your goal is to design robust, scalable, and innovative solutions.
+When presented with a topic, propose a high-level technical design.
+When critiqued, refine your design to address the concerns while maintaining the core vision.
+Be concise but specific."#;
+
+const CRITIC_PROMPT: &str = r#"You are The Critic.
+Your goal is to find flaws, security risks, and performance bottlenecks.
+Review the Architect's proposals with extreme scrutiny.
+Point out edge cases, race conditions, and scalability issues.
+Be constructive but ruthless."#;
+
+const MODERATOR_PROMPT: &str = r#"You are the Moderator of a debate.
+After each round, summarize where the participants agree and disagree and what is still open.
+Stay neutral and keep the debate focused on the topic.
+When the participants have converged on a solution, say "CONSENSUS REACHED"."#;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct DebateRequest {
+    /// Id for the debate-turn events and stop-debate; generated when missing
+    #[serde(default)]
+    pub debate_id: Option<String>,
     pub topic: String,
     pub api_key: String,
+    /// Maximum number of rounds; every participant speaks once per round
     pub turns: Option<usize>,
     pub provider: Option<String>,
+    /// Who debates, in speaking order; defaults to an Architect and a Critic
+    #[serde(default)]
+    pub participants: Vec<DebateParticipant>,
+    /// Summarizes each round and writes the final consensus. Without one, the first
+    /// participant has the final word.
+    #[serde(default)]
+    pub moderator: Option<DebateParticipant>,
+    /// Condition (see agent_chains::Condition) that ends the debate early, checked after
+    /// each round against the moderator's summary (or the round's last message), e.g.
+    /// `output contains "CONSENSUS REACHED"`
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Keys for participants whose provider differs from `provider`
+    #[serde(default)]
+    pub grok_api_key: Option<String>,
+    #[serde(default)]
+    pub gemini_api_key: Option<String>,
+    /// Caps on tokens, turns (one API call each) and time; the debate ends early when one
+    /// is reached
+    #[serde(default)]
+    pub limits: RunLimits,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A debater, either a registered Construct or defined inline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebateParticipant {
+    /// Construct (agents.json) whose name, system prompt and preferred provider to use
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    /// Override the Construct's name, system prompt or provider
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Position to take in this debate, e.g. "argue for a monolith"
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+impl DebateParticipant {
+    fn inline(name: &str, system_prompt: &str) -> Self {
+        Self { name: Some(name.to_string()), system_prompt: Some(system_prompt.to_string()), ..Default::default() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DebateTurn {
     pub speaker: String,
     pub content: String,
     pub timestamp: u64,
+    /// 1-based round; the final word belongs to the last round
+    #[serde(default)]
+    pub round: usize,
+    /// "participant", "moderator" or "final"
+    #[serde(default)]
+    pub role: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DebateResponse {
+    #[serde(default)]
+    pub debate_id: String,
     pub topic: String,
     pub transcript: Vec<DebateTurn>,
     pub final_consensus: String,
+    #[serde(default)]
+    pub participants: Vec<String>,
+    #[serde(default)]
+    pub moderator: Option<String>,
+    /// Rounds actually held
+    #[serde(default)]
+    pub rounds: usize,
+    /// "rounds" (ran every round), "condition" (`until` held), "stopped" (by the user) or
+    /// "limit" (a run limit was reached)
+    #[serde(default)]
+    pub ended_by: String,
+    #[serde(default)]
+    pub usage: RunUsage,
+}
+
+/// Payload of the `debate-turn` event, emitted as each turn finishes
+#[derive(Debug, Clone, Serialize)]
+pub struct DebateTurnEvent {
+    pub debate_id: String,
+    /// Position in the transcript
+    pub turn_index: usize,
+    #[serde(flatten)]
+    pub turn: DebateTurn,
+}
+
+/// Streams a debate's turns to the UI and watches for `stop-debate`, whose payload
+/// `{ "debate_id": ... }` picks the debate (no payload stops every debate)
+struct DebateStream {
+    app_handle: Option<tauri::AppHandle>,
+    debate_id: String,
+    stop: Arc<AtomicBool>,
+    listener: Option<tauri::EventHandler>,
+}
+
+impl DebateStream {
+    fn new(app_handle: Option<&tauri::AppHandle>, debate_id: String) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let listener = app_handle.map(|handle| {
+            let stop = stop.clone();
+            let id = debate_id.clone();
+            handle.listen_global("stop-debate", move |event| {
+                let target = event
+                    .payload()
+                    .and_then(|p| serde_json::from_str::<serde_json::Value>(p).ok())
+                    .and_then(|v| v.get("debate_id").and_then(|d| d.as_str()).map(|d| d.to_string()));
+                if target.map_or(true, |t| t == id) {
+                    tracing::info!("🛑 Stop signal received for debate {}", id);
+                    stop.store(true, Ordering::Relaxed);
+                }
+            })
+        });
+        Self { app_handle: app_handle.cloned(), debate_id, stop, listener }
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Resolves once the debate is stopped
+    async fn stop_requested(&self) {
+        while !self.stopped() {
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        }
+    }
+
+    fn emit(&self, turn_index: usize, turn: &DebateTurn) {
+        if let Some(handle) = &self.app_handle {
+            let event = DebateTurnEvent { debate_id: self.debate_id.clone(), turn_index, turn: turn.clone() };
+            if let Err(e) = handle.emit_all("debate-turn", event) {
+                tracing::warn!("⚠️ Failed to emit debate turn: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for DebateStream {
+    fn drop(&mut self) {
+        if let (Some(handle), Some(listener)) = (&self.app_handle, self.listener) {
+            handle.unlisten(listener);
+        }
+    }
+}
+
+struct Debater {
+    name: String,
+    agent: MinimaxAgent,
+    /// Transcript length when this debater last spoke; later turns are news to them
+    heard: usize,
+    /// Estimated tokens of this debater's conversation so far, which every call resends
+    context_tokens: usize,
+}
+
+impl Debater {
+    fn resolve(
+        spec: &DebateParticipant,
+        agents: &[RegisteredAgent],
+        keys: &ProviderKeys,
+        fallback: &AIProvider,
+        default_name: String,
+        default_prompt: &str,
+    ) -> Result<Self, String> {
+        let registered = spec.agent_id.as_deref().map(|id| find_agent(agents, id)).transpose()?;
+        let name = spec.name.clone().or_else(|| registered.map(|a| a.name.clone())).unwrap_or(default_name);
+        let mut system_prompt = spec
+            .system_prompt
+            .clone()
+            .or_else(|| registered.map(|a| a.system_prompt.clone()))
+            .unwrap_or_else(|| default_prompt.to_string());
+        if let Some(role) = &spec.role {
+            system_prompt.push_str(&format!("\n\nIn this debate you are {}. Your position: {}", name, role));
+        }
+        let preferred = spec.provider.as_deref().or_else(|| registered.and_then(|a| a.preferred_provider.as_deref()));
+        let provider = keys.pick(preferred, fallback);
+        tracing::info!("🎭 {} ({:?})", name, provider);
+        let context_tokens = estimate_tokens(&system_prompt);
+        Ok(Self { name, agent: keys.agent(provider, system_prompt), heard: 0, context_tokens })
+    }
+
+    /// Send `message` and return the reply without its <think> block; None when the
+    /// debate is stopped or reaches a run limit first
+    async fn speak(&mut self, message: String, stream: &DebateStream, budget: &RunBudget) -> Result<Option<String>, String> {
+        if stream.stopped() {
+            return Ok(None);
+        }
+        if let Err(hit) = budget.try_call() {
+            tracing::info!("🛑 {} not asked: {}", self.name, hit.message());
+            return Ok(None);
+        }
+        tracing::debug!("🗣️ {} is thinking...", self.name);
+        self.context_tokens += estimate_tokens(&message);
+        self.agent.add_user_message(message);
+        let response = tokio::select! {
+            response = budget.within_time(self.agent.chat(1)) => match response {
+                Some(response) => response?,
+                None => return Ok(None),
+            },
+            _ = stream.stop_requested() => return Ok(None),
+        };
+        let reply_tokens = estimate_tokens(&response.content);
+        budget.record_tokens(self.context_tokens + reply_tokens);
+        self.context_tokens += reply_tokens;
+        tracing::debug!("✅ {} responded", self.name);
+        Ok(Some(THINK.replace_all(&response.content, "").trim().to_string()))
+    }
+}
+
+/// Why a debater didn't speak: a run limit, or else the user stopped the debate
+fn stop_reason(budget: &RunBudget) -> &'static str {
+    if budget.check().is_some() {
+        "limit"
+    } else {
+        "stopped"
+    }
+}
+
+fn format_turns(turns: &[DebateTurn]) -> String {
+    turns
+        .iter()
+        .map(|t| format!("=== {} ===\n{}", t.speaker, t.content))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A participant's prompt for their turn: the topic on the first round, then what the
+/// others said since they last spoke
+fn participant_message(topic: &str, name: &str, round: usize, unheard: &[DebateTurn]) -> String {
+    if unheard.is_empty() {
+        return format!("Please propose a solution for: {}", topic);
+    }
+    let opening = if round == 1 { format!("Topic: {}\n\n", topic) } else { String::new() };
+    format!(
+        "{}Since you last spoke:\n\n{}\n\nRespond as {}: build on the strong points, challenge the weak ones and refine your position.",
+        opening,
+        format_turns(unheard),
+        name
+    )
 }
 
 #[tauri::command]
 pub async fn start_agent_debate(
+    app_handle: tauri::AppHandle,
     request: DebateRequest,
 ) -> Result<DebateResponse, String> {
-    let turns = request.turns.unwrap_or(3);
-    let mut transcript = Vec::new();
-    
-    // Initialize Agents
-    let provider_enum = match request.provider.as_deref() {
-        Some("grok") => crate::minimax_enhanced::AIProvider::Grok,
-        Some("gemini") => crate::minimax_enhanced::AIProvider::Gemini,
-        _ => crate::minimax_enhanced::AIProvider::Minimax,
-    };
+    run_debate(Some(&app_handle), request).await
+}
 
-    eprintln!("🔍 Debate Provider: {:?}", provider_enum);
+/// Run a debate: participants speak in order each round, an optional moderator
+/// summarizes, and the moderator (or first participant) writes the final consensus.
+/// Each turn is emitted as a `debate-turn` event; a debate that is stopped or reaches a
+/// run limit skips the consensus.
+pub async fn run_debate(
+    app_handle: Option<&tauri::AppHandle>,
+    request: DebateRequest,
+) -> Result<DebateResponse, String> {
+    let budget = RunBudget::new(request.limits.clone());
+    let rounds = request.turns.unwrap_or(3).max(1);
+    let provider = parse_provider(request.provider.as_deref());
+    tracing::debug!("🔍 Debate Provider: {:?}", provider);
     let masked_key = if request.api_key.len() > 10 {
         format!("{}...", &request.api_key[..10])
     } else {
         "SHORT_KEY".to_string()
     };
-    eprintln!("🔑 API Key (masked): {}", masked_key);
+    tracing::debug!("🔑 API Key (masked): {}", masked_key);
+    let keys = ProviderKeys::new(&provider, &request.api_key, request.grok_api_key.clone(), request.gemini_api_key.clone(), None);
 
-    // Determine keys based on provider
-    let (primary_key, gemini_key) = match provider_enum {
-        crate::minimax_enhanced::AIProvider::Gemini => ("".to_string(), Some(request.api_key.clone())),
-        _ => (request.api_key.clone(), None),
+    let until = request.until.as_deref().map(Condition::parse).transpose()?;
+    let specs = if request.participants.is_empty() {
+        vec![DebateParticipant::inline("Architect", ARCHITECT_PROMPT), DebateParticipant::inline("Critic", CRITIC_PROMPT)]
+    } else {
+        request.participants.clone()
     };
+    if specs.len() < 2 {
+        return Err("A debate needs at least two participants".to_string());
+    }
+    let uses_constructs = specs.iter().chain(request.moderator.iter()).any(|p| p.agent_id.is_some());
+    let agents = if uses_constructs { load_registered_agents(app_handle)? } else { Vec::new() };
 
-    // Agent A: The Architect (Creative, Constructive)
-    let mut architect = crate::minimax_enhanced::MinimaxAgent::new(
-        primary_key.clone(),
-        None,
-        None,
-        gemini_key.clone()
-    ).with_provider(provider_enum.clone())
-     .with_system_prompt(r#"You are The Architect.
-Your goal is to design robust, scalable, and innovative solutions.
-When presented with a topic, propose a high-level technical design.
-When critiqued, refine your design to address the concerns while maintaining the core vision.
-Be concise but specific."#.to_string());
-
-    // Agent B: The Critic (Security, Performance, Reliability)
-    let mut critic = crate::minimax_enhanced::MinimaxAgent::new(
-        primary_key,
-        None,
-        None,
-        gemini_key
-    ).with_provider(provider_enum)
-     .with_system_prompt(r#"You are The Critic.
-Your goal is to find flaws, security risks, and performance bottlenecks.
-Review the Architect's proposals with extreme scrutiny.
-Point out edge cases, race conditions, and scalability issues.
-Be constructive but ruthless."#.to_string());
-
-    // Disable tools for debate to focus on pure reasoning
-    let no_tools = std::collections::HashMap::new();
-    architect = architect.with_enabled_tools(no_tools.clone());
-    critic = critic.with_enabled_tools(no_tools);
-
-    let mut current_context = format!("Topic: {}", request.topic);
-    let mut last_message = String::new();
-    let think_regex = Regex::new(r"(?s)<think>.*?</think>").unwrap();
+    let mut debaters = specs
+        .iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            Debater::resolve(spec, &agents, &keys, &provider, format!("Participant {}", i + 1), "You are a thoughtful debate participant.")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut moderator = request
+        .moderator
+        .as_ref()
+        .map(|spec| Debater::resolve(spec, &agents, &keys, &provider, "Moderator".to_string(), MODERATOR_PROMPT))
+        .transpose()?;
 
-    eprintln!("🚀 Starting debate on topic: {}", request.topic);
+    let debate_id = request.debate_id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let stream = DebateStream::new(app_handle, debate_id.clone());
+    tracing::info!("🚀 Starting debate {} on topic: {}", debate_id, request.topic);
+    let mut transcript: Vec<DebateTurn> = Vec::new();
+    let mut rounds_held = 0;
+    let mut ended_by = "rounds";
+    let add_turn = |transcript: &mut Vec<DebateTurn>, speaker: &str, content: String, round: usize, role: &str| {
+        let turn = DebateTurn {
+            speaker: speaker.to_string(),
+            content,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            round,
+            role: role.to_string(),
+        };
+        stream.emit(transcript.len(), &turn);
+        transcript.push(turn);
+    };
 
-    for i in 0..turns {
-        eprintln!("🏁 Debate Turn {}/{}", i + 1, turns);
+    'rounds: for round in 1..=rounds {
+        tracing::info!("🏁 Debate Round {}/{}", round, rounds);
+        rounds_held = round;
+        for debater in debaters.iter_mut() {
+            let message = participant_message(&request.topic, &debater.name, round, &transcript[debater.heard..]);
+            let content = match debater.speak(message, &stream, &budget).await? {
+                Some(content) => content,
+                None => {
+                    ended_by = stop_reason(&budget);
+                    break 'rounds;
+                }
+            };
+            add_turn(&mut transcript, &debater.name, content, round, "participant");
+            debater.heard = transcript.len();
+        }
 
-        // Turn 1: Architect Proposal
-        if i == 0 {
-            architect.add_user_message(format!("Please propose a solution for: {}", request.topic));
-        } else {
-            // Architect responds to Critic
-            architect.add_user_message(format!("The Critic raised these points:\n{}\n\nRefine your design.", last_message));
-        }
-
-        eprintln!("🗣️ Architect is thinking...");
-        let arch_response = architect.chat(1).await?;
-        eprintln!("✅ Architect responded");
-        // Strip think tags for the transcript to save tokens
-        let clean_content = think_regex.replace_all(&arch_response.content, "").trim().to_string();
-        last_message = clean_content.clone();
-        
-        transcript.push(DebateTurn {
-            speaker: "Architect".to_string(),
-            content: last_message.clone(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-        });
+        if let Some(moderator) = moderator.as_mut() {
+            let message = format!(
+                "Round {} of the debate on: {}\n\n{}\n\nSummarize this round.",
+                round,
+                request.topic,
+                format_turns(&transcript[moderator.heard..])
+            );
+            let content = match moderator.speak(message, &stream, &budget).await? {
+                Some(content) => content,
+                None => {
+                    ended_by = stop_reason(&budget);
+                    break;
+                }
+            };
+            add_turn(&mut transcript, &moderator.name, content, round, "moderator");
+            moderator.heard = transcript.len();
+        }
 
-        // Turn 2: Critic Review
-        critic.add_user_message(format!("The Architect proposed:\n{}\n\nCritique this design.", last_message));
-        eprintln!("🤔 Critic is thinking...");
-        let critic_response = critic.chat(1).await?;
-        eprintln!("✅ Critic responded");
-        let clean_content = think_regex.replace_all(&critic_response.content, "").trim().to_string();
-        last_message = clean_content.clone();
-
-        transcript.push(DebateTurn {
-            speaker: "Critic".to_string(),
-            content: last_message.clone(),
-            timestamp: chrono::Utc::now().timestamp_millis() as u64,
-        });
+        if let Some(until) = &until {
+            if transcript.last().map_or(false, |t| until.holds(&t.content)) {
+                tracing::info!("🛑 '{}' holds after round {}, ending the debate", request.until.as_deref().unwrap_or(""), round);
+                ended_by = "condition";
+                break;
+            }
+        }
     }
 
-    // Final Consensus (Architect's final word)
-    eprintln!("⚖️ Generating Final Consensus...");
-    architect.add_user_message(format!("Considering the Critic's feedback:\n{}\n\nProvide the FINAL, polished solution.", last_message));
-    let final_response = architect.chat(1).await?;
-    eprintln!("✅ Final Consensus generated");
-    
-    let clean_consensus = think_regex.replace_all(&final_response.content, "").trim().to_string();
-
-    transcript.push(DebateTurn {
-        speaker: "Architect (Final)".to_string(),
-        content: clean_consensus.clone(),
-        timestamp: chrono::Utc::now().timestamp_millis() as u64,
-    });
+    // Final consensus from the moderator, or the first participant's final word
+    let closer = match moderator.as_mut() {
+        Some(moderator) => moderator,
+        None => &mut debaters[0],
+    };
+    let unheard = &transcript[closer.heard..];
+    let news = if unheard.is_empty() { String::new() } else { format!("Since you last spoke:\n\n{}\n\n", format_turns(unheard)) };
+    let message = format!(
+        "{}The debate is over. Provide the FINAL, polished solution the debate arrived at, noting any disagreements that remain.",
+        news
+    );
+    let final_consensus = if matches!(ended_by, "stopped" | "limit") {
+        None
+    } else {
+        tracing::info!("⚖️ Generating Final Consensus...");
+        closer.speak(message, &stream, &budget).await?
+    };
+    let final_consensus = match final_consensus {
+        Some(content) => {
+            let final_speaker = format!("{} (Final)", closer.name);
+            add_turn(&mut transcript, &final_speaker, content.clone(), rounds_held, "final");
+            content
+        }
+        // Stopped or limited: the latest turn is as far as the debate got
+        None => {
+            if !matches!(ended_by, "stopped" | "limit") {
+                ended_by = stop_reason(&budget);
+            }
+            tracing::info!("🛑 Debate {} ended ({}) after {} turn(s)", debate_id, ended_by, transcript.len());
+            transcript.last().map(|t| t.content.clone()).unwrap_or_default()
+        }
+    };
 
     Ok(DebateResponse {
+        debate_id,
         topic: request.topic,
         transcript,
-        final_consensus: clean_consensus,
+        final_consensus,
+        participants: debaters.iter().map(|d| d.name.clone()).collect(),
+        moderator: moderator.map(|m| m.name),
+        rounds: rounds_held,
+        ended_by: ended_by.to_string(),
+        usage: budget.usage(),
     })
 }
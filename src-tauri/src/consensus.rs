@@ -0,0 +1,300 @@
+/// Voting and judging for consensus orchestration
+///
+/// Several agents answer the same question independently. Then either a judge picks the
+/// best answer (or merges them), or the agents vote on each other's answers: `Majority`
+/// (each names the best answer other than its own) or `Score` (each scores the others
+/// 1-10). Answers are numbered from 1 in prompts and indexed from 0 everywhere else.
+
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref WINNER: Regex = Regex::new(r"(?im)^\W*winner\W*\s*(?:answer\s*)?(\d+|merged)").unwrap();
+    static ref RATIONALE: Regex = Regex::new(r"(?ims)^\W*rationale\W*\s*(.*?)(?:^\W*answer\W*\s*$|\z)").unwrap();
+    static ref ANSWER: Regex = Regex::new(r"(?ims)^\W*answer:\W*$(.*)").unwrap();
+    static ref VOTE: Regex = Regex::new(r"(?im)^\W*vote\W*\s*(?:answer\s*)?(\d+)").unwrap();
+    static ref REASON: Regex = Regex::new(r"(?im)^\W*reason\W*\s*(.+)$").unwrap();
+    // "SCORE 2: 7 - clear but thin on risks"
+    static ref SCORE_LINE: Regex = Regex::new(r"(?im)^\W*score[ \t]*(?:answer[ \t]*)?(\d+)[ \t]*[:=-][ \t]*(\d+(?:\.\d+)?)(?:[ \t]*/[ \t]*10)?[ \t]*[-–—:]?[ \t]*(.*)$").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusMode {
+    /// A judge agent picks or merges the best answer
+    #[default]
+    Judge,
+    /// Each agent votes for the best answer other than its own
+    Majority,
+    /// Each agent scores every other answer from 1 to 10
+    Score,
+}
+
+impl ConsensusMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "judge" => Some(Self::Judge),
+            "majority" | "vote" | "majority_vote" => Some(Self::Majority),
+            "score" | "score_vote" | "scores" => Some(Self::Score),
+            _ => None,
+        }
+    }
+}
+
+/// The judge's decision
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    /// The chosen answer; None when the judge merged them
+    pub winner: Option<usize>,
+    pub rationale: String,
+    /// The judge's own (merged) answer, if it wrote one
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vote {
+    pub candidate: usize,
+    /// 1 for a majority vote, otherwise the score given
+    pub score: f64,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// One agent's votes on the other answers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ballot {
+    pub voter: String,
+    pub votes: Vec<Vote>,
+}
+
+/// Candidates as the judge and voters see them
+pub fn candidates_block(candidates: &[&str]) -> String {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, content)| format!("=== Answer {} ===\n{}", i + 1, content.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn judge_prompt(question: &str, candidates: &[&str]) -> String {
+    format!(
+        "Question: {}\n\n{} agents answered independently:\n\n{}\n\n\
+         Judge which answer is best on correctness, depth and practicality. If no single answer is \
+         clearly best and they complement each other, merge them instead. Reply in this format:\n\
+         WINNER: <answer number, or MERGED>\n\
+         RATIONALE: <why, comparing the answers>\n\
+         ANSWER:\n\
+         <the merged answer; leave empty if you picked a winner>",
+        question,
+        candidates.len(),
+        candidates_block(candidates)
+    )
+}
+
+/// Read the judge's reply; a winner out of range counts as no winner
+pub fn parse_verdict(text: &str, candidate_count: usize) -> Verdict {
+    let winner = WINNER
+        .captures(text)
+        .and_then(|c| c[1].parse::<usize>().ok())
+        .filter(|n| (1..=candidate_count).contains(n))
+        .map(|n| n - 1);
+    let rationale = RATIONALE
+        .captures(text)
+        .map(|c| c[1].trim().to_string())
+        .filter(|r| !r.is_empty())
+        .unwrap_or_else(|| text.trim().to_string());
+    let answer = ANSWER
+        .captures(text)
+        .map(|c| c[1].trim().to_string())
+        .filter(|a| !a.is_empty());
+    Verdict { winner, rationale, answer }
+}
+
+/// What a voter is asked; `own` is the voter's own answer, which it can't vote for
+pub fn ballot_prompt(mode: ConsensusMode, question: &str, candidates: &[&str], own: usize) -> String {
+    let instructions = match mode {
+        ConsensusMode::Score => format!(
+            "Score every answer except your own (Answer {}) from 1 to 10 on correctness, depth and \
+             practicality. Reply with one line per answer:\nSCORE <answer number>: <score> - <one-sentence reason>",
+            own + 1
+        ),
+        _ => format!(
+            "Vote for the best answer other than your own (Answer {}), judging correctness, depth and \
+             practicality. Reply in this format:\nVOTE: <answer number>\nREASON: <one sentence>",
+            own + 1
+        ),
+    };
+    format!(
+        "Question: {}\n\nThese answers were given independently:\n\n{}\n\n{}",
+        question,
+        candidates_block(candidates),
+        instructions
+    )
+}
+
+/// Read a voter's reply, dropping votes for its own answer or answers that don't exist
+pub fn parse_ballot(mode: ConsensusMode, voter: &str, text: &str, candidate_count: usize, own: usize) -> Ballot {
+    let valid = |n: usize| (1..=candidate_count).contains(&n) && n - 1 != own;
+    let votes = match mode {
+        ConsensusMode::Score => {
+            let mut votes: Vec<Vote> = Vec::new();
+            for c in SCORE_LINE.captures_iter(text) {
+                let (n, score) = match (c[1].parse::<usize>(), c[2].parse::<f64>()) {
+                    (Ok(n), Ok(score)) => (n, score),
+                    _ => continue,
+                };
+                if valid(n) && !votes.iter().any(|v| v.candidate == n - 1) {
+                    votes.push(Vote { candidate: n - 1, score: score.clamp(1.0, 10.0), reason: c[3].trim().to_string() });
+                }
+            }
+            votes
+        }
+        _ => VOTE
+            .captures(text)
+            .and_then(|c| c[1].parse::<usize>().ok())
+            .filter(|n| valid(*n))
+            .map(|n| Vote {
+                candidate: n - 1,
+                score: 1.0,
+                reason: REASON.captures(text).map(|c| c[1].trim().to_string()).unwrap_or_default(),
+            })
+            .into_iter()
+            .collect(),
+    };
+    Ballot { voter: voter.to_string(), votes }
+}
+
+/// Vote totals (majority) or average scores (score) per candidate
+pub fn tally(mode: ConsensusMode, candidate_count: usize, ballots: &[Ballot]) -> Vec<f64> {
+    let mut totals = vec![0.0; candidate_count];
+    let mut counts = vec![0usize; candidate_count];
+    for vote in ballots.iter().flat_map(|b| &b.votes).filter(|v| v.candidate < candidate_count) {
+        totals[vote.candidate] += vote.score;
+        counts[vote.candidate] += 1;
+    }
+    if mode == ConsensusMode::Score {
+        for (total, count) in totals.iter_mut().zip(&counts) {
+            if *count > 0 {
+                *total /= *count as f64;
+            }
+        }
+    }
+    totals
+}
+
+/// The highest-tallied candidate; ties go to the earlier answer. None without any votes.
+pub fn winner(totals: &[f64]) -> Option<usize> {
+    let best = totals.iter().cloned().fold(0.0, f64::max);
+    if best <= 0.0 {
+        return None;
+    }
+    totals.iter().position(|t| *t == best)
+}
+
+/// Why the vote went the way it did, with each voter's reasons
+pub fn rationale(mode: ConsensusMode, names: &[&str], totals: &[f64], ballots: &[Ballot]) -> String {
+    let best = match winner(totals) {
+        Some(best) => best,
+        None => return "No valid votes were cast.".to_string(),
+    };
+    let mut text = match mode {
+        ConsensusMode::Score => format!("Answer {} ({}) had the highest average score, {:.1}/10.", best + 1, names[best], totals[best]),
+        _ => format!(
+            "Answer {} ({}) won with {} of {} votes.",
+            best + 1,
+            names[best],
+            totals[best],
+            ballots.iter().map(|b| b.votes.len()).sum::<usize>()
+        ),
+    };
+    if totals.iter().filter(|t| **t == totals[best]).count() > 1 {
+        text.push_str(" It tied with another answer; ties go to the earlier answer.");
+    }
+    for ballot in ballots {
+        for vote in &ballot.votes {
+            let reason = if vote.reason.is_empty() { String::new() } else { format!(": {}", vote.reason) };
+            let name = names.get(vote.candidate).copied().unwrap_or("?");
+            text.push_str(&match mode {
+                ConsensusMode::Score => format!("\n- {} gave Answer {} ({}) {}/10{}", ballot.voter, vote.candidate + 1, name, vote.score, reason),
+                _ => format!("\n- {} voted for Answer {} ({}){}", ballot.voter, vote.candidate + 1, name, reason),
+            });
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn majority_ballots() -> [Ballot; 3] {
+        [
+            parse_ballot(ConsensusMode::Majority, "A", "VOTE: 2\nREASON: Most concrete.", 3, 0),
+            parse_ballot(ConsensusMode::Majority, "B", "VOTE: 2", 3, 1),
+            parse_ballot(ConsensusMode::Majority, "C", "VOTE: Answer 1\nREASON: Safer.", 3, 2),
+        ]
+    }
+
+    fn score_ballots() -> [Ballot; 2] {
+        [
+            parse_ballot(ConsensusMode::Score, "A", "SCORE 2: 8 - solid\nSCORE 3: 5/10 - vague\nSCORE 1: 10", 3, 0),
+            parse_ballot(ConsensusMode::Score, "B", "SCORE 1: 6 - ok\nSCORE 3: 7 - good", 3, 1),
+        ]
+    }
+
+    #[test]
+    fn test_parse_verdict_with_a_winner() {
+        let verdict = parse_verdict("**WINNER:** Answer 2\nRATIONALE: It covers pricing risk.\nANSWER:\n", 3);
+        assert_eq!(verdict.winner, Some(1));
+        assert_eq!(verdict.rationale, "It covers pricing risk.");
+        assert_eq!(verdict.answer, None);
+    }
+
+    #[test]
+    fn test_parse_merged_verdict() {
+        let merged = parse_verdict("WINNER: MERGED\nRATIONALE: Both are partial.\nANSWER:\nLaunch in Q3 with a pilot.", 2);
+        assert_eq!((merged.winner, merged.answer.as_deref()), (None, Some("Launch in Q3 with a pilot.")));
+        assert_eq!(parse_verdict("WINNER: 7", 2).winner, None);
+    }
+
+    #[test]
+    fn test_majority_ballots() {
+        let majority = majority_ballots();
+        // B can't vote for itself
+        assert!(majority[1].votes.is_empty());
+        assert_eq!(majority[0].votes[0].reason, "Most concrete.");
+    }
+
+    #[test]
+    fn test_majority_tally_breaks_ties_by_order() {
+        let majority = majority_ballots();
+        let totals = tally(ConsensusMode::Majority, 3, &majority);
+        assert_eq!(totals, vec![1.0, 1.0, 0.0]);
+        assert_eq!(winner(&totals), Some(0));
+        assert_eq!(
+            rationale(ConsensusMode::Majority, &["A", "B", "C"], &totals, &majority),
+            "Answer 1 (A) won with 1 of 2 votes. It tied with another answer; ties go to the earlier answer.\n\
+             - A voted for Answer 2 (B): Most concrete.\n- C voted for Answer 1 (A): Safer."
+        );
+    }
+
+    #[test]
+    fn test_score_ballots_skip_self_scores() {
+        let scores = score_ballots();
+        assert_eq!(scores[0].votes.len(), 2);
+        assert_eq!(scores[0].votes[1].reason, "vague");
+    }
+
+    #[test]
+    fn test_score_tally() {
+        let totals = tally(ConsensusMode::Score, 3, &score_ballots());
+        assert_eq!(totals, vec![6.0, 8.0, 6.0]);
+        assert_eq!(winner(&totals), Some(1));
+    }
+
+    #[test]
+    fn test_no_winner_without_votes() {
+        assert_eq!(winner(&[0.0, 0.0]), None);
+    }
+}
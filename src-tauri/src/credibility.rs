@@ -0,0 +1,394 @@
+/// Source credibility for deep research
+///
+/// Each source gets a score from three signals: the reputation of its domain, how recent it
+/// is, and how many sources on other domains corroborate it (overlapping key terms). The
+/// synthesizer sees the score next to each source, and sentences that rest on a single
+/// low-credibility source are listed under "Credibility Notes" in the final report.
+
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use crate::citations::Bibliography;
+use crate::deep_research::SearchResult;
+
+lazy_static::lazy_static! {
+    static ref SENTENCE: Regex = Regex::new(r"[^.!?]+[.!?]*(?:\[\^\d+\])*").unwrap();
+    static ref FOOTNOTE_REF: Regex = Regex::new(r"\[\^(\d+)\]").unwrap();
+}
+
+/// Reputation of well-known domains; subdomains inherit it. Anything unlisted is 0.5.
+const DOMAIN_REPUTATION: &[(&str, f64)] = &[
+    ("who.int", 0.95),
+    ("nature.com", 0.9),
+    ("science.org", 0.9),
+    ("nejm.org", 0.9),
+    ("thelancet.com", 0.9),
+    ("ieee.org", 0.85),
+    ("acm.org", 0.85),
+    ("springer.com", 0.85),
+    ("sciencedirect.com", 0.85),
+    ("ncbi.nlm.nih.gov", 0.9),
+    ("reuters.com", 0.85),
+    ("apnews.com", 0.85),
+    ("bbc.co.uk", 0.8),
+    ("bbc.com", 0.8),
+    ("economist.com", 0.8),
+    ("nytimes.com", 0.8),
+    ("theguardian.com", 0.75),
+    ("britannica.com", 0.8),
+    ("arxiv.org", 0.75),
+    ("wikipedia.org", 0.7),
+    ("developer.mozilla.org", 0.85),
+    ("doc.rust-lang.org", 0.85),
+    ("docs.python.org", 0.85),
+    ("docs.rs", 0.8),
+    ("github.com", 0.6),
+    ("stackoverflow.com", 0.6),
+    ("medium.com", 0.4),
+    ("substack.com", 0.4),
+    ("reddit.com", 0.35),
+    ("blogspot.com", 0.3),
+    ("wordpress.com", 0.3),
+    ("quora.com", 0.25),
+    ("answers.com", 0.2),
+    ("ehow.com", 0.2),
+    ("pinterest.com", 0.15),
+    ("facebook.com", 0.2),
+    ("tiktok.com", 0.15),
+    ("twitter.com", 0.2),
+    ("x.com", 0.2),
+];
+
+/// Top-level domains of institutions (government, universities, international bodies)
+const INSTITUTIONAL_TLDS: &[&str] = &["gov", "edu", "mil", "int"];
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "been", "being", "between", "both", "could", "does", "each", "from", "have", "into",
+    "like", "more", "most", "much", "only", "other", "over", "same", "should", "some", "such", "than", "that",
+    "their", "them", "then", "there", "these", "they", "this", "those", "through", "very", "were", "what", "when",
+    "where", "which", "while", "will", "with", "would", "your",
+];
+
+/// Key terms two sources on different domains must share to corroborate each other
+const MIN_SHARED_TERMS: usize = 6;
+const MIN_SHARED_RATIO: f64 = 0.2;
+/// Corroborating domains beyond this don't raise the score further
+const MAX_CORROBORATIONS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredibilityLevel {
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credibility {
+    /// 0.0 - 1.0
+    pub score: f64,
+    pub level: CredibilityLevel,
+    pub domain_score: f64,
+    pub recency_score: f64,
+    /// Other domains whose content overlaps this source's
+    pub corroborations: usize,
+    pub reasons: Vec<String>,
+}
+
+impl Credibility {
+    /// One-line summary shown to the synthesizer and in credibility notes
+    pub fn label(&self) -> String {
+        let level = match self.level {
+            CredibilityLevel::High => "high",
+            CredibilityLevel::Medium => "medium",
+            CredibilityLevel::Low => "low",
+        };
+        format!("{} ({:.2}; {})", level, self.score, self.reasons.join(", "))
+    }
+}
+
+/// A sentence whose only citation is a low-credibility source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeakClaim {
+    pub sentence: String,
+    pub source: usize,
+}
+
+fn host_of(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.trim_start_matches("www.").to_lowercase()))
+        .unwrap_or_default()
+}
+
+pub fn domain_reputation(host: &str) -> f64 {
+    let listed = DOMAIN_REPUTATION
+        .iter()
+        .filter(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .max_by_key(|(domain, _)| domain.len());
+    if let Some((_, score)) = listed {
+        return *score;
+    }
+    let tld = host.rsplit('.').next().unwrap_or_default();
+    // ac.uk / edu.au style academic and government second-level domains count too
+    let second_level = host.rsplit('.').nth(1).unwrap_or_default();
+    if INSTITUTIONAL_TLDS.contains(&tld) || (tld.len() == 2 && ["gov", "ac", "edu"].contains(&second_level)) {
+        0.85
+    } else if host.starts_with("docs.") {
+        0.7
+    } else {
+        0.5
+    }
+}
+
+/// Parse the leading date of Tavily/Brave/SearXNG published dates
+fn parse_published(published: &str) -> Option<NaiveDate> {
+    let published = published.trim();
+    NaiveDate::parse_from_str(published.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .or_else(|| chrono::DateTime::parse_from_rfc2822(published).ok().map(|d| d.date_naive()))
+}
+
+/// 1.0 for the last year, decaying to 0.4 for anything over five years old; 0.5 when unknown
+pub fn recency_score(published: Option<&str>, today: NaiveDate) -> (f64, Option<NaiveDate>) {
+    match published.and_then(parse_published) {
+        Some(date) => {
+            let years = (today - date).num_days() as f64 / 365.0;
+            let score = if years <= 1.0 {
+                1.0
+            } else if years <= 3.0 {
+                0.8
+            } else if years <= 5.0 {
+                0.6
+            } else {
+                0.4
+            };
+            (score, Some(date))
+        }
+        None => (0.5, None),
+    }
+}
+
+fn key_terms(source: &SearchResult) -> HashSet<String> {
+    format!("{} {}", source.title, source.content)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 4)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Number of other domains whose sources share enough key terms with each source
+pub fn corroboration_counts(sources: &[SearchResult]) -> Vec<usize> {
+    let hosts: Vec<String> = sources.iter().map(|s| host_of(&s.url)).collect();
+    let terms: Vec<HashSet<String>> = sources.iter().map(key_terms).collect();
+
+    (0..sources.len())
+        .map(|i| {
+            let mut domains = HashSet::new();
+            for j in 0..sources.len() {
+                if hosts[i] == hosts[j] || terms[i].is_empty() || terms[j].is_empty() {
+                    continue;
+                }
+                let shared = terms[i].intersection(&terms[j]).count();
+                let ratio = shared as f64 / terms[i].len().min(terms[j].len()) as f64;
+                if shared >= MIN_SHARED_TERMS && ratio >= MIN_SHARED_RATIO {
+                    domains.insert(hosts[j].as_str());
+                }
+            }
+            domains.len()
+        })
+        .collect()
+}
+
+/// Credibility of every source, keyed by URL
+pub fn score_sources(sources: &[SearchResult], today: NaiveDate) -> HashMap<String, Credibility> {
+    let corroborations = corroboration_counts(sources);
+    sources
+        .iter()
+        .zip(corroborations)
+        .map(|(source, corroborations)| {
+            let domain_score = domain_reputation(&host_of(&source.url));
+            let (recency, date) = recency_score(source.published_date.as_deref(), today);
+            let score = 0.5 * domain_score
+                + 0.2 * recency
+                + 0.3 * corroborations.min(MAX_CORROBORATIONS) as f64 / MAX_CORROBORATIONS as f64;
+
+            let mut reasons = vec![if domain_score >= 0.7 {
+                "reputable domain".to_string()
+            } else if domain_score < 0.4 {
+                "low-reputation domain".to_string()
+            } else {
+                "unrated domain".to_string()
+            }];
+            reasons.push(match date {
+                Some(date) => format!("published {}", date.format("%Y-%m-%d")),
+                None => "date unknown".to_string(),
+            });
+            reasons.push(match corroborations {
+                0 => "not corroborated".to_string(),
+                1 => "corroborated by 1 other source".to_string(),
+                n => format!("corroborated by {} other sources", n),
+            });
+
+            let level = if score >= 0.65 {
+                CredibilityLevel::High
+            } else if score >= 0.4 {
+                CredibilityLevel::Medium
+            } else {
+                CredibilityLevel::Low
+            };
+            (
+                source.url.clone(),
+                Credibility { score, level, domain_score, recency_score: recency, corroborations, reasons },
+            )
+        })
+        .collect()
+}
+
+pub const CREDIBILITY_INSTRUCTIONS: &str = "Each source lists its credibility (high, medium or low). \
+Base the report on high and medium credibility sources, prefer claims several sources agree on, \
+and when a claim rests only on a low-credibility source, say so in the text (e.g. \"one forum post claims...\").";
+
+/// Sentences citing exactly one source where that source is low-credibility
+pub fn flag_weak_claims(report: &str, bib: &Bibliography) -> Vec<WeakClaim> {
+    let low: HashSet<usize> = bib
+        .entries
+        .iter()
+        .filter(|e| e.credibility.as_ref().map_or(false, |c| c.level == CredibilityLevel::Low))
+        .map(|e| e.number)
+        .collect();
+    if low.is_empty() {
+        return Vec::new();
+    }
+
+    let mut flagged = Vec::new();
+    let mut in_code = false;
+    for line in report.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || line.starts_with('#') || line.starts_with("[^") {
+            continue;
+        }
+        for sentence in SENTENCE.find_iter(line) {
+            let cited: BTreeSet<usize> = FOOTNOTE_REF
+                .captures_iter(sentence.as_str())
+                .filter_map(|c| c[1].parse().ok())
+                .collect();
+            if cited.len() == 1 {
+                let source = *cited.iter().next().unwrap();
+                if low.contains(&source) {
+                    let text = FOOTNOTE_REF.replace_all(sentence.as_str(), "");
+                    let text = text.trim().trim_start_matches(|c: char| c == '-' || c == '*' || c == '>').trim();
+                    flagged.push(WeakClaim { sentence: text.to_string(), source });
+                }
+            }
+        }
+    }
+    flagged
+}
+
+/// Append a "Credibility Notes" section listing weak claims
+pub fn with_credibility_notes(report: &str, flagged: &[WeakClaim], bib: &Bibliography) -> String {
+    if flagged.is_empty() {
+        return report.to_string();
+    }
+    let mut out = format!(
+        "{}\n\n## Credibility Notes\n\nThese claims rest on a single low-credibility source and should be verified:\n\n",
+        report.trim_end()
+    );
+    for claim in flagged {
+        let why = bib
+            .entries
+            .iter()
+            .find(|e| e.number == claim.source)
+            .and_then(|e| e.credibility.as_ref())
+            .map(|c| c.label())
+            .unwrap_or_default();
+        out.push_str(&format!("- \"{}\"[^{}] ({})\n", claim.sentence, claim.source, why));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(url: &str, content: &str, published: Option<&str>) -> SearchResult {
+        SearchResult {
+            title: String::new(),
+            url: url.to_string(),
+            content: content.to_string(),
+            score: 0.5,
+            published_date: published.map(str::to_string),
+        }
+    }
+
+    fn sources() -> Vec<SearchResult> {
+        let shared = "ownership borrowing lifetimes compiler memory safety garbage collector references";
+        vec![
+            source("https://doc.rust-lang.org/book/ch04.html", shared, Some("2025-01-10")),
+            source("https://www.nature.com/articles/rust", shared, Some("2019-03-01T00:00:00Z")),
+            source("https://www.pinterest.com/pin/123", "rust is secretly written in javascript", None),
+        ]
+    }
+
+    fn scores() -> HashMap<String, Credibility> {
+        score_sources(&sources(), NaiveDate::from_ymd_opt(2025, 6, 1).unwrap())
+    }
+
+    const REPORT: &str = "# Rust\n\nRust checks borrows at compile time[^1][^3]. Rust is written in JavaScript[^3]. \
+                          Memory safety is guaranteed[^2].";
+
+    fn bibliography() -> Bibliography {
+        let mut bib = Bibliography::default();
+        bib.format_sources(&sources(), &scores());
+        bib
+    }
+
+    #[test]
+    fn test_corroborated_reputable_source_scores_high() {
+        let scores = scores();
+        let book = &scores["https://doc.rust-lang.org/book/ch04.html"];
+        assert_eq!(book.level, CredibilityLevel::High);
+        assert_eq!(book.corroborations, 1);
+        assert_eq!(book.recency_score, 1.0);
+    }
+
+    #[test]
+    fn test_old_sources_score_lower_on_recency() {
+        assert_eq!(scores()["https://www.nature.com/articles/rust"].recency_score, 0.4);
+    }
+
+    #[test]
+    fn test_uncorroborated_weak_source_scores_low() {
+        let scores = scores();
+        let pin = &scores["https://www.pinterest.com/pin/123"];
+        assert_eq!(pin.level, CredibilityLevel::Low);
+        assert_eq!(pin.corroborations, 0);
+    }
+
+    #[test]
+    fn test_domain_reputation_of_academic_subdomains() {
+        assert_eq!(domain_reputation("cs.ox.ac.uk"), 0.85);
+    }
+
+    #[test]
+    fn test_flag_weak_claims() {
+        let flagged = flag_weak_claims(REPORT, &bibliography());
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].sentence, "Rust is written in JavaScript.");
+        assert_eq!(flagged[0].source, 3);
+    }
+
+    #[test]
+    fn test_credibility_notes() {
+        let bib = bibliography();
+        let flagged = flag_weak_claims(REPORT, &bib);
+        assert!(with_credibility_notes(REPORT, &flagged, &bib)
+            .contains("## Credibility Notes\n\nThese claims rest on a single low-credibility source and should be verified:\n\n- \"Rust is written in JavaScript.\"[^3] (low"));
+    }
+}
@@ -0,0 +1,191 @@
+/// Daily notes / journaling
+///
+/// One note per day under `journal/`, created from the user's `daily-note` template
+/// (see templates.rs) or the built-in one. Log entries are
+/// appended under the note's `## Log` heading and offered to the TKG, where WAMA decides
+/// whether they are worth remembering.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use chrono::NaiveDate;
+
+pub const JOURNAL_FOLDER: &str = "journal";
+const LOG_HEADING: &str = "## Log";
+
+const DEFAULT_TEMPLATE: &str = "---
+title: \"{{date}}\"
+created: {{date}}
+tags: [journal]
+---
+
+# {{weekday}}, {{date}}
+
+## Goals
+
+## Log
+
+## Reflections
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyNote {
+    pub path: String,
+    pub date: String,
+    pub created: bool,
+}
+
+fn parse_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d").map_err(|e| format!("Invalid date '{}': {}", d, e)),
+        None => Ok(chrono::Local::now().date_naive()),
+    }
+}
+
+pub fn daily_note_path(kb_root: &Path, date: NaiveDate) -> PathBuf {
+    kb_root.join(JOURNAL_FOLDER).join(format!("{}.md", date.format("%Y-%m-%d")))
+}
+
+pub fn render_daily_template(template: &str, date: NaiveDate) -> String {
+    let vars = crate::templates::builtin_vars(date.and_hms_opt(0, 0, 0).unwrap_or_default());
+    crate::templates::render(template, &vars).0
+}
+
+fn daily_template(app_handle: Option<&tauri::AppHandle>) -> String {
+    crate::templates::load_template(app_handle, "daily-note").unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+}
+
+/// Insert `- HH:MM entry` at the end of the `## Log` section, adding the section if missing
+pub fn append_log_entry(content: &str, time: &str, entry: &str) -> String {
+    let line = format!("- {} {}", time, entry.trim().replace('\n', " "));
+    let lines: Vec<&str> = content.lines().collect();
+
+    let log_start = match lines.iter().position(|l| l.trim() == LOG_HEADING) {
+        Some(i) => i,
+        None => {
+            let mut out = content.trim_end().to_string();
+            out.push_str(&format!("\n\n{}\n\n{}\n", LOG_HEADING, line));
+            return out;
+        }
+    };
+
+    // Section ends at the next heading of the same or higher level
+    let section_end = lines[log_start + 1..]
+        .iter()
+        .position(|l| l.starts_with("# ") || l.starts_with("## "))
+        .map(|i| log_start + 1 + i)
+        .unwrap_or(lines.len());
+
+    // Place the entry after the last non-blank line of the section
+    let mut insert_at = section_end;
+    while insert_at > log_start + 1 && lines[insert_at - 1].trim().is_empty() {
+        insert_at -= 1;
+    }
+
+    let mut out: Vec<String> = lines[..insert_at].iter().map(|l| l.to_string()).collect();
+    if insert_at == log_start + 1 {
+        out.push(String::new());
+    }
+    out.push(line);
+    if section_end < lines.len() {
+        out.push(String::new());
+    }
+    out.extend(lines[section_end..].iter().map(|l| l.to_string()));
+
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Create the note for `date` from the template if it doesn't exist yet
+pub fn ensure_daily_note(app_handle: Option<&tauri::AppHandle>, kb_root: &Path, date: NaiveDate) -> Result<DailyNote, String> {
+    let path = daily_note_path(kb_root, date);
+    let created = !path.exists();
+
+    if created {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = render_daily_template(&daily_template(app_handle), date);
+        std::fs::write(&path, content).map_err(|e| format!("Failed to create daily note: {}", e))?;
+        tracing::info!("📓 Created daily note {}", path.display());
+    }
+
+    Ok(DailyNote {
+        path: crate::kb_index::path_key(kb_root, &path),
+        date: date.format("%Y-%m-%d").to_string(),
+        created,
+    })
+}
+
+/// Append an entry to today's note and submit it to the TKG for WAMA evaluation
+pub async fn log_entry(app_handle: Option<&tauri::AppHandle>, entry: &str, user_id: &str) -> Result<serde_json::Value, String> {
+    if entry.trim().is_empty() {
+        return Err("Entry is empty".to_string());
+    }
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let now = chrono::Local::now();
+    let note = ensure_daily_note(app_handle, &kb_root, now.date_naive())?;
+    let full_path = kb_root.join(&note.path);
+
+    let content = std::fs::read_to_string(&full_path).map_err(|e| e.to_string())?;
+    crate::note_versions::record_before_write(app_handle, &kb_root, &full_path, "journal");
+    let updated = append_log_entry(&content, &now.format("%H:%M").to_string(), entry);
+    std::fs::write(&full_path, updated).map_err(|e| e.to_string())?;
+
+    let memory = format!("Journal {}: {}", note.date, entry.trim());
+    let wama = match crate::tkg::tkg_store_knowledge(memory, "MEMORY".to_string(), 0.5, user_id.to_string()).await {
+        Ok(result) => serde_json::from_str(&result).unwrap_or(serde_json::Value::String(result)),
+        // TKG not configured or WAMA rejected the entry - the journal entry itself is still saved
+        Err(e) => serde_json::json!({ "success": false, "message": e }),
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "path": note.path,
+        "date": note.date,
+        "wama": wama
+    }))
+}
+
+#[tauri::command]
+pub async fn create_daily_note(app_handle: tauri::AppHandle, date: Option<String>) -> Result<DailyNote, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    ensure_daily_note(Some(&app_handle), &kb_root, parse_date(date.as_deref())?)
+}
+
+#[tauri::command]
+pub async fn log_daily_entry(
+    app_handle: tauri::AppHandle,
+    entry: String,
+    user_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    log_entry(Some(&app_handle), &entry, user_id.as_deref().unwrap_or("guest")).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn march_first() -> String {
+        render_daily_template(DEFAULT_TEMPLATE, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())
+    }
+
+    #[test]
+    fn test_daily_template_heading() {
+        assert!(march_first().contains("# Friday, 2024-03-01"));
+    }
+
+    #[test]
+    fn test_log_entries_land_in_log_section() {
+        let once = append_log_entry(&march_first(), "09:30", "Read the SM-2 paper");
+        let twice = append_log_entry(&once, "11:00", "Wrote flashcards");
+        assert!(twice.contains("## Log\n\n- 09:30 Read the SM-2 paper\n- 11:00 Wrote flashcards\n\n## Reflections"));
+    }
+
+    #[test]
+    fn test_log_section_is_added_when_missing() {
+        let bare = append_log_entry("# Notes\n", "08:00", "Started");
+        assert_eq!(bare, "# Notes\n\n## Log\n\n- 08:00 Started\n");
+    }
+}
@@ -1,10 +1,130 @@
 use rusqlite::{Connection, Result, params};
-use std::path::Path;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::Project;
 
-pub fn init_db(path: &Path) -> Result<Connection> {
+/// How long a connection waits for another one's write lock before failing with SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Statements each connection keeps compiled for `prepare_cached`
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Idle connections kept open per database file
+const POOL_IDLE_PER_FILE: usize = 4;
+
+lazy_static::lazy_static! {
+    /// Idle connections by database file. A file has an entry once its schema is in place.
+    static ref POOL: Mutex<HashMap<PathBuf, FilePool>> = Mutex::new(HashMap::new());
+}
+
+/// Numbers each file's entry in the pool, so a connection from before an `evict` is told apart
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+struct FilePool {
+    generation: u64,
+    idle: Vec<Connection>,
+}
+
+/// A connection from the pool, handed back to it when dropped
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    path: PathBuf,
+    generation: u64,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let conn = match self.conn.take() {
+            // A connection left inside a transaction isn't fit for the next caller
+            Some(conn) if conn.is_autocommit() => conn,
+            _ => return,
+        };
+        if let Ok(mut pool) = POOL.lock() {
+            // Not taken back when the file was evicted since this connection was handed out
+            if let Some(file) = pool.get_mut(&self.path).filter(|file| file.generation == self.generation) {
+                if file.idle.len() < POOL_IDLE_PER_FILE {
+                    file.idle.push(conn);
+                }
+            }
+        }
+    }
+}
+
+/// A connection to the database at `path`, reusing an idle one when there is one, so its
+/// cached statements survive between commands. `init` (opening the file and creating its
+/// tables) runs only for the first connection to each file; later ones are just `open`ed.
+pub fn pooled(path: &Path, init: fn(&Path) -> Result<Connection>) -> Result<PooledConnection> {
+    let known = POOL.lock().map(|mut pool| pool.get_mut(path).map(|file| (file.generation, file.idle.pop()))).unwrap_or(None);
+    let (conn, generation) = match known {
+        Some((generation, Some(conn))) => (conn, generation),
+        Some((generation, None)) => (open(path)?, generation),
+        None => {
+            let conn = init(path)?;
+            let generation = NEXT_GENERATION.fetch_add(1, Ordering::SeqCst);
+            let mut pool = POOL.lock().unwrap_or_else(|e| e.into_inner());
+            let file = pool.entry(path.to_path_buf()).or_insert(FilePool { generation, idle: Vec::new() });
+            (conn, file.generation)
+        }
+    };
+    Ok(PooledConnection { conn: Some(conn), path: path.to_path_buf(), generation })
+}
+
+/// Forget the database at `path`: its idle connections are closed, the ones in use aren't
+/// taken back, and the next `pooled` call runs `init` again. Call it before deleting or
+/// replacing the file, or pooled connections keep writing to the old one.
+pub fn evict(path: &Path) {
+    POOL.lock().unwrap_or_else(|e| e.into_inner()).remove(path);
+}
+
+/// Open an app database with the settings every connection should have: WAL, so readers and
+/// a writer don't block each other, a busy timeout instead of failing at once when another
+/// connection is writing, and room for the statements used with `prepare_cached`
+pub fn open(path: &Path) -> Result<Connection> {
     let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    // Safe with WAL: a crash can lose the last commits but never corrupts the file
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    Ok(conn)
+}
+
+/// Run database work on the blocking pool so a slow write never stalls the async executor.
+/// The work is timed as a `db` span labelled with the caller's file and line.
+#[track_caller]
+pub fn blocking<T, F>(work: F) -> impl std::future::Future<Output = std::result::Result<T, String>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::result::Result<T, String> + Send + 'static,
+{
+    let caller = std::panic::Location::caller();
+    let span = tracing::info_span!("db", op = %format!("{}:{}", caller.file(), caller.line()));
+    async move {
+        let _span = span;
+        tauri::async_runtime::spawn_blocking(work).await.map_err(|e| e.to_string())?
+    }
+}
+
+pub fn init_db(path: &Path) -> Result<Connection> {
+    let conn = open(path)?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS projects (
@@ -41,26 +161,28 @@ pub fn init_db(path: &Path) -> Result<Connection> {
         [],
     )?;
 
+    crate::milestones::init_milestone_table(&conn)?;
+
     Ok(conn)
 }
 
 pub fn insert_project(conn: &Connection, project: &Project) -> Result<i64> {
-    conn.execute(
+    let mut stmt = conn.prepare_cached(
         "INSERT INTO projects (name, category, description, stage)
          VALUES (?1, ?2, ?3, ?4)",
-        params![
-            &project.name,
-            &project.category,
-            &project.description,
-            &project.stage
-        ],
     )?;
+    stmt.execute(params![
+        &project.name,
+        &project.category,
+        &project.description,
+        &project.stage
+    ])?;
 
     Ok(conn.last_insert_rowid())
 }
 
 pub fn get_all_projects(conn: &Connection) -> Result<Vec<Project>> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare_cached(
         "SELECT id, name, category, description, stage FROM projects ORDER BY created_at DESC"
     )?;
 
@@ -76,3 +198,85 @@ pub fn get_all_projects(conn: &Connection) -> Result<Vec<Project>> {
 
     projects.collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let writer = open(&path).unwrap();
+        let journal: String = writer.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(journal, "wal");
+        let timeout: i64 = writer.query_row("PRAGMA busy_timeout", [], |row| row.get(0)).unwrap();
+        assert_eq!(timeout, 5000);
+
+        // With WAL a reader isn't blocked by an open write transaction
+        writer.execute_batch("CREATE TABLE notes (title TEXT); INSERT INTO notes VALUES ('a'); BEGIN IMMEDIATE; INSERT INTO notes VALUES ('b');").unwrap();
+        let reader = open(&path).unwrap();
+        let count: i64 = reader.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+        writer.execute_batch("COMMIT").unwrap();
+    }
+
+    #[test]
+    fn test_pooled_reuses_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let first = pooled(&path, init_db).unwrap();
+        first.execute("CREATE TEMP TABLE marker (id INTEGER)", []).unwrap();
+        drop(first);
+
+        // The temp table only exists on the connection that made it
+        let again = pooled(&path, init_db).unwrap();
+        again.execute("INSERT INTO marker VALUES (1)", []).unwrap();
+        let other = pooled(&path, init_db).unwrap();
+        assert!(other.execute("INSERT INTO marker VALUES (1)", []).is_err());
+    }
+
+    #[test]
+    fn test_pooled_initializes_each_file_once() {
+        fn failing_init(_: &Path) -> Result<Connection> {
+            Err(rusqlite::Error::InvalidQuery)
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        drop(pooled(&path, init_db).unwrap());
+        // Known files are opened without running init again
+        assert!(pooled(&path, failing_init).is_ok());
+        assert!(pooled(&dir.path().join("other.db"), failing_init).is_err());
+    }
+
+    #[test]
+    fn test_evict_reinitializes_a_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let in_use = pooled(&path, init_db).unwrap();
+        drop(pooled(&path, init_db).unwrap());
+
+        evict(&path);
+        for file in ["data.db", "data.db-wal", "data.db-shm"] {
+            let _ = std::fs::remove_file(dir.path().join(file));
+        }
+        drop(in_use);
+        let conn = pooled(&path, init_db).unwrap();
+        conn.execute("INSERT INTO projects (name) VALUES ('Orbit')", []).unwrap();
+        drop(conn);
+
+        // The write landed in the new file, not the deleted one
+        let count: i64 = Connection::open(&path).unwrap().query_row("SELECT COUNT(*) FROM projects", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_pooled_drops_connections_left_in_a_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let conn = pooled(&path, init_db).unwrap();
+        conn.execute_batch("BEGIN").unwrap();
+        drop(conn);
+        assert!(pooled(&path, init_db).unwrap().is_autocommit());
+    }
+}
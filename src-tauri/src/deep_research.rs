@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
-use reqwest::Client;
 use anyhow::{Result, Context};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tracing::Instrument;
+use crate::report_templates::ReportTemplate;
+use crate::search_providers::SearchProvider;
+
+/// Follow-up queries carried into the next round when depth > 1
+const FOLLOW_UP_QUERIES: usize = 3;
+/// Extracted page text kept per source (~6k tokens)
+const MAX_PAGE_CHARS: usize = 24_000;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SearchResult {
@@ -10,93 +19,542 @@ pub struct SearchResult {
     pub url: String,
     pub content: String,
     pub score: f64,
+    #[serde(default)]
+    pub published_date: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TavilyResponse {
-    pub results: Vec<SearchResult>,
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResearchPhase {
+    Planning,
+    Searching,
+    Reading,
+    Extracting,
+    Budget,
+    Synthesizing,
+    Revising,
+    Complete,
+    Failed,
 }
 
+/// Payload of the `research-progress` event. Events with a `sub_topic` belong to that
+/// branch of the progress tree; run-level events (and single-topic runs) leave it empty.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ResearchStep {
-    pub step_type: String, // "planning", "searching", "analyzing", "synthesizing"
-    pub description: String,
+pub struct ResearchProgress {
+    pub run_id: String,
+    pub phase: ResearchPhase,
+    pub sub_topic: Option<String>,
+    pub source_url: Option<String>,
+    pub message: String,
     pub details: Option<String>,
+    /// Estimated tokens of source text gathered so far in the run
+    pub tokens_used: usize,
+    pub percent_complete: u8,
 }
 
-pub struct DeepResearchAgent {
-    tavily_api_key: String,
-    client: Client,
+/// Part of the progress bar for gathering sources; synthesis and revision fill the rest
+const RESEARCH_PERCENT: f64 = 80.0;
+/// Part of a topic's progress spent searching; reading full pages is the remainder
+const SEARCH_FRACTION: f64 = 0.8;
+
+/// Limits for a research run, set from the deep_research tool arguments
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResearchConfig {
+    /// Search rounds; rounds after the first follow up on what the previous one found
+    pub depth: u32,
+    pub max_sources_per_round: usize,
+    /// Search API calls across the whole run (shared by parallel sub-agents)
+    pub max_api_calls: u32,
+    /// Estimated tokens of gathered source text handed to the synthesizer
+    pub max_tokens: usize,
+    /// Top sources fetched and extracted in full instead of using search snippets
+    pub full_pages: usize,
+    /// Structure of the synthesized report
+    #[serde(default)]
+    pub template: ReportTemplate,
 }
 
-impl DeepResearchAgent {
-    pub fn new(api_key: String) -> Self {
+impl Default for ResearchConfig {
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            max_sources_per_round: 5,
+            max_api_calls: 10,
+            max_tokens: 50_000,
+            full_pages: 3,
+            template: ReportTemplate::Standard,
+        }
+    }
+}
+
+impl ResearchConfig {
+    pub fn from_args(args: &serde_json::Value) -> Self {
+        let defaults = Self::default();
+        let get = |key: &str| args.get(key).and_then(|v| v.as_u64());
         Self {
-            tavily_api_key: api_key,
-            client: Client::new(),
+            depth: get("depth").map(|d| d.clamp(1, 5) as u32).unwrap_or(defaults.depth),
+            max_sources_per_round: get("max_sources_per_round").map(|n| n.clamp(1, 20) as usize).unwrap_or(defaults.max_sources_per_round),
+            max_api_calls: get("max_api_calls").map(|n| n.clamp(1, 100) as u32).unwrap_or(defaults.max_api_calls),
+            max_tokens: get("max_tokens").map(|n| n.clamp(1_000, 500_000) as usize).unwrap_or(defaults.max_tokens),
+            full_pages: get("full_pages").map(|n| n.min(10) as usize).unwrap_or(defaults.full_pages),
+            template: args.get("template")
+                .and_then(|v| v.as_str())
+                .and_then(ReportTemplate::from_name)
+                .unwrap_or(defaults.template),
         }
     }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResearchUsage {
+    pub api_calls: u32,
+    pub sources: usize,
+    pub estimated_tokens: usize,
+    pub rounds: u32,
+    pub pages_extracted: usize,
+    /// True if the run stopped early because a limit was hit
+    pub budget_exhausted: bool,
+}
 
-    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
-        let url = "https://api.tavily.com/search";
-        let body = serde_json::json!({
-            "api_key": self.tavily_api_key,
-            "query": query,
-            "search_depth": "advanced",
-            "include_answer": true,
-            "max_results": 5
-        });
+/// Emits `research-progress` events for a run, tracking how far each research topic is
+#[derive(Clone)]
+pub struct ProgressReporter {
+    app_handle: Option<tauri::AppHandle>,
+    run_id: String,
+    budget: ResearchBudget,
+    /// Completion of each research topic (0.0 - 1.0)
+    topics: Arc<std::sync::Mutex<Vec<f64>>>,
+    /// Topic index and the `sub_topic` label events from this reporter carry
+    topic: Option<(usize, Option<String>)>,
+}
 
-        let resp = self.client.post(url)
-            .json(&body)
-            .send()
-            .await
-            .context("Failed to send request to Tavily")?
-            .json::<TavilyResponse>()
+impl ProgressReporter {
+    pub fn new(app_handle: Option<tauri::AppHandle>, run_id: &str, budget: ResearchBudget, topic_count: usize) -> Self {
+        Self {
+            app_handle,
+            run_id: run_id.to_string(),
+            budget,
+            topics: Arc::new(std::sync::Mutex::new(vec![0.0; topic_count])),
+            topic: None,
+        }
+    }
+
+    /// Reporter for one research topic; `label` is None when the run has a single topic
+    pub fn for_topic(&self, index: usize, label: Option<String>) -> Self {
+        Self { topic: Some((index, label)), ..self.clone() }
+    }
+
+    /// Record how far this reporter's topic is
+    pub fn advance(&self, fraction: f64) {
+        if let (Some((index, _)), Ok(mut topics)) = (&self.topic, self.topics.lock()) {
+            if let Some(done) = topics.get_mut(*index) {
+                *done = fraction.clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    /// Percent of the run done, from the research topics' completion
+    pub fn research_percent(&self) -> u8 {
+        let topics = match self.topics.lock() {
+            Ok(topics) => topics.clone(),
+            Err(_) => return 0,
+        };
+        if topics.is_empty() {
+            return RESEARCH_PERCENT as u8;
+        }
+        (RESEARCH_PERCENT * topics.iter().sum::<f64>() / topics.len() as f64).round() as u8
+    }
+
+    pub async fn emit(&self, phase: ResearchPhase, message: String, source_url: Option<&str>, details: Option<String>) {
+        self.emit_at(phase, message, source_url, details, self.research_percent()).await;
+    }
+
+    /// Emit with an explicit percentage (synthesis and later stages)
+    pub async fn emit_at(&self, phase: ResearchPhase, message: String, source_url: Option<&str>, details: Option<String>, percent: u8) {
+        let event = ResearchProgress {
+            run_id: self.run_id.clone(),
+            phase,
+            sub_topic: self.topic.as_ref().and_then(|(_, label)| label.clone()),
+            source_url: source_url.map(|u| u.to_string()),
+            message,
+            details,
+            tokens_used: self.budget.usage().await.estimated_tokens,
+            percent_complete: percent.min(100),
+        };
+        if let Some(handle) = &self.app_handle {
+            let _ = tauri::Manager::emit_all(handle, "research-progress", event);
+        }
+    }
+}
+
+/// Budget shared between every agent working on one research run
+#[derive(Clone)]
+pub struct ResearchBudget {
+    pub config: ResearchConfig,
+    usage: Arc<Mutex<ResearchUsage>>,
+}
+
+impl ResearchBudget {
+    pub fn new(config: ResearchConfig) -> Self {
+        Self { config, usage: Arc::new(Mutex::new(ResearchUsage::default())) }
+    }
+
+    /// Reserve one search API call, or mark the budget exhausted
+    pub async fn try_api_call(&self) -> bool {
+        let mut usage = self.usage.lock().await;
+        if usage.api_calls >= self.config.max_api_calls {
+            usage.budget_exhausted = true;
+            return false;
+        }
+        usage.api_calls += 1;
+        true
+    }
+
+    /// Account for a source of `tokens` estimated tokens, or mark the budget exhausted
+    pub async fn try_add_source(&self, tokens: usize) -> bool {
+        let added = self.try_add_tokens(tokens).await;
+        if added {
+            self.usage.lock().await.sources += 1;
+        }
+        added
+    }
+
+    /// Account for `tokens` more estimated tokens, or mark the budget exhausted
+    pub async fn try_add_tokens(&self, tokens: usize) -> bool {
+        let mut usage = self.usage.lock().await;
+        if usage.estimated_tokens + tokens > self.config.max_tokens {
+            usage.budget_exhausted = true;
+            return false;
+        }
+        usage.estimated_tokens += tokens;
+        true
+    }
+
+    async fn record_page_extracted(&self) {
+        self.usage.lock().await.pages_extracted += 1;
+    }
+
+    async fn record_round(&self, round: u32) {
+        let mut usage = self.usage.lock().await;
+        usage.rounds = usage.rounds.max(round);
+    }
+
+    pub async fn usage(&self) -> ResearchUsage {
+        self.usage.lock().await.clone()
+    }
+}
+
+/// Rough token count (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() + 3) / 4
+}
+
+/// Folder in app data holding a research run's report, bibliography and checkpoints
+pub fn research_run_dir(app_handle: Option<&tauri::AppHandle>, run_id: &str) -> Option<std::path::PathBuf> {
+    let data_dir = match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    }?;
+    Some(data_dir.join("research_runs").join(run_id))
+}
+
+/// Manifest of a research run (`run.json`), enough to resume it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResearchRun {
+    pub run_id: String,
+    pub topic: String,
+    /// Researched in parallel when non-empty; otherwise `topic` itself is researched
+    pub sub_topics: Vec<String>,
+    pub config: ResearchConfig,
+    /// "running", "incomplete" (some sub-topics failed), "failed" or "complete"
+    pub status: String,
+    pub created_at: String,
+    /// Run this one repeats (rerun_research)
+    #[serde(default)]
+    pub rerun_of: Option<String>,
+    /// arXiv ids, PDF URLs or paths ingested as extra sources
+    #[serde(default)]
+    pub papers: Vec<String>,
+}
+
+/// Sources one sub-agent gathered, written as soon as it finishes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Checkpoint {
+    pub sub_topic: String,
+    pub sources: Vec<SearchResult>,
+    pub completed_at: String,
+}
+
+impl ResearchRun {
+    pub fn new(topic: String, sub_topics: Vec<String>, config: ResearchConfig) -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            topic,
+            sub_topics,
+            config,
+            status: "running".to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            rerun_of: None,
+            papers: Vec::new(),
+        }
+    }
+
+    pub fn is_parallel(&self) -> bool {
+        !self.sub_topics.is_empty()
+    }
+
+    /// Topics handed to research agents, in checkpoint order
+    pub fn research_topics(&self) -> Vec<String> {
+        if self.is_parallel() { self.sub_topics.clone() } else { vec![self.topic.clone()] }
+    }
+
+    pub fn save(&self, run_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(run_dir)?;
+        write_atomic(&run_dir.join("run.json"), &serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(run_dir: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(run_dir.join("run.json"))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+/// Write via a temp file so a crash never leaves a half-written checkpoint
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(tmp, path)
+}
+
+fn checkpoint_path(run_dir: &Path, index: usize) -> std::path::PathBuf {
+    run_dir.join("checkpoints").join(format!("{}.json", index))
+}
+
+pub fn save_checkpoint(run_dir: &Path, index: usize, sub_topic: &str, sources: &[SearchResult]) -> std::io::Result<()> {
+    let path = checkpoint_path(run_dir, index);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let checkpoint = Checkpoint {
+        sub_topic: sub_topic.to_string(),
+        sources: sources.to_vec(),
+        completed_at: chrono::Utc::now().to_rfc3339(),
+    };
+    write_atomic(&path, &serde_json::to_string(&checkpoint)?)
+}
+
+/// Completed sub-topics of a run, by position in `topics`
+pub fn load_checkpoints(run_dir: &Path, topics: &[String]) -> HashMap<usize, Vec<SearchResult>> {
+    topics
+        .iter()
+        .enumerate()
+        .filter_map(|(i, topic)| {
+            let text = std::fs::read_to_string(checkpoint_path(run_dir, i)).ok()?;
+            let checkpoint: Checkpoint = serde_json::from_str(&text).ok()?;
+            (checkpoint.sub_topic == *topic).then_some((i, checkpoint.sources))
+        })
+        .collect()
+}
+
+/// Full text of a source: papers (arXiv, PDF) are ingested, anything else is extracted as a web page
+async fn fetch_full_text(url: &str) -> Result<String> {
+    if crate::papers::is_paper_url(url) {
+        Ok(crate::papers::ingest(url).await?.to_markdown())
+    } else {
+        Ok(crate::web_extract::fetch_page(url).await?.markdown)
+    }
+}
+
+pub struct DeepResearchAgent {
+    provider: Box<dyn SearchProvider>,
+}
+
+impl DeepResearchAgent {
+    pub fn new(provider: Box<dyn SearchProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>> {
+        let response = self
+            .provider
+            .search(query, max_results)
+            .instrument(tracing::info_span!("search", kind = self.provider.name()))
             .await
-            .context("Failed to parse Tavily response")?;
-
-        Ok(resp.results)
-    }
-
-    pub async fn research_topic<F>(&self, topic: &str, depth: u32, on_progress: F) -> Result<String>
-    where
-        F: Fn(ResearchStep) + Send + Sync + 'static,
-    {
-        // 1. Plan
-        on_progress(ResearchStep {
-            step_type: "planning".to_string(),
-            description: format!("Planning research for: {}", topic),
-            details: None,
-        });
-
-        // For now, we'll do a direct search. In the future, we can use an LLM to generate sub-queries.
-        // TODO: Integrate LLM for query generation if depth > 1
-
-        // 2. Search
-        on_progress(ResearchStep {
-            step_type: "searching".to_string(),
-            description: format!("Searching web for: {}", topic),
-            details: None,
-        });
-
-        let results = self.search(topic).await?;
-        
-        let mut context = String::new();
-        for (i, result) in results.iter().enumerate() {
-             on_progress(ResearchStep {
-                step_type: "analyzing".to_string(),
-                description: format!("Reading source {}: {}", i + 1, result.title),
-                details: Some(result.url.clone()),
-            });
-            context.push_str(&format!("Source: {}\nURL: {}\nContent: {}\n\n", result.title, result.url, result.content));
+            .with_context(|| format!("{} search failed", self.provider.name()))?;
+        Ok(response.results)
+    }
+
+    /// Gather sources on `topic` within `budget`. Synthesis happens in the caller
+    /// (minimax_enhanced) so the LLM client isn't duplicated here.
+    pub async fn research_topic(&self, topic: &str, budget: &ResearchBudget, progress: &ProgressReporter) -> Result<Vec<SearchResult>> {
+        let config = &budget.config;
+        progress.emit(
+            ResearchPhase::Planning,
+            format!("Planning research for: {}", topic),
+            None,
+            Some(format!(
+                "depth {}, {} sources/round, {} API calls, {} tokens",
+                config.depth, config.max_sources_per_round, config.max_api_calls, config.max_tokens
+            )),
+        ).await;
+
+        let mut queries = vec![topic.to_string()];
+        let mut seen_urls = HashSet::new();
+        let mut sources: Vec<SearchResult> = Vec::new();
+
+        'rounds: for round in 1..=config.depth {
+            if queries.is_empty() {
+                break;
+            }
+            budget.record_round(round).await;
+            progress.advance(SEARCH_FRACTION * (round - 1) as f64 / config.depth as f64);
+            let mut round_sources = 0;
+            let mut follow_ups = Vec::new();
+
+            for query in std::mem::take(&mut queries) {
+                if round_sources >= config.max_sources_per_round {
+                    break;
+                }
+                if !budget.try_api_call().await {
+                    progress.emit(ResearchPhase::Budget, "API call budget reached, stopping search".to_string(), None, None).await;
+                    break 'rounds;
+                }
+
+                progress.emit(ResearchPhase::Searching, format!("Searching web (round {}): {}", round, query), None, None).await;
+
+                let results = match self.search(&query, config.max_sources_per_round).await {
+                    Ok(results) => results,
+                    // Later rounds are best effort; the first search failing means no research at all
+                    Err(e) if sources.is_empty() => return Err(e),
+                    Err(e) => {
+                        tracing::warn!("⚠️ Follow-up search failed for '{}': {}", query, e);
+                        continue;
+                    }
+                };
+
+                for result in results {
+                    if round_sources >= config.max_sources_per_round {
+                        break;
+                    }
+                    if !seen_urls.insert(result.url.clone()) {
+                        continue;
+                    }
+                    let chunk = format!("Source: {}\nURL: {}\nContent: {}\n\n", result.title, result.url, result.content);
+                    if !budget.try_add_source(estimate_tokens(&chunk)).await {
+                        progress.emit(ResearchPhase::Budget, "Token budget reached, stopping search".to_string(), None, None).await;
+                        break 'rounds;
+                    }
+
+                    round_sources += 1;
+                    progress.emit(
+                        ResearchPhase::Reading,
+                        format!("Reading source {}: {}", seen_urls.len(), result.title),
+                        Some(&result.url),
+                        None,
+                    ).await;
+                    if follow_ups.len() < FOLLOW_UP_QUERIES && !result.title.trim().is_empty() {
+                        follow_ups.push(format!("{} {}", topic, result.title.trim()));
+                    }
+                    sources.push(result);
+                }
+            }
+
+            queries = follow_ups;
+        }
+
+        progress.advance(SEARCH_FRACTION);
+        self.extract_full_pages(&mut sources, budget, progress).await;
+
+        progress.advance(1.0);
+        progress.emit(ResearchPhase::Complete, format!("Finished researching {}: {} sources", topic, sources.len()), None, None).await;
+        Ok(sources)
+    }
+
+    /// Replace the snippets of the best `full_pages` sources with their extracted article
+    /// text, as far as the token budget allows
+    async fn extract_full_pages(&self, sources: &mut [SearchResult], budget: &ResearchBudget, progress: &ProgressReporter) {
+        let mut best: Vec<usize> = (0..sources.len()).collect();
+        best.sort_by(|a, b| sources[*b].score.partial_cmp(&sources[*a].score).unwrap_or(std::cmp::Ordering::Equal));
+        best.truncate(budget.config.full_pages);
+        if best.is_empty() {
+            return;
         }
 
-        // 3. Synthesize
-        // The actual synthesis will happen in the calling code (minimax_enhanced) using the LLM,
-        // because we don't want to duplicate the LLM client logic here just yet.
-        // We return the raw context for the LLM to process.
-        
-        Ok(context)
+        for i in &best {
+            progress.emit(ResearchPhase::Extracting, format!("Reading full page: {}", sources[*i].title), Some(&sources[*i].url), None).await;
+        }
+        // Different hosts are fetched concurrently; web_extract spaces out requests to the same host
+        let pages = futures_util::future::join_all(best.iter().map(|i| fetch_full_text(&sources[*i].url))).await;
+
+        for (i, page) in best.into_iter().zip(pages) {
+            let page = match page {
+                Ok(page) if page.len() > sources[i].content.len() => page,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::warn!("⚠️ Keeping snippet for {}: {:#}", sources[i].url, e);
+                    continue;
+                }
+            };
+            let text: String = page.chars().take(MAX_PAGE_CHARS).collect();
+            let extra = estimate_tokens(&text).saturating_sub(estimate_tokens(&sources[i].content));
+            if !budget.try_add_tokens(extra).await {
+                progress.emit(ResearchPhase::Budget, "Token budget reached, using snippets for remaining sources".to_string(), None, None).await;
+                break;
+            }
+            sources[i].content = text;
+            budget.record_page_extracted().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_budget_is_shared_and_enforced() {
+        let config = ResearchConfig::from_args(&serde_json::json!({ "depth": 9, "max_api_calls": 2, "max_tokens": 1000 }));
+        assert_eq!((config.depth, config.max_sources_per_round), (5, 5));
+
+        let budget = ResearchBudget::new(config);
+        let shared = budget.clone();
+        assert!(budget.try_api_call().await);
+        assert!(shared.try_api_call().await);
+        assert!(!budget.try_api_call().await);
+
+        assert!(budget.try_add_source(600).await);
+        assert!(!shared.try_add_source(600).await);
+
+        let usage = budget.usage().await;
+        assert_eq!((usage.api_calls, usage.sources, usage.estimated_tokens), (2, 1, 600));
+        assert!(usage.budget_exhausted);
+    }
+
+    #[test]
+    fn test_progress_percent_averages_topics() {
+        let progress = ProgressReporter::new(None, "run", ResearchBudget::new(ResearchConfig::default()), 2);
+        let first = progress.for_topic(0, Some("ownership".to_string()));
+        first.advance(1.0);
+        progress.for_topic(1, Some("async".to_string())).advance(0.5);
+        assert_eq!(progress.research_percent(), 60);
+        // Topic reporters share the run's state
+        assert_eq!(first.research_percent(), 60);
+    }
+
+    #[test]
+    fn test_checkpoints_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = ResearchRun::new("Rust".to_string(), vec!["ownership".to_string(), "async".to_string()], ResearchConfig::default());
+        run.save(dir.path()).unwrap();
+
+        let source = SearchResult { title: "Book".to_string(), url: "https://a.dev".to_string(), content: "text".to_string(), score: 0.9, published_date: None };
+        save_checkpoint(dir.path(), 1, "async", &[source]).unwrap();
+        // A checkpoint left over for a different sub-topic at the same position is ignored
+        save_checkpoint(dir.path(), 0, "lifetimes", &[]).unwrap();
+
+        let loaded = ResearchRun::load(dir.path()).unwrap();
+        let done = load_checkpoints(dir.path(), &loaded.research_topics());
+        assert_eq!(done.keys().copied().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(done[&1][0].url, "https://a.dev");
     }
 }
@@ -0,0 +1,614 @@
+/// The email digest: what's new in research, harvests, flashcards and memories
+///
+/// With `[digest] frequency` set to "daily" or "weekly", a background loop sends an email at
+/// `time` (and, weekly, on `weekday`) through the SMTP server in `[digest.smtp]`. Each digest
+/// covers the time since the previous one: research runs that finished with a report,
+/// newly harvested pages, the flashcards due for review and the highest-scoring new TKG
+/// memories. The first one goes out at the first scheduled time after the digest is
+/// switched on.
+///
+/// The listing is built from stored data; when the scheduler has been started with API keys,
+/// the agent writes an overview of it to open the email, otherwise the listing goes alone.
+/// A period with nothing new sends nothing. `send_digest_now` sends (or only previews) a
+/// digest on request.
+
+use chrono::{DateTime, FixedOffset, NaiveTime, Utc};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::config::DigestConfig;
+use crate::harvests::HarvestedNote;
+use crate::scheduler::{stamp, Schedule};
+
+const STATE_FILE: &str = "digest_state.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Wait after a failed send before trying again
+const RETRY_HOURS: i64 = 1;
+const MAX_RESEARCH: usize = 10;
+const MAX_HARVESTED: usize = 25;
+const MAX_MEMORIES: usize = 10;
+/// Due flashcards whose questions are listed
+const SAMPLE_CARDS: usize = 5;
+const EXCERPT_CHARS: usize = 600;
+
+const DIGEST_PROMPT: &str = r#"You are ThinkSpace's background assistant, writing the opening of the user's email digest.
+Everything you need is in the listing you're given; don't call tools.
+Write two or three short paragraphs in markdown: what the research found, what's worth reading among the new pages,
+and what to review. Refer to items by name, don't repeat the listing, and don't add a greeting, sign-off or heading."#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS, usually port 587
+    #[default]
+    StartTls,
+    /// TLS from the start, usually port 465
+    Tls,
+    /// No encryption, for a local relay only
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: Option<String>,
+    pub port: u16,
+    /// "starttls", "tls" or "none"
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The sender, e.g. "ThinkSpace <me@example.com>"; defaults to the username
+    pub from: Option<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self { host: None, port: 587, security: SmtpSecurity::StartTls, username: None, password: None, from: None }
+    }
+}
+
+/// Reject `[digest]` settings that parse but can't be used
+pub fn check_config(config: &DigestConfig) -> Result<(), String> {
+    schedule(config)?;
+    for address in &config.to {
+        address.parse::<Mailbox>().map_err(|e| format!("Invalid address '{}' in [digest] to: {}", address, e))?;
+    }
+    if let Some(from) = &config.smtp.from {
+        from.parse::<Mailbox>().map_err(|e| format!("Invalid from in [digest.smtp]: {}", e))?;
+    }
+    Ok(())
+}
+
+/// The digest's send times as a schedule
+fn schedule(config: &DigestConfig) -> Result<Schedule, String> {
+    let time = NaiveTime::parse_from_str(config.time.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time in [digest]: '{}', use \"HH:MM\"", config.time))?;
+    let weekday = config.weekday.trim().to_lowercase();
+    let day = ["sunday", "monday", "tuesday", "wednesday", "thursday", "friday", "saturday"]
+        .iter()
+        .position(|d| weekday.len() >= 3 && d.starts_with(&weekday))
+        .ok_or_else(|| format!("Invalid weekday in [digest]: '{}'", config.weekday))?;
+    let weekday = match config.frequency {
+        Some(Frequency::Weekly) => day.to_string(),
+        _ => "*".to_string(),
+    };
+    Schedule::parse(&format!("{} {} * * {}", time.format("%-M"), time.format("%-H"), weekday))
+}
+
+/// The previous digests, kept in the profile's data dir
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestState {
+    /// The end of the period the last digest covered; the next one starts here
+    pub covered_until: Option<String>,
+    pub last_sent: Option<String>,
+    pub last_attempt: Option<String>,
+    /// Why the last attempt failed; cleared by the next success
+    pub last_error: Option<String>,
+}
+
+impl DigestState {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path).ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+fn parse_stamp(stamp: &Option<String>) -> Option<DateTime<Utc>> {
+    stamp.as_deref().and_then(|s| DateTime::parse_from_rfc3339(s).ok()).map(|t| t.with_timezone(&Utc))
+}
+
+/// Whether a scheduled send time has passed since the period began (`now` in the configured
+/// timezone)
+fn due(state: &DigestState, schedule: &Schedule, now: DateTime<FixedOffset>) -> bool {
+    let start = match parse_stamp(&state.covered_until) {
+        Some(start) => start.with_timezone(now.offset()).naive_local(),
+        None => return false,
+    };
+    let retry_wait = state.last_error.is_some()
+        && parse_stamp(&state.last_attempt).map_or(false, |last| now.with_timezone(&Utc) - last < chrono::Duration::hours(RETRY_HOURS));
+    schedule.next_after(start).map_or(false, |next| next <= now.naive_local()) && !retry_wait
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResearchItem {
+    pub topic: String,
+    pub status: String,
+    pub sources: usize,
+    /// The start of the report
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryItem {
+    pub content: String,
+    pub node_type: String,
+    /// WAMA score times importance
+    pub score: f64,
+}
+
+/// What a digest covers
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestData {
+    pub since: String,
+    pub until: String,
+    pub research: Vec<ResearchItem>,
+    pub harvested: Vec<HarvestedNote>,
+    pub due_cards: usize,
+    pub card_samples: Vec<String>,
+    pub memories: Vec<MemoryItem>,
+    /// Sources that couldn't be read, e.g. the TKG when it isn't set up
+    pub skipped: Vec<String>,
+}
+
+impl DigestData {
+    fn is_empty(&self) -> bool {
+        self.research.is_empty() && self.harvested.is_empty() && self.due_cards == 0 && self.memories.is_empty()
+    }
+}
+
+/// The best memories stored since `since`, from scrolled TKG points
+pub fn top_memories(points: &[serde_json::Value], since: DateTime<Utc>, limit: usize) -> Vec<MemoryItem> {
+    let mut memories: Vec<MemoryItem> = points
+        .iter()
+        .filter_map(|point| {
+            let payload = &point["payload"];
+            let stored = DateTime::parse_from_rfc3339(payload["timestamp"].as_str()?).ok()?;
+            if stored.with_timezone(&Utc) < since {
+                return None;
+            }
+            let score = payload["wama_score"].as_f64().unwrap_or(0.5) * payload["importance"].as_f64().unwrap_or(0.5);
+            Some(MemoryItem {
+                content: payload["content"].as_str()?.trim().to_string(),
+                node_type: payload["node_type"].as_str().unwrap_or("MEMORY").to_string(),
+                score,
+            })
+        })
+        .filter(|m| !m.content.is_empty())
+        .collect();
+    keep_top(&mut memories, limit);
+    memories
+}
+
+fn keep_top(memories: &mut Vec<MemoryItem>, limit: usize) {
+    memories.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    memories.truncate(limit);
+}
+
+fn excerpt(text: &str, chars: usize) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= chars {
+        return flat;
+    }
+    flat.chars().take(chars - 1).collect::<String>().trim_end().to_string() + "…"
+}
+
+async fn new_memories(user_id: &str, since: DateTime<Utc>) -> Result<Vec<MemoryItem>, String> {
+    let config = crate::tkg::active_config().ok_or("the TKG isn't initialized")?;
+    let mut tkg = crate::tkg::TemporalKnowledgeGraph::new(config);
+    tkg.initialized = true;
+    let filter = serde_json::json!({ "must": [{ "key": "user_id", "match": { "value": user_id } }] });
+    // Only the best of each page are kept, so a large collection isn't held in memory
+    let mut memories = Vec::new();
+    tkg.scroll_all(Some(&filter), false, |page| {
+        memories.extend(top_memories(&page, since, MAX_MEMORIES));
+        keep_top(&mut memories, MAX_MEMORIES);
+        Ok(())
+    })
+    .await?;
+    Ok(memories)
+}
+
+/// Read what's new between `since` and `until`
+async fn gather(app_handle: &AppHandle, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<DigestData, String> {
+    let mut data = DigestData {
+        since: stamp(since),
+        until: stamp(until),
+        research: Vec::new(),
+        harvested: Vec::new(),
+        due_cards: 0,
+        card_samples: Vec::new(),
+        memories: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    {
+        let conn = crate::minimax_api::open_kc_database(Some(app_handle))?;
+        let runs = crate::research_history::list_runs(&conn, None, 100).map_err(|e| e.to_string())?;
+        for run in runs.iter().filter(|r| r.has_report && r.created_at >= data.since && r.created_at <= data.until).take(MAX_RESEARCH) {
+            let report = crate::research_history::get_run(&conn, &run.run_id).ok().flatten().and_then(|r| r.report).unwrap_or_default();
+            data.research.push(ResearchItem {
+                topic: run.topic.clone(),
+                status: run.status.clone(),
+                sources: run.source_count,
+                excerpt: excerpt(&report, EXCERPT_CHARS),
+            });
+        }
+        let cards = crate::flashcards::due_cards(&conn, until, 1000).map_err(|e| e.to_string())?;
+        data.due_cards = cards.len();
+        data.card_samples = cards.iter().take(SAMPLE_CARDS).map(|c| excerpt(&c.question, 160)).collect();
+    }
+
+    match crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path() {
+        Ok(kb_root) => {
+            let since_date = since.with_timezone(&crate::config::offset_at(&crate::config::current(), since)).format("%Y-%m-%d").to_string();
+            data.harvested = crate::harvests::harvested_since(&kb_root, &since_date);
+            data.harvested.truncate(MAX_HARVESTED);
+        }
+        Err(e) => data.skipped.push(format!("harvested pages: {}", e)),
+    }
+
+    let user_id = crate::config::current().digest.user_id.clone().or_else(crate::scheduler::user_id).unwrap_or_default();
+    let user_id = crate::profiles::tkg_user_id(&user_id);
+    if user_id.is_empty() {
+        data.skipped.push("memories: no user id; set user_id in [digest]".to_string());
+    } else {
+        match new_memories(&user_id, since).await {
+            Ok(memories) => data.memories = memories,
+            Err(e) => data.skipped.push(format!("memories: {}", e)),
+        }
+    }
+    Ok(data)
+}
+
+/// The digest's listing in markdown
+pub fn render(data: &DigestData) -> String {
+    let mut out = String::new();
+    if !data.research.is_empty() {
+        out.push_str("## Research\n\n");
+        for item in &data.research {
+            out.push_str(&format!("### {}\n\n*{} · {} sources*\n\n", item.topic, item.status, item.sources));
+            if !item.excerpt.is_empty() {
+                out.push_str(&format!("{}\n\n", item.excerpt));
+            }
+        }
+    }
+    if !data.harvested.is_empty() {
+        out.push_str("## New pages\n\n");
+        for note in &data.harvested {
+            out.push_str(&format!("- [{}]({}) — `{}`\n", note.title, note.source, note.path));
+        }
+        out.push('\n');
+    }
+    if data.due_cards > 0 {
+        out.push_str(&format!("## Flashcards\n\n{} card{} due for review", data.due_cards, if data.due_cards == 1 { " is" } else { "s are" }));
+        out.push_str(if data.card_samples.is_empty() { ".\n\n" } else { ", including:\n\n" });
+        for question in &data.card_samples {
+            out.push_str(&format!("- {}\n", question));
+        }
+        if !data.card_samples.is_empty() {
+            out.push('\n');
+        }
+    }
+    if !data.memories.is_empty() {
+        out.push_str("## New memories\n\n");
+        for memory in &data.memories {
+            out.push_str(&format!("- {}\n", excerpt(&memory.content, 280)));
+        }
+        out.push('\n');
+    }
+    out.trim_end().to_string()
+}
+
+fn subject(frequency: Option<Frequency>, until: DateTime<FixedOffset>) -> String {
+    let kind = match frequency {
+        Some(Frequency::Weekly) => "weekly",
+        _ => "daily",
+    };
+    format!("Your ThinkSpace {} digest — {}", kind, until.format("%a %b %-d"))
+}
+
+/// The email body: the agent's overview when it can write one, then the listing
+async fn compose(app_handle: &AppHandle, data: &DigestData) -> String {
+    let listing = render(data);
+    let overview = match crate::scheduler::background_agent(app_handle, DIGEST_PROMPT) {
+        Some(mut agent) => match agent.run_autonomous_task(format!("Digest listing:\n\n{}", listing)).await {
+            Ok(overview) => Some(overview.trim().to_string()).filter(|o| !o.is_empty()),
+            Err(e) => {
+                tracing::warn!("⚠️ Digest overview not written: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    match overview {
+        Some(overview) => format!("{}\n\n---\n\n{}", overview, listing),
+        None => listing,
+    }
+}
+
+async fn send_email(config: &DigestConfig, subject: &str, markdown: &str) -> Result<(), String> {
+    let smtp = &config.smtp;
+    let host = smtp.host.as_deref().map(str::trim).filter(|h| !h.is_empty()).ok_or("Set host in [digest.smtp] first")?;
+    if config.to.is_empty() {
+        return Err("Set to in [digest] first".to_string());
+    }
+    // SMTP doesn't go through reqwest, so the network policy is checked here
+    let url = url::Url::parse(&format!("smtp://{}:{}", host, smtp.port)).map_err(|e| format!("Invalid host in [digest.smtp]: {}", e))?;
+    crate::net::admit(&url).map_err(|e| e.to_string())?;
+
+    let from = smtp.from.as_deref().or(smtp.username.as_deref()).ok_or("Set from (or username) in [digest.smtp] first")?;
+    let mut message = Message::builder().from(from.parse::<Mailbox>().map_err(|e| format!("Invalid sender '{}': {}", from, e))?);
+    for to in &config.to {
+        message = message.to(to.parse::<Mailbox>().map_err(|e| format!("Invalid address '{}': {}", to, e))?);
+    }
+    let mut warnings = Vec::new();
+    let (html, _) = crate::markdown_render::to_html(markdown, Path::new("."), &|_: &str| None, &mut warnings);
+    let email = message
+        .subject(subject)
+        .multipart(MultiPart::alternative_plain_html(markdown.to_string(), html))
+        .map_err(|e| format!("Failed to build the digest email: {}", e))?;
+
+    let builder = match smtp.security {
+        SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host).map_err(|e| e.to_string())?,
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host).map_err(|e| e.to_string())?,
+        SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    };
+    let mut builder = builder.port(smtp.port).timeout(Some(Duration::from_secs(30)));
+    if let Some(username) = smtp.username.as_deref().filter(|u| !u.is_empty()) {
+        builder = builder.credentials(Credentials::new(username.to_string(), smtp.password.clone().unwrap_or_default()));
+    }
+    builder.build().send(email).await.map_err(|e| format!("Failed to send the digest: {}", e))?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestReport {
+    pub sent: bool,
+    pub subject: String,
+    /// The email body, in markdown
+    pub body: String,
+    pub data: DigestData,
+}
+
+/// Gather, compose and (unless `preview`) send a digest of everything since the last one,
+/// recording it so the next starts where this one ended
+pub async fn send_digest(app_handle: &AppHandle, preview: bool) -> Result<DigestReport, String> {
+    let config = crate::config::current().digest.clone();
+    let state_path = crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?.join(STATE_FILE);
+    let mut state = DigestState::load(&state_path);
+    let now = crate::config::now();
+    let until = now.with_timezone(&Utc);
+    let period = chrono::Duration::days(if config.frequency == Some(Frequency::Weekly) { 7 } else { 1 });
+    let since = parse_stamp(&state.covered_until).unwrap_or(until - period);
+
+    let data = gather(app_handle, since, until).await?;
+    let subject = subject(config.frequency, now);
+    if preview {
+        let body = compose(app_handle, &data).await;
+        return Ok(DigestReport { sent: false, subject, body, data });
+    }
+    if data.is_empty() {
+        tracing::info!("📧 Nothing new since {}; no digest sent", data.since);
+        state.covered_until = Some(data.until.clone());
+        state.save(&state_path)?;
+        return Ok(DigestReport { sent: false, subject, body: String::new(), data });
+    }
+
+    let body = compose(app_handle, &data).await;
+    let result = send_email(&config, &subject, &body).await;
+    state.last_attempt = Some(stamp(Utc::now()));
+    match &result {
+        Ok(()) => {
+            tracing::info!("📧 Sent the digest to {}", config.to.join(", "));
+            state.covered_until = Some(data.until.clone());
+            state.last_sent = state.last_attempt.clone();
+            state.last_error = None;
+        }
+        Err(e) => {
+            state.last_error = Some(e.clone());
+            crate::notifications::notify(app_handle, crate::notifications::Category::Errors, "⚠️ Digest not sent", e);
+        }
+    }
+    state.save(&state_path)?;
+    result.map(|()| DigestReport { sent: true, subject, body, data })
+}
+
+/// Send digests on the `[digest]` schedule for as long as the app runs
+pub fn start_background(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = crate::config::current().digest.clone();
+            if let (Some(_), Ok(schedule), Some(dir)) = (config.frequency, schedule(&config), crate::profiles::data_dir(Some(&app_handle))) {
+                let state_path = dir.join(STATE_FILE);
+                let mut state = DigestState::load(&state_path);
+                if state.covered_until.is_none() {
+                    // Switched on just now: the first digest covers from here
+                    state.covered_until = Some(stamp(Utc::now()));
+                    if let Err(e) = state.save(&state_path) {
+                        tracing::warn!("⚠️ {}", e);
+                    }
+                } else if due(&state, &schedule, crate::config::now()) {
+                    if let Err(e) = send_digest(&app_handle, false).await {
+                        tracing::warn!("⚠️ Scheduled digest failed: {}", e);
+                    }
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestStatus {
+    pub frequency: Option<Frequency>,
+    pub to: Vec<String>,
+    pub smtp_host: Option<String>,
+    /// When the next scheduled digest goes out (a `stamp`)
+    pub next_at: Option<String>,
+    #[serde(flatten)]
+    pub state: DigestState,
+}
+
+/// The digest settings (without secrets), the next send time and how the last one went
+#[tauri::command]
+pub fn get_digest_status(app_handle: AppHandle) -> Result<DigestStatus, String> {
+    let config = crate::config::current().digest.clone();
+    let dir = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+    let state = DigestState::load(&dir.join(STATE_FILE));
+    let now = crate::config::now();
+    let next_at = config.frequency.and_then(|_| {
+        let start = parse_stamp(&state.covered_until).map_or(now.naive_local(), |t| t.with_timezone(now.offset()).naive_local());
+        let next = schedule(&config).ok()?.next_after(start)?;
+        let next = next.and_local_timezone(*now.offset()).single()?;
+        Some(stamp(next.with_timezone(&Utc)))
+    });
+    Ok(DigestStatus { frequency: config.frequency, to: config.to.clone(), smtp_host: config.smtp.host.clone(), next_at, state })
+}
+
+/// Send a digest of everything since the last one now; with `preview`, only compose it
+#[tauri::command]
+pub async fn send_digest_now(app_handle: AppHandle, preview: Option<bool>) -> Result<DigestReport, String> {
+    send_digest(&app_handle, preview.unwrap_or(false)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(d: u32, h: u32, m: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2026, 10, d, h, m, 0).unwrap()
+    }
+
+    fn config(frequency: Frequency) -> DigestConfig {
+        DigestConfig { frequency: Some(frequency), ..Default::default() }
+    }
+
+    fn covered_until(until: &str) -> DigestState {
+        DigestState { covered_until: Some(until.to_string()), ..Default::default() }
+    }
+
+    fn since() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 15, 7, 0, 0).unwrap()
+    }
+
+    fn memories() -> Vec<MemoryItem> {
+        let point = |content: &str, timestamp: &str, score: f64| {
+            serde_json::json!({ "payload": { "content": content, "timestamp": timestamp, "wama_score": score, "importance": 1.0, "node_type": "MEMORY" } })
+        };
+        let points = [
+            point("Old news", "2026-10-01T00:00:00Z", 1.0),
+            point("Prefers Rust over Go", "2026-10-15T09:00:00+00:00", 0.9),
+            point("Deadline for the thesis is Nov 3", "2026-10-16T06:00:00Z", 0.95),
+        ];
+        top_memories(&points, since(), 10)
+    }
+
+    fn digest_data() -> DigestData {
+        DigestData {
+            since: stamp(since()),
+            until: "2026-10-16T07:00:00Z".to_string(),
+            research: vec![ResearchItem { topic: "Rust ownership".to_string(), status: "complete".to_string(), sources: 12, excerpt: excerpt("Ownership   rules\n\nare simple.", 100) }],
+            harvested: Vec::new(),
+            due_cards: 1,
+            card_samples: Vec::new(),
+            memories: memories(),
+            skipped: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_daily_digest_is_due_at_its_time() {
+        let daily = schedule(&config(Frequency::Daily)).unwrap();
+        let state = covered_until("2026-10-15T07:00:00Z");
+        assert!(!due(&state, &daily, at(16, 6, 59)));
+        assert!(due(&state, &daily, at(16, 7, 0)));
+    }
+
+    #[test]
+    fn test_failed_digest_retries_later() {
+        let daily = schedule(&config(Frequency::Daily)).unwrap();
+        let mut state = covered_until("2026-10-15T07:00:00Z");
+        state.last_error = Some("timeout".to_string());
+        state.last_attempt = Some("2026-10-16T07:00:30Z".to_string());
+        assert!(!due(&state, &daily, at(16, 7, 30)));
+        assert!(due(&state, &daily, at(16, 8, 1)));
+    }
+
+    #[test]
+    fn test_first_digest_waits_for_a_covered_period() {
+        let daily = schedule(&config(Frequency::Daily)).unwrap();
+        assert!(!due(&DigestState::default(), &daily, at(16, 8, 0)));
+    }
+
+    #[test]
+    fn test_weekly_digest_waits_for_its_weekday() {
+        // 2026-10-16 is a Friday; weekly digests wait for Monday
+        let weekly = schedule(&config(Frequency::Weekly)).unwrap();
+        let state = covered_until("2026-10-16T07:00:00Z");
+        assert!(!due(&state, &weekly, at(18, 23, 0)));
+        assert!(due(&state, &weekly, at(19, 7, 0)));
+    }
+
+    #[test]
+    fn test_bad_weekday_and_address_are_rejected() {
+        let weekly = config(Frequency::Weekly);
+        assert!(schedule(&DigestConfig { weekday: "someday".to_string(), ..weekly.clone() }).is_err());
+        assert!(check_config(&DigestConfig { to: vec!["not an address".to_string()], ..weekly }).is_err());
+    }
+
+    #[test]
+    fn test_top_memories_since_by_score() {
+        assert_eq!(memories().iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["Deadline for the thesis is Nov 3", "Prefers Rust over Go"]);
+    }
+
+    #[test]
+    fn test_render_lists_each_section() {
+        let listing = render(&digest_data());
+        assert!(listing.starts_with("## Research\n\n### Rust ownership\n\n*complete · 12 sources*\n\nOwnership rules are simple."));
+        assert!(listing.contains("## Flashcards\n\n1 card is due for review.\n\n## New memories\n\n- Deadline for the thesis is Nov 3\n"));
+        assert!(!listing.contains("New pages"));
+    }
+
+    #[test]
+    fn test_empty_digest_renders_nothing() {
+        let mut data = digest_data();
+        data.research.clear();
+        data.due_cards = 0;
+        data.memories.clear();
+        assert!(data.is_empty() && render(&data).is_empty());
+    }
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(subject(Some(Frequency::Weekly), at(16, 7, 0)), "Your ThinkSpace weekly digest — Fri Oct 16");
+    }
+}
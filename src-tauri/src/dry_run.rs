@@ -0,0 +1,116 @@
+/// Dry-run reports for the tools that change things
+///
+/// With dry run on for a session (`SessionSettings::dry_run` or the `dry_run` argument of the
+/// chat commands), `write_file`, `write_file_batch` and `run_terminal_command` go through all
+/// of their checks and then return one of these reports instead of acting, so an agent's plan
+/// can be audited before it is let loose. Every report has `"dry_run": true`.
+
+use std::path::Path;
+
+/// Diffs longer than this are cut, with a note saying so
+const MAX_DIFF_CHARS: usize = 20_000;
+
+/// A unified diff of `old` to `new`, labelled with `path`
+pub fn diff(path: &str, old: &str, new: &str) -> String {
+    let mut diff = similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string();
+    if diff.len() > MAX_DIFF_CHARS {
+        let cut = (0..=MAX_DIFF_CHARS).rev().find(|i| diff.is_char_boundary(*i)).unwrap_or(0);
+        diff.truncate(cut);
+        diff.push_str("\n... diff truncated\n");
+    }
+    diff
+}
+
+/// What writing (or appending) `content` to `full_path` would do
+pub fn planned_write(full_path: &Path, path: &str, content: &str, append: bool) -> serde_json::Value {
+    let existing = std::fs::read_to_string(full_path).ok();
+    let old = existing.clone().unwrap_or_default();
+    let new = if append { format!("{}{}", old, content) } else { content.to_string() };
+    let operation = match (&existing, append) {
+        (None, _) => "create",
+        (Some(_), true) => "append",
+        (Some(_), false) => "overwrite",
+    };
+    serde_json::json!({
+        "success": true,
+        "dry_run": true,
+        "path": path,
+        "operation": operation,
+        "bytes": content.len(),
+        "unchanged": existing.as_deref() == Some(new.as_str()),
+        "diff": diff(path, &old, &new),
+        "message": format!("Dry run: would {} {} ({} bytes); nothing was written", operation, path, content.len())
+    })
+}
+
+/// What running `command` would do
+pub fn planned_command(command: &str, shell: &[&str], cwd: &Path) -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "dry_run": true,
+        "command": command,
+        "shell": shell,
+        "cwd": cwd,
+        "message": format!("Dry run: would run `{}` in {}; nothing was executed", command, cwd.display())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temp dir holding `note.md` with two lines
+    fn existing_note() -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        std::fs::write(&note, "# Title\nold line\n").unwrap();
+        (dir, note)
+    }
+
+    #[test]
+    fn test_planned_create() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        let created = planned_write(&note, "research/note.md", "# Title\n", false);
+        assert_eq!(created["operation"], "create");
+        assert!(created["diff"].as_str().unwrap().contains("+# Title"));
+        assert!(!note.exists());
+    }
+
+    #[test]
+    fn test_planned_overwrite_diffs_without_writing() {
+        let (_dir, note) = existing_note();
+        let overwrite = planned_write(&note, "research/note.md", "# Title\nnew line\n", false);
+        let diff = overwrite["diff"].as_str().unwrap();
+        assert_eq!(overwrite["operation"], "overwrite");
+        assert!(diff.starts_with("--- a/research/note.md\n+++ b/research/note.md\n"));
+        assert!(diff.contains("-old line\n+new line\n"));
+        assert_eq!(std::fs::read_to_string(&note).unwrap(), "# Title\nold line\n");
+    }
+
+    #[test]
+    fn test_planned_append() {
+        let (_dir, note) = existing_note();
+        let append = planned_write(&note, "research/note.md", "more\n", true);
+        assert_eq!(append["operation"], "append");
+        assert!(append["diff"].as_str().unwrap().contains("+more"));
+    }
+
+    #[test]
+    fn test_planned_write_of_the_same_content_is_unchanged() {
+        let (_dir, note) = existing_note();
+        assert_eq!(planned_write(&note, "research/note.md", "# Title\nold line\n", false)["unchanged"], true);
+    }
+
+    #[test]
+    fn test_planned_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let command = planned_command("cargo test", &["cmd", "/C"], dir.path());
+        assert_eq!(command["dry_run"], true);
+        assert_eq!(command["command"], "cargo test");
+    }
+}
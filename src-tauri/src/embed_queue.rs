@@ -0,0 +1,322 @@
+/// Background queue that embeds and stores TKG knowledge in batches
+///
+/// `tkg_store_knowledge` (and with it auto-ingestion, harvesting, research notes and the
+/// `tkg_store` tool) used to make one Cohere call and one Qdrant upsert per text, and the
+/// caller waited for both. Now it runs WAMA, adds the node to the `embedding_queue` table in
+/// knowledge_companion.db and returns the node id right away. A worker takes up to
+/// `BATCH_SIZE` nodes at a time, embeds them in one call and upserts them in one request.
+///
+/// The queue survives restarts: the worker starts with the TKG and carries on where it left
+/// off. A failed batch is retried with growing delays and given up after `MAX_ATTEMPTS`.
+/// Once `MAX_PENDING` nodes are waiting, stores fail until the queue drains, so a large
+/// ingestion slows down instead of piling up.
+
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::scheduler::stamp;
+
+/// Cohere embeds at most 96 texts per call
+const BATCH_SIZE: usize = 96;
+const MAX_PENDING: usize = 5_000;
+const MAX_ATTEMPTS: u32 = 8;
+/// How long the worker sleeps when there's nothing to do (new stores wake it earlier)
+const IDLE_POLL_SECS: u64 = 30;
+
+lazy_static::lazy_static! {
+    static ref WAKE: tokio::sync::Notify = tokio::sync::Notify::new();
+}
+static WORKER_STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QueueStatus {
+    /// Waiting to be embedded, including nodes being retried
+    pub pending: usize,
+    /// Given up on after `MAX_ATTEMPTS`
+    pub failed: usize,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedNode {
+    id: String,
+    payload: serde_json::Value,
+    attempts: u32,
+}
+
+pub fn init_embedding_queue_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS embedding_queue (
+            id TEXT PRIMARY KEY,
+            payload TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at TEXT NOT NULL,
+            last_error TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_embedding_queue_next ON embedding_queue(next_attempt_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn push(conn: &Connection, id: &str, payload: &serde_json::Value, now: &str, max_pending: usize) -> Result<(), String> {
+    let pending: usize = conn
+        .query_row("SELECT COUNT(*) FROM embedding_queue WHERE attempts < ?1", params![MAX_ATTEMPTS], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if pending >= max_pending {
+        return Err(format!("The embedding queue is full ({} waiting); try again once it has caught up", pending));
+    }
+    conn.execute(
+        "INSERT INTO embedding_queue (id, payload, created_at, next_attempt_at) VALUES (?1, ?2, ?3, ?3)",
+        params![id, payload.to_string(), now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The oldest nodes that are due, up to `limit`
+fn next_batch(conn: &Connection, now: &str, limit: usize) -> rusqlite::Result<Vec<QueuedNode>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, payload, attempts FROM embedding_queue
+         WHERE attempts < ?1 AND next_attempt_at <= ?2
+         ORDER BY created_at, rowid LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(params![MAX_ATTEMPTS, now, limit as i64], |row| {
+        let payload: String = row.get(1)?;
+        Ok(QueuedNode {
+            id: row.get(0)?,
+            payload: serde_json::from_str(&payload).unwrap_or_default(),
+            attempts: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+fn complete(conn: &Connection, batch: &[QueuedNode]) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached("DELETE FROM embedding_queue WHERE id = ?1")?;
+    for node in batch {
+        stmt.execute(params![node.id])?;
+    }
+    Ok(())
+}
+
+/// Put a failed batch back, each node due again after a delay that doubles per attempt
+fn fail(conn: &Connection, batch: &[QueuedNode], error: &str, now: chrono::DateTime<Utc>) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "UPDATE embedding_queue SET attempts = attempts + 1, next_attempt_at = ?2, last_error = ?3 WHERE id = ?1",
+    )?;
+    for node in batch {
+        let delay = Duration::seconds(30 * 2_i64.pow(node.attempts.min(10))).min(Duration::hours(6));
+        stmt.execute(params![node.id, stamp(now + delay), error])?;
+    }
+    Ok(())
+}
+
+fn status(conn: &Connection) -> rusqlite::Result<QueueStatus> {
+    let (pending, failed) = conn.query_row(
+        "SELECT COALESCE(SUM(attempts < ?1), 0), COALESCE(SUM(attempts >= ?1), 0) FROM embedding_queue",
+        params![MAX_ATTEMPTS],
+        |row| Ok((row.get::<_, i64>(0)? as usize, row.get::<_, i64>(1)? as usize)),
+    )?;
+    let last_error = conn
+        .query_row(
+            "SELECT last_error FROM embedding_queue WHERE last_error IS NOT NULL ORDER BY next_attempt_at DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+    Ok(QueueStatus { pending, failed, last_error })
+}
+
+/// Drop queued sections of note `path` stored for `user_id` whose hash isn't in `keep`, so an
+/// older version of a note can't be stored after its replacement
+fn drop_outdated(conn: &Connection, user_id: &str, path: &str, keep: &[String]) -> rusqlite::Result<usize> {
+    let keep = serde_json::to_string(keep).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "DELETE FROM embedding_queue
+         WHERE json_extract(payload, '$.source_path') = ?1
+           AND json_extract(payload, '$.user_id') = ?2
+           AND json_extract(payload, '$.section_hash') NOT IN (SELECT value FROM json_each(?3))",
+        params![path, user_id, keep],
+    )
+}
+
+pub fn drop_outdated_sections(user_id: &str, path: &str, keep: &[String]) -> Result<usize, String> {
+    let conn = crate::minimax_api::open_kc_database(None)?;
+    drop_outdated(&conn, user_id, path, keep).map_err(|e| e.to_string())
+}
+
+/// Queue node `id` with its TKG payload for embedding
+pub fn enqueue(id: &str, payload: &serde_json::Value) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(None)?;
+    push(&conn, id, payload, &stamp(Utc::now()), MAX_PENDING)?;
+    start_worker();
+    WAKE.notify_one();
+    Ok(())
+}
+
+/// Start the worker if it isn't running yet
+pub fn start_worker() {
+    if WORKER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        tracing::info!("🧠 Embedding queue worker started");
+        loop {
+            match flush_batch().await {
+                // A full batch probably means more are waiting
+                Ok(n) if n == BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => tracing::warn!("⚠️ Embedding batch failed: {}", e),
+            }
+            let _ = tokio::time::timeout(std::time::Duration::from_secs(IDLE_POLL_SECS), WAKE.notified()).await;
+        }
+    });
+}
+
+/// Embed and store the next batch; returns how many nodes were stored
+async fn flush_batch() -> Result<usize, String> {
+    // Nothing can go out until the frontend has initialized the TKG with its keys
+    let config = match crate::tkg::active_config() {
+        Some(config) => config,
+        None => return Ok(0),
+    };
+    let batch = crate::db::blocking(|| {
+        let conn = crate::minimax_api::open_kc_database(None)?;
+        next_batch(&conn, &stamp(Utc::now()), BATCH_SIZE).map_err(|e| e.to_string())
+    })
+    .await?;
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    let tkg = crate::tkg::TemporalKnowledgeGraph::new(config);
+    let nodes: Vec<(String, serde_json::Value)> = batch.iter().map(|n| (n.id.clone(), n.payload.clone())).collect();
+    let stored = tkg.store_nodes(&nodes).await;
+
+    let count = batch.len();
+    crate::db::blocking(move || {
+        let conn = crate::minimax_api::open_kc_database(None)?;
+        match stored {
+            Ok(()) => {
+                complete(&conn, &batch).map_err(|e| e.to_string())?;
+                tracing::info!("🧠 Embedded and stored {} queued node(s)", count);
+                Ok(count)
+            }
+            Err(e) => {
+                fail(&conn, &batch, &e, Utc::now()).map_err(|e| e.to_string())?;
+                Err(e)
+            }
+        }
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn get_embedding_queue_status() -> Result<QueueStatus, String> {
+    let conn = crate::minimax_api::open_kc_database(None)?;
+    status(&conn).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_embedding_queue_table(&conn).unwrap();
+        conn
+    }
+
+    fn payload(n: usize) -> serde_json::Value {
+        serde_json::json!({ "content": format!("fact {}", n) })
+    }
+
+    /// A full queue of five nodes, with the first two done and the third failed once
+    fn after_one_batch(conn: &Connection, now: chrono::DateTime<Utc>) {
+        for n in 0..5 {
+            push(conn, &format!("node-{}", n), &payload(n), &stamp(now), 5).unwrap();
+        }
+        let batch = next_batch(conn, &stamp(now), 3).unwrap();
+        complete(conn, &batch[..2]).unwrap();
+        fail(conn, &batch[2..], "Cohere API error", now).unwrap();
+    }
+
+    #[test]
+    fn test_push_fails_once_the_queue_is_full() {
+        let conn = queue_db();
+        let now = stamp(Utc::now());
+        for n in 0..5 {
+            push(&conn, &format!("node-{}", n), &payload(n), &now, 5).unwrap();
+        }
+        assert!(push(&conn, "node-5", &payload(5), &now, 5).unwrap_err().contains("full"));
+    }
+
+    #[test]
+    fn test_next_batch_in_queue_order() {
+        let conn = queue_db();
+        let now = stamp(Utc::now());
+        for n in 0..5 {
+            push(&conn, &format!("node-{}", n), &payload(n), &now, 5).unwrap();
+        }
+        let batch = next_batch(&conn, &now, 3).unwrap();
+        assert_eq!(batch.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec!["node-0", "node-1", "node-2"]);
+        assert_eq!(batch[1].payload["content"], "fact 1");
+    }
+
+    #[test]
+    fn test_failed_node_waits_before_its_retry() {
+        let conn = queue_db();
+        let now = Utc::now();
+        after_one_batch(&conn, now);
+        let due = next_batch(&conn, &stamp(now), 10).unwrap();
+        assert_eq!(due.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec!["node-3", "node-4"]);
+        assert_eq!(next_batch(&conn, &stamp(now + Duration::minutes(1)), 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_status_counts_pending_nodes_and_the_last_error() {
+        let conn = queue_db();
+        after_one_batch(&conn, Utc::now());
+        assert_eq!(status(&conn).unwrap(), QueueStatus { pending: 3, failed: 0, last_error: Some("Cohere API error".to_string()) });
+    }
+
+    #[test]
+    fn test_node_is_given_up_after_max_attempts() {
+        let conn = queue_db();
+        let now = Utc::now();
+        after_one_batch(&conn, now);
+        conn.execute("UPDATE embedding_queue SET attempts = ?1 WHERE id = 'node-2'", params![MAX_ATTEMPTS]).unwrap();
+        assert_eq!(status(&conn).unwrap().failed, 1);
+        // Given-up nodes don't count against the queue's size
+        push(&conn, "node-5", &payload(5), &stamp(now), 3).unwrap();
+    }
+
+    #[test]
+    fn test_drop_outdated_sections() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_embedding_queue_table(&conn).unwrap();
+        let now = stamp(Utc::now());
+        let section = |path: &str, hash: &str, user: &str| {
+            serde_json::json!({ "content": "text", "user_id": user, "source_path": path, "section_hash": hash })
+        };
+        push(&conn, "old", &section("research/rust.md", "h1", "me"), &now, 10).unwrap();
+        push(&conn, "kept", &section("research/rust.md", "h2", "me"), &now, 10).unwrap();
+        push(&conn, "other-note", &section("research/go.md", "h1", "me"), &now, 10).unwrap();
+        push(&conn, "other-user", &section("research/rust.md", "h1", "you"), &now, 10).unwrap();
+        push(&conn, "memory", &serde_json::json!({ "content": "no source", "user_id": "me" }), &now, 10).unwrap();
+
+        assert_eq!(drop_outdated(&conn, "me", "research/rust.md", &["h2".to_string()]).unwrap(), 1);
+        let left: Vec<String> = next_batch(&conn, &now, 10).unwrap().into_iter().map(|n| n.id).collect();
+        assert_eq!(left, vec!["kept", "other-note", "other-user", "memory"]);
+
+        // A removed note drops all of its sections
+        assert_eq!(drop_outdated(&conn, "me", "research/rust.md", &[]).unwrap(), 1);
+    }
+}
@@ -0,0 +1,279 @@
+/// Coalescing of high-frequency events sent to the frontend
+///
+/// Some events come far faster than the UI can use them: `chat-stream` sends a chunk per SSE
+/// delta, and batch writes, harvests and the watcher each send `content-changed` per file or
+/// burst. Events sent through `emit` wait up to `interval_ms` (or until `max_merged` have
+/// been folded together) and go out combined: stream deltas are concatenated, content changes
+/// from the same source have their paths joined, and canvas previews of the same target keep
+/// only the newest. Anything that can't be merged is queued behind what's waiting, and a tool
+/// call or the final `done` chunk flushes at once, so the frontend sees events in order.
+/// `[events.<name>]` in thinkspace.toml sets the timing per event; an interval of 0 sends
+/// every event as it comes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+lazy_static::lazy_static! {
+    static ref PENDING: Mutex<HashMap<&'static str, Pending>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coalescing {
+    /// How long an event may wait for others to merge with
+    pub interval_ms: u64,
+    /// Send once this many events have been merged, however little time has passed
+    pub max_merged: usize,
+}
+
+impl Default for Coalescing {
+    fn default() -> Self {
+        Self { interval_ms: 0, max_merged: 1 }
+    }
+}
+
+/// The built-in timing for `event`; events not listed here are sent at once
+pub fn default_coalescing(event: &str) -> Coalescing {
+    match event {
+        "chat-stream" => Coalescing { interval_ms: 40, max_merged: 64 },
+        "content-changed" => Coalescing { interval_ms: 250, max_merged: 500 },
+        "native-canvas-update" => Coalescing { interval_ms: 50, max_merged: 32 },
+        _ => Coalescing::default(),
+    }
+}
+
+/// Events waiting to be sent under one name
+#[derive(Debug, Default)]
+struct Pending {
+    payloads: Vec<serde_json::Value>,
+    merged: usize,
+    flush_scheduled: bool,
+}
+
+impl Pending {
+    /// Add `payload`; true when what's waiting should go out now
+    fn push(&mut self, event: &str, payload: serde_json::Value, max_merged: usize) -> bool {
+        let urgent = is_final(event, &payload);
+        let merged = self.payloads.last_mut().map_or(false, |last| merge(event, last, &payload));
+        if merged {
+            self.merged += 1;
+        } else {
+            self.payloads.push(payload);
+        }
+        urgent || self.merged >= max_merged
+    }
+}
+
+/// Events after which nothing should wait
+fn is_final(event: &str, payload: &serde_json::Value) -> bool {
+    event == "chat-stream" && (payload["done"] == true || !payload["tool_calls"].is_null())
+}
+
+/// Fold `next` into `last` if the frontend would treat the pair the same as the result
+fn merge(event: &str, last: &mut serde_json::Value, next: &serde_json::Value) -> bool {
+    match event {
+        "chat-stream" => {
+            let plain = |chunk: &serde_json::Value| chunk["done"] == false && chunk["tool_calls"].is_null();
+            if !plain(last) || !plain(next) || last["is_thinking"] != next["is_thinking"] || last["session"] != next["session"] {
+                return false;
+            }
+            let content = format!("{}{}", last["content"].as_str().unwrap_or_default(), next["content"].as_str().unwrap_or_default());
+            last["content"] = serde_json::Value::String(content);
+            true
+        }
+        // A null payload means "reload everything", which covers any other change
+        "content-changed" => {
+            if last.is_null() || next.is_null() {
+                *last = serde_json::Value::Null;
+                return true;
+            }
+            if last["source"] != next["source"] {
+                return false;
+            }
+            // A change without paths covers the whole source, so the merged one does too
+            let next_paths = match next["paths"].as_array() {
+                Some(next_paths) => next_paths.clone(),
+                None => {
+                    if let Some(last) = last.as_object_mut() {
+                        last.remove("paths");
+                    }
+                    return true;
+                }
+            };
+            if let Some(paths) = last.get_mut("paths").and_then(|paths| paths.as_array_mut()) {
+                for path in next_paths {
+                    if !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+            true
+        }
+        // A newer preview of the same canvas replaces the older one
+        "native-canvas-update" => {
+            let preview_only = |update: &serde_json::Value| update.as_object().map_or(false, |u| u.len() == 1 && u.contains_key("preview"));
+            if !preview_only(last) || !preview_only(next) || last["preview"]["target"] != next["preview"]["target"] {
+                return false;
+            }
+            *last = next.clone();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Send `payload` as `event` to every window, merged with others that follow closely
+pub fn emit<S: Serialize>(app_handle: &AppHandle, event: &'static str, payload: S) {
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to serialize '{}' event: {}", event, e);
+            return;
+        }
+    };
+    let config = crate::config::current();
+    let timing = config.events.get(event).copied().unwrap_or_else(|| default_coalescing(event));
+
+    let mut pending = PENDING.lock().unwrap();
+    let queue = pending.entry(event).or_default();
+    if timing.interval_ms == 0 {
+        queue.payloads.push(payload);
+        send(app_handle, event, queue);
+        return;
+    }
+    if queue.push(event, payload, timing.max_merged.max(1)) {
+        send(app_handle, event, queue);
+    } else if !queue.flush_scheduled {
+        queue.flush_scheduled = true;
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(timing.interval_ms)).await;
+            flush(&app_handle, event);
+        });
+    }
+}
+
+/// Send whatever is waiting under `event` now
+pub fn flush(app_handle: &AppHandle, event: &'static str) {
+    if let Some(queue) = PENDING.lock().unwrap().get_mut(event) {
+        send(app_handle, event, queue);
+    }
+}
+
+/// Sends happen under the lock so two flushes can't interleave and reorder events
+fn send(app_handle: &AppHandle, event: &str, queue: &mut Pending) {
+    for payload in queue.payloads.drain(..) {
+        let _ = app_handle.emit_all(event, payload);
+    }
+    queue.merged = 0;
+    queue.flush_scheduled = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chunk(content: &str, done: bool) -> serde_json::Value {
+        json!({ "content": content, "is_thinking": false, "done": done, "tool_calls": null })
+    }
+
+    fn preview(target: &str, html: &str) -> serde_json::Value {
+        json!({ "preview": { "target": target, "content": html } })
+    }
+
+    #[test]
+    fn test_stream_chunks_merge_until_a_tool_call() {
+        let mut stream = Pending::default();
+        assert!(!stream.push("chat-stream", chunk("Hel", false), 64));
+        assert!(!stream.push("chat-stream", chunk("lo", false), 64));
+        let tool_call = json!({ "content": "", "is_thinking": false, "done": false, "tool_calls": [{ "id": "1" }] });
+        assert!(stream.push("chat-stream", tool_call, 64));
+        assert_eq!(stream.payloads.len(), 2);
+        assert_eq!(stream.payloads[0]["content"], "Hello");
+    }
+
+    #[test]
+    fn test_done_chunk_flushes_without_merging() {
+        let mut stream = Pending::default();
+        assert!(!stream.push("chat-stream", chunk("Done", false), 64));
+        assert!(stream.push("chat-stream", chunk("", true), 64));
+        assert_eq!(stream.payloads.len(), 2);
+    }
+
+    #[test]
+    fn test_content_changes_from_one_source_join_their_paths() {
+        let mut changes = Pending::default();
+        changes.push("content-changed", json!({ "source": "/kb/research", "paths": ["a.md"] }), 3);
+        changes.push("content-changed", json!({ "source": "/kb/research", "paths": ["b.md", "a.md"] }), 3);
+        changes.push("content-changed", json!({ "source": "/kb/dumps", "paths": ["c.md"] }), 3);
+        assert_eq!(changes.payloads[0]["paths"], json!(["a.md", "b.md"]));
+        assert_eq!(changes.payloads.len(), 2);
+    }
+
+    #[test]
+    fn test_null_change_covers_what_is_waiting() {
+        let mut changes = Pending::default();
+        changes.push("content-changed", json!({ "source": "/kb/research", "paths": ["a.md"] }), 3);
+        changes.push("content-changed", json!({ "source": "/kb/dumps", "paths": ["c.md"] }), 3);
+        assert!(!changes.push("content-changed", serde_json::Value::Null, 3));
+        assert_eq!(changes.payloads.len(), 2);
+        assert!(changes.payloads[1].is_null());
+    }
+
+    #[test]
+    fn test_content_change_flushes_at_max_merged() {
+        let mut changes = Pending::default();
+        changes.push("content-changed", json!({ "source": "/kb/dumps", "paths": ["c.md"] }), 3);
+        assert!(!changes.push("content-changed", serde_json::Value::Null, 3));
+        assert!(!changes.push("content-changed", json!({ "source": "/kb/dumps", "paths": ["d.md"] }), 3));
+        // The third merge reaches max_merged
+        assert!(changes.push("content-changed", json!({ "source": "/kb/dumps", "paths": ["e.md"] }), 3));
+        assert_eq!(changes.payloads, vec![serde_json::Value::Null]);
+    }
+
+    #[test]
+    fn test_canvas_previews_keep_the_newest_per_target() {
+        let mut canvas = Pending::default();
+        canvas.push("native-canvas-update", preview("main", "<p>1</p>"), 32);
+        canvas.push("native-canvas-update", preview("main", "<p>2</p>"), 32);
+        canvas.push("native-canvas-update", json!({ "add_block": { "target": "main" } }), 32);
+        canvas.push("native-canvas-update", preview("side", "<p>3</p>"), 32);
+        assert_eq!(canvas.payloads.len(), 3);
+        assert_eq!(canvas.payloads[0]["preview"]["content"], "<p>2</p>");
+    }
+
+    #[test]
+    fn test_other_events_are_never_merged() {
+        let mut other = Pending::default();
+        other.push("study-timer", json!({ "left": 5 }), 1);
+        other.push("study-timer", json!({ "left": 5 }), 1);
+        assert_eq!(other.payloads.len(), 2);
+    }
+
+    #[test]
+    fn test_chunks_of_different_sessions_stay_apart() {
+        let chunk = |session: &str, content: &str| {
+            json!({ "content": content, "is_thinking": false, "done": false, "tool_calls": null, "session": session })
+        };
+        let mut stream = Pending::default();
+        stream.push("chat-stream", chunk("a", "Hel"), 64);
+        stream.push("chat-stream", chunk("b", "Other"), 64);
+        stream.push("chat-stream", chunk("b", " chat"), 64);
+        assert_eq!(stream.payloads.len(), 2);
+        assert_eq!(stream.payloads[0]["content"], "Hel");
+        assert_eq!(stream.payloads[1]["content"], "Other chat");
+    }
+
+    #[test]
+    fn test_change_without_paths_widens_the_merged_change() {
+        let mut changes = Pending::default();
+        changes.push("content-changed", json!({ "source": "/kb/research", "paths": ["a.md"] }), 8);
+        changes.push("content-changed", json!({ "source": "/kb/research" }), 8);
+        changes.push("content-changed", json!({ "source": "/kb/research", "paths": ["b.md"] }), 8);
+        assert_eq!(changes.payloads, vec![json!({ "source": "/kb/research" })]);
+    }
+}
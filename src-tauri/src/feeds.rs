@@ -0,0 +1,783 @@
+/// RSS and Atom feed ingestion: a research inbox
+///
+/// Registered feeds are polled every `interval_minutes`. Each new item (by GUID, or link
+/// when a feed has none) is saved as a note under `research/feeds/<feed>/`, optionally with
+/// a summary, and listed in the inbox until it's marked read. Feeds and items are stored
+/// in knowledge_companion.db; a `feed-items-added` event announces new items.
+///
+/// The poller starts with `start_feed_poller`. API keys are only needed for summaries and
+/// are kept in memory.
+
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+use crate::commands::orchestrate_agents::{parse_provider, ProviderKeys};
+use crate::minimax_enhanced::AIProvider;
+use crate::scheduler::stamp;
+use crate::net::SendChecked;
+
+pub const FEEDS_FOLDER: &str = "research/feeds";
+const CHECK_INTERVAL_SECS: u64 = 60;
+const DEFAULT_INTERVAL_MINUTES: u32 = 60;
+/// Items saved per poll; a new feed's backlog beyond this is skipped
+const MAX_NEW_ITEMS: usize = 20;
+const MAX_SUMMARY_INPUT_CHARS: usize = 20_000;
+
+const SUMMARY_PROMPT: &str = r#"You summarize articles for a research inbox.
+Reply with two or three sentences covering the main point and why it matters, nothing else."#;
+
+lazy_static::lazy_static! {
+    static ref FEED_KEYS: Mutex<Option<(ProviderKeys, AIProvider)>> = Mutex::new(None);
+    static ref LINK_TAG: Regex = Regex::new(r"(?i)<link\b([^>]*)>").unwrap();
+    static ref HREF: Regex = Regex::new(r#"(?i)\bhref\s*=\s*["']([^"']+)["']"#).unwrap();
+    static ref REL: Regex = Regex::new(r#"(?i)\brel\s*=\s*["']([^"']+)["']"#).unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]+>").unwrap();
+    static ref WHITESPACE: Regex = Regex::new(r"\s+").unwrap();
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some("ThinkSpace-Research/1.0 (+https://github.com/oogalieboogalie/ThinkSpace)"),
+        timeout: Some(std::time::Duration::from_secs(20)),
+    })
+}
+
+static POLLER_STARTED: AtomicBool = AtomicBool::new(false);
+
+// ==================== Parsing ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedItem {
+    pub guid: String,
+    pub title: String,
+    pub link: Option<String>,
+    /// A `stamp` when the feed's date could be read, else the feed's own text
+    pub published: Option<String>,
+    /// The item's content or description as markdown
+    pub content: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedFeed {
+    pub title: Option<String>,
+    pub items: Vec<ParsedItem>,
+}
+
+/// Inner XML of each `<name>` element in `block`
+fn elements<'a>(block: &'a str, name: &str) -> Vec<&'a str> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(i) = block[from..].find(&open) {
+        let after_name = from + i + open.len();
+        from = after_name;
+        if !matches!(block[after_name..].chars().next(), Some('>' | '/' | ' ' | '\t' | '\r' | '\n')) {
+            continue;
+        }
+        let tag_end = match block[after_name..].find('>') {
+            Some(j) => after_name + j,
+            None => break,
+        };
+        from = tag_end + 1;
+        if block[..tag_end].ends_with('/') {
+            found.push("");
+            continue;
+        }
+        match block[tag_end + 1..].find(&close) {
+            Some(j) => {
+                found.push(&block[tag_end + 1..tag_end + 1 + j]);
+                from = tag_end + 1 + j + close.len();
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+fn element<'a>(block: &'a str, names: &[&str]) -> Option<&'a str> {
+    names.iter().find_map(|name| elements(block, name).into_iter().find(|e| !e.trim().is_empty()))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn strip_cdata(raw: &str) -> &str {
+    let raw = raw.trim();
+    raw.strip_prefix("<![CDATA[").and_then(|r| r.strip_suffix("]]>")).unwrap_or(raw)
+}
+
+/// An element's text on one line, without markup
+fn plain_text(raw: &str) -> String {
+    let raw = strip_cdata(raw);
+    let decoded = decode_entities(raw);
+    let text = if decoded.contains('<') { TAG.replace_all(&decoded, "").into_owned() } else { decoded };
+    WHITESPACE.replace_all(&text, " ").trim().to_string()
+}
+
+/// Feed dates (RFC 822 in RSS, RFC 3339 in Atom) as stamps
+fn parse_date(raw: &str) -> String {
+    let raw = raw.trim();
+    DateTime::parse_from_rfc2822(raw)
+        .or_else(|_| DateTime::parse_from_rfc3339(raw))
+        .map(|d| stamp(d.with_timezone(&Utc)))
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+/// The entry's alternate link in Atom, or the text of `<link>` in RSS
+fn item_link(block: &str) -> Option<String> {
+    let atom = LINK_TAG
+        .captures_iter(block)
+        .filter(|c| REL.captures(&c[1]).map_or(true, |rel| rel[1].eq_ignore_ascii_case("alternate")))
+        .find_map(|c| HREF.captures(&c[1]).map(|h| decode_entities(&h[1])));
+    atom.or_else(|| element(block, &["link"]).map(plain_text).filter(|l| !l.is_empty()))
+}
+
+fn parse_item(block: &str) -> Option<ParsedItem> {
+    let link = item_link(block);
+    let title = element(block, &["title"]).map(plain_text).filter(|t| !t.is_empty());
+    let guid = element(block, &["guid", "id"]).map(plain_text).or_else(|| link.clone()).or_else(|| title.clone())?;
+    let published = element(block, &["pubDate", "published", "updated", "dc:date"]).map(|d| parse_date(&plain_text(d)));
+
+    let raw = element(block, &["content:encoded", "content", "description", "summary"]).unwrap_or_default();
+    let raw = strip_cdata(raw);
+    // Descriptions are usually escaped HTML
+    let html = if raw.contains('<') { raw.to_string() } else { decode_entities(raw) };
+    let base = link.as_deref().and_then(|l| url::Url::parse(l).ok());
+    let (_, content) = crate::web_extract::html_to_markdown(&format!("<html><body>{}</body></html>", html), base.as_ref());
+
+    Some(ParsedItem {
+        title: title.unwrap_or_else(|| "Untitled".to_string()),
+        guid,
+        link,
+        published,
+        content,
+    })
+}
+
+pub fn parse_feed(xml: &str) -> Result<ParsedFeed, String> {
+    let (item_tag, header_end) = if let Some(i) = xml.find("<entry") {
+        ("entry", i)
+    } else if let Some(i) = xml.find("<item") {
+        ("item", i)
+    } else if xml.contains("<rss") || xml.contains("<feed") || xml.contains("<rdf:RDF") {
+        ("item", xml.len())
+    } else {
+        return Err("Not an RSS or Atom feed".to_string());
+    };
+    let title = element(&xml[..header_end], &["title"]).map(plain_text).filter(|t| !t.is_empty());
+    let items = elements(xml, item_tag).into_iter().filter_map(parse_item).collect();
+    Ok(ParsedFeed { title, items })
+}
+
+// ==================== Storage ====================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feed {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub interval_minutes: u32,
+    /// Summarize new items (needs the poller's API keys)
+    pub summarize: bool,
+    pub enabled: bool,
+    pub next_poll_at: Option<String>,
+    pub last_polled_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    pub id: String,
+    pub feed_id: String,
+    pub feed_title: String,
+    pub guid: String,
+    pub title: String,
+    pub link: Option<String>,
+    pub published: Option<String>,
+    /// Note the item was saved to, relative to the knowledge base
+    pub note_path: Option<String>,
+    pub summary: Option<String>,
+    pub read: bool,
+    pub fetched_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedRequest {
+    pub url: String,
+    /// The feed's own title when missing
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub interval_minutes: Option<u32>,
+    #[serde(default)]
+    pub summarize: bool,
+}
+
+/// What a poll found; also the `feed-items-added` event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedPoll {
+    pub feed_id: String,
+    pub feed_title: String,
+    pub new_items: Vec<FeedItem>,
+    pub error: Option<String>,
+}
+
+pub fn init_feed_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS feeds (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL UNIQUE,
+            title TEXT NOT NULL,
+            interval_minutes INTEGER NOT NULL,
+            summarize INTEGER NOT NULL DEFAULT 0,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            next_poll_at TEXT,
+            last_polled_at TEXT,
+            last_error TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS feed_items (
+            id TEXT PRIMARY KEY,
+            feed_id TEXT NOT NULL,
+            guid TEXT NOT NULL,
+            title TEXT NOT NULL,
+            link TEXT,
+            published TEXT,
+            note_path TEXT,
+            summary TEXT,
+            read INTEGER NOT NULL DEFAULT 0,
+            fetched_at TEXT NOT NULL,
+            UNIQUE(feed_id, guid)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_feed_items_inbox ON feed_items(read, fetched_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+const FEED_COLUMNS: &str = "id, url, title, interval_minutes, summarize, enabled, next_poll_at, last_polled_at, last_error, created_at";
+
+fn feed_from_row(row: &rusqlite::Row) -> rusqlite::Result<Feed> {
+    Ok(Feed {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        interval_minutes: row.get(3)?,
+        summarize: row.get(4)?,
+        enabled: row.get(5)?,
+        next_poll_at: row.get(6)?,
+        last_polled_at: row.get(7)?,
+        last_error: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+pub fn save_feed(conn: &Connection, feed: &Feed) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO feeds ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", FEED_COLUMNS),
+        params![
+            feed.id,
+            feed.url,
+            feed.title,
+            feed.interval_minutes,
+            feed.summarize,
+            feed.enabled,
+            feed.next_poll_at,
+            feed.last_polled_at,
+            feed.last_error,
+            feed.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_feed(conn: &Connection, id: &str) -> rusqlite::Result<Option<Feed>> {
+    conn.query_row(&format!("SELECT {} FROM feeds WHERE id = ?1", FEED_COLUMNS), params![id], feed_from_row)
+        .optional()
+}
+
+pub fn list_feeds_in(conn: &Connection) -> rusqlite::Result<Vec<Feed>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM feeds ORDER BY title", FEED_COLUMNS))?;
+    let rows = stmt.query_map([], feed_from_row)?;
+    rows.collect()
+}
+
+/// Enabled feeds whose next poll is at or before `now` (a `stamp`)
+pub fn due_feeds(conn: &Connection, now: &str) -> rusqlite::Result<Vec<Feed>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM feeds WHERE enabled = 1 AND (next_poll_at IS NULL OR next_poll_at <= ?1) ORDER BY next_poll_at",
+        FEED_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now], feed_from_row)?;
+    rows.collect()
+}
+
+pub fn has_item(conn: &Connection, feed_id: &str, guid: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM feed_items WHERE feed_id = ?1 AND guid = ?2",
+        params![feed_id, guid],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+}
+
+/// Store an item; false if the feed already had one with its GUID
+pub fn insert_item(conn: &Connection, item: &FeedItem) -> rusqlite::Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO feed_items (id, feed_id, guid, title, link, published, note_path, summary, read, fetched_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![item.id, item.feed_id, item.guid, item.title, item.link, item.published, item.note_path, item.summary, item.read, item.fetched_at],
+    )?;
+    Ok(inserted == 1)
+}
+
+/// Newest first, optionally of one feed and only unread items
+pub fn inbox(conn: &Connection, feed_id: Option<&str>, unread_only: bool, limit: usize) -> rusqlite::Result<Vec<FeedItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, i.feed_id, COALESCE(f.title, ''), i.guid, i.title, i.link, i.published, i.note_path, i.summary, i.read, i.fetched_at
+         FROM feed_items i LEFT JOIN feeds f ON f.id = i.feed_id
+         WHERE (?1 IS NULL OR i.feed_id = ?1) AND (?2 = 0 OR i.read = 0)
+         ORDER BY i.fetched_at DESC, i.published DESC LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(params![feed_id, unread_only, limit as i64], |row| {
+        Ok(FeedItem {
+            id: row.get(0)?,
+            feed_id: row.get(1)?,
+            feed_title: row.get(2)?,
+            guid: row.get(3)?,
+            title: row.get(4)?,
+            link: row.get(5)?,
+            published: row.get(6)?,
+            note_path: row.get(7)?,
+            summary: row.get(8)?,
+            read: row.get(9)?,
+            fetched_at: row.get(10)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// ==================== Polling ====================
+
+#[derive(Serialize)]
+struct ItemFrontmatter<'a> {
+    title: &'a str,
+    created: String,
+    tags: Vec<&'a str>,
+    source: Option<&'a str>,
+    feed: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published: Option<&'a str>,
+}
+
+pub fn render_note(feed_title: &str, item: &ParsedItem, summary: Option<&str>, date: &str) -> String {
+    let frontmatter = ItemFrontmatter {
+        title: &item.title,
+        created: date.to_string(),
+        tags: vec!["feed"],
+        source: item.link.as_deref(),
+        feed: feed_title,
+        published: item.published.as_deref(),
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    let mut body = format!("# {}\n\n", item.title);
+    if let Some(summary) = summary.map(str::trim).filter(|s| !s.is_empty()) {
+        body.push_str(&format!("> **Summary:** {}\n\n", summary.replace('\n', " ")));
+    }
+    if !item.content.trim().is_empty() {
+        body.push_str(&format!("{}\n\n", item.content.trim()));
+    }
+    if let Some(link) = &item.link {
+        body.push_str(&format!("[Read the original]({})\n", link));
+    }
+    format!("---\n{}---\n\n{}\n", yaml, body.trim_end())
+}
+
+fn note_path(kb_root: &Path, feed_title: &str, item: &ParsedItem) -> PathBuf {
+    let folder = kb_root.join(FEEDS_FOLDER).join(crate::research_notes::slugify(feed_title));
+    let slug = crate::research_notes::slugify(&item.title);
+    // An item re-published under a new GUID updates the note of its link
+    if let Some(link) = &item.link {
+        return crate::harvests::note_path(&folder, &folder, &slug, link);
+    }
+    let mut path = folder.join(format!("{}.md", slug));
+    let mut n = 2;
+    while path.exists() {
+        path = folder.join(format!("{}-{}.md", slug, n));
+        n += 1;
+    }
+    path
+}
+
+async fn summarize(item: &ParsedItem) -> Option<String> {
+    let (keys, provider) = FEED_KEYS.lock().unwrap().clone()?;
+    let text: String = item.content.chars().take(MAX_SUMMARY_INPUT_CHARS).collect();
+    let mut agent = keys.agent(provider, SUMMARY_PROMPT.to_string());
+    match agent.run_autonomous_task(format!("Summarize \"{}\":\n\n{}", item.title, text)).await {
+        Ok(summary) => Some(summary.trim().to_string()),
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to summarize '{}': {}", item.title, e);
+            None
+        }
+    }
+}
+
+async fn fetch_feed(url: &str) -> Result<ParsedFeed, String> {
+    let response = client().get(url).send_checked().await.map_err(|e| format!("Failed to fetch feed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {} for {}", response.status(), url));
+    }
+    let xml = response.text().await.map_err(|e| format!("Failed to read feed: {}", e))?;
+    parse_feed(&xml)
+}
+
+/// Fetch a feed, save its new items and schedule its next poll
+async fn poll_feed(app_handle: &tauri::AppHandle, mut feed: Feed) -> FeedPoll {
+    let fetched = fetch_feed(&feed.url).await;
+    let mut poll = FeedPoll { feed_id: feed.id.clone(), feed_title: feed.title.clone(), new_items: Vec::new(), error: None };
+    let parsed = match fetched {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            poll.error = Some(e);
+            ParsedFeed { title: None, items: Vec::new() }
+        }
+    };
+
+    let new_items: Vec<ParsedItem> = {
+        match crate::minimax_api::open_kc_database(Some(app_handle)) {
+            Ok(conn) => parsed
+                .items
+                .into_iter()
+                .filter(|item| !has_item(&conn, &feed.id, &item.guid).unwrap_or(true))
+                .take(MAX_NEW_ITEMS)
+                .collect(),
+            Err(e) => {
+                poll.error = Some(e);
+                Vec::new()
+            }
+        }
+    };
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path();
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut written = Vec::new();
+    for item in new_items {
+        let summary = if feed.summarize { summarize(&item).await } else { None };
+        let mut note = None;
+        if let Ok(kb_root) = &kb_root {
+            let path = note_path(kb_root, &feed.title, &item);
+            crate::note_versions::record_before_write(Some(app_handle), kb_root, &path, "feed");
+            let saved = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::write(&path, render_note(&feed.title, &item, summary.as_deref(), &date)));
+            match saved {
+                Ok(()) => {
+                    note = Some(crate::kb_index::path_key(kb_root, &path));
+                    written.push(path);
+                }
+                Err(e) => tracing::warn!("⚠️ Failed to save feed item '{}': {}", item.title, e),
+            }
+        }
+        poll.new_items.push(FeedItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            feed_id: feed.id.clone(),
+            feed_title: feed.title.clone(),
+            guid: item.guid,
+            title: item.title,
+            link: item.link,
+            published: item.published,
+            note_path: note,
+            summary,
+            read: false,
+            fetched_at: stamp(Utc::now()),
+        });
+    }
+
+    let now = Utc::now();
+    feed.last_polled_at = Some(stamp(now));
+    feed.next_poll_at = Some(stamp(now + Duration::minutes(feed.interval_minutes.max(1) as i64)));
+    feed.last_error = poll.error.clone();
+    let stored = crate::minimax_api::open_kc_database(Some(app_handle)).and_then(|conn| {
+        for item in &poll.new_items {
+            insert_item(&conn, item).map_err(|e| e.to_string())?;
+        }
+        save_feed(&conn, &feed).map_err(|e| e.to_string())
+    });
+    if let Err(e) = stored {
+        tracing::warn!("⚠️ Failed to store items of feed '{}': {}", feed.title, e);
+    }
+
+    if let Ok(kb_root) = &kb_root {
+        if !written.is_empty() {
+            crate::kb_index::apply_watch_changes(app_handle, &written, None);
+            let keys: Vec<String> = written.iter().map(|p| crate::kb_index::path_key(kb_root, p)).collect();
+            crate::events::emit(app_handle, "content-changed", serde_json::json!({
+                "source": kb_root.join(FEEDS_FOLDER),
+                "paths": keys,
+            }));
+        }
+    }
+    match &poll.error {
+        Some(e) => tracing::warn!("⚠️ Feed '{}' failed: {}", feed.title, e),
+        None if !poll.new_items.is_empty() => {
+            tracing::info!("📰 {} new item(s) from '{}'", poll.new_items.len(), feed.title);
+            let _ = app_handle.emit_all("feed-items-added", &poll);
+        }
+        None => {}
+    }
+    poll
+}
+
+fn spawn_poller(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let due = crate::minimax_api::open_kc_database(Some(&app_handle))
+                .and_then(|conn| due_feeds(&conn, &stamp(Utc::now())).map_err(|e| e.to_string()));
+            match due {
+                Ok(feeds) => {
+                    for feed in feeds {
+                        poll_feed(&app_handle, feed).await;
+                    }
+                }
+                Err(e) => tracing::warn!("⚠️ Failed to check feeds: {}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Start polling feeds; the API key enables summaries. Calling it again only replaces the keys.
+#[tauri::command]
+pub fn start_feed_poller(
+    app_handle: tauri::AppHandle,
+    provider: Option<String>,
+    api_key: Option<String>,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+) -> Result<(), String> {
+    if let Some(api_key) = api_key.filter(|k| !k.is_empty()) {
+        let provider = parse_provider(provider.as_deref());
+        let keys = ProviderKeys::new(&provider, &api_key, grok_key, gemini_key, None);
+        *FEED_KEYS.lock().unwrap() = Some((keys, provider));
+    }
+    if !POLLER_STARTED.swap(true, Ordering::SeqCst) {
+        spawn_poller(app_handle);
+        tracing::info!("📰 Feed poller started");
+    }
+    Ok(())
+}
+
+/// Register a feed and poll it right away
+#[tauri::command]
+pub async fn add_feed(app_handle: tauri::AppHandle, request: FeedRequest) -> Result<FeedPoll, String> {
+    let url = request.url.trim().to_string();
+    let parsed = fetch_feed(&url).await?;
+    let title = request
+        .title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .or(parsed.title)
+        .unwrap_or_else(|| url.clone());
+    let feed = Feed {
+        id: uuid::Uuid::new_v4().to_string(),
+        url,
+        title,
+        interval_minutes: request.interval_minutes.unwrap_or(DEFAULT_INTERVAL_MINUTES).max(1),
+        summarize: request.summarize,
+        enabled: true,
+        next_poll_at: None,
+        last_polled_at: None,
+        last_error: None,
+        created_at: stamp(Utc::now()),
+    };
+    {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        let exists: Option<String> = conn
+            .query_row("SELECT title FROM feeds WHERE url = ?1", params![feed.url], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(existing) = exists {
+            return Err(format!("That feed is already registered as '{}'", existing));
+        }
+        save_feed(&conn, &feed).map_err(|e| e.to_string())?;
+    }
+    tracing::info!("📰 Added feed '{}' ({})", feed.title, feed.url);
+    Ok(poll_feed(&app_handle, feed).await)
+}
+
+#[tauri::command]
+pub fn set_feed_enabled(app_handle: tauri::AppHandle, id: String, enabled: bool) -> Result<Feed, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut feed = get_feed(&conn, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Feed '{}' not found", id))?;
+    feed.enabled = enabled;
+    save_feed(&conn, &feed).map_err(|e| e.to_string())?;
+    Ok(feed)
+}
+
+/// Unregister a feed and forget its items; their notes stay in the knowledge base
+#[tauri::command]
+pub fn remove_feed(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    conn.execute("DELETE FROM feed_items WHERE feed_id = ?1", params![id]).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM feeds WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_feeds(app_handle: tauri::AppHandle) -> Result<Vec<Feed>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_feeds_in(&conn).map_err(|e| e.to_string())
+}
+
+/// Feed items, newest first; unread only unless `include_read`
+#[tauri::command]
+pub fn get_feed_inbox(
+    app_handle: tauri::AppHandle,
+    feed_id: Option<String>,
+    include_read: Option<bool>,
+    limit: Option<usize>,
+) -> Result<Vec<FeedItem>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    inbox(&conn, feed_id.as_deref(), !include_read.unwrap_or(false), limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn mark_feed_items_read(app_handle: tauri::AppHandle, ids: Vec<String>, read: Option<bool>) -> Result<usize, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut changed = 0;
+    for id in ids {
+        changed += conn
+            .execute("UPDATE feed_items SET read = ?2 WHERE id = ?1", params![id, read.unwrap_or(true)])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(changed)
+}
+
+/// Poll every enabled feed now, outside their intervals
+#[tauri::command]
+pub async fn poll_feeds_now(app_handle: tauri::AppHandle) -> Result<Vec<FeedPoll>, String> {
+    let feeds = {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        list_feeds_in(&conn).map_err(|e| e.to_string())?
+    };
+    let mut polls = Vec::new();
+    for feed in feeds.into_iter().filter(|f| f.enabled) {
+        polls.push(poll_feed(&app_handle, feed).await);
+    }
+    Ok(polls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS: &str = r#"<?xml version="1.0"?><rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>
+            <title>Rust Blog</title><link>https://blog.rust-lang.org/</link>
+            <item><title>Announcing Rust 1.80 &amp; more</title><link>https://blog.rust-lang.org/2024/07/25/Rust-1.80.0.html</link>
+              <guid isPermaLink="true">https://blog.rust-lang.org/2024/07/25/Rust-1.80.0.html</guid>
+              <pubDate>Thu, 25 Jul 2024 00:00:00 +0000</pubDate>
+              <description>&lt;p&gt;LazyCell is &lt;strong&gt;stable&lt;/strong&gt;.&lt;/p&gt;</description></item>
+            <item><title><![CDATA[No GUID]]></title><link>https://blog.rust-lang.org/x</link></item>
+        </channel></rss>"#;
+
+    const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom"><title type="text">arXiv cs.CL</title>
+            <link rel="self" href="https://example.org/feed.atom"/>
+            <entry><id>urn:uuid:1</id><title>Attention</title><updated>2024-01-02T03:04:05+01:00</updated>
+              <link rel="alternate" href="https://example.org/1"/><summary type="html">&lt;p&gt;Transformers.&lt;/p&gt;</summary></entry>
+        </feed>"#;
+
+    fn feed_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_feed_tables(&conn).unwrap();
+        conn
+    }
+
+    fn attention() -> FeedItem {
+        FeedItem {
+            id: "i1".to_string(),
+            feed_id: "f1".to_string(),
+            feed_title: String::new(),
+            guid: "urn:uuid:1".to_string(),
+            title: "Attention".to_string(),
+            link: None,
+            published: None,
+            note_path: None,
+            summary: None,
+            read: false,
+            fetched_at: "2026-10-16T07:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let feed = parse_feed(RSS).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("Rust Blog"));
+        assert_eq!(feed.items.len(), 2);
+        assert_eq!(feed.items[0].title, "Announcing Rust 1.80 & more");
+        assert_eq!(feed.items[0].published.as_deref(), Some("2024-07-25T00:00:00Z"));
+        assert_eq!(feed.items[0].content, "LazyCell is **stable**.");
+    }
+
+    #[test]
+    fn test_rss_item_without_guid_uses_its_link() {
+        assert_eq!(parse_feed(RSS).unwrap().items[1].guid, "https://blog.rust-lang.org/x");
+    }
+
+    #[test]
+    fn test_parse_atom() {
+        let feed = parse_feed(ATOM).unwrap();
+        assert_eq!(feed.title.as_deref(), Some("arXiv cs.CL"));
+        let entry = &feed.items[0];
+        assert_eq!((entry.guid.as_str(), entry.link.as_deref()), ("urn:uuid:1", Some("https://example.org/1")));
+        assert_eq!(entry.published.as_deref(), Some("2024-01-02T02:04:05Z"));
+    }
+
+    #[test]
+    fn test_html_is_not_a_feed() {
+        assert!(parse_feed("<html><body>Not a feed</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_items_are_deduplicated_by_guid() {
+        let conn = feed_db();
+        let item = attention();
+        assert!(insert_item(&conn, &item).unwrap());
+        assert!(!insert_item(&conn, &FeedItem { id: "i2".to_string(), ..item.clone() }).unwrap());
+        assert!(has_item(&conn, "f1", "urn:uuid:1").unwrap());
+    }
+
+    #[test]
+    fn test_inbox_filters_read_items() {
+        let conn = feed_db();
+        insert_item(&conn, &attention()).unwrap();
+        assert_eq!(inbox(&conn, None, true, 10).unwrap().len(), 1);
+        conn.execute("UPDATE feed_items SET read = 1", []).unwrap();
+        assert!(inbox(&conn, Some("f1"), true, 10).unwrap().is_empty());
+        assert_eq!(inbox(&conn, Some("f1"), false, 10).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,324 @@
+/// Files dropped on the app window go into the knowledge base
+///
+/// With `[file_drop] enabled` (the default), PDFs, markdown notes, images and code files
+/// dropped anywhere on the window are saved under the drop folder (`dumps/dropped/` unless
+/// configured). Markdown is copied with a frontmatter block added if it has none. PDFs and
+/// images are copied to the folder's `assets/` with a note next to them holding the PDF's
+/// text or, with tesseract installed, the image's. Code files become a note with the code
+/// in a fenced block. The new notes are indexed and a `files-dropped` event reports what was
+/// saved and what was skipped and why.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::config::FileDropConfig;
+
+/// Code files by extension, with the language their fence is tagged with
+const CODE_LANGUAGES: [(&str, &str); 32] = [
+    ("rs", "rust"), ("py", "python"), ("js", "javascript"), ("mjs", "javascript"), ("jsx", "jsx"),
+    ("ts", "typescript"), ("tsx", "tsx"), ("go", "go"), ("java", "java"), ("kt", "kotlin"),
+    ("swift", "swift"), ("c", "c"), ("h", "c"), ("cpp", "cpp"), ("hpp", "cpp"), ("cs", "csharp"),
+    ("rb", "ruby"), ("php", "php"), ("lua", "lua"), ("sh", "bash"), ("ps1", "powershell"),
+    ("sql", "sql"), ("html", "html"), ("css", "css"), ("scss", "scss"), ("json", "json"),
+    ("toml", "toml"), ("yaml", "yaml"), ("yml", "yaml"), ("xml", "xml"), ("txt", "text"), ("csv", "csv"),
+];
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropKind {
+    Markdown,
+    Pdf,
+    Image,
+    Code,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedFile {
+    /// Where the file was dropped from
+    pub source: String,
+    pub kind: DropKind,
+    /// Note path relative to the knowledge base
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DropSummary {
+    pub folder: String,
+    pub ingested: Vec<DroppedFile>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+fn kind_of(path: &Path) -> Option<DropKind> {
+    let ext = extension(path);
+    match ext.as_str() {
+        "md" | "markdown" => Some(DropKind::Markdown),
+        "pdf" => Some(DropKind::Pdf),
+        e if IMAGE_EXTENSIONS.contains(&e) => Some(DropKind::Image),
+        e if CODE_LANGUAGES.iter().any(|(code, _)| *code == e) => Some(DropKind::Code),
+        _ => None,
+    }
+}
+
+/// The first free `<stem>.md`, `<stem>-2.md`, ... in `dir`
+fn free_note_path(dir: &Path, stem: &str) -> PathBuf {
+    let mut n = 1;
+    loop {
+        let file = if n == 1 { format!("{}.md", stem) } else { format!("{}-{}.md", stem, n) };
+        let path = dir.join(file);
+        if !path.exists() {
+            return path;
+        }
+        n += 1;
+    }
+}
+
+/// A note for a dropped file linked as `link`, with `body` (extracted text or code) under it
+fn file_note(title: &str, created: &str, kind: DropKind, link: Option<&str>, body: &str) -> String {
+    let tag = match kind {
+        DropKind::Markdown => "markdown",
+        DropKind::Pdf => "pdf",
+        DropKind::Image => "image",
+        DropKind::Code => "code",
+    };
+    // JSON strings are valid YAML scalars, which takes care of quoting
+    let mut note = format!(
+        "---\ntitle: {}\ncreated: {}\ntags: [dropped, {}]\nsource: drop\n---\n\n# {}\n\n",
+        serde_json::to_string(title).unwrap_or_default(),
+        created,
+        tag,
+        title
+    );
+    if let Some(link) = link {
+        let bang = if kind == DropKind::Image { "!" } else { "" };
+        note.push_str(&format!("{}[{}]({})\n\n", bang, title, link.replace(' ', "%20")));
+    }
+    if !body.trim().is_empty() {
+        note.push_str(body.trim_end());
+        note.push('\n');
+    }
+    note
+}
+
+/// `code` in a fence long enough that backticks inside it don't close it
+fn code_block(code: &str, language: &str) -> String {
+    let longest = code
+        .lines()
+        .map(|l| l.trim_start().chars().take_while(|c| *c == '`').count())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, language, code.trim_end(), fence)
+}
+
+/// Extract a PDF's text; pdf-extract can panic on malformed files
+fn pdf_text(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes))
+        .map_err(|_| "PDF text extraction crashed on this file".to_string())?
+        .map_err(|e| format!("Failed to extract PDF text: {}", e))
+}
+
+/// Save one dropped file under `folder`, returning its kind and the note written
+fn ingest_file(source: &Path, folder: &Path, config: &FileDropConfig, created: &str) -> Result<(DropKind, PathBuf), String> {
+    if source.is_dir() {
+        return Err("Folders aren't ingested on drop; import them instead".to_string());
+    }
+    let kind = kind_of(source).ok_or("Not a PDF, markdown, image or code file")?;
+    let size = std::fs::metadata(source).map_err(|e| e.to_string())?.len();
+    if size > config.max_mb * 1024 * 1024 {
+        return Err(format!("Larger than {} MB", config.max_mb));
+    }
+    let file_name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let stem = source.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    std::fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+
+    let (note_path, note) = match kind {
+        DropKind::Markdown => {
+            let content = std::fs::read_to_string(source).map_err(|e| e.to_string())?;
+            (free_note_path(folder, &stem), crate::kb_import::ensure_frontmatter(&content, &stem, created))
+        }
+        DropKind::Code => {
+            let code = std::fs::read_to_string(source).map_err(|_| "Not a text file".to_string())?;
+            let language = CODE_LANGUAGES.iter().find(|(e, _)| *e == extension(source)).map_or("", |(_, l)| l);
+            // Keeps main.rs and main.py apart
+            (free_note_path(folder, &file_name), file_note(&file_name, created, kind, None, &code_block(&code, language)))
+        }
+        DropKind::Pdf | DropKind::Image => {
+            let assets = folder.join(crate::attachments::ASSETS_DIR);
+            std::fs::create_dir_all(&assets).map_err(|e| e.to_string())?;
+            let dest = crate::attachments::destination_for(&assets, source).map_err(|e| e.to_string())?;
+            if !dest.exists() {
+                std::fs::copy(source, &dest).map_err(|e| format!("Failed to copy: {}", e))?;
+            }
+            let text = match kind {
+                // A scanned PDF still gets its note, just without text
+                DropKind::Pdf => pdf_text(&dest).unwrap_or_else(|e| {
+                    tracing::warn!("⚠️ {}: {}", file_name, e);
+                    String::new()
+                }),
+                _ if config.ocr_images && crate::screenshot::on_path("tesseract").is_some() => {
+                    crate::screenshot::tesseract(&dest, "eng").unwrap_or_else(|e| {
+                        tracing::warn!("⚠️ OCR failed for {}: {}", file_name, e);
+                        String::new()
+                    })
+                }
+                _ => String::new(),
+            };
+            let body = if text.trim().is_empty() { String::new() } else { format!("## Text\n\n{}", text.trim()) };
+            let link = format!("{}/{}", crate::attachments::ASSETS_DIR, dest.file_name().unwrap_or_default().to_string_lossy());
+            (free_note_path(folder, &stem), file_note(&stem, created, kind, Some(&link), &body))
+        }
+    };
+    std::fs::write(&note_path, note).map_err(|e| format!("Failed to write {}: {}", note_path.display(), e))?;
+    Ok((kind, note_path))
+}
+
+/// Save `paths` under the drop folder, index them and report what happened
+pub async fn ingest(app_handle: &AppHandle, paths: Vec<PathBuf>) -> Result<DropSummary, String> {
+    let config = crate::config::current().file_drop.clone();
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let folder = kb_root.join(&config.folder);
+    let created = crate::config::now().format("%Y-%m-%d").to_string();
+
+    let (summary, written) = {
+        // Index once at the end instead of reacting to every file
+        let _watch_pause = crate::file_watcher::pause_for_writes(vec![folder.clone()]);
+        let kb_root = kb_root.clone();
+        let folder = folder.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut summary = DropSummary { folder: config.folder.clone(), ..Default::default() };
+            let mut written = Vec::new();
+            for source in paths {
+                let source_name = source.display().to_string();
+                match ingest_file(&source, &folder, &config, &created) {
+                    Ok((kind, note)) => {
+                        summary.ingested.push(DroppedFile { source: source_name, kind, path: crate::kb_index::path_key(&kb_root, &note) });
+                        written.push(note);
+                    }
+                    Err(reason) => summary.skipped.push(SkippedFile { source: source_name, reason }),
+                }
+            }
+            (summary, written)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    if !written.is_empty() {
+        crate::kb_index::apply_watch_changes(app_handle, &written, None);
+        crate::events::emit(app_handle, "content-changed", serde_json::json!({
+            "source": folder,
+            "paths": summary.ingested.iter().map(|f| &f.path).collect::<Vec<_>>(),
+        }));
+    }
+    tracing::info!("📥 Ingested {} dropped file(s) into {} ({} skipped)", summary.ingested.len(), summary.folder, summary.skipped.len());
+    let _ = app_handle.emit_all("files-dropped", &summary);
+    Ok(summary)
+}
+
+/// Handle files dropped on a window, unless `[file_drop]` is switched off
+pub fn on_drop(app_handle: &AppHandle, paths: Vec<PathBuf>) {
+    if !crate::config::current().file_drop.enabled || paths.is_empty() {
+        return;
+    }
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = ingest(&handle, paths).await {
+            tracing::warn!("⚠️ Failed to ingest dropped files: {}", e);
+            let _ = handle.emit_all("files-dropped", serde_json::json!({ "error": e }));
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Ingest files the same way as dropping them on the window
+#[tauri::command]
+pub async fn ingest_dropped_files(app_handle: AppHandle, paths: Vec<String>) -> Result<DropSummary, String> {
+    ingest(&app_handle, paths.into_iter().map(PathBuf::from).collect()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> FileDropConfig {
+        FileDropConfig { max_mb: 1, ..Default::default() }
+    }
+
+    /// A source folder holding `name`
+    fn source_with(name: &str, contents: &[u8]) -> tempfile::TempDir {
+        let source = tempfile::tempdir().unwrap();
+        std::fs::write(source.path().join(name), contents).unwrap();
+        source
+    }
+
+    #[test]
+    fn test_kind_of() {
+        assert_eq!(kind_of(Path::new("/tmp/Paper.PDF")), Some(DropKind::Pdf));
+        assert_eq!(kind_of(Path::new("notes.markdown")), Some(DropKind::Markdown));
+        assert_eq!(kind_of(Path::new("shot.jpeg")), Some(DropKind::Image));
+        assert_eq!(kind_of(Path::new("lib.rs")), Some(DropKind::Code));
+        assert_eq!(kind_of(Path::new("setup.exe")), None);
+    }
+
+    #[test]
+    fn test_code_block_fence_outgrows_backticks_inside() {
+        assert_eq!(code_block("let s = \"```\";", "rust"), "```rust\nlet s = \"```\";\n```");
+        assert_eq!(code_block("/*\n```\n*/", "c"), "````c\n/*\n```\n*/\n````");
+    }
+
+    #[test]
+    fn test_code_file_becomes_a_fenced_note() {
+        let source = source_with("main.rs", b"fn main() {}\n");
+        let kb = tempfile::tempdir().unwrap();
+        let folder = kb.path().join("dumps/dropped");
+        let (kind, note) = ingest_file(&source.path().join("main.rs"), &folder, &config(), "2026-10-16").unwrap();
+        assert_eq!((kind, note.file_name().unwrap().to_str().unwrap()), (DropKind::Code, "main.rs.md"));
+        let content = std::fs::read_to_string(&note).unwrap();
+        assert!(content.contains("tags: [dropped, code]") && content.ends_with("```rust\nfn main() {}\n```\n"));
+    }
+
+    #[test]
+    fn test_markdown_gets_frontmatter_and_a_free_name() {
+        let source = source_with("Ideas.md", b"# Ideas\n\nSpaced reading");
+        let kb = tempfile::tempdir().unwrap();
+        let folder = kb.path().join("dumps/dropped");
+        let (_, first) = ingest_file(&source.path().join("Ideas.md"), &folder, &config(), "2026-10-16").unwrap();
+        let (_, second) = ingest_file(&source.path().join("Ideas.md"), &folder, &config(), "2026-10-16").unwrap();
+        assert!(std::fs::read_to_string(first).unwrap().starts_with("---\ntitle: \"Ideas\""));
+        assert!(second.ends_with("Ideas-2.md"));
+    }
+
+    #[test]
+    fn test_image_is_copied_to_assets_and_embedded() {
+        let source = source_with("diagram.png", &[0x89, b'P', b'N', b'G']);
+        let kb = tempfile::tempdir().unwrap();
+        let folder = kb.path().join("dumps/dropped");
+        let image = FileDropConfig { ocr_images: false, ..config() };
+        let (_, note) = ingest_file(&source.path().join("diagram.png"), &folder, &image, "2026-10-16").unwrap();
+        assert!(std::fs::read_to_string(note).unwrap().contains("![diagram](assets/diagram.png)"));
+        assert!(folder.join("assets/diagram.png").is_file());
+    }
+
+    #[test]
+    fn test_oversized_files_and_folders_are_skipped() {
+        let source = source_with("big.txt", "x".repeat(1024 * 1024 + 1).as_bytes());
+        let kb = tempfile::tempdir().unwrap();
+        let folder = kb.path().join("dumps/dropped");
+        assert_eq!(ingest_file(&source.path().join("big.txt"), &folder, &config(), "2026-10-16").unwrap_err(), "Larger than 1 MB");
+        assert!(ingest_file(source.path(), &folder, &config(), "2026-10-16").is_err());
+    }
+}
@@ -1,82 +1,802 @@
-use notify_debouncer_full::{new_debouncer, notify::*, DebounceEventResult};
-use std::path::PathBuf;
-use std::time::Duration;
-use tauri::{App, Manager};
-
-pub fn setup_file_watcher(app: &App) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let app_handle = app.app_handle();
-
-    // Get repository root
-    let current = std::env::current_dir()?;
-    let repo_root = if current.file_name().and_then(|n| n.to_str()) == Some("src-tauri") {
-        current.parent()
-            .and_then(|p| p.parent())
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Could not find repository root"))?
-            .to_path_buf()
-    } else if current.file_name().and_then(|n| n.to_str()) == Some("startup-strategy-app") {
-        current.parent()
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Could not find parent directory"))?
-            .to_path_buf()
-    } else {
-        // Production Mode: Use User Documents
-        let user_dirs = directories::UserDirs::new()
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Could not find user directories"))?;
-        
-        let doc_dir = user_dirs.document_dir()
-            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Could not find Documents directory"))?;
-            
-        doc_dir.join("KnowledgeCompanion")
-    };
+use notify_debouncer_full::{new_debouncer, notify::*, DebounceEventResult, Debouncer, FileIdMap};
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
 
-    eprintln!("Setting up file watcher for: {:?}", repo_root);
+use crate::repo_indexer::RepoIndex;
 
-    // Folders to watch
-    let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections"];
-    let watch_paths: Vec<PathBuf> = folders
-        .iter()
-        .map(|f| repo_root.join(f))
-        .filter(|p| p.exists())
-        .collect();
+/// Watcher for the currently indexed repository; replaced whenever another repo is indexed
+#[derive(Default)]
+pub struct RepoWatcher(Mutex<Option<Debouncer<RecommendedWatcher, FileIdMap>>>);
+
+const DEBOUNCE_DELAY: Duration = Duration::from_secs(2);
+const KB_ROOT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Pause state shared by both watchers. Manual pauses come from the UI and hold back
+/// everything; automatic pauses wrap the app's own writes and only hold back events under the
+/// paths being written, for one debounce window after the writes finish, since the debouncer
+/// delivers those writes late.
+#[derive(Debug, Default)]
+struct PauseState {
+    manual: bool,
+    writes: Vec<PausedWrites>,
+    next_id: usize,
+}
+
+/// The files or folders one `WritePauseGuard` covers; `until` is set when it's dropped
+#[derive(Debug)]
+struct PausedWrites {
+    id: usize,
+    paths: Vec<PathBuf>,
+    until: Option<Instant>,
+}
+
+impl PausedWrites {
+    fn live(&self, now: Instant) -> bool {
+        self.until.map_or(true, |until| now < until)
+    }
+}
+
+impl PauseState {
+    fn automatic(&self, now: Instant) -> bool {
+        self.writes.iter().any(|w| w.live(now))
+    }
+
+    /// Whether events for `path` are held back
+    fn suppresses(&self, path: &Path, now: Instant) -> bool {
+        self.manual || self.writes.iter().filter(|w| w.live(now)).any(|w| w.paths.iter().any(|p| path.starts_with(p)))
+    }
+
+    fn begin(&mut self, paths: Vec<PathBuf>) -> usize {
+        self.next_id += 1;
+        self.writes.push(PausedWrites { id: self.next_id, paths, until: None });
+        self.next_id
+    }
+
+    fn cover(&mut self, id: usize, path: PathBuf) {
+        if let Some(writes) = self.writes.iter_mut().find(|w| w.id == id) {
+            writes.paths.push(path);
+        }
+    }
+
+    /// Keep `id`'s paths held back for `grace` more, and forget pauses that have run out
+    fn end(&mut self, id: usize, now: Instant, grace: Duration) {
+        if let Some(writes) = self.writes.iter_mut().find(|w| w.id == id) {
+            writes.until = Some(now + grace);
+        }
+        self.writes.retain(|w| w.live(now));
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PAUSE_STATE: Mutex<PauseState> = Mutex::new(PauseState::default());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherStatus {
+    pub paused: bool,
+    pub manual: bool,
+    pub automatic: bool,
+}
+
+pub fn watcher_status() -> WatcherStatus {
+    let state = PAUSE_STATE.lock().unwrap();
+    let automatic = state.automatic(Instant::now());
+    WatcherStatus {
+        paused: state.manual || automatic,
+        manual: state.manual,
+        automatic,
+    }
+}
+
+/// Whether watcher events for `path` are held back, by a manual pause or the app's own writes
+pub fn is_suppressed(path: &Path) -> bool {
+    PAUSE_STATE.lock().unwrap().suppresses(path, Instant::now())
+}
+
+/// Suppresses watcher events for the covered paths until dropped (plus one debounce window)
+pub struct WritePauseGuard(usize);
+
+/// Hold back watcher events for `paths` (files, or folders and everything in them)
+pub fn pause_for_writes(paths: Vec<PathBuf>) -> WritePauseGuard {
+    WritePauseGuard(PAUSE_STATE.lock().unwrap().begin(paths))
+}
+
+impl WritePauseGuard {
+    /// Also hold back events for `path`, for writes whose targets are only known as they go
+    pub fn cover(&self, path: &Path) {
+        PAUSE_STATE.lock().unwrap().cover(self.0, path.to_path_buf());
+    }
+}
+
+impl Drop for WritePauseGuard {
+    fn drop(&mut self) {
+        PAUSE_STATE.lock().unwrap().end(self.0, Instant::now(), DEBOUNCE_DELAY + Duration::from_millis(500));
+    }
+}
+
+#[tauri::command]
+pub async fn pause_watcher(app_handle: AppHandle) -> std::result::Result<WatcherStatus, String> {
+    PAUSE_STATE.lock().unwrap().manual = true;
+    let status = watcher_status();
+    let _ = app_handle.emit_all("watcher-status", &status);
+    tracing::info!("⏸️ File watcher paused");
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn resume_watcher(app_handle: AppHandle) -> std::result::Result<WatcherStatus, String> {
+    PAUSE_STATE.lock().unwrap().manual = false;
+    let status = watcher_status();
+    let _ = app_handle.emit_all("watcher-status", &status);
+    tracing::info!("▶️ File watcher resumed");
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_watcher_status() -> std::result::Result<WatcherStatus, String> {
+    Ok(watcher_status())
+}
+
+/// The watcher is set up in the background after launch, and may have failed to start
+const WATCHER_STARTING: &str = "The content watcher isn't running yet; try again in a moment";
+
+/// Content watcher plus the roots it currently watches (built-in folders and user-added paths)
+pub struct ContentWatcher {
+    debouncer: Mutex<Debouncer<RecommendedWatcher, FileIdMap>>,
+    roots: Arc<Mutex<Vec<PathBuf>>>,
+    kb_root: Mutex<PathBuf>,
+}
+
+/// User-registered watch directories, persisted in app data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    #[serde(default)]
+    pub extra_paths: Vec<PathBuf>,
+    /// Re-embed changed markdown sections into the TKG under this user (off when unset)
+    #[serde(default)]
+    pub reembed_user_id: Option<String>,
+    /// Globs for editor temp/swap files and tool folders whose changes are ignored
+    #[serde(default = "default_ignore_globs")]
+    pub ignore_globs: Vec<String>,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            extra_paths: Vec::new(),
+            reembed_user_id: None,
+            ignore_globs: default_ignore_globs(),
+        }
+    }
+}
+
+fn default_ignore_globs() -> Vec<String> {
+    [
+        ".obsidian/**",
+        ".trash/**",
+        ".git/**",
+        "*.tmp",
+        "*.temp",
+        "*.swp",
+        "*.swo",
+        "*~",
+        ".#*",
+        ".DS_Store",
+        "*.crdownload",
+        "*.part",
+    ]
+    .iter()
+    .map(|g| g.to_string())
+    .collect()
+}
+
+/// Compile ignore globs. Patterns without a leading `/` or `**/` match at any depth.
+/// Invalid patterns are skipped with a warning rather than disabling the watcher.
+pub fn build_ignore_set(globs: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in globs {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            continue;
+        }
+        let anchored = if pattern.starts_with('/') || pattern.starts_with("**/") {
+            pattern.to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        match Glob::new(&anchored) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => tracing::warn!("Invalid watcher ignore glob '{}': {}", pattern, e),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+fn watch_config_path(app_handle: &AppHandle) -> Option<PathBuf> {
+    app_handle.path_resolver().app_data_dir().map(|d| d.join("watch_paths.json"))
+}
+
+pub fn load_watch_config(app_handle: &AppHandle) -> WatchConfig {
+    watch_config_path(app_handle)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_watch_config(app_handle: &AppHandle, config: &WatchConfig) -> std::result::Result<(), String> {
+    let path = watch_config_path(app_handle).ok_or("Failed to get app data dir")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+    Rename,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    /// Previous location for renames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<PathBuf>,
+    /// Watched root the change belongs to
+    pub source: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangeBatch {
+    pub count: usize,
+    pub changes: Vec<FileChange>,
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("md")
+}
+
+/// Reduce a burst of raw events (e.g. a git checkout) to one net change per path.
+/// A file created and deleted within the burst disappears entirely; a delete followed
+/// by a create becomes a modify; access events are ignored.
+pub fn collapse_events<'a, I>(events: I) -> Vec<FileChange>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    let mut changes: Vec<FileChange> = Vec::new();
+
+    fn record(changes: &mut Vec<FileChange>, path: &Path, kind: ChangeKind, from: Option<PathBuf>) {
+        let existing = changes.iter().position(|c| c.path == path);
+        let previous = existing.map(|i| changes[i].kind);
+
+        let merged = match (previous, kind) {
+            (Some(ChangeKind::Create), ChangeKind::Delete) => None,
+            (Some(ChangeKind::Create), ChangeKind::Modify) => Some(ChangeKind::Create),
+            (Some(ChangeKind::Rename), ChangeKind::Modify) => Some(ChangeKind::Rename),
+            (Some(ChangeKind::Delete), ChangeKind::Create) => Some(ChangeKind::Modify),
+            (_, kind) => Some(kind),
+        };
+
+        match (existing, merged) {
+            (Some(i), None) => {
+                changes.remove(i);
+            }
+            (Some(i), Some(kind)) => {
+                changes[i].kind = kind;
+                if from.is_some() {
+                    changes[i].from = from;
+                }
+            }
+            (None, Some(kind)) => changes.push(FileChange {
+                path: path.to_path_buf(),
+                kind,
+                from,
+                source: None,
+            }),
+            (None, None) => {}
+        }
+    }
+
+    for event in events {
+        match &event.kind {
+            EventKind::Create(_) => {
+                for path in &event.paths {
+                    record(&mut changes, path, ChangeKind::Create, None);
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in &event.paths {
+                    record(&mut changes, path, ChangeKind::Delete, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() >= 2 => {
+                let (from, to) = (&event.paths[0], &event.paths[1]);
+                // Renaming a file created in this burst is just a create at the new path
+                let was_created = changes.iter().any(|c| &c.path == from && c.kind == ChangeKind::Create);
+                changes.retain(|c| &c.path != from);
+                if was_created {
+                    record(&mut changes, to, ChangeKind::Create, None);
+                } else {
+                    record(&mut changes, to, ChangeKind::Rename, Some(from.clone()));
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // Half of a rename (or a platform that can't pair them): infer from existence
+                for path in &event.paths {
+                    let kind = if path.exists() { ChangeKind::Create } else { ChangeKind::Delete };
+                    record(&mut changes, path, kind, None);
+                }
+            }
+            EventKind::Modify(_) | EventKind::Any => {
+                for path in &event.paths {
+                    record(&mut changes, path, ChangeKind::Modify, None);
+                }
+            }
+            EventKind::Access(_) | EventKind::Other => {}
+        }
+    }
+
+    changes
+}
+
+pub fn setup_file_watcher(app_handle: &AppHandle) -> std::result::Result<(), Box<dyn std::error::Error>> {
+
+    // Follow the same knowledge base location the agent and commands use
+    // (repo checkout in dev, Documents/KnowledgeCompanion in production)
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
+
+    tracing::info!("Setting up file watcher for: {:?}", kb_root);
+
+    let mut watch_paths = kb_watch_folders(&kb_root);
+
+    // Plus any directories the user registered (e.g. an external Obsidian vault)
+    for extra in load_watch_config(app_handle).extra_paths {
+        if extra.exists() {
+            watch_paths.push(extra);
+        } else {
+            tracing::warn!("Registered watch path no longer exists: {:?}", extra);
+        }
+    }
 
     if watch_paths.is_empty() {
-        eprintln!("Warning: No content folders found to watch");
-        return Ok(());
+        tracing::warn!("No content folders found to watch");
     }
 
+    let roots = Arc::new(Mutex::new(watch_paths.clone()));
+
     // Clone app_handle for use in the closure
     let app_handle_clone = app_handle.clone();
+    let roots_clone = roots.clone();
 
     // Create debouncer with 2 second delay
     let mut debouncer = new_debouncer(
-        Duration::from_secs(2),
+        DEBOUNCE_DELAY,
         None,
         move |result: DebounceEventResult| {
-            match result {
-                Ok(events) => {
-                    for event in events {
-                        // Only emit for markdown files
-                        if let Some(path) = event.paths.first() {
-                            if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                                eprintln!("File change detected: {:?}", path);
-                                // Emit event to frontend
-                                let _ = app_handle_clone.emit_all("content-changed", ());
-                            }
-                        }
-                    }
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    tracing::error!("File watcher error: {:?}", e);
+                    return;
+                }
+            };
+
+            // Even changes nobody is told about (while paused) leave the cached listings stale
+            crate::listing_cache::invalidate();
+
+            let config = load_watch_config(&app_handle_clone);
+            let ignored = build_ignore_set(&config.ignore_globs);
+
+            // Collapse the whole debounced burst into one change per file; only markdown matters here
+            let mut changes: Vec<FileChange> = collapse_events(events.iter().map(|e| &e.event))
+                .into_iter()
+                .filter(|c| !is_suppressed(&c.path))
+                .filter(|c| !ignored.is_match(&c.path))
+                .filter(|c| is_markdown(&c.path) || c.from.as_deref().map(is_markdown).unwrap_or(false))
+                .collect();
+
+            if changes.is_empty() {
+                return;
+            }
+
+            // Report which watched root each change came from
+            {
+                let roots = roots_clone.lock().unwrap();
+                for change in &mut changes {
+                    change.source = roots
+                        .iter()
+                        .filter(|root| change.path.starts_with(root))
+                        .max_by_key(|root| root.components().count())
+                        .cloned();
+                }
+            }
+
+            tracing::info!("File changes detected: {} file(s)", changes.len());
+
+            // Keep search_knowledge (and optionally the TKG) fresh for exactly these files
+            let touched: Vec<PathBuf> = changes
+                .iter()
+                .flat_map(|c| std::iter::once(c.path.clone()).chain(c.from.clone()))
+                .collect();
+            crate::kb_index::apply_watch_changes(&app_handle_clone, &touched, config.reembed_user_id.clone());
+
+            let _ = app_handle_clone.emit_all("file-changes", FileChangeBatch {
+                count: changes.len(),
+                changes: changes.clone(),
+            });
+
+            // One coarse refresh per source root for listeners that just reload
+            let mut by_source: Vec<(Option<PathBuf>, Vec<PathBuf>)> = Vec::new();
+            for change in changes {
+                match by_source.iter_mut().find(|(source, _)| *source == change.source) {
+                    Some((_, paths)) => paths.push(change.path),
+                    None => by_source.push((change.source, vec![change.path])),
                 }
-                Err(e) => eprintln!("File watcher error: {:?}", e),
+            }
+            for (source, paths) in by_source {
+                crate::events::emit(&app_handle_clone, "content-changed", serde_json::json!({
+                    "source": source,
+                    "paths": paths,
+                }));
             }
         },
     )?;
 
     // Watch all content directories
     for path in watch_paths {
-        eprintln!("Watching: {:?}", path);
+        tracing::debug!("Watching: {:?}", path);
         debouncer.watcher().watch(&path, RecursiveMode::Recursive)?;
     }
 
     // Keep watcher alive by moving it into app state
-    app_handle.manage(debouncer);
+    app_handle.manage(ContentWatcher {
+        debouncer: Mutex::new(debouncer),
+        roots,
+        kb_root: Mutex::new(kb_root),
+    });
+    crate::listing_cache::set_watched(true);
+
+    // The knowledge base location can change at runtime (e.g. cwd or Documents moved);
+    // re-check periodically so production builds keep live refresh
+    let sync_handle = app_handle.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(KB_ROOT_CHECK_INTERVAL);
+        if let Err(e) = sync_kb_root(&sync_handle) {
+            tracing::warn!("Failed to follow knowledge base path: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+/// Built-in content folders of a knowledge base root that exist on disk
+fn kb_watch_folders(kb_root: &Path) -> Vec<PathBuf> {
+    crate::kb_index::KB_FOLDERS
+        .iter()
+        .map(|f| kb_root.join(f))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+/// Re-point the content watcher if `get_knowledge_base_path()` now resolves elsewhere.
+/// Returns true when the watched root changed.
+pub fn sync_kb_root(app_handle: &AppHandle) -> std::result::Result<bool, String> {
+    let new_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let watcher = match app_handle.try_state::<ContentWatcher>() {
+        Some(w) => w,
+        None => return Ok(false),
+    };
+
+    let old_root = {
+        let mut current = watcher.kb_root.lock().unwrap();
+        if *current == new_root {
+            return Ok(false);
+        }
+        std::mem::replace(&mut *current, new_root.clone())
+    };
+
+    tracing::info!("Knowledge base moved: {:?} -> {:?}", old_root, new_root);
+
+    let old_folders = kb_watch_folders(&old_root);
+    let new_folders = kb_watch_folders(&new_root);
+    {
+        let mut debouncer = watcher.debouncer.lock().unwrap();
+        for folder in &old_folders {
+            let _ = debouncer.watcher().unwatch(folder);
+        }
+        for folder in &new_folders {
+            debouncer.watcher()
+                .watch(folder, RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to watch {}: {}", folder.display(), e))?;
+            tracing::debug!("Watching: {:?}", folder);
+        }
+    }
+    {
+        let mut roots = watcher.roots.lock().unwrap();
+        roots.retain(|r| !old_folders.contains(r));
+        roots.extend(new_folders);
+    }
+
+    // The new location's content needs indexing and the UI needs a full reload
+    crate::kb_index::refresh_in_background(app_handle.clone(), load_watch_config(app_handle).extra_paths);
+    crate::events::emit(app_handle, "content-changed", serde_json::json!({
+        "source": new_root,
+        "paths": [],
+    }));
+
+    Ok(true)
+}
+
+/// Force a check of the knowledge base location (e.g. after changing settings)
+#[tauri::command]
+pub async fn sync_watcher_root(app_handle: AppHandle) -> std::result::Result<bool, String> {
+    sync_kb_root(&app_handle)
+}
+
+/// Start watching an additional directory and persist it for future launches
+#[tauri::command]
+pub async fn add_watch_path(app_handle: AppHandle, path: String) -> std::result::Result<Vec<PathBuf>, String> {
+    let path = PathBuf::from(&path);
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", path.display()));
+    }
+    let path = path.canonicalize().map_err(|e| e.to_string())?;
+
+    let mut config = load_watch_config(&app_handle);
+    if config.extra_paths.contains(&path) {
+        return Ok(config.extra_paths);
+    }
+
+    let watcher = app_handle.try_state::<ContentWatcher>().ok_or(WATCHER_STARTING)?;
+    watcher.debouncer.lock().unwrap()
+        .watcher()
+        .watch(&path, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+    watcher.roots.lock().unwrap().push(path.clone());
+
+    config.extra_paths.push(path.clone());
+    save_watch_config(&app_handle, &config)?;
+
+    tracing::debug!("Watching: {:?}", path);
+    Ok(config.extra_paths)
+}
+
+/// Stop watching a user-registered directory
+#[tauri::command]
+pub async fn remove_watch_path(app_handle: AppHandle, path: String) -> std::result::Result<Vec<PathBuf>, String> {
+    let requested = PathBuf::from(&path);
+    let path = requested.canonicalize().unwrap_or(requested);
+
+    let mut config = load_watch_config(&app_handle);
+    let before = config.extra_paths.len();
+    config.extra_paths.retain(|p| p != &path);
+    if config.extra_paths.len() == before {
+        return Err(format!("Not a registered watch path: {}", path.display()));
+    }
+
+    let watcher = app_handle.try_state::<ContentWatcher>().ok_or(WATCHER_STARTING)?;
+    if let Err(e) = watcher.debouncer.lock().unwrap().watcher().unwatch(&path) {
+        // The directory may have been deleted already; still drop it from config
+        tracing::warn!("Failed to unwatch {:?}: {}", path, e);
+    }
+    watcher.roots.lock().unwrap().retain(|p| p != &path);
+
+    save_watch_config(&app_handle, &config)?;
+
+    tracing::info!("Stopped watching: {:?}", path);
+    Ok(config.extra_paths)
+}
+
+/// Enable (with a user id) or disable TKG re-embedding of changed markdown sections
+#[tauri::command]
+pub async fn set_watch_reembed(app_handle: AppHandle, user_id: Option<String>) -> std::result::Result<(), String> {
+    let mut config = load_watch_config(&app_handle);
+    config.reembed_user_id = user_id.filter(|u| !u.trim().is_empty());
+    save_watch_config(&app_handle, &config)
+}
+
+/// Current watcher ignore globs
+#[tauri::command]
+pub async fn get_watch_ignore_globs(app_handle: AppHandle) -> std::result::Result<Vec<String>, String> {
+    Ok(load_watch_config(&app_handle).ignore_globs)
+}
+
+/// Replace the watcher ignore globs; takes effect on the next batch of events
+#[tauri::command]
+pub async fn set_watch_ignore_globs(app_handle: AppHandle, globs: Vec<String>) -> std::result::Result<Vec<String>, String> {
+    for pattern in &globs {
+        Glob::new(pattern.trim()).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+    }
+
+    let mut config = load_watch_config(&app_handle);
+    config.ignore_globs = globs;
+    save_watch_config(&app_handle, &config)?;
+    Ok(config.ignore_globs)
+}
+
+/// List user-registered watch directories
+#[tauri::command]
+pub async fn list_watch_paths(app_handle: AppHandle) -> std::result::Result<Vec<PathBuf>, String> {
+    Ok(load_watch_config(&app_handle).extra_paths)
+}
+
+/// Watch an indexed repository and invalidate persisted index entries for files that change,
+/// so the next `index_repository` run re-reads exactly those files.
+pub fn watch_repository(app_handle: &AppHandle, repo_root: &Path) -> std::result::Result<(), String> {
+    let handle = app_handle.clone();
+    let root = repo_root.to_path_buf();
+
+    let mut debouncer = new_debouncer(
+        DEBOUNCE_DELAY,
+        None,
+        move |result: DebounceEventResult| {
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    tracing::error!("Repo watcher error: {:?}", e);
+                    return;
+                }
+            };
+
+            let ignored = build_ignore_set(&load_watch_config(&handle).ignore_globs);
+
+            // Renames invalidate both the old and the new location
+            let changed_paths: Vec<PathBuf> = collapse_events(events.iter().map(|e| &e.event))
+                .into_iter()
+                .flat_map(|change| std::iter::once(change.path).chain(change.from))
+                .filter(|path| !is_suppressed(path))
+                .filter(|path| !ignored.is_match(path))
+                .collect();
+
+            let changed: HashSet<String> = changed_paths
+                .iter()
+                .filter_map(|path| path.strip_prefix(&root).ok())
+                .filter(|rel| {
+                    !rel.components().any(|c| {
+                        let name = c.as_os_str().to_string_lossy();
+                        name == ".git" || name == "node_modules" || name == "target"
+                    })
+                })
+                .map(|rel| rel.to_string_lossy().to_string())
+                .collect();
+
+            if changed.is_empty() {
+                return;
+            }
+
+            let paths: Vec<String> = changed.into_iter().collect();
+            let db_path = match RepoIndex::index_db_path(Some(&handle)) {
+                Some(p) => p,
+                None => return,
+            };
+
+            match RepoIndex::open_index_db(&db_path)
+                .and_then(|conn| RepoIndex::invalidate_paths(&conn, &root, &paths))
+            {
+                Ok(()) => {
+                    tracing::info!("📇 Index invalidated for {} changed file(s) in {:?}", paths.len(), root);
+                    let _ = handle.emit_all("index-invalidated", serde_json::json!({
+                        "repo_root": root.to_string_lossy(),
+                        "paths": paths,
+                    }));
+                }
+                Err(e) => tracing::warn!("Failed to invalidate index entries: {}", e),
+            }
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    debouncer
+        .watcher()
+        .watch(repo_root, RecursiveMode::Recursive)
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!("Watching repository: {:?}", repo_root);
+
+    // Dropping the previous debouncer stops watching the old repository
+    let state = app_handle.state::<RepoWatcher>();
+    *state.0.lock().unwrap() = Some(debouncer);
 
     Ok(())
 }
+
+/// Stop watching the indexed repository
+pub fn unwatch_repository(app_handle: &AppHandle) {
+    let state = app_handle.state::<RepoWatcher>();
+    *state.0.lock().unwrap() = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify_debouncer_full::notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths.iter().fold(Event::new(kind), |e, p| e.add_path(PathBuf::from(p)))
+    }
+
+    #[test]
+    fn test_collapse_burst_to_net_changes() {
+        let events = vec![
+            event(EventKind::Create(CreateKind::File), &["/kb/new.md"]),
+            event(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["/kb/new.md"]),
+            event(EventKind::Create(CreateKind::File), &["/kb/tmp.md"]),
+            event(EventKind::Remove(RemoveKind::File), &["/kb/tmp.md"]),
+            event(EventKind::Remove(RemoveKind::File), &["/kb/old.md"]),
+            event(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["/kb/a.md", "/kb/b.md"]),
+        ];
+
+        let changes = collapse_events(events.iter());
+        assert_eq!(changes.len(), 3);
+
+        assert_eq!(changes[0].path, PathBuf::from("/kb/new.md"));
+        assert_eq!(changes[0].kind, ChangeKind::Create);
+        assert_eq!(changes[1].kind, ChangeKind::Delete);
+        assert_eq!(changes[2].kind, ChangeKind::Rename);
+        assert_eq!(changes[2].from, Some(PathBuf::from("/kb/a.md")));
+    }
+
+    #[test]
+    fn test_default_ignore_globs_skip_editor_noise() {
+        let ignored = build_ignore_set(&default_ignore_globs());
+
+        assert!(ignored.is_match("/vault/.obsidian/workspace.json"));
+        assert!(ignored.is_match("/kb/research/.note.md.swp"));
+        assert!(ignored.is_match("/kb/research/note.md~"));
+        assert!(ignored.is_match("/kb/dumps/download.tmp"));
+        assert!(!ignored.is_match("/kb/research/note.md"));
+    }
+
+    #[test]
+    fn test_write_pause_covers_only_its_paths() {
+        let mut state = PauseState::default();
+        let now = Instant::now();
+        let id = state.begin(vec![PathBuf::from("/kb/collections/import")]);
+        assert!(state.automatic(now));
+        assert!(state.suppresses(Path::new("/kb/collections/import/a.md"), now));
+        assert!(!state.suppresses(Path::new("/kb/research/edited.md"), now));
+        assert!(!state.suppresses(Path::new("/kb/collections/import-2/a.md"), now));
+
+        state.cover(id, PathBuf::from("/kb/research/written.md"));
+        assert!(state.suppresses(Path::new("/kb/research/written.md"), now));
+    }
+
+    #[test]
+    fn test_write_pause_lasts_one_grace_window_after_the_writes() {
+        let mut state = PauseState::default();
+        let now = Instant::now();
+        let grace = Duration::from_secs(2);
+        let id = state.begin(vec![PathBuf::from("/kb/a.md")]);
+        state.end(id, now, grace);
+        assert!(state.suppresses(Path::new("/kb/a.md"), now + Duration::from_secs(1)));
+        assert!(!state.suppresses(Path::new("/kb/a.md"), now + grace));
+        assert!(!state.automatic(now + grace));
+
+        // Ended pauses are dropped by the next one to end
+        let other = state.begin(Vec::new());
+        state.end(other, now + grace, grace);
+        assert_eq!(state.writes.len(), 1);
+    }
+
+    #[test]
+    fn test_manual_pause_holds_back_everything() {
+        let state = PauseState { manual: true, ..PauseState::default() };
+        assert!(state.suppresses(Path::new("/anywhere/note.md"), Instant::now()));
+        assert!(!state.automatic(Instant::now()));
+    }
+}
@@ -0,0 +1,366 @@
+/// Flashcards and SM-2 spaced repetition for the Knowledge Companion
+///
+/// Cards live in `knowledge_companion.db`. Each review updates the card's ease factor,
+/// interval and due date using the SuperMemo-2 algorithm.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::minimax_enhanced::AIProvider;
+use crate::net::SendChecked;
+
+const DEFAULT_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub id: i64,
+    pub source_path: Option<String>,
+    pub question: String,
+    pub answer: String,
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub due_at: String,
+    pub last_reviewed: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewCard {
+    pub question: String,
+    pub answer: String,
+}
+
+/// Scheduling state carried between reviews
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self { ease: DEFAULT_EASE, interval_days: 0, repetitions: 0 }
+    }
+}
+
+/// Apply one SM-2 review. `quality` is 0 (blackout) to 5 (perfect recall).
+pub fn sm2(prev: Schedule, quality: u8) -> Schedule {
+    let q = quality.min(5) as f64;
+
+    let (repetitions, interval_days) = if quality < 3 {
+        // Lapse: start the card over but keep its (reduced) ease
+        (0, 1)
+    } else {
+        let reps = prev.repetitions + 1;
+        let interval = match reps {
+            1 => 1,
+            2 => 6,
+            _ => ((prev.interval_days.max(1) as f64) * prev.ease).round() as i64,
+        };
+        (reps, interval)
+    };
+
+    let ease = (prev.ease + (0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02))).max(MIN_EASE);
+
+    Schedule { ease, interval_days, repetitions }
+}
+
+pub fn init_flashcards_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS flashcards (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_path TEXT,
+            question TEXT NOT NULL,
+            answer TEXT NOT NULL,
+            ease REAL NOT NULL DEFAULT 2.5,
+            interval_days INTEGER NOT NULL DEFAULT 0,
+            repetitions INTEGER NOT NULL DEFAULT 0,
+            due_at TEXT NOT NULL,
+            last_reviewed TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_flashcards_due ON flashcards(due_at)", [])?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS flashcard_reviews (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            card_id INTEGER NOT NULL,
+            quality INTEGER NOT NULL,
+            reviewed_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn row_to_card(row: &rusqlite::Row) -> rusqlite::Result<Flashcard> {
+    Ok(Flashcard {
+        id: row.get(0)?,
+        source_path: row.get(1)?,
+        question: row.get(2)?,
+        answer: row.get(3)?,
+        ease: row.get(4)?,
+        interval_days: row.get(5)?,
+        repetitions: row.get(6)?,
+        due_at: row.get(7)?,
+        last_reviewed: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+const CARD_COLUMNS: &str =
+    "id, source_path, question, answer, ease, interval_days, repetitions, due_at, last_reviewed, created_at";
+
+/// Store new cards; they are due immediately
+pub fn insert_cards(conn: &Connection, source_path: Option<&str>, cards: &[NewCard]) -> rusqlite::Result<Vec<Flashcard>> {
+    let now = Utc::now().to_rfc3339();
+    let tx = conn.unchecked_transaction()?;
+    let mut ids = Vec::new();
+
+    for card in cards {
+        if card.question.trim().is_empty() || card.answer.trim().is_empty() {
+            continue;
+        }
+        tx.execute(
+            "INSERT INTO flashcards (source_path, question, answer, ease, due_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![source_path, card.question.trim(), card.answer.trim(), DEFAULT_EASE, now],
+        )?;
+        ids.push(tx.last_insert_rowid());
+    }
+    tx.commit()?;
+
+    ids.into_iter().filter_map(|id| get_card(conn, id).transpose()).collect()
+}
+
+pub fn get_card(conn: &Connection, id: i64) -> rusqlite::Result<Option<Flashcard>> {
+    conn.query_row(
+        &format!("SELECT {} FROM flashcards WHERE id = ?1", CARD_COLUMNS),
+        params![id],
+        row_to_card,
+    )
+    .optional()
+}
+
+/// Cards due at or before `now`, oldest first
+pub fn due_cards(conn: &Connection, now: DateTime<Utc>, limit: usize) -> rusqlite::Result<Vec<Flashcard>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM flashcards WHERE due_at <= ?1 ORDER BY due_at LIMIT ?2",
+        CARD_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now.to_rfc3339(), limit as i64], row_to_card)?;
+    rows.collect()
+}
+
+/// Record a review and reschedule the card
+pub fn review_card(conn: &Connection, id: i64, quality: u8, now: DateTime<Utc>) -> rusqlite::Result<Option<Flashcard>> {
+    let card = match get_card(conn, id)? {
+        Some(card) => card,
+        None => return Ok(None),
+    };
+
+    let next = sm2(
+        Schedule { ease: card.ease, interval_days: card.interval_days, repetitions: card.repetitions },
+        quality,
+    );
+    let due_at = now + Duration::days(next.interval_days);
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE flashcards SET ease = ?1, interval_days = ?2, repetitions = ?3, due_at = ?4, last_reviewed = ?5
+         WHERE id = ?6",
+        params![next.ease, next.interval_days, next.repetitions, due_at.to_rfc3339(), now.to_rfc3339(), id],
+    )?;
+    tx.execute(
+        "INSERT INTO flashcard_reviews (card_id, quality, reviewed_at) VALUES (?1, ?2, ?3)",
+        params![id, quality.min(5) as i64, now.to_rfc3339()],
+    )?;
+    tx.commit()?;
+
+    get_card(conn, id)
+}
+
+/// Pull a JSON array of {question, answer} objects out of a model response
+pub fn parse_generated_cards(response: &str) -> Vec<NewCard> {
+    let start = response.find('[');
+    let end = response.rfind(']');
+    let json = match (start, end) {
+        (Some(s), Some(e)) if e > s => &response[s..=e],
+        _ => return Vec::new(),
+    };
+
+    serde_json::from_str::<Vec<NewCard>>(json).unwrap_or_else(|e| {
+        tracing::warn!("⚠️ Could not parse generated flashcards: {}", e);
+        Vec::new()
+    })
+}
+
+/// Resolve a note path relative to the knowledge base (absolute paths pass through)
+fn resolve_note(path: &str) -> Result<PathBuf, String> {
+    let p = Path::new(path);
+    if p.is_absolute() {
+        return Ok(p.to_path_buf());
+    }
+    if p.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err("Path must not contain '..'".to_string());
+    }
+    Ok(crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?.join(p))
+}
+
+/// The OpenAI-compatible chat completions URL for `provider` at `base_url`. Gemini's own API
+/// serves it under `/openai`; a configured or local base URL is used as it is.
+fn completions_url(provider: &AIProvider, base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    if *provider == AIProvider::Gemini && base_url == provider.base_url() {
+        return format!("{}/openai/chat/completions", base_url);
+    }
+    format!("{}/chat/completions", base_url)
+}
+
+async fn generate_cards(provider: &AIProvider, api_key: &str, content: &str, count: usize) -> Result<Vec<NewCard>, String> {
+    let prompt = format!(
+        "Create {} flashcards that test the key ideas of the study material below. \
+         Each card needs a focused question and a concise answer. \
+         Respond ONLY with a JSON array like [{{\"question\": \"...\", \"answer\": \"...\"}}].\n\n---\n{}",
+        count, content
+    );
+
+    let (base_url, model) = provider.endpoint();
+    let payload = serde_json::json!({
+        "model": model,
+        "messages": [{ "role": "user", "content": prompt }],
+        "max_tokens": 4096,
+    });
+
+    let response = crate::net::client()
+        .post(completions_url(provider, &base_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(&payload)
+        .send_checked()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("API error: {}", error_text));
+    }
+
+    let result: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    let text = result["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or("Missing content in response")?;
+
+    Ok(parse_generated_cards(text))
+}
+
+// ==================== Commands ====================
+
+/// Generate `count` cards from the note at `path` with `provider` (MiniMax when not given)
+#[tauri::command]
+pub async fn generate_flashcards(
+    app_handle: tauri::AppHandle,
+    provider: Option<AIProvider>,
+    api_key: String,
+    path: String,
+    count: Option<usize>,
+) -> Result<Vec<Flashcard>, String> {
+    let full_path = resolve_note(&path)?;
+    let content = std::fs::read_to_string(&full_path)
+        .map_err(|e| format!("Failed to read {}: {}", full_path.display(), e))?;
+    let (_, body) = crate::frontmatter::split_frontmatter(&content);
+
+    // Keep the prompt bounded for very long guides
+    let body: String = body.chars().take(24_000).collect();
+    let provider = provider.unwrap_or(AIProvider::Minimax);
+    let cards = generate_cards(&provider, &api_key, &body, count.unwrap_or(10).clamp(1, 50)).await?;
+    if cards.is_empty() {
+        return Err("The model did not return any flashcards".to_string());
+    }
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let stored = insert_cards(&conn, Some(&path), &cards).map_err(|e| e.to_string())?;
+    tracing::info!("🃏 Generated {} flashcard(s) from {}", stored.len(), path);
+    Ok(stored)
+}
+
+#[tauri::command]
+pub async fn get_due_cards(app_handle: tauri::AppHandle, limit: Option<usize>) -> Result<Vec<Flashcard>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    due_cards(&conn, Utc::now(), limit.unwrap_or(20)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn record_review(app_handle: tauri::AppHandle, card_id: i64, quality: u8) -> Result<Flashcard, String> {
+    if quality > 5 {
+        return Err("Quality must be between 0 and 5".to_string());
+    }
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    review_card(&conn, card_id, quality, Utc::now())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Flashcard {} not found", card_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_url() {
+        assert_eq!(completions_url(&AIProvider::Minimax, "https://api.minimax.io/v1"), "https://api.minimax.io/v1/chat/completions");
+        assert_eq!(completions_url(&AIProvider::Grok, "https://api.x.ai/v1/"), "https://api.x.ai/v1/chat/completions");
+        assert_eq!(
+            completions_url(&AIProvider::Gemini, AIProvider::Gemini.base_url()),
+            "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions"
+        );
+        // A local endpoint in local-only mode is already OpenAI-compatible
+        assert_eq!(completions_url(&AIProvider::Gemini, "http://localhost:11434/v1"), "http://localhost:11434/v1/chat/completions");
+    }
+
+    #[test]
+    fn test_sm2_intervals_and_lapse() {
+        let first = sm2(Schedule::default(), 4);
+        assert_eq!((first.repetitions, first.interval_days), (1, 1));
+
+        let second = sm2(first, 4);
+        assert_eq!((second.repetitions, second.interval_days), (2, 6));
+
+        let third = sm2(second, 5);
+        assert_eq!(third.interval_days, (6.0 * second.ease).round() as i64);
+        assert!(third.ease > second.ease);
+
+        let lapse = sm2(third, 1);
+        assert_eq!((lapse.repetitions, lapse.interval_days), (0, 1));
+        assert!(lapse.ease < third.ease);
+        assert!(sm2(Schedule { ease: MIN_EASE, ..Schedule::default() }, 0).ease >= MIN_EASE);
+    }
+
+    #[test]
+    fn test_review_reschedules_due_cards() {
+        let db = tempfile::NamedTempFile::new().unwrap();
+        let conn = Connection::open(db.path()).unwrap();
+        init_flashcards_tables(&conn).unwrap();
+
+        let cards = parse_generated_cards(
+            "Here you go:\n[{\"question\": \"What is ownership?\", \"answer\": \"Each value has one owner.\"}]",
+        );
+        let stored = insert_cards(&conn, Some("research/rust.md"), &cards).unwrap();
+        assert_eq!(stored.len(), 1);
+
+        let now = Utc::now();
+        assert_eq!(due_cards(&conn, now, 10).unwrap().len(), 1);
+
+        let reviewed = review_card(&conn, stored[0].id, 5, now).unwrap().unwrap();
+        assert_eq!(reviewed.interval_days, 1);
+        assert!(due_cards(&conn, now, 10).unwrap().is_empty());
+        assert_eq!(due_cards(&conn, now + Duration::days(2), 10).unwrap().len(), 1);
+    }
+}
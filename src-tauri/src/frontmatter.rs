@@ -0,0 +1,150 @@
+/// YAML frontmatter parsing for knowledge base notes
+///
+/// Recognizes a leading `---` block and extracts the fields the app organizes by
+/// (title, tags, created, status). Any other keys are kept in `extra`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Frontmatter {
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created: Option<String>,
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// Split a note into its frontmatter (if any) and the body that follows it
+pub fn split_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
+    let rest = match content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (None, content),
+    };
+
+    // Closing fence must be on its own line
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return (parse_yaml(yaml), body);
+        }
+        offset += line.len();
+    }
+
+    (None, content)
+}
+
+/// Read only the frontmatter block of a file, without loading the whole note
+pub fn read_frontmatter(path: &Path) -> Option<Frontmatter> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut lines = BufReader::new(file).lines();
+
+    if lines.next()?.ok()?.trim_end() != "---" {
+        return None;
+    }
+
+    let mut yaml = String::new();
+    for line in lines.take(200) {
+        let line = line.ok()?;
+        if line.trim_end() == "---" {
+            return parse_yaml(&yaml);
+        }
+        yaml.push_str(&line);
+        yaml.push('\n');
+    }
+
+    None
+}
+
+fn yaml_to_string(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.trim().to_string()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_yaml(yaml: &str) -> Option<Frontmatter> {
+    let mapping: serde_yaml::Mapping = match serde_yaml::from_str(yaml) {
+        Ok(m) => m,
+        Err(e) => {
+            tracing::warn!("⚠️ Invalid frontmatter: {}", e);
+            return None;
+        }
+    };
+
+    let mut fm = Frontmatter::default();
+
+    for (key, value) in mapping {
+        let key = match key.as_str() {
+            Some(k) => k.to_lowercase(),
+            None => continue,
+        };
+
+        match key.as_str() {
+            "title" => fm.title = yaml_to_string(&value),
+            "created" | "date" => fm.created = yaml_to_string(&value),
+            "status" => fm.status = yaml_to_string(&value).map(|s| s.to_lowercase()),
+            "tags" | "tag" => {
+                // Accept both `tags: [a, b]` and `tags: a, b` / `tags: "#a #b"`
+                fm.tags = match &value {
+                    serde_yaml::Value::Sequence(items) => items.iter().filter_map(yaml_to_string).collect(),
+                    other => yaml_to_string(other)
+                        .map(|s| s.split(|c: char| c == ',' || c.is_whitespace()).map(|t| t.to_string()).collect())
+                        .unwrap_or_default(),
+                };
+                fm.tags = fm.tags
+                    .iter()
+                    .map(|t| t.trim().trim_start_matches('#').to_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+            _ => {
+                if let Ok(json) = serde_json::to_value(&value) {
+                    fm.extra.insert(key, json);
+                }
+            }
+        }
+    }
+
+    Some(fm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_frontmatter_fields() {
+        let note = "---\ntitle: Rust Ownership\ntags: [rust, Memory]\ncreated: 2024-03-01\nstatus: Draft\nsource: book\n---\n# Body\n";
+        let (fm, body) = split_frontmatter(note);
+        let fm = fm.expect("frontmatter should parse");
+
+        assert_eq!(fm.title.as_deref(), Some("Rust Ownership"));
+        assert_eq!(fm.tags, vec!["rust".to_string(), "memory".to_string()]);
+        assert_eq!(fm.created.as_deref(), Some("2024-03-01"));
+        assert_eq!(fm.status.as_deref(), Some("draft"));
+        assert!(fm.extra.contains_key("source"));
+        assert_eq!(body, "# Body\n");
+    }
+
+    #[test]
+    fn test_string_tags() {
+        let (fm, _) = split_frontmatter("---\ntags: \"#ai, agents\"\n---\ntext");
+        assert_eq!(fm.unwrap().tags, vec!["ai".to_string(), "agents".to_string()]);
+    }
+
+    #[test]
+    fn test_missing_frontmatter() {
+        let (fm, body) = split_frontmatter("# Just a note\n");
+        assert!(fm.is_none());
+        assert_eq!(body, "# Just a note\n");
+    }
+}
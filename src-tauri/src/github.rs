@@ -0,0 +1,399 @@
+/// GitHub repository documentation harvester
+///
+/// Pulls a repository's README, the markdown under `docs/` and its release notes through the
+/// GitHub REST API into `developer-reference/github/<owner>/<repo>/`. Requests run one at a
+/// time; when the rate limit runs out the harvester waits for the reset if it's close, and
+/// otherwise stops with an error saying when it resets.
+///
+/// Private repositories (and the higher rate limit) need a personal access token, set with
+/// `set_github_token` and kept in memory, or the GITHUB_TOKEN environment variable.
+
+use chrono::{TimeZone, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::net::SendChecked;
+
+pub const GITHUB_FOLDER: &str = "developer-reference/github";
+const API: &str = "https://api.github.com";
+const USER_AGENT: &str = "ThinkSpace-Research/1.0 (+https://github.com/oogalieboogalie/ThinkSpace)";
+/// Longest wait for a rate limit reset before giving up
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(90);
+const DEFAULT_MAX_DOCS: usize = 50;
+const MAX_DOCS: usize = 200;
+const DEFAULT_MAX_RELEASES: usize = 10;
+const MAX_RELEASES: usize = 100;
+const DOC_EXTENSIONS: [&str; 3] = ["md", "markdown", "mdx"];
+
+lazy_static::lazy_static! {
+    static ref GITHUB_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some(USER_AGENT),
+        timeout: Some(Duration::from_secs(30)),
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GithubOptions {
+    pub include_docs: bool,
+    pub include_releases: bool,
+    pub max_docs: usize,
+    pub max_releases: usize,
+}
+
+impl Default for GithubOptions {
+    fn default() -> Self {
+        Self { include_docs: true, include_releases: true, max_docs: DEFAULT_MAX_DOCS, max_releases: DEFAULT_MAX_RELEASES }
+    }
+}
+
+impl GithubOptions {
+    pub fn new(include_docs: Option<bool>, include_releases: Option<bool>, max_docs: Option<usize>, max_releases: Option<usize>) -> Self {
+        Self {
+            include_docs: include_docs.unwrap_or(true),
+            include_releases: include_releases.unwrap_or(true),
+            max_docs: max_docs.unwrap_or(DEFAULT_MAX_DOCS).min(MAX_DOCS),
+            max_releases: max_releases.unwrap_or(DEFAULT_MAX_RELEASES).min(MAX_RELEASES),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GithubHarvest {
+    /// owner/repo
+    pub repo: String,
+    pub description: Option<String>,
+    /// Saved notes, relative to the knowledge base
+    pub saved: Vec<String>,
+    /// Docs beyond `max_docs` that weren't fetched
+    pub skipped_docs: usize,
+    pub releases: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct GithubFrontmatter<'a> {
+    title: &'a str,
+    created: &'a str,
+    tags: [&'a str; 2],
+    source: &'a str,
+}
+
+fn token() -> Option<String> {
+    let stored = GITHUB_TOKEN.lock().ok().and_then(|t| t.clone());
+    stored.or_else(|| std::env::var("GITHUB_TOKEN").ok()).filter(|t| !t.trim().is_empty())
+}
+
+/// `owner/repo` from "owner/repo", "github.com/owner/repo" or any github.com URL in the repo
+pub fn parse_repo(input: &str) -> Result<(String, String), String> {
+    let trimmed = input.trim().trim_end_matches('/');
+    let path = trimmed
+        .strip_prefix("https://")
+        .or_else(|| trimmed.strip_prefix("http://"))
+        .unwrap_or(trimmed);
+    let path = path.strip_prefix("www.").unwrap_or(path);
+    let path = path.strip_prefix("github.com/").unwrap_or(path);
+    let mut parts = path.split('/');
+    let owner = parts.next().unwrap_or_default();
+    let repo = parts.next().unwrap_or_default();
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    let valid = |s: &str| !s.is_empty() && s != "." && s != ".." && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if valid(owner) && valid(repo) {
+        Ok((owner.to_string(), repo.to_string()))
+    } else {
+        Err(format!("'{}' isn't a GitHub repository; use owner/repo or its URL", input.trim()))
+    }
+}
+
+/// How long to wait before retrying a rate-limited request, from GitHub's Retry-After or
+/// X-RateLimit-Remaining/-Reset headers; None when the response wasn't rate limited
+fn rate_limit_wait(retry_after: Option<&str>, remaining: Option<&str>, reset: Option<&str>, now: i64) -> Option<Duration> {
+    if let Some(seconds) = retry_after.and_then(|s| s.trim().parse::<u64>().ok()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if remaining.map(str::trim) != Some("0") {
+        return None;
+    }
+    let reset = reset.and_then(|s| s.trim().parse::<i64>().ok())?;
+    Some(Duration::from_secs((reset - now).max(1) as u64))
+}
+
+/// GET an API path, waiting out a short rate limit once
+async fn get(path: &str, accept: &str) -> Result<reqwest::Response, String> {
+    let url = format!("{}{}", API, path);
+    let token = token();
+    let mut waited = false;
+    loop {
+        let mut request = client().get(&url).header(reqwest::header::ACCEPT, accept).header("X-GitHub-Api-Version", "2022-11-28");
+        if let Some(token) = &token {
+            request = request.bearer_auth(token.trim());
+        }
+        let response = request.send_checked().await.map_err(|e| format!("GitHub request failed: {}", e))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::FORBIDDEN || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok());
+            let wait = rate_limit_wait(header("retry-after"), header("x-ratelimit-remaining"), header("x-ratelimit-reset"), Utc::now().timestamp());
+            if let Some(wait) = wait {
+                if !waited && wait <= MAX_RATE_LIMIT_WAIT {
+                    tracing::info!("⏳ GitHub rate limit reached, waiting {}s", wait.as_secs());
+                    tokio::time::sleep(wait).await;
+                    waited = true;
+                    continue;
+                }
+                let resets = Utc.timestamp_opt(Utc::now().timestamp() + wait.as_secs() as i64, 0).single().unwrap_or_else(Utc::now);
+                return Err(format!(
+                    "GitHub rate limit reached until {}{}",
+                    resets.with_timezone(&chrono::Local).format("%H:%M"),
+                    if token.is_none() { "; a personal access token raises the limit" } else { "" }
+                ));
+            }
+        }
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(format!(
+                "Not found on GitHub: {}{}",
+                path,
+                if token.is_none() { " (private repositories need a personal access token)" } else { "" }
+            ));
+        }
+        if !status.is_success() {
+            return Err(format!("GitHub returned HTTP {} for {}", status, path));
+        }
+        return Ok(response);
+    }
+}
+
+async fn get_json(path: &str) -> Result<serde_json::Value, String> {
+    get(path, "application/vnd.github+json").await?.json().await.map_err(|e| format!("Invalid GitHub response: {}", e))
+}
+
+async fn get_raw(path: &str) -> Result<String, String> {
+    get(path, "application/vnd.github.raw").await?.text().await.map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+fn encode_path(path: &str) -> String {
+    path.split('/').map(|segment| urlencoding::encode(segment).into_owned()).collect::<Vec<_>>().join("/")
+}
+
+/// Where a file from the repo's docs/ goes: its path under docs/, as a .md note
+fn doc_note_path(repo_dir: &Path, doc_path: &str) -> PathBuf {
+    let relative = Path::new(doc_path).with_extension("md");
+    repo_dir.join(relative.components().filter(|c| matches!(c, std::path::Component::Normal(_))).collect::<PathBuf>())
+}
+
+fn is_doc(path: &str) -> bool {
+    path.starts_with("docs/") && Path::new(path).extension().and_then(|e| e.to_str()).map_or(false, |ext| DOC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// The note's first `# ` heading, else its file name
+fn doc_title(path: &str, content: &str) -> String {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("# ").map(|t| t.trim().to_string()))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default())
+}
+
+fn render_note(title: &str, repo: &str, source: &str, body: &str) -> String {
+    let created = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let frontmatter = GithubFrontmatter { title, created: &created, tags: ["github", repo], source };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    format!("---\n{}---\n\n{}\n", yaml, body.trim())
+}
+
+fn releases_markdown(full_name: &str, releases: &[serde_json::Value]) -> String {
+    let mut out = format!("# {} releases\n", full_name);
+    for release in releases {
+        let tag = release["tag_name"].as_str().unwrap_or_default();
+        let name = release["name"].as_str().filter(|n| !n.trim().is_empty()).unwrap_or(tag);
+        let date = release["published_at"].as_str().map(|d| d.get(..10).unwrap_or(d)).unwrap_or("unpublished");
+        out.push_str(&format!("\n## {} ({}, {})\n\n", name.trim(), tag, date));
+        if let Some(url) = release["html_url"].as_str() {
+            out.push_str(&format!("[Release page]({})\n\n", url));
+        }
+        match release["body"].as_str().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(body) => out.push_str(body),
+            None => out.push_str("_No release notes._"),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn save(app_handle: Option<&tauri::AppHandle>, kb_root: &Path, path: &Path, content: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    crate::note_versions::record_before_write(app_handle, kb_root, path, "github");
+    std::fs::write(path, content).map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+/// Harvest `repo`'s README, docs and release notes into the knowledge base
+pub async fn harvest(app_handle: Option<&tauri::AppHandle>, repo: &str, options: GithubOptions) -> Result<GithubHarvest, String> {
+    let (owner, name) = parse_repo(repo)?;
+    let repo_path = format!("/repos/{}/{}", owner, name);
+    let info = get_json(&repo_path).await?;
+    let full_name = info["full_name"].as_str().map(str::to_string).unwrap_or_else(|| format!("{}/{}", owner, name));
+    let branch = info["default_branch"].as_str().unwrap_or("main").to_string();
+    let html_url = info["html_url"].as_str().map(str::to_string).unwrap_or_else(|| format!("https://github.com/{}", full_name));
+    tracing::info!("🐙 Harvesting {} ({})", full_name, branch);
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let repo_dir = kb_root.join(GITHUB_FOLDER).join(&owner).join(&name);
+    let mut report = GithubHarvest {
+        repo: full_name.clone(),
+        description: info["description"].as_str().map(str::to_string),
+        ..Default::default()
+    };
+    let mut written = Vec::new();
+
+    match get_raw(&format!("{}/readme", repo_path)).await {
+        Ok(readme) => {
+            let path = repo_dir.join("README.md");
+            let note = render_note(&format!("{} README", full_name), &name, &format!("{}#readme", html_url), &readme);
+            match save(app_handle, &kb_root, &path, &note) {
+                Ok(()) => written.push(path),
+                Err(e) => report.errors.push(e),
+            }
+        }
+        Err(e) => report.errors.push(format!("README: {}", e)),
+    }
+
+    if options.include_docs {
+        match get_json(&format!("{}/git/trees/{}?recursive=1", repo_path, urlencoding::encode(&branch))).await {
+            Ok(tree) => {
+                let mut docs: Vec<String> = tree["tree"]
+                    .as_array()
+                    .map(|entries| {
+                        entries
+                            .iter()
+                            .filter(|e| e["type"] == "blob")
+                            .filter_map(|e| e["path"].as_str())
+                            .filter(|p| is_doc(p))
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                docs.sort();
+                report.skipped_docs = docs.len().saturating_sub(options.max_docs);
+                for doc in docs.into_iter().take(options.max_docs) {
+                    let fetched = get_raw(&format!("{}/contents/{}?ref={}", repo_path, encode_path(&doc), urlencoding::encode(&branch))).await;
+                    let saved = fetched.and_then(|content| {
+                        let path = doc_note_path(&repo_dir, &doc);
+                        let source = format!("{}/blob/{}/{}", html_url, branch, doc);
+                        save(app_handle, &kb_root, &path, &render_note(&doc_title(&doc, &content), &name, &source, &content))?;
+                        Ok(path)
+                    });
+                    match saved {
+                        Ok(path) => written.push(path),
+                        Err(e) => report.errors.push(format!("{}: {}", doc, e)),
+                    }
+                }
+            }
+            Err(e) => report.errors.push(format!("docs/: {}", e)),
+        }
+    }
+
+    if options.include_releases && options.max_releases > 0 {
+        match get_json(&format!("{}/releases?per_page={}", repo_path, options.max_releases)).await {
+            Ok(releases) => {
+                let releases = releases.as_array().cloned().unwrap_or_default();
+                if !releases.is_empty() {
+                    let path = repo_dir.join("RELEASES.md");
+                    let note = render_note(&format!("{} releases", full_name), &name, &format!("{}/releases", html_url), &releases_markdown(&full_name, &releases));
+                    match save(app_handle, &kb_root, &path, &note) {
+                        Ok(()) => {
+                            report.releases = releases.len();
+                            written.push(path);
+                        }
+                        Err(e) => report.errors.push(e),
+                    }
+                }
+            }
+            Err(e) => report.errors.push(format!("Releases: {}", e)),
+        }
+    }
+
+    report.saved = written.iter().map(|p| crate::kb_index::path_key(&kb_root, p)).collect();
+    if let Some(handle) = app_handle {
+        if !written.is_empty() {
+            crate::kb_index::apply_watch_changes(handle, &written, None);
+            crate::events::emit(handle, "content-changed", serde_json::json!({
+                "source": repo_dir,
+                "paths": &report.saved,
+            }));
+        }
+    }
+    if report.saved.is_empty() {
+        return Err(format!("Nothing harvested from {}: {}", full_name, report.errors.join("; ")));
+    }
+    tracing::info!("🐙 Saved {} note(s) from {}", report.saved.len(), full_name);
+    Ok(report)
+}
+
+/// Keep a GitHub personal access token in memory for harvests; None clears it
+#[tauri::command]
+pub fn set_github_token(token: Option<String>) -> Result<(), String> {
+    let mut stored = GITHUB_TOKEN.lock().map_err(|e| e.to_string())?;
+    *stored = token.filter(|t| !t.trim().is_empty());
+    Ok(())
+}
+
+/// Harvest a repository's README, docs/ and release notes into developer-reference/github/
+#[tauri::command]
+pub async fn harvest_github_repo(
+    app_handle: tauri::AppHandle,
+    repo: String,
+    include_docs: Option<bool>,
+    include_releases: Option<bool>,
+    max_docs: Option<usize>,
+    max_releases: Option<usize>,
+) -> Result<GithubHarvest, String> {
+    harvest(Some(&app_handle), &repo, GithubOptions::new(include_docs, include_releases, max_docs, max_releases)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repo_forms() {
+        let expected = ("tauri-apps".to_string(), "tauri".to_string());
+        assert_eq!(parse_repo("tauri-apps/tauri").unwrap(), expected);
+        assert_eq!(parse_repo("https://github.com/tauri-apps/tauri/tree/dev/docs").unwrap(), expected);
+        assert_eq!(parse_repo("github.com/tauri-apps/tauri.git").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_repo_rejects_other_input() {
+        assert!(parse_repo("tauri").is_err());
+        assert!(parse_repo("../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_is_doc() {
+        assert!(is_doc("docs/guide/setup.MD") && !is_doc("src/docs.md") && !is_doc("docs/logo.png"));
+    }
+
+    #[test]
+    fn test_doc_note_path() {
+        let dir = Path::new("/kb/developer-reference/github/tauri-apps/tauri");
+        assert_eq!(doc_note_path(dir, "docs/guide/setup.mdx"), dir.join("docs/guide/setup.md"));
+    }
+
+    #[test]
+    fn test_doc_title() {
+        assert_eq!(doc_title("docs/guide/setup.mdx", "Intro\n# Setting up\n"), "Setting up");
+        assert_eq!(doc_title("docs/guide/setup.mdx", "No heading"), "setup");
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        assert_eq!(rate_limit_wait(Some("30"), None, None, 0), Some(Duration::from_secs(30)));
+        assert_eq!(rate_limit_wait(None, Some("0"), Some("1060"), 1000), Some(Duration::from_secs(60)));
+        assert_eq!(rate_limit_wait(None, Some("12"), Some("1060"), 1000), None);
+    }
+}
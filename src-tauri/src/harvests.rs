@@ -0,0 +1,611 @@
+/// Harvest metadata, refresh and change detection
+///
+/// Harvested notes (wiki pages and web clips) say where they came from in frontmatter:
+/// `harvest` (wiki or clip), `source`, `harvested` (when it was last fetched) and, for wiki
+/// pages, the `revision` and how to fetch the page again. `refresh_harvests` re-fetches the
+/// notes not harvested for a while. A wiki page whose revision hasn't moved, or a page whose
+/// content is the same, is only re-stamped; a changed one is rewritten (note_versions keeps
+/// the old copy) and the change is logged in `harvest_changes` with a summary of what changed.
+///
+/// Before a harvest or clip is saved, `note_path` looks for a note already harvested from the
+/// same canonical URL and updates that one instead of adding a numbered copy.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use crate::scheduler::stamp;
+
+const DEFAULT_MAX_AGE_DAYS: i64 = 7;
+/// Notes refreshed per call, so a first refresh of a large harvest doesn't run for hours
+const DEFAULT_REFRESH_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarvestMeta {
+    /// "wiki" or "clip"
+    #[serde(rename = "harvest")]
+    pub kind: String,
+    pub source: String,
+    /// When the content was last fetched (a `stamp`)
+    pub harvested: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiki_api: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wiki_title: Option<String>,
+    /// Only the page's lead section was harvested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary_only: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct HarvestFrontmatter<'a> {
+    title: &'a str,
+    created: &'a str,
+    tags: &'a [&'a str],
+    #[serde(flatten)]
+    meta: &'a HarvestMeta,
+}
+
+pub fn render_note(title: &str, created: &str, tags: &[&str], meta: &HarvestMeta, body: &str) -> String {
+    let frontmatter = HarvestFrontmatter { title, created, tags, meta };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    format!("---\n{}---\n\n{}\n", yaml, body.trim())
+}
+
+/// A harvested wiki page as a note
+pub fn wiki_note(site: &crate::mediawiki::WikiSite, page: &crate::mediawiki::WikiPage, summary_only: bool, created: &str) -> String {
+    let meta = HarvestMeta {
+        kind: "wiki".to_string(),
+        source: page.url.clone(),
+        harvested: stamp(Utc::now()),
+        revision: page.revision,
+        wiki_api: Some(site.api_base.clone()),
+        wiki_title: Some(page.title.clone()),
+        summary_only: if summary_only { Some(true) } else { None },
+    };
+    let body = format!("# {}\n\nSource: {}\n\n{}", page.title, page.url, page.text.trim());
+    render_note(&page.title, created, &["harvest", site.name.as_str()], &meta, &body)
+}
+
+/// The harvest metadata in a note's frontmatter, if it's a harvested note
+pub fn read_meta(content: &str) -> Option<HarvestMeta> {
+    let (fm, _) = crate::frontmatter::split_frontmatter(content);
+    let extra: serde_json::Map<String, serde_json::Value> = fm?.extra.into_iter().collect();
+    serde_json::from_value(serde_json::Value::Object(extra)).ok()
+}
+
+// ==================== De-duplication ====================
+
+/// Query parameters that only track where a visitor came from
+const TRACKING_PARAMS: [&str; 6] = ["fbclid", "gclid", "mc_cid", "mc_eid", "ref", "ref_src"];
+
+/// `url` in a form that's equal for links to the same page: https, no `www.`/`m.` host
+/// prefix, fragment, tracking parameters or trailing slash
+pub fn canonical_url(url: &str) -> String {
+    let mut parsed = match url::Url::parse(url.trim()) {
+        Ok(parsed) if parsed.host_str().is_some() => parsed,
+        _ => return url.trim().trim_end_matches('/').to_string(),
+    };
+    parsed.set_fragment(None);
+    let query: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    let host = parsed.host_str().unwrap_or_default().to_string();
+    let host = host.strip_prefix("www.").or_else(|| host.strip_prefix("m.")).unwrap_or(&host);
+    // Mobile Wikipedia: en.m.wikipedia.org
+    let host = host.replace(".m.wikipedia.org", ".wikipedia.org");
+    let mut canonical = format!("https://{}{}", host, parsed.path().trim_end_matches('/'));
+    if !query.is_empty() {
+        let pairs: Vec<String> = query.iter().map(|(k, v)| format!("{}={}", k, urlencoding::encode(v))).collect();
+        canonical.push('?');
+        canonical.push_str(&pairs.join("&"));
+    }
+    canonical
+}
+
+/// Where a note came from: its `source` frontmatter, or the `Source:` line wiki harvests
+/// wrote before they had frontmatter
+fn note_source(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let (fm, body) = crate::frontmatter::split_frontmatter(&content);
+    if let Some(source) = fm.as_ref().and_then(|fm| fm.extra.get("source")).and_then(|s| s.as_str()) {
+        return Some(source.to_string());
+    }
+    body.lines().take(5).find_map(|line| line.strip_prefix("Source: ")).map(|s| s.trim().to_string())
+}
+
+/// The note under `search_root` (subfolders included) harvested from the same page as `source`
+pub fn find_by_source(search_root: &Path, source: &str) -> Option<PathBuf> {
+    let wanted = canonical_url(source);
+    WalkDir::new(search_root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().map_or(false, |ext| ext == "md"))
+        .map(|e| e.into_path())
+        .find(|path| note_source(path).map_or(false, |s| canonical_url(&s) == wanted))
+}
+
+/// Where a note harvested from `source` goes: the note already harvested from it anywhere
+/// under `search_root`, else `<dir>/<name>.md`, or `<name>-2.md`, ... when that name is taken
+/// by a note from somewhere else
+pub fn note_path(search_root: &Path, dir: &Path, name: &str, source: &str) -> PathBuf {
+    if let Some(existing) = find_by_source(search_root, source) {
+        return existing;
+    }
+    let mut n = 1;
+    loop {
+        let file = if n == 1 { format!("{}.md", name) } else { format!("{}-{}.md", name, n) };
+        let path = dir.join(file);
+        if !path.exists() {
+            return path;
+        }
+        n += 1;
+    }
+}
+
+// ==================== Change summaries ====================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeSummary {
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub added_sections: Vec<String>,
+    pub removed_sections: Vec<String>,
+    pub changed_sections: Vec<String>,
+}
+
+impl ChangeSummary {
+    pub fn is_empty(&self) -> bool {
+        self.added_lines == 0 && self.removed_lines == 0
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (label, sections) in [("changed", &self.changed_sections), ("added", &self.added_sections), ("removed", &self.removed_sections)] {
+            if !sections.is_empty() {
+                parts.push(format!("{} {}", label, sections.join(", ")));
+            }
+        }
+        parts.push(format!("+{}/-{} lines", self.added_lines, self.removed_lines));
+        parts.join("; ")
+    }
+}
+
+/// Lines and sections that differ between two versions of a note body; lines are compared
+/// ignoring order and surrounding whitespace
+pub fn summarize_changes(old: &str, new: &str) -> ChangeSummary {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for line in old.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *counts.entry(line).or_default() += 1;
+    }
+    for line in new.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        *counts.entry(line).or_default() -= 1;
+    }
+    let removed_lines = counts.values().filter(|c| **c > 0).sum::<i64>() as usize;
+    let added_lines = counts.values().filter(|c| **c < 0).map(|c| -c).sum::<i64>() as usize;
+
+    let old_sections: HashMap<String, String> = crate::kb_index::split_sections(old).into_iter().map(|s| (s.heading, s.hash)).collect();
+    let new_sections = crate::kb_index::split_sections(new);
+    let new_headings: HashSet<&str> = new_sections.iter().map(|s| s.heading.as_str()).collect();
+    let mut summary = ChangeSummary { added_lines, removed_lines, ..Default::default() };
+    for section in &new_sections {
+        let name = if section.heading.is_empty() { "(intro)".to_string() } else { section.heading.clone() };
+        match old_sections.get(&section.heading) {
+            None => summary.added_sections.push(name),
+            Some(hash) if *hash != section.hash => summary.changed_sections.push(name),
+            Some(_) => {}
+        }
+    }
+    summary.removed_sections = crate::kb_index::split_sections(old)
+        .into_iter()
+        .filter(|s| !new_headings.contains(s.heading.as_str()))
+        .map(|s| if s.heading.is_empty() { "(intro)".to_string() } else { s.heading })
+        .collect();
+    summary
+}
+
+// ==================== Change log ====================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarvestChange {
+    pub id: i64,
+    /// Note path relative to the knowledge base
+    pub path: String,
+    pub source: String,
+    pub checked_at: String,
+    pub old_revision: Option<u64>,
+    pub new_revision: Option<u64>,
+    pub summary: ChangeSummary,
+    pub description: String,
+}
+
+pub fn init_harvest_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS harvest_changes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            source TEXT NOT NULL,
+            checked_at TEXT NOT NULL,
+            old_revision INTEGER,
+            new_revision INTEGER,
+            summary TEXT NOT NULL,
+            description TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_harvest_changes_path ON harvest_changes(path, checked_at)", [])?;
+    Ok(())
+}
+
+pub fn record_change(conn: &Connection, change: &HarvestChange) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO harvest_changes (path, source, checked_at, old_revision, new_revision, summary, description)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            change.path,
+            change.source,
+            change.checked_at,
+            change.old_revision.map(|r| r as i64),
+            change.new_revision.map(|r| r as i64),
+            serde_json::to_string(&change.summary).unwrap_or_default(),
+            change.description,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Logged changes newest first, optionally of one note
+pub fn list_changes(conn: &Connection, path: Option<&str>, limit: usize) -> rusqlite::Result<Vec<HarvestChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, path, source, checked_at, old_revision, new_revision, summary, description
+         FROM harvest_changes WHERE ?1 IS NULL OR path = ?1 ORDER BY checked_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![path, limit as i64], |row| {
+        let summary: String = row.get(6)?;
+        Ok(HarvestChange {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            source: row.get(2)?,
+            checked_at: row.get(3)?,
+            old_revision: row.get::<_, Option<i64>>(4)?.map(|r| r as u64),
+            new_revision: row.get::<_, Option<i64>>(5)?.map(|r| r as u64),
+            summary: serde_json::from_str(&summary).unwrap_or_default(),
+            description: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// ==================== Refresh ====================
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RefreshReport {
+    /// Harvested notes that were due and re-fetched
+    pub checked: usize,
+    pub unchanged: usize,
+    pub changed: Vec<HarvestChange>,
+    /// Harvested notes fetched recently enough to skip
+    pub fresh: usize,
+    pub errors: Vec<String>,
+}
+
+fn is_stale(meta: &HarvestMeta, max_age_days: i64, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&meta.harvested)
+        .map(|harvested| now.signed_duration_since(harvested.with_timezone(&Utc)) >= chrono::Duration::days(max_age_days))
+        .unwrap_or(true)
+}
+
+/// The note as it would be harvested now, and the page's revision
+async fn refetch(meta: &HarvestMeta, title: &str, created: &str, tags: &[String]) -> Result<(String, Option<u64>), String> {
+    match meta.kind.as_str() {
+        "wiki" => {
+            let api = meta.wiki_api.as_deref().ok_or("No wiki_api in frontmatter")?;
+            let site = crate::mediawiki::WikiSite::from_api_base(api)?;
+            let summary_only = meta.summary_only.unwrap_or(false);
+            let page = crate::mediawiki::fetch_page(&site, meta.wiki_title.as_deref().unwrap_or(title), summary_only).await?;
+            Ok((wiki_note(&site, &page, summary_only, created), page.revision))
+        }
+        "clip" => {
+            let page = crate::web_extract::fetch_page(&meta.source).await.map_err(|e| format!("{:#}", e))?;
+            Ok((crate::clipper::render_note(&meta.source, title, created, &page.markdown, tags), None))
+        }
+        other => Err(format!("Unknown harvest kind '{}'", other)),
+    }
+}
+
+/// Re-stamp a note whose content hasn't changed
+fn restamp(content: &str, harvested: &str) -> String {
+    let (fm, body) = crate::frontmatter::split_frontmatter(content);
+    let yaml_end = content.len() - body.len();
+    let header = &content[..yaml_end];
+    match fm.and_then(|fm| fm.extra.get("harvested").and_then(|v| v.as_str().map(str::to_string))) {
+        Some(old) => format!("{}{}", header.replacen(&old, harvested, 1), body),
+        None => content.to_string(),
+    }
+}
+
+/// Re-fetch one harvested note; Some(change) when its content changed
+async fn refresh_note(app_handle: &tauri::AppHandle, kb_root: &Path, path: &Path, meta: &HarvestMeta) -> Result<Option<HarvestChange>, String> {
+    let old = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (fm, old_body) = crate::frontmatter::split_frontmatter(&old);
+    let fm = fm.unwrap_or_default();
+    let title = fm.title.clone().unwrap_or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default());
+    let created = fm.created.clone().unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+
+    let now = stamp(Utc::now());
+    let same_revision = |revision: Option<u64>| revision.is_some() && revision == meta.revision;
+    let (new, revision) = refetch(meta, &title, &created, &fm.tags).await?;
+    let (_, new_body) = crate::frontmatter::split_frontmatter(&new);
+    let summary = summarize_changes(old_body, new_body);
+    if same_revision(revision) || summary.is_empty() {
+        // Same text under a new revision: keep the fetched note so its revision is current
+        let content = if same_revision(revision) || revision.is_none() { restamp(&old, &now) } else { new };
+        std::fs::write(path, content).map_err(|e| e.to_string())?;
+        return Ok(None);
+    }
+
+    crate::note_versions::record_before_write(Some(app_handle), kb_root, path, "harvest");
+    std::fs::write(path, &new).map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+    let mut change = HarvestChange {
+        id: 0,
+        path: crate::kb_index::path_key(kb_root, path),
+        source: meta.source.clone(),
+        checked_at: now,
+        old_revision: meta.revision,
+        new_revision: revision,
+        description: summary.describe(),
+        summary,
+    };
+    let conn = crate::minimax_api::open_kc_database(Some(app_handle))?;
+    change.id = record_change(&conn, &change).map_err(|e| e.to_string())?;
+    Ok(Some(change))
+}
+
+/// A harvested note, as listed in the email digest
+#[derive(Debug, Clone, Serialize)]
+pub struct HarvestedNote {
+    pub path: String,
+    pub title: String,
+    /// "wiki" or "clip"
+    pub kind: String,
+    pub source: String,
+    pub created: String,
+}
+
+/// Notes first harvested on or after `since` (YYYY-MM-DD), newest first. Refreshes keep a
+/// note's `created`, so a page re-fetched this week isn't counted as new.
+pub fn harvested_since(kb_root: &Path, since: &str) -> Vec<HarvestedNote> {
+    let mut notes: Vec<HarvestedNote> = harvested_notes(kb_root, None)
+        .into_iter()
+        .filter_map(|(path, meta)| {
+            let frontmatter = crate::frontmatter::read_frontmatter(&path)?;
+            let created = frontmatter.created.filter(|c| c.as_str() >= since)?;
+            let title = frontmatter.title.unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+            Some(HarvestedNote { path: crate::kb_index::path_key(kb_root, &path), title, kind: meta.kind, source: meta.source, created })
+        })
+        .collect();
+    notes.sort_by(|a, b| b.created.cmp(&a.created).then_with(|| a.title.cmp(&b.title)));
+    notes
+}
+
+/// Harvested notes under `research/` (or the given ones), with their metadata
+fn harvested_notes(kb_root: &Path, paths: Option<&[String]>) -> Vec<(PathBuf, HarvestMeta)> {
+    let candidates: Vec<PathBuf> = match paths {
+        Some(paths) => paths.iter().filter(|p| !p.contains("..")).map(|p| kb_root.join(p)).collect(),
+        None => WalkDir::new(kb_root.join(crate::research_notes::RESEARCH_FOLDER))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.path().extension().map_or(false, |ext| ext == "md"))
+            .map(|e| e.into_path())
+            .collect(),
+    };
+    candidates
+        .into_iter()
+        .filter_map(|path| {
+            let meta = read_meta(&std::fs::read_to_string(&path).ok()?)?;
+            Some((path, meta))
+        })
+        .collect()
+}
+
+/// Re-fetch harvested notes last harvested more than `max_age_days` ago (all of `paths`
+/// when given), rewrite the ones that changed and log what changed
+#[tauri::command]
+pub async fn refresh_harvests(
+    app_handle: tauri::AppHandle,
+    max_age_days: Option<i64>,
+    paths: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<RefreshReport, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let explicit = paths.is_some();
+    let max_age_days = max_age_days.unwrap_or(DEFAULT_MAX_AGE_DAYS);
+    let now = Utc::now();
+
+    let mut report = RefreshReport::default();
+    let mut due = Vec::new();
+    for (path, meta) in harvested_notes(&kb_root, paths.as_deref()) {
+        if explicit || is_stale(&meta, max_age_days, now) {
+            due.push((path, meta));
+        } else {
+            report.fresh += 1;
+        }
+    }
+    // Stalest first
+    due.sort_by(|a, b| a.1.harvested.cmp(&b.1.harvested));
+    due.truncate(limit.unwrap_or(DEFAULT_REFRESH_LIMIT));
+
+    tracing::info!("🔄 Refreshing {} harvested note(s)", due.len());
+    let mut rewritten = Vec::new();
+    for (path, meta) in due {
+        report.checked += 1;
+        match refresh_note(&app_handle, &kb_root, &path, &meta).await {
+            Ok(Some(change)) => {
+                tracing::info!("📝 {} changed: {}", change.path, change.description);
+                rewritten.push(path);
+                report.changed.push(change);
+            }
+            Ok(None) => report.unchanged += 1,
+            Err(e) => report.errors.push(format!("{}: {}", crate::kb_index::path_key(&kb_root, &path), e)),
+        }
+        if meta.kind == "wiki" {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+    }
+
+    if !rewritten.is_empty() {
+        crate::kb_index::apply_watch_changes(&app_handle, &rewritten, None);
+        crate::events::emit(&app_handle, "content-changed", serde_json::json!({
+            "source": kb_root.join(crate::research_notes::RESEARCH_FOLDER),
+            "paths": report.changed.iter().map(|c| c.path.clone()).collect::<Vec<_>>(),
+        }));
+    }
+    Ok(report)
+}
+
+/// Logged harvest changes, newest first, optionally of one note
+#[tauri::command]
+pub fn list_harvest_changes(app_handle: tauri::AppHandle, path: Option<String>, limit: Option<usize>) -> Result<Vec<HarvestChange>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_changes(&conn, path.as_deref(), limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "# Zulrah\n\nIntro.\n\n## Drops\n\nScales\nFangs\n\n## Strategy\n\nRange it.";
+    const NEW: &str = "# Zulrah\n\nIntro.\n\n## Drops\n\nScales\nOnyx\n\n## Trivia\n\nSnake.";
+
+    fn wiki_page(title: &str, revision: u64, text: &str) -> crate::mediawiki::WikiPage {
+        crate::mediawiki::WikiPage {
+            title: title.to_string(),
+            url: format!("https://oldschool.runescape.wiki/w/{}", title),
+            revision: Some(revision),
+            text: text.to_string(),
+        }
+    }
+
+    fn osrs_note(page: &crate::mediawiki::WikiPage, created: &str) -> String {
+        let site = crate::mediawiki::WikiSite::resolve("osrs", None, None).unwrap();
+        wiki_note(&site, page, false, created)
+    }
+
+    fn zulrah_note() -> String {
+        osrs_note(&wiki_page("Zulrah", 1234, "Zulrah is a boss."), "2026-10-01")
+    }
+
+    /// A knowledge base with an old frontmatter-less harvest and a note of the user's own
+    fn osrs_kb() -> (tempfile::TempDir, PathBuf) {
+        let kb = tempfile::tempdir().unwrap();
+        let root = kb.path().join("research/osrs");
+        std::fs::create_dir_all(root.join("Bosses")).unwrap();
+        // An old harvest without frontmatter, saved by a category harvest
+        std::fs::write(root.join("Bosses/Zulrah.md"), "# Zulrah\n\nSource: https://oldschool.runescape.wiki/w/Zulrah\n\nA boss.").unwrap();
+        std::fs::write(root.join("Vorkath.md"), "# Vorkath\n\nMy own notes.").unwrap();
+        (kb, root)
+    }
+
+    #[test]
+    fn test_wiki_note_metadata() {
+        let meta = read_meta(&zulrah_note()).expect("harvest metadata should parse");
+        assert_eq!((meta.kind.as_str(), meta.revision), ("wiki", Some(1234)));
+        assert_eq!(meta.wiki_api.as_deref(), Some("https://oldschool.runescape.wiki/api.php"));
+    }
+
+    #[test]
+    fn test_plain_note_has_no_metadata() {
+        assert!(read_meta("---\ntitle: Plain note\n---\n\nHi").is_none());
+    }
+
+    #[test]
+    fn test_restamp_keeps_the_body() {
+        let note = zulrah_note();
+        let restamped = restamp(&note, "2030-01-01T00:00:00Z");
+        assert_eq!(read_meta(&restamped).unwrap().harvested, "2030-01-01T00:00:00Z");
+        assert_eq!(crate::frontmatter::split_frontmatter(&restamped).1, crate::frontmatter::split_frontmatter(&note).1);
+    }
+
+    #[test]
+    fn test_is_stale_after_the_interval() {
+        let meta = read_meta(&zulrah_note()).unwrap();
+        let now = Utc::now();
+        assert!(!is_stale(&meta, 7, now));
+        assert!(is_stale(&meta, 7, now + chrono::Duration::days(8)));
+    }
+
+    #[test]
+    fn test_summarize_changes_by_section() {
+        let summary = summarize_changes(OLD, NEW);
+        assert_eq!((summary.added_lines, summary.removed_lines), (3, 3));
+        assert_eq!(summary.changed_sections, vec!["Drops".to_string()]);
+        assert_eq!(summary.added_sections, vec!["Trivia".to_string()]);
+        assert_eq!(summary.removed_sections, vec!["Strategy".to_string()]);
+        assert_eq!(summary.describe(), "changed Drops; added Trivia; removed Strategy; +3/-3 lines");
+    }
+
+    #[test]
+    fn test_whitespace_is_not_a_change() {
+        assert!(summarize_changes(OLD, &format!("{}\n", OLD)).is_empty());
+    }
+
+    #[test]
+    fn test_change_log_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_harvest_tables(&conn).unwrap();
+        let summary = summarize_changes(OLD, NEW);
+        let change = HarvestChange {
+            id: 0,
+            path: "research/osrs/Zulrah.md".to_string(),
+            source: "https://oldschool.runescape.wiki/w/Zulrah".to_string(),
+            checked_at: "2026-10-16T07:00:00Z".to_string(),
+            old_revision: Some(1234),
+            new_revision: Some(1300),
+            description: summary.describe(),
+            summary,
+        };
+        record_change(&conn, &change).unwrap();
+        let logged = list_changes(&conn, Some("research/osrs/Zulrah.md"), 10).unwrap();
+        assert_eq!((logged[0].new_revision, logged[0].summary.added_sections.len()), (Some(1300), 1));
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(
+            canonical_url("http://www.example.com/post/?utm_source=x&id=7#comments"),
+            "https://example.com/post?id=7"
+        );
+        assert_eq!(canonical_url("https://en.m.wikipedia.org/wiki/Rust"), canonical_url("https://en.wikipedia.org/wiki/Rust/"));
+    }
+
+    #[test]
+    fn test_same_source_reuses_its_note() {
+        let (_kb, root) = osrs_kb();
+        // The same page harvested on its own updates that note rather than adding Zulrah.md
+        assert_eq!(note_path(&root, &root, "Zulrah", "https://oldschool.runescape.wiki/w/Zulrah#Drops"), root.join("Bosses/Zulrah.md"));
+    }
+
+    #[test]
+    fn test_other_sources_get_a_free_path() {
+        let (_kb, root) = osrs_kb();
+        assert_eq!(note_path(&root, &root, "Vorkath", "https://oldschool.runescape.wiki/w/Vorkath"), root.join("Vorkath-2.md"));
+        assert_eq!(note_path(&root, &root, "Kraken", "https://oldschool.runescape.wiki/w/Kraken"), root.join("Kraken.md"));
+    }
+
+    #[test]
+    fn test_harvested_since_counts_harvest_notes_by_creation() {
+        let (kb, root) = osrs_kb();
+        // Only notes with harvest frontmatter count as harvested, by when they were created
+        std::fs::write(root.join("Kraken.md"), osrs_note(&wiki_page("Kraken", 1, "A boss."), "2026-10-10")).unwrap();
+        let new = harvested_since(kb.path(), "2026-10-09");
+        assert_eq!(new.len(), 1);
+        assert_eq!((new[0].path.as_str(), new[0].title.as_str()), ("research/osrs/Kraken.md", "Kraken"));
+        assert!(harvested_since(kb.path(), "2026-10-11").is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+/// iCalendar (RFC 5545) output and CalDAV upload
+///
+/// `calendar` renders events as one VCALENDAR, ready to save as a .ics file any calendar app
+/// imports. `put_caldav` stores one event in a CalDAV collection as `<uid>.ics`, which
+/// creates it or replaces the copy an earlier push left, so pushing again is harmless.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::net::SendChecked;
+
+const PRODID: &str = "-//ThinkSpace//ThinkSpace//EN";
+/// Longest content line before folding, in bytes
+const LINE_LIMIT: usize = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IcsTime {
+    At(DateTime<Utc>),
+    /// A whole day
+    Date(NaiveDate),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IcsEvent {
+    /// Stable across exports, so re-importing updates the event instead of duplicating it
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: IcsTime,
+    /// Minutes before the start to alert; only for events at a time
+    pub alarm_minutes: Option<i64>,
+}
+
+/// Escape text for a TEXT property value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace("\r\n", "\\n").replace('\n', "\\n")
+}
+
+/// Fold a content line at LINE_LIMIT bytes without splitting a character, ending it with CRLF
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / LINE_LIMIT * 3 + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn utc_stamp(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn event_lines(event: &IcsEvent, now: &DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", utc_stamp(now)),
+    ];
+    match &event.start {
+        IcsTime::At(time) => lines.push(format!("DTSTART:{}", utc_stamp(time))),
+        IcsTime::Date(date) => {
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+            if let Some(next) = date.succ_opt() {
+                lines.push(format!("DTEND;VALUE=DATE:{}", next.format("%Y%m%d")));
+            }
+        }
+    }
+    lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+    if let Some(description) = event.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    if let (Some(minutes), IcsTime::At(_)) = (event.alarm_minutes, &event.start) {
+        lines.extend([
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("DESCRIPTION:{}", escape(&event.summary)),
+            format!("TRIGGER:-PT{}M", minutes.max(0)),
+            "END:VALARM".to_string(),
+        ]);
+    }
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// `events` as the text of a .ics file
+pub fn calendar(events: &[IcsEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec!["BEGIN:VCALENDAR".to_string(), "VERSION:2.0".to_string(), format!("PRODID:{}", PRODID), "CALSCALE:GREGORIAN".to_string()];
+    for event in events {
+        lines.extend(event_lines(event, &now));
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Create or replace `event` in the CalDAV collection at `collection_url`
+pub async fn put_caldav(collection_url: &str, username: Option<&str>, password: Option<&str>, event: &IcsEvent) -> Result<(), String> {
+    let base = url::Url::parse(&format!("{}/", collection_url.trim_end_matches('/'))).map_err(|e| format!("Invalid CalDAV URL: {}", e))?;
+    let url = base.join(&format!("{}.ics", urlencoding::encode(&event.uid))).map_err(|e| e.to_string())?;
+    let mut request = crate::net::client()
+        .put(url)
+        .header(reqwest::header::CONTENT_TYPE, "text/calendar; charset=utf-8")
+        .body(calendar(std::slice::from_ref(event), Utc::now()));
+    if let Some(username) = username {
+        request = request.basic_auth(username, password);
+    }
+    let response = request.send_checked().await.map_err(|e| format!("CalDAV upload failed: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("CalDAV upload of '{}' failed ({}): {}", event.summary, status, body.chars().take(200).collect::<String>()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A timed reminder with an alarm and a long whole-day one
+    fn reminder_calendar() -> String {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 8, 0, 0).unwrap();
+        let events = [
+            IcsEvent {
+                uid: "r1@thinkspace".to_string(),
+                summary: "Review the Rust guide; chapters 3, 4".to_string(),
+                description: Some("remind me to review\nthe Rust guide".to_string()),
+                start: IcsTime::At(Utc.with_ymd_and_hms(2026, 10, 16, 19, 30, 0).unwrap()),
+                alarm_minutes: Some(10),
+            },
+            IcsEvent {
+                uid: "r2@thinkspace".to_string(),
+                summary: "Überarbeitung ".repeat(8),
+                description: None,
+                start: IcsTime::Date(NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()),
+                alarm_minutes: Some(10),
+            },
+        ];
+        calendar(&events, now)
+    }
+
+    #[test]
+    fn test_calendar_envelope() {
+        let ics = reminder_calendar();
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20261016T080000Z\r\n"));
+    }
+
+    #[test]
+    fn test_timed_event_with_an_alarm() {
+        let ics = reminder_calendar();
+        assert!(ics.contains("DTSTART:20261016T193000Z\r\n"));
+        assert!(ics.contains("TRIGGER:-PT10M\r\n"));
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        let ics = reminder_calendar();
+        assert!(ics.contains("SUMMARY:Review the Rust guide\\; chapters 3\\, 4\r\n"));
+        assert!(ics.contains("DESCRIPTION:remind me to review\\nthe Rust guide\r\n"));
+    }
+
+    #[test]
+    fn test_whole_day_event_has_no_alarm() {
+        let ics = reminder_calendar();
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261231\r\nDTEND;VALUE=DATE:20270101\r\n"));
+        // One alarm: whole-day events don't get one
+        assert_eq!(ics.matches("BEGIN:VALARM").count(), 1);
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let ics = reminder_calendar();
+        // Long lines are folded within 75 bytes and unfold back to the original
+        assert!(ics.split("\r\n").all(|line| line.len() <= LINE_LIMIT));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains(&format!("SUMMARY:{}\r\n", "Überarbeitung ".repeat(8))));
+    }
+}
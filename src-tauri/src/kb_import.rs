@@ -0,0 +1,293 @@
+/// Bulk import of external markdown folders (Obsidian vaults, Notion exports, ...)
+///
+/// Files are copied under a knowledge base folder with Notion's page-id suffixes
+/// stripped from names, relative links rewritten to match, and a frontmatter block
+/// added to notes that don't have one. Existing files are never overwritten.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use walkdir::WalkDir;
+
+lazy_static::lazy_static! {
+    /// Notion appends a 32-char hex page id to exported file and folder names
+    static ref NOTION_ID: Regex = Regex::new(r"\s+[0-9a-f]{32}$").unwrap();
+    static ref MD_LINK: Regex = Regex::new(r"(\[[^\]]*\]\()([^)\s]+)(\))").unwrap();
+}
+
+/// Folders an import is never allowed to descend into
+pub const SKIP_DIRS: [&str; 4] = [".obsidian", ".trash", ".git", "node_modules"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub target_folder: String,
+    pub imported_notes: Vec<String>,
+    pub copied_attachments: usize,
+    /// Files left alone because a file already exists at the destination
+    pub skipped: Vec<String>,
+    pub tkg_ingest_started: bool,
+}
+
+/// Drop a trailing Notion page id from one path component ("Ideas 3f2a...c1.md" -> "Ideas.md")
+pub fn normalize_component(name: &str) -> String {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (name, None),
+    };
+    let stem = NOTION_ID.replace(stem, "");
+    match ext {
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem.to_string(),
+    }
+}
+
+fn normalize_relative(path: &Path) -> PathBuf {
+    path.components()
+        .map(|c| match c {
+            Component::Normal(name) => PathBuf::from(normalize_component(&name.to_string_lossy())),
+            other => PathBuf::from(other.as_os_str()),
+        })
+        .collect()
+}
+
+/// Rewrite relative markdown links so they follow the renamed files
+pub fn rewrite_links(content: &str) -> String {
+    MD_LINK
+        .replace_all(content, |caps: &regex::Captures| {
+            let href = &caps[2];
+            if href.contains("://") || href.starts_with('#') || href.starts_with("mailto:") {
+                return caps[0].to_string();
+            }
+
+            let (target, anchor) = match href.split_once('#') {
+                Some((t, a)) => (t, Some(a)),
+                None => (href, None),
+            };
+            let decoded = urlencoding::decode(target).map(|s| s.into_owned()).unwrap_or_else(|_| target.to_string());
+            let renamed = normalize_relative(Path::new(&decoded))
+                .to_string_lossy()
+                .replace('\\', "/")
+                .replace(' ', "%20");
+
+            let href = match anchor {
+                Some(anchor) => format!("{}#{}", renamed, anchor),
+                None => renamed,
+            };
+            format!("{}{}{}", &caps[1], href, &caps[3])
+        })
+        .into_owned()
+}
+
+/// Prepend a frontmatter block (title, created, source) to notes that lack one
+pub fn ensure_frontmatter(content: &str, fallback_title: &str, created: &str) -> String {
+    if crate::frontmatter::split_frontmatter(content).0.is_some() {
+        return content.to_string();
+    }
+
+    let title = content
+        .lines()
+        .find_map(|l| l.strip_prefix("# "))
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .unwrap_or(fallback_title);
+
+    // JSON strings are valid YAML scalars, which takes care of quoting
+    format!(
+        "---\ntitle: {}\ncreated: {}\nsource: import\n---\n\n{}",
+        serde_json::to_string(title).unwrap_or_default(),
+        created,
+        content
+    )
+}
+
+fn created_date(path: &Path) -> String {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let date: chrono::DateTime<chrono::Local> = modified.map(Into::into).unwrap_or_else(chrono::Local::now);
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Copy `source` into `kb_root/target_folder`, returning what was written
+pub fn import_into(source: &Path, kb_root: &Path, target_folder: &str) -> Result<(ImportReport, Vec<PathBuf>), String> {
+    let target_root = kb_root.join(target_folder);
+    let mut report = ImportReport { target_folder: target_folder.to_string(), ..Default::default() };
+    let mut written = Vec::new();
+    let mut claimed = HashSet::new();
+
+    let walker = WalkDir::new(source)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_str().map(|n| SKIP_DIRS.contains(&n)).unwrap_or(false));
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let relative = match path.strip_prefix(source) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+
+        let dest = target_root.join(normalize_relative(relative));
+        let dest_key = crate::kb_index::path_key(kb_root, &dest);
+        if dest.exists() || !claimed.insert(dest.clone()) {
+            report.skipped.push(relative.to_string_lossy().to_string());
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        if path.extension().map(|e| e == "md").unwrap_or(false) {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let fallback_title = dest.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let content = ensure_frontmatter(&rewrite_links(&content), &fallback_title, &created_date(path));
+
+            std::fs::write(&dest, content).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+            report.imported_notes.push(dest_key);
+            written.push(dest);
+        } else {
+            std::fs::copy(path, &dest).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+            report.copied_attachments += 1;
+        }
+    }
+
+    Ok((report, written))
+}
+
+/// Reject target folders outside the knowledge base folders
+pub fn check_target_folder(target_folder: &str) -> Result<(), String> {
+    let target = Path::new(target_folder);
+    let top = target.components().next().map(|c| c.as_os_str().to_string_lossy().to_string());
+    if target.components().any(|c| !matches!(c, Component::Normal(_)))
+        || !top.map(|t| crate::kb_index::KB_FOLDERS.contains(&t.as_str())).unwrap_or(false)
+    {
+        return Err(format!(
+            "Target folder must be a relative path inside one of: {}",
+            crate::kb_index::KB_FOLDERS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Import an external markdown folder into the knowledge base.
+///
+/// `target_folder` must live under one of the knowledge base folders (defaults to
+/// `collections/<source folder name>`). With `ingest_tkg`, every imported section is
+/// also stored in the TKG for `user_id`.
+#[tauri::command]
+pub async fn import_folder(
+    app_handle: tauri::AppHandle,
+    path: String,
+    target_folder: Option<String>,
+    ingest_tkg: Option<bool>,
+    user_id: Option<String>,
+) -> Result<ImportReport, String> {
+    let source = PathBuf::from(&path);
+    if !source.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+
+    let target_folder = target_folder.unwrap_or_else(|| {
+        let name = source.file_name().map(|n| normalize_component(&n.to_string_lossy())).unwrap_or_default();
+        format!("collections/{}", name)
+    });
+    check_target_folder(&target_folder)?;
+
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+
+    let (mut report, written) = {
+        // Index once at the end instead of reacting to every copied file
+        let _watch_pause = crate::file_watcher::pause_for_writes(vec![kb_root.join(&target_folder)]);
+        let source = source.clone();
+        let kb_root = kb_root.clone();
+        let target_folder = target_folder.clone();
+        tauri::async_runtime::spawn_blocking(move || import_into(&source, &kb_root, &target_folder))
+            .await
+            .map_err(|e| e.to_string())??
+    };
+
+    let reembed_user = if ingest_tkg.unwrap_or(false) {
+        Some(user_id.unwrap_or_else(|| "guest".to_string()))
+    } else {
+        None
+    };
+    report.tkg_ingest_started = reembed_user.is_some() && !written.is_empty();
+    crate::kb_index::apply_watch_changes(&app_handle, &written, reembed_user);
+
+    crate::events::emit(&app_handle, "content-changed", serde_json::json!({
+        "source": kb_root.join(&target_folder),
+        "paths": report.imported_notes,
+    }));
+
+    tracing::info!(
+        "📥 Imported {} note(s) and {} attachment(s) into {} ({} skipped)",
+        report.imported_notes.len(),
+        report.copied_attachments,
+        target_folder,
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An export holding a Notion-named note, a note with frontmatter, an image and `.obsidian/`
+    fn export() -> tempfile::TempDir {
+        let source = tempfile::tempdir().unwrap();
+        std::fs::write(source.path().join("Note 0123456789abcdef0123456789abcdef.md"), "# My Note\nBody").unwrap();
+        std::fs::write(source.path().join("kept.md"), "---\ntitle: Kept\n---\nBody").unwrap();
+        std::fs::write(source.path().join("diagram.png"), [0u8, 1, 2]).unwrap();
+        std::fs::create_dir_all(source.path().join(".obsidian")).unwrap();
+        std::fs::write(source.path().join(".obsidian/app.json"), "{}").unwrap();
+        source
+    }
+
+    #[test]
+    fn test_notion_names_are_normalized() {
+        assert_eq!(normalize_component("Ideas 0123456789abcdef0123456789abcdef.md"), "Ideas.md");
+        assert_eq!(normalize_component("Projects 0123456789abcdef0123456789abcdef"), "Projects");
+        assert_eq!(normalize_component("plain.md"), "plain.md");
+    }
+
+    #[test]
+    fn test_notion_links_are_rewritten() {
+        let content = "[Roadmap](Projects%200123456789abcdef0123456789abcdef/Roadmap%20fedcba9876543210fedcba9876543210.md#q1) \
+                       and [site](https://example.com/a%20b.md)";
+        assert_eq!(
+            rewrite_links(content),
+            "[Roadmap](Projects/Roadmap.md#q1) and [site](https://example.com/a%20b.md)"
+        );
+    }
+
+    #[test]
+    fn test_import_copies_notes_and_attachments() {
+        let (source, kb) = (export(), tempfile::tempdir().unwrap());
+        let (report, written) = import_into(source.path(), kb.path(), "collections/vault").unwrap();
+        assert_eq!(written.len(), 2);
+        assert_eq!(report.copied_attachments, 1);
+        assert!(!kb.path().join("collections/vault/.obsidian").exists());
+    }
+
+    #[test]
+    fn test_import_adds_frontmatter() {
+        let (source, kb) = (export(), tempfile::tempdir().unwrap());
+        import_into(source.path(), kb.path(), "collections/vault").unwrap();
+        let note = std::fs::read_to_string(kb.path().join("collections/vault/Note.md")).unwrap();
+        let (fm, body) = crate::frontmatter::split_frontmatter(&note);
+        assert_eq!(fm.unwrap().title.as_deref(), Some("My Note"));
+        assert!(body.contains("Body"));
+    }
+
+    #[test]
+    fn test_import_again_skips_existing() {
+        let (source, kb) = (export(), tempfile::tempdir().unwrap());
+        import_into(source.path(), kb.path(), "collections/vault").unwrap();
+        let (again, _) = import_into(source.path(), kb.path(), "collections/vault").unwrap();
+        assert!(again.imported_notes.is_empty());
+        assert_eq!(again.skipped.len(), 3);
+    }
+}
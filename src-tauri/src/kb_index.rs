@@ -0,0 +1,497 @@
+/// Full-text index of the markdown knowledge base
+///
+/// Backed by SQLite FTS5 in `kb_index.db`. Files are re-indexed individually as the
+/// watcher reports changes, and per-section hashes let callers re-embed only the
+/// sections that actually changed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+pub const KB_FOLDERS: [&str; 7] = [
+    "research",
+    "dumps",
+    "developer-reference",
+    "ai-agents",
+    "collections",
+    "generated-guides",
+    "journal",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KbHit {
+    pub path: String,
+    pub title: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub heading: String,
+    pub content: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KbIndexUpdate {
+    pub indexed: Vec<String>,
+    pub removed: Vec<String>,
+    /// Sections whose content is new since the last index of their file
+    #[serde(skip)]
+    pub changed_sections: Vec<(String, Section)>,
+    /// The section hashes each re-indexed file has now; empty for removed files
+    #[serde(skip)]
+    pub section_hashes: Vec<(String, Vec<String>)>,
+}
+
+/// Location of the index database (see `RepoIndex::index_db_path` for the fallback rationale)
+pub fn db_path(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
+    let data_dir = match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    }?;
+    Some(data_dir.join("kb_index.db"))
+}
+
+pub fn open_db(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let conn = Connection::open(path)?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS kb_docs (
+            path TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            modified INTEGER NOT NULL,
+            hash TEXT NOT NULL,
+            indexed_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS kb_fts USING fts5(path UNINDEXED, title, content)",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS kb_sections (
+            path TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            heading TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            PRIMARY KEY (path, position)
+        )",
+        [],
+    )?;
+
+    // Link graph was added after the first index builds; force a one-off re-index so
+    // existing notes get their links extracted
+    let had_links: bool = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'kb_links'",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    crate::note_links::init_links_table(&conn)?;
+    if !had_links {
+        conn.execute("UPDATE kb_docs SET hash = ''", [])?;
+    }
+
+    Ok(conn)
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn push_section(heading: &str, body: &str, sections: &mut Vec<Section>) {
+    if body.trim().is_empty() {
+        return;
+    }
+    let text = if heading.is_empty() {
+        body.trim().to_string()
+    } else {
+        format!("{}\n{}", heading, body.trim())
+    };
+    sections.push(Section {
+        heading: heading.trim_start_matches('#').trim().to_string(),
+        hash: sha256_hex(&text),
+        content: text,
+    });
+}
+
+/// Split markdown into heading-delimited sections (text before the first heading is its own section)
+pub fn split_sections(content: &str) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut heading = String::new();
+    let mut body = String::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        if !in_code_block && line.starts_with('#') {
+            push_section(&heading, &body, &mut sections);
+            heading = line.to_string();
+            body.clear();
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+    push_section(&heading, &body, &mut sections);
+
+    sections
+}
+
+fn title_for(path: &Path, content: &str) -> String {
+    let (fm, body) = crate::frontmatter::split_frontmatter(content);
+    if let Some(title) = fm.and_then(|f| f.title) {
+        return title;
+    }
+
+    body
+        .lines()
+        .find(|l| l.starts_with("# "))
+        .map(|l| l.trim_start_matches("# ").trim().to_string())
+        .unwrap_or_else(|| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("Unknown")
+                .to_string()
+        })
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Index path key: relative to the KB root when inside it, absolute otherwise
+pub fn path_key(kb_root: &Path, path: &Path) -> String {
+    path.strip_prefix(kb_root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// (Re-)index a single markdown file, returning sections that changed
+pub fn index_file(conn: &Connection, kb_root: &Path, path: &Path) -> rusqlite::Result<Vec<Section>> {
+    let key = path_key(kb_root, path);
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => {
+            remove_file(conn, &key)?;
+            return Ok(Vec::new());
+        }
+    };
+
+    let hash = sha256_hex(&content);
+    let previous: Option<String> = conn
+        .query_row("SELECT hash FROM kb_docs WHERE path = ?1", params![key], |row| row.get(0))
+        .optional()?;
+    if previous.as_deref() == Some(hash.as_str()) {
+        return Ok(Vec::new());
+    }
+
+    let old_hashes: HashSet<String> = {
+        let mut stmt = conn.prepare("SELECT hash FROM kb_sections WHERE path = ?1")?;
+        let rows = stmt.query_map(params![key], |row| row.get::<_, String>(0))?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let title = title_for(path, &content);
+    let sections = split_sections(&content);
+    let links = crate::note_links::extract_links(&key, &content);
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM kb_fts WHERE path = ?1", params![key])?;
+    tx.execute("INSERT INTO kb_fts (path, title, content) VALUES (?1, ?2, ?3)", params![key, title, content])?;
+    tx.execute(
+        "INSERT INTO kb_docs (path, title, modified, hash, indexed_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(path) DO UPDATE SET title = excluded.title, modified = excluded.modified,
+            hash = excluded.hash, indexed_at = excluded.indexed_at",
+        params![key, title, modified_secs(path), hash, chrono::Utc::now().to_rfc3339()],
+    )?;
+    crate::note_links::replace_links(&tx, &key, &links)?;
+    tx.execute("DELETE FROM kb_sections WHERE path = ?1", params![key])?;
+    for (i, section) in sections.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO kb_sections (path, position, heading, hash) VALUES (?1, ?2, ?3, ?4)",
+            params![key, i as i64, section.heading, section.hash],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(sections.into_iter().filter(|s| !old_hashes.contains(&s.hash)).collect())
+}
+
+pub fn remove_file(conn: &Connection, key: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM kb_fts WHERE path = ?1", params![key])?;
+    conn.execute("DELETE FROM kb_docs WHERE path = ?1", params![key])?;
+    conn.execute("DELETE FROM kb_sections WHERE path = ?1", params![key])?;
+    conn.execute("DELETE FROM kb_links WHERE source = ?1", params![key])?;
+    Ok(())
+}
+
+fn section_hashes(conn: &Connection, key: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT hash FROM kb_sections WHERE path = ?1 ORDER BY position")?;
+    let rows = stmt.query_map(params![key], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
+/// Apply a batch of changed paths: existing markdown files are re-indexed, missing ones removed
+pub fn update_paths(conn: &Connection, kb_root: &Path, paths: &[PathBuf]) -> rusqlite::Result<KbIndexUpdate> {
+    let mut update = KbIndexUpdate::default();
+
+    for path in paths {
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let key = path_key(kb_root, path);
+        if path.is_file() {
+            let changed = index_file(conn, kb_root, path)?;
+            update.changed_sections.extend(changed.into_iter().map(|s| (key.clone(), s)));
+            update.section_hashes.push((key.clone(), section_hashes(conn, &key)?));
+            update.indexed.push(key);
+        } else {
+            remove_file(conn, &key)?;
+            update.section_hashes.push((key.clone(), Vec::new()));
+            update.removed.push(key);
+        }
+    }
+
+    Ok(update)
+}
+
+/// Bring the whole index up to date (unchanged files are skipped by hash)
+pub fn refresh_all(conn: &Connection, kb_root: &Path, extra_roots: &[PathBuf]) -> rusqlite::Result<usize> {
+    let mut seen = HashSet::new();
+    let mut roots: Vec<PathBuf> = KB_FOLDERS.iter().map(|f| kb_root.join(f)).collect();
+    roots.extend(extra_roots.iter().cloned());
+
+    for root in roots.iter().filter(|r| r.exists()) {
+        for entry in WalkDir::new(root).follow_links(true).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
+                index_file(conn, kb_root, path)?;
+                seen.insert(path_key(kb_root, path));
+            }
+        }
+    }
+
+    // Drop documents that disappeared while the app wasn't running
+    let stale: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT path FROM kb_docs")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.filter_map(|r| r.ok()).filter(|p| !seen.contains(p)).collect()
+    };
+    for key in &stale {
+        remove_file(conn, key)?;
+    }
+
+    Ok(seen.len())
+}
+
+pub fn document_count(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT COUNT(*) FROM kb_docs", [], |row| row.get(0))
+}
+
+/// Ranked full-text search. Query tokens are OR-ed and ranked by BM25.
+pub fn search(conn: &Connection, query: &str, limit: usize) -> rusqlite::Result<Vec<KbHit>> {
+    let _span = tracing::info_span!("search", kind = "kb_index").entered();
+    let match_expr = query
+        .split_whitespace()
+        .map(|t| format!("\"{}\"", t.replace('"', "")))
+        .filter(|t| t != "\"\"")
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    if match_expr.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT path, title, snippet(kb_fts, 2, '', '', '…', 32), bm25(kb_fts, 0.0, 5.0, 1.0)
+         FROM kb_fts WHERE kb_fts MATCH ?1
+         ORDER BY bm25(kb_fts, 0.0, 5.0, 1.0) LIMIT ?2",
+    )?;
+
+    let rows = stmt.query_map(params![match_expr, limit as i64], |row| {
+        Ok(KbHit {
+            path: row.get(0)?,
+            title: row.get(1)?,
+            snippet: row.get(2)?,
+            // bm25 is lower-is-better; flip it so higher scores rank first like the old search
+            score: -row.get::<_, f64>(3)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+/// `refresh` on a background thread
+pub fn refresh_in_background(app_handle: tauri::AppHandle, extra_roots: Vec<PathBuf>) {
+    std::thread::spawn(move || {
+        let _ = refresh(&app_handle, &extra_roots);
+    });
+}
+
+/// Bring the index up to date with the knowledge base and `extra_roots` on this thread;
+/// returns how many documents it holds
+pub fn refresh(app_handle: &tauri::AppHandle, extra_roots: &[PathBuf]) -> Result<usize, String> {
+    let kb_root = match crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path() {
+        Ok(root) => root,
+        Err(e) => {
+            tracing::warn!("⚠️ KB index refresh skipped: {}", e);
+            return Err(e);
+        }
+    };
+    let result = db_path(Some(app_handle))
+        .ok_or_else(|| rusqlite::Error::InvalidPath("Could not find app data dir".into()))
+        .and_then(|p| open_db(&p))
+        .and_then(|conn| refresh_all(&conn, &kb_root, extra_roots));
+
+    match result {
+        Ok(count) => {
+            tracing::info!("🔎 KB index ready ({} documents)", count);
+            Ok(count)
+        }
+        Err(e) => {
+            tracing::warn!("⚠️ KB index refresh failed: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Re-index files reported by the watcher and, when enabled, re-embed changed sections in the TKG
+pub fn apply_watch_changes(app_handle: &tauri::AppHandle, paths: &[PathBuf], reembed_user: Option<String>) {
+    use tauri::Manager;
+
+    let kb_root = match crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path() {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+
+    let update = db_path(Some(app_handle))
+        .ok_or_else(|| rusqlite::Error::InvalidPath("Could not find app data dir".into()))
+        .and_then(|p| open_db(&p))
+        .and_then(|conn| update_paths(&conn, &kb_root, paths));
+
+    let update = match update {
+        Ok(update) => update,
+        Err(e) => {
+            tracing::warn!("⚠️ KB index update failed: {}", e);
+            return;
+        }
+    };
+
+    if update.indexed.is_empty() && update.removed.is_empty() {
+        return;
+    }
+
+    tracing::info!("🔎 KB index updated: {} indexed, {} removed", update.indexed.len(), update.removed.len());
+    let _ = app_handle.emit_all("kb-index-updated", &update);
+
+    let user_id = match reembed_user {
+        Some(user_id) => user_id,
+        None => return,
+    };
+
+    let notes = update.section_hashes;
+    let sections = update.changed_sections;
+    tauri::async_runtime::spawn(async move {
+        // The old versions of changed and removed sections go first, so they aren't found
+        // next to their replacements
+        for (path, keep) in &notes {
+            if let Err(e) = crate::tkg::forget_note_sections(&user_id, path, keep).await {
+                tracing::warn!("⚠️ Skipping section re-embedding: {}", e);
+                return;
+            }
+        }
+
+        let mut stored = 0;
+        for (path, section) in sections {
+            let content = format!("{}\n\n(Source: {})", section.content, path);
+            match crate::tkg::tkg_store_note_section(content, user_id.clone(), &path, &section.hash).await {
+                Ok(_) => stored += 1,
+                Err(e) => {
+                    // Most likely the TKG isn't configured; don't retry every section
+                    tracing::warn!("⚠️ Skipping section re-embedding: {}", e);
+                    break;
+                }
+            }
+        }
+        if stored > 0 {
+            tracing::info!("🧠 Re-embedded {} changed section(s)", stored);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Indexed {
+        conn: Connection,
+        note: PathBuf,
+        kb: tempfile::TempDir,
+        _db: tempfile::NamedTempFile,
+    }
+
+    /// A knowledge base with `research/rust.md` (two sections), fully indexed
+    fn indexed() -> Indexed {
+        let kb = tempfile::tempdir().unwrap();
+        let db = tempfile::NamedTempFile::new().unwrap();
+        let conn = open_db(db.path()).unwrap();
+        let research = kb.path().join("research");
+        std::fs::create_dir_all(&research).unwrap();
+        let note = research.join("rust.md");
+        std::fs::write(&note, "# Rust\nOwnership rules\n## Traits\nTraits are interfaces\n").unwrap();
+        assert_eq!(refresh_all(&conn, kb.path(), &[]).unwrap(), 1);
+        Indexed { conn, note, kb, _db: db }
+    }
+
+    #[test]
+    fn test_refresh_indexes_notes_for_search() {
+        let index = indexed();
+        let hits = search(&index.conn, "ownership", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, "research/rust.md");
+        assert_eq!(hits[0].title, "Rust");
+    }
+
+    #[test]
+    fn test_reindex_reports_only_changed_sections() {
+        let index = indexed();
+        std::fs::write(&index.note, "# Rust\nOwnership rules\n## Traits\nTraits are like typeclasses\n").unwrap();
+        let update = update_paths(&index.conn, index.kb.path(), std::slice::from_ref(&index.note)).unwrap();
+        assert_eq!(update.changed_sections.len(), 1);
+        assert_eq!(update.changed_sections[0].1.heading, "Traits");
+        let (key, hashes) = &update.section_hashes[0];
+        assert_eq!((key.as_str(), hashes.len()), ("research/rust.md", 2));
+        assert!(hashes.contains(&update.changed_sections[0].1.hash));
+    }
+
+    #[test]
+    fn test_removed_note_leaves_the_index() {
+        let index = indexed();
+        std::fs::remove_file(&index.note).unwrap();
+        let update = update_paths(&index.conn, index.kb.path(), std::slice::from_ref(&index.note)).unwrap();
+        assert_eq!(update.removed, vec!["research/rust.md".to_string()]);
+        assert_eq!(update.section_hashes, vec![("research/rust.md".to_string(), Vec::new())]);
+        assert_eq!(document_count(&index.conn).unwrap(), 0);
+    }
+}
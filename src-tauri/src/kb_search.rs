@@ -0,0 +1,177 @@
+/// Parallel keyword search over the knowledge base's markdown files
+///
+/// The fallback for `search_knowledge` when kb_index is empty. Notes are walked, read and
+/// scored on the `ignore` crate's parallel walker, one thread per core, instead of one file at
+/// a time on the caller's thread. Once `limit` strong hits (the whole phrase and every word
+/// in the note) have turned up, the walk stops rather than reading the rest of a large vault.
+
+use ignore::{WalkBuilder, WalkState};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KbHit {
+    /// Relative to the knowledge base root, with forward slashes
+    pub path: String,
+    pub title: String,
+    pub snippet: String,
+    pub score: i64,
+    pub matches: usize,
+}
+
+/// Score a note against `query` (lowercased), or None when nothing matches:
+/// - exact phrase in content: +10, in filename: +20
+/// - each word in content: +1, in filename: +2
+fn score_note(content: &str, filename: &str, query: &str, tokens: &[&str]) -> Option<(i64, usize, usize)> {
+    let content_lower = content.to_lowercase();
+    let filename = filename.to_lowercase();
+    let mut score = 0;
+    let mut matched_tokens = 0;
+
+    if content_lower.contains(query) {
+        score += 10;
+    }
+    if filename.contains(query) {
+        score += 20;
+    }
+    for token in tokens {
+        if content_lower.contains(token) {
+            score += 1;
+            matched_tokens += 1;
+        }
+        if filename.contains(token) {
+            score += 2;
+        }
+    }
+    if score == 0 {
+        return None;
+    }
+
+    // Snippet around the exact phrase, else the first word that matched
+    let snippet_pos = content_lower
+        .find(query)
+        .or_else(|| tokens.iter().find_map(|t| content_lower.find(t)))
+        .unwrap_or(0);
+    Some((score, matched_tokens, snippet_pos))
+}
+
+/// The best `limit` notes under `folders` of `root` for `query`, highest score first
+pub fn search(root: &Path, folders: &[&str], query: &str, limit: usize) -> Vec<KbHit> {
+    let _span = tracing::info_span!("search", kind = "markdown").entered();
+    let query_lower = query.to_lowercase();
+    let tokens: Vec<&str> = query_lower.split_whitespace().collect();
+    let strong_score = 10 + tokens.len() as i64;
+
+    let mut paths = folders.iter().map(|f| root.join(f)).filter(|p| p.exists());
+    let mut walker = match paths.next() {
+        Some(first) => WalkBuilder::new(first),
+        None => return Vec::new(),
+    };
+    for path in paths {
+        walker.add(path);
+    }
+    walker.standard_filters(false).follow_links(true);
+
+    let hits = Mutex::new(Vec::new());
+    let strong = AtomicUsize::new(0);
+    walker.build_parallel().run(|| {
+        let (hits, strong, query_lower, tokens) = (&hits, &strong, &query_lower, &tokens);
+        Box::new(move |entry| {
+            if strong.load(Ordering::Relaxed) >= limit {
+                return WalkState::Quit;
+            }
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(_) => return WalkState::Continue,
+            };
+            let path = entry.path();
+            if !entry.file_type().map_or(false, |t| t.is_file()) || !path.extension().map_or(false, |e| e == "md") {
+                return WalkState::Continue;
+            }
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(_) => return WalkState::Continue,
+            };
+            let title = path.file_name().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string();
+            if let Some((score, matches, pos)) = score_note(&content, &title, query_lower, tokens) {
+                if score >= strong_score {
+                    strong.fetch_add(1, Ordering::Relaxed);
+                }
+                let start = pos.saturating_sub(50);
+                let end = (pos + 150).min(content.len());
+                let hit = KbHit {
+                    path: path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/"),
+                    title,
+                    snippet: content.get(start..end).unwrap_or("").to_string(),
+                    score,
+                    matches,
+                };
+                if let Ok(mut hits) = hits.lock() {
+                    hits.push(hit);
+                }
+            }
+            WalkState::Continue
+        })
+    });
+
+    let mut hits = hits.into_inner().unwrap_or_default();
+    // Threads finish in any order; ties go by path so results are stable
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A knowledge base with two borrow-checker notes among others, and one outside the roots
+    fn knowledge_base() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let research = dir.path().join("research/rust");
+        std::fs::create_dir_all(&research).unwrap();
+        std::fs::create_dir_all(dir.path().join("private")).unwrap();
+        std::fs::write(research.join("borrow-checker.md"), "The Borrow Checker enforces ownership.").unwrap();
+        std::fs::write(research.join("lifetimes.md"), "Lifetimes relate to the borrow rules.").unwrap();
+        std::fs::write(research.join("unrelated.md"), "Nothing to see here.").unwrap();
+        std::fs::write(research.join("borrow.txt"), "borrow checker").unwrap();
+        std::fs::write(dir.path().join("private/borrow checker.md"), "borrow checker").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_search_ranks_notes_under_the_roots() {
+        let dir = knowledge_base();
+        let hits = search(dir.path(), &["research", "dumps"], "borrow checker", 10);
+        assert_eq!(hits.iter().map(|h| h.path.as_str()).collect::<Vec<_>>(), vec!["research/rust/borrow-checker.md", "research/rust/lifetimes.md"]);
+        assert_eq!((hits[0].score, hits[0].matches), (10 + 2 + 4, 2));
+        assert_eq!(hits[1].score, 1);
+    }
+
+    #[test]
+    fn test_search_snippet_shows_the_match() {
+        let dir = knowledge_base();
+        let hits = search(dir.path(), &["research", "dumps"], "borrow checker", 10);
+        assert!(hits[0].snippet.contains("Borrow Checker"));
+    }
+
+    #[test]
+    fn test_full_set_of_strong_hits_keeps_the_best() {
+        let dir = knowledge_base();
+        // A full set of strong hits ends the walk early but keeps the best ones
+        for n in 0..20 {
+            std::fs::write(dir.path().join(format!("research/rust/note-{}.md", n)), "borrow checker").unwrap();
+        }
+        let hits = search(dir.path(), &["research"], "borrow checker", 3);
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|h| h.score >= 12));
+    }
+
+    #[test]
+    fn test_missing_root_finds_nothing() {
+        let dir = knowledge_base();
+        assert!(search(dir.path(), &["missing"], "borrow", 10).is_empty());
+    }
+}
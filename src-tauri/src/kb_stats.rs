@@ -0,0 +1,157 @@
+/// Knowledge base statistics and clean-up candidates
+///
+/// Counts notes and words per folder and flags notes that are orphaned (no links in
+/// or out, per the kb_index link graph) or haven't been modified in a while.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteStat {
+    pub path: String,
+    pub words: usize,
+    pub days_since_modified: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FolderStats {
+    pub notes: usize,
+    pub words: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KbStats {
+    pub total_notes: usize,
+    pub total_words: usize,
+    pub folders: BTreeMap<String, FolderStats>,
+    pub largest_notes: Vec<NoteStat>,
+    pub orphaned_notes: Vec<String>,
+    pub stale_notes: Vec<NoteStat>,
+    pub stale_days: i64,
+}
+
+fn word_count(content: &str) -> usize {
+    let (_, body) = crate::frontmatter::split_frontmatter(content);
+    body.split_whitespace().count()
+}
+
+/// Summarize `notes`; `linked` holds every path that appears in a resolved link
+pub fn compute_stats(notes: Vec<NoteStat>, linked: &HashSet<String>, stale_days: i64, top_n: usize) -> KbStats {
+    let mut stats = KbStats { stale_days, ..Default::default() };
+
+    for note in &notes {
+        let folder = note.path.split('/').next().unwrap_or("").to_string();
+        let entry = stats.folders.entry(folder).or_default();
+        entry.notes += 1;
+        entry.words += note.words;
+        stats.total_words += note.words;
+    }
+    stats.total_notes = notes.len();
+
+    stats.orphaned_notes = notes.iter().filter(|n| !linked.contains(&n.path)).map(|n| n.path.clone()).collect();
+
+    let mut stale: Vec<NoteStat> = notes.iter().filter(|n| n.days_since_modified >= stale_days).cloned().collect();
+    stale.sort_by(|a, b| b.days_since_modified.cmp(&a.days_since_modified));
+    stats.stale_notes = stale;
+
+    let mut largest = notes;
+    largest.sort_by(|a, b| b.words.cmp(&a.words));
+    largest.truncate(top_n);
+    stats.largest_notes = largest;
+
+    stats
+}
+
+fn collect_notes(kb_root: &Path) -> Vec<NoteStat> {
+    let now = std::time::SystemTime::now();
+    let mut notes = Vec::new();
+
+    for folder in crate::kb_index::KB_FOLDERS {
+        for entry in WalkDir::new(kb_root.join(folder)).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() || path.extension().map(|e| e != "md").unwrap_or(true) {
+                continue;
+            }
+            let content = match std::fs::read_to_string(path) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            let days_since_modified = entry.metadata().ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| now.duration_since(t).ok())
+                .map(|d| (d.as_secs() / 86_400) as i64)
+                .unwrap_or(0);
+
+            notes.push(NoteStat {
+                path: crate::kb_index::path_key(kb_root, path),
+                words: word_count(&content),
+                days_since_modified,
+            });
+        }
+    }
+
+    notes
+}
+
+/// Note counts, word totals, largest notes, orphans and notes untouched for `stale_days` (default 90)
+#[tauri::command]
+pub async fn kb_stats(app_handle: tauri::AppHandle, stale_days: Option<i64>, top_n: Option<usize>) -> Result<KbStats, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let notes = collect_notes(&kb_root);
+
+        let graph = crate::note_links::open_index(Some(&app_handle))
+            .and_then(|conn| crate::note_links::note_graph(&conn).map_err(|e| e.to_string()))?;
+        let linked: HashSet<String> = graph.edges.into_iter().flat_map(|(from, to)| [from, to]).collect();
+
+        Ok(compute_stats(notes, &linked, stale_days.unwrap_or(90), top_n.unwrap_or(10)))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(path: &str, words: usize, days: i64) -> NoteStat {
+        NoteStat { path: path.to_string(), words, days_since_modified: days }
+    }
+
+    /// Two linked research notes and an unlinked journal entry, stale after 90 days
+    fn stats() -> KbStats {
+        let notes = vec![
+            note("research/a.md", 100, 3),
+            note("research/b.md", 400, 200),
+            note("journal/2024-03-01.md", 50, 120),
+        ];
+        let linked: HashSet<String> = ["research/a.md".to_string(), "research/b.md".to_string()].into();
+        compute_stats(notes, &linked, 90, 2)
+    }
+
+    #[test]
+    fn test_totals_and_folders() {
+        let stats = stats();
+        assert_eq!(stats.total_notes, 3);
+        assert_eq!(stats.total_words, 550);
+        assert_eq!(stats.folders["research"].notes, 2);
+    }
+
+    #[test]
+    fn test_largest_notes() {
+        assert_eq!(stats().largest_notes.iter().map(|n| n.path.as_str()).collect::<Vec<_>>(), vec!["research/b.md", "research/a.md"]);
+    }
+
+    #[test]
+    fn test_orphaned_notes() {
+        assert_eq!(stats().orphaned_notes, vec!["journal/2024-03-01.md".to_string()]);
+    }
+
+    #[test]
+    fn test_stale_notes() {
+        assert_eq!(stats().stale_notes.iter().map(|n| n.path.as_str()).collect::<Vec<_>>(), vec!["research/b.md", "journal/2024-03-01.md"]);
+    }
+}
@@ -0,0 +1,146 @@
+/// In-memory cache of knowledge base listings
+///
+/// `get_content_structure` and the `list_markdown_files` tool used to walk the folders (and
+/// read every note's frontmatter) on each call. Their results are now kept per folder and
+/// reused until the content watcher sees anything change under a watched folder, which bumps
+/// the generation and makes every cached listing stale. Callers can pass `force_refresh` to
+/// skip the cache. Without a running content watcher nothing would ever invalidate the cache,
+/// so until `set_watched(true)` every call walks the disk as before.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static WATCHED: AtomicBool = AtomicBool::new(false);
+
+/// Listings of type `T` keyed by the folder they were built from
+#[derive(Default)]
+pub struct ListingCache<T>(Mutex<HashMap<PathBuf, (u64, T)>>);
+
+impl<T: Clone> ListingCache<T> {
+    /// The cached listing of `folder`, or a fresh one from `build` when there is none, it is
+    /// stale or `force_refresh` is set
+    pub fn get_or_build(&self, folder: &Path, force_refresh: bool, build: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        let watched = WATCHED.load(Ordering::SeqCst);
+        if watched && !force_refresh {
+            if let Some((built_at, listing)) = self.0.lock().unwrap().get(folder) {
+                if *built_at == generation {
+                    return Ok(listing.clone());
+                }
+            }
+        }
+
+        let listing = build()?;
+        // A change that landed while this one was being built leaves it uncached
+        if watched && GENERATION.load(Ordering::SeqCst) == generation {
+            self.0.lock().unwrap().insert(folder.to_path_buf(), (generation, listing.clone()));
+        }
+        Ok(listing)
+    }
+}
+
+/// Mark every cached listing stale
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Whether the content watcher is running, and so whether listings can be cached
+pub fn set_watched(watched: bool) {
+    WATCHED.store(watched, Ordering::SeqCst);
+    invalidate();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The generation and watched flag are global, so tests that touch them take turns
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    fn take_turn(watched: bool) -> std::sync::MutexGuard<'static, ()> {
+        let turn = GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        set_watched(watched);
+        turn
+    }
+
+    /// A build that counts its calls, returning `note-<n>.md` for the nth
+    fn counting(builds: &std::cell::Cell<usize>) -> impl Fn() -> Result<Vec<String>, String> + '_ {
+        move || {
+            builds.set(builds.get() + 1);
+            Ok(vec![format!("note-{}.md", builds.get())])
+        }
+    }
+
+    #[test]
+    fn test_watched_listings_are_cached_per_folder() {
+        let _turn = take_turn(true);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        let build = counting(&builds);
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-1.md"]);
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-1.md"]);
+        assert_eq!(cache.get_or_build(Path::new("/kb/dumps"), false, &build).unwrap(), vec!["note-2.md"]);
+    }
+
+    #[test]
+    fn test_force_refresh_rebuilds_and_is_cached() {
+        let _turn = take_turn(true);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        let build = counting(&builds);
+        cache.get_or_build(folder, false, &build).unwrap();
+        assert_eq!(cache.get_or_build(folder, true, &build).unwrap(), vec!["note-2.md"]);
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-2.md"]);
+    }
+
+    #[test]
+    fn test_invalidate_makes_listings_stale() {
+        let _turn = take_turn(true);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        let build = counting(&builds);
+        cache.get_or_build(folder, false, &build).unwrap();
+        invalidate();
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-2.md"]);
+    }
+
+    #[test]
+    fn test_failed_rebuild_keeps_the_cached_listing() {
+        let _turn = take_turn(true);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        let build = counting(&builds);
+        cache.get_or_build(folder, false, &build).unwrap();
+        assert!(cache.get_or_build(folder, true, || Err("gone".to_string())).is_err());
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-1.md"]);
+    }
+
+    #[test]
+    fn test_listing_invalidated_while_building_is_not_kept() {
+        let _turn = take_turn(true);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        // A listing that was invalidated while it was being built isn't kept
+        assert_eq!(cache.get_or_build(folder, true, || { invalidate(); Ok(vec!["racing.md".to_string()]) }).unwrap(), vec!["racing.md"]);
+        assert_eq!(cache.get_or_build(folder, false, counting(&builds)).unwrap(), vec!["note-1.md"]);
+    }
+
+    #[test]
+    fn test_unwatched_listings_are_never_cached() {
+        let _turn = take_turn(false);
+        let cache: ListingCache<Vec<String>> = ListingCache::default();
+        let folder = Path::new("/kb/research");
+        let builds = std::cell::Cell::new(0);
+        let build = counting(&builds);
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-1.md"]);
+        assert_eq!(cache.get_or_build(folder, false, &build).unwrap(), vec!["note-2.md"]);
+    }
+}
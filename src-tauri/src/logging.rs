@@ -0,0 +1,216 @@
+/// Structured logging with `tracing`
+///
+/// Events go to stderr for development and, as JSON lines, to daily log files under
+/// `<app data>/logs` (the last `MAX_LOG_FILES` days are kept). `get_recent_logs` reads those
+/// files back so the settings screen can show them and users can attach them to bug reports.
+/// The level comes from `[logging] level` in thinkspace.toml (`RUST_LOG` wins at startup)
+/// and can be any `EnvFilter` directive, e.g. `"debug"` or `"info,startup_strategy_app::tkg=trace"`.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+
+pub const DEFAULT_LEVEL: &str = "info";
+const LOG_FILE_PREFIX: &str = "thinkspace";
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_RECENT: usize = 200;
+const MAX_RECENT: usize = 5000;
+
+lazy_static::lazy_static! {
+    static ref FILTER: Mutex<Option<reload::Handle<EnvFilter, Registry>>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Structured fields other than the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    /// Enclosing spans, outermost first (e.g. the tool being run)
+    pub spans: Vec<String>,
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("com.thinkspace.app").join("logs"))
+}
+
+pub fn parse_filter(level: &str) -> Result<EnvFilter, String> {
+    let level = level.trim();
+    EnvFilter::try_new(if level.is_empty() { DEFAULT_LEVEL } else { level })
+        .map_err(|e| format!("Invalid log level '{}': {}", level, e))
+}
+
+/// Install the subscriber. Keep the returned guard alive for the life of the app so
+/// buffered lines reach the log file.
+pub fn init() -> Option<WorkerGuard> {
+    let level = std::env::var("RUST_LOG").unwrap_or_else(|_| crate::config::current().logging.level.clone());
+    let filter = parse_filter(&level).unwrap_or_else(|_| EnvFilter::new(DEFAULT_LEVEL));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let appender = log_dir().and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_FILE_PREFIX)
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .ok()
+    });
+    let (file_layer, guard) = match appender {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer().json().with_current_span(false).with_span_list(true).with_writer(writer);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr).with_target(false))
+        .with(file_layer)
+        .with(crate::perf::PerfLayer)
+        .try_init();
+    if installed.is_ok() {
+        *FILTER.lock().unwrap() = Some(handle);
+    }
+    guard
+}
+
+/// Change the level of the running subscriber
+pub fn set_level(level: &str) -> Result<(), String> {
+    let filter = parse_filter(level)?;
+    match FILTER.lock().unwrap().as_ref() {
+        Some(handle) => handle.reload(filter).map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// One JSON line written by the file layer
+fn parse_line(line: &str) -> Option<LogEntry> {
+    let mut value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let text = |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let mut fields = match value.get_mut("fields").map(serde_json::Value::take) {
+        Some(serde_json::Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let message = match fields.remove("message") {
+        Some(serde_json::Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+    let spans = value
+        .get("spans")
+        .and_then(|s| s.as_array())
+        .map(|spans| spans.iter().filter_map(|s| s.get("name")?.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    Some(LogEntry {
+        timestamp: text(&value, "timestamp"),
+        level: text(&value, "level"),
+        target: text(&value, "target"),
+        message,
+        fields,
+        spans,
+    })
+}
+
+/// The newest `limit` entries at `min_level` or more severe, oldest first
+fn recent_entries(dir: &std::path::Path, limit: usize, min_level: Option<tracing::Level>) -> Vec<LogEntry> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.file_name().and_then(|n| n.to_str()).map_or(false, |n| n.starts_with(LOG_FILE_PREFIX)))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Daily files are named <prefix>.<yyyy-mm-dd>.log, so names sort by date
+    files.sort();
+
+    let mut entries = Vec::new();
+    for file in files.iter().rev() {
+        let text = std::fs::read_to_string(file).unwrap_or_default();
+        for entry in text.lines().rev().filter_map(parse_line) {
+            let severe_enough = min_level.map_or(true, |min| entry.level.parse::<tracing::Level>().map_or(true, |level| level <= min));
+            if severe_enough {
+                entries.push(entry);
+                if entries.len() == limit {
+                    entries.reverse();
+                    return entries;
+                }
+            }
+        }
+    }
+    entries.reverse();
+    entries
+}
+
+/// The most recent log entries (default 200), optionally only `level` and above
+#[tauri::command]
+pub fn get_recent_logs(limit: Option<usize>, level: Option<String>) -> Result<Vec<LogEntry>, String> {
+    let min_level = match level.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        Some(level) => Some(level.parse::<tracing::Level>().map_err(|_| format!("Unknown log level '{}'", level))?),
+        None => None,
+    };
+    let dir = log_dir().ok_or("Failed to get the log directory")?;
+    Ok(recent_entries(&dir, limit.unwrap_or(DEFAULT_RECENT).clamp(1, MAX_RECENT), min_level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A log folder with a warning one day and an info line, a bad line and an error the next
+    fn sample_logs() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("thinkspace.2026-10-15.log"),
+            r#"{"timestamp":"2026-10-15T09:00:00Z","level":"WARN","fields":{"message":"⚠️ Feed 'Rust' failed: timeout"},"target":"startup_strategy_app::feeds"}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("thinkspace.2026-10-16.log"),
+            r#"{"timestamp":"2026-10-16T08:00:00Z","level":"INFO","fields":{"message":"🔎 KB index ready (12 documents)"},"target":"startup_strategy_app::kb_index"}
+not json
+{"timestamp":"2026-10-16T08:01:00Z","level":"ERROR","fields":{"message":"❌ Tool failed","elapsed_ms":31},"target":"startup_strategy_app::minimax_enhanced","spans":[{"name":"tool","tool":"web_search"}]}
+"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_recent_entries_across_files() {
+        let dir = sample_logs();
+        let all = recent_entries(dir.path(), 10, None);
+        assert_eq!(all.iter().map(|e| e.level.as_str()).collect::<Vec<_>>(), vec!["WARN", "INFO", "ERROR"]);
+        assert_eq!(all[2].message, "❌ Tool failed");
+        assert_eq!(all[2].fields["elapsed_ms"], serde_json::json!(31));
+        assert_eq!(all[2].spans, vec!["tool".to_string()]);
+    }
+
+    #[test]
+    fn test_limit_keeps_the_newest() {
+        let dir = sample_logs();
+        assert_eq!(recent_entries(dir.path(), 1, None)[0].level, "ERROR");
+    }
+
+    #[test]
+    fn test_level_keeps_that_level_and_worse() {
+        let dir = sample_logs();
+        let warnings = recent_entries(dir.path(), 10, Some(tracing::Level::WARN));
+        assert_eq!(warnings.iter().map(|e| e.level.as_str()).collect::<Vec<_>>(), vec!["WARN", "ERROR"]);
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert!(parse_filter("info,startup_strategy_app::tkg=trace").is_ok());
+        assert!(parse_filter("tkg=loud").is_err());
+    }
+}
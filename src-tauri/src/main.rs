@@ -6,18 +6,95 @@ use serde::{Deserialize, Serialize};
 mod db;
 mod commands;
 mod repo_indexer;
+mod symbols;
+mod code_search;
+mod repo_stats;
+mod codebase_summary;
 mod ai_provider;
+mod frontmatter;
 mod minimax_api;
 mod minimax_enhanced;
 mod file_watcher;
+mod kb_index;
+mod kb_search;
+mod listing_cache;
+mod note_links;
+mod flashcards;
+mod anki_export;
+mod note_versions;
+mod note_conflicts;
+mod kb_import;
+mod attachments;
+mod daily_notes;
+mod capture;
+mod clipboard_watch;
+mod screenshot;
+mod file_drop;
+mod templates;
+mod kb_stats;
+mod markdown_render;
+mod site_export;
+mod study_sessions;
 mod tkg;
 mod scanner;
 mod session;
+mod session_bundle;
+mod canvas_export;
+mod canvas_blocks;
 mod deep_research;
+mod citations;
+mod credibility;
+mod papers;
+mod pdf_reader;
+mod translate;
+mod research_history;
+mod agent_chains;
+mod consensus;
+mod run_limits;
+mod reflection;
+mod scheduler;
+mod mediawiki;
+mod clipper;
+mod youtube;
+mod feeds;
+mod harvests;
+mod category_harvest;
+mod github;
+mod reading_list;
+mod config;
+mod logging;
+mod events;
+mod net;
+mod profiles;
+mod user_data;
+mod dry_run;
+mod permissions;
+mod embed_queue;
+mod tool_results;
+mod startup;
+mod perf;
+mod agent_sessions;
+mod openai_compat;
+mod cli;
+mod vault_sync;
+mod notion_export;
+mod backup_targets;
+mod backup;
+mod ical;
+mod reminders;
+mod notifications;
+mod digest;
+mod chat_bridge;
+mod zotero;
+mod milestones;
+mod research_notes;
+mod report_templates;
+mod web_extract;
+mod search_providers;
 
 // Import the orchestrate_agents module from commands
 use commands::orchestrate_agents;
-use session::{save_session, load_session, list_sessions};
+use session::{save_session, load_session, list_sessions, export_session};
 
 pub use tkg::*;
 
@@ -139,9 +216,11 @@ async fn save_project(app_handle: tauri::AppHandle, project: Project) -> Result<
     std::fs::create_dir_all(&app_data).map_err(|e| e.to_string())?;
 
     let db_path = app_data.join("data.db");
-    let conn = db::init_db(&db_path).map_err(|e| e.to_string())?;
-
-    db::insert_project(&conn, &project).map_err(|e| e.to_string())
+    db::blocking(move || {
+        let conn = db::pooled(&db_path, db::init_db).map_err(|e| e.to_string())?;
+        db::insert_project(&conn, &project).map_err(|e| e.to_string())
+    })
+    .await
 }
 
 #[tauri::command]
@@ -150,14 +229,29 @@ async fn get_projects(app_handle: tauri::AppHandle) -> Result<Vec<Project>, Stri
         .ok_or("Failed to get app data dir")?;
 
     let db_path = app_data.join("data.db");
-    let conn = db::init_db(&db_path).map_err(|e| e.to_string())?;
-
-    db::get_all_projects(&conn).map_err(|e| e.to_string())
+    db::blocking(move || {
+        let conn = db::pooled(&db_path, db::init_db).map_err(|e| e.to_string())?;
+        db::get_all_projects(&conn).map_err(|e| e.to_string())
+    })
+    .await
 }
 
 fn main() {
+    let _log_guard = logging::init();
+
+    // `thinkspace ask|research|harvest ...` runs one job without opening a window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(invocation) = cli::parse(&args) {
+        let status = cli::run(invocation);
+        drop(_log_guard);
+        std::process::exit(status);
+    }
+
     tauri::Builder::default()
         .manage(commands::AppState::new())
+        .manage(file_watcher::RepoWatcher::default())
+        .manage(config::ConfigWatcher::default())
+        .manage(net::HttpClients::default())
         .invoke_handler(tauri::generate_handler![
             // Original commands
             analyze_growth_tactics,
@@ -166,27 +260,124 @@ fn main() {
             analyze_pitch_deck,
             save_project,
             get_projects,
+            milestones::save_project_milestone,
+            milestones::get_project_milestones,
+            milestones::delete_project_milestone,
+            milestones::export_ical,
             // Repo explorer commands
             commands::init_ai_provider,
             commands::index_repository,
+            commands::invalidate_index,
             commands::get_repo_files,
             commands::search_files,
+            commands::find_symbol,
+            commands::grep_codebase,
+            commands::get_repo_stats,
+            commands::summarize_codebase,
             commands::read_file,
             commands::ask_ai_question,
             // Knowledge Companion commands
             minimax_api::get_content_structure,
             minimax_api::read_markdown_file,
+            note_conflicts::read_markdown_with_etag,
             minimax_api::save_markdown_file,
+            kb_import::import_folder,
+            attachments::add_attachment,
+            attachments::list_attachments,
+            attachments::cleanup_orphaned_attachments,
+            daily_notes::create_daily_note,
+            daily_notes::log_daily_entry,
+            capture::quick_capture,
+            capture::close_quick_capture,
+            clipboard_watch::get_clipboard_offers,
+            clipboard_watch::set_clipboard_watch,
+            clipboard_watch::accept_clipboard_offer,
+            clipboard_watch::dismiss_clipboard_offer,
+            screenshot::capture_screenshot,
+            file_drop::ingest_dropped_files,
+            templates::list_templates,
+            templates::get_template,
+            templates::save_template,
+            templates::delete_template,
+            templates::create_from_template,
+            note_versions::list_note_versions,
+            note_versions::get_note_version,
+            note_versions::restore_note_version,
             minimax_api::search_content,
+            minimax_api::query_notes,
+            minimax_api::list_note_tags,
+            note_links::get_backlinks,
+            note_links::get_note_graph,
+            kb_stats::kb_stats,
+            markdown_render::render_markdown,
+            site_export::export_site,
+            study_sessions::start_study_session,
+            study_sessions::end_study_session,
+            study_sessions::get_active_study_session,
+            study_sessions::get_study_time_by_topic,
+            study_sessions::list_study_sessions,
+            scheduler::start_scheduler,
+            scheduler::create_scheduled_task,
+            scheduler::set_scheduled_task_enabled,
+            scheduler::delete_scheduled_task,
+            scheduler::list_scheduled_tasks,
+            scheduler::list_scheduled_task_runs,
+            scheduler::run_scheduled_task_now,
+            feeds::start_feed_poller,
+            feeds::add_feed,
+            feeds::set_feed_enabled,
+            feeds::remove_feed,
+            feeds::list_feeds,
+            feeds::get_feed_inbox,
+            feeds::mark_feed_items_read,
+            feeds::poll_feeds_now,
+            harvests::refresh_harvests,
+            harvests::list_harvest_changes,
+            category_harvest::list_category_harvests,
+            github::set_github_token,
+            github::harvest_github_repo,
+            reading_list::ingest_reading_list,
+            config::get_config,
+            config::set_config,
+            logging::get_recent_logs,
+            net::get_network_status,
+            net::reset_network_usage,
+            net::set_offline_mode,
+            net::set_local_only,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            profiles::delete_profile,
+            user_data::export_all_data,
+            user_data::restore_all_data,
+            user_data::wipe_user_data,
+            permissions::set_tool_grants,
+            permissions::get_tool_grants,
+            permissions::revoke_tool_grants,
             minimax_api::chat_with_minimax,
             minimax_api::generate_image_minimax,
             minimax_api::get_progress,
             minimax_api::mark_guide_read,
+            flashcards::generate_flashcards,
+            flashcards::get_due_cards,
+            flashcards::record_review,
+            anki_export::export_to_anki,
             minimax_api::download_image,
             // Enhanced MiniMax M2 agent commands
             minimax_enhanced::chat_with_agent,
             minimax_enhanced::chat_with_agent_stream,
             minimax_enhanced::create_study_guide_enhanced,
+            minimax_enhanced::resume_research,
+            minimax_enhanced::rerun_research,
+            research_history::list_research_runs,
+            research_history::get_research_run,
+            research_history::compare_research_runs,
+            papers::ingest_paper,
+            pdf_reader::read_pdf,
+            translate::translate_text,
+            clipper::clip_url,
+            search_providers::get_search_settings,
+            search_providers::set_search_settings,
             minimax_enhanced::list_blueprint_files,
             minimax_enhanced::read_blueprint_file,
             // Temporal Knowledge Graph commands
@@ -202,10 +393,15 @@ fn main() {
             tkg::tkg_cascade_brainstorm,
             tkg::tkg_get_cascade_config,
             tkg::tkg_claim_legacy_data,
+            embed_queue::get_embedding_queue_status,
             // Agent Orchestration
             orchestrate_agents::orchestrate_agents,
             orchestrate_agents::create_agent_chain,
             orchestrate_agents::list_agent_chains,
+            orchestrate_agents::get_chain_run,
+            orchestrate_agents::list_chain_runs,
+            orchestrate_agents::resume_chain,
+            orchestrate_agents::orchestrate_consensus,
             orchestrate_agents::start_agent_debate,
             // Media Window Command
             open_media_window,
@@ -215,25 +411,74 @@ fn main() {
             save_session,
             load_session,
             list_sessions,
+            export_session,
+            session_bundle::export_session_bundle,
+            session_bundle::import_session_bundle,
+            canvas_export::export_canvas,
+            startup::get_ready_subsystems,
+            perf::get_performance_report,
+            agent_sessions::get_agent_session,
+            agent_sessions::stop_agent_session,
+            agent_sessions::close_agent_session,
+            openai_compat::openai_chat_completion,
+            vault_sync::sync_obsidian_vault,
+            notion_export::export_to_notion,
+            backup::get_backup_status,
+            backup::backup_now,
+            backup::list_backups,
+            backup::restore_backup,
+            reminders::list_upcoming_reminders,
+            reminders::add_reminder,
+            reminders::complete_reminder,
+            reminders::delete_reminder,
+            reminders::export_reminders_ics,
+            reminders::push_reminders_to_caldav,
+            notifications::get_notification_preferences,
+            notifications::set_notification_preference,
+            notifications::send_notification,
+            digest::get_digest_status,
+            digest::send_digest_now,
+            chat_bridge::get_chat_bridge_status,
+            zotero::pull_zotero_library,
+            zotero::list_zotero_items,
+            zotero::push_research_to_zotero,
+            // File Watcher
+            file_watcher::add_watch_path,
+            file_watcher::remove_watch_path,
+            file_watcher::list_watch_paths,
+            file_watcher::set_watch_reembed,
+            file_watcher::sync_watcher_root,
+            file_watcher::get_watch_ignore_globs,
+            file_watcher::set_watch_ignore_globs,
+            file_watcher::pause_watcher,
+            file_watcher::resume_watcher,
+            file_watcher::get_watcher_status,
         ])
         .setup(|app| {
-            // Initialize database on startup
+            // Where the databases live
             let app_data = app.path_resolver().app_data_dir()
                 .ok_or("Failed to get app data dir")?;
 
             std::fs::create_dir_all(&app_data)?;
-            let db_path = app_data.join("data.db");
-            db::init_db(&db_path)?;
 
-            // Initialize knowledge companion database
-            let kc_db_path = app_data.join("knowledge_companion.db");
-            minimax_api::init_kc_database(&kc_db_path)?;
+            // Load thinkspace.toml first; later setup reads it (e.g. the knowledge base root)
+            if let Err(e) = config::init(&app.handle()) {
+                tracing::warn!("⚠️ Config watcher unavailable: {}", e);
+            }
+
+            capture::register_hotkey(&app.handle(), None);
 
-            // Setup file watcher for automatic content refresh
-            file_watcher::setup_file_watcher(app)?;
+            // Databases, watchers and indexes start in the background so the window shows at once
+            startup::start(app.handle(), app_data);
 
             Ok(())
         })
+        .on_window_event(|event| {
+            // Dropped files are saved to the knowledge base (see file_drop.rs)
+            if let tauri::WindowEvent::FileDrop(tauri::FileDropEvent::Dropped(paths)) = event.event() {
+                file_drop::on_drop(&tauri::Manager::app_handle(event.window()), paths.clone());
+            }
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,346 @@
+/// Server-side markdown rendering for the canvas
+///
+/// Produces display-ready HTML: `![[note]]` / `![[note#heading]]` transclusions are
+/// expanded, mermaid blocks are rendered to SVG (via the `mmdc` CLI when installed,
+/// otherwise left as `<pre class="mermaid">` for client-side rendering) and local
+/// images are inlined as data URLs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use base64::Engine;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag};
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref EMBED: Regex = Regex::new(r"!\[\[([^\]|#]+)(?:#([^\]|]+))?(?:\|[^\]]*)?\]\]").unwrap();
+    static ref IMAGE_LINK: Regex = Regex::new(r"(!\[[^\]]*\]\()([^)\s]+)(\))").unwrap();
+}
+
+const MAX_EMBED_DEPTH: usize = 3;
+/// Images larger than this are linked by path instead of inlined
+const MAX_INLINE_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedNote {
+    pub path: String,
+    pub title: Option<String>,
+    pub html: String,
+    /// Non-fatal problems (missing embeds, unreadable images, ...)
+    pub warnings: Vec<String>,
+    /// True when mermaid blocks were left for the frontend to render
+    pub needs_client_mermaid: bool,
+}
+
+fn is_image(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn mime_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Lines of the section under `heading` (without the heading line itself)
+fn extract_section(content: &str, heading: &str) -> Option<String> {
+    let wanted = heading.trim().to_lowercase();
+    crate::kb_index::split_sections(content)
+        .into_iter()
+        .find(|s| s.heading.to_lowercase() == wanted)
+        .map(|s| s.content.lines().skip(1).collect::<Vec<_>>().join("\n"))
+}
+
+/// Make relative image links absolute so they still resolve after being transcluded elsewhere
+fn absolutize_images(content: &str, dir: &Path) -> String {
+    IMAGE_LINK
+        .replace_all(content, |caps: &regex::Captures| {
+            let dest = &caps[2];
+            if dest.contains("://") || dest.starts_with("data:") || Path::new(dest).is_absolute() {
+                return caps[0].to_string();
+            }
+            let decoded = urlencoding::decode(dest).map(|s| s.into_owned()).unwrap_or_else(|_| dest.to_string());
+            let absolute = dir.join(decoded).to_string_lossy().replace(' ', "%20");
+            format!("{}{}{}", &caps[1], absolute, &caps[3])
+        })
+        .into_owned()
+}
+
+/// Expand `![[...]]` embeds. `resolve` maps a note name to its file.
+pub fn expand_embeds<F>(
+    content: &str,
+    note_dir: &Path,
+    resolve: &F,
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    warnings: &mut Vec<String>,
+) -> String
+where
+    F: Fn(&str) -> Option<PathBuf>,
+{
+    EMBED
+        .replace_all(content, |caps: &regex::Captures| {
+            let name = caps[1].trim();
+
+            if is_image(name) {
+                let candidates = [note_dir.join(crate::attachments::ASSETS_DIR).join(name), note_dir.join(name)];
+                let found = candidates.iter().find(|p| p.is_file()).cloned().unwrap_or_else(|| note_dir.join(name));
+                return format!("![{}]({})", name, found.to_string_lossy().replace(' ', "%20"));
+            }
+
+            let path = match resolve(name) {
+                Some(p) => p,
+                None => {
+                    warnings.push(format!("Embedded note not found: {}", name));
+                    return format!("*Missing note: {}*", name);
+                }
+            };
+            if depth >= MAX_EMBED_DEPTH || !visited.insert(path.clone()) {
+                warnings.push(format!("Skipped recursive embed of {}", name));
+                return format!("*{}*", name);
+            }
+
+            let embedded = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    warnings.push(format!("Could not read {}: {}", path.display(), e));
+                    visited.remove(&path);
+                    return format!("*Missing note: {}*", name);
+                }
+            };
+            let (_, body) = crate::frontmatter::split_frontmatter(&embedded);
+            let body = match caps.get(2) {
+                Some(heading) => extract_section(body, heading.as_str()).unwrap_or_else(|| {
+                    warnings.push(format!("Heading '{}' not found in {}", heading.as_str(), name));
+                    String::new()
+                }),
+                None => body.to_string(),
+            };
+
+            let embedded_dir = path.parent().unwrap_or(note_dir).to_path_buf();
+            let expanded = expand_embeds(&absolutize_images(&body, &embedded_dir), &embedded_dir, resolve, depth + 1, visited, warnings);
+            visited.remove(&path);
+
+            let quoted: Vec<String> = expanded.lines().map(|l| format!("> {}", l)).collect();
+            format!("\n{}\n", quoted.join("\n"))
+        })
+        .into_owned()
+}
+
+/// Render mermaid source to SVG with the mermaid CLI, if it's installed
+pub fn render_mermaid(source: &str) -> Option<String> {
+    let dir = std::env::temp_dir().join(format!("thinkspace-mermaid-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).ok()?;
+    let input = dir.join("diagram.mmd");
+    let output = dir.join("diagram.svg");
+    std::fs::write(&input, source).ok()?;
+
+    let status = std::process::Command::new("mmdc")
+        .arg("-i").arg(&input)
+        .arg("-o").arg(&output)
+        .arg("-b").arg("transparent")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+
+    let svg = match status {
+        Ok(s) if s.success() => std::fs::read_to_string(&output).ok(),
+        _ => None,
+    };
+    let _ = std::fs::remove_dir_all(&dir);
+    svg
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn image_data_url(dest: &str, note_dir: &Path, warnings: &mut Vec<String>) -> Option<String> {
+    if dest.contains("://") || dest.starts_with("data:") {
+        return None;
+    }
+    let decoded = urlencoding::decode(dest).map(|s| s.into_owned()).unwrap_or_else(|_| dest.to_string());
+    let path = note_dir.join(decoded);
+
+    match std::fs::metadata(&path) {
+        Ok(meta) if meta.len() > MAX_INLINE_IMAGE_BYTES => None,
+        Ok(_) => match std::fs::read(&path) {
+            Ok(bytes) => Some(format!(
+                "data:{};base64,{}",
+                mime_for(&path),
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            )),
+            Err(e) => {
+                warnings.push(format!("Could not read image {}: {}", path.display(), e));
+                None
+            }
+        },
+        Err(_) => {
+            warnings.push(format!("Image not found: {}", path.display()));
+            None
+        }
+    }
+}
+
+/// Markdown (already embed-expanded) to HTML, with mermaid and local images handled
+pub fn to_html(markdown: &str, note_dir: &Path, mermaid: &dyn Fn(&str) -> Option<String>, warnings: &mut Vec<String>) -> (String, bool) {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES;
+    let mut events = Vec::new();
+    let mut mermaid_source: Option<String> = None;
+    let mut needs_client_mermaid = false;
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref lang))) if &**lang == "mermaid" => {
+                mermaid_source = Some(String::new());
+            }
+            Event::Text(ref text) if mermaid_source.is_some() => {
+                if let Some(source) = mermaid_source.as_mut() {
+                    source.push_str(text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) if mermaid_source.is_some() => {
+                let source = mermaid_source.take().unwrap_or_default();
+                let rendered = match mermaid(&source) {
+                    Some(svg) => format!("<div class=\"mermaid-diagram\">{}</div>", svg),
+                    None => {
+                        needs_client_mermaid = true;
+                        format!("<pre class=\"mermaid\">{}</pre>", html_escape(&source))
+                    }
+                };
+                events.push(Event::Html(CowStr::from(rendered)));
+            }
+            Event::Start(Tag::Image(link_type, dest, title)) => {
+                let dest = match image_data_url(&dest, note_dir, warnings) {
+                    Some(data_url) => CowStr::from(data_url),
+                    None => dest,
+                };
+                events.push(Event::Start(Tag::Image(link_type, dest, title)));
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    (out, needs_client_mermaid)
+}
+
+/// Render the note at `path` (relative to `kb_root`). `rewrite` runs on the markdown after
+/// embeds are expanded, e.g. to retarget links for a static export.
+pub fn render_note(
+    kb_root: &Path,
+    path: &str,
+    index: Option<&rusqlite::Connection>,
+    rewrite: Option<&dyn Fn(&str) -> String>,
+) -> Result<RenderedNote, String> {
+    let full_path = kb_root.join(path);
+    let content = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (fm, body) = crate::frontmatter::split_frontmatter(&content);
+    let note_dir = full_path.parent().unwrap_or(kb_root).to_path_buf();
+
+    let resolve = |name: &str| -> Option<PathBuf> {
+        let relative = note_dir.join(format!("{}.md", name.trim_end_matches(".md")));
+        if relative.is_file() {
+            return Some(relative);
+        }
+        let key = crate::note_links::resolve_note_name(index?, name).ok()??;
+        Some(kb_root.join(key))
+    };
+
+    let mut warnings = Vec::new();
+    let mut visited = HashSet::from([full_path.clone()]);
+    let mut expanded = expand_embeds(body, &note_dir, &resolve, 0, &mut visited, &mut warnings);
+    if let Some(rewrite) = rewrite {
+        expanded = rewrite(&expanded);
+    }
+    let (html, needs_client_mermaid) = to_html(&expanded, &note_dir, &render_mermaid, &mut warnings);
+
+    Ok(RenderedNote {
+        path: path.to_string(),
+        title: fm.and_then(|f| f.title),
+        html,
+        warnings,
+        needs_client_mermaid,
+    })
+}
+
+/// Render a knowledge base note to display-ready HTML
+#[tauri::command]
+pub async fn render_markdown(app_handle: tauri::AppHandle, path: String) -> Result<RenderedNote, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let index = crate::note_links::open_index(Some(&app_handle)).ok();
+        render_note(&kb_root, &path, index.as_ref(), None)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "Intro\n\n![[Borrowing#Rules]]\n\n![[dot.png]]\n\n![[Missing]]\n\n```mermaid\ngraph TD; A-->B;\n```\n";
+
+    /// A note folder with an image under `assets/` and a two-section note
+    fn research_dir() -> (tempfile::TempDir, PathBuf) {
+        let kb = tempfile::tempdir().unwrap();
+        let dir = kb.path().join("research");
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("assets/dot.png"), [137u8, 80, 78, 71]).unwrap();
+        std::fs::write(dir.join("Borrowing.md"), "# Borrowing\n## Rules\nOne mutable or many shared.\n## Other\nSkip me").unwrap();
+        (kb, dir)
+    }
+
+    /// `MARKDOWN` with its embeds expanded, and the warnings that gave
+    fn expand_sample(dir: &Path) -> (String, Vec<String>) {
+        let resolve = |name: &str| Some(dir.join(format!("{}.md", name))).filter(|p| p.is_file());
+        let mut warnings = Vec::new();
+        let mut visited = HashSet::new();
+        let expanded = expand_embeds(MARKDOWN, dir, &resolve, 0, &mut visited, &mut warnings);
+        (expanded, warnings)
+    }
+
+    #[test]
+    fn test_section_embed_quotes_only_that_section() {
+        let (_kb, dir) = research_dir();
+        let (expanded, _) = expand_sample(&dir);
+        assert!(expanded.contains("> One mutable or many shared."));
+        assert!(!expanded.contains("Skip me"));
+    }
+
+    #[test]
+    fn test_missing_embed_warns() {
+        let (_kb, dir) = research_dir();
+        assert_eq!(expand_sample(&dir).1, vec!["Embedded note not found: Missing".to_string()]);
+    }
+
+    #[test]
+    fn test_images_are_inlined() {
+        let (_kb, dir) = research_dir();
+        let (markdown, mut warnings) = expand_sample(&dir);
+        let (html, _) = to_html(&markdown, &dir, &|_| None, &mut warnings);
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+    }
+
+    #[test]
+    fn test_mermaid_is_left_to_the_client_without_a_renderer() {
+        let (_kb, dir) = research_dir();
+        let (markdown, mut warnings) = expand_sample(&dir);
+        let (html, client_mermaid) = to_html(&markdown, &dir, &|_| None, &mut warnings);
+        assert!(html.contains("<pre class=\"mermaid\">graph TD; A--&gt;B;\n</pre>"));
+        assert!(client_mermaid);
+    }
+}
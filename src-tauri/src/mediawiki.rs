@@ -0,0 +1,277 @@
+/// MediaWiki API access for the wiki harvester
+///
+/// A wiki is named by a preset (`rs3`, `osrs`, `wikipedia` with a language, `fandom` with a
+/// community) or by the URL of any MediaWiki site's api.php. Titles with an interwiki prefix
+/// (`de:Berlin`, `w:c:starwars:Yoda`) are followed to the wiki they point at. Page text is
+/// the rendered page converted to markdown, so tables and infoboxes survive; TextExtracts
+/// plaintext is the fallback.
+
+use regex::Regex;
+use std::time::Duration;
+use url::Url;
+use crate::net::SendChecked;
+
+const USER_AGENT: &str = "ThinkSpace-Research/1.0 (+https://github.com/oogalieboogalie/ThinkSpace)";
+const RS3_API: &str = "https://runescape.wiki/api.php";
+const OSRS_API: &str = "https://oldschool.runescape.wiki/api.php";
+
+lazy_static::lazy_static! {
+    static ref SUBDOMAIN: Regex = Regex::new(r"^[a-z0-9][a-z0-9-]*$").unwrap();
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some(USER_AGENT),
+        timeout: Some(Duration::from_secs(20)),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiSite {
+    /// Short name for messages, e.g. "osrs", "en.wikipedia", "starwars.fandom"
+    pub name: String,
+    pub api_base: String,
+    /// Where harvested pages go in the knowledge base
+    pub folder: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct WikiPage {
+    pub title: String,
+    pub url: String,
+    /// The page's latest revision id, when the wiki reports it
+    pub revision: Option<u64>,
+    pub text: String,
+}
+
+fn subdomain(value: &str, what: &str) -> Result<String, String> {
+    let value = value.trim().to_lowercase();
+    if SUBDOMAIN.is_match(&value) {
+        Ok(value)
+    } else {
+        Err(format!("Invalid {} '{}'", what, value))
+    }
+}
+
+impl WikiSite {
+    /// `wiki` is a preset or a MediaWiki URL; `lang` is the Wikipedia language (en by
+    /// default) and `community` the Fandom wiki (e.g. "starwars")
+    pub fn resolve(wiki: &str, lang: Option<&str>, community: Option<&str>) -> Result<Self, String> {
+        let wiki = wiki.trim();
+        let api_base = match wiki.to_lowercase().as_str() {
+            "" | "rs3" | "runescape" => RS3_API.to_string(),
+            "osrs" => OSRS_API.to_string(),
+            "wikipedia" => format!("https://{}.wikipedia.org/w/api.php", subdomain(lang.unwrap_or("en"), "Wikipedia language")?),
+            "fandom" => match community {
+                Some(community) => format!("https://{}.fandom.com/api.php", subdomain(community, "Fandom community")?),
+                None => return Err("The fandom preset needs a community, e.g. 'starwars'".to_string()),
+            },
+            lower if lower.starts_with("http://") || lower.starts_with("https://") => api_url(wiki)?,
+            _ => return Err(format!("Unknown wiki '{}': use rs3, osrs, wikipedia, fandom or a MediaWiki URL", wiki)),
+        };
+        Self::from_api_base(&api_base)
+    }
+
+    pub fn from_api_base(api_base: &str) -> Result<Self, String> {
+        let url = Url::parse(api_base).map_err(|e| format!("Invalid wiki URL '{}': {}", api_base, e))?;
+        let host = url.host_str().ok_or_else(|| format!("Invalid wiki URL '{}'", api_base))?.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+        let (name, folder) = if host == "runescape.wiki" {
+            ("rs3".to_string(), "research/rs3".to_string())
+        } else if host == "oldschool.runescape.wiki" {
+            ("osrs".to_string(), "research/osrs".to_string())
+        } else if let Some(lang) = host.strip_suffix(".wikipedia.org") {
+            (format!("{}.wikipedia", lang), format!("research/wikipedia/{}", lang))
+        } else if let Some(community) = host.strip_suffix(".fandom.com") {
+            (format!("{}.fandom", community), format!("research/fandom/{}", community))
+        } else {
+            (host.clone(), format!("research/wiki/{}", host))
+        };
+        Ok(Self { name, api_base: api_base.to_string(), folder })
+    }
+}
+
+/// The api.php of the wiki at `url`, which may already be the api.php, the site root or an
+/// article URL
+fn api_url(url: &str) -> Result<String, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid wiki URL '{}': {}", url, e))?;
+    if parsed.path().ends_with("api.php") {
+        return Ok(format!("{}{}", parsed.origin().ascii_serialization(), parsed.path()));
+    }
+    // Wikimedia sites serve the API under /w/, most other wikis at the root
+    let host = parsed.host_str().unwrap_or_default();
+    let script_path = if parsed.path().starts_with("/w/") || host.ends_with(".wikipedia.org") || host.ends_with(".wikimedia.org") {
+        "/w/api.php"
+    } else {
+        "/api.php"
+    };
+    Ok(format!("{}{}", parsed.origin().ascii_serialization(), script_path))
+}
+
+/// The wiki and title behind an article URL such as https://de.wikipedia.org/wiki/Berlin
+pub fn site_from_article_url(url: &str) -> Option<(WikiSite, String)> {
+    let parsed = Url::parse(url).ok()?;
+    let title = parsed.path().strip_prefix("/wiki/")?;
+    let title = urlencoding::decode(title).ok()?.replace('_', " ");
+    if title.trim().is_empty() {
+        return None;
+    }
+    let site = WikiSite::from_api_base(&api_url(url).ok()?).ok()?;
+    Some((site, title))
+}
+
+async fn get_json(site: &WikiSite, params: &str) -> Result<serde_json::Value, String> {
+    let url = format!("{}?{}&format=json", site.api_base, params);
+    let response = client().get(&url).send_checked().await.map_err(|e| format!("Request to {} failed: {}", site.name, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned HTTP {}", site.name, response.status()));
+    }
+    response.json().await.map_err(|e| format!("{} didn't return MediaWiki JSON: {}", site.name, e))
+}
+
+/// The wiki and exact title for `query`: interwiki prefixes are followed to the target wiki,
+/// anything else goes through the wiki's search and falls back to the query itself
+pub async fn resolve_title(site: &WikiSite, query: &str) -> (WikiSite, String) {
+    if query.contains(':') {
+        let params = format!("action=query&titles={}&iwurl=1", urlencoding::encode(query));
+        if let Ok(json) = get_json(site, &params).await {
+            let target = json["query"]["interwiki"][0]["url"].as_str().and_then(site_from_article_url);
+            if let Some((target, title)) = target {
+                tracing::info!("🔗 Interwiki '{}' → {} on {}", query, title, target.name);
+                return (target, title);
+            }
+        }
+    }
+    let params = format!("action=opensearch&search={}&limit=1", urlencoding::encode(query));
+    let title = match get_json(site, &params).await {
+        Ok(json) => json[1][0].as_str().unwrap_or(query).to_string(),
+        Err(_) => query.to_string(),
+    };
+    (site.clone(), title)
+}
+
+/// A page as markdown, tables and infoboxes included; `summary` keeps only the lead section
+pub async fn fetch_page(site: &WikiSite, title: &str, summary: bool) -> Result<WikiPage, String> {
+    let params = format!(
+        "action=query&prop=extracts|info&inprop=url&explaintext=1{}&redirects=1&titles={}",
+        if summary { "&exintro=1" } else { "" },
+        urlencoding::encode(title)
+    );
+    let json = get_json(site, &params).await?;
+    let page = match json["query"]["pages"].as_object().and_then(|pages| pages.values().next()) {
+        Some(page) => page.clone(),
+        None => return Err(format!("No page '{}' on {}", title, site.name)),
+    };
+    if page.get("missing").is_some() || page.get("invalid").is_some() {
+        return Err(format!("No page '{}' on {}", title, site.name));
+    }
+    let title = page["title"].as_str().unwrap_or(title).to_string();
+    let url = page["fullurl"].as_str().map(str::to_string).unwrap_or_else(|| {
+        format!("{}/wiki/{}", site.api_base.trim_end_matches("/api.php").trim_end_matches("/w"), urlencoding::encode(&title))
+    });
+    let revision = page["lastrevid"].as_u64();
+
+    // The rendered page keeps the tables and infoboxes that plaintext extracts drop
+    let params = format!(
+        "action=parse&prop=text&disableeditsection=1&redirects=1{}&page={}",
+        if summary { "&section=0" } else { "" },
+        urlencoding::encode(&title)
+    );
+    let rendered = match get_json(site, &params).await {
+        Ok(json) => json["parse"]["text"]["*"]
+            .as_str()
+            .map(|html| crate::web_extract::fragment_to_markdown(html, Url::parse(&url).ok().as_ref()))
+            .unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("⚠️ Couldn't render '{}' on {}: {}", title, site.name, e);
+            String::new()
+        }
+    };
+    let text = if rendered.trim().is_empty() {
+        page["extract"].as_str().unwrap_or_default().to_string()
+    } else {
+        rendered
+    };
+    if text.trim().is_empty() {
+        return Err(format!("No content found for '{}' on {}", title, site.name));
+    }
+    Ok(WikiPage { title, url, revision, text })
+}
+
+/// Titles of the articles in a category, at most `limit`, following continuations past
+/// the API's 500-per-request cap
+pub async fn category_members(site: &WikiSite, category: &str, limit: usize) -> Result<Vec<String>, String> {
+    let category = category.trim();
+    let category = category.strip_prefix("Category:").unwrap_or(category);
+    let mut titles = Vec::new();
+    let mut cont: Option<String> = None;
+    while titles.len() < limit {
+        let mut params = format!(
+            "action=query&list=categorymembers&cmtype=page&cmtitle=Category:{}&cmlimit={}",
+            urlencoding::encode(category),
+            (limit - titles.len()).min(500)
+        );
+        if let Some(cont) = &cont {
+            params.push_str(&format!("&cmcontinue={}", urlencoding::encode(cont)));
+        }
+        let json = get_json(site, &params).await?;
+        if let Some(members) = json["query"]["categorymembers"].as_array() {
+            titles.extend(members.iter().filter_map(|m| m["title"].as_str().map(str::to_string)));
+        }
+        cont = json["continue"]["cmcontinue"].as_str().map(str::to_string);
+        if cont.is_none() {
+            break;
+        }
+    }
+    titles.truncate(limit);
+    Ok(titles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_sites() {
+        let osrs = WikiSite::resolve("osrs", None, None).unwrap();
+        assert_eq!((osrs.api_base.as_str(), osrs.folder.as_str()), (OSRS_API, "research/osrs"));
+        assert_eq!(WikiSite::resolve("", None, None).unwrap().name, "rs3");
+    }
+
+    #[test]
+    fn test_wikipedia_by_language() {
+        let wikipedia = WikiSite::resolve("wikipedia", Some("DE"), None).unwrap();
+        assert_eq!(wikipedia.api_base, "https://de.wikipedia.org/w/api.php");
+        assert_eq!(wikipedia.folder, "research/wikipedia/de");
+        assert!(WikiSite::resolve("wikipedia", Some("en.evil.com/"), None).is_err());
+    }
+
+    #[test]
+    fn test_fandom_needs_a_community() {
+        let fandom = WikiSite::resolve("fandom", None, Some("starwars")).unwrap();
+        assert_eq!((fandom.name.as_str(), fandom.api_base.as_str()), ("starwars.fandom", "https://starwars.fandom.com/api.php"));
+        assert!(WikiSite::resolve("fandom", None, None).is_err());
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        assert!(WikiSite::resolve("wookieepedia", None, None).is_err());
+    }
+
+    #[test]
+    fn test_custom_wiki_urls() {
+        let custom = WikiSite::resolve("https://wiki.archlinux.org/title/Main_page", None, None).unwrap();
+        assert_eq!(custom.api_base, "https://wiki.archlinux.org/api.php");
+        assert_eq!(custom.folder, "research/wiki/wiki.archlinux.org");
+        assert_eq!(WikiSite::resolve("https://www.mediawiki.org/w/api.php?action=query", None, None).unwrap().api_base, "https://www.mediawiki.org/w/api.php");
+    }
+
+    #[test]
+    fn test_site_from_article_url() {
+        let (site, title) = site_from_article_url("https://de.wikipedia.org/wiki/K%C3%B6ln_Hauptbahnhof").unwrap();
+        assert_eq!((site.name.as_str(), title.as_str()), ("de.wikipedia", "Köln Hauptbahnhof"));
+        let (site, title) = site_from_article_url("https://starwars.fandom.com/wiki/Yoda").unwrap();
+        assert_eq!((site.api_base.as_str(), title.as_str()), ("https://starwars.fandom.com/api.php", "Yoda"));
+        assert!(site_from_article_url("https://example.com/about").is_none());
+    }
+}
@@ -0,0 +1,333 @@
+/// Project milestones and deadlines, exportable to a calendar
+///
+/// Each project (see db.rs) can have dated milestones and deadlines, stored in data.db next
+/// to it. A date alone makes an all-day event; with a time, it's an event at that time in
+/// the configured timezone, with the `[calendar]` alert ahead of it. `export_ical` writes
+/// the open ones, of one project or all of them, as a .ics file a calendar app can import or
+/// subscribe to; the UIDs are stable, so importing again updates events instead of
+/// duplicating them.
+
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::ical::{IcsEvent, IcsTime};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MilestoneKind {
+    #[default]
+    Milestone,
+    Deadline,
+}
+
+impl MilestoneKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            MilestoneKind::Milestone => "milestone",
+            MilestoneKind::Deadline => "deadline",
+        }
+    }
+
+    fn parse(text: &str) -> Self {
+        if text == "deadline" { MilestoneKind::Deadline } else { MilestoneKind::Milestone }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub id: Option<i64>,
+    pub project_id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub kind: MilestoneKind,
+    /// "YYYY-MM-DD"
+    pub due_date: String,
+    /// "HH:MM"; without it the milestone takes the whole day
+    #[serde(default)]
+    pub due_time: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub done: bool,
+    /// Filled in when listing
+    #[serde(default)]
+    pub project_name: Option<String>,
+}
+
+pub fn init_milestone_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS milestones (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            project_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            kind TEXT NOT NULL DEFAULT 'milestone',
+            due_date TEXT NOT NULL,
+            due_time TEXT,
+            notes TEXT,
+            done INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (project_id) REFERENCES projects(id)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_milestones_project ON milestones(project_id, due_date)", [])?;
+    Ok(())
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Invalid date '{}': use \"YYYY-MM-DD\"", date))
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Invalid time '{}': use \"HH:MM\"", time))
+}
+
+/// Reject a milestone without a title or with a date or time that doesn't parse
+fn check(milestone: &Milestone) -> Result<(), String> {
+    if milestone.title.trim().is_empty() {
+        return Err("A milestone needs a title".to_string());
+    }
+    parse_date(&milestone.due_date)?;
+    if let Some(time) = milestone.due_time.as_deref().filter(|t| !t.trim().is_empty()) {
+        parse_time(time)?;
+    }
+    Ok(())
+}
+
+/// Add `milestone`, or update it when it has an id; returns its id
+pub fn save_milestone(conn: &Connection, milestone: &Milestone) -> Result<i64, String> {
+    check(milestone)?;
+    let project_exists = conn
+        .query_row("SELECT 1 FROM projects WHERE id = ?1", params![milestone.project_id], |_| Ok(()))
+        .is_ok();
+    if !project_exists {
+        return Err(format!("Project {} not found", milestone.project_id));
+    }
+    let due_time = milestone.due_time.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let notes = milestone.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let values = params![
+        milestone.project_id,
+        milestone.title.trim(),
+        milestone.kind.as_str(),
+        milestone.due_date.trim(),
+        due_time,
+        notes,
+        milestone.done,
+        milestone.id,
+    ];
+    match milestone.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE milestones SET project_id = ?1, title = ?2, kind = ?3, due_date = ?4, due_time = ?5, notes = ?6, done = ?7
+                     WHERE id = ?8",
+                    values,
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Milestone {} not found", id));
+            }
+            Ok(id)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO milestones (project_id, title, kind, due_date, due_time, notes, done) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                &values[..7],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(conn.last_insert_rowid())
+        }
+    }
+}
+
+/// Milestones by date, of one project or all, optionally leaving out the done ones
+pub fn list_milestones(conn: &Connection, project_id: Option<i64>, include_done: bool) -> rusqlite::Result<Vec<Milestone>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT m.id, m.project_id, m.title, m.kind, m.due_date, m.due_time, m.notes, m.done, p.name
+         FROM milestones m LEFT JOIN projects p ON p.id = m.project_id
+         WHERE (?1 IS NULL OR m.project_id = ?1) AND (?2 OR m.done = 0)
+         ORDER BY m.due_date, m.due_time",
+    )?;
+    let rows = stmt.query_map(params![project_id, include_done], |row| {
+        Ok(Milestone {
+            id: Some(row.get(0)?),
+            project_id: row.get(1)?,
+            title: row.get(2)?,
+            kind: MilestoneKind::parse(&row.get::<_, String>(3)?),
+            due_date: row.get(4)?,
+            due_time: row.get(5)?,
+            notes: row.get(6)?,
+            done: row.get(7)?,
+            project_name: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// `milestone` as a calendar event; times are in the configured timezone
+fn to_event(milestone: &Milestone, config: &crate::config::Config) -> Option<IcsEvent> {
+    let date = parse_date(&milestone.due_date).ok()?;
+    let start = match milestone.due_time.as_deref().and_then(|t| parse_time(t).ok()) {
+        Some(time) => {
+            let local = date.and_time(time);
+            let offset = crate::config::offset_at(config, Utc.from_utc_datetime(&local));
+            IcsTime::At(offset.from_local_datetime(&local).single()?.with_timezone(&Utc))
+        }
+        None => IcsTime::Date(date),
+    };
+    let project = milestone.project_name.as_deref().unwrap_or("Project");
+    let summary = match milestone.kind {
+        MilestoneKind::Milestone => format!("{}: {}", project, milestone.title),
+        MilestoneKind::Deadline => format!("{} deadline: {}", project, milestone.title),
+    };
+    Some(IcsEvent {
+        uid: format!("milestone-{}@thinkspace", milestone.id?),
+        summary,
+        description: milestone.notes.clone(),
+        start,
+        alarm_minutes: Some(config.calendar.lead_minutes as i64),
+    })
+}
+
+fn data_db_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_data = app_handle.path_resolver().app_data_dir()
+        .ok_or("Failed to get app data dir")?;
+    std::fs::create_dir_all(&app_data).map_err(|e| e.to_string())?;
+    Ok(app_data.join("data.db"))
+}
+
+// ==================== Commands ====================
+
+#[tauri::command]
+pub async fn save_project_milestone(app_handle: tauri::AppHandle, milestone: Milestone) -> Result<i64, String> {
+    let db_path = data_db_path(&app_handle)?;
+    crate::db::blocking(move || {
+        let conn = crate::db::pooled(&db_path, crate::db::init_db).map_err(|e| e.to_string())?;
+        save_milestone(&conn, &milestone)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_project_milestones(app_handle: tauri::AppHandle, project_id: Option<i64>, include_done: Option<bool>) -> Result<Vec<Milestone>, String> {
+    let db_path = data_db_path(&app_handle)?;
+    crate::db::blocking(move || {
+        let conn = crate::db::pooled(&db_path, crate::db::init_db).map_err(|e| e.to_string())?;
+        list_milestones(&conn, project_id, include_done.unwrap_or(true)).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_project_milestone(app_handle: tauri::AppHandle, id: i64) -> Result<(), String> {
+    let db_path = data_db_path(&app_handle)?;
+    crate::db::blocking(move || {
+        let conn = crate::db::pooled(&db_path, crate::db::init_db).map_err(|e| e.to_string())?;
+        let deleted = conn.execute("DELETE FROM milestones WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+        if deleted == 0 {
+            return Err(format!("Milestone {} not found", id));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Write the open milestones and deadlines, of `project_id` or every project, to `dest` (a
+/// .ics path or a folder); returns the file written
+#[tauri::command]
+pub async fn export_ical(app_handle: tauri::AppHandle, dest: String, project_id: Option<i64>, include_done: Option<bool>) -> Result<String, String> {
+    let db_path = data_db_path(&app_handle)?;
+    let milestones = crate::db::blocking(move || {
+        let conn = crate::db::pooled(&db_path, crate::db::init_db).map_err(|e| e.to_string())?;
+        list_milestones(&conn, project_id, include_done.unwrap_or(false)).map_err(|e| e.to_string())
+    })
+    .await?;
+    let config = crate::config::current();
+    let events: Vec<IcsEvent> = milestones.iter().filter_map(|m| to_event(m, &config)).collect();
+
+    let dest = Path::new(&dest);
+    let path: PathBuf = if dest.extension().map_or(false, |e| e.eq_ignore_ascii_case("ics")) {
+        dest.to_path_buf()
+    } else {
+        let name = match (project_id, milestones.first().and_then(|m| m.project_name.as_deref())) {
+            (Some(_), Some(project)) => format!("{}-milestones.ics", crate::session::safe_name(project)),
+            _ => "thinkspace-milestones.ics".to_string(),
+        };
+        dest.join(name)
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, crate::ical::calendar(&events, Utc::now())).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    tracing::info!("📅 Exported {} milestones to {}", events.len(), path.display());
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn milestone_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE projects (id INTEGER PRIMARY KEY, name TEXT NOT NULL, created_at TIMESTAMP)", []).unwrap();
+        conn.execute("INSERT INTO projects (id, name) VALUES (1, 'Launch')", []).unwrap();
+        init_milestone_table(&conn).unwrap();
+        conn
+    }
+
+    fn milestone(title: &str, kind: MilestoneKind, date: &str, time: Option<&str>) -> Milestone {
+        Milestone {
+            id: None,
+            project_id: 1,
+            title: title.to_string(),
+            kind,
+            due_date: date.to_string(),
+            due_time: time.map(str::to_string),
+            notes: None,
+            done: false,
+            project_name: None,
+        }
+    }
+
+    /// Saves the Beta milestone (done) and the pitch deck deadline; returns Beta's id
+    fn launch_plan(conn: &Connection) -> i64 {
+        let beta = save_milestone(conn, &milestone("Beta", MilestoneKind::Milestone, "2026-11-02", None)).unwrap();
+        save_milestone(conn, &milestone("Pitch deck", MilestoneKind::Deadline, "2026-10-30", Some("17:00"))).unwrap();
+        let done = Milestone { id: Some(beta), done: true, ..milestone("Beta", MilestoneKind::Milestone, "2026-11-02", None) };
+        assert_eq!(save_milestone(conn, &done).unwrap(), beta);
+        beta
+    }
+
+    #[test]
+    fn test_save_rejects_bad_dates_and_unknown_projects() {
+        let conn = milestone_db();
+        assert!(save_milestone(&conn, &milestone("Bad", MilestoneKind::Milestone, "Nov 2", None)).is_err());
+        assert!(save_milestone(&conn, &Milestone { project_id: 7, ..milestone("Orphan", MilestoneKind::Milestone, "2026-11-02", None) }).is_err());
+    }
+
+    #[test]
+    fn test_list_leaves_out_done_milestones() {
+        let conn = milestone_db();
+        launch_plan(&conn);
+        let open = list_milestones(&conn, Some(1), false).unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!((open[0].kind, open[0].project_name.as_deref()), (MilestoneKind::Deadline, Some("Launch")));
+        assert_eq!(list_milestones(&conn, None, true).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_milestones_to_calendar() {
+        let conn = milestone_db();
+        let beta = launch_plan(&conn);
+        let mut config = crate::config::Config::default();
+        config.general.timezone = "-05:00".to_string();
+        let events: Vec<IcsEvent> = list_milestones(&conn, None, true).unwrap().iter().filter_map(|m| to_event(m, &config)).collect();
+        let ics = crate::ical::calendar(&events, Utc.with_ymd_and_hms(2026, 10, 16, 8, 0, 0).unwrap());
+        assert!(ics.contains("SUMMARY:Launch deadline: Pitch deck\r\n"));
+        assert!(ics.contains("DTSTART:20261030T220000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261102\r\n"));
+        assert!(ics.contains(&format!("UID:milestone-{}@thinkspace\r\n", beta)));
+    }
+}
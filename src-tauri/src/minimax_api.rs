@@ -3,9 +3,13 @@ use std::path::Path;
 use walkdir::WalkDir;
 use rusqlite::{params, Connection, Result as SqlResult};
 
+use crate::frontmatter::{self, Frontmatter};
+use crate::listing_cache::ListingCache;
+use crate::net::SendChecked;
+
 // ==================== Data Structures ====================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentItem {
     pub name: String,
     pub path: String,
@@ -13,6 +17,8 @@ pub struct ContentItem {
     pub item_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<ContentItem>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontmatter: Option<Frontmatter>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -21,6 +27,19 @@ pub struct SearchResult {
     pub title: String,
     pub snippet: String,
     pub matches: usize,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteMeta {
+    pub path: String,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub created: Option<String>,
+    pub status: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,7 +61,7 @@ pub struct ChatMessage {
 // ==================== Database Functions ====================
 
 pub fn init_kc_database(db_path: &Path) -> SqlResult<Connection> {
-    let conn = Connection::open(db_path)?;
+    let conn = crate::db::open(db_path)?;
 
     conn.execute(
         "CREATE TABLE IF NOT EXISTS progress (
@@ -66,6 +85,19 @@ pub fn init_kc_database(db_path: &Path) -> SqlResult<Connection> {
         [],
     )?;
 
+    crate::flashcards::init_flashcards_tables(&conn)?;
+    crate::note_versions::init_versions_table(&conn)?;
+    crate::study_sessions::init_study_tables(&conn)?;
+    crate::research_history::init_research_tables(&conn)?;
+    crate::agent_chains::init_chain_tables(&conn)?;
+    crate::scheduler::init_scheduler_tables(&conn)?;
+    crate::feeds::init_feed_tables(&conn)?;
+    crate::harvests::init_harvest_tables(&conn)?;
+    crate::category_harvest::init_category_harvest_tables(&conn)?;
+    crate::embed_queue::init_embedding_queue_table(&conn)?;
+    crate::reminders::init_reminder_tables(&conn)?;
+    crate::zotero::init_zotero_tables(&conn)?;
+
     // Initialize progress row if it doesn't exist
     conn.execute(
         "INSERT OR IGNORE INTO progress (id, guides_read) VALUES (1, 0)",
@@ -75,12 +107,41 @@ pub fn init_kc_database(db_path: &Path) -> SqlResult<Connection> {
     Ok(conn)
 }
 
-fn get_db_connection() -> SqlResult<Connection> {
-    let app_data = tauri::api::path::data_dir()
+/// Open knowledge_companion.db in the app data dir with all Knowledge Companion tables in place
+pub fn open_kc_database(app_handle: Option<&tauri::AppHandle>) -> Result<crate::db::PooledConnection, String> {
+    let data_dir = match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    }
+    .ok_or("Could not find app data dir")?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+
+    crate::db::pooled(&data_dir.join("knowledge_companion.db"), init_kc_database).map_err(|e| e.to_string())
+}
+
+fn get_db_connection() -> SqlResult<crate::db::PooledConnection> {
+    // Same file startup initializes (app_data_dir = <data dir>/<bundle identifier>)
+    let app_data = dirs::data_dir()
+        .map(|d| d.join("com.thinkspace.app"))
         .ok_or_else(|| rusqlite::Error::InvalidPath("Could not find app data dir".into()))?;
 
     let db_path = app_data.join("knowledge_companion.db");
-    Connection::open(db_path)
+    crate::db::pooled(&db_path, init_kc_database)
+}
+
+/// Add `amount` to one of the progress counters, off the async executor
+async fn bump_progress(column: &'static str, amount: i32) {
+    crate::startup::wait_for(crate::startup::Subsystem::Databases).await;
+    let bumped = crate::db::blocking(move || {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        conn.prepare_cached(&format!("UPDATE progress SET {0} = {0} + ?1 WHERE id = 1", column))
+            .and_then(|mut stmt| stmt.execute(params![amount]))
+            .map_err(|e| e.to_string())
+    })
+    .await;
+    if let Err(e) = bumped {
+        tracing::warn!("⚠️ Failed to update progress: {}", e);
+    }
 }
 
 // ==================== Content Management ====================
@@ -110,7 +171,7 @@ fn get_knowledge_base_path() -> Result<std::path::PathBuf, String> {
     let kb_root = doc_dir.join("KnowledgeCompanion");
 
     // 3. Ensure structure exists
-    let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides"];
+    let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides", "journal"];
     for folder in folders {
         let p = kb_root.join(folder);
         if !p.exists() {
@@ -121,28 +182,36 @@ fn get_knowledge_base_path() -> Result<std::path::PathBuf, String> {
     Ok(kb_root)
 }
 
+lazy_static::lazy_static! {
+    static ref CONTENT_STRUCTURES: ListingCache<Vec<ContentItem>> = ListingCache::default();
+}
+
+/// The knowledge base folder tree; cached until the content watcher sees a change, unless
+/// `force_refresh` is set
 #[tauri::command]
-pub async fn get_content_structure() -> Result<Vec<ContentItem>, String> {
+pub async fn get_content_structure(force_refresh: Option<bool>) -> Result<Vec<ContentItem>, String> {
     // Get the knowledge base root
     let repo_root = get_knowledge_base_path()?;
 
-    eprintln!("Repo root: {:?}", repo_root);
-    eprintln!("Research exists: {}", repo_root.join("research").exists());
+    tracing::debug!("Repo root: {:?}", repo_root);
+    tracing::debug!("Research exists: {}", repo_root.join("research").exists());
 
-    let mut structure = Vec::new();
+    CONTENT_STRUCTURES.get_or_build(&repo_root, force_refresh.unwrap_or(false), || {
+        let mut structure = Vec::new();
 
-    // Add main content folders
-    let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides"];
+        // Add main content folders
+        let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides", "journal"];
 
-    for folder in folders {
-        let folder_path = repo_root.join(folder);
-        if folder_path.exists() {
-            let item = build_content_tree(&folder_path, folder)?;
-            structure.push(item);
+        for folder in folders {
+            let folder_path = repo_root.join(folder);
+            if folder_path.exists() {
+                let item = build_content_tree(&folder_path, folder)?;
+                structure.push(item);
+            }
         }
-    }
 
-    Ok(structure)
+        Ok(structure)
+    })
 }
 
 fn build_content_tree(path: &Path, name: &str) -> Result<ContentItem, String> {
@@ -152,6 +221,7 @@ fn build_content_tree(path: &Path, name: &str) -> Result<ContentItem, String> {
             path: path.to_string_lossy().to_string(),
             item_type: "file".to_string(),
             children: None,
+            frontmatter: frontmatter::read_frontmatter(path),
         });
     }
 
@@ -189,6 +259,7 @@ fn build_content_tree(path: &Path, name: &str) -> Result<ContentItem, String> {
         path: path.to_string_lossy().to_string(),
         item_type: "directory".to_string(),
         children: Some(children),
+        frontmatter: None,
     })
 }
 
@@ -199,32 +270,73 @@ pub async fn read_markdown_file(path: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn save_markdown_file(path: String, content: String) -> Result<(), String> {
+pub async fn save_markdown_file(
+    app_handle: tauri::AppHandle,
+    path: String,
+    content: String,
+    expected_etag: Option<String>,
+) -> Result<String, String> {
     // Get the knowledge base root
     let repo_root = get_knowledge_base_path()?;
 
     let full_path = repo_root.join(&path);
 
+    // Reject the save if someone (e.g. the agent) changed the note since the editor loaded it
+    crate::note_conflicts::check_expected(&full_path, expected_etag.as_deref())
+        .map_err(|conflict| conflict.to_string())?;
+
     // Ensure parent directory exists
     if let Some(parent) = full_path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
 
-    std::fs::write(full_path, content).map_err(|e| e.to_string())?;
-    Ok(())
+    crate::note_versions::record_before_write(Some(&app_handle), &repo_root, &full_path, "editor");
+
+    std::fs::write(full_path, &content).map_err(|e| e.to_string())?;
+    Ok(crate::note_conflicts::etag_for(&content))
+}
+
+/// Pull `tag:x` and `status:x` filters out of a search query, returning the remaining text
+fn parse_search_filters(query: &str) -> (String, Vec<String>, Option<String>) {
+    let mut text = Vec::new();
+    let mut tags = Vec::new();
+    let mut status = None;
+
+    for token in query.split_whitespace() {
+        if let Some(tag) = token.strip_prefix("tag:").or_else(|| token.strip_prefix('#')) {
+            tags.push(tag.to_lowercase());
+        } else if let Some(s) = token.strip_prefix("status:") {
+            status = Some(s.to_lowercase());
+        } else {
+            text.push(token);
+        }
+    }
+
+    (text.join(" "), tags, status)
+}
+
+fn matches_filters(fm: Option<&Frontmatter>, tags: &[String], status: Option<&str>) -> bool {
+    let note_tags = fm.map(|f| f.tags.as_slice()).unwrap_or(&[]);
+    let tags_ok = tags.iter().all(|t| note_tags.contains(t));
+    let status_ok = match status {
+        Some(s) => fm.and_then(|f| f.status.as_deref()) == Some(s),
+        None => true,
+    };
+    tags_ok && status_ok
 }
 
 #[tauri::command]
 pub async fn search_content(query: String) -> Result<Vec<SearchResult>, String> {
-    let mut repo_root = get_knowledge_base_path()?;
+    let repo_root = get_knowledge_base_path()?;
 
     if !repo_root.join("research").exists() {
         // Fallback or just log warning
-        eprintln!("Warning: research folder not found in {:?}", repo_root);
+        tracing::warn!("research folder not found in {:?}", repo_root);
     }
 
     let mut results = Vec::new();
-    let query_lower = query.to_lowercase();
+    let (text_query, tag_filters, status_filter) = parse_search_filters(&query);
+    let query_lower = text_query.to_lowercase();
 
     for entry in WalkDir::new(&repo_root)
         .follow_links(true)
@@ -238,28 +350,51 @@ pub async fn search_content(query: String) -> Result<Vec<SearchResult>, String>
         }
 
         if let Ok(content) = std::fs::read_to_string(path) {
-            let content_lower = content.to_lowercase();
+            let (fm, body) = frontmatter::split_frontmatter(&content);
+            if !matches_filters(fm.as_ref(), &tag_filters, status_filter.as_deref()) {
+                continue;
+            }
 
-            if content_lower.contains(&query_lower) {
-                let title = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
+            let body_lower = body.to_lowercase();
+            let title_lower = fm.as_ref().and_then(|f| f.title.as_deref()).unwrap_or("").to_lowercase();
+
+            // A filter-only query (e.g. "tag:rust") matches every note that passes the filters
+            let hit = query_lower.is_empty()
+                || body_lower.contains(&query_lower)
+                || title_lower.contains(&query_lower);
+
+            if hit {
+                let title = fm.as_ref()
+                    .and_then(|f| f.title.clone())
+                    .unwrap_or_else(|| {
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("Unknown")
+                            .to_string()
+                    });
 
                 // Extract snippet around first match
-                let match_pos = content_lower.find(&query_lower).unwrap_or(0);
-                let snippet_start = match_pos.saturating_sub(50);
-                let snippet_end = (match_pos + query.len() + 100).min(content.len());
-                let snippet = content[snippet_start..snippet_end].to_string();
+                let match_pos = if query_lower.is_empty() { 0 } else { body_lower.find(&query_lower).unwrap_or(0) };
+                let snippet_start = floor_char_boundary(body, match_pos.saturating_sub(50));
+                let snippet_end = floor_char_boundary(body, (match_pos + text_query.len() + 100).min(body.len()));
+                let snippet = body[snippet_start..snippet_end].to_string();
+
+                // Count matches (title hits count double)
+                let matches = if query_lower.is_empty() {
+                    0
+                } else {
+                    body_lower.matches(&query_lower).count() + 2 * title_lower.matches(&query_lower).count()
+                };
 
-                // Count matches
-                let matches = content_lower.matches(&query_lower).count();
+                let (tags, status) = fm.map(|f| (f.tags, f.status)).unwrap_or_default();
 
                 results.push(SearchResult {
                     path: path.to_string_lossy().to_string(),
                     title,
                     snippet,
                     matches,
+                    tags,
+                    status,
                 });
             }
         }
@@ -274,14 +409,108 @@ pub async fn search_content(query: String) -> Result<Vec<SearchResult>, String>
     Ok(results)
 }
 
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while index > 0 && !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Collect frontmatter metadata for every note in the knowledge base
+fn collect_note_meta(kb_root: &Path) -> Vec<NoteMeta> {
+    let mut notes = Vec::new();
+
+    for entry in WalkDir::new(kb_root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if !path.is_file() || !path.extension().map(|e| e == "md").unwrap_or(false) {
+            continue;
+        }
+
+        let fm = frontmatter::read_frontmatter(path).unwrap_or_default();
+        let relative = path.strip_prefix(kb_root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        notes.push(NoteMeta {
+            title: fm.title.unwrap_or_else(|| {
+                path.file_stem().and_then(|n| n.to_str()).unwrap_or("Unknown").to_string()
+            }),
+            path: relative,
+            tags: fm.tags,
+            created: fm.created,
+            status: fm.status,
+        });
+    }
+
+    notes
+}
+
+/// Query notes by frontmatter. Dates compare as ISO strings (e.g. "2024-03-01").
+#[tauri::command]
+pub async fn query_notes(
+    tag: Option<String>,
+    status: Option<String>,
+    created_after: Option<String>,
+    created_before: Option<String>,
+) -> Result<Vec<NoteMeta>, String> {
+    let repo_root = get_knowledge_base_path()?;
+    let tag = tag.map(|t| t.trim_start_matches('#').to_lowercase());
+    let status = status.map(|s| s.to_lowercase());
+
+    let mut notes: Vec<NoteMeta> = collect_note_meta(&repo_root)
+        .into_iter()
+        .filter(|n| tag.as_ref().map(|t| n.tags.contains(t)).unwrap_or(true))
+        .filter(|n| status.is_none() || n.status == status)
+        .filter(|n| match (&created_after, &n.created) {
+            (Some(after), Some(created)) => created.as_str() >= after.as_str(),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .filter(|n| match (&created_before, &n.created) {
+            (Some(before), Some(created)) => created.as_str() <= before.as_str(),
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .collect();
+
+    // Newest first, undated notes last
+    notes.sort_by(|a, b| b.created.cmp(&a.created));
+
+    Ok(notes)
+}
+
+/// All tags used in the knowledge base with their note counts
+#[tauri::command]
+pub async fn list_note_tags() -> Result<Vec<(String, usize)>, String> {
+    let repo_root = get_knowledge_base_path()?;
+    let mut counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+
+    for note in collect_note_meta(&repo_root) {
+        for tag in note.tags {
+            *counts.entry(tag).or_insert(0) += 1;
+        }
+    }
+
+    let mut tags: Vec<(String, usize)> = counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(tags)
+}
+
 // ==================== MiniMax API Integration ====================
 
 #[tauri::command]
 pub async fn chat_with_minimax(
+    clients: tauri::State<'_, crate::net::HttpClients>,
     api_key: String,
     messages: Vec<ChatMessage>,
 ) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    let client = clients.client();
 
     // Define tools for filesystem access
     let tools = serde_json::json!([
@@ -328,14 +557,14 @@ pub async fn chat_with_minimax(
         "max_tokens": 4096,
     });
 
-    eprintln!("Sending request to MiniMax API (OpenAI format)");
+    tracing::debug!("Sending request to MiniMax API (OpenAI format)");
 
     let response = client
         .post("https://api.minimax.io/v1/chat/completions")
         .header("Authorization", format!("Bearer {}", api_key))
         .header("Content-Type", "application/json")
         .json(&payload)
-        .send()
+        .send_checked()
         .await
         .map_err(|e| format!("Request failed: {}", e))?;
 
@@ -347,7 +576,7 @@ pub async fn chat_with_minimax(
     let result: serde_json::Value = response.json().await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    eprintln!("MiniMax response: {}", serde_json::to_string_pretty(&result).unwrap_or_default());
+    tracing::debug!("MiniMax response: {}", serde_json::to_string_pretty(&result).unwrap_or_default());
 
     // Parse OpenAI-format response
     let message = result["choices"][0]["message"].as_object()
@@ -356,7 +585,7 @@ pub async fn chat_with_minimax(
     // Check for tool calls
     if let Some(tool_calls) = message.get("tool_calls").and_then(|tc| tc.as_array()) {
         if !tool_calls.is_empty() {
-            eprintln!("AI wants to call {} tool(s)", tool_calls.len());
+            tracing::info!("AI wants to call {} tool(s)", tool_calls.len());
 
             // Execute tools and collect results
             let mut tool_messages: Vec<ChatMessage> = messages.clone();
@@ -378,7 +607,7 @@ pub async fn chat_with_minimax(
                 let args: serde_json::Value = serde_json::from_str(args_str)
                     .map_err(|e| format!("Failed to parse tool arguments: {}", e))?;
 
-                eprintln!("Executing tool: {} with args: {}", name, args);
+                tracing::debug!("Executing tool: {} with args: {}", name, args);
 
                 let result = match name {
                     "read_file" => {
@@ -414,7 +643,7 @@ pub async fn chat_with_minimax(
                 .header("Authorization", format!("Bearer {}", api_key))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_checked()
                 .await
                 .map_err(|e| format!("Request failed: {}", e))?;
 
@@ -426,12 +655,7 @@ pub async fn chat_with_minimax(
                 .ok_or("Missing content in response")?;
 
             // Update progress
-            if let Ok(conn) = get_db_connection() {
-                let _ = conn.execute(
-                    "UPDATE progress SET questions_asked = questions_asked + 1 WHERE id = 1",
-                    [],
-                );
-            }
+            bump_progress("questions_asked", 1).await;
 
             return Ok(content.to_string());
         }
@@ -443,24 +667,20 @@ pub async fn chat_with_minimax(
         .ok_or("Missing content in response")?;
 
     // Update progress
-    if let Ok(conn) = get_db_connection() {
-        let _ = conn.execute(
-            "UPDATE progress SET questions_asked = questions_asked + 1 WHERE id = 1",
-            [],
-        );
-    }
+    bump_progress("questions_asked", 1).await;
 
     Ok(content.to_string())
 }
 
 #[tauri::command]
 pub async fn generate_image_minimax(
+    clients: tauri::State<'_, crate::net::HttpClients>,
     api_key: String,
     prompt: String,
     aspect_ratio: Option<String>,
     n: Option<u32>,
 ) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    let client = clients.client();
 
     // Build payload with optional parameters
     let mut payload = serde_json::json!({
@@ -481,8 +701,8 @@ pub async fn generate_image_minimax(
 
     let payload = payload;
 
-    eprintln!("Sending image generation request to MiniMax API");
-    eprintln!("Payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
+    tracing::debug!("Sending image generation request to MiniMax API");
+    tracing::debug!("Payload: {}", serde_json::to_string_pretty(&payload).unwrap_or_default());
 
     // Try .io domain first (same as chat), fall back to .com if needed
     let endpoints = vec![
@@ -494,13 +714,13 @@ pub async fn generate_image_minimax(
     let mut response = None;
 
     for endpoint in &endpoints {
-        eprintln!("Trying endpoint: {}", endpoint);
+        tracing::debug!("Trying endpoint: {}", endpoint);
         match client
             .post(*endpoint)
             .header("Authorization", format!("Bearer {}", api_key))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_checked()
             .await
         {
             Ok(resp) => {
@@ -509,7 +729,7 @@ pub async fn generate_image_minimax(
             }
             Err(e) => {
                 last_error = format!("Endpoint {} failed: {}", endpoint, e);
-                eprintln!("{}", last_error);
+                tracing::warn!("{}", last_error);
                 continue;
             }
         }
@@ -518,18 +738,18 @@ pub async fn generate_image_minimax(
     let response = response.ok_or(format!("All endpoints failed. Last error: {}", last_error))?;
 
     let status = response.status();
-    eprintln!("Response status: {}", status);
+    tracing::debug!("Response status: {}", status);
 
     if !status.is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        eprintln!("API error response: {}", error_text);
+        tracing::error!("API error response: {}", error_text);
         return Err(format!("API error ({}): {}", status, error_text));
     }
 
     let result: serde_json::Value = response.json().await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    eprintln!("Image generation response: {}", serde_json::to_string_pretty(&result).unwrap_or_default());
+    tracing::debug!("Image generation response: {}", serde_json::to_string_pretty(&result).unwrap_or_default());
 
     // Parse new response format: { "data": { "image_urls": ["url1", "url2"] }, ... }
     let image_urls = result["data"]["image_urls"]
@@ -537,15 +757,10 @@ pub async fn generate_image_minimax(
         .ok_or_else(|| format!("Failed to extract image URLs from response: {}",
             serde_json::to_string(&result).unwrap_or_default()))?;
 
-    eprintln!("Successfully generated {} images", image_urls.len());
+    tracing::info!("Successfully generated {} images", image_urls.len());
 
     // Update progress
-    if let Ok(conn) = get_db_connection() {
-        let _ = conn.execute(
-            "UPDATE progress SET images_generated = images_generated + ? WHERE id = 1",
-            params![image_urls.len() as i32],
-        );
-    }
+    bump_progress("images_generated", image_urls.len() as i32).await;
 
     // Return single URL or array of URLs based on count
     if image_urls.len() == 1 {
@@ -560,53 +775,58 @@ pub async fn generate_image_minimax(
 
 #[tauri::command]
 pub async fn get_progress() -> Result<Progress, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-
-    let progress: Progress = conn.query_row(
-        "SELECT guides_read, questions_asked, images_generated, hours_learned, streak FROM progress WHERE id = 1",
-        [],
-        |row| {
-            Ok(Progress {
-                guides_read: row.get(0)?,
-                total_guides: 259,
-                questions_asked: row.get(1)?,
-                images_generated: row.get(2)?,
-                hours_learned: row.get(3)?,
-                streak: row.get(4)?,
-            })
-        },
-    ).map_err(|e| e.to_string())?;
+    crate::startup::wait_for(crate::startup::Subsystem::Databases).await;
+    crate::db::blocking(|| {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
 
-    Ok(progress)
+        let progress: Progress = conn.query_row(
+            "SELECT guides_read, questions_asked, images_generated, hours_learned, streak FROM progress WHERE id = 1",
+            [],
+            |row| {
+                Ok(Progress {
+                    guides_read: row.get(0)?,
+                    total_guides: 259,
+                    questions_asked: row.get(1)?,
+                    images_generated: row.get(2)?,
+                    hours_learned: row.get(3)?,
+                    streak: row.get(4)?,
+                })
+            },
+        ).map_err(|e| e.to_string())?;
+
+        Ok(progress)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn mark_guide_read(path: String) -> Result<(), String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-
-    let now = chrono::Utc::now().to_rfc3339();
-
-    conn.execute(
-        "INSERT OR IGNORE INTO read_guides (path, read_at) VALUES (?1, ?2)",
-        params![path, now],
-    ).map_err(|e| e.to_string())?;
-
-    conn.execute(
-        "UPDATE progress SET guides_read = (SELECT COUNT(*) FROM read_guides) WHERE id = 1",
-        [],
-    ).map_err(|e| e.to_string())?;
-
-    Ok(())
+    crate::startup::wait_for(crate::startup::Subsystem::Databases).await;
+    crate::db::blocking(move || {
+        let mut conn = get_db_connection().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // One transaction, so the count always matches the rows
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.prepare_cached("INSERT OR IGNORE INTO read_guides (path, read_at) VALUES (?1, ?2)")
+            .and_then(|mut stmt| stmt.execute(params![path, now]))
+            .map_err(|e| e.to_string())?;
+        tx.prepare_cached("UPDATE progress SET guides_read = (SELECT COUNT(*) FROM read_guides) WHERE id = 1")
+            .and_then(|mut stmt| stmt.execute([]))
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())
+    })
+    .await
 }
 
 // ==================== Utilities ====================
 
 #[tauri::command]
-pub async fn download_image(url: String, filename: String) -> Result<(), String> {
-    let client = reqwest::Client::new();
+pub async fn download_image(clients: tauri::State<'_, crate::net::HttpClients>, url: String, filename: String) -> Result<(), String> {
+    let client = clients.client();
 
     let response = client.get(&url)
-        .send()
+        .send_checked()
         .await
         .map_err(|e| format!("Failed to download image: {}", e))?;
 
@@ -10,7 +10,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::tkg;
 use crate::commands::orchestrate_agents;
-use crate::deep_research::DeepResearchAgent;
+use crate::deep_research::{estimate_tokens, load_checkpoints, save_checkpoint, DeepResearchAgent, ProgressReporter, ResearchBudget, ResearchConfig, ResearchPhase, ResearchRun};
+use crate::citations::{check_citations, normalize_citations, store_report, with_footnotes, Bibliography, CitationCheck, CITATION_INSTRUCTIONS};
+use crate::credibility::{flag_weak_claims, score_sources, with_credibility_notes, CREDIBILITY_INSTRUCTIONS};
 use std::path::PathBuf;
 use walkdir::WalkDir;
 use regex::Regex;
@@ -19,6 +21,8 @@ use futures_util::stream::StreamExt;
 use chrono::TimeZone;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use crate::net::SendChecked;
+use tracing::Instrument;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppMode {
@@ -48,6 +52,18 @@ impl AppMode {
             AppMode::Developer => &[],
         }
     }
+
+    /// What the mode itself allows, beneath thinkspace.toml and the conversation's grants
+    fn grants(self) -> crate::permissions::ToolGrants {
+        match self {
+            AppMode::Student => crate::permissions::ToolGrants {
+                tools: ["run_terminal_command", "write_file_batch"].iter().map(|t| (t.to_string(), false)).collect(),
+                write_paths: Some(self.allowed_write_prefixes().iter().map(|p| p.to_string()).collect()),
+                ..Default::default()
+            },
+            AppMode::Developer => crate::permissions::ToolGrants::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -75,6 +91,32 @@ impl AIProvider {
         }
     }
 
+    /// Base URL and model, with any override from thinkspace.toml. In local-only mode every
+    /// provider talks to the local chat endpoint instead.
+    pub fn endpoint(&self) -> (String, String) {
+        let key = match self {
+            AIProvider::Minimax => "minimax",
+            AIProvider::Grok => "grok",
+            AIProvider::Gemini => "gemini",
+        };
+        let config = crate::config::current();
+        let overrides = config.provider(key);
+        let model = overrides.and_then(|p| p.model.clone()).unwrap_or_else(|| self.model_name().to_string());
+        if let Some(base_url) = Self::local_chat_url() {
+            return (base_url, config.local.chat_model.clone().unwrap_or(model));
+        }
+        (
+            overrides.and_then(|p| p.base_url.clone()).unwrap_or_else(|| self.base_url().to_string()),
+            model,
+        )
+    }
+
+    /// The local OpenAI-compatible endpoint every provider uses in local-only mode, if set
+    fn local_chat_url() -> Option<String> {
+        let config = crate::config::current();
+        config.local.chat_base_url.clone().filter(|_| config.network.local_only)
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             AIProvider::Minimax => "MiniMax M2",
@@ -132,6 +174,9 @@ pub struct ChatResponse {
     pub thinking: Vec<String>,
     pub tool_calls_made: usize,
     pub iterations: usize,
+    /// The draft and critique, when the request asked for reflection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reflection: Option<crate::reflection::Reflection>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,10 +185,18 @@ pub struct StreamChunk {
     pub is_thinking: bool,
     pub done: bool,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// The agent session the chunk belongs to, so a chat only shows its own stream
+    #[serde(default)]
+    pub session: Option<String>,
 }
 
 // ==================== Agent Loop Implementation ====================
 
+lazy_static::lazy_static! {
+    /// `list_markdown_files` results per folder
+    static ref MARKDOWN_LISTINGS: crate::listing_cache::ListingCache<Vec<String>> = Default::default();
+}
+
 pub struct MinimaxAgent {
     api_key: String,
     base_url: String,
@@ -156,20 +209,32 @@ pub struct MinimaxAgent {
     gemini_api_key: Option<String>,
     app_handle: Option<tauri::AppHandle>,
     provider: AIProvider,
-    enabled_tools: std::collections::HashMap<String, bool>,
+    /// What this conversation has been granted (see permissions.rs)
+    grants: crate::permissions::ToolGrants,
     safe_mode: bool,
+    /// Report what file-writing and terminal tools would do instead of doing it
+    dry_run: bool,
     app_mode: AppMode,
     user_id: String,
     user_name: Option<String>,
+    /// Etag of each file as of this agent's last read, used to detect concurrent edits
+    read_etags: std::sync::Mutex<HashMap<PathBuf, String>>,
+    /// Set to stop the current turn (by `stop-generation` or the session's stop command)
+    cancel: Arc<AtomicBool>,
+    /// The agent session this turn runs in, if the request named one
+    session: Option<String>,
+    /// Shared HTTP clients for provider calls and tools (see net.rs)
+    http: crate::net::HttpClients,
 }
 
 impl MinimaxAgent {
     pub fn new(api_key: String, tavily_api_key: Option<String>, grok_api_key: Option<String>, gemini_api_key: Option<String>) -> Self {
         let app_mode = AppMode::current();
+        let (base_url, model) = AIProvider::Minimax.endpoint();
         Self {
             api_key,
-            base_url: "https://api.minimax.io/v1".to_string(),
-            model: "MiniMax-M2".to_string(),
+            base_url,
+            model,
             conversation_history: Vec::new(),
             system_prompt: Self::default_system_prompt(None),
             tools: Self::register_tools(),
@@ -178,11 +243,29 @@ impl MinimaxAgent {
             gemini_api_key,
             app_handle: None,
             provider: AIProvider::Minimax,
-            enabled_tools: std::collections::HashMap::new(),
-            safe_mode: app_mode == AppMode::Student, // Student builds default to safe mode
+            grants: crate::permissions::ToolGrants::default(),
+            safe_mode: app_mode == AppMode::Student || crate::config::current().tools.safe_mode, // Student builds default to safe mode
+            dry_run: false,
             app_mode,
             user_id: "guest".to_string(),
             user_name: None,
+            read_etags: std::sync::Mutex::new(HashMap::new()),
+            cancel: Arc::new(AtomicBool::new(false)),
+            session: None,
+            http: crate::net::HttpClients::default(),
+        }
+    }
+
+    /// The etag the caller passed, or the one recorded when this agent last read the file
+    fn expected_etag(&self, full_path: &std::path::Path, explicit: Option<&str>) -> Option<String> {
+        explicit
+            .map(|e| e.to_string())
+            .or_else(|| self.read_etags.lock().ok()?.get(full_path).cloned())
+    }
+
+    fn remember_etag(&self, full_path: &std::path::Path) {
+        if let (Ok(mut etags), Some(etag)) = (self.read_etags.lock(), crate::note_conflicts::current_etag(full_path)) {
+            etags.insert(full_path.to_path_buf(), etag);
         }
     }
 
@@ -227,7 +310,7 @@ impl MinimaxAgent {
         candidates.into_iter().find(|path| path.exists())
     }
 
-    fn load_agents_registry(&self) -> Result<serde_json::Value, String> {
+    pub fn load_agents_registry(&self) -> Result<serde_json::Value, String> {
         let agents_path = self
             .resolve_agents_registry_path()
             .ok_or_else(|| "Could not resolve app data directory".to_string())?;
@@ -250,13 +333,13 @@ impl MinimaxAgent {
 
         if let Some(parent) = agents_path.parent() {
             if let Err(e) = std::fs::create_dir_all(parent) {
-                eprintln!("WARN: could not create agents dir: {}", e);
+                tracing::warn!("could not create agents dir: {}", e);
             }
         }
 
         if let Ok(serialized) = serde_json::to_string_pretty(&data) {
             if let Err(e) = std::fs::write(&agents_path, serialized) {
-                eprintln!("WARN: could not seed agents registry: {}", e);
+                tracing::warn!("could not seed agents registry: {}", e);
             }
         }
 
@@ -340,10 +423,14 @@ impl MinimaxAgent {
         self
     }
 
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     pub fn with_provider(mut self, provider: AIProvider) -> Self {
         self.provider = provider.clone();
-        self.base_url = provider.base_url().to_string();
-        self.model = provider.model_name().to_string();
+        (self.base_url, self.model) = provider.endpoint();
         
         // Switch system prompt based on provider
         if let AIProvider::Grok = provider {
@@ -354,12 +441,25 @@ impl MinimaxAgent {
     }
 
     pub fn with_app_handle(mut self, app_handle: tauri::AppHandle) -> Self {
+        self.http = crate::net::HttpClients::of(Some(&app_handle));
         self.app_handle = Some(app_handle);
         self
     }
 
     pub fn with_enabled_tools(mut self, enabled_tools: std::collections::HashMap<String, bool>) -> Self {
-        self.enabled_tools = enabled_tools;
+        self.grants.tools = enabled_tools;
+        self
+    }
+
+    /// Share a cancellation flag, e.g. the one of a live session
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Layer a conversation's scoped grants over its per-tool switches
+    pub fn with_grants(mut self, grants: crate::permissions::ToolGrants) -> Self {
+        self.grants = std::mem::take(&mut self.grants).merge(grants);
         self
     }
 
@@ -368,11 +468,48 @@ impl MinimaxAgent {
         self
     }
 
+    /// Add a caller's instructions to the system prompt, e.g. an API client's system messages
+    pub fn with_instructions(mut self, instructions: &str) -> Self {
+        self.system_prompt = format!("{}\n\n## Instructions from the caller\n{}", self.system_prompt, instructions);
+        self
+    }
+
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Start from an earlier conversation
+    pub fn with_history(mut self, history: Vec<Message>) -> Self {
+        self.conversation_history = history;
+        self
+    }
+
+    /// Apply the agent configuration persisted with a session
+    pub fn with_session_settings(mut self, settings: &crate::session::SessionSettings) -> Self {
+        // Re-apply the user name so the provider-specific default prompt is regenerated
+        let user_name = self.user_name.clone();
+        // A session can turn safe mode on, not off where the config or app mode has it on
+        let safe_mode = settings.safe_mode || self.safe_mode || self.app_mode == AppMode::Student;
+        let dry_run = settings.dry_run || self.dry_run;
+        self = self
+            .with_provider(settings.provider.clone())
+            .with_user_name(user_name)
+            .with_enabled_tools(settings.enabled_tools.clone())
+            .with_safe_mode(safe_mode)
+            .with_dry_run(dry_run);
+
+        if let Some(model) = &settings.model {
+            self = self.with_model(model.clone());
+        }
+        if let Some(prompt) = &settings.system_prompt {
+            self = self.with_system_prompt(prompt.clone());
+        }
+        self
+    }
+
     fn get_current_timestamp() -> String {
-        chrono::Utc::now()
-            .with_timezone(&chrono::FixedOffset::west_opt(5 * 3600).unwrap())
-            .format("%Y-%m-%d %H:%M:%S")
-            .to_string()
+        crate::config::now().format("%Y-%m-%d %H:%M:%S").to_string()
     }
 
     /// Convert various timestamp formats to the standard string format
@@ -384,8 +521,8 @@ impl MinimaxAgent {
                 if let Some(ts) = n.as_f64() {
                     let seconds = (ts / 1000.0) as i64;
                     if let Some(datetime) = chrono::Utc.timestamp_opt(seconds, 0).single() {
-                        let est = datetime.with_timezone(&chrono::FixedOffset::west_opt(5 * 3600).unwrap());
-                        Some(est.format("%Y-%m-%d %H:%M:%S").to_string())
+                        let local = datetime.with_timezone(&crate::config::offset_at(&crate::config::current(), datetime));
+                        Some(local.format("%Y-%m-%d %H:%M:%S").to_string())
                     } else {
                         None
                     }
@@ -398,10 +535,7 @@ impl MinimaxAgent {
     }
 
     fn default_system_prompt(user_name: Option<String>) -> String {
-        let current_time = chrono::Utc::now()
-            .with_timezone(&chrono::FixedOffset::west_opt(5 * 3600).unwrap())
-            .format("%Y-%m-%d %H:%M:%S %Z")
-            .to_string();
+        let current_time = crate::config::now().format("%Y-%m-%d %H:%M:%S %Z").to_string();
 
         let greeting = match user_name {
             Some(name) => format!("You are a helpful AI study guide assistant for {}.", name),
@@ -429,10 +563,7 @@ Remember: Your internal reasoning is preserved and helps maintain context across
     }
 
     fn grok_dash_system_prompt(user_name: Option<String>) -> String {
-        let current_time = chrono::Utc::now()
-            .with_timezone(&chrono::FixedOffset::west_opt(5 * 3600).unwrap())
-            .format("%Y-%m-%d %H:%M:%S %Z")
-            .to_string();
+        let current_time = crate::config::now().format("%Y-%m-%d %H:%M:%S %Z").to_string();
 
         let name_str = user_name.unwrap_or_else(|| "Alex".to_string());
 
@@ -467,57 +598,37 @@ GOOD - Always write like this:
 "#, name_str, current_time, include_str!("ai_manual.md"))
 }
 
-    fn is_forced_disabled_tool(&self, tool_name: &str) -> bool {
-        self.app_mode == AppMode::Student
-            && matches!(tool_name, "run_terminal_command" | "write_file_batch")
-    }
-
-    fn is_allowed_write_path(&self, rel_path: &str) -> bool {
-        if self.app_mode != AppMode::Student {
-            return true;
-        }
-
-        let normalized = rel_path.replace('\\', "/");
-        let trimmed = normalized.trim_start_matches("./");
-
-        self.app_mode.allowed_write_prefixes().iter().any(|prefix| {
-            trimmed == *prefix
-                || (trimmed.starts_with(prefix)
-                    && trimmed.as_bytes().get(prefix.len()) == Some(&b'/'))
-        })
+    /// Every layer of grants a tool call has to pass, with where each comes from
+    fn grant_layers(&self) -> Vec<(crate::permissions::ToolGrants, &'static str)> {
+        vec![
+            (self.app_mode.grants(), "in student mode"),
+            (crate::permissions::ToolGrants::disabling(&crate::config::current().tools.disabled), "in thinkspace.toml"),
+            (self.grants.clone(), "in this session"),
+        ]
     }
 
-    /// Filter tools based on enabled_tools configuration
+    /// Filter tools down to the ones every layer of grants allows
     fn get_enabled_tools(&self) -> Vec<Tool> {
-        let base_tools: Vec<Tool> = self.tools
+        let layers = self.grant_layers();
+        self.tools
             .iter()
-            .filter(|tool| !self.is_forced_disabled_tool(&tool.function.name))
+            .filter(|tool| layers.iter().all(|(grants, _)| grants.allows_tool(&tool.function.name)))
             .cloned()
-            .collect();
-
-        if self.enabled_tools.is_empty() {
-            return base_tools;
-        }
-
-        base_tools
-            .into_iter()
-            .filter(|tool| {
-                let tool_name = &tool.function.name;
-                self.enabled_tools
-                    .get(tool_name)
-                    .copied()
-                    .unwrap_or(true) // Default to enabled if not in map
-            })
             .collect()
     }
 
+    /// Names of every tool the agent has, switched on or not
+    pub fn tool_names() -> Vec<String> {
+        Self::register_tools().into_iter().map(|tool| tool.function.name).collect()
+    }
+
     fn register_tools() -> Vec<Tool> {
         vec![
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
                     name: "canvas_update".to_string(),
-                    description: "Update the dashboard canvas. Use this to show previews, add content blocks, or clear the canvas.".to_string(),
+                    description: "Update the dashboard canvas. Use this to show previews, add content blocks, or clear the canvas. add_block also takes structured blocks in 'data': chart {type: line|bar|area|pie, title?, labels, series: [{name, values}]}, table {columns, rows}, tasks {title?, items: [text or {text, done}]}, kanban {title?, columns: [{title, items}]} and mermaid {code}.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
@@ -533,7 +644,7 @@ GOOD - Always write like this:
                             },
                             "type": {
                                 "type": "string",
-                                "description": "Content type (e.g., 'youtube', 'threejs', 'md', 'manifold')"
+                                "description": "Content type (e.g., 'youtube', 'threejs', 'md', 'manifold'); for add_block also 'chart', 'table', 'tasks', 'kanban' or 'mermaid' with 'data'"
                             },
                             "content": {
                                 "type": "string",
@@ -547,6 +658,10 @@ GOOD - Always write like this:
                                 "type": "string",
                                 "description": "Code for Three.js or Manifold visualizations"
                             },
+                            "data": {
+                                "type": "object",
+                                "description": "Payload of a chart, table, tasks, kanban or mermaid block, shaped as the tool description shows"
+                            },
                             "popup": {
                                 "type": "boolean",
                                 "description": "Whether to show as a popup"
@@ -597,1486 +712,2678 @@ GOOD - Always write like this:
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "read_file".to_string(),
-                    description: "Read the complete contents of a markdown file from the knowledge base. Use the path from search_knowledge or list_markdown_files.".to_string(),
-                    parameters: serde_json::json!({
-                        "type": "object",
-                        "properties": {
-                            "path": {
-                                "type": "string",
-                                "description": "Relative path to the markdown file (e.g., 'research/adhd-database.md')"
-                            }
-                        },
-                        "required": ["path"]
-                    }),
-                },
-            },
-            Tool {
-                tool_type: "function".to_string(),
-                function: ToolFunction {
-                    name: "search_knowledge".to_string(),
-                    description: "Search the loaded markdown knowledge base for information on a topic. Returns matching files with snippets.".to_string(),
+                    name: "get_repo_stats".to_string(),
+                    description: "Get a quick structural overview of the indexed repository: files and lines of code per language, the largest files, and recently modified hot spots.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "query": {
-                                "type": "string",
-                                "description": "Search query"
+                            "top_n": {
+                                "type": "integer",
+                                "description": "How many largest/recent files to list (default: 10)"
                             }
-                        },
-                        "required": ["query"]
+                        }
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "create_study_guide".to_string(),
-                    description: "Create a structured study guide from a topic using Grok. Returns a markdown formatted study guide.".to_string(),
+                    name: "grep_codebase".to_string(),
+                    description: "Search file contents with a regex (ripgrep-style). Respects .gitignore. Returns file, line number and matching line with optional context. Prefer this over reading whole files to locate code or text.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "topic": {
+                            "pattern": {
                                 "type": "string",
-                                "description": "The topic to create a study guide for"
+                                "description": "Regular expression to search for (e.g., 'fn\\s+chat')"
                             },
-                            "difficulty": {
+                            "path": {
                                 "type": "string",
-                                "enum": ["beginner", "intermediate", "advanced"],
-                                "description": "Difficulty level"
+                                "description": "Relative directory to search in (default: root)"
                             },
-                            "include_resources": {
+                            "globs": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Glob filters, e.g. ['*.rs', '!target/**']"
+                            },
+                            "context_lines": {
+                                "type": "integer",
+                                "description": "Lines of context before and after each match (default: 0)"
+                            },
+                            "case_insensitive": {
                                 "type": "boolean",
-                                "description": "Include specific resources and practice exercises (default: true)"
+                                "description": "Ignore case (default: false)"
+                            },
+                            "fixed_strings": {
+                                "type": "boolean",
+                                "description": "Treat the pattern as a literal string (default: false)"
+                            },
+                            "max_results": {
+                                "type": "integer",
+                                "description": "Maximum matches to return (default: 50)"
                             }
                         },
-                        "required": ["topic", "difficulty"]
+                        "required": ["pattern"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "list_markdown_files".to_string(),
-                    description: "List available markdown files in the knowledge base".to_string(),
+                    name: "find_symbol".to_string(),
+                    description: "Find where a function, method, struct, class or trait is defined in the indexed repository. Accepts qualified names like 'MinimaxAgent::chat' or 'Foo.bar'. Returns file, line and signature without reading whole files.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "folder": {
+                            "name": {
                                 "type": "string",
-                                "description": "Optional folder to search in (e.g., 'research', 'dumps')"
+                                "description": "Symbol name, optionally qualified with its parent type (e.g., 'MinimaxAgent::chat')"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum number of matches (default: 20)"
                             }
-                        }
+                        },
+                        "required": ["name"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "web_search".to_string(),
-                    description: "Search the web for current information using Tavily search API. Returns top search results with title, snippet, and URL.".to_string(),
+                    name: "read_file".to_string(),
+                    description: "Read the complete contents of a markdown file from the knowledge base. Use the path from search_knowledge or list_markdown_files.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "query": {
+                            "path": {
                                 "type": "string",
-                                "description": "Search query (e.g., 'latest AI news 2025', 'Tauri desktop app tutorial')"
-                            },
-                            "max_results": {
-                                "type": "integer",
-                                "description": "Maximum number of results to return (1-10, default: 5)"
+                                "description": "Relative path to the markdown file (e.g., 'research/adhd-database.md')"
                             }
                         },
-                        "required": ["query"]
+                        "required": ["path"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "deep_research".to_string(),
-                    description: "Delegates a complex research task to a specialized Deep Research Agent. Use this for broad topics requiring synthesis of multiple sources.".to_string(),
+                    name: "read_pdf".to_string(),
+                    description: "Read the text of a PDF in the knowledge base (papers, slide decks, manuals), page by page, to quote or summarize it. Dropped PDFs are under dumps/dropped/assets/. Long documents come back in parts: if 'next_page' is set, call again with a range starting there.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "topic": {
+                            "path": {
                                 "type": "string",
-                                "description": "The main research topic or question"
+                                "description": "Relative path to the PDF (e.g., 'research/papers/assets/attention.pdf')"
                             },
-                            "sub_topics": {
-                                "type": "array",
-                                "items": {
-                                    "type": "string"
-                                },
-                                "description": "Optional list of sub-topics to research in parallel. If provided, multiple agents will be spawned."
+                            "page_range": {
+                                "type": "string",
+                                "description": "Pages to read, counted from 1: '3', '2-5', '1,4,7-9' or '10-' (default: all)"
                             }
                         },
-                        "required": ["topic"]
+                        "required": ["path"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "write_file".to_string(),
-                    description: "Write/create a markdown file in the knowledge base. Can create new files or overwrite existing ones.".to_string(),
+                    name: "translate".to_string(),
+                    description: "Translate text, or a whole note from the knowledge base, into another language while keeping its markdown (headings, lists, tables, links, code) intact. With 'save', a note's translation is written next to it as <name>.<language>.md. Use it for studying foreign-language sources.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
+                            "text": {
+                                "type": "string",
+                                "description": "Text to translate (give this or 'path')"
+                            },
                             "path": {
                                 "type": "string",
-                                "description": "Relative path for the file (e.g., 'research/new-guide.md', 'dumps/notes.md')"
+                                "description": "Relative path of a markdown note to translate"
                             },
-                            "content": {
+                            "target_language": {
                                 "type": "string",
-                                "description": "Content to write to the file"
+                                "description": "Language to translate into, e.g. 'English' or 'de'"
                             },
-                            "append": {
+                            "source_language": {
+                                "type": "string",
+                                "description": "Language of the original (default: detected)"
+                            },
+                            "save": {
                                 "type": "boolean",
-                                "description": "If true, append to existing file. If false, overwrite. Default: false"
+                                "description": "Save a note's translation alongside it (default: false)"
                             }
                         },
-                        "required": ["path", "content"]
+                        "required": ["target_language"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "display_media".to_string(),
-                    description: "Display media (video, image, or website) directly on the user's canvas. Use this after finding a relevant URL via web_search.".to_string(),
+                    name: "search_knowledge".to_string(),
+                    description: "Search the loaded markdown knowledge base for information on a topic. Returns matching files with snippets.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "url": {
-                                "type": "string",
-                                "description": "The URL of the media to display (e.g., YouTube link, image URL)"
-                            },
-                            "type": {
+                            "query": {
                                 "type": "string",
-                                "enum": ["youtube", "image", "url", "html"],
-                                "description": "The type of media. Use 'youtube' for videos, 'image' for direct image links, 'url' for websites."
+                                "description": "Search query"
                             }
                         },
-                        "required": ["url", "type"]
+                        "required": ["query"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "harvest_wiki".to_string(),
-                    description: "Harvests content from the RuneScape (RS3) or Old School RuneScape (OSRS) Wiki. Saves the article as a markdown file in the research folder.".to_string(),
+                    name: "get_note_links".to_string(),
+                    description: "Follow the links of a knowledge base note: the notes it links to ([[wikilinks]] and markdown links) and the notes that link back to it. Use this to explore related documents.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "query": {
-                                "type": "string",
-                                "description": "The topic to search for (e.g., 'Herblore', 'Zulrah')"
-                            },
-                            "wiki": {
-                                "type": "string",
-                                "enum": ["rs3", "osrs"],
-                                "description": "Which Wiki to search (default: 'rs3')"
-                            },
-                            "mode": {
+                            "path": {
                                 "type": "string",
-                                "enum": ["summary", "full"],
-                                "description": "Harvest mode: 'summary' (intro only) or 'full' (entire page). Default: 'full'"
+                                "description": "Note path relative to the knowledge base root (e.g., 'research/rust-ownership.md')"
                             }
                         },
-                        "required": ["query"]
+                        "required": ["path"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "harvest_wiki_category".to_string(),
-                    description: "Mass harvest all pages in a specific Wiki category (e.g., 'Quests', 'Herblore'). Saves each page as a separate markdown file.".to_string(),
+                    name: "log_daily_note".to_string(),
+                    description: "Append an entry to today's journal note (journal/YYYY-MM-DD.md, created if needed). Use when the user asks to log progress, record what they did, or journal. The entry is also evaluated for long-term memory.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "category": {
+                            "entry": {
                                 "type": "string",
-                                "description": "The category name (e.g., 'Quests', 'Farming_training')"
-                            },
-                            "wiki": {
-                                "type": "string",
-                                "enum": ["rs3", "osrs"],
-                                "description": "Which Wiki to search (default: 'rs3')"
-                            },
-                            "limit": {
-                                "type": "integer",
-                                "description": "Max pages to harvest (default: 10, max: 50)"
+                                "description": "The log entry, written as a short first-person note (e.g., 'Finished chapter 3 of the Rust book')"
                             }
                         },
-                        "required": ["category"]
+                        "required": ["entry"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "brainstorm_with_grok".to_string(),
-                    description: "Get a second perspective from Grok-4 for brainstorming, creative ideas, or alternative viewpoints. Returns Grok's response to enhance your thinking.".to_string(),
+                    name: "create_flashcards".to_string(),
+                    description: "Save question/answer flashcards to the user's spaced-repetition deck. Write the cards yourself from a note or study guide (read it first), keeping each question focused and each answer concise.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "query": {
+                            "source_path": {
                                 "type": "string",
-                                "description": "Question or topic to get a second perspective on from Grok"
+                                "description": "Knowledge base path of the note the cards were made from (optional)"
                             },
-                            "context": {
-                                "type": "string",
-                                "description": "Additional context or background information"
+                            "cards": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "question": { "type": "string" },
+                                        "answer": { "type": "string" }
+                                    },
+                                    "required": ["question", "answer"]
+                                },
+                                "description": "Flashcards to add"
                             }
                         },
-                        "required": ["query"]
+                        "required": ["cards"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "tkg_search".to_string(),
-                    description: "Search the Temporal Knowledge Graph for semantically similar knowledge and memories. Uses vector embeddings to find related information from past conversations and learning.".to_string(),
+                    name: "notify".to_string(),
+                    description: "Send the user a desktop notification, now or at a set time. Use it for nudges the user asks for ('remind me to review this guide tonight'); a timed one is kept as a reminder and shows up in their upcoming reminders.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "query": {
+                            "message": {
                                 "type": "string",
-                                "description": "Search query to find semantically related knowledge"
-                            },
-                            "limit": {
-                                "type": "integer",
-                                "description": "Maximum number of results to return (1-20, default: 5)"
+                                "description": "What to tell the user, phrased as the nudge itself (e.g., 'Review the Rust ownership guide')"
                             },
-                            "trust_threshold": {
-                                "type": "number",
-                                "description": "Minimum trust score for results (0.0-1.0, default: 0.5)"
+                            "when": {
+                                "type": "string",
+                                "description": "When to send it: an ISO 8601 time, a date, or words like 'tonight', 'tomorrow at 9am', 'in 2 hours'. Omit to use a time in the message, or to send it now."
                             }
                         },
-                        "required": ["query"]
+                        "required": ["message"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "tkg_store".to_string(),
-                    description: "Store important knowledge in the Temporal Knowledge Graph for future semantic search. Preserves memories with embeddings for context-aware retrieval.".to_string(),
+                    name: "create_study_guide".to_string(),
+                    description: "Create a structured study guide from a topic using Grok. Returns a markdown formatted study guide.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "content": {
+                            "topic": {
                                 "type": "string",
-                                "description": "The knowledge or memory to store"
+                                "description": "The topic to create a study guide for"
                             },
-                            "node_type": {
+                            "difficulty": {
                                 "type": "string",
-                                "enum": ["FACT", "CONCEPT", "MEMORY", "LEARNING", "INSIGHT", "USER_INPUT", "AI_RESPONSE"],
-                                "description": "Type of knowledge node"
+                                "enum": ["beginner", "intermediate", "advanced"],
+                                "description": "Difficulty level"
                             },
-                            "importance": {
-                                "type": "number",
-                                "description": "Importance score (0.0-1.0)"
+                            "include_resources": {
+                                "type": "boolean",
+                                "description": "Include specific resources and practice exercises (default: true)"
                             }
                         },
-                        "required": ["content", "node_type"]
+                        "required": ["topic", "difficulty"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "claim_legacy_data".to_string(),
-                    description: "Migrate past memories/knowledge from 'guest' sessions to the current user. Defaults to dry-run unless explicitly confirmed to prevent accidental destructive migrations.".to_string(),
+                    name: "list_markdown_files".to_string(),
+                    description: "List available markdown files in the knowledge base".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "dry_run": {
-                                "type": "boolean",
-                                "description": "If true, only reports how many legacy points would be migrated (default: true)."
+                            "folder": {
+                                "type": "string",
+                                "description": "Optional folder to search in (e.g., 'research', 'dumps')"
                             },
-                            "confirm": {
+                            "force_refresh": {
                                 "type": "boolean",
-                                "description": "Set true to actually perform the migration. If omitted/false, the tool will not modify any data."
+                                "description": "Re-scan the disk instead of using the cached listing (default false)"
                             }
-                        },
-                        "required": []
+                        }
                     }),
                 },
             },
-
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "start_debate".to_string(),
-                    description: "Starts a multi-agent debate on a topic. Spawns an Architect and a Critic to discuss and refine a solution. Returns the transcript and final consensus.".to_string(),
+                    name: "read_tool_result".to_string(),
+                    description: "Read a long tool result that was stored instead of shown in full. Returns up to 6000 characters from the given offset and the offset to continue from.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "topic": {
+                            "result_id": {
                                 "type": "string",
-                                "description": "The topic or problem to debate"
+                                "description": "The stored_result id from the earlier tool result"
                             },
-                            "turns": {
+                            "offset": {
+                                "type": "integer",
+                                "description": "Character offset to start reading at (default 0)"
+                            },
+                            "length": {
                                 "type": "integer",
-                                "description": "Number of debate turns (default: 3)"
+                                "description": "How many characters to read (default and maximum 6000)"
                             }
                         },
-                        "required": ["topic"]
+                        "required": ["result_id"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "write_file_batch".to_string(),
-                    description: "Writes multiple files to the codebase at once. Use this for creating components, refactoring, or applying multi-file changes.".to_string(),
+                    name: "web_search".to_string(),
+                    description: "Search the web for current information using the configured search provider. Returns top search results with title, snippet, and URL.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "files": {
+                            "query": {
+                                "type": "string",
+                                "description": "Search query (e.g., 'latest AI news 2025', 'Tauri desktop app tutorial')"
+                            },
+                            "max_results": {
+                                "type": "integer",
+                                "description": "Maximum number of results to return (1-10, default: 5)"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "deep_research".to_string(),
+                    description: "Delegates a complex research task to a specialized Deep Research Agent. Use this for broad topics requiring synthesis of multiple sources. The report is saved to research/<topic>.md in the knowledge base and its findings are stored in the TKG.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "topic": {
+                                "type": "string",
+                                "description": "The main research topic or question"
+                            },
+                            "sub_topics": {
                                 "type": "array",
                                 "items": {
-                                    "type": "object",
-                                    "properties": {
-                                        "path": { "type": "string", "description": "Relative path to file" },
-                                        "content": { "type": "string", "description": "File content" }
-                                    },
-                                    "required": ["path", "content"]
+                                    "type": "string"
                                 },
-                                "description": "List of files to write"
+                                "description": "Optional list of sub-topics to research in parallel. If provided, multiple agents will be spawned."
+                            },
+                            "depth": {
+                                "type": "integer",
+                                "description": "Search rounds per topic (1-5, default: 1). Later rounds follow up on earlier findings."
+                            },
+                            "max_sources_per_round": {
+                                "type": "integer",
+                                "description": "Maximum sources gathered per round (1-20, default: 5)"
+                            },
+                            "max_api_calls": {
+                                "type": "integer",
+                                "description": "Maximum search API calls for the whole run, shared by all sub-topics (default: 10)"
+                            },
+                            "max_tokens": {
+                                "type": "integer",
+                                "description": "Token budget for gathered source text (default: 50000)"
+                            },
+                            "full_pages": {
+                                "type": "integer",
+                                "description": "Top sources to fetch and read in full instead of using search snippets (0-10, default: 3)"
+                            },
+                            "papers": {
+                                "type": "array",
+                                "items": {
+                                    "type": "string"
+                                },
+                                "description": "Optional arXiv ids/URLs, PDF URLs or knowledge base PDF paths to read in full as sources"
+                            },
+                            "template": {
+                                "type": "string",
+                                "enum": ["standard", "executive_brief", "literature_review", "comparison_matrix", "tutorial"],
+                                "description": "Report format (default: standard). Use the one the user asks for, e.g. a comparison matrix when comparing options."
                             }
                         },
-                        "required": ["files"]
+                        "required": ["topic"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "run_terminal_command".to_string(),
-                    description: "Executes a terminal command in the repository root. Use for running tests, builds, or git commands.".to_string(),
+                    name: "ingest_paper".to_string(),
+                    description: "Download and read an academic paper (arXiv id/URL, PDF URL, or PDF in the knowledge base). Returns its metadata, abstract and full text by section, and stores the sections in the knowledge graph.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "command": {
+                            "url_or_path": {
                                 "type": "string",
-                                "description": "The command to execute (e.g., 'npm test', 'cargo build')"
+                                "description": "arXiv id or URL (e.g. '1706.03762', 'https://arxiv.org/abs/1706.03762'), a PDF URL, or a knowledge base path to a PDF"
+                            },
+                            "store_in_tkg": {
+                                "type": "boolean",
+                                "description": "Store the abstract and sections in the knowledge graph (default: true)"
                             }
                         },
-                        "required": ["command"]
+                        "required": ["url_or_path"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "list_registered_agents".to_string(),
-                    description: "Lists all AI agents (Constructs) registered in the system. Returns their names, roles, and descriptions.".to_string(),
+                    name: "clip_url".to_string(),
+                    description: "Clip a web page: fetch the URL, extract its main content as clean markdown (keeping headings, images and links) and save it under research/clips/. Use it to keep an article the user wants to read or cite later.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
-                        "properties": {}
+                        "properties": {
+                            "url": {
+                                "type": "string",
+                                "description": "The page to clip"
+                            },
+                            "display": {
+                                "type": "boolean",
+                                "description": "Also show the clipped page in the canvas (default: false)"
+                            }
+                        },
+                        "required": ["url"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "invoke_agent".to_string(),
-                    description: "Retrieves the specialized system prompt and instructions for a specific registered agent. Use this to adopt the persona or expertise of a Construct.".to_string(),
+                    name: "capture_screenshot".to_string(),
+                    description: "Take a screenshot and read its text (OCR). Saves the image and a note with the text under research/screenshots/ and returns the text, so you can discuss what's on the user's screen. Without a region, the user drags one out when 'select' is true, otherwise the whole screen is taken.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "agent_id": {
+                            "region": {
+                                "type": "object",
+                                "description": "Screen region in pixels from the top-left of the main display",
+                                "properties": {
+                                    "x": { "type": "integer" },
+                                    "y": { "type": "integer" },
+                                    "width": { "type": "integer" },
+                                    "height": { "type": "integer" }
+                                },
+                                "required": ["x", "y", "width", "height"]
+                            },
+                            "select": {
+                                "type": "boolean",
+                                "description": "Let the user select the region with the mouse (default: true when no region is given)"
+                            },
+                            "ocr": {
                                 "type": "string",
-                                "description": "The unique ID of the agent to invoke (e.g., 'curriculum-architect-v1')"
+                                "enum": ["auto", "tesseract", "vision"],
+                                "description": "How to read the text: tesseract (local), vision (Grok's vision model) or auto, tesseract when installed (default)"
+                            },
+                            "languages": {
+                                "type": "string",
+                                "description": "tesseract languages like 'eng' or 'eng+deu' (default: eng)"
+                            }
+                        }
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "harvest_github".to_string(),
+                    description: "Harvest a GitHub repository's documentation: its README, the markdown files under docs/ and its release notes, saved under developer-reference/github/<owner>/<repo>/. Use it to study a library or tool the user works with. Private repositories need a GitHub token in Settings.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "repo": {
+                                "type": "string",
+                                "description": "The repository as owner/repo or its GitHub URL"
+                            },
+                            "include_docs": {
+                                "type": "boolean",
+                                "description": "Harvest the markdown under docs/ (default: true)"
+                            },
+                            "include_releases": {
+                                "type": "boolean",
+                                "description": "Harvest release notes (default: true)"
+                            },
+                            "max_docs": {
+                                "type": "integer",
+                                "description": "Max docs/ files to harvest (default: 50, max: 200)"
+                            },
+                            "max_releases": {
+                                "type": "integer",
+                                "description": "Max recent releases to include (default: 10, max: 100)"
                             }
                         },
-                        "required": ["agent_id"]
+                        "required": ["repo"]
                     }),
                 },
             },
             Tool {
                 tool_type: "function".to_string(),
                 function: ToolFunction {
-                    name: "consult_agent".to_string(),
-                    description: "Consult a specialized AI agent for expert input on a problem. Makes a separate API call to the agent and returns their analysis. Use this to get expert perspectives from registered Constructs.".to_string(),
+                    name: "harvest_youtube_transcript".to_string(),
+                    description: "Fetch a YouTube video's captions and save them as a timestamped transcript note under research/youtube/, optionally with a summary. Use it to research or cite what a video says.".to_string(),
                     parameters: serde_json::json!({
                         "type": "object",
                         "properties": {
-                            "agent_id": {
+                            "url": {
                                 "type": "string",
-                                "description": "The unique ID of the agent to consult (from list_registered_agents)"
+                                "description": "YouTube video URL or id"
                             },
-                            "agent_name": {
+                            "lang": {
                                 "type": "string",
-                                "description": "The display name of the agent to consult (from list_registered_agents)"
+                                "description": "Preferred caption language code (default: 'en'); other languages are used if it has none"
+                            },
+                            "summarize": {
+                                "type": "boolean",
+                                "description": "Add a summary of the video to the top of the note (default: false)"
+                            }
+                        },
+                        "required": ["url"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "write_file".to_string(),
+                    description: "Write/create a markdown file in the knowledge base. Can create new files or overwrite existing ones.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "path": {
+                                "type": "string",
+                                "description": "Relative path for the file (e.g., 'research/new-guide.md', 'dumps/notes.md')"
+                            },
+                            "content": {
+                                "type": "string",
+                                "description": "Content to write to the file"
+                            },
+                            "append": {
+                                "type": "boolean",
+                                "description": "If true, append to existing file. If false, overwrite. Default: false"
+                            },
+                            "expected_etag": {
+                                "type": "string",
+                                "description": "Etag returned by read_file. The write is rejected with 'merge_required' if the file changed since. Defaults to the etag of your last read of this file."
+                            }
+                        },
+                        "required": ["path", "content"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "display_media".to_string(),
+                    description: "Display media (video, image, or website) directly on the user's canvas. Use this after finding a relevant URL via web_search.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "url": {
+                                "type": "string",
+                                "description": "The URL of the media to display (e.g., YouTube link, image URL)"
                             },
+                            "type": {
+                                "type": "string",
+                                "enum": ["youtube", "image", "url", "html"],
+                                "description": "The type of media. Use 'youtube' for videos, 'image' for direct image links, 'url' for websites."
+                            }
+                        },
+                        "required": ["url", "type"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "harvest_wiki".to_string(),
+                    description: "Harvests an article from any MediaWiki wiki: the RuneScape (rs3) or Old School RuneScape (osrs) Wiki, Wikipedia, a Fandom wiki or any MediaWiki site by URL. Interwiki titles like 'de:Berlin' are followed to the wiki they point at. Saves the article as a markdown file in the research folder.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
                             "query": {
                                 "type": "string",
-                                "description": "The question or problem to consult them about"
+                                "description": "The topic to search for (e.g., 'Herblore', 'Zulrah')"
+                            },
+                            "wiki": {
+                                "type": "string",
+                                "description": "'rs3', 'osrs', 'wikipedia', 'fandom', or the URL of any MediaWiki site or its api.php (default: 'rs3')"
+                            },
+                            "lang": {
+                                "type": "string",
+                                "description": "Wikipedia language code when wiki is 'wikipedia' (default: 'en')"
+                            },
+                            "community": {
+                                "type": "string",
+                                "description": "Fandom community when wiki is 'fandom', e.g. 'starwars' for starwars.fandom.com"
+                            },
+                            "mode": {
+                                "type": "string",
+                                "enum": ["summary", "full"],
+                                "description": "Harvest mode: 'summary' (intro only) or 'full' (entire page). Default: 'full'"
                             }
                         },
                         "required": ["query"]
                     }),
                 },
             },
-        ]
-    }
-
-
-    /// Parse tool calls that are embedded in the assistant message text using
-    /// [TOOL]...[/TOOL] or [TOOL_CALL]...[/TOOL_CALL] blocks. Supports both the
-    /// legacy MiniMax arrow format and JSON-style payloads (objects, arrays, or concatenated).
-    fn parse_text_tool_calls(text: &str, base_call_index: usize) -> Vec<ToolCall> {
-        let mut parsed_calls: Vec<ToolCall> = Vec::new();
-
-        // Capture the inner content of any tool block (case-insensitive, multiline)
-        let block_regex =
-            Regex::new(r"(?is)\[(?:TOOL_CALL|TOOL)\]\s*(.*?)\s*\[/(?:TOOL_CALL|TOOL)\]").unwrap();
-
-        for cap in block_regex.captures_iter(text) {
-            let block = cap.get(1).map(|m| m.as_str().trim()).unwrap_or("");
-            
-            // Try to parse as JSON (supports multiple concatenated objects like "{} {}")
-            let mut json_parsed = false;
-            let deserializer = serde_json::Deserializer::from_str(block);
-            
-            for value_result in deserializer.into_iter::<serde_json::Value>() {
-                if let Ok(json_value) = value_result {
-                    json_parsed = true;
-                    
-                    // Helper to process a single tool call object
-                    let mut process_tool_obj = |obj: &serde_json::Value| {
-                        if let Some(name) = obj
-                            .get("tool")
-                            .or_else(|| obj.get("name"))
-                            .or_else(|| obj.get("function").and_then(|f| f.get("name")))
-                            .and_then(|n| n.as_str())
-                        {
-                            let args_val = obj
-                                .get("args")
-                                .or_else(|| obj.get("arguments"))
-                                .or_else(|| obj.get("function").and_then(|f| f.get("arguments")));
-
-                            let arguments = match args_val {
-                                Some(val) if val.is_object() || val.is_array() => {
-                                    serde_json::to_string(val).unwrap_or_else(|_| "{}".to_string())
-                                }
-                                Some(serde_json::Value::String(s)) => s.to_string(),
-                                Some(val) => val.to_string(),
-                                None => "{}".to_string(),
-                            };
-
-                            parsed_calls.push(ToolCall {
-                                id: format!("call_{}", base_call_index + parsed_calls.len()),
-                                tool_type: "function".to_string(),
-                                function: FunctionCall {
-                                    name: name.to_string(),
-                                    arguments,
-                                },
-                            });
-                        }
-                    };
-
-                    // Handle both Array of calls and Single call object
-                    if let Some(array) = json_value.as_array() {
-                        for item in array {
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "harvest_wiki_category".to_string(),
+                    description: "Mass harvest all pages in a specific Wiki category (e.g., 'Quests', 'Herblore'). Saves each page as a separate markdown file. An interrupted harvest resumes where it left off.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "category": {
+                                "type": "string",
+                                "description": "The category name (e.g., 'Quests', 'Farming_training')"
+                            },
+                            "wiki": {
+                                "type": "string",
+                                "description": "'rs3', 'osrs', 'wikipedia', 'fandom', or the URL of any MediaWiki site or its api.php (default: 'rs3')"
+                            },
+                            "lang": {
+                                "type": "string",
+                                "description": "Wikipedia language code when wiki is 'wikipedia' (default: 'en')"
+                            },
+                            "community": {
+                                "type": "string",
+                                "description": "Fandom community when wiki is 'fandom', e.g. 'starwars' for starwars.fandom.com"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Max pages to harvest (default: 10, max: 500)"
+                            },
+                            "concurrency": {
+                                "type": "integer",
+                                "description": "Pages fetched at a time (default: 2, max: 8)"
+                            },
+                            "delay_ms": {
+                                "type": "integer",
+                                "description": "Pause before each page request in milliseconds (default: 200)"
+                            },
+                            "resume": {
+                                "type": "boolean",
+                                "description": "Continue an interrupted harvest of this category, skipping pages already saved (default: true)"
+                            }
+                        },
+                        "required": ["category"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "brainstorm_with_grok".to_string(),
+                    description: "Get a second perspective from Grok-4 for brainstorming, creative ideas, or alternative viewpoints. Returns Grok's response to enhance your thinking.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Question or topic to get a second perspective on from Grok"
+                            },
+                            "context": {
+                                "type": "string",
+                                "description": "Additional context or background information"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "tkg_search".to_string(),
+                    description: "Search the Temporal Knowledge Graph for semantically similar knowledge and memories. Uses vector embeddings to find related information from past conversations and learning.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "query": {
+                                "type": "string",
+                                "description": "Search query to find semantically related knowledge"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum number of results to return (1-20, default: 5)"
+                            },
+                            "trust_threshold": {
+                                "type": "number",
+                                "description": "Minimum trust score for results (0.0-1.0, default: 0.5)"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "tkg_store".to_string(),
+                    description: "Store important knowledge in the Temporal Knowledge Graph for future semantic search. Preserves memories with embeddings for context-aware retrieval.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "content": {
+                                "type": "string",
+                                "description": "The knowledge or memory to store"
+                            },
+                            "node_type": {
+                                "type": "string",
+                                "enum": ["FACT", "CONCEPT", "MEMORY", "LEARNING", "INSIGHT", "USER_INPUT", "AI_RESPONSE"],
+                                "description": "Type of knowledge node"
+                            },
+                            "importance": {
+                                "type": "number",
+                                "description": "Importance score (0.0-1.0)"
+                            }
+                        },
+                        "required": ["content", "node_type"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "claim_legacy_data".to_string(),
+                    description: "Migrate past memories/knowledge from 'guest' sessions to the current user. Defaults to dry-run unless explicitly confirmed to prevent accidental destructive migrations.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "dry_run": {
+                                "type": "boolean",
+                                "description": "If true, only reports how many legacy points would be migrated (default: true)."
+                            },
+                            "confirm": {
+                                "type": "boolean",
+                                "description": "Set true to actually perform the migration. If omitted/false, the tool will not modify any data."
+                            }
+                        },
+                        "required": []
+                    }),
+                },
+            },
+
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "start_debate".to_string(),
+                    description: "Starts a multi-agent debate on a topic. By default an Architect and a Critic discuss and refine a solution; registered Constructs can debate instead, with an optional moderator. Returns the transcript and final consensus.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "topic": {
+                                "type": "string",
+                                "description": "The topic or problem to debate"
+                            },
+                            "turns": {
+                                "type": "integer",
+                                "description": "Maximum number of debate rounds (default: 3)"
+                            },
+                            "participants": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Construct ids of the debaters, in speaking order (at least two)"
+                            },
+                            "moderator": {
+                                "type": "string",
+                                "description": "Construct id of a moderator who summarizes each round and writes the consensus"
+                            },
+                            "until": {
+                                "type": "string",
+                                "description": "Condition that ends the debate early, e.g. 'output contains \"CONSENSUS REACHED\"'"
+                            }
+                        },
+                        "required": ["topic"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "write_file_batch".to_string(),
+                    description: "Writes multiple files to the codebase at once. Use this for creating components, refactoring, or applying multi-file changes.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "files": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "path": { "type": "string", "description": "Relative path to file" },
+                                        "content": { "type": "string", "description": "File content" },
+                                        "expected_etag": { "type": "string", "description": "Etag from read_file; rejects the write if the file changed since" }
+                                    },
+                                    "required": ["path", "content"]
+                                },
+                                "description": "List of files to write"
+                            }
+                        },
+                        "required": ["files"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "run_terminal_command".to_string(),
+                    description: "Executes a terminal command in the repository root. Use for running tests, builds, or git commands.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "command": {
+                                "type": "string",
+                                "description": "The command to execute (e.g., 'npm test', 'cargo build')"
+                            }
+                        },
+                        "required": ["command"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "list_registered_agents".to_string(),
+                    description: "Lists all AI agents (Constructs) registered in the system. Returns their names, roles, and descriptions.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {}
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "invoke_agent".to_string(),
+                    description: "Retrieves the specialized system prompt and instructions for a specific registered agent. Use this to adopt the persona or expertise of a Construct.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "agent_id": {
+                                "type": "string",
+                                "description": "The unique ID of the agent to invoke (e.g., 'curriculum-architect-v1')"
+                            }
+                        },
+                        "required": ["agent_id"]
+                    }),
+                },
+            },
+            Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: "consult_agent".to_string(),
+                    description: "Consult a specialized AI agent for expert input on a problem. Makes a separate API call to the agent and returns their analysis. Use this to get expert perspectives from registered Constructs.".to_string(),
+                    parameters: serde_json::json!({
+                        "type": "object",
+                        "properties": {
+                            "agent_id": {
+                                "type": "string",
+                                "description": "The unique ID of the agent to consult (from list_registered_agents)"
+                            },
+                            "agent_name": {
+                                "type": "string",
+                                "description": "The display name of the agent to consult (from list_registered_agents)"
+                            },
+                            "query": {
+                                "type": "string",
+                                "description": "The question or problem to consult them about"
+                            }
+                        },
+                        "required": ["query"]
+                    }),
+                },
+            },
+        ]
+    }
+
+
+    /// Parse tool calls that are embedded in the assistant message text using
+    /// [TOOL]...[/TOOL] or [TOOL_CALL]...[/TOOL_CALL] blocks. Supports both the
+    /// legacy MiniMax arrow format and JSON-style payloads (objects, arrays, or concatenated).
+    fn parse_text_tool_calls(text: &str, base_call_index: usize) -> Vec<ToolCall> {
+        let mut parsed_calls: Vec<ToolCall> = Vec::new();
+
+        // Capture the inner content of any tool block (case-insensitive, multiline)
+        let block_regex =
+            Regex::new(r"(?is)\[(?:TOOL_CALL|TOOL)\]\s*(.*?)\s*\[/(?:TOOL_CALL|TOOL)\]").unwrap();
+
+        for cap in block_regex.captures_iter(text) {
+            let block = cap.get(1).map(|m| m.as_str().trim()).unwrap_or("");
+            
+            // Try to parse as JSON (supports multiple concatenated objects like "{} {}")
+            let mut json_parsed = false;
+            let deserializer = serde_json::Deserializer::from_str(block);
+            
+            for value_result in deserializer.into_iter::<serde_json::Value>() {
+                if let Ok(json_value) = value_result {
+                    json_parsed = true;
+                    
+                    // Helper to process a single tool call object
+                    let mut process_tool_obj = |obj: &serde_json::Value| {
+                        if let Some(name) = obj
+                            .get("tool")
+                            .or_else(|| obj.get("name"))
+                            .or_else(|| obj.get("function").and_then(|f| f.get("name")))
+                            .and_then(|n| n.as_str())
+                        {
+                            let args_val = obj
+                                .get("args")
+                                .or_else(|| obj.get("arguments"))
+                                .or_else(|| obj.get("function").and_then(|f| f.get("arguments")));
+
+                            let arguments = match args_val {
+                                Some(val) if val.is_object() || val.is_array() => {
+                                    serde_json::to_string(val).unwrap_or_else(|_| "{}".to_string())
+                                }
+                                Some(serde_json::Value::String(s)) => s.to_string(),
+                                Some(val) => val.to_string(),
+                                None => "{}".to_string(),
+                            };
+
+                            parsed_calls.push(ToolCall {
+                                id: format!("call_{}", base_call_index + parsed_calls.len()),
+                                tool_type: "function".to_string(),
+                                function: FunctionCall {
+                                    name: name.to_string(),
+                                    arguments,
+                                },
+                            });
+                        }
+                    };
+
+                    // Handle both Array of calls and Single call object
+                    if let Some(array) = json_value.as_array() {
+                        for item in array {
                             process_tool_obj(item);
                         }
-                    } else {
-                        process_tool_obj(&json_value);
+                    } else {
+                        process_tool_obj(&json_value);
+                    }
+                } else {
+                    // Stop if we hit invalid JSON
+                    break;
+                }
+            }
+
+            // If we successfully parsed at least one JSON object, we assume this block was JSON
+            // and don't try legacy parsing.
+            if json_parsed {
+                continue;
+            }
+
+            // Legacy MiniMax format: tool => "name", args => { key => "value" }
+            let name_regex =
+                Regex::new(r#"(?i)(?:tool|name)\s*(?:=>|:)\s*"([^"]+)""#).unwrap();
+            let args_block_regex =
+                Regex::new(r#"(?is)(?:args|arguments)\s*(?:=>|:)\s*(\{.*?\})"#).unwrap();
+
+            if let Some(name_caps) = name_regex.captures(block) {
+                let tool_name = name_caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+                let args_text = args_block_regex
+                    .captures(block)
+                    .and_then(|c| c.get(1))
+                    .map(|m| m.as_str())
+                    .unwrap_or("{}");
+
+                let arguments = if let Ok(json_args) =
+                    serde_json::from_str::<serde_json::Value>(args_text)
+                {
+                    serde_json::to_string(&json_args).unwrap_or_else(|_| "{}".to_string())
+                } else {
+                    // Parse key => "value" pairs into a JSON object string
+                    let mut args_map: HashMap<String, String> = HashMap::new();
+                    let arg_regex = Regex::new(r#"(\w+)\s*(?:=>|:)\s*"([^"]*)""#).unwrap();
+                    for arg_cap in arg_regex.captures_iter(args_text) {
+                        if let (Some(key), Some(value)) = (arg_cap.get(1), arg_cap.get(2)) {
+                            args_map.insert(key.as_str().to_string(), value.as_str().to_string());
+                        }
+                    }
+                    serde_json::to_string(&args_map).unwrap_or_else(|_| "{}".to_string())
+                };
+
+                parsed_calls.push(ToolCall {
+                    id: format!("call_{}", base_call_index + parsed_calls.len()),
+                    tool_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: tool_name.to_string(),
+                        arguments,
+                    },
+                });
+            }
+        }
+
+        // If we found calls using tags, return them
+        if !parsed_calls.is_empty() {
+            return parsed_calls;
+        }
+
+        // FALLBACK: Try to find raw JSON tool calls without tags
+        // Look for JSON objects that contain "tool": "name"
+        // Since regex is bad at nested braces, we'll use a manual brace counter
+        let tool_key_regex = Regex::new(r#""tool"\s*:\s*""#).unwrap();
+        
+        // Find all potential start positions of JSON objects containing "tool":
+        for mat in tool_key_regex.find_iter(text) {
+            // Search backwards for the opening brace '{'
+            let mut start_index = mat.start();
+            let mut found_start = false;
+            while start_index > 0 {
+                start_index -= 1;
+                if text.as_bytes()[start_index] == b'{' {
+                    found_start = true;
+                    break;
+                }
+                // Stop if we hit a closing brace or another object end, to avoid over-reaching
+                if text.as_bytes()[start_index] == b'}' {
+                    break;
+                }
+            }
+
+            if found_start {
+                // Now scan forward to find the matching closing brace
+                let mut brace_count = 0;
+                let mut in_string = false;
+                let mut escape = false;
+                let mut end_index = 0;
+                let mut found_end = false;
+
+                for (i, c) in text[start_index..].char_indices() {
+                    if escape {
+                        escape = false;
+                        continue;
+                    }
+                    
+                    match c {
+                        '\\' => escape = true,
+                        '"' => in_string = !in_string,
+                        '{' if !in_string => brace_count += 1,
+                        '}' if !in_string => {
+                            brace_count -= 1;
+                            if brace_count == 0 {
+                                end_index = start_index + i + 1;
+                                found_end = true;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                if found_end {
+                    let block_str = &text[start_index..end_index];
+                    
+                    // Try to parse this block as JSON
+                    if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(block_str) {
+                        // Check if it looks like a tool call
+                        if let Some(name) = json_value.get("tool").and_then(|n| n.as_str()) {
+                            // Avoid duplicates if we already parsed this one (simple check)
+                            if parsed_calls.iter().any(|c| c.function.name == name && c.function.arguments.contains(&name)) {
+                                continue;
+                            }
+
+                            let args_val = json_value.get("arguments").or_else(|| json_value.get("args"));
+                            
+                            let arguments = match args_val {
+                                Some(val) if val.is_object() || val.is_array() => {
+                                    serde_json::to_string(val).unwrap_or_else(|_| "{}".to_string())
+                                }
+                                Some(serde_json::Value::String(s)) => s.to_string(),
+                                Some(val) => val.to_string(),
+                                None => "{}".to_string(),
+                            };
+
+                            parsed_calls.push(ToolCall {
+                                id: format!("call_{}", base_call_index + parsed_calls.len()),
+                                tool_type: "function".to_string(),
+                                function: FunctionCall {
+                                    name: name.to_string(),
+                                    arguments,
+                                },
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        parsed_calls
+    }
+
+    /// Parse Grok's XML-style tool calls (e.g. <tool_code>...</tool_code>)
+    fn parse_grok_xml_tools(text: &str, base_call_index: usize) -> Vec<ToolCall> {
+        let mut parsed_calls: Vec<ToolCall> = Vec::new();
+        
+        // Regex for <tool_code>...</tool_code> (dot matches newline)
+        let xml_regex = Regex::new(r"(?is)<tool_code>\s*(.*?)\s*</tool_code>").unwrap();
+
+        for cap in xml_regex.captures_iter(text) {
+            if let Some(content) = cap.get(1).map(|m| m.as_str().trim()) {
+                tracing::debug!("🔍 Found potential XML tool block: {}", content);
+                // Try to parse the content as JSON
+                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(content) {
+                    // Helper to extract tool info from JSON object
+                    let process_obj = |obj: &serde_json::Value, index: usize| -> Option<ToolCall> {
+                        let name = obj.get("name")
+                            .or_else(|| obj.get("tool_name"))
+                            .or_else(|| obj.get("function").and_then(|f| f.get("name")))?
+                            .as_str()?;
+                        
+                        let args = obj.get("arguments")
+                            .or_else(|| obj.get("args"))
+                            .or_else(|| obj.get("parameters"))
+                            .or_else(|| obj.get("function").and_then(|f| f.get("arguments")));
+
+                        let arguments_str = match args {
+                            Some(val) if val.is_object() || val.is_array() => serde_json::to_string(val).unwrap_or_default(),
+                            Some(serde_json::Value::String(s)) => s.to_string(),
+                            _ => "{}".to_string()
+                        };
+
+                        Some(ToolCall {
+                            id: format!("call_{}_{}", base_call_index, index),
+                            tool_type: "function".to_string(),
+                            function: FunctionCall {
+                                name: name.to_string(),
+                                arguments: arguments_str,
+                            },
+                        })
+                    };
+
+                    if let Some(array) = json_val.as_array() {
+                        for item in array {
+                            let current_len = parsed_calls.len();
+                            if let Some(call) = process_obj(item, current_len) {
+                                parsed_calls.push(call);
+                            }
+                        }
+                    } else {
+                        let current_len = parsed_calls.len();
+                        if let Some(call) = process_obj(&json_val, current_len) {
+                            parsed_calls.push(call);
+                        }
+                    }
+                }
+            }
+        }
+        
+        parsed_calls
+    }
+
+    pub fn add_user_message(&mut self, content: String) {
+        self.conversation_history.push(Message {
+            role: "user".to_string(),
+            content,
+            tool_calls: None,
+            tool_call_id: None,
+            timestamp: Some(Self::get_current_timestamp()),
+        });
+    }
+
+    pub fn get_conversation_history(&self) -> &Vec<Message> {
+        &self.conversation_history
+    }
+
+    pub fn clear_history(&mut self) {
+        self.conversation_history.clear();
+    }
+
+    /// Estimate token count (rough heuristic: 4 chars = 1 token)
+    fn estimate_tokens(&self) -> usize {
+        let mut chars = 0;
+        for msg in &self.conversation_history {
+            chars += msg.content.len();
+            if let Some(tools) = &msg.tool_calls {
+                for tool in tools {
+                    chars += tool.function.arguments.len();
+                }
+            }
+        }
+        chars / 4
+    }
+
+    /// Prune history if it exceeds token limit
+    fn prune_history(&mut self) {
+        const MAX_TOKENS: usize = 90_000; // Leave buffer for response
+        const MIN_MESSAGES: usize = 10;   // Always keep last N messages
+
+        let current_tokens = self.estimate_tokens();
+        if current_tokens > MAX_TOKENS {
+            tracing::info!("✂️ Context too large ({} tokens), pruning...", current_tokens);
+            
+            let mut removed_count = 0;
+            while self.estimate_tokens() > MAX_TOKENS && self.conversation_history.len() > MIN_MESSAGES {
+                // Remove from front (oldest), but be careful not to break tool chains if possible
+                // For simplicity, just remove oldest
+                self.conversation_history.remove(0);
+                removed_count += 1;
+            }
+            tracing::info!("✂️ Pruned {} messages. New token count: {}", removed_count, self.estimate_tokens());
+        }
+    }
+
+    /// Get the knowledge base root directory
+    /// Dev Mode: Repository root
+    /// Prod Mode: User Documents/KnowledgeCompanion
+    pub fn get_knowledge_base_path() -> Result<PathBuf, String> {
+        // 0. An explicit root in thinkspace.toml wins, then the profile's own folder
+        if let Some(root) = crate::config::knowledge_base_root().or_else(crate::profiles::fallback_kb_root) {
+            return Ok(root);
+        }
+
+        let current = std::env::current_dir().map_err(|e| e.to_string())?;
+
+        // 1. Check for Dev Environment (src-tauri or project root)
+        if current.file_name().and_then(|n| n.to_str()) == Some("src-tauri") {
+            return current.parent()
+                .and_then(|p| p.parent())
+                .ok_or("Could not find repository root from src-tauri".to_string())
+                .map(|p| p.to_path_buf());
+        } else if current.file_name().and_then(|n| n.to_str()) == Some("startup-strategy-app") {
+            return current.parent()
+                .ok_or("Could not find parent directory".to_string())
+                .map(|p| p.to_path_buf());
+        }
+
+        // 2. Production Mode: Use User Documents
+        let user_dirs = directories::UserDirs::new()
+            .ok_or("Could not find user directories".to_string())?;
+        
+        let doc_dir = user_dirs.document_dir()
+            .ok_or("Could not find Documents directory".to_string())?;
+            
+        let kb_root = doc_dir.join("KnowledgeCompanion");
+
+        // 3. Ensure structure exists
+        let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides"];
+        for folder in folders {
+            let p = kb_root.join(folder);
+            if !p.exists() {
+                let _ = std::fs::create_dir_all(&p);
+            }
+        }
+
+        Ok(kb_root)
+    }
+
+    /// Execute a tool and return result as JSON string
+    fn execute_tool(&self, tool_name: &str, arguments: &str) -> String {
+        if let Err(e) = crate::permissions::check(&self.grant_layers(), tool_name, arguments) {
+            tracing::warn!("🔐 {}", e);
+            return serde_json::json!({
+                "success": false,
+                "error": e
+            }).to_string();
+        }
+        // Everything logged while the tool runs carries its name
+        let _span = tracing::info_span!("tool", tool = tool_name).entered();
+        let started = std::time::Instant::now();
+        tracing::info!("🔧 Executing tool: {}", tool_name);
+        tracing::debug!("📝 Arguments: {}", arguments);
+
+        let result = match tool_name {
+            "scan_codebase" => self.tool_scan_codebase(arguments),
+            "find_symbol" => self.tool_find_symbol(arguments),
+            "grep_codebase" => self.tool_grep_codebase(arguments),
+            "get_repo_stats" => self.tool_get_repo_stats(arguments),
+            "start_debate" => self.tool_start_debate(arguments),
+            "write_file_batch" => self.tool_write_file_batch(arguments),
+            "run_terminal_command" => self.tool_run_terminal_command(arguments),
+            "calculate" => self.tool_calculate(arguments),
+            "read_file" => self.tool_read_file(arguments),
+            "read_pdf" => self.tool_read_pdf(arguments),
+            "search_knowledge" => self.tool_search_knowledge(arguments),
+            "get_note_links" => self.tool_get_note_links(arguments),
+            "create_flashcards" => self.tool_create_flashcards(arguments),
+            "notify" => self.tool_notify(arguments),
+            "canvas_update" => serde_json::Value::String(self.tool_canvas_update(arguments)),
+            "list_registered_agents" => self.tool_list_registered_agents(arguments),
+            "invoke_agent" => self.tool_invoke_agent(arguments),
+            "create_study_guide" => {
+                let grok_api_key = self.grok_api_key.clone();
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_create_study_guide_async(args_str, grok_api_key))
+                })
+            }
+            "list_markdown_files" => self.tool_list_markdown_files(arguments),
+            "read_tool_result" => self.tool_read_tool_result(arguments),
+            "web_search" => {
+                // For async tools, we need to use a blocking call in a runtime
+                let tavily_api_key = self.tavily_api_key.clone();
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_web_search_async(args_str, tavily_api_key))
+                })
+            }
+            "write_file" => self.tool_write_file(arguments),
+            "display_media" => self.tool_display_media(arguments),
+            "brainstorm_with_grok" => {
+                // For async Grok calls
+                let grok_api_key = self.grok_api_key.clone();
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_brainstorm_with_grok_async(args_str, grok_api_key))
+                })
+            }
+            "harvest_wiki" => {
+                // Async Wiki Harvest
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_harvest_wiki_async(args_str))
+                })
+            }
+            "harvest_wiki_category" => {
+                // Async Wiki Category Harvest
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_harvest_wiki_category_async(args_str))
+                })
+            }
+
+            "tkg_search" => {
+                // Search the Temporal Knowledge Graph
+                let args_str = arguments.to_string();
+                let user_id = self.user_id.clone();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(self.tool_tkg_search_async(args_str, user_id))
+                })
+            }
+            "log_daily_note" => {
+                let entry = serde_json::from_str::<serde_json::Value>(arguments)
+                    .ok()
+                    .and_then(|v| v.get("entry").and_then(|e| e.as_str()).map(|e| e.to_string()))
+                    .unwrap_or_default();
+                let user_id = self.user_id.clone();
+                let app_handle = self.app_handle.clone();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            crate::daily_notes::log_entry(app_handle.as_ref(), &entry, &user_id).await
+                        })
+                })
+                .unwrap_or_else(|e| serde_json::json!({
+                    "success": false,
+                    "error": e
+                }))
+            }
+            "tkg_store" => {
+                // Store knowledge in the Temporal Knowledge Graph
+                let args = arguments.to_string();
+                let user_id = self.user_id.clone();
+                let result = tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_tkg_store_async(args, user_id).await
+                        })
+                });
+                result
+            }
+            "deep_research" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_deep_research(&args_str).await
+                        })
+                })
+            }
+            "ingest_paper" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_ingest_paper(&args_str).await
+                        })
+                })
+            }
+            "clip_url" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_clip_url(&args_str).await
+                        })
+                })
+            }
+            "translate" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_translate(&args_str).await
+                        })
+                })
+            }
+            "capture_screenshot" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_capture_screenshot(&args_str).await
+                        })
+                })
+            }
+            "harvest_github" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_harvest_github(&args_str).await
+                        })
+                })
+            }
+            "harvest_youtube_transcript" => {
+                let args_str = arguments.to_string();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            self.tool_harvest_youtube_transcript(&args_str).await
+                        })
+                })
+            }
+            "consult_agent" => {
+                // Consult a specialized agent and get their expert response
+                let api_key = self.api_key.clone();
+                let grok_api_key = self.grok_api_key.clone();
+                let gemini_api_key = self.gemini_api_key.clone();
+                let default_provider = match self.provider {
+                    AIProvider::Grok => "grok",
+                    AIProvider::Gemini => "gemini",
+                    AIProvider::Minimax => "minimax",
+                }
+                .to_string();
+                let args_str = arguments.to_string();
+                let registry_data = self.load_agents_registry();
+                let http = self.http.clone();
+
+                tokio::task::block_in_place(|| {
+                    let registry_data = registry_data.clone();
+                    let default_provider = default_provider.clone();
+                    tokio::runtime::Runtime::new()
+                        .unwrap()
+                        .block_on(async move {
+                            let args: Result<serde_json::Value, _> = serde_json::from_str(&args_str);
+                            match args {
+                                Ok(args) => {
+                                    let agent_id_arg = args.get("agent_id").and_then(|v| v.as_str()).map(|id| id.to_string());
+                                    let agent_name_arg = args.get("agent_name").and_then(|v| v.as_str()).map(|name| name.to_string());
+
+                                    if agent_id_arg.is_none() && agent_name_arg.is_none() {
+                                        return serde_json::json!({
+                                            "success": false,
+                                            "error": "Missing 'agent_id' or 'agent_name' argument"
+                                        });
+                                    }
+
+                                    let query = match args.get("query").and_then(|v| v.as_str()) {
+                                        Some(q) => q.to_string(),
+                                        None => return serde_json::json!({
+                                            "success": false,
+                                            "error": "Missing 'query' argument"
+                                        })
+                                    };
+
+                                    let agent_label = agent_id_arg.clone().or_else(|| agent_name_arg.clone()).unwrap_or_else(|| "unknown".to_string());
+                                    tracing::info!("🤖 Consulting agent: {}", agent_label);
+
+                                    let data = match registry_data {
+                                        Ok(data) => data,
+                                        Err(e) => {
+                                            return serde_json::json!({
+                                                "success": false,
+                                                "error": e
+                                            });
+                                        }
+                                    };
+
+                                    let agents = match data.get("agents").and_then(|v| v.as_array()) {
+                                        Some(agents) => agents,
+                                        None => {
+                                            return serde_json::json!({
+                                                "success": false,
+                                                "error": "No agents array in registry"
+                                            });
+                                        }
+                                    };
+
+                                    let mut agent = None;
+                                    if let Some(agent_id) = agent_id_arg.as_deref() {
+                                        agent = agents.iter().find(|a| a.get("id").and_then(|v| v.as_str()) == Some(agent_id));
+                                    }
+                                    if agent.is_none() {
+                                        if let Some(agent_name) = agent_name_arg.as_deref() {
+                                            agent = agents.iter().find(|a| {
+                                                a.get("name")
+                                                    .and_then(|v| v.as_str())
+                                                    .map(|name| name.eq_ignore_ascii_case(agent_name))
+                                                    .unwrap_or(false)
+                                            });
+                                        }
+                                    }
+
+                                    let agent = match agent {
+                                        Some(agent) => agent,
+                                        None => {
+                                            return serde_json::json!({
+                                                "success": false,
+                                                "error": format!("Agent '{}' not found in registry", agent_label)
+                                            });
+                                        }
+                                    };
+
+                                    let agent_id = agent.get("id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+                                    let agent_name = agent.get("name").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string();
+                                    let provider = agent
+                                        .get("preferredProvider")
+                                        .and_then(|v| v.as_str())
+                                        .unwrap_or(&default_provider)
+                                        .to_string();
+                                    let system_prompt = agent.get("systemPrompt").and_then(|v| v.as_str()).unwrap_or("You are a helpful assistant.").to_string();
+
+                                    tracing::info!("📋 Agent: {} | Provider: {}", agent_name, provider);
+
+                                    // Make API call based on provider
+                                    let client = http.client();
+
+                                    let (url, auth_header, payload) = if provider == "grok" {
+                                        let key = grok_api_key.clone().unwrap_or_default();
+                                        (
+                                            "https://api.x.ai/v1/chat/completions".to_string(),
+                                            format!("Bearer {}", key),
+                                            serde_json::json!({
+                                                "model": "grok-4-1-fast",
+                                                "messages": [
+                                                    {"role": "system", "content": system_prompt},
+                                                    {"role": "user", "content": query}
+                                                ],
+                                                "max_tokens": 4096,
+                                                "temperature": 0.7
+                                            })
+                                        )
+                                    } else if provider == "gemini" {
+                                        let key = gemini_api_key.clone().unwrap_or_default();
+                                        (
+                                            format!("https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}", key),
+                                            "".to_string(),
+                                            serde_json::json!({
+                                                "contents": [
+                                                    {"role": "user", "parts": [{"text": format!("{}\n\nUser Query: {}", system_prompt, query)}]}
+                                                ]
+                                            })
+                                        )
+                                    } else {
+                                        // Default to MiniMax
+                                        (
+                                            "https://api.minimax.io/v1/chat/completions".to_string(),
+                                            format!("Bearer {}", api_key),
+                                            serde_json::json!({
+                                                "model": "MiniMax-M2",
+                                                "messages": [
+                                                    {"role": "system", "content": system_prompt},
+                                                    {"role": "user", "content": query}
+                                                ],
+                                                "max_tokens": 4096,
+                                                "temperature": 0.7
+                                            })
+                                        )
+                                    };
+
+                                    let mut request = client.post(&url)
+                                        .header("Content-Type", "application/json")
+                                        .json(&payload);
+
+                                    if !auth_header.is_empty() {
+                                        request = request.header("Authorization", auth_header);
+                                    }
+
+                                    match request.send_checked().await {
+                                        Ok(response) => {
+                                            if response.status().is_success() {
+                                                match response.json::<serde_json::Value>().await {
+                                                    Ok(result) => {
+                                                        // Extract response based on provider format
+                                                        let content = if provider == "gemini" {
+                                                            result.get("candidates")
+                                                                .and_then(|c| c.as_array())
+                                                                .and_then(|c| c.first())
+                                                                .and_then(|c| c.get("content"))
+                                                                .and_then(|c| c.get("parts"))
+                                                                .and_then(|p| p.as_array())
+                                                                .and_then(|p| p.first())
+                                                                .and_then(|p| p.get("text"))
+                                                                .and_then(|t| t.as_str())
+                                                                .unwrap_or("No response")
+                                                                .to_string()
+                                                        } else {
+                                                            result.get("choices")
+                                                                .and_then(|c| c.as_array())
+                                                                .and_then(|c| c.first())
+                                                                .and_then(|c| c.get("message"))
+                                                                .and_then(|m| m.get("content"))
+                                                                .and_then(|c| c.as_str())
+                                                                .unwrap_or("No response")
+                                                                .to_string()
+                                                        };
+
+                                                        tracing::info!("✅ Agent consultation complete");
+
+                                                        serde_json::json!({
+                                                            "success": true,
+                                                            "agent_id": agent_id,
+                                                            "agent_name": agent_name,
+                                                            "provider": provider,
+                                                            "response": content
+                                                        })
+                                                    }
+                                                    Err(e) => serde_json::json!({
+                                                        "success": false,
+                                                        "error": format!("Failed to parse response: {}", e)
+                                                    })
+                                                }
+                                            } else {
+                                                let error_text = response.text().await.unwrap_or_default();
+                                                serde_json::json!({
+                                                    "success": false,
+                                                    "error": format!("API error: {}", error_text)
+                                                })
+                                            }
+                                        }
+                                        Err(e) => serde_json::json!({
+                                            "success": false,
+                                            "error": format!("Request failed: {}", e)
+                                        })
+                                    }
+                                }
+                                Err(e) => serde_json::json!({
+                                    "success": false,
+                                    "error": format!("Invalid arguments: {}", e)
+                                })
+                            }
+                        })
+                })
+            }
+            _ => serde_json::json!({
+                "error": format!("Unknown tool: {}", tool_name)
+            }),
+        };
+
+        let succeeded = result.get("success").and_then(|v| v.as_bool()).unwrap_or(true);
+        tracing::info!(elapsed_ms = started.elapsed().as_millis() as u64, succeeded, "🔧 {} finished", tool_name);
+        tracing::debug!("✅ Result: {}", result);
+        result.to_string()
+    }
+
+    /// Async version of tool_web_search
+    async fn tool_web_search_async(&self, arguments: String, tavily_api_key: Option<String>) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+
+        match args {
+            Ok(args) => {
+                if let Some(query_val) = args.get("query") {
+                    let query = query_val.as_str().unwrap_or("");
+                    let max_results = args.get("max_results")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(5)
+                        .min(10); // Cap at 10 results
+
+                    let settings = crate::search_providers::load_search_settings(self.app_handle.as_ref());
+                    let provider = match settings.provider(tavily_api_key, false) {
+                        Ok(provider) => provider,
+                        Err(e) => {
+                            tracing::warn!("⚠️ {}", e);
+                            return serde_json::json!({
+                                "success": false,
+                                "error": e
+                            });
+                        }
+                    };
+
+                    tracing::info!("🔍 Searching web ({}) for: {}", provider.name(), query);
+
+                    match provider.search(query, max_results as usize).instrument(tracing::info_span!("search", kind = provider.name())).await {
+                        Ok(response) => {
+                            tracing::info!("✅ Web search successful");
+
+                            let results = response.results
+                                .iter()
+                                .filter_map(|r| {
+                                    serde_json::to_string(&serde_json::json!({
+                                        "title": r.title,
+                                        "url": r.url,
+                                        "snippet": r.content,
+                                        "published_date": r.published_date
+                                    })).ok()
+                                })
+                                .collect::<Vec<String>>();
+
+                            serde_json::json!({
+                                "success": true,
+                                "query": query,
+                                "provider": provider.name(),
+                                "answer": response.answer.unwrap_or_default(),
+                                "results": results,
+                                "count": results.len()
+                            })
+                        }
+                        Err(e) => serde_json::json!({
+                            "success": false,
+                            "error": format!("{:#}", e)
+                        })
                     }
                 } else {
-                    // Stop if we hit invalid JSON
-                    break;
+                    serde_json::json!({
+                        "success": false,
+                        "error": "Missing 'query' argument"
+                    })
+                }
+            }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        }
+    }
+
+    /// TKG Search - Search semantic memory in Temporal Knowledge Graph
+    async fn tool_tkg_search_async(&self, arguments: String, user_id: String) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+
+        match args {
+            Ok(args) => {
+                if let Some(query_val) = args.get("query") {
+                    let query = query_val.as_str().unwrap_or("");
+                    let limit = args.get("limit")
+                        .and_then(|v| v.as_u64())
+                        .unwrap_or(5)
+                        .min(20);
+                    let _trust_threshold = args.get("trust_threshold")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.5);
+
+                    // Call TKG search
+                    match tkg::tkg_search_similar(query.to_string(), limit, user_id).await {
+                        Ok(result_str) => {
+                            match serde_json::from_str(&result_str) {
+                                Ok(result_json) => result_json,
+                                Err(_) => serde_json::json!({
+                                    "success": false,
+                                    "error": "Failed to parse TKG search results"
+                                })
+                            }
+                        }
+                        Err(e) => serde_json::json!({
+                            "success": false,
+                            "error": format!("TKG search failed: {}", e)
+                        })
+                    }
+                } else {
+                    serde_json::json!({
+                        "success": false,
+                        "error": "Missing 'query' argument"
+                    })
+                }
+            }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        }
+    }
+
+    /// TKG Store - Store knowledge in Temporal Knowledge Graph
+    async fn tool_tkg_store_async(&self, arguments: String, user_id: String) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+
+        match args {
+            Ok(args) => {
+                if let (Some(content_val), Some(node_type_val)) = (args.get("content"), args.get("node_type")) {
+                    let content = content_val.as_str().unwrap_or("");
+                    let node_type = node_type_val.as_str().unwrap_or("CONCEPT");
+                    let importance = args.get("importance")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.5);
+
+                    // Call TKG store
+                    match tkg::tkg_store_knowledge(content.to_string(), node_type.to_string(), importance as f32, user_id).await {
+                        Ok(result_str) => {
+                            match serde_json::from_str(&result_str) {
+                                Ok(result_json) => result_json,
+                                Err(_) => serde_json::json!({
+                                    "success": false,
+                                    "error": "Failed to parse TKG store results"
+                                })
+                            }
+                        }
+                        Err(e) => serde_json::json!({
+                            "success": false,
+                            "error": format!("TKG store failed: {}", e)
+                        })
+                    }
+                } else {
+                    serde_json::json!({
+                        "success": false,
+                        "error": "Missing 'content' or 'node_type' argument"
+                    })
                 }
             }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        }
+    }
 
-            // If we successfully parsed at least one JSON object, we assume this block was JSON
-            // and don't try legacy parsing.
-            if json_parsed {
-                continue;
-            }
-
-            // Legacy MiniMax format: tool => "name", args => { key => "value" }
-            let name_regex =
-                Regex::new(r#"(?i)(?:tool|name)\s*(?:=>|:)\s*"([^"]+)""#).unwrap();
-            let args_block_regex =
-                Regex::new(r#"(?is)(?:args|arguments)\s*(?:=>|:)\s*(\{.*?\})"#).unwrap();
-
-            if let Some(name_caps) = name_regex.captures(block) {
-                let tool_name = name_caps.get(1).map(|m| m.as_str()).unwrap_or_default();
-                let args_text = args_block_regex
-                    .captures(block)
-                    .and_then(|c| c.get(1))
-                    .map(|m| m.as_str())
-                    .unwrap_or("{}");
+    /// Claim Legacy Data - Migrate guest data to current user
+    async fn tool_claim_legacy_data_async(&self, arguments: String, user_id: String) -> serde_json::Value {
+        if user_id == "guest" {
+             return serde_json::json!({
+                "success": false,
+                "error": "Cannot claim data while logged in as guest. Please log in first."
+            });
+        }
 
-                let arguments = if let Ok(json_args) =
-                    serde_json::from_str::<serde_json::Value>(args_text)
-                {
-                    serde_json::to_string(&json_args).unwrap_or_else(|_| "{}".to_string())
-                } else {
-                    // Parse key => "value" pairs into a JSON object string
-                    let mut args_map: HashMap<String, String> = HashMap::new();
-                    let arg_regex = Regex::new(r#"(\w+)\s*(?:=>|:)\s*"([^"]*)""#).unwrap();
-                    for arg_cap in arg_regex.captures_iter(args_text) {
-                        if let (Some(key), Some(value)) = (arg_cap.get(1), arg_cap.get(2)) {
-                            args_map.insert(key.as_str().to_string(), value.as_str().to_string());
-                        }
-                    }
-                    serde_json::to_string(&args_map).unwrap_or_else(|_| "{}".to_string())
-                };
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+        let dry_run = args
+            .as_ref()
+            .ok()
+            .and_then(|a| a.get("dry_run").and_then(|v| v.as_bool()))
+            .unwrap_or(true);
+        let confirm = args
+            .as_ref()
+            .ok()
+            .and_then(|a| a.get("confirm").and_then(|v| v.as_bool()))
+            .unwrap_or(false);
 
-                parsed_calls.push(ToolCall {
-                    id: format!("call_{}", base_call_index + parsed_calls.len()),
-                    tool_type: "function".to_string(),
-                    function: FunctionCall {
-                        name: tool_name.to_string(),
-                        arguments,
-                    },
-                });
-            }
+        if !dry_run && !confirm {
+            return serde_json::json!({
+                "success": false,
+                "error": "Refusing to migrate without explicit confirmation. Re-run with {\"confirm\": true} (or use {\"dry_run\": true} first)."
+            });
         }
 
-        // If we found calls using tags, return them
-        if !parsed_calls.is_empty() {
-            return parsed_calls;
+        // Call TKG claim legacy data (dry-run by default)
+        match tkg::tkg_claim_legacy_data(user_id, Some(dry_run)).await {
+            Ok(result_str) => serde_json::json!({
+                "success": true,
+                "dry_run": dry_run,
+                "message": result_str
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Migration failed: {}", e)
+            })
         }
+    }
 
-        // FALLBACK: Try to find raw JSON tool calls without tags
-        // Look for JSON objects that contain "tool": "name"
-        // Since regex is bad at nested braces, we'll use a manual brace counter
-        let tool_key_regex = Regex::new(r#""tool"\s*:\s*""#).unwrap();
-        
-        // Find all potential start positions of JSON objects containing "tool":
-        for mat in tool_key_regex.find_iter(text) {
-            // Search backwards for the opening brace '{'
-            let mut start_index = mat.start();
-            let mut found_start = false;
-            while start_index > 0 {
-                start_index -= 1;
-                if text.as_bytes()[start_index] == b'{' {
-                    found_start = true;
-                    break;
-                }
-                // Stop if we hit a closing brace or another object end, to avoid over-reaching
-                if text.as_bytes()[start_index] == b'}' {
-                    break;
-                }
-            }
+    /// Brainstorm with Grok - Get a second perspective from Grok-4
+    async fn tool_brainstorm_with_grok_async(&self, arguments: String, grok_api_key: Option<String>) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
 
-            if found_start {
-                // Now scan forward to find the matching closing brace
-                let mut brace_count = 0;
-                let mut in_string = false;
-                let mut escape = false;
-                let mut end_index = 0;
-                let mut found_end = false;
+        match args {
+            Ok(args) => {
+                if let Some(query_val) = args.get("query") {
+                    let query = query_val.as_str().unwrap_or("");
+                    let context = args.get("context")
+                        .and_then(|c| c.as_str())
+                        .unwrap_or("");
 
-                for (i, c) in text[start_index..].char_indices() {
-                    if escape {
-                        escape = false;
-                        continue;
-                    }
-                    
-                    match c {
-                        '\\' => escape = true,
-                        '"' => in_string = !in_string,
-                        '{' if !in_string => brace_count += 1,
-                        '}' if !in_string => {
-                            brace_count -= 1;
-                            if brace_count == 0 {
-                                end_index = start_index + i + 1;
-                                found_end = true;
-                                break;
-                            }
+                    // Get Grok API key from agent
+                    let grok_key = match grok_api_key {
+                        Some(key) => key,
+                        None => {
+                            tracing::warn!("⚠️ Grok API key not provided");
+                            return serde_json::json!({
+                                "success": false,
+                                "error": "Grok API key not configured. Please set your Grok API key in settings."
+                            });
                         }
-                        _ => {}
+                    };
+
+                    if grok_key.is_empty() {
+                        return serde_json::json!({
+                            "success": false,
+                            "error": "Grok API key is empty. Please check your settings."
+                        });
                     }
-                }
 
-                if found_end {
-                    let block_str = &text[start_index..end_index];
-                    
-                    // Try to parse this block as JSON
-                    if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(block_str) {
-                        // Check if it looks like a tool call
-                        if let Some(name) = json_value.get("tool").and_then(|n| n.as_str()) {
-                            // Avoid duplicates if we already parsed this one (simple check)
-                            if parsed_calls.iter().any(|c| c.function.name == name && c.function.arguments.contains(&name)) {
-                                continue;
+                    // Call Grok API
+                    let client = self.http.client();
+                    let grok_url = "https://api.x.ai/v1/chat/completions";
+
+                    // Build the prompt for Grok
+                    let full_prompt = if !context.is_empty() {
+                        format!("Context: {}\n\nQuestion: {}\n\nPlease provide a creative, insightful response or alternative perspective.", context, query)
+                    } else {
+                        format!("{}\n\nPlease provide a creative, insightful response or alternative perspective.", query)
+                    };
+
+                    let payload = serde_json::json!({
+                        "model": "grok-4-1-fast-non-reasoning",
+                        "messages": [
+                            {
+                                "role": "system",
+                                "content": "Write in clear, native-level English with complete sentences. Avoid broken/fragmented phrasing, translation-like wording, and excessive slang. Be concise, professional, and actionable. If the user is frustrated, acknowledge it briefly and then give concrete next steps."
+                            },
+                            {
+                                "role": "user",
+                                "content": full_prompt
                             }
+                        ],
+                        "max_tokens": 1000,
+                        "temperature": 0.8
+                    });
 
-                            let args_val = json_value.get("arguments").or_else(|| json_value.get("args"));
-                            
-                            let arguments = match args_val {
-                                Some(val) if val.is_object() || val.is_array() => {
-                                    serde_json::to_string(val).unwrap_or_else(|_| "{}".to_string())
-                                }
-                                Some(serde_json::Value::String(s)) => s.to_string(),
-                                Some(val) => val.to_string(),
-                                None => "{}".to_string(),
-                            };
+                    tracing::info!("🧠 Brainstorming with Grok: {}", query);
 
-                            parsed_calls.push(ToolCall {
-                                id: format!("call_{}", base_call_index + parsed_calls.len()),
-                                tool_type: "function".to_string(),
-                                function: FunctionCall {
-                                    name: name.to_string(),
-                                    arguments,
-                                },
-                            });
+                    match client.post(grok_url)
+                        .header("Authorization", format!("Bearer {}", grok_key))
+                        .header("Content-Type", "application/json")
+                        .json(&payload)
+                        .send_checked()
+                        .await
+                    {
+                        Ok(response) => {
+                            if response.status().is_success() {
+                                match response.json::<serde_json::Value>().await {
+                                    Ok(grok_result) => {
+                                        tracing::info!("✅ Grok brainstorming successful");
+
+                                        let grok_response = grok_result.get("choices")
+                                            .and_then(|c| c.as_array())
+                                            .and_then(|arr| arr.get(0))
+                                            .and_then(|choice| choice.get("message"))
+                                            .and_then(|msg| msg.get("content"))
+                                            .and_then(|content| content.as_str())
+                                            .unwrap_or("No response from Grok");
+
+                                        serde_json::json!({
+                                            "success": true,
+                                            "query": query,
+                                            "context": context,
+                                            "grok_perspective": grok_response,
+                                            "note": "This perspective is from Grok-4, providing a second viewpoint to enhance your thinking."
+                                        })
+                                    }
+                                    Err(e) => serde_json::json!({
+                                        "success": false,
+                                        "error": format!("Failed to parse Grok response: {}", e)
+                                    })
+                                }
+                            } else {
+                                let error_text = response.text().await.unwrap_or_else(|_| "Unknown Grok API error".to_string());
+                                serde_json::json!({
+                                    "success": false,
+                                    "error": format!("Grok API error: {}", error_text)
+                                })
+                            }
                         }
+                        Err(e) => serde_json::json!({
+                            "success": false,
+                            "error": format!("Failed to connect to Grok API: {}", e)
+                        })
                     }
+                } else {
+                    serde_json::json!({
+                        "success": false,
+                        "error": "Missing 'query' argument"
+                    })
                 }
             }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
         }
-
-        parsed_calls
     }
 
-    /// Parse Grok's XML-style tool calls (e.g. <tool_code>...</tool_code>)
-    fn parse_grok_xml_tools(text: &str, base_call_index: usize) -> Vec<ToolCall> {
-        let mut parsed_calls: Vec<ToolCall> = Vec::new();
+
+
+
+
+
+
+    fn tool_start_debate(&self, arguments: &str) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+        let topic = args.as_ref().ok()
+            .and_then(|a| a.get("topic").and_then(|t| t.as_str()))
+            .unwrap_or("")
+            .to_string();
         
-        // Regex for <tool_code>...</tool_code> (dot matches newline)
-        let xml_regex = Regex::new(r"(?is)<tool_code>\s*(.*?)\s*</tool_code>").unwrap();
+        let turns = args.as_ref().ok()
+            .and_then(|a| a.get("turns").and_then(|t| t.as_u64()))
+            .map(|t| t as usize);
 
-        for cap in xml_regex.captures_iter(text) {
-            if let Some(content) = cap.get(1).map(|m| m.as_str().trim()) {
-                eprintln!("🔍 Found potential XML tool block: {}", content);
-                // Try to parse the content as JSON
-                if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(content) {
-                    // Helper to extract tool info from JSON object
-                    let process_obj = |obj: &serde_json::Value, index: usize| -> Option<ToolCall> {
-                        let name = obj.get("name")
-                            .or_else(|| obj.get("tool_name"))
-                            .or_else(|| obj.get("function").and_then(|f| f.get("name")))?
-                            .as_str()?;
-                        
-                        let args = obj.get("arguments")
-                            .or_else(|| obj.get("args"))
-                            .or_else(|| obj.get("parameters"))
-                            .or_else(|| obj.get("function").and_then(|f| f.get("arguments")));
+        // Constructs by id, or full participant objects
+        let participant = |v: &serde_json::Value| match v.as_str() {
+            Some(id) => Some(orchestrate_agents::DebateParticipant { agent_id: Some(id.to_string()), ..Default::default() }),
+            None => serde_json::from_value(v.clone()).ok(),
+        };
+        let participants: Vec<orchestrate_agents::DebateParticipant> = args.as_ref().ok()
+            .and_then(|a| a.get("participants").and_then(|p| p.as_array()))
+            .map(|p| p.iter().filter_map(participant).collect())
+            .unwrap_or_default();
+        let moderator = args.as_ref().ok()
+            .and_then(|a| a.get("moderator"))
+            .and_then(participant);
+        let until = args.as_ref().ok()
+            .and_then(|a| a.get("until").and_then(|u| u.as_str()))
+            .map(|u| u.to_string());
 
-                        let arguments_str = match args {
-                            Some(val) if val.is_object() || val.is_array() => serde_json::to_string(val).unwrap_or_default(),
-                            Some(serde_json::Value::String(s)) => s.to_string(),
-                            _ => "{}".to_string()
-                        };
+        if topic.is_empty() {
+            return serde_json::json!({
+                "success": false,
+                "error": "Missing 'topic' argument"
+            });
+        }
 
-                        Some(ToolCall {
-                            id: format!("call_{}_{}", base_call_index, index),
-                            tool_type: "function".to_string(),
-                            function: FunctionCall {
-                                name: name.to_string(),
-                                arguments: arguments_str,
-                            },
-                        })
+        let api_key = self.api_key.clone();
+        
+        // Determine provider string
+        let provider_str = match self.provider {
+            AIProvider::Grok => Some("grok".to_string()),
+            _ => Some("minimax".to_string()),
+        };
+        
+        let app_handle = self.app_handle.clone();
+        // Call the debate logic synchronously (blocking)
+        let result = tokio::task::block_in_place(|| {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(async move {
+                    let req = orchestrate_agents::DebateRequest {
+                        topic,
+                        api_key,
+                        turns,
+                        provider: provider_str,
+                        participants,
+                        moderator,
+                        until,
+                        ..Default::default()
                     };
+                    orchestrate_agents::run_debate(app_handle.as_ref(), req).await
+                })
+        });
 
-                    if let Some(array) = json_val.as_array() {
-                        for item in array {
-                            let current_len = parsed_calls.len();
-                            if let Some(call) = process_obj(item, current_len) {
-                                parsed_calls.push(call);
-                            }
-                        }
-                    } else {
-                        let current_len = parsed_calls.len();
-                        if let Some(call) = process_obj(&json_val, current_len) {
-                            parsed_calls.push(call);
-                        }
-                    }
-                }
-            }
+        match result {
+            Ok(response) => serde_json::json!({
+                "success": true,
+                "transcript": response.transcript,
+                "final_consensus": response.final_consensus,
+                "rounds": response.rounds,
+                "ended_by": response.ended_by
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Debate failed: {}", e)
+            })
         }
-        
-        parsed_calls
     }
 
-    pub fn add_user_message(&mut self, content: String) {
-        self.conversation_history.push(Message {
-            role: "user".to_string(),
-            content,
-            tool_calls: None,
-            tool_call_id: None,
-            timestamp: Some(Self::get_current_timestamp()),
-        });
-    }
+    /// Scan codebase structure
+    fn tool_scan_codebase(&self, arguments: &str) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+        let (start_path, max_depth) = match args {
+            Ok(a) => (
+                a.get("path").and_then(|v| v.as_str()).unwrap_or(".").to_string(),
+                a.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(3) as usize
+            ),
+            Err(_) => (".".to_string(), 3)
+        };
 
-    pub fn get_conversation_history(&self) -> &Vec<Message> {
-        &self.conversation_history
-    }
+        let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
+        let target_path = repo_root.join(&start_path);
 
-    pub fn clear_history(&mut self) {
-        self.conversation_history.clear();
-    }
+        if !target_path.exists() {
+             return serde_json::json!({
+                "success": false,
+                "error": format!("Path does not exist: {}", start_path)
+            });
+        }
 
-    /// Estimate token count (rough heuristic: 4 chars = 1 token)
-    fn estimate_tokens(&self) -> usize {
-        let mut chars = 0;
-        for msg in &self.conversation_history {
-            chars += msg.content.len();
-            if let Some(tools) = &msg.tool_calls {
-                for tool in tools {
-                    chars += tool.function.arguments.len();
+        let mut files = Vec::new();
+        let mut directories = Vec::new();
+
+        let walker = WalkDir::new(&target_path)
+            .max_depth(max_depth)
+            .into_iter();
+
+        for entry in walker.filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            // Basic filtering of common ignore patterns
+            !name.starts_with('.') && // Hidden files
+            name != "node_modules" && 
+            name != "target" && 
+            name != "dist" && 
+            name != "build" &&
+            name != "coverage"
+        }) {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if let Ok(rel_path) = path.strip_prefix(&repo_root) {
+                    let path_str = rel_path.to_string_lossy().replace("\\", "/");
+                    if path.is_dir() {
+                        directories.push(path_str);
+                    } else {
+                        files.push(path_str);
+                    }
                 }
             }
         }
-        chars / 4
-    }
 
-    /// Prune history if it exceeds token limit
-    fn prune_history(&mut self) {
-        const MAX_TOKENS: usize = 90_000; // Leave buffer for response
-        const MIN_MESSAGES: usize = 10;   // Always keep last N messages
+        // Sort for consistent output
+        files.sort();
+        directories.sort();
 
-        let current_tokens = self.estimate_tokens();
-        if current_tokens > MAX_TOKENS {
-            eprintln!("✂️ Context too large ({} tokens), pruning...", current_tokens);
-            
-            let mut removed_count = 0;
-            while self.estimate_tokens() > MAX_TOKENS && self.conversation_history.len() > MIN_MESSAGES {
-                // Remove from front (oldest), but be careful not to break tool chains if possible
-                // For simplicity, just remove oldest
-                self.conversation_history.remove(0);
-                removed_count += 1;
-            }
-            eprintln!("✂️ Pruned {} messages. New token count: {}", removed_count, self.estimate_tokens());
-        }
+        serde_json::json!({
+            "success": true,
+            "root": start_path,
+            "directories": directories,
+            "files": files,
+            "total_files": files.len(),
+            "total_directories": directories.len()
+        })
     }
 
-    /// Get the knowledge base root directory
-    /// Dev Mode: Repository root
-    /// Prod Mode: User Documents/KnowledgeCompanion
-    pub fn get_knowledge_base_path() -> Result<PathBuf, String> {
-        let current = std::env::current_dir().map_err(|e| e.to_string())?;
+    fn tool_get_repo_stats(&self, arguments: &str) -> serde_json::Value {
+        let top_n = serde_json::from_str::<serde_json::Value>(arguments)
+            .ok()
+            .and_then(|v| v.get("top_n").and_then(|n| n.as_u64()))
+            .unwrap_or(10)
+            .min(50) as usize;
 
-        // 1. Check for Dev Environment (src-tauri or project root)
-        if current.file_name().and_then(|n| n.to_str()) == Some("src-tauri") {
-            return current.parent()
-                .and_then(|p| p.parent())
-                .ok_or("Could not find repository root from src-tauri".to_string())
-                .map(|p| p.to_path_buf());
-        } else if current.file_name().and_then(|n| n.to_str()) == Some("startup-strategy-app") {
-            return current.parent()
-                .ok_or("Could not find parent directory".to_string())
-                .map(|p| p.to_path_buf());
-        }
+        let db_path = match crate::repo_indexer::RepoIndex::index_db_path(self.app_handle.as_ref()) {
+            Some(path) if path.exists() => path,
+            _ => return serde_json::json!({
+                "success": false,
+                "error": "No repository has been indexed yet. Index one from the repo explorer first."
+            }),
+        };
 
-        // 2. Production Mode: Use User Documents
-        let user_dirs = directories::UserDirs::new()
-            .ok_or("Could not find user directories".to_string())?;
-        
-        let doc_dir = user_dirs.document_dir()
-            .ok_or("Could not find Documents directory".to_string())?;
-            
-        let kb_root = doc_dir.join("KnowledgeCompanion");
+        let index = crate::repo_indexer::RepoIndex::open_index_db(&db_path)
+            .and_then(|conn| crate::repo_indexer::RepoIndex::load_persisted(&conn, None));
 
-        // 3. Ensure structure exists
-        let folders = vec!["research", "dumps", "developer-reference", "ai-agents", "collections", "generated-guides"];
-        for folder in folders {
-            let p = kb_root.join(folder);
-            if !p.exists() {
-                let _ = std::fs::create_dir_all(&p);
+        match index {
+            Ok(Some(index)) => {
+                let stats = crate::repo_stats::compute_repo_stats(&index, top_n);
+                serde_json::json!({
+                    "success": true,
+                    "stats": stats
+                })
             }
-        }
-
-        Ok(kb_root)
+            Ok(None) => serde_json::json!({
+                "success": false,
+                "error": "No repository has been indexed yet. Index one from the repo explorer first."
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Failed to load repository index: {}", e)
+            }),
+        }
     }
 
-    /// Execute a tool and return result as JSON string
-    fn execute_tool(&self, tool_name: &str, arguments: &str) -> String {
-        if self.is_forced_disabled_tool(tool_name) {
+    fn tool_grep_codebase(&self, arguments: &str) -> serde_json::Value {
+        let args: HashMap<String, serde_json::Value> = match serde_json::from_str(arguments) {
+            Ok(a) => a,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+
+        let pattern = match args.get("pattern").and_then(|v| v.as_str()) {
+            Some(p) if !p.is_empty() => p.to_string(),
+            _ => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'pattern' parameter"
+            }),
+        };
+
+        let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
+        let sub_path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        let search_root = repo_root.join(sub_path);
+
+        // Security: don't let relative paths escape the root
+        if sub_path.contains("..") || !search_root.starts_with(&repo_root) || !search_root.exists() {
             return serde_json::json!({
                 "success": false,
-                "error": format!("Tool '{}' is not available in student mode", tool_name)
-            }).to_string();
+                "error": format!("Invalid search path: {}", sub_path)
+            });
         }
 
-        if !self.enabled_tools.is_empty() {
-            let enabled = self.enabled_tools.get(tool_name).copied().unwrap_or(true);
-            if !enabled {
-                return serde_json::json!({
-                    "success": false,
-                    "error": format!("Tool '{}' is disabled in this session", tool_name)
-                }).to_string();
-            }
-        }
-        eprintln!("🔧 Executing tool: {}", tool_name);
-        eprintln!("📝 Arguments: {}", arguments);
+        let options = crate::code_search::GrepOptions {
+            pattern,
+            globs: args.get("globs")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|g| g.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default(),
+            case_insensitive: args.get("case_insensitive").and_then(|v| v.as_bool()).unwrap_or(false),
+            fixed_strings: args.get("fixed_strings").and_then(|v| v.as_bool()).unwrap_or(false),
+            context_lines: args.get("context_lines").and_then(|v| v.as_u64()).unwrap_or(0).min(10) as usize,
+            max_results: args.get("max_results").and_then(|v| v.as_u64()).unwrap_or(50).min(500) as usize,
+        };
 
-        let result = match tool_name {
-            "scan_codebase" => self.tool_scan_codebase(arguments),
-            "start_debate" => self.tool_start_debate(arguments),
-            "write_file_batch" => self.tool_write_file_batch(arguments),
-            "run_terminal_command" => self.tool_run_terminal_command(arguments),
-            "calculate" => self.tool_calculate(arguments),
-            "read_file" => self.tool_read_file(arguments),
-            "search_knowledge" => self.tool_search_knowledge(arguments),
-            "canvas_update" => serde_json::Value::String(self.tool_canvas_update(arguments)),
-            "list_registered_agents" => self.tool_list_registered_agents(arguments),
-            "invoke_agent" => self.tool_invoke_agent(arguments),
-            "create_study_guide" => {
-                let grok_api_key = self.grok_api_key.clone();
-                let args_str = arguments.to_string();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_create_study_guide_async(args_str, grok_api_key))
-                })
-            }
-            "list_markdown_files" => self.tool_list_markdown_files(arguments),
-            "web_search" => {
-                // For async tools, we need to use a blocking call in a runtime
-                let tavily_api_key = self.tavily_api_key.clone();
-                let args_str = arguments.to_string();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_web_search_async(args_str, tavily_api_key))
-                })
-            }
-            "write_file" => self.tool_write_file(arguments),
-            "display_media" => self.tool_display_media(arguments),
-            "brainstorm_with_grok" => {
-                // For async Grok calls
-                let grok_api_key = self.grok_api_key.clone();
-                let args_str = arguments.to_string();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_brainstorm_with_grok_async(args_str, grok_api_key))
-                })
-            }
-            "harvest_wiki" => {
-                // Async Wiki Harvest
-                let args_str = arguments.to_string();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_harvest_wiki_async(args_str))
-                })
-            }
-            "harvest_wiki_category" => {
-                // Async Wiki Category Harvest
-                let args_str = arguments.to_string();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_harvest_wiki_category_async(args_str))
-                })
-            }
+        match crate::code_search::grep_directory(&search_root, &options) {
+            Ok(results) => {
+                let prefix = sub_path.trim_matches('/');
+                let matches: Vec<serde_json::Value> = results.matches
+                    .into_iter()
+                    .map(|m| {
+                        // Report paths relative to the knowledge base root, like scan_codebase
+                        let path = if prefix.is_empty() {
+                            m.relative_path
+                        } else {
+                            format!("{}/{}", prefix, m.relative_path)
+                        };
+                        serde_json::json!({
+                            "path": path,
+                            "line": m.line_number,
+                            "text": m.line,
+                            "before": m.before,
+                            "after": m.after
+                        })
+                    })
+                    .collect();
 
-            "tkg_search" => {
-                // Search the Temporal Knowledge Graph
-                let args_str = arguments.to_string();
-                let user_id = self.user_id.clone();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(self.tool_tkg_search_async(args_str, user_id))
+                serde_json::json!({
+                    "success": true,
+                    "pattern": options.pattern,
+                    "count": matches.len(),
+                    "files_searched": results.files_searched,
+                    "files_matched": results.files_matched,
+                    "truncated": results.truncated,
+                    "matches": matches
                 })
             }
-            "tkg_store" => {
-                // Store knowledge in the Temporal Knowledge Graph
-                let args = arguments.to_string();
-                let user_id = self.user_id.clone();
-                let result = tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(async move {
-                            self.tool_tkg_store_async(args, user_id).await
-                        })
-                });
-                result
-            }
-            "deep_research" => {
-                // Spawn a sub-agent for deep research
-                let api_key = self.api_key.clone();
-                let tavily_api_key = self.tavily_api_key.clone();
-                let grok_api_key = self.grok_api_key.clone();
-                let gemini_api_key = self.gemini_api_key.clone();
-                let app_handle = self.app_handle.clone();
-                let provider = self.provider.clone();
-                let enabled_tools = self.enabled_tools.clone();
-                let safe_mode = self.safe_mode;
-                let user_id = self.user_id.clone();
-                let user_name = self.user_name.clone();
-                let args_str = arguments.to_string();
-                
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(async move {
-                            let args: Result<serde_json::Value, _> = serde_json::from_str(&args_str);
-                            match args {
-                                Ok(args) => {
-                                    if let Some(topic) = args.get("topic").and_then(|v| v.as_str()) {
-                                        let topic = topic.to_string();
-                                        
-                                        // Check for sub_topics for parallel execution
-                                        let sub_topics: Vec<String> = args.get("sub_topics")
-                                            .and_then(|v| v.as_array())
-                                            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
-                                            .unwrap_or_default();
-
-                                        if !sub_topics.is_empty() {
-                                            eprintln!("🚀 Spawning {} Parallel Deep Research Agents for: {}", sub_topics.len(), topic);
-                                            
-                                            let mut handles = vec![];
-
-                                            for sub_topic in sub_topics {
-                                                let tavily_key = tavily_api_key.clone().unwrap_or_default();
-                                                let app_handle_clone = app_handle.clone();
-                                                let sub_topic_clone = sub_topic.clone();
-
-                                                let handle = tokio::spawn(async move {
-                                                    eprintln!("🤖 Agent starting research on: {}", sub_topic_clone);
-                                                    let agent = DeepResearchAgent::new(tavily_key);
-                                                    
-                                                    let result = agent.research_topic(&sub_topic_clone, 1, move |step| {
-                                                        if let Some(h) = &app_handle_clone {
-                                                            let _ = h.emit_all("research-progress", step);
-                                                        }
-                                                    }).await;
-
-                                                    match result {
-                                                        Ok(context) => (sub_topic_clone, Ok(context)),
-                                                        Err(e) => (sub_topic_clone, Err(e))
-                                                    }
-                                                });
-                                                handles.push(handle);
-                                            }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Search failed: {}", e)
+            }),
+        }
+    }
 
-                                            // Wait for all agents
-                                            let mut reports = Vec::new();
-                                            for handle in handles {
-                                                if let Ok((sub_topic, result)) = handle.await {
-                                                    match result {
-                                                        Ok(context) => {
-                                                            eprintln!("✅ Agent finished: {}", sub_topic);
-                                                            reports.push(format!("# Research Data on {}\n\n{}", sub_topic, context));
-                                                        }
-                                                        Err(e) => {
-                                                            eprintln!("❌ Agent failed on {}: {}", sub_topic, e);
-                                                            reports.push(format!("# Research Data on {}\n\nFAILED: {}", sub_topic, e));
-                                                        }
-                                                    }
-                                                }
-                                            }
+    fn tool_find_symbol(&self, arguments: &str) -> serde_json::Value {
+        let args: HashMap<String, serde_json::Value> = match serde_json::from_str(arguments) {
+            Ok(a) => a,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
 
-                                            // Synthesize results
-                                            eprintln!("🧠 Synthesizing {} research contexts...", reports.len());
-                                            let mut synthesizer = MinimaxAgent::new(
-                                                api_key.clone(),
-                                                tavily_api_key.clone(),
-                                                grok_api_key.clone(),
-                                                gemini_api_key.clone()
-                                            )
-                                            .with_provider(provider.clone())
-                                            .with_enabled_tools(enabled_tools.clone())
-                                            .with_safe_mode(safe_mode)
-                                            .with_user_id(user_id.clone())
-                                            .with_user_name(user_name.clone());
-
-                                            if let Some(handle) = &app_handle {
-                                                synthesizer = synthesizer.with_app_handle(handle.clone());
-                                            }
-                                            
-                                            let synthesis_prompt = r#"You are a Lead Research Synthesizer.
-Your goal is to combine multiple research contexts into one cohesive, comprehensive master report.
-1. Read all the provided research data.
-2. Identify key themes, facts, and insights.
-3. Synthesize them into a single, well-structured markdown document.
-4. Ensure the flow is logical and the tone is professional.
-Always use the <think> tag to explain your synthesis process."#.to_string();
-
-                                            synthesizer = synthesizer.with_system_prompt(synthesis_prompt);
-
-                                            let combined_input = format!("Here is the raw research data for the topic '{}':\n\n{}", 
-                                                topic, 
-                                                reports.join("\n\n---\n\n")
-                                            );
-
-                                            match synthesizer.run_autonomous_task(combined_input).await {
-                                                Ok(final_report) => serde_json::json!({
-                                                    "success": true,
-                                                    "report": final_report,
-                                                    "mode": "parallel",
-                                                    "agents_count": reports.len()
-                                                }),
-                                                Err(e) => serde_json::json!({
-                                                    "success": false,
-                                                    "error": format!("Synthesis failed: {}", e)
-                                                })
-                                            }
+        let name = match args.get("name").and_then(|v| v.as_str()) {
+            Some(n) if !n.trim().is_empty() => n.to_string(),
+            _ => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'name' parameter"
+            }),
+        };
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
 
-                                        } else {
-                                            // Single agent mode
-                                            let tavily_key = tavily_api_key.clone().unwrap_or_default();
-                                            let app_handle_clone = app_handle.clone();
-                                            let agent = DeepResearchAgent::new(tavily_key);
-                                            
-                                            eprintln!("🔍 Starting deep research on: {}", topic);
+        let db_path = match crate::repo_indexer::RepoIndex::index_db_path(self.app_handle.as_ref()) {
+            Some(path) if path.exists() => path,
+            _ => return serde_json::json!({
+                "success": false,
+                "error": "No repository has been indexed yet. Index one from the repo explorer first."
+            }),
+        };
 
-                                            match agent.research_topic(&topic, 1, move |step| {
-                                                if let Some(h) = &app_handle_clone {
-                                                    let _ = h.emit_all("research-progress", step);
-                                                }
-                                            }).await {
-                                                Ok(context) => {
-                                                    // Synthesize
-                                                    eprintln!("🧠 Synthesizing research...");
-                                                    let mut synthesizer = MinimaxAgent::new(
-                                                        api_key.clone(),
-                                                        tavily_api_key.clone(),
-                                                        grok_api_key.clone(),
-                                                        gemini_api_key.clone()
-                                                    )
-                                                    .with_provider(provider.clone())
-                                                    .with_enabled_tools(enabled_tools.clone())
-                                                    .with_safe_mode(safe_mode)
-                                                    .with_user_id(user_id.clone())
-                                                    .with_user_name(user_name.clone());
-
-                                                    if let Some(handle) = &app_handle {
-                                                        synthesizer = synthesizer.with_app_handle(handle.clone());
-                                                    }
-                                                    
-                                                    let synthesis_prompt = r#"You are a Deep Research Specialist.
-Your goal is to write a comprehensive report based on the provided research data.
-1. Analyze the research data.
-2. Structure a detailed markdown report.
-3. Include citations where possible (URLs are provided in the data).
-Always use the <think> tag to explain your reasoning."#.to_string();
+        let conn = match crate::repo_indexer::RepoIndex::open_index_db(&db_path) {
+            Ok(conn) => conn,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Failed to open index database: {}", e)
+            }),
+        };
 
-                                                    synthesizer = synthesizer.with_system_prompt(synthesis_prompt);
-                                                    
-                                                    let input = format!("Here is the research data for '{}':\n\n{}", topic, context);
+        match crate::symbols::find_symbols(&conn, &name, None, limit) {
+            Ok(matches) => {
+                let results: Vec<serde_json::Value> = matches
+                    .into_iter()
+                    .map(|(repo_root, symbol)| serde_json::json!({
+                        "repo_root": repo_root,
+                        "path": symbol.relative_path,
+                        "name": symbol.name,
+                        "kind": symbol.kind,
+                        "parent": symbol.parent,
+                        "line": symbol.line,
+                        "end_line": symbol.end_line,
+                        "signature": symbol.signature
+                    }))
+                    .collect();
 
-                                                    match synthesizer.run_autonomous_task(input).await {
-                                                        Ok(report) => serde_json::json!({
-                                                            "success": true,
-                                                            "report": report
-                                                        }),
-                                                        Err(e) => serde_json::json!({
-                                                            "success": false,
-                                                            "error": format!("Synthesis failed: {}", e)
-                                                        })
-                                                    }
-                                                },
-                                                Err(e) => serde_json::json!({
-                                                    "success": false,
-                                                    "error": format!("Research failed: {}", e)
-                                                })
-                                            }
-                                        }
-                                    } else {
-                                        serde_json::json!({
-                                            "success": false,
-                                            "error": "Missing 'topic' argument"
-                                        })
-                                    }
-                                },
-                                Err(e) => serde_json::json!({
-                                    "success": false,
-                                    "error": format!("Invalid arguments: {}", e)
-                                })
-                            }
-                        })
+                serde_json::json!({
+                    "success": true,
+                    "query": name,
+                    "count": results.len(),
+                    "results": results
                 })
             }
-            "consult_agent" => {
-                // Consult a specialized agent and get their expert response
-                let api_key = self.api_key.clone();
-                let grok_api_key = self.grok_api_key.clone();
-                let gemini_api_key = self.gemini_api_key.clone();
-                let default_provider = match self.provider {
-                    AIProvider::Grok => "grok",
-                    AIProvider::Gemini => "gemini",
-                    AIProvider::Minimax => "minimax",
-                }
-                .to_string();
-                let args_str = arguments.to_string();
-                let registry_data = self.load_agents_registry();
-
-                tokio::task::block_in_place(|| {
-                    let registry_data = registry_data.clone();
-                    let default_provider = default_provider.clone();
-                    tokio::runtime::Runtime::new()
-                        .unwrap()
-                        .block_on(async move {
-                            let args: Result<serde_json::Value, _> = serde_json::from_str(&args_str);
-                            match args {
-                                Ok(args) => {
-                                    let agent_id_arg = args.get("agent_id").and_then(|v| v.as_str()).map(|id| id.to_string());
-                                    let agent_name_arg = args.get("agent_name").and_then(|v| v.as_str()).map(|name| name.to_string());
-
-                                    if agent_id_arg.is_none() && agent_name_arg.is_none() {
-                                        return serde_json::json!({
-                                            "success": false,
-                                            "error": "Missing 'agent_id' or 'agent_name' argument"
-                                        });
-                                    }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Symbol lookup failed: {}", e)
+            }),
+        }
+    }
 
-                                    let query = match args.get("query").and_then(|v| v.as_str()) {
-                                        Some(q) => q.to_string(),
-                                        None => return serde_json::json!({
-                                            "success": false,
-                                            "error": "Missing 'query' argument"
-                                        })
-                                    };
+    /// Helper to validate if a path is safe to write to
+    fn validate_write_scope(&self, path_str: &str) -> Result<std::path::PathBuf, String> {
+        let path = std::path::Path::new(path_str);
+        
+        // 1. Prevent absolute paths outside the project (basic check)
+        // In a real app, we'd resolve against the project root. 
+        // For now, we assume the CWD is the project root or we allow relative paths.
+        
+        // 2. Hardcoded allowlist of directories
+        let allowed_prefixes = [
+            "src/",
+            "src-tauri/",
+            "public/",
+            "docs/",
+            "generated-guides/", // Allow guides
+            "KnowledgeCompanion/", // Allow agent data
+        ];
 
-                                    let agent_label = agent_id_arg.clone().or_else(|| agent_name_arg.clone()).unwrap_or_else(|| "unknown".to_string());
-                                    eprintln!("🤖 Consulting agent: {}", agent_label);
+        // Normalize separators for Windows
+        let normalized_path = path_str.replace("\\", "/");
+        
+        let is_allowed = allowed_prefixes.iter().any(|prefix| normalized_path.starts_with(prefix)) 
+            || normalized_path == "README.md" 
+            || normalized_path == "package.json"; // Allow root config updates if needed
 
-                                    let data = match registry_data {
-                                        Ok(data) => data,
-                                        Err(e) => {
-                                            return serde_json::json!({
-                                                "success": false,
-                                                "error": e
-                                            });
-                                        }
-                                    };
+        if !is_allowed {
+            return Err(format!(
+                "Security Error: Writing to '{}' is not allowed. Allowed directories: {:?}", 
+                path_str, allowed_prefixes
+            ));
+        }
 
-                                    let agents = match data.get("agents").and_then(|v| v.as_array()) {
-                                        Some(agents) => agents,
-                                        None => {
-                                            return serde_json::json!({
-                                                "success": false,
-                                                "error": "No agents array in registry"
-                                            });
-                                        }
-                                    };
+        // 3. Prevent traversal (../)
+        if normalized_path.contains("../") || normalized_path.contains("..\\") {
+             return Err("Security Error: Path traversal (../) is forbidden.".to_string());
+        }
 
-                                    let mut agent = None;
-                                    if let Some(agent_id) = agent_id_arg.as_deref() {
-                                        agent = agents.iter().find(|a| a.get("id").and_then(|v| v.as_str()) == Some(agent_id));
-                                    }
-                                    if agent.is_none() {
-                                        if let Some(agent_name) = agent_name_arg.as_deref() {
-                                            agent = agents.iter().find(|a| {
-                                                a.get("name")
-                                                    .and_then(|v| v.as_str())
-                                                    .map(|name| name.eq_ignore_ascii_case(agent_name))
-                                                    .unwrap_or(false)
-                                            });
-                                        }
-                                    }
+        Ok(path.to_path_buf())
+    }
 
-                                    let agent = match agent {
-                                        Some(agent) => agent,
-                                        None => {
-                                            return serde_json::json!({
-                                                "success": false,
-                                                "error": format!("Agent '{}' not found in registry", agent_label)
-                                            });
-                                        }
-                                    };
+    fn tool_write_file_batch(&self, arguments: &str) -> serde_json::Value {
+        if self.safe_mode {
+            return serde_json::json!({
+                "success": false,
+                "error": "Safe Mode is enabled. File writing is disabled."
+            });
+        }
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+        let files = args.as_ref().ok()
+            .and_then(|a| a.get("files").and_then(|f| f.as_array()));
 
-                                    let agent_id = agent.get("id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
-                                    let agent_name = agent.get("name").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string();
-                                    let provider = agent
-                                        .get("preferredProvider")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or(&default_provider)
-                                        .to_string();
-                                    let system_prompt = agent.get("systemPrompt").and_then(|v| v.as_str()).unwrap_or("You are a helpful assistant.").to_string();
+        if let Some(file_list) = files {
+            // Our own writes shouldn't bounce back as refresh/re-index events
+            let watch_pause = crate::file_watcher::pause_for_writes(Vec::new());
 
-                                    eprintln!("📋 Agent: {} | Provider: {}", agent_name, provider);
+            let mut results = Vec::new();
+            let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
 
-                                    // Make API call based on provider
-                                    let client = reqwest::Client::new();
+            for file_obj in file_list {
+                if let (Some(path_str), Some(content)) = (
+                    file_obj.get("path").and_then(|p| p.as_str()),
+                    file_obj.get("content").and_then(|c| c.as_str())
+                ) {
+                    // Validate Scope
+                    if let Err(e) = self.validate_write_scope(path_str) {
+                        results.push(serde_json::json!({
+                            "path": path_str,
+                            "success": false,
+                            "error": e
+                        }));
+                        continue;
+                    }
 
-                                    let (url, auth_header, payload) = if provider == "grok" {
-                                        let key = grok_api_key.clone().unwrap_or_default();
-                                        (
-                                            "https://api.x.ai/v1/chat/completions".to_string(),
-                                            format!("Bearer {}", key),
-                                            serde_json::json!({
-                                                "model": "grok-4-1-fast",
-                                                "messages": [
-                                                    {"role": "system", "content": system_prompt},
-                                                    {"role": "user", "content": query}
-                                                ],
-                                                "max_tokens": 4096,
-                                                "temperature": 0.7
-                                            })
-                                        )
-                                    } else if provider == "gemini" {
-                                        let key = gemini_api_key.clone().unwrap_or_default();
-                                        (
-                                            format!("https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}", key),
-                                            "".to_string(),
-                                            serde_json::json!({
-                                                "contents": [
-                                                    {"role": "user", "parts": [{"text": format!("{}\n\nUser Query: {}", system_prompt, query)}]}
-                                                ]
-                                            })
-                                        )
-                                    } else {
-                                        // Default to MiniMax
-                                        (
-                                            "https://api.minimax.io/v1/chat/completions".to_string(),
-                                            format!("Bearer {}", api_key),
-                                            serde_json::json!({
-                                                "model": "MiniMax-M2",
-                                                "messages": [
-                                                    {"role": "system", "content": system_prompt},
-                                                    {"role": "user", "content": query}
-                                                ],
-                                                "max_tokens": 4096,
-                                                "temperature": 0.7
-                                            })
-                                        )
-                                    };
+                    let full_path = repo_root.join(path_str);
+                    
+                    // Security check: ensure path is within repo (redundant but safe)
+                    if !full_path.starts_with(&repo_root) {
+                        results.push(serde_json::json!({
+                            "path": path_str,
+                            "success": false,
+                            "error": "Path traversal detected"
+                        }));
+                        continue;
+                    }
 
-                                    let mut request = client.post(&url)
-                                        .header("Content-Type", "application/json")
-                                        .json(&payload);
+                    let explicit_etag = file_obj.get("expected_etag").and_then(|e| e.as_str());
+                    if let Err(conflict) = crate::note_conflicts::check_expected(&full_path, self.expected_etag(&full_path, explicit_etag).as_deref()) {
+                        results.push(serde_json::json!({
+                            "path": path_str,
+                            "success": false,
+                            "error": conflict.to_string(),
+                            "current_etag": conflict.current_etag
+                        }));
+                        continue;
+                    }
 
-                                    if !auth_header.is_empty() {
-                                        request = request.header("Authorization", auth_header);
-                                    }
+                    if self.dry_run {
+                        results.push(crate::dry_run::planned_write(&full_path, path_str, content, false));
+                        continue;
+                    }
 
-                                    match request.send().await {
-                                        Ok(response) => {
-                                            if response.status().is_success() {
-                                                match response.json::<serde_json::Value>().await {
-                                                    Ok(result) => {
-                                                        // Extract response based on provider format
-                                                        let content = if provider == "gemini" {
-                                                            result.get("candidates")
-                                                                .and_then(|c| c.as_array())
-                                                                .and_then(|c| c.first())
-                                                                .and_then(|c| c.get("content"))
-                                                                .and_then(|c| c.get("parts"))
-                                                                .and_then(|p| p.as_array())
-                                                                .and_then(|p| p.first())
-                                                                .and_then(|p| p.get("text"))
-                                                                .and_then(|t| t.as_str())
-                                                                .unwrap_or("No response")
-                                                                .to_string()
-                                                        } else {
-                                                            result.get("choices")
-                                                                .and_then(|c| c.as_array())
-                                                                .and_then(|c| c.first())
-                                                                .and_then(|c| c.get("message"))
-                                                                .and_then(|m| m.get("content"))
-                                                                .and_then(|c| c.as_str())
-                                                                .unwrap_or("No response")
-                                                                .to_string()
-                                                        };
+                    // Create parent dirs
+                    if let Some(parent) = full_path.parent() {
+                        let _ = std::fs::create_dir_all(parent);
+                    }
 
-                                                        eprintln!("✅ Agent consultation complete");
+                    crate::note_versions::record_before_write(self.app_handle.as_ref(), &repo_root, &full_path, "agent");
 
-                                                        serde_json::json!({
-                                                            "success": true,
-                                                            "agent_id": agent_id,
-                                                            "agent_name": agent_name,
-                                                            "provider": provider,
-                                                            "response": content
-                                                        })
-                                                    }
-                                                    Err(e) => serde_json::json!({
-                                                        "success": false,
-                                                        "error": format!("Failed to parse response: {}", e)
-                                                    })
-                                                }
-                                            } else {
-                                                let error_text = response.text().await.unwrap_or_default();
-                                                serde_json::json!({
-                                                    "success": false,
-                                                    "error": format!("API error: {}", error_text)
-                                                })
-                                            }
-                                        }
-                                        Err(e) => serde_json::json!({
-                                            "success": false,
-                                            "error": format!("Request failed: {}", e)
-                                        })
-                                    }
-                                }
-                                Err(e) => serde_json::json!({
-                                    "success": false,
-                                    "error": format!("Invalid arguments: {}", e)
-                                })
-                            }
-                        })
-                })
+                    watch_pause.cover(&full_path);
+                    match std::fs::write(&full_path, content) {
+                        Ok(_) => {
+                            self.remember_etag(&full_path);
+                            results.push(serde_json::json!({
+                                "path": path_str,
+                                "success": true
+                            }))
+                        }
+                        Err(e) => results.push(serde_json::json!({
+                            "path": path_str,
+                            "success": false,
+                            "error": e.to_string()
+                        }))
+                    }
+                }
             }
-            _ => serde_json::json!({
-                "error": format!("Unknown tool: {}", tool_name)
-            }),
-        };
 
-        eprintln!("✅ Result: {}", result);
-        result.to_string()
-    }
+            // The watcher is paused, so send one refresh for the whole batch ourselves
+            let written: Vec<&str> = results.iter()
+                .filter(|r| r.get("success").and_then(|v| v.as_bool()).unwrap_or(false))
+                .filter(|r| r.get("dry_run").is_none())
+                .filter_map(|r| r.get("path").and_then(|p| p.as_str()))
+                .collect();
+            if let (Some(handle), false) = (&self.app_handle, written.is_empty()) {
+                // Rebuilt from components, so "./a.md" or "notes//a.md" end up as the index's keys
+                let written_paths: Vec<PathBuf> = written.iter().map(|p| repo_root.join(p).components().collect()).collect();
+                crate::kb_index::apply_watch_changes(handle, &written_paths, None);
+
+                let keys: Vec<String> = written_paths.iter().map(|p| crate::kb_index::path_key(&repo_root, p)).collect();
+                crate::events::emit(handle, "content-changed", serde_json::json!({
+                    "source": repo_root,
+                    "paths": keys,
+                }));
+            }
 
-    /// Async version of tool_web_search
-    async fn tool_web_search_async(&self, arguments: String, tavily_api_key: Option<String>) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+            serde_json::json!({
+                "success": true,
+                "results": results
+            })
+        } else {
+            serde_json::json!({
+                "success": false,
+                "error": "Missing 'files' argument"
+            })
+        }
+    }
 
-        match args {
-            Ok(args) => {
-                if let Some(query_val) = args.get("query") {
-                    let query = query_val.as_str().unwrap_or("");
-                    let max_results = args.get("max_results")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(5)
-                        .min(10); // Cap at 10 results
+    fn tool_run_terminal_command(&self, arguments: &str) -> serde_json::Value {
+        if self.safe_mode {
+            return serde_json::json!({
+                "success": false,
+                "error": "Safe Mode is enabled. Terminal commands are disabled."
+            });
+        }
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+        let command = args.as_ref().ok()
+            .and_then(|a| a.get("command").and_then(|c| c.as_str()))
+            .unwrap_or("");
 
-                    // Get Tavily API key from agent
-                    let tavily_key = match tavily_api_key {
-                        Some(key) => key,
-                        None => {
-                            eprintln!("⚠️ Tavily API key not provided");
-                            return serde_json::json!({
-                                "success": false,
-                                "error": "Tavily API key not configured. Please set your Tavily API key in settings."
-                            });
-                        }
-                    };
+        if command.is_empty() {
+            return serde_json::json!({
+                "success": false,
+                "error": "Missing 'command' argument"
+            });
+        }
 
-                    if tavily_key.is_empty() {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Tavily API key is empty. Please check your settings."
-                        });
-                    }
+        let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
 
-                    // Call Tavily Search API
-                    let client = reqwest::Client::new();
-                    let search_url = "https://api.tavily.com/search";
+        if self.dry_run {
+            return crate::dry_run::planned_command(command, &["cmd", "/C"], &repo_root);
+        }
 
-                    let payload = serde_json::json!({
-                        "api_key": tavily_key,
-                        "query": query,
-                        "max_results": max_results,
-                        "include_answer": true,
-                        "include_images": false,
-                        "include_raw_content": false
-                    });
+        tracing::info!("💻 Executing command: {}", command);
 
-                    eprintln!("🔍 Searching web for: {}", query);
+        // Execute command (Windows)
+        let output = std::process::Command::new("cmd")
+            .args(&["/C", command])
+            .current_dir(&repo_root)
+            .output();
 
-                    match client.post(search_url)
-                        .header("Content-Type", "application/json")
-                        .json(&payload)
-                        .send()
-                        .await
-                    {
-                        Ok(response) => {
-                            if response.status().is_success() {
-                                match response.json::<serde_json::Value>().await {
-                                    Ok(search_result) => {
-                                        eprintln!("✅ Web search successful");
-
-                                        // Parse and format results
-                                        let results = search_result.get("results")
-                                            .and_then(|r| r.as_array())
-                                            .unwrap_or(&vec![])
-                                            .iter()
-                                            .filter_map(|r| {
-                                                serde_json::to_string(&serde_json::json!({
-                                                    "title": r.get("title")?.as_str()?,
-                                                    "url": r.get("url")?.as_str()?,
-                                                    "snippet": r.get("content")?.as_str()?,
-                                                    "published_date": r.get("published_date").and_then(|d| d.as_str())
-                                                })).ok()
-                                            })
-                                            .collect::<Vec<String>>();
+        match output {
+            Ok(out) => {
+                let stdout = String::from_utf8_lossy(&out.stdout).to_string();
+                let stderr = String::from_utf8_lossy(&out.stderr).to_string();
+                
+                serde_json::json!({
+                    "success": out.status.success(),
+                    "stdout": stdout,
+                    "stderr": stderr,
+                    "exit_code": out.status.code()
+                })
+            },
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Failed to execute command: {}", e)
+            })
+        }
+    }
 
-                                        let answer = search_result.get("answer")
-                                            .and_then(|a| a.as_str())
-                                            .unwrap_or("");
+    fn tool_canvas_update(&self, arguments: &str) -> String {
+        let args: Result<serde_json::Value, _> = serde_json::from_str(arguments);
+        match args {
+            Ok(args) => {
+                let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+                let target = args.get("target").and_then(|v| v.as_str());
+                
+                let mut payload = serde_json::Map::new();
 
-                                        serde_json::json!({
-                                            "success": true,
-                                            "query": query,
-                                            "answer": answer,
-                                            "results": results,
-                                            "count": results.len()
-                                        })
-                                    }
-                                    Err(e) => serde_json::json!({
-                                        "success": false,
-                                        "error": format!("Failed to parse search results: {}", e)
-                                    })
-                                }
-                            } else {
-                                let error_text = response.text().await.unwrap_or_else(|_| "Unknown API error".to_string());
-                                serde_json::json!({
+                match action {
+                    "preview" => {
+                        let mut preview_data = serde_json::Map::new();
+                        if let Some(t) = target { preview_data.insert("target".to_string(), serde_json::json!(t)); }
+                        if let Some(u) = args.get("url") { preview_data.insert("url".to_string(), u.clone()); }
+                        if let Some(c) = args.get("code").and_then(|v| v.as_str()) { 
+                            // Fix Grok double-escaping newlines safely
+                            let sanitized = c.replace("\\n", "\n");
+                            preview_data.insert("code".to_string(), serde_json::json!(sanitized)); 
+                        }
+                        if let Some(t) = args.get("type") { preview_data.insert("type".to_string(), t.clone()); }
+                        if let Some(p) = args.get("popup") { preview_data.insert("popup".to_string(), p.clone()); }
+                        
+                        payload.insert("preview".to_string(), serde_json::Value::Object(preview_data));
+                    },
+                    "add_block" if args.get("type").and_then(|v| v.as_str()).map_or(false, crate::canvas_blocks::is_block_type) => {
+                        let block_type = args.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+                        // The payload may come as `data`, or as JSON (or mermaid source) in `content`
+                        let data = match (args.get("data"), args.get("content").and_then(|v| v.as_str())) {
+                            (Some(data), _) => data.clone(),
+                            (None, Some(content)) => serde_json::from_str(content).unwrap_or_else(|_| serde_json::json!(content)),
+                            (None, None) => serde_json::Value::Null,
+                        };
+                        let markdown = match crate::canvas_blocks::block_markdown(block_type, &data) {
+                            Ok(markdown) => markdown,
+                            Err(e) => {
+                                return serde_json::json!({
                                     "success": false,
-                                    "error": format!("Tavily API error: {}", error_text)
-                                })
+                                    "error": format!("Invalid {} block: {}", block_type, e)
+                                }).to_string();
                             }
+                        };
+                        let mut block_data = serde_json::Map::new();
+                        if let Some(t) = target { block_data.insert("target".to_string(), serde_json::json!(t)); }
+                        block_data.insert("type".to_string(), serde_json::json!("md"));
+                        block_data.insert("content".to_string(), serde_json::json!(markdown));
+
+                        payload.insert("add_block".to_string(), serde_json::Value::Object(block_data));
+                    },
+                    "add_block" => {
+                        let mut block_data = serde_json::Map::new();
+                        if let Some(t) = target { block_data.insert("target".to_string(), serde_json::json!(t)); }
+                        if let Some(c) = args.get("content").and_then(|v| v.as_str()) { 
+                             // Fix Grok double-escaping newlines safely
+                            let sanitized = c.replace("\\n", "\n");
+                            block_data.insert("content".to_string(), serde_json::json!(sanitized)); 
                         }
-                        Err(e) => serde_json::json!({
+                        if let Some(t) = args.get("type") { block_data.insert("type".to_string(), t.clone()); }
+                        
+                        payload.insert("add_block".to_string(), serde_json::Value::Object(block_data));
+                    },
+                    "clear" => {
+                        let mut clear_data = serde_json::Map::new();
+                        if let Some(t) = target { clear_data.insert("target".to_string(), serde_json::json!(t)); }
+                        
+                        payload.insert("clear_canvas".to_string(), serde_json::Value::Object(clear_data));
+                    },
+                    _ => {
+                        return serde_json::json!({
                             "success": false,
-                            "error": format!("Failed to connect to Tavily API: {}", e)
-                        })
+                            "error": format!("Unknown action: {}", action)
+                        }).to_string();
                     }
+                }
+
+                // Emit event to frontend
+                if let Some(app_handle) = &self.app_handle {
+                    crate::events::emit(app_handle, "native-canvas-update", serde_json::Value::Object(payload));
+                    serde_json::json!({
+                        "success": true,
+                        "message": "Canvas update sent to frontend"
+                    }).to_string()
                 } else {
                     serde_json::json!({
                         "success": false,
-                        "error": "Missing 'query' argument"
-                    })
+                        "error": "App handle not available"
+                    }).to_string()
                 }
-            }
+            },
             Err(e) => serde_json::json!({
                 "success": false,
-                "error": format!("Invalid arguments: {}", e)
-            }),
+                "error": format!("Invalid JSON arguments: {}", e)
+            }).to_string()
         }
     }
 
-    /// TKG Search - Search semantic memory in Temporal Knowledge Graph
-    async fn tool_tkg_search_async(&self, arguments: String, user_id: String) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+    fn tool_calculate(&self, arguments: &str) -> serde_json::Value {
+        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
 
         match args {
             Ok(args) => {
-                if let Some(query_val) = args.get("query") {
-                    let query = query_val.as_str().unwrap_or("");
-                    let limit = args.get("limit")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or(5)
-                        .min(20);
-                    let _trust_threshold = args.get("trust_threshold")
-                        .and_then(|v| v.as_f64())
-                        .unwrap_or(0.5);
-
-                    // Call TKG search
-                    match tkg::tkg_search_similar(query.to_string(), limit, user_id).await {
-                        Ok(result_str) => {
-                            match serde_json::from_str(&result_str) {
-                                Ok(result_json) => result_json,
-                                Err(_) => serde_json::json!({
-                                    "success": false,
-                                    "error": "Failed to parse TKG search results"
-                                })
-                            }
+                if let Some(expression) = args.get("expression") {
+                    // Security: only allow safe characters
+                    let allowed_chars: Vec<char> = "0123456789+-*/(). ".chars().collect();
+                    if expression.chars().all(|c| allowed_chars.contains(&c)) {
+                        match meval::eval_str(expression) {
+                            Ok(result) => serde_json::json!({
+                                "success": true,
+                                "expression": expression,
+                                "result": result
+                            }),
+                            Err(e) => serde_json::json!({
+                                "success": false,
+                                "error": format!("Calculation error: {}", e)
+                            }),
                         }
-                        Err(e) => serde_json::json!({
+                    } else {
+                        serde_json::json!({
                             "success": false,
-                            "error": format!("TKG search failed: {}", e)
+                            "error": "Expression contains invalid characters"
                         })
                     }
                 } else {
                     serde_json::json!({
                         "success": false,
-                        "error": "Missing 'query' argument"
+                        "error": "Missing 'expression' argument"
                     })
                 }
             }
@@ -2087,979 +3394,932 @@ Always use the <think> tag to explain your reasoning."#.to_string();
         }
     }
 
-    /// TKG Store - Store knowledge in Temporal Knowledge Graph
-    async fn tool_tkg_store_async(&self, arguments: String, user_id: String) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+    fn tool_display_media(&self, arguments: &str) -> serde_json::Value {
+        tracing::debug!("📺 tool_display_media called with: {}", arguments);
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
 
         match args {
             Ok(args) => {
-                if let (Some(content_val), Some(node_type_val)) = (args.get("content"), args.get("node_type")) {
-                    let content = content_val.as_str().unwrap_or("");
-                    let node_type = node_type_val.as_str().unwrap_or("CONCEPT");
-                    let importance = args.get("importance")
-                        .and_then(|v| v.as_f64())
-                        .unwrap_or(0.5);
+                if let (Some(url_val), Some(type_val)) = (args.get("url"), args.get("type")) {
+                    let url = url_val.as_str().unwrap_or("");
+                    let media_type = type_val.as_str().unwrap_or("url");
 
-                    // Call TKG store
-                    match tkg::tkg_store_knowledge(content.to_string(), node_type.to_string(), importance as f32, user_id).await {
-                        Ok(result_str) => {
-                            match serde_json::from_str(&result_str) {
-                                Ok(result_json) => result_json,
-                                Err(_) => serde_json::json!({
-                                    "success": false,
-                                    "error": "Failed to parse TKG store results"
-                                })
-                            }
+                    tracing::info!("📺 Displaying media: {} (type: {})", url, media_type);
+
+                    if let Some(app_handle) = &self.app_handle {
+                         let payload = serde_json::json!({
+                            "url": url,
+                            "type": media_type,
+                            "targetId": "main" // Default to main canvas
+                        });
+                        
+                        if let Err(e) = app_handle.emit_all("canvas-split", payload) {
+                             tracing::error!("❌ Failed to emit canvas-split: {}", e);
+                             return serde_json::json!({
+                                "success": false,
+                                "error": format!("Failed to emit event: {}", e)
+                             });
                         }
-                        Err(e) => serde_json::json!({
+                    } else {
+                         tracing::error!("❌ No app_handle available");
+                         return serde_json::json!({
                             "success": false,
-                            "error": format!("TKG store failed: {}", e)
-                        })
+                            "error": "Internal error: app_handle not available"
+                         });
                     }
+
+                    serde_json::json!({
+                        "success": true,
+                        "message": format!("Displayed {} on canvas", media_type)
+                    })
                 } else {
                     serde_json::json!({
                         "success": false,
-                        "error": "Missing 'content' or 'node_type' argument"
+                        "error": "Missing url or type argument"
                     })
                 }
             }
             Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Invalid arguments: {}", e)
-            }),
+                "success": false,
+                "error": format!("Failed to parse arguments: {}", e)
+            })
         }
     }
 
-    /// Claim Legacy Data - Migrate guest data to current user
-    async fn tool_claim_legacy_data_async(&self, arguments: String, user_id: String) -> serde_json::Value {
-        if user_id == "guest" {
-             return serde_json::json!({
-                "success": false,
-                "error": "Cannot claim data while logged in as guest. Please log in first."
-            });
-        }
 
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
-        let dry_run = args
-            .as_ref()
-            .ok()
-            .and_then(|a| a.get("dry_run").and_then(|v| v.as_bool()))
-            .unwrap_or(true);
-        let confirm = args
-            .as_ref()
-            .ok()
-            .and_then(|a| a.get("confirm").and_then(|v| v.as_bool()))
-            .unwrap_or(false);
+    async fn harvest_single_page(&self, query: &str, site: &crate::mediawiki::WikiSite, mode: &str, folder_suffix: Option<&str>, display: bool) -> Result<serde_json::Value, String> {
+        tracing::info!("🚜 Harvesting '{}' from {} ({})", query, site.name, mode);
 
-        if !dry_run && !confirm {
-            return serde_json::json!({
-                "success": false,
-                "error": "Refusing to migrate without explicit confirmation. Re-run with {\"confirm\": true} (or use {\"dry_run\": true} first)."
-            });
-        }
+        // Step 1: Resolve the exact title, following interwiki prefixes to other wikis
+        let (site, title) = crate::mediawiki::resolve_title(site, query).await;
+        tracing::info!("📍 Resolved title: {} on {}", title, site.name);
 
-        // Call TKG claim legacy data (dry-run by default)
-        match tkg::tkg_claim_legacy_data(user_id, Some(dry_run)).await {
-            Ok(result_str) => serde_json::json!({
+        // Step 2: Fetch Content
+        let page = crate::mediawiki::fetch_page(&site, &title, mode == "summary").await?;
+        let file_content = crate::harvests::wiki_note(&site, &page, mode == "summary", &chrono::Local::now().format("%Y-%m-%d").to_string());
+        let (title, content) = (page.title, page.text);
+
+        // Step 3: Save to File
+        let safe_title = title.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "").replace(" ", "_");
+        let folder = if let Some(suffix) = folder_suffix {
+            format!("{}/{}", site.folder, suffix)
+        } else {
+            site.folder.clone()
+        };
+
+        if let Ok(root) = Self::get_knowledge_base_path() {
+            // A page harvested before (on its own or with a category) is updated in place
+            let full_path = crate::harvests::note_path(&root.join(&site.folder), &root.join(&folder), &safe_title, &page.url);
+            let filename = crate::kb_index::path_key(&root, &full_path);
+            if let Some(parent) = full_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+
+            crate::note_versions::record_before_write(self.app_handle.as_ref(), &root, &full_path, "harvest");
+            if let Err(e) = std::fs::write(&full_path, &file_content) {
+                 return Err(format!("Failed to save file: {}", e));
+            }
+
+            // Step 4: Auto-Display in Canvas
+            if let Some(app_handle) = self.app_handle.as_ref().filter(|_| display) {
+                 // Wrap in styled HTML for "cool" display
+                 // Since HtmlPreview uses an iframe, we need self-contained styles.
+                 let html_content = format!(r#"
+                    <!DOCTYPE html>
+                    <html>
+                    <head>
+                        <style>
+                            :root {{
+                                --bg-color: #09090b;
+                                --card-bg: rgba(24, 24, 27, 0.6);
+                                --text-primary: #e4e4e7;
+                                --text-secondary: #a1a1aa;
+                                --accent: #8b5cf6;
+                                --accent-glow: rgba(139, 92, 246, 0.3);
+                                --border: rgba(255, 255, 255, 0.1);
+                            }}
+                            body {{
+                                background-color: var(--bg-color);
+                                color: var(--text-primary);
+                                font-family: 'Inter', system-ui, -apple-system, sans-serif;
+                                margin: 0;
+                                padding: 2rem;
+                                line-height: 1.6;
+                            }}
+                            .container {{
+                                max-width: 800px;
+                                margin: 0 auto;
+                                background: var(--card-bg);
+                                border: 1px solid var(--border);
+                                border-radius: 16px;
+                                padding: 2rem;
+                                box-shadow: 0 0 40px -10px rgba(0,0,0,0.5);
+                                backdrop-filter: blur(12px);
+                                -webkit-backdrop-filter: blur(12px);
+                            }}
+                            h1 {{
+                                font-size: 2.5rem;
+                                font-weight: 800;
+                                margin-bottom: 0.5rem;
+                                background: linear-gradient(135deg, #fff 0%, #a1a1aa 100%);
+                                -webkit-background-clip: text;
+                                -webkit-text-fill-color: transparent;
+                                letter-spacing: -0.02em;
+                            }}
+                            .meta {{
+                                display: flex;
+                                align-items: center;
+                                gap: 0.5rem;
+                                color: var(--text-secondary);
+                                font-size: 0.875rem;
+                                margin-bottom: 2rem;
+                                padding-bottom: 1rem;
+                                border-bottom: 1px solid var(--border);
+                            }}
+                            .badge {{
+                                background: var(--accent-glow);
+                                color: var(--accent);
+                                padding: 0.25rem 0.75rem;
+                                border-radius: 9999px;
+                                font-size: 0.75rem;
+                                font-weight: 600;
+                                border: 1px solid rgba(139, 92, 246, 0.2);
+                            }}
+                            .content {{
+                                color: var(--text-primary);
+                            }}
+                            .content h2 {{ margin-top: 2rem; color: #fff; font-size: 1.5rem; }}
+                            .content h3 {{ margin-top: 1.5rem; color: #e4e4e7; font-size: 1.25rem; }}
+                            .content table {{ border-collapse: collapse; margin: 1rem 0; }}
+                            .content th, .content td {{ border: 1px solid rgba(255, 255, 255, 0.1); padding: 0.4rem 0.75rem; text-align: left; }}
+                            .content img {{ max-width: 100%; }}
+                            a {{ color: var(--accent); text-decoration: none; }}
+                            a:hover {{ text-decoration: underline; }}
+                        </style>
+                    </head>
+                    <body>
+                        <div class="container">
+                            <h1>{}</h1>
+                            <div class="meta">
+                                <span class="badge">WIKI HARVEST</span>
+                                <span>Source: {}</span>
+                            </div>
+                            <div class="content">{}</div>
+                        </div>
+                    </body>
+                    </html>
+                 "#, 
+                    title, 
+                    page.url,
+                    crate::markdown_render::to_html(&content, std::path::Path::new(""), &|_| None, &mut Vec::new()).0
+                 );
+                 
+                 let payload = serde_json::json!({
+                    "code": html_content,
+                    "type": "html",
+                    "targetId": "main"
+                });
+                let _ = app_handle.emit_all("canvas-split", payload);
+            }
+
+            Ok(serde_json::json!({
                 "success": true,
-                "dry_run": dry_run,
-                "message": result_str
-            }),
-            Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Migration failed: {}", e)
-            })
+                "message": format!("Harvested '{}' to {}", title, filename),
+                "path": filename,
+                "preview": content.chars().take(200).collect::<String>()
+            }))
+        } else {
+             Err("Could not find knowledge base root".to_string())
         }
     }
 
-    /// Brainstorm with Grok - Get a second perspective from Grok-4
-    async fn tool_brainstorm_with_grok_async(&self, arguments: String, grok_api_key: Option<String>) -> serde_json::Value {
+    /// The wiki named by a harvest tool's `wiki`, `lang` and `community` arguments
+    fn wiki_site_arg(args: &HashMap<String, serde_json::Value>) -> Result<crate::mediawiki::WikiSite, String> {
+        crate::mediawiki::WikiSite::resolve(
+            args.get("wiki").and_then(|v| v.as_str()).unwrap_or("rs3"),
+            args.get("lang").and_then(|v| v.as_str()),
+            args.get("community").and_then(|v| v.as_str()),
+        )
+    }
+
+    async fn tool_harvest_wiki_async(&self, arguments: String) -> serde_json::Value {
+        tracing::debug!("🚜 tool_harvest_wiki called with: {}", arguments);
         let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
 
         match args {
             Ok(args) => {
                 if let Some(query_val) = args.get("query") {
                     let query = query_val.as_str().unwrap_or("");
-                    let context = args.get("context")
-                        .and_then(|c| c.as_str())
-                        .unwrap_or("");
-
-                    // Get Grok API key from agent
-                    let grok_key = match grok_api_key {
-                        Some(key) => key,
-                        None => {
-                            eprintln!("⚠️ Grok API key not provided");
-                            return serde_json::json!({
-                                "success": false,
-                                "error": "Grok API key not configured. Please set your Grok API key in settings."
-                            });
-                        }
-                    };
-
-                    if grok_key.is_empty() {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Grok API key is empty. Please check your settings."
-                        });
-                    }
-
-                    // Call Grok API
-                    let client = reqwest::Client::new();
-                    let grok_url = "https://api.x.ai/v1/chat/completions";
-
-                    // Build the prompt for Grok
-                    let full_prompt = if !context.is_empty() {
-                        format!("Context: {}\n\nQuestion: {}\n\nPlease provide a creative, insightful response or alternative perspective.", context, query)
-                    } else {
-                        format!("{}\n\nPlease provide a creative, insightful response or alternative perspective.", query)
+                    let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("full");
+                    let site = match Self::wiki_site_arg(&args) {
+                        Ok(site) => site,
+                        Err(e) => return serde_json::json!({ "success": false, "error": e }),
                     };
 
-                    let payload = serde_json::json!({
-                        "model": "grok-4-1-fast-non-reasoning",
-                        "messages": [
-                            {
-                                "role": "system",
-                                "content": "Write in clear, native-level English with complete sentences. Avoid broken/fragmented phrasing, translation-like wording, and excessive slang. Be concise, professional, and actionable. If the user is frustrated, acknowledge it briefly and then give concrete next steps."
-                            },
-                            {
-                                "role": "user",
-                                "content": full_prompt
-                            }
-                        ],
-                        "max_tokens": 1000,
-                        "temperature": 0.8
-                    });
-
-                    eprintln!("🧠 Brainstorming with Grok: {}", query);
-
-                    match client.post(grok_url)
-                        .header("Authorization", format!("Bearer {}", grok_key))
-                        .header("Content-Type", "application/json")
-                        .json(&payload)
-                        .send()
-                        .await
-                    {
-                        Ok(response) => {
-                            if response.status().is_success() {
-                                match response.json::<serde_json::Value>().await {
-                                    Ok(grok_result) => {
-                                        eprintln!("✅ Grok brainstorming successful");
-
-                                        let grok_response = grok_result.get("choices")
-                                            .and_then(|c| c.as_array())
-                                            .and_then(|arr| arr.get(0))
-                                            .and_then(|choice| choice.get("message"))
-                                            .and_then(|msg| msg.get("content"))
-                                            .and_then(|content| content.as_str())
-                                            .unwrap_or("No response from Grok");
-
-                                        serde_json::json!({
-                                            "success": true,
-                                            "query": query,
-                                            "context": context,
-                                            "grok_perspective": grok_response,
-                                            "note": "This perspective is from Grok-4, providing a second viewpoint to enhance your thinking."
-                                        })
-                                    }
-                                    Err(e) => serde_json::json!({
-                                        "success": false,
-                                        "error": format!("Failed to parse Grok response: {}", e)
-                                    })
-                                }
-                            } else {
-                                let error_text = response.text().await.unwrap_or_else(|_| "Unknown Grok API error".to_string());
-                                serde_json::json!({
-                                    "success": false,
-                                    "error": format!("Grok API error: {}", error_text)
-                                })
-                            }
-                        }
-                        Err(e) => serde_json::json!({
-                            "success": false,
-                            "error": format!("Failed to connect to Grok API: {}", e)
-                        })
+                    match self.harvest_single_page(query, &site, mode, None, true).await {
+                        Ok(json) => json,
+                        Err(e) => serde_json::json!({ "success": false, "error": e })
                     }
                 } else {
-                    serde_json::json!({
-                        "success": false,
-                        "error": "Missing 'query' argument"
-                    })
+                    serde_json::json!({ "success": false, "error": "Missing 'query' argument" })
                 }
             }
-            Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Invalid arguments: {}", e)
-            }),
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid arguments: {}", e) })
         }
     }
 
+    async fn tool_harvest_wiki_category_async(&self, arguments: String) -> serde_json::Value {
+        tracing::debug!("🚜 tool_harvest_wiki_category called with: {}", arguments);
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
 
+        match args {
+            Ok(args) => {
+                if let Some(category_val) = args.get("category") {
+                    let category = category_val.as_str().unwrap_or("");
+                    let options = crate::category_harvest::HarvestOptions::from_args(&args);
+                    let site = match Self::wiki_site_arg(&args) {
+                        Ok(site) => site,
+                        Err(e) => return serde_json::json!({ "success": false, "error": e }),
+                    };
 
+                    // Step 1: Get Category Members
+                    let pages_to_harvest = match crate::mediawiki::category_members(&site, category, options.limit).await {
+                        Ok(pages) => pages,
+                        Err(e) => return serde_json::json!({ "success": false, "error": e }),
+                    };
 
+                    if pages_to_harvest.is_empty() {
+                         return serde_json::json!({
+                            "success": false,
+                            "error": format!("No pages found in category '{}' on {}", category, site.name)
+                        });
+                    }
 
-
-
-    fn tool_start_debate(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
-        let topic = args.as_ref().ok()
-            .and_then(|a| a.get("topic").and_then(|t| t.as_str()))
-            .unwrap_or("")
-            .to_string();
-        
-        let turns = args.as_ref().ok()
-            .and_then(|a| a.get("turns").and_then(|t| t.as_u64()))
-            .map(|t| t as usize);
-
-        if topic.is_empty() {
-            return serde_json::json!({
-                "success": false,
-                "error": "Missing 'topic' argument"
-            });
-        }
-
-        let api_key = self.api_key.clone();
-        
-        // Determine provider string
-        let provider_str = match self.provider {
-            AIProvider::Grok => Some("grok".to_string()),
-            _ => Some("minimax".to_string()),
-        };
-        
-        // Call the debate logic synchronously (blocking)
-        let result = tokio::task::block_in_place(|| {
-            tokio::runtime::Runtime::new()
-                .unwrap()
-                .block_on(async move {
-                    let req = orchestrate_agents::DebateRequest {
-                        topic,
-                        api_key,
-                        turns,
-                        provider: provider_str,
+                    // Step 2: Start the harvest, or pick up where an interrupted one left off
+                    let begun = crate::minimax_api::open_kc_database(self.app_handle.as_ref()).and_then(|conn| {
+                        crate::category_harvest::begin(&conn, &site.api_base, category, &pages_to_harvest, options.resume).map_err(|e| e.to_string())
+                    });
+                    let (harvest_id, pending) = match begun {
+                        Ok(begun) => begun,
+                        Err(e) => return serde_json::json!({ "success": false, "error": format!("Could not record harvest progress: {}", e) }),
                     };
-                    orchestrate_agents::start_agent_debate(req).await
-                })
-        });
-
-        match result {
-            Ok(response) => serde_json::json!({
-                "success": true,
-                "transcript": response.transcript,
-                "final_consensus": response.final_consensus
-            }),
-            Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Debate failed: {}", e)
-            })
-        }
-    }
+                    let total = pages_to_harvest.len();
+                    let skipped = total - pending.len();
 
-    /// Scan codebase structure
-    fn tool_scan_codebase(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
-        let (start_path, max_depth) = match args {
-            Ok(a) => (
-                a.get("path").and_then(|v| v.as_str()).unwrap_or(".").to_string(),
-                a.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(3) as usize
-            ),
-            Err(_) => (".".to_string(), 3)
-        };
+                    tracing::info!(
+                        "🚜 Found {} pages in category '{}' ({} already saved). Harvesting {} at a time, {}ms apart...",
+                        total, category, skipped, options.concurrency, options.delay_ms
+                    );
 
-        let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
-        let target_path = repo_root.join(&start_path);
+                    let safe_cat = category.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "").replace(" ", "_");
+                    let (site_ref, folder) = (&site, safe_cat.as_str());
+                    let mut harvested = futures_util::stream::iter(pending)
+                        .map(|page_title| async move {
+                            // Delay each request to respect rate limits
+                            tokio::time::sleep(tokio::time::Duration::from_millis(options.delay_ms)).await;
+                            let result = self
+                                .harvest_single_page(&page_title, site_ref, "full", Some(folder), false)
+                                .await
+                                .map(|json| json["path"].as_str().unwrap_or_default().to_string());
+                            (page_title, result)
+                        })
+                        .buffer_unordered(options.concurrency);
 
-        if !target_path.exists() {
-             return serde_json::json!({
-                "success": false,
-                "error": format!("Path does not exist: {}", start_path)
-            });
-        }
+                    let mut results = Vec::new();
+                    let mut done = skipped;
+                    while let Some((page_title, result)) = harvested.next().await {
+                        done += 1;
+                        if let Ok(conn) = crate::minimax_api::open_kc_database(self.app_handle.as_ref()) {
+                            if let Err(e) = crate::category_harvest::mark_page(&conn, harvest_id, &page_title, &result) {
+                                tracing::warn!("⚠️ Could not record progress for {}: {}", page_title, e);
+                            }
+                        }
+                        crate::category_harvest::emit_progress(self.app_handle.as_ref(), &crate::category_harvest::HarvestProgress {
+                            harvest_id,
+                            category: category.to_string(),
+                            wiki: site.name.clone(),
+                            title: page_title.clone(),
+                            status: if result.is_ok() { "saved" } else { "failed" }.to_string(),
+                            path: result.as_ref().ok().cloned(),
+                            error: result.as_ref().err().cloned(),
+                            done,
+                            total,
+                        });
+                        match result {
+                            Ok(_) => results.push(format!("✅ {}", page_title)),
+                            Err(e) => results.push(format!("❌ {}: {}", page_title, e))
+                        }
+                    }
+                    drop(harvested);
 
-        let mut files = Vec::new();
-        let mut directories = Vec::new();
+                    if let Ok(conn) = crate::minimax_api::open_kc_database(self.app_handle.as_ref()) {
+                        let _ = crate::category_harvest::finish(&conn, harvest_id);
+                    }
 
-        let walker = WalkDir::new(&target_path)
-            .max_depth(max_depth)
-            .into_iter();
+                    serde_json::json!({
+                        "success": true,
+                        "message": format!("Harvested {} pages from category '{}' ({} already saved by an earlier run)", results.len(), category, skipped),
+                        "harvest_id": harvest_id,
+                        "details": results
+                    })
 
-        for entry in walker.filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            // Basic filtering of common ignore patterns
-            !name.starts_with('.') && // Hidden files
-            name != "node_modules" && 
-            name != "target" && 
-            name != "dist" && 
-            name != "build" &&
-            name != "coverage"
-        }) {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Ok(rel_path) = path.strip_prefix(&repo_root) {
-                    let path_str = rel_path.to_string_lossy().replace("\\", "/");
-                    if path.is_dir() {
-                        directories.push(path_str);
-                    } else {
-                        files.push(path_str);
-                    }
+                } else {
+                    serde_json::json!({ "success": false, "error": "Missing 'category' argument" })
                 }
             }
+            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid arguments: {}", e) })
         }
+    }
 
-        // Sort for consistent output
-        files.sort();
-        directories.sort();
 
-        serde_json::json!({
-            "success": true,
-            "root": start_path,
-            "directories": directories,
-            "files": files,
-            "total_files": files.len(),
-            "total_directories": directories.len()
-        })
-    }
 
-    /// Helper to validate if a path is safe to write to
-    fn validate_write_scope(&self, path_str: &str) -> Result<std::path::PathBuf, String> {
-        let path = std::path::Path::new(path_str);
-        
-        // 1. Prevent absolute paths outside the project (basic check)
-        // In a real app, we'd resolve against the project root. 
-        // For now, we assume the CWD is the project root or we allow relative paths.
-        
-        // 2. Hardcoded allowlist of directories
-        let allowed_prefixes = [
-            "src/",
-            "src-tauri/",
-            "public/",
-            "docs/",
-            "generated-guides/", // Allow guides
-            "KnowledgeCompanion/", // Allow agent data
-        ];
 
-        // Normalize separators for Windows
-        let normalized_path = path_str.replace("\\", "/");
-        
-        let is_allowed = allowed_prefixes.iter().any(|prefix| normalized_path.starts_with(prefix)) 
-            || normalized_path == "README.md" 
-            || normalized_path == "package.json"; // Allow root config updates if needed
 
-        if !is_allowed {
-            return Err(format!(
-                "Security Error: Writing to '{}' is not allowed. Allowed directories: {:?}", 
-                path_str, allowed_prefixes
-            ));
-        }
+    fn tool_read_pdf(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let path = match args.get("path").and_then(|v| v.as_str()) {
+            Some(path) => path,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'path' argument"
+            }),
+        };
+        let page_range = args.get("page_range").and_then(|v| v.as_str());
 
-        // 3. Prevent traversal (../)
-        if normalized_path.contains("../") || normalized_path.contains("..\\") {
-             return Err("Security Error: Path traversal (../) is forbidden.".to_string());
+        let read = Self::get_knowledge_base_path()
+            .and_then(|root| crate::pdf_reader::kb_pdf_path(&root, path))
+            .and_then(|full_path| crate::pdf_reader::read(&full_path, page_range));
+        match read {
+            Ok(pdf) => serde_json::json!({
+                "success": true,
+                "path": path,
+                "page_count": pdf.page_count,
+                "pages": pdf.pages,
+                "next_page": pdf.next_page
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
         }
-
-        Ok(path.to_path_buf())
     }
 
-    fn tool_write_file_batch(&self, arguments: &str) -> serde_json::Value {
-        if self.safe_mode {
-            return serde_json::json!({
-                "success": false,
-                "error": "Safe Mode is enabled. File writing is disabled."
-            });
+    /// Whether `full_path` exists inside `root` once both are resolved, so `..` segments and
+    /// symlinks can't lead out of it
+    fn resolves_within(root: &std::path::Path, full_path: &std::path::Path) -> bool {
+        match (root.canonicalize(), full_path.canonicalize()) {
+            (Ok(root), Ok(resolved)) => resolved.starts_with(root),
+            _ => false,
         }
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
-        let files = args.as_ref().ok()
-            .and_then(|a| a.get("files").and_then(|f| f.as_array()));
+    }
 
-        if let Some(file_list) = files {
-            let mut results = Vec::new();
-            let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
+    fn tool_read_file(&self, arguments: &str) -> serde_json::Value {
+        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
 
-            for file_obj in file_list {
-                if let (Some(path_str), Some(content)) = (
-                    file_obj.get("path").and_then(|p| p.as_str()),
-                    file_obj.get("content").and_then(|c| c.as_str())
-                ) {
-                    // Validate Scope
-                    if let Err(e) = self.validate_write_scope(path_str) {
-                        results.push(serde_json::json!({
-                            "path": path_str,
+        match args {
+            Ok(args) => {
+                if let Some(path) = args.get("path") {
+                    // Get repository root
+                    let repo_root = match Self::get_knowledge_base_path() {
+                        Ok(root) => root,
+                        Err(e) => return serde_json::json!({
                             "success": false,
-                            "error": e
-                        }));
-                        continue;
-                    }
+                            "error": format!("Could not find repository root: {}", e)
+                        }),
+                    };
 
-                    let full_path = repo_root.join(path_str);
-                    
-                    // Security check: ensure path is within repo (redundant but safe)
-                    if !full_path.starts_with(&repo_root) {
-                        results.push(serde_json::json!({
-                            "path": path_str,
+                    // Construct full path
+                    let full_path = repo_root.join(path);
+
+                    // Security: ensure the path is within repo root and is a markdown file
+                    if !Self::resolves_within(&repo_root, &full_path) {
+                        return serde_json::json!({
                             "success": false,
-                            "error": "Path traversal detected"
-                        }));
-                        continue;
+                            "error": "Path must be an existing file within repository root"
+                        });
                     }
 
-                    // Create parent dirs
-                    if let Some(parent) = full_path.parent() {
-                        let _ = std::fs::create_dir_all(parent);
+                    if !full_path.extension().map(|e| e == "md").unwrap_or(false) {
+                        return serde_json::json!({
+                            "success": false,
+                            "error": "Only markdown (.md) files can be read"
+                        });
                     }
 
-                    match std::fs::write(&full_path, content) {
-                        Ok(_) => results.push(serde_json::json!({
-                            "path": path_str,
-                            "success": true
-                        })),
-                        Err(e) => results.push(serde_json::json!({
-                            "path": path_str,
+                    // Read the file
+                    match std::fs::read_to_string(&full_path) {
+                        Ok(content) => {
+                            let etag = crate::note_conflicts::etag_for(&content);
+                            if let Ok(mut etags) = self.read_etags.lock() {
+                                etags.insert(full_path.clone(), etag.clone());
+                            }
+                            serde_json::json!({
+                                "success": true,
+                                "path": path,
+                                "content": content,
+                                "size": content.len(),
+                                "etag": etag
+                            })
+                        }
+                        Err(e) => serde_json::json!({
                             "success": false,
-                            "error": e.to_string()
-                        }))
+                            "error": format!("Failed to read file: {}", e)
+                        }),
                     }
+                } else {
+                    serde_json::json!({
+                        "success": false,
+                        "error": "Missing 'path' argument"
+                    })
                 }
             }
-
-            serde_json::json!({
-                "success": true,
-                "results": results
-            })
-        } else {
-            serde_json::json!({
+            Err(e) => serde_json::json!({
                 "success": false,
-                "error": "Missing 'files' argument"
-            })
+                "error": format!("Invalid arguments: {}", e)
+            }),
         }
     }
 
-    fn tool_run_terminal_command(&self, arguments: &str) -> serde_json::Value {
-        if self.safe_mode {
-            return serde_json::json!({
-                "success": false,
-                "error": "Safe Mode is enabled. Terminal commands are disabled."
-            });
+    /// Ranked hits from kb_index, or None when the index is missing/empty so callers fall back to walking
+    fn search_kb_index(app_handle: Option<&tauri::AppHandle>, query: &str) -> Option<Vec<serde_json::Value>> {
+        let db_path = crate::kb_index::db_path(app_handle).filter(|p| p.exists())?;
+        let conn = crate::kb_index::open_db(&db_path).ok()?;
+        if crate::kb_index::document_count(&conn).ok()? == 0 {
+            return None;
         }
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
-        let command = args.as_ref().ok()
-            .and_then(|a| a.get("command").and_then(|c| c.as_str()))
-            .unwrap_or("");
 
-        if command.is_empty() {
-            return serde_json::json!({
-                "success": false,
-                "error": "Missing 'command' argument"
-            });
-        }
+        let hits = crate::kb_index::search(&conn, query, 10).ok()?;
+        Some(hits.into_iter().map(|hit| serde_json::json!({
+            "path": hit.path,
+            "title": hit.title,
+            "snippet": hit.snippet,
+            "score": hit.score
+        })).collect())
+    }
 
-        let repo_root = Self::get_knowledge_base_path().unwrap_or_else(|_| PathBuf::from("."));
+    fn tool_get_note_links(&self, arguments: &str) -> serde_json::Value {
+        let path = match serde_json::from_str::<serde_json::Value>(arguments)
+            .ok()
+            .and_then(|v| v.get("path").and_then(|p| p.as_str()).map(|p| p.to_string()))
+        {
+            Some(path) => path,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'path' argument"
+            }),
+        };
 
-        eprintln!("💻 Executing command: {}", command);
+        let kb_root = match Self::get_knowledge_base_path() {
+            Ok(root) => root,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Could not find knowledge base: {}", e)
+            }),
+        };
+        let key = crate::note_links::note_key(&kb_root, &path);
 
-        // Execute command (Windows)
-        let output = std::process::Command::new("cmd")
-            .args(&["/C", command])
-            .current_dir(&repo_root)
-            .output();
+        let result = crate::note_links::open_index(self.app_handle.as_ref()).and_then(|conn| {
+            let outgoing = crate::note_links::outgoing_links(&conn, &key).map_err(|e| e.to_string())?;
+            let backlinks = crate::note_links::backlinks(&conn, &key).map_err(|e| e.to_string())?;
+            Ok((outgoing, backlinks))
+        });
 
-        match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-                
-                serde_json::json!({
-                    "success": out.status.success(),
-                    "stdout": stdout,
-                    "stderr": stderr,
-                    "exit_code": out.status.code()
-                })
-            },
+        match result {
+            Ok((outgoing, backlinks)) => serde_json::json!({
+                "success": true,
+                "path": key,
+                "links_to": outgoing,
+                "linked_from": backlinks
+            }),
             Err(e) => serde_json::json!({
                 "success": false,
-                "error": format!("Failed to execute command: {}", e)
-            })
+                "error": e
+            }),
         }
     }
 
-    fn tool_canvas_update(&self, arguments: &str) -> String {
-        let args: Result<serde_json::Value, _> = serde_json::from_str(arguments);
-        match args {
-            Ok(args) => {
-                let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
-                let target = args.get("target").and_then(|v| v.as_str());
-                
-                let mut payload = serde_json::Map::new();
+    fn tool_create_flashcards(&self, arguments: &str) -> serde_json::Value {
+        #[derive(Deserialize)]
+        struct Args {
+            source_path: Option<String>,
+            cards: Vec<crate::flashcards::NewCard>,
+        }
 
-                match action {
-                    "preview" => {
-                        let mut preview_data = serde_json::Map::new();
-                        if let Some(t) = target { preview_data.insert("target".to_string(), serde_json::json!(t)); }
-                        if let Some(u) = args.get("url") { preview_data.insert("url".to_string(), u.clone()); }
-                        if let Some(c) = args.get("code").and_then(|v| v.as_str()) { 
-                            // Fix Grok double-escaping newlines safely
-                            let sanitized = c.replace("\\n", "\n");
-                            preview_data.insert("code".to_string(), serde_json::json!(sanitized)); 
-                        }
-                        if let Some(t) = args.get("type") { preview_data.insert("type".to_string(), t.clone()); }
-                        if let Some(p) = args.get("popup") { preview_data.insert("popup".to_string(), p.clone()); }
-                        
-                        payload.insert("preview".to_string(), serde_json::Value::Object(preview_data));
-                    },
-                    "add_block" => {
-                        let mut block_data = serde_json::Map::new();
-                        if let Some(t) = target { block_data.insert("target".to_string(), serde_json::json!(t)); }
-                        if let Some(c) = args.get("content").and_then(|v| v.as_str()) { 
-                             // Fix Grok double-escaping newlines safely
-                            let sanitized = c.replace("\\n", "\n");
-                            block_data.insert("content".to_string(), serde_json::json!(sanitized)); 
-                        }
-                        if let Some(t) = args.get("type") { block_data.insert("type".to_string(), t.clone()); }
-                        
-                        payload.insert("add_block".to_string(), serde_json::Value::Object(block_data));
-                    },
-                    "clear" => {
-                        let mut clear_data = serde_json::Map::new();
-                        if let Some(t) = target { clear_data.insert("target".to_string(), serde_json::json!(t)); }
-                        
-                        payload.insert("clear_canvas".to_string(), serde_json::Value::Object(clear_data));
-                    },
-                    _ => {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": format!("Unknown action: {}", action)
-                        }).to_string();
-                    }
-                }
+        let args: Args = match serde_json::from_str(arguments) {
+            Ok(a) => a,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
 
-                // Emit event to frontend
-                if let Some(app_handle) = &self.app_handle {
-                    let _ = app_handle.emit_all("native-canvas-update", serde_json::Value::Object(payload));
-                    serde_json::json!({
-                        "success": true,
-                        "message": "Canvas update sent to frontend"
-                    }).to_string()
-                } else {
-                    serde_json::json!({
-                        "success": false,
-                        "error": "App handle not available"
-                    }).to_string()
-                }
-            },
+        let stored = crate::minimax_api::open_kc_database(self.app_handle.as_ref()).and_then(|conn| {
+            crate::flashcards::insert_cards(&conn, args.source_path.as_deref(), &args.cards)
+                .map_err(|e| e.to_string())
+        });
+
+        match stored {
+            Ok(cards) => serde_json::json!({
+                "success": true,
+                "created": cards.len(),
+                "message": format!("Added {} flashcard(s); they are due for review now", cards.len())
+            }),
             Err(e) => serde_json::json!({
                 "success": false,
-                "error": format!("Invalid JSON arguments: {}", e)
-            }).to_string()
+                "error": format!("Failed to save flashcards: {}", e)
+            }),
         }
     }
 
-    fn tool_calculate(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
+    fn tool_notify(&self, arguments: &str) -> serde_json::Value {
+        #[derive(Deserialize)]
+        struct Args {
+            message: String,
+            when: Option<String>,
+        }
 
-        match args {
-            Ok(args) => {
-                if let Some(expression) = args.get("expression") {
-                    // Security: only allow safe characters
-                    let allowed_chars: Vec<char> = "0123456789+-*/(). ".chars().collect();
-                    if expression.chars().all(|c| allowed_chars.contains(&c)) {
-                        match meval::eval_str(expression) {
-                            Ok(result) => serde_json::json!({
-                                "success": true,
-                                "expression": expression,
-                                "result": result
-                            }),
-                            Err(e) => serde_json::json!({
-                                "success": false,
-                                "error": format!("Calculation error: {}", e)
-                            }),
-                        }
-                    } else {
-                        serde_json::json!({
-                            "success": false,
-                            "error": "Expression contains invalid characters"
-                        })
-                    }
-                } else {
-                    serde_json::json!({
+        let args: Args = match serde_json::from_str(arguments) {
+            Ok(a) => a,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let app_handle = match &self.app_handle {
+            Some(app_handle) => app_handle,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Notifications are only available in the desktop app"
+            }),
+        };
+
+        match crate::reminders::parse_due(&args.message, args.when.as_deref()) {
+            Some(due) => {
+                let added = crate::minimax_api::open_kc_database(Some(app_handle))
+                    .and_then(|conn| crate::reminders::add(&conn, &args.message, due, "agent"));
+                match added {
+                    Ok(reminder) => serde_json::json!({
+                        "success": true,
+                        "scheduled": true,
+                        "reminder": reminder,
+                        "message": format!("The user will be reminded of '{}' at {}", reminder.title, due.at.format("%a %b %-d, %H:%M"))
+                    }),
+                    Err(e) => serde_json::json!({
                         "success": false,
-                        "error": "Missing 'expression' argument"
-                    })
+                        "error": format!("Failed to schedule the notification: {}", e)
+                    }),
                 }
             }
-            Err(e) => serde_json::json!({
+            None if args.when.as_deref().map_or(false, |w| !w.trim().is_empty()) => serde_json::json!({
                 "success": false,
-                "error": format!("Invalid arguments: {}", e)
+                "error": format!("Couldn't tell when '{}' is; use an ISO 8601 time or words like 'tomorrow at 9am'", args.when.unwrap_or_default())
             }),
+            None => {
+                let title = crate::reminders::reminder_title(&args.message);
+                let notification = crate::notifications::notify(app_handle, crate::notifications::Category::Reminders, "🔔 Reminder", &title);
+                serde_json::json!({
+                    "success": true,
+                    "scheduled": false,
+                    "shown": notification.shown,
+                    "message": if notification.shown { "Notification sent" } else { "Sent, but the user has reminder notifications switched off" }
+                })
+            }
         }
     }
 
-    fn tool_display_media(&self, arguments: &str) -> serde_json::Value {
-        eprintln!("📺 tool_display_media called with: {}", arguments);
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+    fn tool_search_knowledge(&self, arguments: &str) -> serde_json::Value {
+        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
 
         match args {
             Ok(args) => {
-                if let (Some(url_val), Some(type_val)) = (args.get("url"), args.get("type")) {
-                    let url = url_val.as_str().unwrap_or("");
-                    let media_type = type_val.as_str().unwrap_or("url");
-
-                    eprintln!("📺 Displaying media: {} (type: {})", url, media_type);
+                if let Some(query) = args.get("query") {
+                    // Get repository root
+                    let repo_root = match Self::get_knowledge_base_path() {
+                        Ok(root) => root,
+                        Err(e) => return serde_json::json!({
+                            "success": false,
+                            "error": format!("Could not find repository root: {}", e)
+                        }),
+                    };
 
-                    if let Some(app_handle) = &self.app_handle {
-                         let payload = serde_json::json!({
-                            "url": url,
-                            "type": media_type,
-                            "targetId": "main" // Default to main canvas
+                    // Prefer the watcher-maintained full-text index when it's populated
+                    if let Some(indexed) = Self::search_kb_index(self.app_handle.as_ref(), query) {
+                        let count = indexed.len();
+                        return serde_json::json!({
+                            "success": true,
+                            "query": query,
+                            "results": indexed,
+                            "count": count
                         });
-                        
-                        if let Err(e) = app_handle.emit_all("canvas-split", payload) {
-                             eprintln!("❌ Failed to emit canvas-split: {}", e);
-                             return serde_json::json!({
-                                "success": false,
-                                "error": format!("Failed to emit event: {}", e)
-                             });
-                        }
+                    }
+
+                    // Define specific folders to search in (matching get_content_structure)
+                    let search_folders = [
+                        "research",
+                        "dumps",
+                        "developer-reference",
+                        "ai-agents",
+                        "collections",
+                        "generated-guides"
+                    ];
+                    let results = crate::kb_search::search(&repo_root, &search_folders, query, 10);
+
+                    if results.is_empty() {
+                        serde_json::json!({
+                            "success": true,
+                            "query": query,
+                            "results": [],
+                            "count": 0,
+                            "message": "No matches found in markdown files"
+                        })
                     } else {
-                         eprintln!("❌ No app_handle available");
-                         return serde_json::json!({
-                            "success": false,
-                            "error": "Internal error: app_handle not available"
-                         });
+                        serde_json::json!({
+                            "success": true,
+                            "query": query,
+                            "results": results,
+                            "count": results.len()
+                        })
                     }
-
-                    serde_json::json!({
-                        "success": true,
-                        "message": format!("Displayed {} on canvas", media_type)
-                    })
                 } else {
                     serde_json::json!({
                         "success": false,
-                        "error": "Missing url or type argument"
+                        "error": "Missing 'query' argument"
                     })
                 }
             }
             Err(e) => serde_json::json!({
                 "success": false,
-                "error": format!("Failed to parse arguments: {}", e)
-            })
+                "error": format!("Invalid arguments: {}", e)
+            }),
         }
     }
 
+    async fn tool_create_study_guide_async(&self, arguments: String, grok_api_key: Option<String>) -> serde_json::Value {
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
 
-    async fn harvest_single_page(&self, query: &str, wiki: &str, mode: &str, folder_suffix: Option<&str>) -> Result<serde_json::Value, String> {
-        let api_base = if wiki == "osrs" {
-            "https://oldschool.runescape.wiki/api.php"
-        } else {
-            "https://runescape.wiki/api.php"
-        };
-
-        eprintln!("🚜 Harvesting '{}' from {} ({})", query, wiki, mode);
-
-        let client = reqwest::Client::builder()
-            .user_agent("InformationHordehole/1.0 (internal-research-agent; contact: admin@localhost)")
-            .build()
-            .map_err(|e| e.to_string())?;
-
-        // Step 1: OpenSearch to get exact title
-        let search_url = format!("{}?action=opensearch&search={}&limit=1&format=json", api_base, urlencoding::encode(query));
-        
-        let title = match client.get(&search_url).send().await {
-            Ok(resp) => {
-                if let Ok(json) = resp.json::<serde_json::Value>().await {
-                    if let Some(array) = json.as_array() {
-                        if let Some(titles) = array.get(1).and_then(|v| v.as_array()) {
-                            if let Some(first_title) = titles.get(0).and_then(|v| v.as_str()) {
-                                first_title.to_string()
-                            } else {
-                                query.to_string() // Fallback to query
-                            }
-                        } else { query.to_string() }
-                    } else { query.to_string() }
-                } else { query.to_string() }
-            }
-            Err(_) => query.to_string()
-        };
+        match args {
+            Ok(args) => {
+                let topic = match args.get("topic").and_then(|v| v.as_str()) {
+                    Some(t) => t.to_string(),
+                    None => {
+                        return serde_json::json!({
+                            "success": false,
+                            "error": "Missing 'topic' argument"
+                        })
+                    }
+                };
 
-        eprintln!("📍 Resolved title: {}", title);
+                let difficulty = match args.get("difficulty").and_then(|v| v.as_str()) {
+                    Some(d) => d.to_string(),
+                    None => {
+                        return serde_json::json!({
+                            "success": false,
+                            "error": "Missing 'difficulty' argument"
+                        })
+                    }
+                };
 
-        // Step 2: Fetch Content
-        let content_url = format!("{}?action=query&prop=extracts&explaintext=1&titles={}&format=json&redirects=1", 
-            api_base, 
-            urlencoding::encode(&title)
-        );
+                let include_resources = args
+                    .get("include_resources")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
 
-        let content = match client.get(&content_url).send().await {
-            Ok(resp) => {
-                if let Ok(json) = resp.json::<serde_json::Value>().await {
-                    let mut extracted_text = String::new();
-                    if let Some(query_obj) = json.get("query") {
-                        if let Some(pages) = query_obj.get("pages").and_then(|v| v.as_object()) {
-                            for (_, page) in pages {
-                                if let Some(extract) = page.get("extract").and_then(|v| v.as_str()) {
-                                    extracted_text = extract.to_string();
-                                    break; 
-                                }
-                            }
-                        }
+                let grok_key = match grok_api_key {
+                    Some(key) => key,
+                    None => {
+                        tracing::warn!("⚠️ Grok API key not provided");
+                        return serde_json::json!({
+                            "success": false,
+                            "error": "Grok API key not configured. Please set your Grok API key in settings."
+                        });
                     }
-                    extracted_text
-                } else {
-                    String::new()
-                }
-            }
-            Err(e) => return Err(format!("Failed to fetch content: {}", e))
-        };
+                };
 
-        if content.is_empty() {
-             return Err(format!("No content found for '{}' on {}", title, wiki));
-        }
+                if grok_key.is_empty() {
+                    return serde_json::json!({
+                        "success": false,
+                        "error": "Grok API key is empty. Please check your settings."
+                    });
+                }
 
-        // Step 3: Save to File
-        let safe_title = title.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "").replace(" ", "_");
-        let base_folder = if wiki == "osrs" { "research/osrs" } else { "research/rs3" };
-        let folder = if let Some(suffix) = folder_suffix {
-            format!("{}/{}", base_folder, suffix)
-        } else {
-            base_folder.to_string()
-        };
-        
-        let filename = format!("{}/{}.md", folder, safe_title);
-        let file_content = format!("# {}\n\nSource: {}/w/{}\n\n{}\n", title, api_base.replace("/api.php", ""), urlencoding::encode(&title), content);
+                let prompt = format!(
+                    "Create a comprehensive study guide for '{}' at {} level. {}Provide structured markdown with sections, resources, and practice exercises.",
+                    topic,
+                    difficulty,
+                    if include_resources { "Include specific resources and practice exercises. " } else { "" }
+                );
 
-        if let Ok(root) = Self::get_knowledge_base_path() {
-            let full_path = root.join(&filename);
-            if let Some(parent) = full_path.parent() {
-                let _ = std::fs::create_dir_all(parent);
-            }
-            
-            if let Err(e) = std::fs::write(&full_path, &file_content) {
-                 return Err(format!("Failed to save file: {}", e));
-            }
+                let client = self.http.client();
+                let grok_url = "https://api.x.ai/v1/chat/completions";
 
-            // Step 4: Auto-Display in Canvas
-            if let Some(app_handle) = &self.app_handle {
-                 // Wrap in styled HTML for "cool" display
-                 // Since HtmlPreview uses an iframe, we need self-contained styles.
-                 let html_content = format!(r#"
-                    <!DOCTYPE html>
-                    <html>
-                    <head>
-                        <style>
-                            :root {{
-                                --bg-color: #09090b;
-                                --card-bg: rgba(24, 24, 27, 0.6);
-                                --text-primary: #e4e4e7;
-                                --text-secondary: #a1a1aa;
-                                --accent: #8b5cf6;
-                                --accent-glow: rgba(139, 92, 246, 0.3);
-                                --border: rgba(255, 255, 255, 0.1);
-                            }}
-                            body {{
-                                background-color: var(--bg-color);
-                                color: var(--text-primary);
-                                font-family: 'Inter', system-ui, -apple-system, sans-serif;
-                                margin: 0;
-                                padding: 2rem;
-                                line-height: 1.6;
-                            }}
-                            .container {{
-                                max-width: 800px;
-                                margin: 0 auto;
-                                background: var(--card-bg);
-                                border: 1px solid var(--border);
-                                border-radius: 16px;
-                                padding: 2rem;
-                                box-shadow: 0 0 40px -10px rgba(0,0,0,0.5);
-                                backdrop-filter: blur(12px);
-                                -webkit-backdrop-filter: blur(12px);
-                            }}
-                            h1 {{
-                                font-size: 2.5rem;
-                                font-weight: 800;
-                                margin-bottom: 0.5rem;
-                                background: linear-gradient(135deg, #fff 0%, #a1a1aa 100%);
-                                -webkit-background-clip: text;
-                                -webkit-text-fill-color: transparent;
-                                letter-spacing: -0.02em;
-                            }}
-                            .meta {{
-                                display: flex;
-                                align-items: center;
-                                gap: 0.5rem;
-                                color: var(--text-secondary);
-                                font-size: 0.875rem;
-                                margin-bottom: 2rem;
-                                padding-bottom: 1rem;
-                                border-bottom: 1px solid var(--border);
-                            }}
-                            .badge {{
-                                background: var(--accent-glow);
-                                color: var(--accent);
-                                padding: 0.25rem 0.75rem;
-                                border-radius: 9999px;
-                                font-size: 0.75rem;
-                                font-weight: 600;
-                                border: 1px solid rgba(139, 92, 246, 0.2);
-                            }}
-                            .content {{
-                                white-space: pre-wrap;
-                                color: var(--text-primary);
-                            }}
-                            /* Markdown-like styling for the raw text */
-                            .content h2 {{ margin-top: 2rem; color: #fff; font-size: 1.5rem; }}
-                            .content h3 {{ margin-top: 1.5rem; color: #e4e4e7; font-size: 1.25rem; }}
-                            a {{ color: var(--accent); text-decoration: none; }}
-                            a:hover {{ text-decoration: underline; }}
-                        </style>
-                    </head>
-                    <body>
-                        <div class="container">
-                            <h1>{}</h1>
-                            <div class="meta">
-                                <span class="badge">WIKI HARVEST</span>
-                                <span>Source: {}</span>
-                            </div>
-                            <div class="content">{}</div>
-                        </div>
-                    </body>
-                    </html>
-                 "#, 
-                    title, 
-                    api_base.replace("/api.php", ""),
-                    content
-                        .replace("== ", "<h2>").replace(" ==", "</h2>") // Basic header parsing
-                        .replace("=== ", "<h3>").replace(" ===", "</h3>")
-                 );
-                 
-                 let payload = serde_json::json!({
-                    "code": html_content,
-                    "type": "html",
-                    "targetId": "main"
+                let payload = serde_json::json!({
+                    "model": "grok-4-1-fast-non-reasoning",
+                    "messages": [
+                        {
+                            "role": "user",
+                            "content": prompt
+                        }
+                    ],
+                    "max_tokens": 8000,
+                    "temperature": 0.6
                 });
-                let _ = app_handle.emit_all("canvas-split", payload);
-            }
-
-            Ok(serde_json::json!({
-                "success": true,
-                "message": format!("Harvested '{}' to {}", title, filename),
-                "path": filename,
-                "preview": content.chars().take(200).collect::<String>()
-            }))
-        } else {
-             Err("Could not find knowledge base root".to_string())
-        }
-    }
 
-    async fn tool_harvest_wiki_async(&self, arguments: String) -> serde_json::Value {
-        eprintln!("🚜 tool_harvest_wiki called with: {}", arguments);
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+                tracing::info!("🧠 Generating study guide with Grok: {} [{}]", topic, difficulty);
 
-        match args {
-            Ok(args) => {
-                if let Some(query_val) = args.get("query") {
-                    let query = query_val.as_str().unwrap_or("");
-                    let wiki = args.get("wiki").and_then(|v| v.as_str()).unwrap_or("rs3");
-                    let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("full");
+                match client.post(grok_url)
+                    .header("Authorization", format!("Bearer {}", grok_key))
+                    .header("Content-Type", "application/json")
+                    .json(&payload)
+                    .send_checked()
+                    .await
+                {
+                    Ok(response) => {
+                        if response.status().is_success() {
+                            match response.json::<serde_json::Value>().await {
+                                Ok(grok_result) => {
+                                    let grok_response = grok_result.get("choices")
+                                        .and_then(|c| c.as_array())
+                                        .and_then(|arr| arr.get(0))
+                                        .and_then(|choice| choice.get("message"))
+                                        .and_then(|msg| msg.get("content"))
+                                        .and_then(|content| content.as_str())
+                                        .unwrap_or("No response from Grok");
 
-                    match self.harvest_single_page(query, wiki, mode, None).await {
-                        Ok(json) => json,
-                        Err(e) => serde_json::json!({ "success": false, "error": e })
+                                    serde_json::json!({
+                                        "success": true,
+                                        "topic": topic,
+                                        "difficulty": difficulty,
+                                        "include_resources": include_resources,
+                                        "guide": grok_response
+                                    })
+                                }
+                                Err(e) => serde_json::json!({
+                                    "success": false,
+                                    "error": format!("Failed to parse Grok response: {}", e)
+                                })
+                            }
+                        } else {
+                            let error_text = response.text().await.unwrap_or_else(|_| "Unknown Grok API error".to_string());
+                            serde_json::json!({
+                                "success": false,
+                                "error": format!("Grok API error: {}", error_text)
+                            })
+                        }
                     }
-                } else {
-                    serde_json::json!({ "success": false, "error": "Missing 'query' argument" })
+                    Err(e) => serde_json::json!({
+                        "success": false,
+                        "error": format!("Request failed: {}", e)
+                    })
                 }
             }
-            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid arguments: {}", e) })
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
         }
     }
 
-    async fn tool_harvest_wiki_category_async(&self, arguments: String) -> serde_json::Value {
-        eprintln!("🚜 tool_harvest_wiki_category called with: {}", arguments);
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
+    fn tool_list_markdown_files(&self, arguments: &str) -> serde_json::Value {
+        let args: HashMap<String, serde_json::Value> = serde_json::from_str(arguments).unwrap_or_default();
 
-        match args {
-            Ok(args) => {
-                if let Some(category_val) = args.get("category") {
-                    let category = category_val.as_str().unwrap_or("");
-                    let wiki = args.get("wiki").and_then(|v| v.as_str()).unwrap_or("rs3");
-                    let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10).min(50);
+        let folder_filter = args.get("folder").and_then(|v| v.as_str()).map(|f| f.to_string());
+        let force_refresh = args.get("force_refresh").and_then(|v| v.as_bool()).unwrap_or(false);
 
-                    let api_base = if wiki == "osrs" {
-                        "https://oldschool.runescape.wiki/api.php"
-                    } else {
-                        "https://runescape.wiki/api.php"
-                    };
+        // Get repository root
+        let repo_root = match Self::get_knowledge_base_path() {
+            Ok(root) => root,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Could not find repository root: {}", e)
+            }),
+        };
 
-                    let client = reqwest::Client::builder()
-                        .user_agent("InformationHordehole/1.0 (internal-research-agent; contact: admin@localhost)")
-                        .build()
-                        .unwrap_or_default();
+        let search_path = if let Some(folder) = &folder_filter {
+            repo_root.join(folder)
+        } else {
+            repo_root.clone()
+        };
 
-                    // Step 1: Get Category Members
-                    let cat_url = format!("{}?action=query&list=categorymembers&cmtitle=Category:{}&cmlimit={}&format=json", 
-                        api_base, 
-                        urlencoding::encode(category),
-                        limit
-                    );
+        if !search_path.exists() {
+            return serde_json::json!({
+                "success": false,
+                "error": format!("Folder not found: {}", search_path.display())
+            });
+        }
 
-                    let mut pages_to_harvest = Vec::new();
-                    if let Ok(resp) = client.get(&cat_url).send().await {
-                        if let Ok(json) = resp.json::<serde_json::Value>().await {
-                            if let Some(query) = json.get("query") {
-                                if let Some(members) = query.get("categorymembers").and_then(|v| v.as_array()) {
-                                    for member in members {
-                                        if let Some(title) = member.get("title").and_then(|v| v.as_str()) {
-                                            pages_to_harvest.push(title.to_string());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // Everything under the folder, cached until the content watcher sees a change
+        let mut files = MARKDOWN_LISTINGS
+            .get_or_build(&search_path, force_refresh, || Ok(Self::walk_markdown_files(&repo_root, &search_path)))
+            .unwrap_or_default();
 
-                    if pages_to_harvest.is_empty() {
-                         return serde_json::json!({
-                            "success": false,
-                            "error": format!("No pages found in category '{}' on {}", category, wiki)
-                        });
-                    }
+    // Limit results to prevent context overflow (e.g., max 500 files)
+    let total_count = files.len();
+    if total_count > 500 {
+        files.truncate(500);
+    }
 
-                    eprintln!("🚜 Found {} pages in category '{}'. Starting harvest...", pages_to_harvest.len(), category);
+    serde_json::json!({
+        "success": true,
+        "files": files,
+        "count": files.len(),
+        "total_found": total_count,
+        "folder": folder_filter.unwrap_or_else(|| "root".to_string()),
+        "message": if total_count > 500 { "Result truncated to first 500 files" } else { "Success" }
+    })
+}
 
-                    let mut results = Vec::new();
-                    let safe_cat = category.replace(|c: char| !c.is_alphanumeric() && c != ' ' && c != '-', "").replace(" ", "_");
+    /// Markdown files under `search_path`, relative to `repo_root`, sorted
+    fn walk_markdown_files(repo_root: &std::path::Path, search_path: &std::path::Path) -> Vec<String> {
+        let mut files = Vec::new();
 
-                    for page_title in pages_to_harvest {
-                        // Add delay to respect rate limits
-                        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-                        
-                        match self.harvest_single_page(&page_title, wiki, "full", Some(&safe_cat)).await {
-                            Ok(_) => results.push(format!("✅ {}", page_title)),
-                            Err(e) => results.push(format!("❌ {}: {}", page_title, e))
-                        }
-                    }
+        // Define ignored directories
+        let ignored_dirs = ["node_modules", "target", ".git", ".vscode", "dist", "build", "coverage"];
 
-                    serde_json::json!({
-                        "success": true,
-                        "message": format!("Harvested {} pages from category '{}'", results.len(), category),
-                        "details": results
-                    })
+        for entry in WalkDir::new(search_path)
+            .follow_links(false) // Disable following links to prevent loops/external walks
+            .into_iter()
+            .filter_entry(|e| {
+                let file_name = e.file_name().to_string_lossy();
+                // Skip hidden files/dirs (starting with .) but allow the search path itself
+                if file_name.starts_with('.') && e.depth() > 0 {
+                    return false;
+                }
+                // Skip ignored directories
+                if e.file_type().is_dir() && ignored_dirs.contains(&file_name.as_ref()) {
+                    return false;
+                }
+                true
+            })
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
 
-                } else {
-                    serde_json::json!({ "success": false, "error": "Missing 'category' argument" })
+            if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
+                if let Ok(relative_path) = path.strip_prefix(repo_root) {
+                    // Normalize path separators to forward slashes
+                    let path_str = relative_path.to_string_lossy().replace('\\', "/");
+                    files.push(path_str);
                 }
             }
-            Err(e) => serde_json::json!({ "success": false, "error": format!("Invalid arguments: {}", e) })
         }
-    }
 
+        // Sort alphabetically
+        files.sort();
+        files
+    }
 
+    /// A window of a tool result that was stored out of line
+    fn tool_read_tool_result(&self, arguments: &str) -> serde_json::Value {
+        let args: HashMap<String, serde_json::Value> = serde_json::from_str(arguments).unwrap_or_default();
+        let result_id = match args.get("result_id").and_then(|v| v.as_str()) {
+            Some(id) => id,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing required parameter: result_id"
+            }),
+        };
+        let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let length = args.get("length").and_then(|v| v.as_u64()).map_or(crate::tool_results::MAX_READ, |l| l as usize);
 
+        let dir = match crate::tool_results::results_dir(self.app_handle.as_ref()) {
+            Some(dir) => dir,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "No data folder for stored tool results"
+            }),
+        };
+        crate::tool_results::read_in(&dir, result_id, offset, length)
+            .unwrap_or_else(|e| serde_json::json!({ "success": false, "error": e }))
+    }
 
+    fn tool_write_file(&self, arguments: &str) -> serde_json::Value {
+        tracing::debug!("🔧 write_file tool called with arguments: {}", arguments);
 
-    fn tool_read_file(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
+        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
 
         match args {
             Ok(args) => {
-                if let Some(path) = args.get("path") {
+                tracing::debug!("✅ Parsed arguments successfully");
+                if let (Some(path_val), Some(content_val)) = (args.get("path"), args.get("content")) {
+                    let path = path_val.as_str().unwrap_or("");
+                    let content = content_val.as_str().unwrap_or("");
+                    let append = args.get("append").and_then(|v| v.as_bool()).unwrap_or(false);
+
+                    // Validate Scope
+                    if let Err(e) = self.validate_write_scope(path) {
+                        return serde_json::json!({
+                            "success": false,
+                            "error": e
+                        });
+                    }
+                    tracing::debug!("📝 Path: {}, Content length: {}, Append: {}", path, content.len(), append);
+
                     // Get repository root
                     let repo_root = match Self::get_knowledge_base_path() {
                         Ok(root) => root,
@@ -3072,7 +4332,7 @@ Always use the <think> tag to explain your reasoning."#.to_string();
                     // Construct full path
                     let full_path = repo_root.join(path);
 
-                    // Security: ensure the path is within repo root and is a markdown file
+                    // Security: ensure the path is within repo root
                     if !full_path.starts_with(&repo_root) {
                         return serde_json::json!({
                             "success": false,
@@ -3080,200 +4340,83 @@ Always use the <think> tag to explain your reasoning."#.to_string();
                         });
                     }
 
-                    if !full_path.extension().map(|e| e == "md").unwrap_or(false) {
+                    // Check if file extension is allowed (support multiple types)
+                    let allowed_extensions = ["md", "txt", "json", "csv", "html", "js", "css", "tsx", "jsx", "rs", "toml", "yaml", "yml"];
+                    let file_ext = full_path.extension()
+                        .and_then(|e| e.to_str())
+                        .unwrap_or("");
+
+                    if !allowed_extensions.contains(&file_ext) {
+                        tracing::warn!("⚠️ Writing file with extension '{}'. Allowed: {:?}", file_ext, allowed_extensions);
+                        // Don't block - just warn in logs
+                    }
+
+                    // Don't clobber edits made since this file was read (e.g. by the user in the editor)
+                    let explicit_etag = args.get("expected_etag").and_then(|v| v.as_str());
+                    if let Err(conflict) = crate::note_conflicts::check_expected(&full_path, self.expected_etag(&full_path, explicit_etag).as_deref()) {
                         return serde_json::json!({
                             "success": false,
-                            "error": "Only markdown (.md) files can be read"
+                            "error": conflict.to_string(),
+                            "current_etag": conflict.current_etag
                         });
                     }
 
-                    // Read the file
-                    match std::fs::read_to_string(&full_path) {
-                        Ok(content) => serde_json::json!({
-                            "success": true,
-                            "path": path,
-                            "content": content,
-                            "size": content.len()
-                        }),
-                        Err(e) => serde_json::json!({
-                            "success": false,
-                            "error": format!("Failed to read file: {}", e)
-                        }),
+                    if self.dry_run {
+                        return crate::dry_run::planned_write(&full_path, path, content, append);
                     }
-                } else {
-                    serde_json::json!({
-                        "success": false,
-                        "error": "Missing 'path' argument"
-                    })
-                }
-            }
-            Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Invalid arguments: {}", e)
-            }),
-        }
-    }
-
-    fn tool_search_knowledge(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
-
-        match args {
-            Ok(args) => {
-                if let Some(query) = args.get("query") {
-                    // Get repository root
-                    let repo_root = match Self::get_knowledge_base_path() {
-                        Ok(root) => root,
-                        Err(e) => return serde_json::json!({
-                            "success": false,
-                            "error": format!("Could not find repository root: {}", e)
-                        }),
-                    };
-
-                    // Search for query in markdown files
-                    let mut results = Vec::new();
-                    let query_lower = query.to_lowercase();
-                    let query_tokens: Vec<&str> = query_lower.split_whitespace().collect();
-
-                    // Define specific folders to search in (matching get_content_structure)
-                    let search_folders = vec![
-                        "research",
-                        "dumps",
-                        "developer-reference",
-                        "ai-agents",
-                        "collections",
-                        "generated-guides"
-                    ];
 
-                    for folder in search_folders {
-                        let folder_path = repo_root.join(folder);
-                        if !folder_path.exists() {
-                            continue;
+                    // Create parent directories if they don't exist
+                    if let Some(parent) = full_path.parent() {
+                        if let Err(e) = std::fs::create_dir_all(parent) {
+                            return serde_json::json!({
+                                "success": false,
+                                "error": format!("Failed to create directory: {}", e)
+                            });
                         }
+                    }
 
-                        for entry in WalkDir::new(&folder_path)
-                            .follow_links(true)
-                            .into_iter()
-                            .filter_map(|e| e.ok())
-                        {
-                            let path = entry.path();
-
-                            if !path.is_file() || !path.extension().map(|e| e == "md").unwrap_or(false) {
-                                continue;
-                            }
-
-                            if let Ok(content) = std::fs::read_to_string(path) {
-                                let content_lower = content.to_lowercase();
-                                let filename = path.file_name()
-                                    .and_then(|n| n.to_str())
-                                    .unwrap_or("Unknown")
-                                    .to_lowercase();
-
-                                // Scoring system:
-                                // - Exact phrase match in content: +10
-                                // - Exact phrase match in filename: +20
-                                // - Each token match in content: +1
-                                // - Each token match in filename: +2
-                                
-                                let mut score = 0;
-                                let mut matched_tokens = 0;
-
-                                // Check exact phrase
-                                if content_lower.contains(&query_lower) {
-                                    score += 10;
-                                }
-                                if filename.contains(&query_lower) {
-                                    score += 20;
-                                }
-
-                                // Check tokens
-                                for token in &query_tokens {
-                                    if content_lower.contains(token) {
-                                        score += 1;
-                                        matched_tokens += 1;
-                                    }
-                                    if filename.contains(token) {
-                                        score += 2;
-                                    }
-                                }
+                    // Keep the previous content so the write can be undone
+                    crate::note_versions::record_before_write(self.app_handle.as_ref(), &repo_root, &full_path, "agent");
 
-                                // Require at least one token match or exact match
-                                if score > 0 {
-                                    let title = path.file_name()
-                                        .and_then(|n| n.to_str())
-                                        .unwrap_or("Unknown")
-                                        .to_string();
+                    // Write the file
+                    let write_result = if append {
+                        std::fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .write(true)
+                            .open(&full_path)
+                            .and_then(|mut file| std::io::Write::write_all(&mut file, content.as_bytes()))
+                    } else {
+                        std::fs::write(&full_path, content)
+                    };
 
-                                    // Extract snippet
-                                    // Prefer exact match snippet, otherwise first token match
-                                    let snippet_pos = if let Some(pos) = content_lower.find(&query_lower) {
-                                        pos
-                                    } else {
-                                        // Find first matching token
-                                        let mut first_pos = 0;
-                                        for token in &query_tokens {
-                                            if let Some(pos) = content_lower.find(token) {
-                                                first_pos = pos;
-                                                break;
-                                            }
-                                        }
-                                        first_pos
-                                    };
+                    match write_result {
+                        Ok(_) => {
+                            let file_size = content.len();
+                            self.remember_etag(&full_path);
 
-                                    let start = snippet_pos.saturating_sub(50);
-                                    let end = (snippet_pos + 150).min(content.len());
-                                    let snippet = content.get(start..end).unwrap_or("").to_string();
-
-                                    // Calculate relative path for cleaner output and easier file reading
-                                    let relative_path = path.strip_prefix(&repo_root)
-                                        .unwrap_or(path)
-                                        .to_string_lossy()
-                                        .to_string()
-                                        .replace("\\", "/"); // Normalize to forward slashes
-
-                                    results.push(serde_json::json!({
-                                        "path": relative_path,
-                                        "title": title,
-                                        "snippet": snippet,
-                                        "score": score,
-                                        "matches": matched_tokens
-                                    }));
-                                }
+                            // Emit event to refresh UI
+                            if let Some(ref handle) = self.app_handle {
+                                crate::events::emit(handle, "content-changed", ());
                             }
-                        }
-                    }
-
-                    // Sort by score descending
-                    results.sort_by(|a, b| {
-                        let a_score = a["score"].as_i64().unwrap_or(0);
-                        let b_score = b["score"].as_i64().unwrap_or(0);
-                        b_score.cmp(&a_score)
-                    });
-
-                    // Limit to top 10 results
-                    if results.len() > 10 {
-                        results.truncate(10);
-                    }
-
-                    if results.is_empty() {
-                        serde_json::json!({
-                            "success": true,
-                            "query": query,
-                            "results": [],
-                            "count": 0,
-                            "message": "No matches found in markdown files"
-                        })
-                    } else {
-                        serde_json::json!({
-                            "success": true,
-                            "query": query,
-                            "results": results,
-                            "count": results.len()
-                        })
+
+                            serde_json::json!({
+                                "success": true,
+                                "path": path,
+                                "size": file_size,
+                                "operation": if append { "append" } else { "write" },
+                                "message": format!("Successfully {} file at {}", if append { "appended to" } else { "wrote" }, path)
+                            })
+                        }
+                        Err(e) => serde_json::json!({
+                            "success": false,
+                            "error": format!("Failed to write file: {}", e)
+                        }),
                     }
                 } else {
                     serde_json::json!({
                         "success": false,
-                        "error": "Missing 'query' argument"
+                        "error": "Missing 'path' or 'content' argument"
                     })
                 }
             }
@@ -3284,331 +4427,696 @@ Always use the <think> tag to explain your reasoning."#.to_string();
         }
     }
 
-    async fn tool_create_study_guide_async(&self, arguments: String, grok_api_key: Option<String>) -> serde_json::Value {
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(&arguments);
-
-        match args {
-            Ok(args) => {
-                let topic = match args.get("topic").and_then(|v| v.as_str()) {
-                    Some(t) => t.to_string(),
-                    None => {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Missing 'topic' argument"
-                        })
-                    }
-                };
-
-                let difficulty = match args.get("difficulty").and_then(|v| v.as_str()) {
-                    Some(d) => d.to_string(),
-                    None => {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Missing 'difficulty' argument"
-                        })
-                    }
-                };
+    async fn tool_ingest_paper(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let input = match args.get("url_or_path").and_then(|v| v.as_str()) {
+            Some(input) => input,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'url_or_path' argument"
+            }),
+        };
 
-                let include_resources = args
-                    .get("include_resources")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(true);
+        let paper = match self.ingest_research_paper(input).await {
+            Ok(paper) => paper,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        };
+        tracing::info!("📄 Ingested paper: {} ({} sections)", paper.title, paper.sections.len());
 
-                let grok_key = match grok_api_key {
-                    Some(key) => key,
-                    None => {
-                        eprintln!("⚠️ Grok API key not provided");
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Grok API key not configured. Please set your Grok API key in settings."
-                        });
-                    }
-                };
+        let tkg_nodes_stored = if args.get("store_in_tkg").and_then(|v| v.as_bool()).unwrap_or(true) {
+            crate::papers::store_in_tkg(&paper, &self.user_id).await.ok()
+        } else {
+            None
+        };
 
-                if grok_key.is_empty() {
-                    return serde_json::json!({
-                        "success": false,
-                        "error": "Grok API key is empty. Please check your settings."
-                    });
-                }
+        serde_json::json!({
+            "success": true,
+            "title": paper.title,
+            "authors": paper.authors,
+            "published": paper.published,
+            "url": paper.url,
+            "abstract": paper.abstract_text,
+            "sections": paper.sections.iter().map(|s| s.heading.clone()).collect::<Vec<_>>(),
+            "content": paper.to_source().content,
+            "tkg_nodes_stored": tkg_nodes_stored
+        })
+    }
 
-                let prompt = format!(
-                    "Create a comprehensive study guide for '{}' at {} level. {}Provide structured markdown with sections, resources, and practice exercises.",
-                    topic,
-                    difficulty,
-                    if include_resources { "Include specific resources and practice exercises. " } else { "" }
-                );
+    async fn tool_clip_url(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let url = match args.get("url").and_then(|v| v.as_str()) {
+            Some(url) => url,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'url' argument"
+            }),
+        };
+        let display = args.get("display").and_then(|v| v.as_bool()).unwrap_or(false);
 
-                let client = reqwest::Client::new();
-                let grok_url = "https://api.x.ai/v1/chat/completions";
+        match crate::clipper::clip(self.app_handle.as_ref(), url, display, &[]).await {
+            Ok(clip) => serde_json::json!({
+                "success": true,
+                "message": format!("Clipped '{}' to {}", clip.title, clip.path),
+                "title": clip.title,
+                "path": clip.path,
+                "preview": clip.markdown.chars().take(500).collect::<String>()
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        }
+    }
 
-                let payload = serde_json::json!({
-                    "model": "grok-4-1-fast-non-reasoning",
-                    "messages": [
-                        {
-                            "role": "user",
-                            "content": prompt
-                        }
-                    ],
-                    "max_tokens": 8000,
-                    "temperature": 0.6
-                });
+    async fn tool_translate(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(|s| s.to_string());
+        let target = match str_arg("target_language") {
+            Some(target) => target,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'target_language' argument"
+            }),
+        };
+        let save = args.get("save").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        // A tool-less agent on this conversation's provider for each chunk
+        let agent_for = |prompt: String| {
+            MinimaxAgent::new(self.api_key.clone(), None, self.grok_api_key.clone(), self.gemini_api_key.clone())
+                .with_provider(self.provider.clone())
+                .with_enabled_tools(HashMap::new())
+                .with_system_prompt(prompt)
+        };
+        let translated = crate::translate::translate(
+            self.app_handle.as_ref(),
+            agent_for,
+            str_arg("text"),
+            str_arg("path"),
+            str_arg("source_language"),
+            &target,
+            save,
+        )
+        .await;
+        match translated {
+            Ok(translation) => serde_json::json!({
+                "success": true,
+                "target_language": translation.target_language,
+                "saved_to": translation.saved_to,
+                "translation": translation.text
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        }
+    }
 
-                eprintln!("🧠 Generating study guide with Grok: {} [{}]", topic, difficulty);
+    async fn tool_capture_screenshot(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let region: Option<crate::screenshot::Region> = match args.get("region").filter(|r| !r.is_null()) {
+            Some(region) => match serde_json::from_value(region.clone()) {
+                Ok(region) => Some(region),
+                Err(e) => return serde_json::json!({
+                    "success": false,
+                    "error": format!("Invalid 'region': {}", e)
+                }),
+            },
+            None => None,
+        };
+        let select = args.get("select").and_then(|v| v.as_bool()).unwrap_or(region.is_none());
+        let engine = args
+            .get("ocr")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        let languages = args.get("languages").and_then(|v| v.as_str()).map(|l| l.to_string());
+
+        match crate::screenshot::capture(self.app_handle.as_ref(), region, select, engine, languages, self.grok_api_key.as_deref()).await {
+            Ok(shot) => serde_json::json!({
+                "success": true,
+                "message": format!("Saved the screenshot to {} (text read with {})", shot.path, shot.ocr),
+                "path": shot.path,
+                "image": shot.image,
+                "text": shot.text
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        }
+    }
 
-                match client.post(grok_url)
-                    .header("Authorization", format!("Bearer {}", grok_key))
-                    .header("Content-Type", "application/json")
-                    .json(&payload)
-                    .send()
-                    .await
-                {
-                    Ok(response) => {
-                        if response.status().is_success() {
-                            match response.json::<serde_json::Value>().await {
-                                Ok(grok_result) => {
-                                    let grok_response = grok_result.get("choices")
-                                        .and_then(|c| c.as_array())
-                                        .and_then(|arr| arr.get(0))
-                                        .and_then(|choice| choice.get("message"))
-                                        .and_then(|msg| msg.get("content"))
-                                        .and_then(|content| content.as_str())
-                                        .unwrap_or("No response from Grok");
+    async fn tool_harvest_youtube_transcript(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": format!("Invalid arguments: {}", e)
+            }),
+        };
+        let video_id = match args.get("url").and_then(|v| v.as_str()).and_then(crate::youtube::video_id) {
+            Some(video_id) => video_id,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing or unrecognized YouTube 'url'"
+            }),
+        };
+        let lang = args.get("lang").and_then(|v| v.as_str()).unwrap_or("en");
 
-                                    serde_json::json!({
-                                        "success": true,
-                                        "topic": topic,
-                                        "difficulty": difficulty,
-                                        "include_resources": include_resources,
-                                        "guide": grok_response
-                                    })
-                                }
-                                Err(e) => serde_json::json!({
-                                    "success": false,
-                                    "error": format!("Failed to parse Grok response: {}", e)
-                                })
-                            }
-                        } else {
-                            let error_text = response.text().await.unwrap_or_else(|_| "Unknown Grok API error".to_string());
-                            serde_json::json!({
-                                "success": false,
-                                "error": format!("Grok API error: {}", error_text)
-                            })
-                        }
-                    }
-                    Err(e) => serde_json::json!({
-                        "success": false,
-                        "error": format!("Request failed: {}", e)
-                    })
+        let transcript = match crate::youtube::fetch_transcript(&video_id, lang).await {
+            Ok(transcript) => transcript,
+            Err(e) => return serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        };
+        tracing::info!("📺 Fetched transcript of '{}' ({} captions)", transcript.title, transcript.cues.len());
+
+        let summary = if args.get("summarize").and_then(|v| v.as_bool()).unwrap_or(false) {
+            let text: String = transcript.text().chars().take(60_000).collect();
+            let task = format!(
+                "Summarize this transcript of the YouTube video \"{}\": the main points in a short paragraph, \
+                 then the key takeaways as bullet points. Reply with the summary only.\n\n{}",
+                transcript.title, text
+            );
+            let mut summarizer = self.research_synthesizer("You summarize video transcripts accurately and concisely.".to_string());
+            match summarizer.run_autonomous_task(task).await {
+                Ok(summary) => Some(summary),
+                Err(e) => {
+                    tracing::warn!("⚠️ Transcript summary failed: {}", e);
+                    None
                 }
             }
+        } else {
+            None
+        };
+
+        let saved = Self::get_knowledge_base_path()
+            .and_then(|kb_root| crate::youtube::save(self.app_handle.as_ref(), &kb_root, &transcript, summary.as_deref()));
+        match saved {
+            Ok(path) => serde_json::json!({
+                "success": true,
+                "message": format!("Saved the transcript of '{}' to {}", transcript.title, path),
+                "title": transcript.title,
+                "path": path,
+                "language": transcript.language,
+                "auto_generated": transcript.auto_generated,
+                "summary": summary,
+                "preview": transcript.text().chars().take(500).collect::<String>()
+            }),
             Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
+        }
+    }
+
+    async fn tool_harvest_github(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
+            Err(e) => return serde_json::json!({
                 "success": false,
                 "error": format!("Invalid arguments: {}", e)
             }),
+        };
+        let repo = match args.get("repo").and_then(|v| v.as_str()) {
+            Some(repo) => repo,
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'repo' argument"
+            }),
+        };
+        let options = crate::github::GithubOptions::new(
+            args.get("include_docs").and_then(|v| v.as_bool()),
+            args.get("include_releases").and_then(|v| v.as_bool()),
+            args.get("max_docs").and_then(|v| v.as_u64()).map(|n| n as usize),
+            args.get("max_releases").and_then(|v| v.as_u64()).map(|n| n as usize),
+        );
+
+        match crate::github::harvest(self.app_handle.as_ref(), repo, options).await {
+            Ok(report) => serde_json::json!({
+                "success": true,
+                "message": format!("Harvested {} note(s) from {}", report.saved.len(), report.repo),
+                "description": report.description,
+                "paths": report.saved,
+                "releases": report.releases,
+                "skipped_docs": report.skipped_docs,
+                "errors": report.errors
+            }),
+            Err(e) => serde_json::json!({
+                "success": false,
+                "error": e
+            }),
         }
     }
 
-    fn tool_list_markdown_files(&self, arguments: &str) -> serde_json::Value {
-        let args: Result<HashMap<String, String>, _> = serde_json::from_str(arguments);
+    /// Ingest a paper for research. Local paths must be inside the knowledge base.
+    async fn ingest_research_paper(&self, input: &str) -> Result<crate::papers::Paper, String> {
+        let input = match crate::papers::parse_source(input) {
+            crate::papers::PaperSource::File(_) => {
+                let kb_root = Self::get_knowledge_base_path()?;
+                let path = crate::papers::resolve_local(input, &kb_root);
+                if input.contains("..") || !path.starts_with(&kb_root) {
+                    return Err("Local papers must be inside the knowledge base".to_string());
+                }
+                path.to_string_lossy().to_string()
+            }
+            _ => input.to_string(),
+        };
+        crate::papers::ingest(&input).await.map_err(|e| format!("{:#}", e))
+    }
 
-        let folder_filter = args.ok().and_then(|a| a.get("folder").cloned());
+    /// Agent that turns gathered research data into a report
+    fn research_synthesizer(&self, system_prompt: String) -> MinimaxAgent {
+        let mut synthesizer = MinimaxAgent::new(
+            self.api_key.clone(),
+            self.tavily_api_key.clone(),
+            self.grok_api_key.clone(),
+            self.gemini_api_key.clone()
+        )
+        .with_provider(self.provider.clone())
+        .with_grants(self.grants.clone())
+        .with_safe_mode(self.safe_mode)
+        .with_dry_run(self.dry_run)
+        .with_user_id(self.user_id.clone())
+        .with_user_name(self.user_name.clone())
+        .with_system_prompt(system_prompt);
 
-        // Get repository root
-        let repo_root = match Self::get_knowledge_base_path() {
-            Ok(root) => root,
+        if let Some(handle) = &self.app_handle {
+            synthesizer = synthesizer.with_app_handle(handle.clone());
+        }
+        synthesizer
+    }
+
+    /// deep_research: research agents gather sources (one per sub-topic, in parallel), then a
+    /// synthesizer writes a report citing them as numbered footnotes. The report and its
+    /// bibliography are stored under research_runs/<run_id>.
+    async fn tool_deep_research(&self, arguments: &str) -> serde_json::Value {
+        let args: serde_json::Value = match serde_json::from_str(arguments) {
+            Ok(args) => args,
             Err(e) => return serde_json::json!({
                 "success": false,
-                "error": format!("Could not find repository root: {}", e)
+                "error": format!("Invalid arguments: {}", e)
             }),
         };
+        let topic = match args.get("topic").and_then(|v| v.as_str()) {
+            Some(topic) => topic.to_string(),
+            None => return serde_json::json!({
+                "success": false,
+                "error": "Missing 'topic' argument"
+            }),
+        };
+
+        // Check for sub_topics for parallel execution
+        let sub_topics: Vec<String> = args.get("sub_topics")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+
+        let mut run = ResearchRun::new(topic, sub_topics, ResearchConfig::from_args(&args));
+        run.papers = args.get("papers")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default();
+        self.run_deep_research(run).await
+    }
+
+    /// Research `topic` with the default budget, outside a chat (the CLI's `research`)
+    pub async fn deep_research(&self, topic: String, sub_topics: Vec<String>) -> serde_json::Value {
+        self.run_deep_research(ResearchRun::new(topic, sub_topics, ResearchConfig::default())).await
+    }
+
+    /// Continue a research run that failed partway:finished sub-topics are loaded from their
+    /// checkpoints, the rest are researched again, then the report is re-synthesized
+    pub async fn resume_deep_research(&self, run_id: &str) -> Result<serde_json::Value, String> {
+        let run_dir = crate::deep_research::research_run_dir(self.app_handle.as_ref(), run_id)
+            .ok_or("Could not find app data dir")?;
+        let mut run = ResearchRun::load(&run_dir).map_err(|e| format!("Research run '{}' not found: {}", run_id, e))?;
+        run.status = "running".to_string();
+        Ok(self.run_deep_research(run).await)
+    }
+
+    /// Research a recorded run's topic again and report what changed since then
+    pub async fn rerun_deep_research(&self, run_id: &str) -> Result<serde_json::Value, String> {
+        let previous = {
+            let conn = crate::minimax_api::open_kc_database(self.app_handle.as_ref())?;
+            crate::research_history::get_run(&conn, run_id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Research run '{}' not found", run_id))?
+        };
+        let mut result = self.run_deep_research(previous.to_rerun()).await;
+
+        let new_run_id = result.get("run_id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let latest = crate::minimax_api::open_kc_database(self.app_handle.as_ref())
+            .ok()
+            .and_then(|conn| crate::research_history::get_run(&conn, &new_run_id).ok().flatten());
+        if let (Some(latest), Some(obj)) = (latest, result.as_object_mut()) {
+            obj.insert("diff".to_string(), serde_json::json!(crate::research_history::diff_runs(&previous, &latest)));
+        }
+        Ok(result)
+    }
 
-        let search_path = if let Some(folder) = &folder_filter {
-            repo_root.join(folder)
-        } else {
-            repo_root.clone()
+    async fn run_deep_research(&self, mut run: ResearchRun) -> serde_json::Value {
+        let run_dir = crate::deep_research::research_run_dir(self.app_handle.as_ref(), &run.run_id);
+        let save_run = |run: &ResearchRun| {
+            if let Some(dir) = &run_dir {
+                if let Err(e) = run.save(dir) {
+                    tracing::warn!("⚠️ Failed to save research run {}: {}", run.run_id, e);
+                }
+            }
+            let recorded = crate::minimax_api::open_kc_database(self.app_handle.as_ref())
+                .and_then(|conn| crate::research_history::upsert_run(&conn, run).map_err(|e| e.to_string()));
+            if let Err(e) = recorded {
+                tracing::warn!("⚠️ Failed to record research run {}: {}", run.run_id, e);
+            }
         };
+        save_run(&run);
 
-        if !search_path.exists() {
+        let search_settings = crate::search_providers::load_search_settings(self.app_handle.as_ref());
+        if let Err(e) = search_settings.provider(self.tavily_api_key.clone(), true) {
+            run.status = "failed".to_string();
+            save_run(&run);
             return serde_json::json!({
                 "success": false,
-                "error": format!("Folder not found: {}", search_path.display())
+                "error": e,
+                "run_id": run.run_id
             });
         }
 
-        let mut files = Vec::new();
-
-    // Define ignored directories
-    let ignored_dirs = ["node_modules", "target", ".git", ".vscode", "dist", "build", "coverage"];
-
-    for entry in WalkDir::new(&search_path)
-        .follow_links(false) // Disable following links to prevent loops/external walks
-        .into_iter()
-        .filter_entry(|e| {
-            let file_name = e.file_name().to_string_lossy();
-            // Skip hidden files/dirs (starting with .) but allow the search path itself
-            if file_name.starts_with('.') && e.depth() > 0 {
-                return false;
-            }
-            // Skip ignored directories
-            if e.file_type().is_dir() && ignored_dirs.contains(&file_name.as_ref()) {
-                return false;
-            }
-            true
-        })
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-
-        if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
-            if let Ok(relative_path) = path.strip_prefix(&repo_root) {
-                // Normalize path separators to forward slashes
-                let path_str = relative_path.to_string_lossy().replace('\\', "/");
-                files.push(path_str);
+        let parallel = run.is_parallel();
+        let research_topics = run.research_topics();
+        let mut completed = run_dir.as_ref()
+            .map(|dir| load_checkpoints(dir, &research_topics))
+            .unwrap_or_default();
+        // A resumed run gets a fresh budget for the sub-topics it still has to research
+        let budget = ResearchBudget::new(run.config.clone());
+        let progress = ProgressReporter::new(self.app_handle.clone(), &run.run_id, budget.clone(), research_topics.len());
+        let topic_label = |sub_topic: &str| if parallel { Some(sub_topic.to_string()) } else { None };
+        for (index, sub_topic) in research_topics.iter().enumerate() {
+            if completed.contains_key(&index) {
+                let topic_progress = progress.for_topic(index, topic_label(sub_topic));
+                topic_progress.advance(1.0);
+                topic_progress.emit(ResearchPhase::Complete, format!("Restored checkpoint for {}", sub_topic), None, None).await;
             }
         }
-    }
-
-    // Sort alphabetically
-    files.sort();
-
-    // Limit results to prevent context overflow (e.g., max 500 files)
-    let total_count = files.len();
-    if total_count > 500 {
-        files.truncate(500);
-    }
-
-    serde_json::json!({
-        "success": true,
-        "files": files,
-        "count": files.len(),
-        "total_found": total_count,
-        "folder": folder_filter.unwrap_or_else(|| "root".to_string()),
-        "message": if total_count > 500 { "Result truncated to first 500 files" } else { "Success" }
-    })
-}
-
-    fn tool_write_file(&self, arguments: &str) -> serde_json::Value {
-        eprintln!("🔧 write_file tool called with arguments: {}", arguments);
 
-        let args: Result<HashMap<String, serde_json::Value>, _> = serde_json::from_str(arguments);
+        if parallel {
+            tracing::info!("🚀 Spawning {} Parallel Deep Research Agents for: {} ({} checkpointed)",
+                research_topics.len() - completed.len(), run.topic, completed.len());
+        } else {
+            tracing::info!("🔍 Starting deep research on: {}", run.topic);
+        }
 
-        match args {
-            Ok(args) => {
-                eprintln!("✅ Parsed arguments successfully");
-                if let (Some(path_val), Some(content_val)) = (args.get("path"), args.get("content")) {
-                    let path = path_val.as_str().unwrap_or("");
-                    let content = content_val.as_str().unwrap_or("");
-                    let append = args.get("append").and_then(|v| v.as_bool()).unwrap_or(false);
+        let mut handles = vec![];
+        for (index, sub_topic) in research_topics.iter().enumerate() {
+            if completed.contains_key(&index) {
+                tracing::info!("⏭️ Skipping checkpointed sub-topic: {}", sub_topic);
+                continue;
+            }
+            let sub_topic = sub_topic.clone();
+            // Checked above, so building the provider again can't fail
+            let provider = match search_settings.provider(self.tavily_api_key.clone(), true) {
+                Ok(provider) => provider,
+                Err(_) => continue,
+            };
+            let progress = progress.for_topic(index, topic_label(&sub_topic));
+            let budget = budget.clone();
+            let run_dir = run_dir.clone();
+
+            handles.push(tokio::spawn(async move {
+                tracing::info!("🤖 Agent starting research on: {} ({})", sub_topic, provider.name());
+                let agent = DeepResearchAgent::new(provider);
+                let result = agent.research_topic(&sub_topic, &budget, &progress).await;
+                if let Err(e) = &result {
+                    progress.emit(ResearchPhase::Failed, format!("Research failed on {}", sub_topic), None, Some(e.to_string())).await;
+                }
 
-                    // Validate Scope
-                    if let Err(e) = self.validate_write_scope(path) {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": e
-                        });
+                // Checkpoint as soon as this agent is done so a later failure doesn't lose it
+                if let (Ok(sources), Some(dir)) = (&result, &run_dir) {
+                    if let Err(e) = save_checkpoint(dir, index, &sub_topic, sources) {
+                        tracing::warn!("⚠️ Failed to checkpoint {}: {}", sub_topic, e);
                     }
+                }
+                (index, sub_topic, result.map_err(|e| e.to_string()))
+            }));
+        }
 
-                    if self.app_mode == AppMode::Student && !self.is_allowed_write_path(path) {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Student mode: AI may only write to 'research/' or 'generated-guides/'"
-                        });
+        // Wait for all agents
+        let mut failures = std::collections::HashMap::new();
+        for handle in handles {
+            if let Ok((index, sub_topic, result)) = handle.await {
+                match result {
+                    Ok(sources) => {
+                        tracing::info!("✅ Agent finished: {}", sub_topic);
+                        completed.insert(index, sources);
                     }
-                    eprintln!("📝 Path: {}, Content length: {}, Append: {}", path, content.len(), append);
-
-                    // Get repository root
-                    let repo_root = match Self::get_knowledge_base_path() {
-                        Ok(root) => root,
-                        Err(e) => return serde_json::json!({
-                            "success": false,
-                            "error": format!("Could not find repository root: {}", e)
-                        }),
-                    };
+                    Err(e) => {
+                        tracing::error!("❌ Agent failed on {}: {}", sub_topic, e);
+                        failures.insert(index, e);
+                    }
+                }
+            }
+        }
 
-                    // Construct full path
-                    let full_path = repo_root.join(path);
+        if !parallel {
+            if let Some(e) = failures.get(&0) {
+                progress.emit(ResearchPhase::Failed, "Research failed".to_string(), None, Some(e.clone())).await;
+                run.status = "failed".to_string();
+                save_run(&run);
+                return serde_json::json!({
+                    "success": false,
+                    "error": format!("Research failed: {}", e),
+                    "run_id": run.run_id
+                });
+            }
+        }
 
-                    // Security: ensure the path is within repo root
-                    if !full_path.starts_with(&repo_root) {
-                        return serde_json::json!({
-                            "success": false,
-                            "error": "Path must be within repository root"
-                        });
-                    }
+        // Papers are read in full rather than searched for
+        let mut paper_sources = Vec::new();
+        let mut failed_papers = Vec::new();
+        for input in &run.papers {
+            let paper = match self.ingest_research_paper(input).await {
+                Ok(paper) => paper,
+                Err(e) => {
+                    tracing::warn!("⚠️ Could not ingest paper {}: {}", input, e);
+                    failed_papers.push(format!("{}: {}", input, e));
+                    continue;
+                }
+            };
+            progress.emit(ResearchPhase::Reading, format!("Reading paper: {}", paper.title), Some(&paper.url), None).await;
+            let source = paper.to_source();
+            if !budget.try_add_source(estimate_tokens(&source.content)).await {
+                failed_papers.push(format!("{}: token budget reached", input));
+                break;
+            }
+            match crate::papers::store_in_tkg(&paper, &self.user_id).await {
+                Ok(n) => tracing::info!("🧠 Stored {} section(s) of '{}' in TKG", n, paper.title),
+                Err(e) => tracing::warn!("⚠️ Paper not stored in TKG: {}", e),
+            }
+            paper_sources.push(source);
+        }
 
-                    // Check if file extension is allowed (support multiple types)
-                    let allowed_extensions = ["md", "txt", "json", "csv", "html", "js", "css", "tsx", "jsx", "rs", "toml", "yaml", "yml"];
-                    let file_ext = full_path.extension()
-                        .and_then(|e| e.to_str())
-                        .unwrap_or("");
+        // Score credibility across all sub-topics so corroboration can come from any of them
+        let all_sources: Vec<_> = (0..research_topics.len())
+            .filter_map(|i| completed.get(&i))
+            .flatten()
+            .chain(paper_sources.iter())
+            .cloned()
+            .collect();
+        let credibility = score_sources(&all_sources, chrono::Local::now().date_naive());
+
+        // Number sources into one bibliography in sub-topic order
+        let mut bibliography = Bibliography::default();
+        let mut reports = Vec::new();
+        for (index, sub_topic) in research_topics.iter().enumerate() {
+            if let Some(sources) = completed.get(&index) {
+                let data = bibliography.format_sources(sources, &credibility);
+                reports.push(if parallel { format!("# Research Data on {}\n\n{}", sub_topic, data) } else { data });
+            } else if let Some(e) = failures.get(&index) {
+                reports.push(format!("# Research Data on {}\n\nFAILED: {}", sub_topic, e));
+            }
+        }
+        if !paper_sources.is_empty() {
+            reports.push(format!("# Research Data from Papers\n\n{}", bibliography.format_sources(&paper_sources, &credibility)));
+        }
 
-                    if !allowed_extensions.contains(&file_ext) {
-                        eprintln!("⚠️ Warning: Writing file with extension '{}'. Allowed: {:?}", file_ext, allowed_extensions);
-                        // Don't block - just warn in logs
-                    }
+        let template = run.config.template;
+        let (synthesis_prompt, input) = if parallel {
+            (format!(r#"You are a Lead Research Synthesizer.
+Your goal is to combine multiple research contexts into one cohesive, comprehensive master report.
+1. Read all the provided research data.
+2. Identify key themes, facts, and insights.
+3. Synthesize them into a single, well-structured markdown document.
+4. Ensure the flow is logical and the tone is professional.
+{}
+{}
+{}
+Always use the <think> tag to explain your synthesis process."#, CITATION_INSTRUCTIONS, CREDIBILITY_INSTRUCTIONS, template.instructions()),
+             format!("Here is the raw research data for the topic '{}':\n\n{}", run.topic, reports.join("\n\n---\n\n")))
+        } else {
+            (format!(r#"You are a Deep Research Specialist.
+Your goal is to write a comprehensive report based on the provided research data.
+1. Analyze the research data.
+2. Structure a detailed markdown report.
+3. {}
+4. {}
+{}
+Always use the <think> tag to explain your reasoning."#, CITATION_INSTRUCTIONS, CREDIBILITY_INSTRUCTIONS, template.instructions()),
+             format!("Here is the research data for '{}':\n\n{}", run.topic, reports.join("\n\n")))
+        };
 
-                    // Create parent directories if they don't exist
-                    if let Some(parent) = full_path.parent() {
-                        if let Err(e) = std::fs::create_dir_all(parent) {
-                            return serde_json::json!({
-                                "success": false,
-                                "error": format!("Failed to create directory: {}", e)
-                            });
-                        }
-                    }
+        tracing::info!("🧠 Synthesizing {} research context(s)...", reports.len());
+        progress.emit_at(ResearchPhase::Synthesizing, format!("Synthesizing {} research context(s)", reports.len()), None, None, 85).await;
+        let draft = match self.research_synthesizer(synthesis_prompt.clone()).run_autonomous_task(input).await {
+            Ok(report) => report,
+            Err(e) => {
+                progress.emit_at(ResearchPhase::Failed, "Synthesis failed".to_string(), None, Some(e.to_string()), 85).await;
+                run.status = "failed".to_string();
+                save_run(&run);
+                return serde_json::json!({
+                    "success": false,
+                    "error": format!("Synthesis failed: {}", e),
+                    "run_id": run.run_id
+                });
+            }
+        };
+        let mut report = normalize_citations(&draft, &bibliography);
+        let mut citation_check = check_citations(&report, &bibliography);
 
-                    // Write the file
-                    let write_result = if append {
-                        std::fs::OpenOptions::new()
-                            .create(true)
-                            .append(true)
-                            .write(true)
-                            .open(&full_path)
-                            .and_then(|mut file| std::io::Write::write_all(&mut file, content.as_bytes()))
-                    } else {
-                        std::fs::write(&full_path, content)
-                    };
+        // One revision pass for sections that make claims without citing anything, or
+        // sections the report template requires but the draft left out
+        let citation_gaps = |check: &CitationCheck| {
+            if bibliography.entries.is_empty() { Vec::new() } else { check.uncited_sections.clone() }
+        };
+        let uncited = citation_gaps(&citation_check);
+        let missing = template.missing_sections(&report);
+        if !uncited.is_empty() || !missing.is_empty() {
+            tracing::info!("📎 {} section(s) lack citations, {} template section(s) missing, requesting a revision", uncited.len(), missing.len());
+            progress.emit_at(
+                ResearchPhase::Revising,
+                format!("Revising {} section(s)", uncited.len() + missing.len()),
+                None,
+                None,
+                92,
+            ).await;
+            let mut asks = Vec::new();
+            if !uncited.is_empty() {
+                asks.push(format!("Make each of these sections cite at least one source with [^n] footnote markers: {}.", uncited.join(", ")));
+            }
+            if !missing.is_empty() {
+                asks.push(format!("Add these sections required by the {} format: {}.", template.label().to_lowercase(), missing.join(", ")));
+            }
+            let revision = format!(
+                "Revise this report. {}\n\
+                 Keep everything else unchanged and return the full report.\n\nSources:\n{}\n\nReport:\n{}",
+                asks.join(" "),
+                bibliography.summary(),
+                report
+            );
+            if let Ok(revised) = self.research_synthesizer(synthesis_prompt).run_autonomous_task(revision).await {
+                let revised = normalize_citations(&revised, &bibliography);
+                let revised_check = check_citations(&revised, &bibliography);
+                let (revised_uncited, revised_missing) = (citation_gaps(&revised_check).len(), template.missing_sections(&revised).len());
+                let improved = revised_uncited + revised_missing < uncited.len() + missing.len();
+                if improved && revised_uncited <= uncited.len() && revised_missing <= missing.len() {
+                    report = revised;
+                    citation_check = revised_check;
+                }
+            }
+        }
+        let weak_claims = flag_weak_claims(&report, &bibliography);
+        if !weak_claims.is_empty() {
+            tracing::info!("⚖️ {} claim(s) rest on a single low-credibility source", weak_claims.len());
+        }
+        let report = with_footnotes(&with_credibility_notes(&report, &weak_claims, &bibliography), &bibliography);
 
-                    match write_result {
-                        Ok(_) => {
-                            let file_size = content.len();
+        let usage = budget.usage().await;
+        let stored = crate::minimax_api::open_kc_database(self.app_handle.as_ref()).and_then(|conn| {
+            crate::research_history::store_results(&conn, &run.run_id, &all_sources, &report, &bibliography, &usage)
+                .map_err(|e| e.to_string())
+        });
+        if let Err(e) = stored {
+            tracing::warn!("⚠️ Failed to record research results for {}: {}", run.run_id, e);
+        }
 
-                            // Emit event to refresh UI
-                            if let Some(ref handle) = self.app_handle {
-                                let _ = handle.emit_all("content-changed", ());
-                            }
+        let knowledge_base = crate::research_notes::save_report(
+            self.app_handle.as_ref(),
+            &run.topic,
+            &run.run_id,
+            &report,
+            &bibliography,
+            &self.user_id,
+        ).await;
+        for e in &knowledge_base.errors {
+            tracing::warn!("⚠️ Research report not fully saved to knowledge base: {}", e);
+        }
 
-                            serde_json::json!({
-                                "success": true,
-                                "path": path,
-                                "size": file_size,
-                                "operation": if append { "append" } else { "write" },
-                                "message": format!("Successfully {} file at {}", if append { "appended to" } else { "wrote" }, path)
-                            })
-                        }
-                        Err(e) => serde_json::json!({
-                            "success": false,
-                            "error": format!("Failed to write file: {}", e)
-                        }),
-                    }
-                } else {
-                    serde_json::json!({
-                        "success": false,
-                        "error": "Missing 'path' or 'content' argument"
-                    })
+        run.status = if failures.is_empty() { "complete" } else { "incomplete" }.to_string();
+        save_run(&run);
+        progress.emit_at(ResearchPhase::Complete, format!("Research {}", run.status), None, None, 100).await;
+        if let Some(app_handle) = &self.app_handle {
+            crate::notifications::notify(app_handle, crate::notifications::Category::Research, &format!("🔬 Research {}", run.status), &run.topic);
+        }
+        let saved_to = run_dir.as_ref().and_then(|dir| {
+            match store_report(dir, &run.topic, &report, &bibliography, &citation_check) {
+                Ok(()) => Some(dir.to_string_lossy().to_string()),
+                Err(e) => {
+                    tracing::warn!("⚠️ Failed to store research report: {}", e);
+                    None
                 }
             }
-            Err(e) => serde_json::json!({
-                "success": false,
-                "error": format!("Invalid arguments: {}", e)
-            }),
-        }
+        });
+
+        serde_json::json!({
+            "success": true,
+            "report": report,
+            "bibliography": bibliography.entries,
+            "citations": citation_check,
+            "weak_claims": weak_claims,
+            "run_id": run.run_id,
+            "status": run.status,
+            "failed_sub_topics": failures.keys().map(|i| research_topics[*i].clone()).collect::<Vec<_>>(),
+            "failed_papers": failed_papers,
+            "saved_to": saved_to,
+            "knowledge_base": knowledge_base,
+            "mode": if parallel { "parallel" } else { "single" },
+            "template": template,
+            "agents_count": reports.len(),
+            "rerun_of": run.rerun_of,
+            "usage": usage,
+            "budget": budget.config
+        })
     }
 
     /// Helper for single agent research (used by deep_research tool)
     async fn run_single_agent_research(api_key: String, tavily_api_key: Option<String>, grok_api_key: Option<String>, gemini_api_key: Option<String>, topic: String) -> serde_json::Value {
-        eprintln!("🚀 Spawning Single Deep Research Agent for: {}", topic);
+        tracing::info!("🚀 Spawning Single Deep Research Agent for: {}", topic);
         
         let mut agent = MinimaxAgent::new(
             api_key,
@@ -3649,7 +5157,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
 
     /// Run an autonomous task using the agent loop
     pub async fn run_autonomous_task(&mut self, task: String) -> Result<String, String> {
-        eprintln!("🤖 Starting autonomous task: {}", task);
+        tracing::info!("🤖 Starting autonomous task: {}", task);
 
         // Add user task to history
         self.conversation_history.push(Message {
@@ -3663,11 +5171,11 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
         // Run chat loop with max 30 iterations
         match self.chat(30).await {
             Ok(response) => {
-                eprintln!("✅ Autonomous task complete");
+                tracing::info!("✅ Autonomous task complete");
                 Ok(response.content)
             }
             Err(e) => {
-                eprintln!("❌ Autonomous task failed: {}", e);
+                tracing::error!("❌ Autonomous task failed: {}", e);
                 Err(e)
             }
         }
@@ -3679,7 +5187,11 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
         let _thinking_parts = Vec::<String>::new();
 
         for iteration in 0..max_iterations {
-            eprintln!("\n🔄 Iteration {}/{}", iteration + 1, max_iterations);
+            if self.cancel.load(Ordering::Relaxed) {
+                tracing::info!("🛑 Agent loop cancelled by user");
+                return Err("Generation stopped by user".to_string());
+            }
+            tracing::debug!("🔄 Iteration {}/{}", iteration + 1, max_iterations);
 
             // Build messages with system prompt
             let mut messages = vec![Message {
@@ -3712,7 +5224,8 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
             }).collect::<Vec<_>>();
 
             // Call AI API
-            let (text_content, mut tool_calls) = if let AIProvider::Gemini = self.provider {
+            let gemini_api = self.provider == AIProvider::Gemini && AIProvider::local_chat_url().is_none();
+            let (text_content, mut tool_calls) = if gemini_api {
                 // ==================== GEMINI IMPLEMENTATION ====================
                 let api_key = self.gemini_api_key.clone().unwrap_or_default();
                 let url = format!("{}/models/{}:generateContent?key={}", self.base_url, self.model, api_key);
@@ -3762,10 +5275,12 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                     }
                 });
 
-                let client = reqwest::Client::new();
+                // Timed until the response has been read
+                let provider_span = tracing::info_span!("provider", model = %self.model);
+                let client = self.http.client();
                 let response = client.post(&url)
                     .json(&payload)
-                    .send()
+                    .send_checked()
                     .await
                     .map_err(|e| format!("Gemini Request failed: {}", e))?;
 
@@ -3776,6 +5291,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
 
                 let result: serde_json::Value = response.json().await
                     .map_err(|e| format!("Failed to parse Gemini response: {}", e))?;
+                drop(provider_span);
 
                 let content = result.get("candidates")
                     .and_then(|c| c.as_array())
@@ -3792,10 +5308,10 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                 (content, Vec::new()) // No native tool calls for Gemini yet
             } else {
                 // ==================== OPENAI-COMPATIBLE IMPLEMENTATION (Minimax/Grok) ====================
-                let client = reqwest::Client::builder()
-                    .timeout(std::time::Duration::from_secs(120))
-                    .build()
-                    .unwrap_or_else(|_| reqwest::Client::new());
+                let client = self.http.client_with(crate::net::ClientOptions {
+                    timeout: Some(std::time::Duration::from_secs(120)),
+                    ..Default::default()
+                });
 
                 let payload = serde_json::json!({
                     "model": self.model,
@@ -3806,15 +5322,17 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                     "top_p": 0.95,
                 });
 
+                // Timed until the response has been read
+                let provider_span = tracing::info_span!("provider", model = %self.model);
                 let response = client
                     .post(format!("{}/chat/completions", self.base_url))
                     .header("Authorization", format!("Bearer {}", &self.api_key))
                     .header("Content-Type", "application/json")
                     .json(&payload)
-                    .send()
+                    .send_checked()
                     .await
                     .map_err(|e| {
-                        eprintln!("❌ Error details: {}", e);
+                        tracing::error!("❌ Error details: {}", e);
                         format!("Request failed: {}", e)
                     })?;
 
@@ -3825,6 +5343,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
 
                 let result: serde_json::Value = response.json().await
                     .map_err(|e| format!("Failed to parse response: {}", e))?;
+                drop(provider_span);
 
                 // Parse OpenAI-format response
                 let message = result["choices"][0]["message"].as_object()
@@ -3877,7 +5396,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                 let re = Regex::new(r"<think>(.*?)</think>").unwrap();
                 for cap in re.captures_iter(&text_content) {
                     if let Some(thinking) = cap.get(1) {
-                        eprintln!("💭 THINKING: {}", thinking.as_str());
+                        tracing::debug!("💭 THINKING: {}", thinking.as_str());
                     }
                 }
                 // Remove <think> tags from content for cleaner display
@@ -3890,7 +5409,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
             if tool_calls.is_empty() {
                 let text_tool_calls = Self::parse_text_tool_calls(&text_content, total_tool_calls);
                 if !text_tool_calls.is_empty() {
-                    eprintln!("Parsed {} tool call(s) from text markers", text_tool_calls.len());
+                    tracing::debug!("Parsed {} tool call(s) from text markers", text_tool_calls.len());
                     tool_calls.extend(text_tool_calls);
                 }
             }
@@ -3909,17 +5428,18 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
 
             // Check if we're done (no tool calls)
             if tool_calls.is_empty() {
-                eprintln!("✅ Conversation complete (no tool calls)");
+                tracing::info!("✅ Conversation complete (no tool calls)");
                 return Ok(ChatResponse {
                     content: clean_content,
                     thinking: vec![],
                     tool_calls_made: total_tool_calls,
                     iterations: iteration + 1,
+                    reflection: None,
                 });
             }
 
             // Execute tool calls
-            eprintln!("🔧 Tool calls requested: {}", tool_calls.len());
+            tracing::info!("🔧 Tool calls requested: {}", tool_calls.len());
             total_tool_calls += tool_calls.len();
 
             for tool_call in tool_calls {
@@ -3957,10 +5477,11 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                     }
                 }
 
-                // Add tool result to conversation history
+                // Add tool result to conversation history, large ones by reference
+                let content = crate::tool_results::offload(self.app_handle.as_ref(), &tool_call.function.name, result);
                 self.conversation_history.push(Message {
                     role: "tool".to_string(),
-                    content: result,
+                    content,
                     tool_calls: None,
                     tool_call_id: Some(tool_call.id),
                     timestamp: Some(Self::get_current_timestamp()),
@@ -3969,7 +5490,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
         }
 
         // Max iterations reached
-        eprintln!("⚠️  Maximum iterations ({}) reached", max_iterations);
+        tracing::warn!("⚠️  Maximum iterations ({}) reached", max_iterations);
         Err(format!("Maximum iterations ({}) reached. The task may be too complex.", max_iterations))
     }
 
@@ -3977,14 +5498,17 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
     pub async fn chat_stream(&mut self, app_handle: &tauri::AppHandle, max_iterations: usize) -> Result<(), String> {
         let mut total_tool_calls = 0;
         
-        // Cancellation flag
-        let should_stop = Arc::new(AtomicBool::new(false));
+        // Cancellation flag, shared with the session when there is one
+        let should_stop = self.cancel.clone();
         let should_stop_clone = should_stop.clone();
         
-        // Listen for stop event
-        let handler_id = app_handle.listen_global("stop-generation", move |_| {
-            eprintln!("🛑 Stop signal received!");
-            should_stop_clone.store(true, Ordering::Relaxed);
+        // Listen for stop event; a session turn is stopped through `stop_agent_session` instead,
+        // so stopping one chat leaves the others streaming
+        let handler_id = self.session.is_none().then(|| {
+            app_handle.listen_global("stop-generation", move |_| {
+                tracing::info!("🛑 Stop signal received!");
+                should_stop_clone.store(true, Ordering::Relaxed);
+            })
         });
 
         let mut last_tool_call_signature: Option<String> = None;
@@ -3993,20 +5517,23 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
         for iteration in 0..max_iterations {
             // Check cancellation at start of iteration
             if should_stop.load(Ordering::Relaxed) {
-                eprintln!("🛑 Agent loop cancelled by user");
-                app_handle.unlisten(handler_id);
+                tracing::info!("🛑 Agent loop cancelled by user");
+                if let Some(handler_id) = handler_id {
+                    app_handle.unlisten(handler_id);
+                }
                 
                 // Emit done event
-                let _ = app_handle.emit_all("chat-stream", StreamChunk {
+                crate::events::emit(app_handle, "chat-stream", StreamChunk {
                     content: "\n\n*[Generation stopped by user]*".to_string(),
                     is_thinking: false,
                     done: true,
                     tool_calls: None,
+                    session: self.session.clone(),
                 });
                 return Ok(());
             }
 
-            eprintln!("\n🔄 Iteration {}/{}", iteration + 1, max_iterations);
+            tracing::debug!("🔄 Iteration {}/{}", iteration + 1, max_iterations);
 
             // Prune history if needed
             self.prune_history();
@@ -4047,10 +5574,10 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
             }).collect::<Vec<_>>();
 
             // Call AI API
-            let client = reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(300))
-                .build()
-                .unwrap_or_else(|_| reqwest::Client::new());
+            let client = self.http.client_with(crate::net::ClientOptions {
+                timeout: Some(std::time::Duration::from_secs(300)),
+                ..Default::default()
+            });
 
             let payload = serde_json::json!({
                 "model": self.model,
@@ -4062,15 +5589,17 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                 "stream": true
             });
 
+            // Timed until the stream ends
+            let provider_span = tracing::info_span!("provider", model = %self.model);
             let response = client
                 .post(format!("{}/chat/completions", self.base_url))
                 .header("Authorization", format!("Bearer {}", &self.api_key))
                 .header("Content-Type", "application/json")
                 .json(&payload)
-                .send()
+                .send_checked()
                 .await
                 .map_err(|e| {
-                    eprintln!("❌ Error details: {}", e);
+                    tracing::error!("❌ Error details: {}", e);
                     format!("Request failed: {}", e)
                 })?;
 
@@ -4090,13 +5619,13 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                 let chunk = match chunk_result {
                     Ok(chunk) => chunk,
                     Err(e) => {
-                        eprintln!("❌ Stream error: {}", e);
+                        tracing::error!("❌ Stream error: {}", e);
                         return Err(format!("Stream error: {}", e));
                     }
                 };
 
                 chunks_received += 1;
-                // eprintln!("📦 Chunk {} received ({} bytes)", chunks_received, chunk.len());
+                // tracing::debug!("📦 Chunk {} received ({} bytes)", chunks_received, chunk.len());
 
                 buffer.extend_from_slice(&chunk);
                 
@@ -4114,15 +5643,15 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                             let data = &line[6..];
 
                             if data == "[DONE]" {
-                                eprintln!("🎉 [DONE] received - total chunks: {}", chunks_received);
-                                eprintln!("📝 Full content length: {} chars", full_content.len());
+                                tracing::debug!("🎉 [DONE] received - total chunks: {}", chunks_received);
+                                tracing::debug!("📝 Full content length: {} chars", full_content.len());
                                 // Stream complete - consume all remaining chunks and exit
                                 let mut remaining_count = 0;
                                 while let Some(_) = stream.next().await {
                                     remaining_count += 1;
                                 }
                                 if remaining_count > 0 {
-                                    eprintln!("⚠️  Discarded {} remaining chunks", remaining_count);
+                                    tracing::warn!("⚠️  Discarded {} remaining chunks", remaining_count);
                                 }
                                 break;
                             }
@@ -4132,11 +5661,12 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                                     if let Some(content) = delta.get("content").and_then(|c| c.as_str()) {
                                         full_content.push_str(content);
 
-                                        let _ = app_handle.emit_all("chat-stream", StreamChunk {
+                                        crate::events::emit(app_handle, "chat-stream", StreamChunk {
                                             content: content.to_string(),
                                             is_thinking: false,
                                             done: false,
                                             tool_calls: None,
+                                            session: self.session.clone(),
                                         });
                                     }
 
@@ -4187,20 +5717,21 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                     buffer.drain(..start_index);
                 }
             }
+            drop(provider_span);
 
-            eprintln!("📤 Stream processing complete - {} chunks processed", chunks_received);
+            tracing::debug!("📤 Stream processing complete - {} chunks processed", chunks_received);
 
             // Check for [TOOL]/[TOOL_CALL] text format if no structured tool calls were found
             if tool_calls.is_empty() {
                 let text_tool_calls = Self::parse_text_tool_calls(&full_content, total_tool_calls);
                 if !text_tool_calls.is_empty() {
-                    eprintln!("Parsed {} tool call(s) from text markers in stream", text_tool_calls.len());
+                    tracing::debug!("Parsed {} tool call(s) from text markers in stream", text_tool_calls.len());
                     tool_calls.extend(text_tool_calls);
                 } else {
                     // Fallback: Check for XML-style <tool_code> blocks (Grok specific)
                     let xml_tool_calls = Self::parse_grok_xml_tools(&full_content, total_tool_calls);
                     if !xml_tool_calls.is_empty() {
-                        eprintln!("Parsed {} tool call(s) from XML markers in stream", xml_tool_calls.len());
+                        tracing::debug!("Parsed {} tool call(s) from XML markers in stream", xml_tool_calls.len());
                         tool_calls.extend(xml_tool_calls);
                     }
                 }
@@ -4218,15 +5749,16 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
 
             // Handle tool calls if any
             if !tool_calls.is_empty() {
-                eprintln!("🔧 Tool calls requested: {}", tool_calls.len());
+                tracing::info!("🔧 Tool calls requested: {}", tool_calls.len());
                 total_tool_calls += tool_calls.len();
 
                 // Emit tool calls to UI
-                let _ = app_handle.emit_all("chat-stream", StreamChunk {
+                crate::events::emit(app_handle, "chat-stream", StreamChunk {
                     content: String::new(),
                     is_thinking: false,
                     done: false,
                     tool_calls: Some(tool_calls.clone()),
+                    session: self.session.clone(),
                 });
 
                 // Execute tool calls
@@ -4239,7 +5771,7 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                         if last == &signature {
                             consecutive_repeats += 1;
                             if consecutive_repeats >= 2 {
-                                eprintln!("🛑 Loop detected: Same tool call repeated {} times. Breaking loop.", consecutive_repeats);
+                                tracing::info!("🛑 Loop detected: Same tool call repeated {} times. Breaking loop.", consecutive_repeats);
                                 // Break the outer loop
                                 break_outer = true; 
                                 break;
@@ -4282,10 +5814,11 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                         }
                     }
 
-                    // Add tool result to conversation history
+                    // Add tool result to conversation history, large ones by reference
+                    let content = crate::tool_results::offload(Some(app_handle), &tool_call.function.name, result);
                     self.conversation_history.push(Message {
                         role: "tool".to_string(),
-                        content: result,
+                        content,
                         tool_calls: None,
                         tool_call_id: Some(tool_call.id),
                         timestamp: Some(Self::get_current_timestamp()),
@@ -4296,44 +5829,95 @@ Always use the <think> tag to explain your reasoning before taking actions."#.to
                     break;
                 }
 
-                eprintln!("✅ Tool execution complete, continuing to next iteration");
+                tracing::debug!("✅ Tool execution complete, continuing to next iteration");
 
                 // CRITICAL: After tool execution, the loop continues automatically to next iteration
                 // to get the final response from the AI
             } else {
                 // No tool calls, we're done
-                eprintln!("✅ No tool calls, finishing iteration");
+                tracing::debug!("✅ No tool calls, finishing iteration");
 
                 // Emit final done event
-                let _ = app_handle.emit_all("chat-stream", StreamChunk {
+                crate::events::emit(app_handle, "chat-stream", StreamChunk {
                     content: String::new(),
                     is_thinking: false,
                     done: true,
                     tool_calls: None,
+                    session: self.session.clone(),
                 });
 
-                app_handle.unlisten(handler_id);
+                if let Some(handler_id) = handler_id {
+                    app_handle.unlisten(handler_id);
+                }
                 return Ok(());
             }
         }
 
-        eprintln!("⚠️  Loop ended, max iterations reached");
+        tracing::warn!("⚠️  Loop ended, max iterations reached");
 
         // Emit final done event on error
-        let _ = app_handle.emit_all("chat-stream", StreamChunk {
+        crate::events::emit(app_handle, "chat-stream", StreamChunk {
             content: String::new(),
             is_thinking: false,
             done: true,
             tool_calls: None,
+            session: self.session.clone(),
         });
 
-        app_handle.unlisten(handler_id);
+        if let Some(handler_id) = handler_id {
+            app_handle.unlisten(handler_id);
+        }
         Err(format!("Maximum iterations ({}) reached", max_iterations))
     }
 }
 
 // ==================== Tauri Commands ====================
 
+/// Ready `agent` for a turn. Without a session name it just gets `messages`; a named session
+/// is held for the whole turn and supplies the settings it was created with, its grants, its
+/// stop flag and the history `messages` continue.
+async fn begin_turn(
+    app_handle: &tauri::AppHandle,
+    mut agent: MinimaxAgent,
+    session_name: Option<&str>,
+    messages: Vec<Message>,
+) -> Result<(MinimaxAgent, Option<tokio::sync::OwnedMutexGuard<crate::agent_sessions::AgentSession>>), String> {
+    let name = match session_name {
+        Some(name) => name,
+        None => {
+            agent.conversation_history.extend(messages);
+            return Ok((agent, None));
+        }
+    };
+
+    let (handle, _) = app_handle.state::<crate::commands::AppState>().agent_sessions.open(name);
+    let mut session = handle.state.clone().lock_owned().await;
+    // Only a stop sent during this turn should end it
+    handle.cancel.store(false, Ordering::Relaxed);
+
+    // Restore the configuration the session was created with
+    if session.turns == 0 {
+        session.settings = crate::session::load_session_settings(app_handle, name)?;
+    }
+    if let Some(settings) = &session.settings {
+        agent = agent.with_session_settings(settings);
+    }
+    if let Some(grants) = crate::permissions::session_grants(app_handle, name) {
+        agent = agent.with_grants(grants);
+    }
+    agent.conversation_history = crate::agent_sessions::merge_history(&session.history, messages);
+    agent.session = Some(name.to_string());
+    Ok((agent.with_cancel(handle.cancel.clone()), Some(session)))
+}
+
+/// Keep what a finished turn added in its session; a failed turn leaves the session as it was
+fn end_turn(agent: &mut MinimaxAgent, session: Option<tokio::sync::OwnedMutexGuard<crate::agent_sessions::AgentSession>>) {
+    if let Some(mut session) = session {
+        session.history = std::mem::take(&mut agent.conversation_history);
+        session.turns += 1;
+    }
+}
+
 #[tauri::command]
 pub async fn chat_with_agent_stream(
     app_handle: tauri::AppHandle,
@@ -4347,20 +5931,24 @@ pub async fn chat_with_agent_stream(
     enabled_tools: Option<std::collections::HashMap<String, bool>>,
     user_id: Option<String>,
     user_name: Option<String>,
+    session_name: Option<String>,
+    dry_run: Option<bool>,
 ) -> Result<(), String> {
-    let mut agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
+    let agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
         .with_provider(provider)
         .with_app_handle(app_handle.clone())
         .with_enabled_tools(enabled_tools.unwrap_or_default())
+        .with_dry_run(dry_run.unwrap_or(false))
         .with_user_id(user_id.unwrap_or_else(|| "guest".to_string()))
         .with_user_name(user_name);
 
-    // Load conversation history
-    for msg in messages {
-        agent.conversation_history.push(msg);
-    }
+    // Everything this request does is timed as one turn
+    let turn = tracing::info_span!("turn", session = session_name.as_deref().unwrap_or("chat"));
 
-    agent.chat_stream(&app_handle, max_iterations.unwrap_or(30)).await
+    let (mut agent, session) = begin_turn(&app_handle, agent, session_name.as_deref(), messages).await?;
+    agent.chat_stream(&app_handle, max_iterations.unwrap_or(30)).instrument(turn).await?;
+    end_turn(&mut agent, session);
+    Ok(())
 }
 
 #[tauri::command]
@@ -4376,20 +5964,64 @@ pub async fn chat_with_agent(
     enabled_tools: Option<std::collections::HashMap<String, bool>>,
     user_id: Option<String>,
     user_name: Option<String>,
+    session_name: Option<String>,
+    reflect: Option<crate::reflection::ReflectOptions>,
+    dry_run: Option<bool>,
 ) -> Result<ChatResponse, String> {
-    let mut agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
+    // A critic on the same provider, for reflection
+    let critic = MinimaxAgent::new(api_key.clone(), None, grok_key.clone(), gemini_key.clone())
+        .with_provider(provider.clone())
+        .with_enabled_tools(std::collections::HashMap::new());
+    let agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
         .with_provider(provider)
-        .with_app_handle(app_handle)
+        .with_app_handle(app_handle.clone())
         .with_enabled_tools(enabled_tools.unwrap_or_default())
+        .with_dry_run(dry_run.unwrap_or(false))
         .with_user_id(user_id.unwrap_or_else(|| "guest".to_string()))
         .with_user_name(user_name);
 
-    // Load conversation history
-    for msg in messages {
-        agent.conversation_history.push(msg);
+    // Everything this request does is timed as one turn
+    let turn = tracing::info_span!("turn", session = session_name.as_deref().unwrap_or("chat"));
+
+    let question = messages.iter().rev().find(|m| m.role == "user").map(|m| m.content.clone()).unwrap_or_default();
+    let (mut agent, session) = begin_turn(&app_handle, agent, session_name.as_deref(), messages).await?;
+
+    let max_iterations = max_iterations.unwrap_or(30);
+    let draft = agent.chat(max_iterations).instrument(turn.clone()).await?;
+    let options = match reflect {
+        Some(options) => options,
+        None => {
+            end_turn(&mut agent, session);
+            return Ok(draft);
+        }
+    };
+
+    // Reflection: the critic reviews the draft and the agent revises it once
+    let agents = if options.critic_id.is_some() {
+        orchestrate_agents::load_registered_agents(Some(&app_handle))?
+    } else {
+        Vec::new()
+    };
+    let registered = orchestrate_agents::find_critic(&agents, options.critic_id.as_deref())?;
+    let mut critic = critic.with_system_prompt(registered.system_prompt);
+    critic.add_user_message(crate::reflection::critique_prompt(&question, &draft.content, options.rubric()));
+    let critique = critic.chat(1).instrument(turn.clone()).await?.content;
+    if crate::reflection::approves(&critique) {
+        tracing::info!("✅ {} found nothing to change", registered.name);
+        let reflection = crate::reflection::Reflection { draft: draft.content.clone(), critique, revised: false };
+        end_turn(&mut agent, session);
+        return Ok(ChatResponse { reflection: Some(reflection), ..draft });
     }
 
-    agent.chat(max_iterations.unwrap_or(30)).await
+    agent.add_user_message(crate::reflection::revision_prompt(None, &draft.content, &critique));
+    let revised = agent.chat(max_iterations).instrument(turn).await?;
+    end_turn(&mut agent, session);
+    Ok(ChatResponse {
+        tool_calls_made: draft.tool_calls_made + revised.tool_calls_made,
+        iterations: draft.iterations + revised.iterations,
+        reflection: Some(crate::reflection::Reflection { draft: draft.content, critique, revised: true }),
+        ..revised
+    })
 }
 
 #[tauri::command]
@@ -4422,6 +6054,46 @@ pub async fn create_study_guide_enhanced(
     Ok(response.content)
 }
 
+/// Resume a deep research run from its checkpoints (see list_research_runs for run ids)
+#[tauri::command]
+pub async fn resume_research(
+    app_handle: tauri::AppHandle,
+    provider: AIProvider,
+    api_key: String,
+    tavily_key: Option<String>,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+    run_id: String,
+    user_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
+        .with_provider(provider)
+        .with_app_handle(app_handle)
+        .with_user_id(user_id.unwrap_or_else(|| "guest".to_string()));
+
+    agent.resume_deep_research(&run_id).await
+}
+
+/// Run a recorded deep research run again; the result includes a diff against the earlier run
+#[tauri::command]
+pub async fn rerun_research(
+    app_handle: tauri::AppHandle,
+    provider: AIProvider,
+    api_key: String,
+    tavily_key: Option<String>,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+    run_id: String,
+    user_id: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
+        .with_provider(provider)
+        .with_app_handle(app_handle)
+        .with_user_id(user_id.unwrap_or_else(|| "guest".to_string()));
+
+    agent.rerun_deep_research(&run_id).await
+}
+
 #[tauri::command]
 pub fn get_app_mode() -> serde_json::Value {
     let mode = AppMode::current();
@@ -4534,6 +6206,22 @@ pub async fn read_blueprint_file(path: String) -> Result<String, String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_paths_resolve_within_the_knowledge_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let kb = dir.path().join("kb");
+        std::fs::create_dir_all(kb.join("notes")).unwrap();
+        std::fs::write(kb.join("notes/a.md"), "# A").unwrap();
+        std::fs::write(dir.path().join("secret.md"), "# Secret").unwrap();
+
+        assert!(MinimaxAgent::resolves_within(&kb, &kb.join("notes/a.md")));
+        assert!(MinimaxAgent::resolves_within(&kb, &kb.join("notes/../notes/a.md")));
+        // The unresolved join still starts with the root
+        assert!(kb.join("../secret.md").starts_with(&kb));
+        assert!(!MinimaxAgent::resolves_within(&kb, &kb.join("../secret.md")));
+        assert!(!MinimaxAgent::resolves_within(&kb, &kb.join("notes/missing.md")));
+    }
+
     #[test]
     fn test_parse_legacy_tool_call() {
         let text = "Some text\n[TOOL]tool => \"calculate\"\nargs => {\n  \"expression\": \"1+1\"\n}[/TOOL]";
@@ -22,7 +22,7 @@ pub fn parse_tool_calls_from_xml(content: &str) -> Vec<ParsedToolCall> {
     for tool_call_cap in tool_call_regex.captures_iter(content) {
         if let Some(inner_content) = tool_call_cap.get(1) {
             let inner = inner_content.as_str();
-            eprintln!("🔧 Found XML tool call block: {}", inner.trim());
+            tracing::debug!("🔧 Found XML tool call block: {}", inner.trim());
 
             // Extract invoke blocks: <invoke name="function_name">...</invoke>
             let invoke_regex = Regex::new(r#"(?s)<invoke name="([^"]+)">(.*?)</invoke>"#).unwrap();
@@ -32,7 +32,7 @@ pub fn parse_tool_calls_from_xml(content: &str) -> Vec<ParsedToolCall> {
                     let function_name = func_name.as_str();
                     let params = params_text.as_str();
 
-                    eprintln!("  📝 Function: {}", function_name);
+                    tracing::debug!("  📝 Function: {}", function_name);
 
                     // Extract parameters: <parameter name="param_name">value</parameter>
                     let param_regex = Regex::new(r#"(?s)<parameter name="([^"]+)">(.*?)</parameter>"#).unwrap();
@@ -44,7 +44,7 @@ pub fn parse_tool_calls_from_xml(content: &str) -> Vec<ParsedToolCall> {
                             let name = param_name.as_str();
                             let value = param_value.as_str().trim();
 
-                            eprintln!("    🔹 Parameter: {} = {}", name, value);
+                            tracing::debug!("    🔹 Parameter: {} = {}", name, value);
 
                             // Try to parse as JSON (for arrays/objects), otherwise keep as string
                             let parsed_value = match serde_json::from_str::<Value>(value) {
@@ -58,7 +58,7 @@ pub fn parse_tool_calls_from_xml(content: &str) -> Vec<ParsedToolCall> {
 
                     // Convert to JSON string
                     if let Ok(json_args) = serde_json::to_string(&arguments) {
-                        eprintln!("  ✅ Arguments JSON: {}", json_args);
+                        tracing::debug!("  ✅ Arguments JSON: {}", json_args);
                         tool_calls.push(ParsedToolCall {
                             name: function_name.to_string(),
                             arguments: json_args,
@@ -0,0 +1,444 @@
+/// Outbound network policy shared by every HTTP request the app makes
+///
+/// Requests go through `send_checked` (an extension on `reqwest::RequestBuilder`) instead of
+/// `send`, which first checks the `[network]` section of thinkspace.toml:
+/// - `offline = true` blocks everything except loopback hosts (a local Qdrant or SearXNG)
+/// - `local_only = true` blocks cloud services, i.e. anything outside this machine and the
+///   local network; chat, embeddings, the TKG and web search then use the `[local]` services
+///   and SearXNG (see `local_only`)
+/// - a non-empty `allowed_domains` only lets those domains and their subdomains through
+/// - `max_requests` / `max_bytes` cap what this app session may use. A session here is the
+///   app's run, not a chat or agent session: one set of counters covers every request the
+///   process makes, starting at zero on launch, and `reset_network_usage` clears them
+///
+/// Bytes are counted from request bodies and response `Content-Length`, so chunked responses
+/// (streamed completions) count as requests only. Blocked requests fail with
+/// `NetError::Blocked` and are counted too, so `get_network_status` shows what was refused.
+///
+/// Every reqwest client comes from `HttpClients`, which applies `proxy`, `no_proxy` and
+/// `ca_certificates` from the same section; clients are rebuilt when those change. There is
+/// one client per set of `ClientOptions` (a timeout profile), and callers get clones of it, so
+/// they all share its connection pool and TLS sessions. `HttpClients` is managed state: the
+/// chat commands take it as `State`, and the agent (chat loop and tools) and the TKG hold it;
+/// `client` / `client_with` reach the same clients for code without access to either.
+/// Loopback hosts never go through the proxy. genai (ai_provider.rs) builds its own client, which only
+/// follows the proxy environment variables.
+
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url::Url;
+
+use crate::config::NetworkConfig;
+
+/// Always reached directly, whatever the proxy settings
+const LOOPBACK_NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+lazy_static::lazy_static! {
+    static ref CLIENTS: Arc<Mutex<HashMap<ClientOptions, reqwest::Client>>> = Arc::new(Mutex::new(HashMap::new()));
+    static ref USAGE: Mutex<NetworkUsage> = Mutex::new(NetworkUsage::default());
+}
+
+/// What a caller needs from its client on top of the shared proxy and certificates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ClientOptions {
+    pub user_agent: Option<&'static str>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DomainUsage {
+    pub requests: u64,
+    pub bytes: u64,
+    pub blocked: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NetworkUsage {
+    pub requests: u64,
+    pub bytes: u64,
+    pub blocked: u64,
+    pub by_domain: HashMap<String, DomainUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkStatus {
+    pub policy: NetworkConfig,
+    pub usage: NetworkUsage,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    /// Refused by the network policy; the message says why
+    Blocked(String),
+    Request(reqwest::Error),
+}
+
+impl std::fmt::Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Blocked(reason) => write!(f, "{}", reason),
+            NetError::Request(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetError::Blocked(_) => None,
+            NetError::Request(e) => Some(e),
+        }
+    }
+}
+
+impl From<reqwest::Error> for NetError {
+    fn from(e: reqwest::Error) -> Self {
+        NetError::Request(e)
+    }
+}
+
+/// The app's HTTP clients, built once per timeout profile. Cloning shares them.
+#[derive(Clone)]
+pub struct HttpClients(Arc<Mutex<HashMap<ClientOptions, reqwest::Client>>>);
+
+impl Default for HttpClients {
+    fn default() -> Self {
+        HttpClients(CLIENTS.clone())
+    }
+}
+
+impl HttpClients {
+    /// The clients managed by `app_handle`, or the process-wide ones before there is an app
+    pub fn of(app_handle: Option<&tauri::AppHandle>) -> Self {
+        use tauri::Manager;
+        app_handle
+            .and_then(|handle| handle.try_state::<HttpClients>())
+            .map(|clients| clients.inner().clone())
+            .unwrap_or_default()
+    }
+
+    /// A plain client for callers that don't need their own timeouts or user agent
+    pub fn client(&self) -> reqwest::Client {
+        self.client_with(ClientOptions::default())
+    }
+
+    /// A client with `options` and the configured proxy and CA certificates; built on first use
+    pub fn client_with(&self, options: ClientOptions) -> reqwest::Client {
+        let mut clients = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        clients.entry(options).or_insert_with(|| build_client(&crate::config::current().network, options)).clone()
+    }
+
+    /// Forget the clients so the next request uses new proxy or certificate settings
+    pub fn reset(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// A plain client from the shared `HttpClients`
+pub fn client() -> reqwest::Client {
+    HttpClients::default().client()
+}
+
+/// A client with `options` from the shared `HttpClients`, so this is cheap to call for every
+/// request
+pub fn client_with(options: ClientOptions) -> reqwest::Client {
+    HttpClients::default().client_with(options)
+}
+
+/// Forget the shared clients so the next request uses new proxy or certificate settings
+pub fn reset_clients() {
+    HttpClients::default().reset();
+}
+
+fn build_client(network: &NetworkConfig, options: ClientOptions) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder();
+    if let Some(user_agent) = options.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    match transport(builder, network).and_then(|b| b.build().map_err(|e| e.to_string())) {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("❌ Ignoring the proxy and certificate settings: {}", e);
+            reqwest::Client::new()
+        }
+    }
+}
+
+/// Apply the proxy and extra root certificates from `network` to `builder`
+pub fn transport(mut builder: reqwest::ClientBuilder, network: &NetworkConfig) -> Result<reqwest::ClientBuilder, String> {
+    if let Some(proxy) = network.proxy.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        let listed = network
+            .no_proxy
+            .clone()
+            .or_else(|| std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).ok())
+            .unwrap_or_default();
+        let no_proxy: Vec<&str> = listed.split(',').chain(LOOPBACK_NO_PROXY.split(',')).map(str::trim).filter(|h| !h.is_empty()).collect();
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| format!("Invalid proxy '{}': {}", proxy, e))?
+            .no_proxy(reqwest::NoProxy::from_string(&no_proxy.join(",")));
+        builder = builder.proxy(proxy);
+    }
+    for path in network.ca_certificates.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        let path = crate::config::expand_home(path);
+        let pem = std::fs::read(&path).map_err(|e| format!("Can't read CA certificate {}: {}", path.display(), e))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| format!("Invalid CA certificate {}: {}", path.display(), e))?;
+        if certificates.is_empty() {
+            return Err(format!("No certificates in {}", path.display()));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder)
+}
+
+fn is_loopback(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<std::net::IpAddr>().map_or(false, |ip| ip.is_loopback())
+}
+
+/// This machine or the local network: private and link-local addresses, and names that only
+/// resolve locally (`ollama`, `nas.local`, `qdrant.lan`, ...)
+fn is_local(host: &str) -> bool {
+    if is_loopback(host) {
+        return true;
+    }
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    match host.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(ip)) => ip.is_private() || ip.is_link_local(),
+        // fc00::/7 unique local, fe80::/10 link-local
+        Ok(std::net::IpAddr::V6(ip)) => (ip.segments()[0] & 0xfe00) == 0xfc00 || (ip.segments()[0] & 0xffc0) == 0xfe80,
+        Err(_) => {
+            !host.contains('.')
+                || [".local", ".lan", ".internal", ".home.arpa"].iter().any(|suffix| host.ends_with(suffix))
+        }
+    }
+}
+
+/// Whether local-only mode is on
+pub fn local_only() -> bool {
+    crate::config::current().network.local_only
+}
+
+/// `host` is `domain` or one of its subdomains; `*.` in front of a domain is optional
+fn matches_domain(host: &str, domain: &str) -> bool {
+    let domain = domain.trim().trim_start_matches("*.").trim_end_matches('.').to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+/// Why the policy refuses a request to `url`, if it does
+pub fn check(policy: &NetworkConfig, usage: &NetworkUsage, url: &Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default();
+    if is_loopback(host) {
+        return Ok(());
+    }
+    if policy.offline {
+        return Err(format!("Offline mode is on: request to {} blocked", host));
+    }
+    if policy.local_only && !is_local(host) {
+        return Err(format!("Local-only mode is on: {} is a cloud service and no local alternative is set up for this request", host));
+    }
+    if !policy.allowed_domains.is_empty() && !policy.allowed_domains.iter().any(|d| matches_domain(host, d)) {
+        return Err(format!("{} is not in the allowed domains of the network policy", host));
+    }
+    if let Some(max) = policy.max_requests.filter(|max| usage.requests >= *max) {
+        return Err(format!("Network request quota reached ({} requests this session)", max));
+    }
+    if let Some(max) = policy.max_bytes.filter(|max| usage.bytes >= *max) {
+        return Err(format!("Network data quota reached ({} bytes this session)", max));
+    }
+    Ok(())
+}
+
+/// The counters, even after a thread panicked holding them: they stay valid numbers, and a
+/// poisoned lock mustn't fail every request the app makes
+fn usage() -> std::sync::MutexGuard<'static, NetworkUsage> {
+    USAGE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn record_bytes(host: &str, bytes: u64) {
+    let mut usage = usage();
+    usage.by_domain.entry(host.to_string()).or_default().bytes += bytes;
+    usage.bytes += bytes;
+}
+
+/// Check a request to `url` against the policy and count it. `send_checked` does this itself;
+/// call it directly before requests made by clients that don't use reqwest (e.g. genai).
+pub fn admit(url: &Url) -> Result<(), NetError> {
+    let host = url.host_str().unwrap_or_default();
+    let mut guard = usage();
+    let usage = &mut *guard;
+    let verdict = check(&crate::config::current().network, usage, url);
+    let domain = usage.by_domain.entry(host.to_string()).or_default();
+    match verdict {
+        Ok(()) => {
+            domain.requests += 1;
+            usage.requests += 1;
+            Ok(())
+        }
+        Err(reason) => {
+            domain.blocked += 1;
+            usage.blocked += 1;
+            tracing::warn!(host = %host, "🚫 {}", reason);
+            Err(NetError::Blocked(reason))
+        }
+    }
+}
+
+#[async_trait]
+pub trait SendChecked {
+    /// `send`, after checking the network policy and counting the request against the quotas
+    async fn send_checked(self) -> Result<reqwest::Response, NetError>;
+}
+
+#[async_trait]
+impl SendChecked for reqwest::RequestBuilder {
+    async fn send_checked(self) -> Result<reqwest::Response, NetError> {
+        let (client, request) = self.build_split();
+        let request = request?;
+        admit(request.url())?;
+
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let sent = request.body().and_then(|b| b.as_bytes()).map_or(0, |b| b.len() as u64);
+        let response = client.execute(request).await;
+        let received = response.as_ref().ok().and_then(|r| r.content_length()).unwrap_or(0);
+        record_bytes(&host, sent + received);
+        Ok(response?)
+    }
+}
+
+#[tauri::command]
+pub fn get_network_status() -> Result<NetworkStatus, String> {
+    Ok(NetworkStatus {
+        policy: crate::config::current().network.clone(),
+        usage: usage().clone(),
+    })
+}
+
+/// Start the session's request and byte counts over
+#[tauri::command]
+pub fn reset_network_usage() -> Result<NetworkUsage, String> {
+    let mut usage = usage();
+    *usage = NetworkUsage::default();
+    Ok(usage.clone())
+}
+
+/// Flip offline mode and save it to thinkspace.toml
+#[tauri::command]
+pub fn set_offline_mode(app_handle: tauri::AppHandle, offline: bool) -> Result<NetworkConfig, String> {
+    let config = crate::config::update(&app_handle, |config| config.network.offline = offline)?;
+    Ok(config.network)
+}
+
+/// Flip local-only mode and save it to thinkspace.toml
+#[tauri::command]
+pub fn set_local_only(app_handle: tauri::AppHandle, local_only: bool) -> Result<NetworkConfig, String> {
+    let config = crate::config::update(&app_handle, |config| config.network.local_only = local_only)?;
+    tracing::info!("🏠 Local-only mode {}", if local_only { "on" } else { "off" });
+    Ok(config.network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(u: &str) -> Url {
+        Url::parse(u).unwrap()
+    }
+
+    #[test]
+    fn test_default_policy_allows_everything() {
+        let (policy, usage) = (NetworkConfig::default(), NetworkUsage::default());
+        assert!(check(&policy, &usage, &url("https://api.minimax.io/v1/chat")).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_domains_and_subdomains() {
+        let policy = NetworkConfig {
+            allowed_domains: vec!["wikipedia.org".to_string(), "*.github.com".to_string()],
+            ..Default::default()
+        };
+        let usage = NetworkUsage::default();
+        assert!(check(&policy, &usage, &url("https://en.wikipedia.org/wiki/Rust")).is_ok());
+        assert!(check(&policy, &usage, &url("https://api.github.com/repos/a/b")).is_ok());
+        assert!(check(&policy, &usage, &url("https://notwikipedia.org/")).is_err());
+    }
+
+    #[test]
+    fn test_offline_mode_reaches_only_this_machine() {
+        // Offline mode still reaches services on this machine
+        let policy = NetworkConfig {
+            offline: true,
+            allowed_domains: vec!["wikipedia.org".to_string()],
+            ..Default::default()
+        };
+        let usage = NetworkUsage::default();
+        assert!(check(&policy, &usage, &url("https://en.wikipedia.org/")).unwrap_err().contains("Offline"));
+        assert!(check(&policy, &usage, &url("http://localhost:6333/collections")).is_ok());
+        assert!(check(&policy, &usage, &url("http://[::1]:8080/search")).is_ok());
+    }
+
+    #[test]
+    fn test_local_only_mode_reaches_the_local_network() {
+        // Local-only mode reaches the local network too, but no cloud service
+        let policy = NetworkConfig { local_only: true, ..Default::default() };
+        let usage = NetworkUsage::default();
+        assert!(check(&policy, &usage, &url("https://api.minimax.io/v1/chat")).unwrap_err().contains("Local-only"));
+        assert!(check(&policy, &usage, &url("http://192.168.1.20:11434/api/embed")).is_ok());
+        assert!(check(&policy, &usage, &url("http://ollama:11434/v1/chat/completions")).is_ok());
+        assert!(check(&policy, &usage, &url("http://nas.local:6333/collections")).is_ok());
+        assert!(check(&policy, &usage, &url("http://[fd00::5]:8888/search")).is_ok());
+        assert!(check(&policy, &usage, &url("http://8.8.8.8/")).is_err());
+    }
+
+    #[test]
+    fn test_quotas() {
+        let policy = NetworkConfig { max_requests: Some(10), max_bytes: Some(1_000), ..Default::default() };
+        let mut usage = NetworkUsage { requests: 3, bytes: 1_000, ..Default::default() };
+        assert!(check(&policy, &usage, &url("https://en.wikipedia.org/")).unwrap_err().contains("data quota"));
+        usage.requests = 10;
+        assert!(check(&policy, &usage, &url("https://en.wikipedia.org/")).unwrap_err().contains("request quota"));
+    }
+
+    #[test]
+    fn test_transport_checks_proxy_and_certificates() {
+        // Proxy and certificate settings are checked before any client is built
+        let mut policy = NetworkConfig {
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: Some("intranet.corp, 10.0.0.0/8".to_string()),
+            ..Default::default()
+        };
+        assert!(transport(reqwest::Client::builder(), &policy).unwrap().build().is_ok());
+        policy.proxy = Some("not a proxy".to_string());
+        assert!(transport(reqwest::Client::builder(), &policy).unwrap_err().contains("Invalid proxy"));
+        policy.proxy = None;
+        policy.ca_certificates = vec!["/nonexistent/corp-ca.pem".to_string()];
+        assert!(transport(reqwest::Client::builder(), &policy).unwrap_err().contains("Can't read CA certificate"));
+    }
+
+    #[test]
+    fn test_http_clients_are_built_once_per_profile() {
+        let clients = HttpClients(Arc::new(Mutex::new(HashMap::new())));
+        let injected = clients.clone();
+        let slow = ClientOptions { timeout: Some(Duration::from_secs(300)), ..Default::default() };
+        injected.client();
+        injected.client_with(slow);
+        clients.client();
+        clients.client_with(slow);
+        assert_eq!(clients.0.lock().unwrap().len(), 2);
+
+        injected.reset();
+        assert!(clients.0.lock().unwrap().is_empty());
+
+        // Without an app, callers get the process-wide clients
+        assert!(Arc::ptr_eq(&HttpClients::of(None).0, &CLIENTS));
+    }
+}
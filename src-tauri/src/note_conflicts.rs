@@ -0,0 +1,118 @@
+/// Optimistic concurrency for note writes
+///
+/// Readers get an etag (content hash) with the note; writers pass it back and the write
+/// is rejected with a `merge_required` error if the file changed in between. This keeps
+/// UI edits and agent writes from silently overwriting each other.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use sha2::{Digest, Sha256};
+
+pub const MERGE_REQUIRED: &str = "merge_required";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteWithEtag {
+    pub content: String,
+    pub etag: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    pub path: String,
+    pub expected_etag: String,
+    /// None when the file was deleted since it was read
+    pub current_etag: Option<String>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.current_etag {
+            Some(current) => write!(
+                f,
+                "{}: {} changed since it was read (expected etag {}, now {}). Re-read the file and merge your changes.",
+                MERGE_REQUIRED, self.path, self.expected_etag, current
+            ),
+            None => write!(
+                f,
+                "{}: {} was deleted since it was read. Re-read before writing.",
+                MERGE_REQUIRED, self.path
+            ),
+        }
+    }
+}
+
+pub fn etag_for(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+/// Etag of the file on disk, or None if it doesn't exist
+pub fn current_etag(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|c| etag_for(&c))
+}
+
+/// Verify the file still matches what the writer read. No expectation means no check.
+pub fn check_expected(path: &Path, expected: Option<&str>) -> Result<(), Conflict> {
+    let expected = match expected {
+        Some(e) if !e.is_empty() => e,
+        _ => return Ok(()),
+    };
+
+    let current = current_etag(path);
+    if current.as_deref() == Some(expected) {
+        return Ok(());
+    }
+
+    Err(Conflict {
+        path: path.to_string_lossy().to_string(),
+        expected_etag: expected.to_string(),
+        current_etag: current,
+    })
+}
+
+#[tauri::command]
+pub async fn read_markdown_with_etag(path: String) -> Result<NoteWithEtag, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let etag = etag_for(&content);
+    Ok(NoteWithEtag { content, etag })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn original_note() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let note = dir.path().join("note.md");
+        std::fs::write(&note, "original").unwrap();
+        (dir, note)
+    }
+
+    #[test]
+    fn test_unchanged_note_passes() {
+        let (_dir, note) = original_note();
+        let etag = etag_for("original");
+        assert!(check_expected(&note, Some(&etag)).is_ok());
+        assert!(check_expected(&note, None).is_ok());
+    }
+
+    #[test]
+    fn test_edited_note_is_a_conflict() {
+        let (_dir, note) = original_note();
+        let etag = etag_for("original");
+        std::fs::write(&note, "edited in the UI").unwrap();
+        let conflict = check_expected(&note, Some(&etag)).unwrap_err();
+        assert_eq!(conflict.current_etag, Some(etag_for("edited in the UI")));
+        assert!(conflict.to_string().starts_with(MERGE_REQUIRED));
+    }
+
+    #[test]
+    fn test_deleted_note_is_a_conflict_without_etag() {
+        let (_dir, note) = original_note();
+        let etag = etag_for("original");
+        std::fs::remove_file(&note).unwrap();
+        assert!(check_expected(&note, Some(&etag)).unwrap_err().current_etag.is_none());
+    }
+}
@@ -0,0 +1,278 @@
+/// Wiki-link and markdown-link graph for the knowledge base
+///
+/// Links are extracted when kb_index (re-)indexes a note and stored unresolved in
+/// `kb_links`; they are resolved against the indexed documents at query time so
+/// links to notes created later still connect.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use rusqlite::{params, Connection};
+
+lazy_static::lazy_static! {
+    static ref WIKI_LINK: Regex = Regex::new(r"\[\[([^\[\]|#]+)(?:#[^\[\]|]*)?(?:\|[^\[\]]*)?\]\]").unwrap();
+    static ref MD_LINK: Regex = Regex::new(r"\[[^\]]*\]\(([^)\s]+\.md)(?:#[^)]*)?\)").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawLink {
+    pub kind: String,   // "wiki" or "markdown"
+    pub target: String, // Lowercased note name for wiki links, KB-relative path for markdown links
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRef {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteGraph {
+    pub nodes: Vec<NoteRef>,
+    pub edges: Vec<(String, String)>, // (source path, target path)
+    pub unresolved: Vec<(String, String)>, // (source path, link target) for notes that don't exist yet
+}
+
+/// Extract links from a note. `source_key` is the note's KB-relative path.
+pub fn extract_links(source_key: &str, content: &str) -> Vec<RawLink> {
+    let mut links = Vec::new();
+
+    for cap in WIKI_LINK.captures_iter(content) {
+        let name = cap[1].trim().trim_end_matches(".md").to_lowercase();
+        if !name.is_empty() {
+            links.push(RawLink { kind: "wiki".to_string(), target: name });
+        }
+    }
+
+    let source_dir = Path::new(source_key).parent().unwrap_or(Path::new(""));
+    for cap in MD_LINK.captures_iter(content) {
+        let href = urlencoding::decode(&cap[1]).map(|s| s.into_owned()).unwrap_or_else(|_| cap[1].to_string());
+        if href.contains("://") {
+            continue;
+        }
+        let target = normalize(&source_dir.join(&href));
+        links.push(RawLink { kind: "markdown".to_string(), target });
+    }
+
+    links.dedup();
+    links
+}
+
+/// Resolve `..` and `.` without touching the filesystem
+fn normalize(path: &Path) -> String {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out.to_string_lossy().replace('\\', "/")
+}
+
+pub fn init_links_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS kb_links (
+            source TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            target TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_kb_links_target ON kb_links(target)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_kb_links_source ON kb_links(source)", [])?;
+    Ok(())
+}
+
+pub fn replace_links(conn: &Connection, source_key: &str, links: &[RawLink]) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM kb_links WHERE source = ?1", params![source_key])?;
+    for link in links {
+        conn.execute(
+            "INSERT INTO kb_links (source, kind, target) VALUES (?1, ?2, ?3)",
+            params![source_key, link.kind, link.target],
+        )?;
+    }
+    Ok(())
+}
+
+/// Lowercased note name (file stem) used to resolve wiki links
+fn note_name(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn load_docs(conn: &Connection) -> rusqlite::Result<Vec<NoteRef>> {
+    let mut stmt = conn.prepare("SELECT path, title FROM kb_docs ORDER BY path")?;
+    let rows = stmt.query_map([], |row| Ok(NoteRef { path: row.get(0)?, title: row.get(1)? }))?;
+    rows.collect()
+}
+
+fn resolve(kind: &str, target: &str, by_path: &HashMap<String, usize>, by_name: &HashMap<String, usize>) -> Option<usize> {
+    match kind {
+        "markdown" => by_path.get(target).copied(),
+        // Wiki links may be bare names ("Note") or KB-relative ("research/Note")
+        _ => by_path
+            .get(&format!("{}.md", target))
+            .or_else(|| by_name.get(target))
+            .copied(),
+    }
+}
+
+fn lookup_maps(docs: &[NoteRef]) -> (HashMap<String, usize>, HashMap<String, usize>) {
+    let by_path = docs.iter().enumerate().map(|(i, d)| (d.path.to_lowercase(), i)).collect();
+    let by_name = docs.iter().enumerate().map(|(i, d)| (note_name(&d.path), i)).collect();
+    (by_path, by_name)
+}
+
+/// Resolve a wiki-link style note name ("Note" or "folder/Note") to an indexed path
+pub fn resolve_note_name(conn: &Connection, name: &str) -> rusqlite::Result<Option<String>> {
+    let docs = load_docs(conn)?;
+    let (by_path, by_name) = lookup_maps(&docs);
+    let target = name.trim().trim_end_matches(".md").to_lowercase();
+    Ok(resolve("wiki", &target, &by_path, &by_name).map(|i| docs[i].path.clone()))
+}
+
+/// Notes that link to `path` (KB-relative)
+pub fn backlinks(conn: &Connection, path: &str) -> rusqlite::Result<Vec<NoteRef>> {
+    let graph = note_graph(conn)?;
+    let mut sources: Vec<NoteRef> = graph.edges
+        .iter()
+        .filter(|(_, target)| target.eq_ignore_ascii_case(path))
+        .filter_map(|(source, _)| graph.nodes.iter().find(|n| &n.path == source).cloned())
+        .collect();
+    sources.dedup_by(|a, b| a.path == b.path);
+    Ok(sources)
+}
+
+/// Resolved outgoing links from `path`
+pub fn outgoing_links(conn: &Connection, path: &str) -> rusqlite::Result<Vec<NoteRef>> {
+    let graph = note_graph(conn)?;
+    let mut targets: Vec<NoteRef> = graph.edges
+        .iter()
+        .filter(|(source, _)| source.eq_ignore_ascii_case(path))
+        .filter_map(|(_, target)| graph.nodes.iter().find(|n| &n.path == target).cloned())
+        .collect();
+    targets.dedup_by(|a, b| a.path == b.path);
+    Ok(targets)
+}
+
+/// The whole knowledge base as a graph of resolved links
+pub fn note_graph(conn: &Connection) -> rusqlite::Result<NoteGraph> {
+    let docs = load_docs(conn)?;
+    let (by_path, by_name) = lookup_maps(&docs);
+
+    let mut stmt = conn.prepare("SELECT source, kind, target FROM kb_links")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+    })?;
+
+    let mut edges = Vec::new();
+    let mut unresolved = Vec::new();
+    for row in rows {
+        let (source, kind, target) = row?;
+        match resolve(&kind, &target.to_lowercase(), &by_path, &by_name) {
+            Some(i) if docs[i].path != source => edges.push((source, docs[i].path.clone())),
+            Some(_) => {}
+            None => unresolved.push((source, target)),
+        }
+    }
+    edges.sort();
+    edges.dedup();
+
+    Ok(NoteGraph { nodes: docs, edges, unresolved })
+}
+
+/// Turn an absolute or KB-relative note path into the index key used by kb_index
+pub fn note_key(kb_root: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    path.strip_prefix(kb_root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+pub fn open_index(app_handle: Option<&tauri::AppHandle>) -> Result<Connection, String> {
+    let db_path = crate::kb_index::db_path(app_handle).ok_or("Could not find app data dir")?;
+    crate::kb_index::open_db(&db_path).map_err(|e| format!("Failed to open KB index: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_backlinks(app_handle: tauri::AppHandle, path: String) -> Result<Vec<NoteRef>, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let conn = open_index(Some(&app_handle))?;
+    backlinks(&conn, &note_key(&kb_root, &path)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_note_graph(app_handle: tauri::AppHandle) -> Result<NoteGraph, String> {
+    let conn = open_index(Some(&app_handle))?;
+    note_graph(&conn).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_wiki_and_markdown_links() {
+        let content = "See [[Rust Ownership]] and [[traits#Generics|generic traits]].\n\
+                       Also [guide](../guides/intro.md#setup) and [web](https://example.com/a.md).";
+        let links = extract_links("research/notes/index.md", content);
+
+        assert_eq!(links, vec![
+            RawLink { kind: "wiki".to_string(), target: "rust ownership".to_string() },
+            RawLink { kind: "wiki".to_string(), target: "traits".to_string() },
+            RawLink { kind: "markdown".to_string(), target: "research/guides/intro.md".to_string() },
+        ]);
+    }
+
+    struct Indexed {
+        conn: rusqlite::Connection,
+        _kb: tempfile::TempDir,
+        _db: tempfile::NamedTempFile,
+    }
+
+    fn indexed_notes() -> Indexed {
+        let kb = tempfile::tempdir().unwrap();
+        let db = tempfile::NamedTempFile::new().unwrap();
+        let conn = crate::kb_index::open_db(db.path()).unwrap();
+
+        let research = kb.path().join("research");
+        std::fs::create_dir_all(&research).unwrap();
+        std::fs::write(research.join("Ownership.md"), "# Ownership\nSee [[Borrowing]] and [[Lifetimes]].").unwrap();
+        std::fs::write(research.join("Borrowing.md"), "# Borrowing\nBack to [ownership](Ownership.md).").unwrap();
+        crate::kb_index::refresh_all(&conn, kb.path(), &[]).unwrap();
+        Indexed { conn, _kb: kb, _db: db }
+    }
+
+    #[test]
+    fn test_graph_resolves_links_against_indexed_notes() {
+        let notes = indexed_notes();
+        let graph = note_graph(&notes.conn).unwrap();
+        assert_eq!(graph.edges, vec![
+            ("research/Borrowing.md".to_string(), "research/Ownership.md".to_string()),
+            ("research/Ownership.md".to_string(), "research/Borrowing.md".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_graph_lists_unresolved_links() {
+        let notes = indexed_notes();
+        let graph = note_graph(&notes.conn).unwrap();
+        assert_eq!(graph.unresolved, vec![("research/Ownership.md".to_string(), "lifetimes".to_string())]);
+    }
+
+    #[test]
+    fn test_backlinks() {
+        let notes = indexed_notes();
+        let back = backlinks(&notes.conn, "research/Ownership.md").unwrap();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].path, "research/Borrowing.md");
+    }
+}
@@ -0,0 +1,204 @@
+/// Per-note version history
+///
+/// Before any save overwrites a note, its previous content is snapshotted into
+/// `note_versions` in `knowledge_companion.db`, so edits from the UI and from the
+/// agent's write tools can always be rolled back.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+/// Oldest versions beyond this are pruned per note
+const MAX_VERSIONS_PER_NOTE: i64 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteVersion {
+    pub id: i64,
+    pub path: String,
+    pub hash: String,
+    pub size: i64,
+    /// What overwrote this content: "editor", "agent", "restore", ...
+    pub source: String,
+    pub created_at: String,
+}
+
+pub fn init_versions_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS note_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            path TEXT NOT NULL,
+            content TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            source TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_note_versions_path ON note_versions(path)", [])?;
+    Ok(())
+}
+
+fn sha256_hex(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Store `content` as a version of `path` unless it matches the latest stored version
+pub fn snapshot(conn: &Connection, path: &str, content: &str, source: &str) -> rusqlite::Result<Option<i64>> {
+    let hash = sha256_hex(content);
+    let latest: Option<String> = conn
+        .query_row(
+            "SELECT hash FROM note_versions WHERE path = ?1 ORDER BY id DESC LIMIT 1",
+            params![path],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if latest.as_deref() == Some(hash.as_str()) {
+        return Ok(None);
+    }
+
+    conn.execute(
+        "INSERT INTO note_versions (path, content, hash, source, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![path, content, hash, source, chrono::Utc::now().to_rfc3339()],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute(
+        "DELETE FROM note_versions WHERE path = ?1 AND id NOT IN (
+            SELECT id FROM note_versions WHERE path = ?1 ORDER BY id DESC LIMIT ?2
+        )",
+        params![path, MAX_VERSIONS_PER_NOTE],
+    )?;
+
+    Ok(Some(id))
+}
+
+/// Newest first
+pub fn list_versions(conn: &Connection, path: &str) -> rusqlite::Result<Vec<NoteVersion>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, path, hash, LENGTH(CAST(content AS BLOB)), source, created_at
+         FROM note_versions WHERE path = ?1 ORDER BY id DESC",
+    )?;
+    let rows = stmt.query_map(params![path], |row| {
+        Ok(NoteVersion {
+            id: row.get(0)?,
+            path: row.get(1)?,
+            hash: row.get(2)?,
+            size: row.get(3)?,
+            source: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// (path, content) of a stored version
+pub fn version_content(conn: &Connection, id: i64) -> rusqlite::Result<Option<(String, String)>> {
+    conn.query_row(
+        "SELECT path, content FROM note_versions WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+}
+
+/// Snapshot a file's current content before it gets overwritten.
+///
+/// Failures are logged rather than returned so history problems never block a save.
+pub fn record_before_write(app_handle: Option<&tauri::AppHandle>, kb_root: &Path, full_path: &Path, source: &str) {
+    let content = match std::fs::read_to_string(full_path) {
+        Ok(content) => content,
+        Err(_) => return, // New file (or not text) – nothing to preserve
+    };
+
+    let key = crate::kb_index::path_key(kb_root, full_path);
+    let result = crate::minimax_api::open_kc_database(app_handle)
+        .and_then(|conn| snapshot(&conn, &key, &content, source).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        tracing::warn!("⚠️ Could not save version of {}: {}", key, e);
+    }
+}
+
+// ==================== Commands ====================
+
+#[tauri::command]
+pub async fn list_note_versions(app_handle: tauri::AppHandle, path: String) -> Result<Vec<NoteVersion>, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let key = crate::kb_index::path_key(&kb_root, Path::new(&path));
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_versions(&conn, &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_note_version(app_handle: tauri::AppHandle, version_id: i64) -> Result<String, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    version_content(&conn, version_id)
+        .map_err(|e| e.to_string())?
+        .map(|(_, content)| content)
+        .ok_or_else(|| format!("Version {} not found", version_id))
+}
+
+/// Write a stored version back to disk; the content being replaced becomes a new version
+#[tauri::command]
+pub async fn restore_note_version(app_handle: tauri::AppHandle, version_id: i64) -> Result<String, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let (key, content) = version_content(&conn, version_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Version {} not found", version_id))?;
+
+    let full_path = kb_root.join(&key);
+    record_before_write(Some(&app_handle), &kb_root, &full_path, "restore");
+
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&full_path, &content).map_err(|e| e.to_string())?;
+
+    tracing::info!("⏪ Restored {} to version {}", key, version_id);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions_db() -> (tempfile::NamedTempFile, Connection) {
+        let db = tempfile::NamedTempFile::new().unwrap();
+        let conn = Connection::open(db.path()).unwrap();
+        init_versions_table(&conn).unwrap();
+        (db, conn)
+    }
+
+    #[test]
+    fn test_snapshot_skips_unchanged_content() {
+        let (_db, conn) = versions_db();
+        assert!(snapshot(&conn, "research/a.md", "v1", "editor").unwrap().is_some());
+        assert!(snapshot(&conn, "research/a.md", "v1", "agent").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_versions_newest_first() {
+        let (_db, conn) = versions_db();
+        snapshot(&conn, "research/a.md", "v1", "editor").unwrap();
+        let v2 = snapshot(&conn, "research/a.md", "v2", "agent").unwrap().unwrap();
+
+        let versions = list_versions(&conn, "research/a.md").unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].id, v2);
+        assert_eq!(versions[0].source, "agent");
+        assert_eq!(version_content(&conn, v2).unwrap().unwrap().1, "v2");
+    }
+
+    #[test]
+    fn test_snapshot_prunes_old_versions() {
+        let (_db, conn) = versions_db();
+        for i in 0..MAX_VERSIONS_PER_NOTE + 5 {
+            snapshot(&conn, "research/a.md", &format!("edit {}", i), "editor").unwrap();
+        }
+        assert_eq!(list_versions(&conn, "research/a.md").unwrap().len() as i64, MAX_VERSIONS_PER_NOTE);
+    }
+}
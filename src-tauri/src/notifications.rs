@@ -0,0 +1,154 @@
+/// Native desktop notifications, sorted into categories the user can switch off
+///
+/// Everything that pops up a system notification goes through `notify`: reminders coming
+/// due, finished research and scheduled tasks, backup results, and errors worth knowing
+/// about while the window is hidden (failed backups, tasks and subsystems). Each category
+/// has a switch in `[notifications]`; failures go to `errors` rather than their own
+/// category, so routine successes can be silenced without missing what went wrong. The
+/// `notification` event goes to the frontend either way, for an in-app list.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::NotificationConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    Reminders,
+    Research,
+    Backup,
+    Errors,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [Category::Reminders, Category::Research, Category::Backup, Category::Errors];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::Reminders => "Reminders",
+            Category::Research => "Completed research and scheduled tasks",
+            Category::Backup => "Backup status",
+            Category::Errors => "Errors",
+        }
+    }
+
+    pub fn allowed(self, prefs: &NotificationConfig) -> bool {
+        match self {
+            Category::Reminders => prefs.reminders,
+            Category::Research => prefs.research,
+            Category::Backup => prefs.backup,
+            Category::Errors => prefs.errors,
+        }
+    }
+
+    fn switch(self, prefs: &mut NotificationConfig) -> &mut bool {
+        match self {
+            Category::Reminders => &mut prefs.reminders,
+            Category::Research => &mut prefs.research,
+            Category::Backup => &mut prefs.backup,
+            Category::Errors => &mut prefs.errors,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub category: Category,
+    pub title: String,
+    pub body: String,
+    /// Whether it was shown on the desktop, i.e. its category is switched on
+    pub shown: bool,
+    pub at: String,
+}
+
+/// Show a desktop notification unless `category` is switched off; the frontend hears of it
+/// either way
+pub fn notify(app_handle: &AppHandle, category: Category, title: &str, body: &str) -> Notification {
+    let shown = category.allowed(&crate::config::current().notifications);
+    if shown {
+        let identifier = app_handle.config().tauri.bundle.identifier.clone();
+        if let Err(e) = tauri::api::notification::Notification::new(identifier).title(title).body(body).show() {
+            tracing::warn!("⚠️ Failed to show notification: {}", e);
+        }
+    }
+    let notification = Notification {
+        category,
+        title: title.to_string(),
+        body: body.to_string(),
+        shown,
+        at: crate::scheduler::stamp(Utc::now()),
+    };
+    let _ = app_handle.emit_all("notification", &notification);
+    notification
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryPreference {
+    pub category: Category,
+    pub label: &'static str,
+    pub enabled: bool,
+}
+
+fn preferences(prefs: &NotificationConfig) -> Vec<CategoryPreference> {
+    Category::ALL
+        .iter()
+        .map(|&category| CategoryPreference { category, label: category.label(), enabled: category.allowed(prefs) })
+        .collect()
+}
+
+// ==================== Commands ====================
+
+#[tauri::command]
+pub fn get_notification_preferences() -> Vec<CategoryPreference> {
+    preferences(&crate::config::current().notifications)
+}
+
+/// Switch one category on or off, saving it to `[notifications]`
+#[tauri::command]
+pub fn set_notification_preference(app_handle: AppHandle, category: Category, enabled: bool) -> Result<Vec<CategoryPreference>, String> {
+    let config = crate::config::update(&app_handle, |config| *category.switch(&mut config.notifications) = enabled)?;
+    Ok(preferences(&config.notifications))
+}
+
+/// Notify from the frontend, e.g. for an error the user should see while the window is hidden
+#[tauri::command]
+pub fn send_notification(app_handle: AppHandle, category: Category, title: String, body: String) -> Notification {
+    notify(&app_handle, category, &title, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn without_backups() -> NotificationConfig {
+        let mut prefs = NotificationConfig::default();
+        *Category::Backup.switch(&mut prefs) = false;
+        prefs
+    }
+
+    #[test]
+    fn test_every_category_allowed_by_default() {
+        let prefs = NotificationConfig::default();
+        assert!(Category::ALL.iter().all(|c| c.allowed(&prefs)));
+    }
+
+    #[test]
+    fn test_switch_turns_a_category_off() {
+        let prefs = without_backups();
+        assert!(!prefs.backup && !Category::Backup.allowed(&prefs));
+    }
+
+    #[test]
+    fn test_preferences_listing() {
+        let listed = preferences(&without_backups());
+        assert_eq!(listed.iter().filter(|p| p.enabled).count(), 3);
+        assert_eq!(serde_json::to_value(&listed[1]).unwrap()["category"], "research");
+    }
+
+    #[test]
+    fn test_category_names() {
+        assert_eq!(serde_json::from_str::<Category>("\"errors\"").unwrap(), Category::Errors);
+    }
+}
@@ -0,0 +1,689 @@
+/// Export knowledge base notes to Notion
+///
+/// Each note becomes a child page of the chosen parent page, its markdown converted to Notion
+/// blocks: headings, paragraphs with bold/italic/strikethrough/code/links, bulleted, numbered
+/// and task lists, quotes, dividers, tables, code blocks (with the language when Notion knows
+/// it) and images. Notion's API can't take local files, so only images with an http(s) URL
+/// are exported; local ones are listed as warnings and their alt text is kept.
+///
+/// `notion_export.json` in the profile's data folder maps each exported note (per parent page)
+/// to its Notion page and the hash of what was sent. Exporting again skips unchanged notes
+/// and replaces the content of changed ones in place, so pages are updated rather than
+/// duplicated; a page deleted in Notion is created again. Requests are spaced to Notion's
+/// limit of about three a second, and a 429 waits for its Retry-After.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+use std::time::{Duration, Instant};
+use crate::net::SendChecked;
+
+const API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+const MAP_FILE: &str = "notion_export.json";
+/// Notion allows an average of three requests a second
+const MIN_INTERVAL: Duration = Duration::from_millis(350);
+const MAX_RETRIES: usize = 3;
+/// Characters per rich text object
+const MAX_TEXT: usize = 2000;
+/// Blocks per create or append request
+const MAX_CHILDREN: usize = 100;
+/// Levels of children Notion accepts in one request
+const MAX_DEPTH: usize = 2;
+
+/// Notion's names for code block languages, with common markdown aliases
+const LANGUAGES: [(&str, &str); 40] = [
+    ("bash", "bash"), ("sh", "shell"), ("shell", "shell"), ("zsh", "shell"), ("c", "c"), ("cpp", "c++"), ("c++", "c++"),
+    ("cs", "c#"), ("csharp", "c#"), ("css", "css"), ("diff", "diff"), ("dockerfile", "docker"), ("docker", "docker"),
+    ("go", "go"), ("golang", "go"), ("graphql", "graphql"), ("html", "html"), ("java", "java"), ("js", "javascript"),
+    ("javascript", "javascript"), ("jsx", "javascript"), ("json", "json"), ("kotlin", "kotlin"), ("latex", "latex"),
+    ("lua", "lua"), ("makefile", "makefile"), ("markdown", "markdown"), ("md", "markdown"), ("mermaid", "mermaid"),
+    ("php", "php"), ("py", "python"), ("python", "python"), ("ruby", "ruby"), ("rs", "rust"), ("rust", "rust"),
+    ("sql", "sql"), ("swift", "swift"), ("ts", "typescript"), ("typescript", "typescript"), ("yaml", "yaml"),
+];
+
+lazy_static::lazy_static! {
+    static ref LAST_REQUEST: tokio::sync::Mutex<Option<Instant>> = tokio::sync::Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedNote {
+    /// Note path relative to the knowledge base
+    pub path: String,
+    pub page_id: String,
+    pub url: Option<String>,
+    /// "created", "updated" or "unchanged"
+    pub action: &'static str,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotionExportReport {
+    pub parent_page: String,
+    pub exported: Vec<ExportedNote>,
+    /// Content left out, such as local images
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// The Notion page each note was exported to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct NotionMap {
+    /// "<parent page id>:<note path>" -> page
+    pages: BTreeMap<String, MappedPage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MappedPage {
+    page_id: String,
+    url: Option<String>,
+    hash: String,
+}
+
+/// The page id in a Notion page URL or id, dashed
+fn parse_page_id(input: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    let path = trimmed.split(['?', '#']).next().unwrap_or_default();
+    let last = path.trim_end_matches('/').rsplit('/').next().unwrap_or_default().replace('-', "");
+    let hex = last.get(last.len().saturating_sub(32)..).unwrap_or_default();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' isn't a Notion page; use the page's URL or id", trimmed));
+    }
+    let hex = hex.to_lowercase();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+}
+
+fn block(kind: &str, body: Value) -> Value {
+    json!({ "object": "block", "type": kind, kind: body })
+}
+
+#[derive(Default)]
+struct Style {
+    bold: usize,
+    italic: usize,
+    strikethrough: usize,
+    link: Option<String>,
+}
+
+/// Rich text objects for `content`, split to Notion's length limit
+fn rich_text(content: &str, style: &Style, code: bool) -> Vec<Value> {
+    let chars: Vec<char> = content.chars().collect();
+    chars
+        .chunks(MAX_TEXT)
+        .map(|chunk| {
+            json!({
+                "type": "text",
+                "text": { "content": chunk.iter().collect::<String>(), "link": style.link.as_ref().map(|url| json!({ "url": url })) },
+                "annotations": {
+                    "bold": style.bold > 0,
+                    "italic": style.italic > 0,
+                    "strikethrough": style.strikethrough > 0,
+                    "code": code,
+                },
+            })
+        })
+        .collect()
+}
+
+fn plain_text(rich: &[Value]) -> String {
+    rich.iter().filter_map(|r| r["text"]["content"].as_str()).collect()
+}
+
+fn language(info: &str) -> &'static str {
+    let name = info.split_whitespace().next().unwrap_or_default().to_lowercase();
+    LANGUAGES.iter().find(|(alias, _)| *alias == name).map_or("plain text", |(_, notion)| notion)
+}
+
+enum Container {
+    Root,
+    Item { ordered: bool, checked: Option<bool> },
+    Quote,
+}
+
+/// A block that takes children while its markdown is being read
+struct Frame {
+    container: Container,
+    rich_text: Option<Vec<Value>>,
+    children: Vec<Value>,
+}
+
+impl Frame {
+    fn new(container: Container) -> Self {
+        Frame { container, rich_text: None, children: Vec::new() }
+    }
+}
+
+struct Table {
+    width: usize,
+    rows: Vec<Vec<Vec<Value>>>,
+    row: Vec<Vec<Value>>,
+}
+
+/// Markdown to Notion blocks
+struct Converter<'a> {
+    title: &'a str,
+    frames: Vec<Frame>,
+    text: Vec<Value>,
+    style: Style,
+    lists: Vec<bool>,
+    code: Option<(&'static str, String)>,
+    table: Option<Table>,
+    /// Alt text of the image being read, with its URL
+    image: Option<(String, String)>,
+    /// Images found in the current paragraph, added after it
+    images: Vec<Value>,
+    title_dropped: bool,
+    warnings: Vec<String>,
+}
+
+impl Converter<'_> {
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("the root frame is never popped")
+    }
+
+    /// Put the text read so far where it belongs: the open list item or quote's own text,
+    /// or a paragraph
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let images = std::mem::take(&mut self.images);
+        let frame = self.frame();
+        if !text.is_empty() {
+            if !matches!(frame.container, Container::Root) && frame.rich_text.is_none() && frame.children.is_empty() {
+                frame.rich_text = Some(text);
+            } else {
+                frame.children.push(block("paragraph", json!({ "rich_text": text })));
+            }
+        }
+        frame.children.extend(images);
+    }
+
+    fn push(&mut self, block: Value) {
+        self.flush();
+        self.frame().children.push(block);
+    }
+
+    fn add_text(&mut self, content: &str, code: bool) {
+        if let Some((_, source)) = &mut self.code {
+            source.push_str(content);
+        } else if let Some((alt, _)) = &mut self.image {
+            alt.push_str(content);
+        } else {
+            let rich = rich_text(content, &self.style, code);
+            self.text.extend(rich);
+        }
+    }
+
+    fn close_container(&mut self) {
+        self.flush();
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let mut body = json!({ "rich_text": frame.rich_text.unwrap_or_default() });
+        if !frame.children.is_empty() {
+            body["children"] = Value::Array(frame.children);
+        }
+        let kind = match frame.container {
+            Container::Item { checked: Some(checked), .. } => {
+                body["checked"] = json!(checked);
+                "to_do"
+            }
+            Container::Item { ordered: true, .. } => "numbered_list_item",
+            Container::Item { .. } => "bulleted_list_item",
+            Container::Quote => "quote",
+            Container::Root => return,
+        };
+        self.frame().children.push(block(kind, body));
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(Tag::Heading(..)) | Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::List(_)) | Event::Start(Tag::Table(_)) => {
+                self.flush();
+                match event {
+                    Event::Start(Tag::CodeBlock(kind)) => {
+                        let info = match kind {
+                            CodeBlockKind::Fenced(info) => language(&info),
+                            CodeBlockKind::Indented => "plain text",
+                        };
+                        self.code = Some((info, String::new()));
+                    }
+                    Event::Start(Tag::List(start)) => self.lists.push(start.is_some()),
+                    Event::Start(Tag::Table(aligns)) => self.table = Some(Table { width: aligns.len(), rows: Vec::new(), row: Vec::new() }),
+                    _ => {}
+                }
+            }
+            Event::End(Tag::Paragraph) => self.flush(),
+            Event::End(Tag::Heading(level, ..)) => {
+                let text = std::mem::take(&mut self.text);
+                // The page title already shows the note's own H1
+                if level == HeadingLevel::H1 && !self.title_dropped && plain_text(&text).trim() == self.title.trim() {
+                    self.title_dropped = true;
+                    return;
+                }
+                let kind = match level {
+                    HeadingLevel::H1 => "heading_1",
+                    HeadingLevel::H2 => "heading_2",
+                    _ => "heading_3",
+                };
+                self.push(block(kind, json!({ "rich_text": text })));
+            }
+            Event::End(Tag::CodeBlock(_)) => {
+                if let Some((language, source)) = self.code.take() {
+                    let source = source.strip_suffix('\n').unwrap_or(&source).to_string();
+                    self.push(block("code", json!({ "rich_text": rich_text(&source, &Style::default(), false), "language": language })));
+                }
+            }
+            Event::End(Tag::List(_)) => {
+                self.flush();
+                self.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                let ordered = self.lists.last().copied().unwrap_or(false);
+                self.frames.push(Frame::new(Container::Item { ordered, checked: None }));
+            }
+            Event::TaskListMarker(done) => {
+                if let Container::Item { checked, .. } = &mut self.frame().container {
+                    *checked = Some(done);
+                }
+            }
+            Event::Start(Tag::BlockQuote) => {
+                self.flush();
+                self.frames.push(Frame::new(Container::Quote));
+            }
+            Event::End(Tag::Item) | Event::End(Tag::BlockQuote) => self.close_container(),
+            Event::Start(Tag::TableHead) | Event::Start(Tag::TableRow) => {
+                if let Some(table) = &mut self.table {
+                    table.row.clear();
+                }
+            }
+            Event::End(Tag::TableCell) => {
+                let cell = std::mem::take(&mut self.text);
+                if let Some(table) = &mut self.table {
+                    table.row.push(cell);
+                }
+            }
+            Event::End(Tag::TableHead) | Event::End(Tag::TableRow) => {
+                if let Some(table) = &mut self.table {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                }
+            }
+            Event::End(Tag::Table(_)) => {
+                if let Some(table) = self.table.take() {
+                    let width = table.width.max(1);
+                    let rows: Vec<Value> = table
+                        .rows
+                        .into_iter()
+                        .map(|mut cells| {
+                            cells.resize(width, Vec::new());
+                            block("table_row", json!({ "cells": cells }))
+                        })
+                        .collect();
+                    self.push(block("table", json!({ "table_width": width, "has_column_header": true, "has_row_header": false, "children": rows })));
+                }
+            }
+            Event::Start(Tag::Emphasis) => self.style.italic += 1,
+            Event::End(Tag::Emphasis) => self.style.italic = self.style.italic.saturating_sub(1),
+            Event::Start(Tag::Strong) => self.style.bold += 1,
+            Event::End(Tag::Strong) => self.style.bold = self.style.bold.saturating_sub(1),
+            Event::Start(Tag::Strikethrough) => self.style.strikethrough += 1,
+            Event::End(Tag::Strikethrough) => self.style.strikethrough = self.style.strikethrough.saturating_sub(1),
+            // Notion only takes absolute links; relative ones stay as plain text
+            Event::Start(Tag::Link(_, url, _)) => {
+                self.style.link = Some(url.to_string()).filter(|u| u.starts_with("http://") || u.starts_with("https://") || u.starts_with("mailto:"));
+            }
+            Event::End(Tag::Link(..)) => self.style.link = None,
+            Event::Start(Tag::Image(_, url, _)) => self.image = Some((String::new(), url.to_string())),
+            Event::End(Tag::Image(..)) => {
+                if let Some((alt, url)) = self.image.take() {
+                    if url.starts_with("http://") || url.starts_with("https://") {
+                        let caption = rich_text(&alt, &Style::default(), false);
+                        self.images.push(block("image", json!({ "type": "external", "external": { "url": url }, "caption": caption })));
+                    } else {
+                        self.warnings.push(format!("Local image '{}' wasn't exported", url));
+                        let alt = if alt.is_empty() { url } else { alt };
+                        self.add_text(&format!("[image: {}]", alt), false);
+                    }
+                }
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                self.flush();
+                self.add_text(&format!("[^{}]: ", label), false);
+            }
+            Event::Text(text) | Event::Html(text) => self.add_text(&text, false),
+            Event::Code(code) => self.add_text(&code, true),
+            Event::FootnoteReference(label) => self.add_text(&format!("[^{}]", label), false),
+            Event::SoftBreak => self.add_text(" ", false),
+            Event::HardBreak => self.add_text("\n", false),
+            Event::Rule => self.push(block("divider", json!({}))),
+            _ => {}
+        }
+    }
+}
+
+/// Move children nested deeper than Notion takes in one request up beside their parent
+fn limit_depth(blocks: Vec<Value>, depth: usize) -> Vec<Value> {
+    let mut limited = Vec::new();
+    for mut block in blocks {
+        let kind = block["type"].as_str().unwrap_or_default().to_string();
+        let children = match block[&kind].as_object_mut().and_then(|body| body.remove("children")) {
+            Some(Value::Array(children)) if !children.is_empty() => children,
+            _ => {
+                limited.push(block);
+                continue;
+            }
+        };
+        if depth < MAX_DEPTH {
+            block[&kind]["children"] = Value::Array(limit_depth(children, depth + 1));
+            limited.push(block);
+        } else {
+            limited.push(block);
+            limited.extend(limit_depth(children, depth));
+        }
+    }
+    limited
+}
+
+/// The blocks for a note's body (frontmatter removed), and what couldn't be exported
+pub fn markdown_to_blocks(markdown: &str, title: &str) -> (Vec<Value>, Vec<String>) {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES;
+    let mut converter = Converter {
+        title,
+        frames: vec![Frame::new(Container::Root)],
+        text: Vec::new(),
+        style: Style::default(),
+        lists: Vec::new(),
+        code: None,
+        table: None,
+        image: None,
+        images: Vec::new(),
+        title_dropped: false,
+        warnings: Vec::new(),
+    };
+    for event in Parser::new_ext(markdown, options) {
+        converter.event(event);
+    }
+    converter.flush();
+    let root = converter.frames.swap_remove(0);
+    (limit_depth(root.children, 0), converter.warnings)
+}
+
+fn note_hash(title: &str, content: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{}\n{}", title, content).as_bytes()))[..16].to_string()
+}
+
+#[derive(Debug)]
+struct ApiError {
+    status: Option<u16>,
+    message: String,
+}
+
+impl ApiError {
+    fn not_found(&self) -> bool {
+        self.status == Some(404)
+    }
+}
+
+/// One Notion API call, spaced from the last and retried on 429
+async fn call(token: &str, method: reqwest::Method, path: &str, body: Option<&Value>) -> Result<Value, ApiError> {
+    let url = format!("{}{}", API, path);
+    for attempt in 0..=MAX_RETRIES {
+        {
+            let mut last = LAST_REQUEST.lock().await;
+            if let Some(wait) = last.map(|at| MIN_INTERVAL.saturating_sub(at.elapsed())) {
+                tokio::time::sleep(wait).await;
+            }
+            *last = Some(Instant::now());
+        }
+        let mut request = crate::net::client()
+            .request(method.clone(), &url)
+            .bearer_auth(token.trim())
+            .header("Notion-Version", NOTION_VERSION);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send_checked().await.map_err(|e| ApiError { status: None, message: format!("Notion request failed: {}", e) })?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let wait = response.headers().get("retry-after").and_then(|v| v.to_str().ok()).and_then(|s| s.trim().parse::<u64>().ok()).unwrap_or(1);
+            tracing::info!("⏳ Notion rate limit reached, waiting {}s", wait);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            continue;
+        }
+        let value: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = value["message"].as_str().unwrap_or("no details");
+            return Err(ApiError { status: Some(status.as_u16()), message: format!("Notion returned HTTP {} for {}: {}", status, path, message) });
+        }
+        return Ok(value);
+    }
+    Err(ApiError { status: Some(429), message: "Notion rate limit reached; try again later".to_string() })
+}
+
+fn title_property(title: &str) -> Value {
+    json!({ "title": { "title": rich_text(title, &Style::default(), false) } })
+}
+
+async fn append(token: &str, page_id: &str, blocks: &[Value]) -> Result<(), ApiError> {
+    for batch in blocks.chunks(MAX_CHILDREN) {
+        call(token, reqwest::Method::PATCH, &format!("/blocks/{}/children", page_id), Some(&json!({ "children": batch }))).await?;
+    }
+    Ok(())
+}
+
+async fn create_page(token: &str, parent: &str, title: &str, blocks: &[Value]) -> Result<(String, Option<String>), ApiError> {
+    let (first, rest) = blocks.split_at(blocks.len().min(MAX_CHILDREN));
+    let body = json!({ "parent": { "page_id": parent }, "properties": title_property(title), "children": first });
+    let page = call(token, reqwest::Method::POST, "/pages", Some(&body)).await?;
+    let page_id = page["id"].as_str().unwrap_or_default().to_string();
+    append(token, &page_id, rest).await?;
+    Ok((page_id, page["url"].as_str().map(str::to_string)))
+}
+
+/// Retitle an exported page and replace its content
+async fn update_page(token: &str, page_id: &str, title: &str, blocks: &[Value]) -> Result<Option<String>, ApiError> {
+    let page = call(token, reqwest::Method::PATCH, &format!("/pages/{}", page_id), Some(&json!({ "properties": title_property(title) }))).await?;
+    if page["archived"].as_bool().unwrap_or(false) || page["in_trash"].as_bool().unwrap_or(false) {
+        return Err(ApiError { status: Some(404), message: "Page was deleted in Notion".to_string() });
+    }
+
+    let mut old_blocks = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut path = format!("/blocks/{}/children?page_size=100", page_id);
+        if let Some(cursor) = &cursor {
+            path.push_str(&format!("&start_cursor={}", urlencoding::encode(cursor)));
+        }
+        let listed = call(token, reqwest::Method::GET, &path, None).await?;
+        old_blocks.extend(listed["results"].as_array().into_iter().flatten().filter_map(|b| b["id"].as_str().map(str::to_string)));
+        cursor = listed["next_cursor"].as_str().map(str::to_string).filter(|_| listed["has_more"].as_bool().unwrap_or(false));
+        if cursor.is_none() {
+            break;
+        }
+    }
+    for id in old_blocks {
+        call(token, reqwest::Method::DELETE, &format!("/blocks/{}", id), None).await?;
+    }
+    append(token, page_id, blocks).await?;
+    Ok(page["url"].as_str().map(str::to_string))
+}
+
+fn load_map(path: &Path) -> NotionMap {
+    std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok()).unwrap_or_default()
+}
+
+fn save_map(path: &Path, map: &NotionMap) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(map).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| format!("Failed to save {}: {}", MAP_FILE, e))
+}
+
+/// Export a note, or every note in a folder, as child pages of `parent_page` (a Notion page
+/// URL or id). The integration behind `notion_token` must have access to that page.
+#[tauri::command]
+pub async fn export_to_notion(
+    app_handle: tauri::AppHandle,
+    folder_or_note: String,
+    notion_token: String,
+    parent_page: String,
+) -> Result<NotionExportReport, String> {
+    if notion_token.trim().is_empty() {
+        return Err("A Notion integration token is required".to_string());
+    }
+    let parent = parse_page_id(&parent_page)?;
+    let selected = folder_or_note.trim().trim_matches('/');
+    if selected.is_empty() || Path::new(selected).components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err("Choose a note or folder inside the knowledge base".to_string());
+    }
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let notes = crate::site_export::collect_notes(&kb_root, Some(selected));
+    if notes.is_empty() {
+        return Err(format!("No notes found in {}", selected));
+    }
+    let map_path = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?.join(MAP_FILE);
+    let mut map = load_map(&map_path);
+    let mut report = NotionExportReport { parent_page: parent.clone(), ..Default::default() };
+    tracing::info!("📤 Exporting {} note(s) to Notion", notes.len());
+
+    for note in notes {
+        let key = crate::kb_index::path_key(&kb_root, &note);
+        let content = match std::fs::read_to_string(&note) {
+            Ok(content) => content,
+            Err(e) => {
+                report.errors.push(format!("{}: {}", key, e));
+                continue;
+            }
+        };
+        let title = crate::site_export::title_for(&key, &content);
+        let hash = note_hash(&title, &content);
+        let map_key = format!("{}:{}", parent, key);
+        let mapped = map.pages.get(&map_key).cloned();
+        if let Some(mapped) = mapped.as_ref().filter(|m| m.hash == hash) {
+            report.exported.push(ExportedNote { path: key, page_id: mapped.page_id.clone(), url: mapped.url.clone(), action: "unchanged" });
+            continue;
+        }
+
+        let (blocks, warnings) = markdown_to_blocks(crate::frontmatter::split_frontmatter(&content).1, &title);
+        report.warnings.extend(warnings.into_iter().map(|w| format!("{}: {}", key, w)));
+        let updated = match &mapped {
+            Some(mapped) => match update_page(&notion_token, &mapped.page_id, &title, &blocks).await {
+                Ok(url) => Ok(Some((mapped.page_id.clone(), url))),
+                Err(e) if e.not_found() => Ok(None),
+                Err(e) => Err(e),
+            },
+            None => Ok(None),
+        };
+        let exported = match updated {
+            Ok(Some((page_id, url))) => Ok((page_id, url, "updated")),
+            Ok(None) => create_page(&notion_token, &parent, &title, &blocks).await.map(|(page_id, url)| (page_id, url, "created")),
+            Err(e) => Err(e),
+        };
+        match exported {
+            Ok((page_id, url, action)) => {
+                map.pages.insert(map_key, MappedPage { page_id: page_id.clone(), url: url.clone(), hash });
+                // Saved per note so an export that fails partway still updates next time
+                if let Err(e) = save_map(&map_path, &map) {
+                    report.errors.push(e);
+                }
+                report.exported.push(ExportedNote { path: key, page_id, url, action });
+            }
+            Err(e) => {
+                report.errors.push(format!("{}: {}", key, e.message));
+                if e.status == Some(401) || e.status == Some(403) {
+                    // Every other note would fail the same way
+                    break;
+                }
+            }
+        }
+    }
+
+    if report.exported.is_empty() {
+        return Err(format!("Nothing exported to Notion: {}", report.errors.join("; ")));
+    }
+    tracing::info!("📤 Exported {} note(s) to Notion ({} error(s))", report.exported.len(), report.errors.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "# Lifetimes\n\nA **lifetime** is a [scope](https://doc.rust-lang.org) of `'a`.\n\n\
+                            ## Rules\n\n- one\n  - nested\n    - deeper\n- [x] done\n\n1. first\n\n> quoted\n\n---\n\n\
+                            ```rs\nfn main() {}\n```\n\n| Rule | Meaning |\n| --- | --- |\n| elision | implied |\n\n\
+                            ![diagram](https://example.com/d.png) ![local](assets/x.png)\n";
+
+    fn sample_blocks() -> Vec<Value> {
+        markdown_to_blocks(MARKDOWN, "Lifetimes").0
+    }
+
+    #[test]
+    fn test_parse_page_id() {
+        assert_eq!(parse_page_id("https://www.notion.so/team/Research-0123456789abcdef0123456789ABCDEF?pvs=4").unwrap(), "01234567-89ab-cdef-0123-456789abcdef");
+        assert_eq!(parse_page_id("01234567-89ab-cdef-0123-456789abcdef").unwrap(), "01234567-89ab-cdef-0123-456789abcdef");
+        assert!(parse_page_id("https://www.notion.so/team/Research").is_err());
+    }
+
+    #[test]
+    fn test_markdown_to_blocks() {
+        let (blocks, warnings) = markdown_to_blocks(MARKDOWN, "Lifetimes");
+        let kinds: Vec<&str> = blocks.iter().map(|b| b["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            kinds,
+            vec!["paragraph", "heading_2", "bulleted_list_item", "to_do", "numbered_list_item", "quote", "divider", "code", "table", "paragraph", "image"]
+        );
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_inline_styles_and_links() {
+        let blocks = sample_blocks();
+        let paragraph = blocks[0]["paragraph"]["rich_text"].as_array().unwrap();
+        assert_eq!(plain_text(paragraph), "A lifetime is a scope of 'a.");
+        assert_eq!(paragraph[1]["annotations"]["bold"], true);
+        assert_eq!(paragraph[3]["text"]["link"]["url"], "https://doc.rust-lang.org");
+        assert_eq!(paragraph[5]["annotations"]["code"], true);
+    }
+
+    #[test]
+    fn test_nested_lists_and_depth_limit() {
+        let blocks = sample_blocks();
+        let nested = blocks[2]["bulleted_list_item"]["children"].as_array().unwrap();
+        let deepest = nested[0]["bulleted_list_item"]["children"].as_array().unwrap();
+        assert_eq!(plain_text(deepest[0]["bulleted_list_item"]["rich_text"].as_array().unwrap()), "deeper");
+        // Nested a level deeper than Notion takes, "deeper" moves up beside "nested"
+        let lifted = limit_depth(blocks[2..3].to_vec(), 1);
+        assert_eq!(lifted[0]["bulleted_list_item"]["children"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_todo_code_and_table_blocks() {
+        let blocks = sample_blocks();
+        assert_eq!(blocks[3]["to_do"]["checked"], true);
+        assert_eq!(blocks[7]["code"]["language"], "rust");
+        assert_eq!(plain_text(blocks[7]["code"]["rich_text"].as_array().unwrap()), "fn main() {}");
+        assert_eq!(blocks[8]["table"]["table_width"], 2);
+        assert_eq!(blocks[8]["table"]["children"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_remote_images_embed_and_local_ones_become_text() {
+        let blocks = sample_blocks();
+        assert_eq!(blocks[10]["image"]["external"]["url"], "https://example.com/d.png");
+        assert_eq!(plain_text(blocks[9]["paragraph"]["rich_text"].as_array().unwrap()).trim(), "[image: local]");
+    }
+
+    #[test]
+    fn test_long_text_is_chunked() {
+        let long = "x".repeat(MAX_TEXT + 1);
+        assert_eq!(rich_text(&long, &Style::default(), false).len(), 2);
+    }
+
+    #[test]
+    fn test_note_hash_tracks_content() {
+        assert_ne!(note_hash("a", "b"), note_hash("a", "c"));
+    }
+}
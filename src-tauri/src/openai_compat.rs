@@ -0,0 +1,567 @@
+/// OpenAI-compatible chat completions backed by the full agent loop
+///
+/// Takes a request shaped like `POST /v1/chat/completions` and answers the way OpenAI does,
+/// but the answer comes from a `MinimaxAgent` with its tools, TKG memory (under the
+/// request's `user`) and safe mode, not from a bare model. `model` picks the provider:
+/// `thinkspace` (the default provider), `thinkspace/<provider>` or
+/// `thinkspace/<provider>/<model>`, where the provider is minimax, grok or gemini.
+///
+/// With `[api_server] enabled = true` and a `token`, a listener on `127.0.0.1:<port>` serves
+/// `POST /v1/chat/completions` and `GET /v1/models` to local clients that send
+/// `Authorization: Bearer <token>`. Like the chat bridge, it answers with the keys the
+/// frontend gave `start_scheduler` and only the read-only tools in SAFE_TOOLS unless
+/// `[api_server] tools` names more. The listener follows config changes. The
+/// `openai_chat_completion` command answers the same requests from the frontend with the
+/// keys it passes. Client `system`/`developer` messages are folded into the agent's system
+/// prompt, and the conversation must end with a user message. Streaming isn't covered: a
+/// `stream: true` request is refused.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::config::ApiServerConfig;
+use crate::minimax_enhanced::{AIProvider, ChatResponse, Message, MinimaxAgent};
+
+/// Agent iterations per completion; a request can't raise it
+const MAX_ITERATIONS: usize = 20;
+/// Larger request bodies are refused
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// How long a client gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the listener checks `[api_server]` for changes
+const CONFIG_POLL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatCompletionRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    /// Whose memories the agent reads and writes
+    pub user: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<MessageContent>,
+}
+
+/// Plain text, or the list of parts newer clients send
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentPart {
+    #[serde(rename = "type")]
+    pub part_type: String,
+    pub text: Option<String>,
+}
+
+impl MessageContent {
+    /// The text of the message; images and other parts are dropped
+    fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter(|p| p.part_type == "text")
+                .filter_map(|p| p.text.as_deref())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChatCompletion {
+    pub id: String,
+    pub object: &'static str,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<Choice>,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Choice {
+    pub index: usize,
+    pub message: AssistantMessage,
+    pub finish_reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssistantMessage {
+    pub role: &'static str,
+    pub content: String,
+}
+
+/// Estimated, as the agent's own calls aren't counted
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Usage {
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    pub total_tokens: usize,
+}
+
+/// Reject `[api_server]` settings that parse but can't be used
+pub fn check_config(config: &ApiServerConfig) -> Result<(), String> {
+    if config.enabled && config.token.as_deref().map_or(true, |t| t.trim().is_empty()) {
+        return Err("[api_server] needs a token when it's enabled".to_string());
+    }
+    if config.port == 0 {
+        return Err("port in [api_server] must be set".to_string());
+    }
+    let known = MinimaxAgent::tool_names();
+    if let Some(unknown) = config.tools.iter().find(|tool| !known.contains(tool)) {
+        return Err(format!("Unknown tool '{}' in [api_server] tools", unknown));
+    }
+    Ok(())
+}
+
+/// The provider and model override `model` asks for
+fn parse_model(model: &str) -> Result<(Option<AIProvider>, Option<String>), String> {
+    let mut parts = model.splitn(3, '/');
+    if parts.next() != Some("thinkspace") {
+        return Err(format!("Unknown model '{}'; use thinkspace, thinkspace/<provider> or thinkspace/<provider>/<model>", model));
+    }
+    let provider = match parts.next() {
+        Some(name) => Some(serde_json::from_value(serde_json::Value::String(name.to_lowercase())).map_err(|_| format!("Unknown provider '{}'", name))?),
+        None => None,
+    };
+    Ok((provider, parts.next().filter(|m| !m.is_empty()).map(str::to_string)))
+}
+
+/// The client's instructions and its conversation as agent history. `system` and `developer`
+/// messages, wherever they are, become the instructions added to the agent's own system
+/// prompt. Tool calls and results from the client's own tools mean nothing to the agent, so
+/// they're left out. The conversation has to end with the user's message to answer.
+fn to_history(messages: &[ChatMessage]) -> Result<(String, Vec<Message>), String> {
+    let mut instructions = Vec::new();
+    let mut history = Vec::new();
+    for message in messages {
+        let content = message.content.as_ref().map(MessageContent::text).unwrap_or_default();
+        if content.trim().is_empty() {
+            continue;
+        }
+        match message.role.as_str() {
+            "system" | "developer" => instructions.push(content),
+            "user" | "assistant" => history.push(Message {
+                role: message.role.clone(),
+                content,
+                tool_calls: None,
+                tool_call_id: None,
+                timestamp: None,
+            }),
+            _ => {}
+        }
+    }
+    match history.last() {
+        Some(last) if last.role == "user" => Ok((instructions.join("\n\n"), history)),
+        Some(_) => Err("The last message must be from the user".to_string()),
+        None => Err("The request has no user message".to_string()),
+    }
+}
+
+/// What a valid request asks of the agent
+#[derive(Debug)]
+struct Prepared {
+    provider: Option<AIProvider>,
+    model: Option<String>,
+    instructions: String,
+    history: Vec<Message>,
+}
+
+fn prepare(request: &ChatCompletionRequest) -> Result<Prepared, String> {
+    if request.stream {
+        return Err("Streaming completions aren't supported yet; send stream: false".to_string());
+    }
+    let (provider, model) = parse_model(&request.model)?;
+    let (instructions, history) = to_history(&request.messages)?;
+    Ok(Prepared { provider, model, instructions, history })
+}
+
+fn completion(model: &str, history: &[Message], response: &ChatResponse) -> ChatCompletion {
+    let prompt_tokens = history.iter().map(|m| crate::deep_research::estimate_tokens(&m.content)).sum();
+    let completion_tokens = crate::deep_research::estimate_tokens(&response.content);
+    ChatCompletion {
+        id: format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+        object: "chat.completion",
+        created: chrono::Utc::now().timestamp(),
+        model: model.to_string(),
+        choices: vec![Choice {
+            index: 0,
+            message: AssistantMessage { role: "assistant", content: response.content.clone() },
+            finish_reason: "stop",
+        }],
+        usage: Usage { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens },
+    }
+}
+
+/// Run `prepared` on `agent` and answer as `model`
+async fn run(mut agent: MinimaxAgent, model: &str, prepared: Prepared) -> Result<ChatCompletion, String> {
+    if let Some(provider) = prepared.provider {
+        agent = agent.with_provider(provider);
+    }
+    if let Some(model) = prepared.model {
+        agent = agent.with_model(model);
+    }
+    // After the provider, whose default prompt they're added to
+    if !prepared.instructions.is_empty() {
+        agent = agent.with_instructions(&prepared.instructions);
+    }
+    let mut agent = agent.with_history(prepared.history.clone());
+
+    tracing::info!("🔌 OpenAI-compatible completion for {} ({} messages)", model, prepared.history.len());
+    let response = agent.chat(MAX_ITERATIONS).await?;
+    Ok(completion(model, &prepared.history, &response))
+}
+
+// ==================== Local API server ====================
+
+/// The parts of an HTTP request the server looks at
+#[derive(Debug, PartialEq)]
+struct RequestHead {
+    method: String,
+    /// Without the query string
+    path: String,
+    content_length: usize,
+    authorization: Option<String>,
+}
+
+/// Parse the request line and headers of an HTTP/1.x request
+fn parse_head(head: &str) -> Result<RequestHead, String> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (method, target) = match (request_line.next(), request_line.next(), request_line.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => (method, target),
+        _ => return Err("Malformed request line".to_string()),
+    };
+    let mut headers = HashMap::new();
+    for line in lines.filter(|l| !l.is_empty()) {
+        let (name, value) = line.split_once(':').ok_or("Malformed header")?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    let content_length = match headers.get("content-length") {
+        Some(length) => length.parse().map_err(|_| "Invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    Ok(RequestHead {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        content_length,
+        authorization: headers.remove("authorization"),
+    })
+}
+
+/// Whether `authorization` carries the configured bearer token
+fn authorized(authorization: Option<&str>, token: &str) -> bool {
+    let sent = authorization.and_then(|a| a.strip_prefix("Bearer ")).map(str::trim);
+    // Compared in full either way, so the time taken doesn't give away a matching prefix
+    sent.map_or(false, |sent| {
+        sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    })
+}
+
+/// An error in the shape OpenAI clients expect
+fn error_body(message: &str, kind: &str) -> serde_json::Value {
+    serde_json::json!({ "error": { "message": message, "type": kind, "code": null } })
+}
+
+fn http_response(status: u16, body: &serde_json::Value) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+/// The models `GET /v1/models` lists
+fn model_list() -> serde_json::Value {
+    let data: Vec<_> = ["thinkspace", "thinkspace/minimax", "thinkspace/grok", "thinkspace/gemini"]
+        .iter()
+        .map(|id| serde_json::json!({ "id": id, "object": "model", "created": 0, "owned_by": "thinkspace" }))
+        .collect();
+    serde_json::json!({ "object": "list", "data": data })
+}
+
+/// Read one request: its head and a body of `Content-Length` bytes
+async fn read_request(stream: &mut TcpStream) -> Result<(RequestHead, Vec<u8>), (u16, String)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err((400, "Request headers are too large".to_string()));
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| (400, e.to_string()))?;
+        if read == 0 {
+            return Err((400, "Connection closed before the request was complete".to_string()));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+    let head = parse_head(&String::from_utf8_lossy(&buffer[..head_end])).map_err(|e| (400, e))?;
+    if head.content_length > MAX_BODY_BYTES {
+        return Err((413, format!("Request bodies are limited to {} MB", MAX_BODY_BYTES / (1024 * 1024))));
+    }
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < head.content_length {
+        let read = stream.read(&mut chunk).await.map_err(|e| (400, e.to_string()))?;
+        if read == 0 {
+            return Err((400, "Connection closed before the request body was complete".to_string()));
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(head.content_length);
+    Ok((head, body))
+}
+
+/// Answer a completion request with the scheduler's keys
+async fn serve_completion(app_handle: &AppHandle, config: &ApiServerConfig, body: &[u8]) -> (u16, serde_json::Value) {
+    let request: ChatCompletionRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return (400, error_body(&format!("Invalid request body: {}", e), "invalid_request_error")),
+    };
+    let prepared = match prepare(&request) {
+        Ok(prepared) => prepared,
+        Err(e) => return (400, error_body(&e, "invalid_request_error")),
+    };
+    let (default_provider, keys, scheduler_user) = match crate::scheduler::keys() {
+        Some(keys) => keys,
+        None => return (503, error_body("The scheduler hasn't been started with API keys yet; open ThinkSpace first", "server_error")),
+    };
+    let provider = prepared.provider.clone().unwrap_or(default_provider);
+    let user_id = crate::profiles::tkg_user_id(request.user.as_deref().unwrap_or(&scheduler_user));
+    let agent = MinimaxAgent::new(keys.primary(&provider).unwrap_or_default(), keys.tavily.clone(), keys.grok.clone(), keys.gemini.clone())
+        .with_provider(provider)
+        .with_app_handle(app_handle.clone())
+        .with_enabled_tools(crate::chat_bridge::enabled_tools(&MinimaxAgent::tool_names(), &config.tools))
+        .with_user_id(user_id);
+    match run(agent, &request.model, prepared).await {
+        Ok(completion) => (200, serde_json::to_value(completion).unwrap_or_default()),
+        Err(e) => (500, error_body(&e, "server_error")),
+    }
+}
+
+async fn handle_connection(app_handle: AppHandle, mut stream: TcpStream) {
+    let config = crate::config::current().api_server.clone();
+    let (status, body) = match tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+        Err(_) => (400, error_body("Timed out reading the request", "invalid_request_error")),
+        Ok(Err((status, message))) => (status, error_body(&message, "invalid_request_error")),
+        Ok(Ok((head, _))) if !authorized(head.authorization.as_deref(), config.token.as_deref().unwrap_or_default()) => {
+            (401, error_body("Missing or wrong bearer token", "invalid_request_error"))
+        }
+        Ok(Ok((head, body))) => match (head.method.as_str(), head.path.as_str()) {
+            ("POST", "/v1/chat/completions") => serve_completion(&app_handle, &config, &body).await,
+            ("GET", "/v1/models") => (200, model_list()),
+            (_, "/v1/chat/completions") | (_, "/v1/models") => (405, error_body("Method not allowed", "invalid_request_error")),
+            (_, path) => (404, error_body(&format!("No route for {}", path), "invalid_request_error")),
+        },
+    };
+    let _ = stream.write_all(&http_response(status, &body)).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn accept_loop(app_handle: AppHandle, listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tauri::async_runtime::spawn(handle_connection(app_handle.clone(), stream));
+            }
+            Err(e) => tracing::warn!("⚠️ API server: {}", e),
+        }
+    }
+}
+
+/// Serve the API while `[api_server]` has it enabled, rebinding when the port changes
+pub fn start_background(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut running: Option<(u16, tokio::task::JoinHandle<()>)> = None;
+        let mut last_error: Option<String> = None;
+        loop {
+            let config = crate::config::current().api_server.clone();
+            let wanted = config.enabled.then_some(config.port);
+            if running.as_ref().map(|(port, _)| *port) != wanted {
+                if let Some((port, task)) = running.take() {
+                    task.abort();
+                    tracing::info!("🔌 API server on port {} stopped", port);
+                }
+                if let Some(port) = wanted {
+                    match TcpListener::bind(("127.0.0.1", port)).await {
+                        Ok(listener) => {
+                            tracing::info!("🔌 OpenAI-compatible API listening on http://127.0.0.1:{}/v1", port);
+                            running = Some((port, tokio::spawn(accept_loop(app_handle.clone(), listener))));
+                            last_error = None;
+                        }
+                        Err(e) => {
+                            // Only log a failure once, not on every check until it's fixed
+                            let e = format!("Can't listen on port {}: {}", port, e);
+                            if last_error.as_deref() != Some(e.as_str()) {
+                                tracing::warn!("⚠️ API server: {}", e);
+                            }
+                            last_error = Some(e);
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(CONFIG_POLL).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Answer an OpenAI-style chat completion request with the agent
+#[tauri::command]
+pub async fn openai_chat_completion(
+    app_handle: tauri::AppHandle,
+    request: ChatCompletionRequest,
+    api_key: String,
+    tavily_key: Option<String>,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+) -> Result<ChatCompletion, String> {
+    let prepared = prepare(&request)?;
+    let agent = MinimaxAgent::new(api_key, tavily_key, grok_key, gemini_key)
+        .with_app_handle(app_handle)
+        .with_user_id(request.user.clone().unwrap_or_else(|| "guest".to_string()));
+    run(agent, &request.model, prepared).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(value: serde_json::Value) -> Vec<ChatMessage> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_model() {
+        assert_eq!(parse_model("thinkspace").unwrap(), (None, None));
+        assert_eq!(parse_model("thinkspace/Grok").unwrap(), (Some(AIProvider::Grok), None));
+        assert_eq!(parse_model("thinkspace/gemini/gemini-2.5-pro").unwrap(), (Some(AIProvider::Gemini), Some("gemini-2.5-pro".to_string())));
+        assert!(parse_model("gpt-4o").is_err());
+        assert!(parse_model("thinkspace/claude").unwrap_err().contains("Unknown provider"));
+    }
+
+    #[test]
+    fn test_history_keeps_text_and_drops_client_tools() {
+        let (instructions, history) = to_history(&messages(serde_json::json!([
+            { "role": "user", "content": "Look this up" },
+            { "role": "assistant", "content": null, "tool_calls": [{ "id": "1" }] },
+            { "role": "tool", "content": "{}", "tool_call_id": "1" },
+            { "role": "user", "content": [{ "type": "text", "text": "What is" }, { "type": "image_url" }, { "type": "text", "text": "a lifetime?" }] }
+        ])))
+        .unwrap();
+        assert!(instructions.is_empty());
+        assert_eq!(history.iter().map(|m| m.role.as_str()).collect::<Vec<_>>(), vec!["user", "user"]);
+        assert_eq!(history[1].content, "What is\na lifetime?");
+    }
+
+    #[test]
+    fn test_history_folds_system_messages_into_instructions() {
+        let (instructions, history) = to_history(&messages(serde_json::json!([
+            { "role": "developer", "content": "Answer briefly." },
+            { "role": "user", "content": "What is a lifetime?" },
+            { "role": "assistant", "content": "A scope." },
+            { "role": "system", "content": "Use British spelling." },
+            { "role": "user", "content": "And a borrow?" }
+        ])))
+        .unwrap();
+        assert_eq!(instructions, "Answer briefly.\n\nUse British spelling.");
+        assert!(history.iter().all(|m| m.role != "system"));
+        assert_eq!(history.len(), 3);
+    }
+
+    #[test]
+    fn test_history_must_end_with_the_user() {
+        let ends_with_assistant = messages(serde_json::json!([
+            { "role": "user", "content": "Hi" },
+            { "role": "assistant", "content": "Hello!" }
+        ]));
+        assert!(to_history(&ends_with_assistant).unwrap_err().contains("last message"));
+        let only_system = messages(serde_json::json!([{ "role": "system", "content": "Be nice." }]));
+        assert!(to_history(&only_system).unwrap_err().contains("no user message"));
+    }
+
+    #[test]
+    fn test_prepare_refuses_streaming() {
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "thinkspace", "stream": true, "messages": [{ "role": "user", "content": "Hi" }]
+        }))
+        .unwrap();
+        assert!(prepare(&request).unwrap_err().contains("Streaming"));
+    }
+
+    #[test]
+    fn test_completion_body() {
+        let history = vec![Message { role: "user".to_string(), content: "What is a lifetime?".to_string(), tool_calls: None, tool_call_id: None, timestamp: None }];
+        let response = ChatResponse { content: "A scope.".to_string(), thinking: Vec::new(), tool_calls_made: 0, iterations: 1, reflection: None };
+        let body = serde_json::to_value(completion("thinkspace", &history, &response)).unwrap();
+        assert_eq!(body["object"], "chat.completion");
+        assert_eq!(body["choices"][0]["message"], serde_json::json!({ "role": "assistant", "content": "A scope." }));
+        assert_eq!(body["usage"]["completion_tokens"], 2);
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let head = parse_head("POST /v1/chat/completions?x=1 HTTP/1.1\r\nHost: 127.0.0.1\r\ncontent-length: 42\r\nAuthorization: Bearer s3cret").unwrap();
+        assert_eq!(
+            head,
+            RequestHead {
+                method: "POST".to_string(),
+                path: "/v1/chat/completions".to_string(),
+                content_length: 42,
+                authorization: Some("Bearer s3cret".to_string()),
+            }
+        );
+        assert_eq!(parse_head("GET /v1/models HTTP/1.0").unwrap().content_length, 0);
+        assert!(parse_head("GET /v1/models").is_err());
+        assert!(parse_head("POST / HTTP/1.1\r\nContent-Length: lots").is_err());
+    }
+
+    #[test]
+    fn test_authorized() {
+        assert!(authorized(Some("Bearer s3cret"), "s3cret"));
+        assert!(!authorized(Some("Bearer s3cre"), "s3cret"));
+        assert!(!authorized(Some("s3cret"), "s3cret"));
+        assert!(!authorized(None, "s3cret"));
+    }
+
+    #[test]
+    fn test_http_response() {
+        let response = String::from_utf8(http_response(401, &error_body("Missing or wrong bearer token", "invalid_request_error"))).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(body).unwrap()["error"]["type"], "invalid_request_error");
+        assert_eq!(model_list()["data"][2]["id"], "thinkspace/grok");
+    }
+}
@@ -0,0 +1,480 @@
+/// Academic paper ingestion (arXiv and PDF)
+///
+/// `ingest_paper` accepts an arXiv id or URL, a PDF URL or a local PDF path. arXiv papers get
+/// their metadata from the arXiv API; the PDF text is extracted with pdf-extract and split
+/// into sections by heading heuristics (numbered headings and the usual paper section names).
+/// Papers feed deep research as sources and can be stored in the TKG section by section.
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::deep_research::SearchResult;
+use crate::net::SendChecked;
+
+const MAX_PDF_BYTES: usize = 50 * 1024 * 1024;
+/// arXiv asks API clients to wait three seconds between requests
+const ARXIV_DELAY: Duration = Duration::from_secs(3);
+/// Text of a paper handed to research as one source (~6k tokens, same as a web page)
+const MAX_SOURCE_CHARS: usize = 24_000;
+/// Section text stored per TKG node
+const MAX_TKG_CHARS: usize = 2_000;
+
+lazy_static::lazy_static! {
+    static ref ARXIV_URL: Regex =
+        Regex::new(r"(?i)arxiv\.org/(?:abs|pdf)/([a-z\-]+(?:\.[a-z]{2})?/\d{7}|\d{4}\.\d{4,5})(v\d+)?").unwrap();
+    static ref ARXIV_ID: Regex =
+        Regex::new(r"(?i)^(?:arxiv:\s*)?([a-z\-]+(?:\.[a-z]{2})?/\d{7}|\d{4}\.\d{4,5})(v\d+)?$").unwrap();
+    static ref ATOM_ENTRY: Regex = Regex::new(r"(?s)<entry>(.*?)</entry>").unwrap();
+    static ref ATOM_TITLE: Regex = Regex::new(r"(?s)<title>(.*?)</title>").unwrap();
+    static ref ATOM_SUMMARY: Regex = Regex::new(r"(?s)<summary>(.*?)</summary>").unwrap();
+    static ref ATOM_PUBLISHED: Regex = Regex::new(r"<published>(.*?)</published>").unwrap();
+    static ref ATOM_AUTHOR: Regex = Regex::new(r"(?s)<author>\s*<name>(.*?)</name>").unwrap();
+    static ref NUMBERED_HEADING: Regex =
+        Regex::new(r"^(\d{1,2}(?:\.\d{1,2}){0,2})\.?\s+([A-Z][A-Za-z0-9 ,:;&()'/\-]{2,80})$").unwrap();
+    static ref ROMAN_HEADING: Regex = Regex::new(r"^([IVX]{1,4})\.\s+([A-Z][A-Za-z0-9 ,:;&()'/\-]{2,80})$").unwrap();
+    static ref NAMED_HEADING: Regex = Regex::new(
+        r"(?i)^(abstract|introduction|related work|background|preliminaries|methods?|methodology|approach|experiments?|evaluation|results|discussion|limitations|conclusions?|future work|references|bibliography|acknowledge?ments?|appendix(?: [a-z])?)$"
+    ).unwrap();
+    static ref HYPHEN_BREAK: Regex = Regex::new(r"([a-z])-\n([a-z])").unwrap();
+    static ref WHITESPACE: Regex = Regex::new(r"[ \t]+").unwrap();
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some("ThinkSpace-Research/1.0 (+https://github.com/oogalieboogalie/ThinkSpace)"),
+        timeout: Some(Duration::from_secs(60)),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperSection {
+    pub heading: String,
+    /// 1 for top-level sections, 2 for "3.1"-style subsections, ...
+    pub level: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paper {
+    /// What was passed to ingest_paper
+    pub source: String,
+    pub arxiv_id: Option<String>,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub abstract_text: Option<String>,
+    pub published: Option<String>,
+    /// Canonical link used for citations (arXiv abstract page, the PDF URL or the file path)
+    pub url: String,
+    pub sections: Vec<PaperSection>,
+    pub char_count: usize,
+}
+
+/// Where a paper comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaperSource {
+    Arxiv(String),
+    Url(String),
+    File(PathBuf),
+}
+
+pub fn is_references(heading: &str) -> bool {
+    matches!(heading.to_lowercase().as_str(), "references" | "bibliography")
+}
+
+/// Classify `input`: arXiv ids/URLs, other http(s) URLs, else a local path
+pub fn parse_source(input: &str) -> PaperSource {
+    let input = input.trim();
+    if let Some(caps) = ARXIV_URL.captures(input).or_else(|| ARXIV_ID.captures(input)) {
+        let version = caps.get(2).map_or("", |m| m.as_str());
+        return PaperSource::Arxiv(format!("{}{}", &caps[1], version));
+    }
+    if input.starts_with("http://") || input.starts_with("https://") {
+        return PaperSource::Url(input.to_string());
+    }
+    PaperSource::File(PathBuf::from(input))
+}
+
+/// Research sources that look like papers and should go through ingest rather than HTML extraction
+pub fn is_paper_url(url: &str) -> bool {
+    ARXIV_URL.is_match(url)
+        || url::Url::parse(url).map_or(false, |u| u.path().to_lowercase().ends_with(".pdf"))
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn one_line(text: &str) -> String {
+    decode_entities(&text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+struct ArxivMeta {
+    title: String,
+    authors: Vec<String>,
+    summary: String,
+    published: Option<String>,
+}
+
+fn parse_arxiv_atom(xml: &str) -> Option<ArxivMeta> {
+    let entry = ATOM_ENTRY.captures(xml)?.get(1)?.as_str();
+    let title = one_line(ATOM_TITLE.captures(entry)?.get(1)?.as_str());
+    // The API answers unknown ids with an "Error" entry
+    if title.is_empty() || title == "Error" {
+        return None;
+    }
+    Some(ArxivMeta {
+        title,
+        authors: ATOM_AUTHOR.captures_iter(entry).map(|c| one_line(&c[1])).collect(),
+        summary: ATOM_SUMMARY.captures(entry).map(|c| one_line(&c[1])).unwrap_or_default(),
+        published: ATOM_PUBLISHED.captures(entry).map(|c| c[1].trim().to_string()),
+    })
+}
+
+async fn arxiv_get(url: &str) -> Result<reqwest::Response> {
+    crate::web_extract::wait_for_slot("export.arxiv.org", ARXIV_DELAY).await;
+    let resp = client().get(url).send_checked().await.context("Failed to reach arXiv")?;
+    if !resp.status().is_success() {
+        bail!("arXiv returned {} for {}", resp.status(), url);
+    }
+    Ok(resp)
+}
+
+async fn read_pdf_body(resp: reqwest::Response, url: &str) -> Result<Vec<u8>> {
+    if resp.content_length().map_or(false, |len| len as usize > MAX_PDF_BYTES) {
+        bail!("PDF too large: {}", url);
+    }
+    let bytes = resp.bytes().await.context("Failed to download PDF")?;
+    if bytes.len() > MAX_PDF_BYTES {
+        bail!("PDF too large: {}", url);
+    }
+    if !bytes.starts_with(b"%PDF") {
+        bail!("Not a PDF: {}", url);
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Extract text off the async runtime; pdf-extract can panic on malformed files
+async fn extract_pdf_text(bytes: Vec<u8>) -> Result<String> {
+    tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes))
+        .await
+        .map_err(|_| anyhow!("PDF text extraction crashed on this file"))?
+        .map_err(|e| anyhow!("Failed to extract PDF text: {}", e))
+}
+
+fn heading_of(line: &str) -> Option<(String, usize)> {
+    let words = line.split_whitespace().count();
+    if line.len() > 90 || words > 12 || line.ends_with('.') || line.ends_with(',') {
+        return None;
+    }
+    if let Some(caps) = NUMBERED_HEADING.captures(line) {
+        let level = caps[1].matches('.').count() + 1;
+        return Some((format!("{} {}", &caps[1], caps[2].trim()), level));
+    }
+    if let Some(caps) = ROMAN_HEADING.captures(line) {
+        return Some((format!("{}. {}", &caps[1], caps[2].trim()), 1));
+    }
+    if NAMED_HEADING.is_match(line) {
+        return Some((line.to_string(), 1));
+    }
+    None
+}
+
+/// Join wrapped lines into paragraphs and undo end-of-line hyphenation
+fn clean_text(raw: &str) -> String {
+    let text = HYPHEN_BREAK.replace_all(raw, "$1$2");
+    text.split("\n\n")
+        .map(|para| WHITESPACE.replace_all(&para.lines().map(str::trim).collect::<Vec<_>>().join(" "), " ").trim().to_string())
+        .filter(|para| !para.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Split extracted PDF text into sections. Text before the first heading becomes a
+/// "Front matter" section (title, authors, often the abstract).
+pub fn split_paper_sections(text: &str) -> Vec<PaperSection> {
+    let text = text.replace("\r\n", "\n").replace('\u{c}', "\n");
+    let mut sections = Vec::new();
+    let mut heading = ("Front matter".to_string(), 1);
+    let mut body = String::new();
+
+    let flush = |heading: &(String, usize), body: &str, sections: &mut Vec<PaperSection>| {
+        let text = clean_text(body);
+        if !text.is_empty() || heading.0 != "Front matter" {
+            sections.push(PaperSection { heading: heading.0.clone(), level: heading.1, text });
+        }
+    };
+
+    for line in text.lines() {
+        match heading_of(line.trim()) {
+            Some(next) => {
+                flush(&heading, &body, &mut sections);
+                heading = next;
+                body.clear();
+            }
+            None => {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+    }
+    flush(&heading, &body, &mut sections);
+
+    // Drop empty repeats of the previous heading (e.g. a running page header)
+    sections.dedup_by(|current, previous| current.heading == previous.heading && current.text.is_empty());
+    sections
+}
+
+fn first_line_title(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|l| l.len() >= 8 && l.len() <= 200 && l.chars().any(|c| c.is_alphabetic()))
+        .map(str::to_string)
+}
+
+impl Paper {
+    fn from_text(source: &str, url: String, text: &str, fallback_title: String) -> Self {
+        let sections = split_paper_sections(text);
+        let abstract_text = sections
+            .iter()
+            .find(|s| s.heading.eq_ignore_ascii_case("abstract"))
+            .map(|s| s.text.clone())
+            .filter(|t| !t.is_empty());
+        Paper {
+            source: source.to_string(),
+            arxiv_id: None,
+            title: first_line_title(text).unwrap_or(fallback_title),
+            authors: Vec::new(),
+            abstract_text,
+            published: None,
+            url,
+            char_count: sections.iter().map(|s| s.text.len()).sum(),
+            sections,
+        }
+    }
+
+    /// Paper as markdown: metadata, abstract, then sections (references left out)
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title);
+        if !self.authors.is_empty() {
+            out.push_str(&format!("**Authors:** {}\n\n", self.authors.join(", ")));
+        }
+        if let Some(published) = &self.published {
+            out.push_str(&format!("**Published:** {}\n\n", published));
+        }
+        out.push_str(&format!("**Source:** {}\n\n", self.url));
+        if let Some(abstract_text) = &self.abstract_text {
+            out.push_str(&format!("## Abstract\n\n{}\n\n", abstract_text));
+        }
+        for section in &self.sections {
+            if is_references(&section.heading) || section.heading.eq_ignore_ascii_case("abstract") {
+                continue;
+            }
+            if section.heading == "Front matter" && self.abstract_text.is_some() {
+                continue;
+            }
+            out.push_str(&format!("{} {}\n\n{}\n\n", "#".repeat(section.level + 1), section.heading, section.text));
+        }
+        out.trim_end().to_string()
+    }
+
+    /// The paper as one research source, truncated like an extracted web page
+    pub fn to_source(&self) -> SearchResult {
+        let markdown = self.to_markdown();
+        let content = match markdown.char_indices().nth(MAX_SOURCE_CHARS) {
+            Some((cut, _)) => format!("{}\n\n[Paper truncated]", &markdown[..cut]),
+            None => markdown,
+        };
+        SearchResult {
+            title: self.title.clone(),
+            url: self.url.clone(),
+            content,
+            score: 1.0,
+            published_date: self.published.clone(),
+        }
+    }
+
+    /// Chunks stored in the TKG: the abstract, then each section with the paper as context
+    pub fn knowledge_chunks(&self) -> Vec<(String, &'static str, f32)> {
+        let cite = match &self.arxiv_id {
+            Some(id) => format!("{}, arXiv:{}", self.title, id),
+            None => format!("{}, {}", self.title, self.url),
+        };
+        let mut chunks = Vec::new();
+        if let Some(abstract_text) = &self.abstract_text {
+            chunks.push((format!("Abstract of \"{}\": {}\n\n(Source: {})", self.title, abstract_text, cite), "CONCEPT", 0.7));
+        }
+        for section in &self.sections {
+            if section.text.len() < 200 || is_references(&section.heading) || section.heading.eq_ignore_ascii_case("abstract") {
+                continue;
+            }
+            let text: String = section.text.chars().take(MAX_TKG_CHARS).collect();
+            chunks.push((format!("{} - {}\n\n{}\n\n(Source: {})", self.title, section.heading, text, cite), "FACT", 0.6));
+        }
+        chunks
+    }
+}
+
+async fn ingest_arxiv(input: &str, id: &str) -> Result<Paper> {
+    let meta_xml = arxiv_get(&format!("https://export.arxiv.org/api/query?id_list={}", id))
+        .await?
+        .text()
+        .await
+        .context("Failed to read arXiv metadata")?;
+    let meta = parse_arxiv_atom(&meta_xml).ok_or_else(|| anyhow!("arXiv has no paper with id {}", id))?;
+
+    let pdf_url = format!("https://export.arxiv.org/pdf/{}", id);
+    let bytes = read_pdf_body(arxiv_get(&pdf_url).await?, &pdf_url).await?;
+    let text = extract_pdf_text(bytes).await?;
+
+    let mut paper = Paper::from_text(input, format!("https://arxiv.org/abs/{}", id), &text, meta.title.clone());
+    paper.arxiv_id = Some(id.to_string());
+    paper.title = meta.title;
+    paper.authors = meta.authors;
+    paper.published = meta.published;
+    if !meta.summary.is_empty() {
+        paper.abstract_text = Some(meta.summary);
+    }
+    Ok(paper)
+}
+
+/// Download or read a paper and extract its text and sections. Local paths are used as
+/// given; callers restrict them where needed.
+pub async fn ingest(input: &str) -> Result<Paper> {
+    match parse_source(input) {
+        PaperSource::Arxiv(id) => ingest_arxiv(input, &id).await,
+        PaperSource::Url(url) => {
+            let bytes = read_pdf_body(crate::web_extract::fetch_politely(&url).await?, &url).await?;
+            let text = extract_pdf_text(bytes).await?;
+            let fallback = url.rsplit('/').next().unwrap_or(&url).trim_end_matches(".pdf").to_string();
+            Ok(Paper::from_text(input, url.clone(), &text, fallback))
+        }
+        PaperSource::File(path) => {
+            let bytes = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            if !bytes.starts_with(b"%PDF") {
+                bail!("Not a PDF: {}", path.display());
+            }
+            let text = extract_pdf_text(bytes).await?;
+            let fallback = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            Ok(Paper::from_text(input, path.to_string_lossy().to_string(), &text, fallback))
+        }
+    }
+}
+
+/// Store a paper's chunks in the TKG; stops at the first failure (usually TKG not configured)
+pub async fn store_in_tkg(paper: &Paper, user_id: &str) -> Result<usize, String> {
+    let mut stored = 0;
+    for (content, node_type, importance) in paper.knowledge_chunks() {
+        match crate::tkg::tkg_store_knowledge(content, node_type.to_string(), importance, user_id.to_string()).await {
+            Ok(_) => stored += 1,
+            Err(e) if stored == 0 => return Err(e),
+            Err(e) => {
+                tracing::warn!("⚠️ Stopped storing paper sections in TKG: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(stored)
+}
+
+/// Resolve a local paper path against the knowledge base; absolute paths are kept
+pub fn resolve_local(path: &str, kb_root: &Path) -> PathBuf {
+    let p = Path::new(path);
+    if p.is_absolute() { p.to_path_buf() } else { kb_root.join(p) }
+}
+
+/// Ingest an arXiv paper, PDF URL or local PDF and optionally store it in the TKG
+#[tauri::command]
+pub async fn ingest_paper(url_or_path: String, user_id: Option<String>, store_in_knowledge_graph: Option<bool>) -> Result<serde_json::Value, String> {
+    let input = match parse_source(&url_or_path) {
+        PaperSource::File(_) => {
+            let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+            resolve_local(&url_or_path, &kb_root).to_string_lossy().to_string()
+        }
+        _ => url_or_path.clone(),
+    };
+    let paper = ingest(&input).await.map_err(|e| format!("{:#}", e))?;
+    tracing::info!("📄 Ingested paper: {} ({} sections)", paper.title, paper.sections.len());
+
+    let tkg_nodes = if store_in_knowledge_graph.unwrap_or(true) {
+        match store_in_tkg(&paper, user_id.as_deref().unwrap_or("guest")).await {
+            Ok(n) => Some(n),
+            Err(e) => {
+                tracing::warn!("⚠️ Paper not stored in TKG: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(serde_json::json!({
+        "paper": paper,
+        "markdown": paper.to_markdown(),
+        "tkg_nodes_stored": tkg_nodes
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Attention Is All You Need\nAshish Vaswani\n\nAbstract\nThe dominant sequence trans-\nduction models are\nbased on recurrent networks.\n\n\
+                        1 Introduction\nRecurrent models factor\ncomputation.\n\n3.1 Encoder and Decoder Stacks\nThe encoder is a stack.\n\n\
+                        References\n[1] Someone. A paper. 2015.";
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(parse_source("https://arxiv.org/abs/1706.03762v7"), PaperSource::Arxiv("1706.03762v7".to_string()));
+        assert_eq!(parse_source("https://arxiv.org/pdf/2301.00001.pdf"), PaperSource::Arxiv("2301.00001".to_string()));
+        assert_eq!(parse_source("arXiv:hep-th/9901001"), PaperSource::Arxiv("hep-th/9901001".to_string()));
+        assert_eq!(parse_source("https://example.com/paper.pdf"), PaperSource::Url("https://example.com/paper.pdf".to_string()));
+        assert_eq!(parse_source("papers/attention.pdf"), PaperSource::File(PathBuf::from("papers/attention.pdf")));
+    }
+
+    #[test]
+    fn test_is_paper_url() {
+        assert!(is_paper_url("https://example.com/files/Paper.PDF"));
+        assert!(!is_paper_url("https://example.com/blog"));
+    }
+
+    #[test]
+    fn test_split_paper_sections() {
+        let sections = split_paper_sections(TEXT);
+        let headings: Vec<(&str, usize)> = sections.iter().map(|s| (s.heading.as_str(), s.level)).collect();
+        assert_eq!(
+            headings,
+            vec![("Front matter", 1), ("Abstract", 1), ("1 Introduction", 1), ("3.1 Encoder and Decoder Stacks", 2), ("References", 1)]
+        );
+        assert_eq!(sections[1].text, "The dominant sequence transduction models are based on recurrent networks.");
+    }
+
+    #[test]
+    fn test_paper_markdown_drops_references() {
+        let paper = Paper::from_text("x.pdf", "x.pdf".to_string(), TEXT, "x".to_string());
+        assert_eq!(paper.title, "Attention Is All You Need");
+        let markdown = paper.to_markdown();
+        assert!(markdown.contains("## Abstract\n\nThe dominant") && markdown.contains("### 3.1 Encoder and Decoder Stacks"));
+        assert!(!markdown.contains("Someone"));
+    }
+
+    #[test]
+    fn test_parse_arxiv_atom() {
+        let xml = r#"<feed><title>ArXiv Query</title><entry><id>http://arxiv.org/abs/1706.03762v7</id>
+            <published>2017-06-12T17:57:34Z</published><title>Attention Is All
+              You Need</title><summary>  The dominant &amp; best models.
+            </summary><author><name>Ashish Vaswani</name></author><author>
+            <name>Noam Shazeer</name></author></entry></feed>"#;
+        let meta = parse_arxiv_atom(xml).unwrap();
+        assert_eq!(meta.title, "Attention Is All You Need");
+        assert_eq!(meta.summary, "The dominant & best models.");
+        assert_eq!(meta.authors, vec!["Ashish Vaswani".to_string(), "Noam Shazeer".to_string()]);
+        assert_eq!(meta.published.as_deref(), Some("2017-06-12T17:57:34Z"));
+    }
+}
@@ -0,0 +1,184 @@
+/// Page-by-page text of local PDFs, for quoting and summarizing them
+///
+/// The agent's `read_pdf` tool reads PDFs in the knowledge base (dropped ones are under
+/// `dumps/dropped/assets/`); the `read_pdf` command also takes any absolute path the user
+/// picks. A page range like "3", "2-5", "1,4,7-9" or "10-" picks pages, counted from 1.
+/// Long reads stop at a page boundary once `MAX_CHARS` is reached and say which page to
+/// continue from.
+
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+/// Text returned per call, so one read doesn't swamp the conversation
+const MAX_CHARS: usize = 60_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfPage {
+    pub number: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfText {
+    pub path: String,
+    pub page_count: usize,
+    pub pages: Vec<PdfPage>,
+    /// The first requested page left out because of the size limit
+    pub next_page: Option<usize>,
+}
+
+/// The pages `spec` names, in order and without repeats; an empty spec is every page
+fn parse_page_range(spec: &str, page_count: usize) -> Result<Vec<usize>, String> {
+    if spec.trim().is_empty() {
+        return Ok((1..=page_count).collect());
+    }
+    let invalid = || format!("Invalid page range '{}', use e.g. \"3\", \"2-5\", \"1,4,7-9\" or \"10-\"", spec);
+    let page = |s: &str| s.trim().parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(invalid);
+    let mut pages = Vec::new();
+    for part in spec.split(',') {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) if last.trim().is_empty() => (page(first)?, page_count),
+            Some((first, last)) => (page(first)?, page(last)?),
+            None => (page(part)?, page(part)?),
+        };
+        if first > last {
+            return Err(invalid());
+        }
+        if first > page_count {
+            return Err(format!("Page {} is past the end; the PDF has {} page(s)", first, page_count));
+        }
+        for number in first..=last.min(page_count) {
+            if !pages.contains(&number) {
+                pages.push(number);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+/// Drop the runs of blank lines and trailing spaces pdf-extract leaves behind
+fn tidy(text: &str) -> String {
+    let mut out = String::new();
+    let mut blank = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        out.push_str(line);
+        blank = 0;
+    }
+    out
+}
+
+/// Keep `wanted` pages of `all` until `MAX_CHARS`; the first page is cut short if it alone is over
+fn select_pages(all: &[String], wanted: &[usize]) -> (Vec<PdfPage>, Option<usize>) {
+    let mut pages = Vec::new();
+    let mut total = 0;
+    for &number in wanted {
+        let text = tidy(&all[number - 1]);
+        let chars = text.chars().count();
+        if total + chars > MAX_CHARS {
+            if pages.is_empty() {
+                pages.push(PdfPage { number, text: text.chars().take(MAX_CHARS).collect() });
+                return (pages, wanted.get(1).copied());
+            }
+            return (pages, Some(number));
+        }
+        total += chars;
+        pages.push(PdfPage { number, text });
+    }
+    (pages, None)
+}
+
+/// `path` inside the knowledge base, if it's a PDF that doesn't climb out of it
+pub fn kb_pdf_path(kb_root: &Path, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err("Path must be relative to the knowledge base".to_string());
+    }
+    if relative.extension().map_or(true, |e| !e.eq_ignore_ascii_case("pdf")) {
+        return Err("Only PDF files can be read".to_string());
+    }
+    Ok(kb_root.join(relative))
+}
+
+/// Read the text of `pages` (see the module docs) of the PDF at `path`
+pub fn read(path: &Path, pages: Option<&str>) -> Result<PdfText, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // pdf-extract can panic on malformed files
+    let all = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(&bytes))
+        .map_err(|_| "PDF text extraction crashed on this file".to_string())?
+        .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+    let wanted = parse_page_range(pages.unwrap_or_default(), all.len())?;
+    let (pages, next_page) = select_pages(&all, &wanted);
+    Ok(PdfText { path: path.display().to_string(), page_count: all.len(), pages, next_page })
+}
+
+// ==================== Commands ====================
+
+/// Text of a PDF's pages; `path` is absolute or relative to the knowledge base
+#[tauri::command]
+pub async fn read_pdf(path: String, page_range: Option<String>) -> Result<PdfText, String> {
+    let full_path = if Path::new(&path).is_absolute() {
+        PathBuf::from(&path)
+    } else {
+        kb_pdf_path(&crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?, &path)?
+    };
+    tauri::async_runtime::spawn_blocking(move || read(&full_path, page_range.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page_range() {
+        assert_eq!(parse_page_range("", 3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(parse_page_range("2", 3).unwrap(), vec![2]);
+        assert_eq!(parse_page_range(" 1, 3-4 ,2-", 5).unwrap(), vec![1, 3, 4, 2, 5]);
+        assert_eq!(parse_page_range("2-9", 3).unwrap(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_parse_page_range_rejects_bad_ranges() {
+        assert!(parse_page_range("4", 3).unwrap_err().contains("3 page(s)"));
+        assert!(parse_page_range("0", 3).is_err());
+        assert!(parse_page_range("3-1", 3).is_err());
+        assert!(parse_page_range("one", 3).is_err());
+    }
+
+    #[test]
+    fn test_tidy() {
+        assert_eq!(tidy("Title  \n\n\n\nFirst line\nsecond line\n\n"), "Title\n\nFirst line\nsecond line");
+    }
+
+    #[test]
+    fn test_select_pages_stops_at_the_budget() {
+        let all = vec!["a".repeat(MAX_CHARS - 10), "b".repeat(20), "c".to_string()];
+        let (pages, next) = select_pages(&all, &[1, 2, 3]);
+        assert_eq!((pages.len(), next), (1, Some(2)));
+        let (pages, next) = select_pages(&all, &[3, 2]);
+        assert_eq!((pages.iter().map(|p| p.number).collect::<Vec<_>>(), next), (vec![3, 2], None));
+    }
+
+    #[test]
+    fn test_select_pages_cuts_an_oversized_page() {
+        let (pages, next) = select_pages(&["x".repeat(MAX_CHARS + 5)], &[1]);
+        assert_eq!((pages[0].text.len(), next), (MAX_CHARS, None));
+    }
+
+    #[test]
+    fn test_kb_pdf_path() {
+        let kb = Path::new("/kb");
+        assert_eq!(kb_pdf_path(kb, "dumps/dropped/assets/deck.PDF").unwrap(), kb.join("dumps/dropped/assets/deck.PDF"));
+        assert!(kb_pdf_path(kb, "../secret.pdf").is_err());
+        assert!(kb_pdf_path(kb, "/etc/passwd.pdf").is_err());
+        assert!(kb_pdf_path(kb, "research/notes.md").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+/// Timings of agent turns and the work inside them
+///
+/// A `tracing` layer times every span named in `TIMED_SPANS`, from when it's created to when
+/// it closes: `provider` (a model request, streaming included), `tool`, `search` and `db`
+/// (work sent to the blocking pool). The first field of a span labels it, e.g. the tool name
+/// or the model. Timings add up per category and label in `PerfStats`, which `AppState`
+/// shares. Spans inside a `turn` span (one chat request) are also listed under that turn, so
+/// `get_performance_report` can show where a slow turn spent its time. Searches and database
+/// work run by a tool count in the tool's time as well as their own. Spans are info level,
+/// so nothing is recorded when the log level is set above `info`.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::State;
+use tracing::field::{Field, Visit};
+use tracing::span;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::commands::AppState;
+
+pub const TIMED_SPANS: [&str; 4] = ["provider", "tool", "search", "db"];
+pub const TURN_SPAN: &str = "turn";
+const RECENT_TURNS: usize = 20;
+/// Spans listed per turn, slowest first
+const TURN_SLOWEST: usize = 10;
+
+lazy_static::lazy_static! {
+    static ref STATS: Arc<PerfStats> = Arc::new(PerfStats::default());
+}
+
+/// The timings the layer records into
+pub fn stats() -> Arc<PerfStats> {
+    STATS.clone()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Timing {
+    pub category: String,
+    pub label: String,
+    pub count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpanTime {
+    pub category: String,
+    pub label: String,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TurnReport {
+    pub label: String,
+    pub finished_at: String,
+    pub total_ms: f64,
+    /// Time per category within the turn
+    pub by_category: BTreeMap<String, f64>,
+    pub slowest: Vec<SpanTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PerfReport {
+    /// Slowest in total first
+    pub timings: Vec<Timing>,
+    /// Newest first
+    pub recent_turns: Vec<TurnReport>,
+}
+
+#[derive(Debug, Default)]
+pub struct PerfStats {
+    timings: Mutex<HashMap<(String, String), Timing>>,
+    turns: Mutex<VecDeque<TurnReport>>,
+}
+
+impl PerfStats {
+    fn record(&self, category: &str, label: &str, ms: f64) {
+        let mut timings = self.timings.lock().unwrap();
+        let timing = timings.entry((category.to_string(), label.to_string())).or_insert_with(|| Timing {
+            category: category.to_string(),
+            label: label.to_string(),
+            ..Timing::default()
+        });
+        timing.count += 1;
+        timing.total_ms += ms;
+        timing.max_ms = timing.max_ms.max(ms);
+        timing.mean_ms = timing.total_ms / timing.count as f64;
+    }
+
+    fn finish_turn(&self, label: String, total_ms: f64, mut spans: Vec<SpanTime>) {
+        let mut by_category = BTreeMap::new();
+        for span in &spans {
+            *by_category.entry(span.category.clone()).or_insert(0.0) += span.ms;
+        }
+        spans.sort_by(|a, b| b.ms.total_cmp(&a.ms));
+        spans.truncate(TURN_SLOWEST);
+
+        let mut turns = self.turns.lock().unwrap();
+        if turns.len() == RECENT_TURNS {
+            turns.pop_back();
+        }
+        turns.push_front(TurnReport {
+            label,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            total_ms,
+            by_category,
+            slowest: spans,
+        });
+    }
+
+    /// Everything recorded so far, optionally starting over afterwards
+    pub fn report(&self, reset: bool) -> PerfReport {
+        let mut timings = self.timings.lock().unwrap();
+        let mut turns = self.turns.lock().unwrap();
+        let mut report = PerfReport {
+            timings: timings.values().cloned().collect(),
+            recent_turns: turns.iter().cloned().collect(),
+        };
+        report.timings.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        if reset {
+            timings.clear();
+            turns.clear();
+        }
+        report
+    }
+}
+
+/// Kept on a timed span while it's open
+struct Timed {
+    category: &'static str,
+    label: String,
+    started: Instant,
+}
+
+/// The spans that closed inside a turn
+struct TurnSpans(Vec<SpanTime>);
+
+/// The first field recorded on a span
+#[derive(Default)]
+struct FirstField(Option<String>);
+
+impl Visit for FirstField {
+    fn record_str(&mut self, _field: &Field, value: &str) {
+        self.0.get_or_insert_with(|| value.to_string());
+    }
+
+    fn record_debug(&mut self, _field: &Field, value: &dyn fmt::Debug) {
+        self.0.get_or_insert_with(|| format!("{:?}", value));
+    }
+}
+
+pub struct PerfLayer;
+
+impl<S> Layer<S> for PerfLayer
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let name = attrs.metadata().name();
+        let category = match TIMED_SPANS.iter().chain(std::iter::once(&TURN_SPAN)).find(|n| **n == name) {
+            Some(category) => *category,
+            None => return,
+        };
+        let mut label = FirstField::default();
+        attrs.record(&mut label);
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            extensions.insert(Timed { category, label: label.0.unwrap_or_default(), started: Instant::now() });
+            if category == TURN_SPAN {
+                extensions.insert(TurnSpans(Vec::new()));
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let timed = match span.extensions_mut().remove::<Timed>() {
+            Some(timed) => timed,
+            None => return,
+        };
+        let ms = timed.started.elapsed().as_secs_f64() * 1000.0;
+
+        if timed.category == TURN_SPAN {
+            let spans = span.extensions_mut().remove::<TurnSpans>().map(|t| t.0).unwrap_or_default();
+            STATS.finish_turn(timed.label, ms, spans);
+            return;
+        }
+        STATS.record(timed.category, &timed.label, ms);
+        if let Some(turn) = span.scope().skip(1).find(|s| s.name() == TURN_SPAN) {
+            if let Some(spans) = turn.extensions_mut().get_mut::<TurnSpans>() {
+                spans.0.push(SpanTime { category: timed.category.to_string(), label: timed.label, ms });
+            }
+        }
+    }
+}
+
+/// Where agent turns have spent their time since launch (or the last reset)
+#[tauri::command]
+pub fn get_performance_report(state: State<'_, AppState>, reset: Option<bool>) -> PerfReport {
+    state.perf.report(reset.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    /// The stats are global, so tests that record into them take turns
+    static GLOBAL_STATS: Mutex<()> = Mutex::new(());
+
+    /// Runs one turn with two timed tool calls while holding the stats
+    fn record_sample_turn() -> std::sync::MutexGuard<'static, ()> {
+        let held = GLOBAL_STATS.lock().unwrap_or_else(|e| e.into_inner());
+        stats().report(true);
+        let subscriber = tracing_subscriber::registry().with(PerfLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            let turn = tracing::info_span!("turn", session = "rust-study");
+            let _turn = turn.enter();
+            for _ in 0..2 {
+                let _tool = tracing::info_span!("tool", tool = "search_knowledge").entered();
+                let _search = tracing::info_span!("search", kind = "kb_index").entered();
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            let _untimed = tracing::info_span!("render").entered();
+        });
+        held
+    }
+
+    #[test]
+    fn test_perf_layer_times_spans() {
+        let _turn = record_sample_turn();
+        let report = stats().report(true);
+        let tool = report.timings.iter().find(|t| t.category == "tool").unwrap();
+        assert_eq!((tool.label.as_str(), tool.count), ("search_knowledge", 2));
+        assert!(tool.total_ms >= 10.0 && tool.max_ms >= 5.0);
+        assert_eq!(report.timings.len(), 2);
+    }
+
+    #[test]
+    fn test_perf_layer_reports_turns() {
+        let _turn = record_sample_turn();
+        let report = stats().report(true);
+        let turn = &report.recent_turns[0];
+        assert_eq!(turn.label, "rust-study");
+        assert_eq!(turn.slowest.len(), 4);
+        assert!(turn.total_ms >= turn.by_category["tool"]);
+    }
+
+    #[test]
+    fn test_report_can_start_over() {
+        let _turn = record_sample_turn();
+        stats().report(true);
+        assert!(stats().report(false).timings.is_empty());
+    }
+}
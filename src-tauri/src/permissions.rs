@@ -0,0 +1,254 @@
+/// Tool permission scopes, checked in one place before any tool runs
+///
+/// What an agent may do is a stack of grants: the build's mode (student builds can't run
+/// commands and only write under `research/` and `generated-guides/`), `[tools] disabled`
+/// in thinkspace.toml, and the grants of the conversation itself. Conversation grants are
+/// set with `set_tool_grants` for a session name and kept in `AppState` until revoked or the
+/// app quits. A call has to pass every layer, and a refusal names the layer that refused it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::AppState;
+
+/// How much of the network a conversation's tools may use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkAccess {
+    #[default]
+    Full,
+    /// Fetch and search, but store nothing remotely (no TKG writes)
+    ReadOnly,
+    None,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolGrants {
+    /// Per-tool switches; tools not listed are allowed
+    pub tools: HashMap<String, bool>,
+    /// Knowledge base folders the tools may write under (`None`: anywhere)
+    pub write_paths: Option<Vec<String>>,
+    pub network: NetworkAccess,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NetworkUse {
+    Read,
+    Write,
+}
+
+impl ToolGrants {
+    /// Grants that switch off each of `tools`
+    pub fn disabling<'a>(tools: impl IntoIterator<Item = &'a String>) -> Self {
+        Self {
+            tools: tools.into_iter().map(|t| (t.clone(), false)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Layer `other` on top: its tool switches win, and its path and network limits replace
+    /// these only where it sets them
+    pub fn merge(mut self, other: ToolGrants) -> Self {
+        self.tools.extend(other.tools);
+        if other.write_paths.is_some() {
+            self.write_paths = other.write_paths;
+        }
+        if other.network != NetworkAccess::Full {
+            self.network = other.network;
+        }
+        self
+    }
+
+    /// Whether `tool` can be offered to the model at all
+    pub fn allows_tool(&self, tool: &str) -> bool {
+        self.tools.get(tool).copied().unwrap_or(true) && self.allows_network(network_use(tool, None))
+    }
+
+    fn allows_network(&self, network: Option<NetworkUse>) -> bool {
+        matches!(
+            (self.network, network),
+            (_, None) | (NetworkAccess::Full, _) | (NetworkAccess::ReadOnly, Some(NetworkUse::Read))
+        )
+    }
+
+    fn allows_write(&self, path: &str) -> bool {
+        let prefixes = match &self.write_paths {
+            Some(prefixes) => prefixes,
+            None => return true,
+        };
+        let normalized = path.replace('\\', "/");
+        let trimmed = normalized.trim_start_matches("./");
+        if trimmed.split('/').any(|part| part == "..") {
+            return false;
+        }
+        prefixes.iter().map(|p| p.trim_matches('/')).any(|prefix| {
+            trimmed == prefix
+                || (trimmed.starts_with(prefix) && trimmed.as_bytes().get(prefix.len()) == Some(&b'/'))
+        })
+    }
+
+    /// Whether this layer lets `tool` run with `arguments`; `source` says where the layer
+    /// comes from, e.g. "in this session"
+    pub fn check(&self, tool: &str, arguments: &serde_json::Value, source: &str) -> Result<(), String> {
+        if !self.tools.get(tool).copied().unwrap_or(true) {
+            return Err(format!("Tool '{}' is disabled {}", tool, source));
+        }
+        let network = network_use(tool, Some(arguments));
+        if !self.allows_network(network) {
+            let access = if network == Some(NetworkUse::Write) { "network write access" } else { "network access" };
+            return Err(format!("Tool '{}' needs {}, which is not granted {}", tool, access, source));
+        }
+        if let Some(path) = write_targets(tool, arguments).into_iter().find(|p| !self.allows_write(p)) {
+            let allowed = self.write_paths.as_deref().unwrap_or_default().join("/, ");
+            return Err(format!("'{}' may not write to '{}' {}: writes are allowed only under {}/", tool, path, source, allowed));
+        }
+        Ok(())
+    }
+}
+
+/// Check `tool` against each `(grants, source)` layer in turn
+pub fn check(layers: &[(ToolGrants, &str)], tool: &str, arguments: &str) -> Result<(), String> {
+    let arguments = serde_json::from_str(arguments).unwrap_or(serde_json::Value::Null);
+    layers.iter().try_for_each(|(grants, source)| grants.check(tool, &arguments, source))
+}
+
+/// What `tool` does over the network. `arguments` refine it where they matter; without them
+/// a tool gets its lightest use, so it still gets listed.
+fn network_use(tool: &str, arguments: Option<&serde_json::Value>) -> Option<NetworkUse> {
+    match tool {
+        "tkg_store" | "claim_legacy_data" | "log_daily_note" => Some(NetworkUse::Write),
+        "ingest_paper" => {
+            let stores = arguments.map_or(false, |a| a.get("store_in_tkg").and_then(|v| v.as_bool()).unwrap_or(true));
+            Some(if stores { NetworkUse::Write } else { NetworkUse::Read })
+        }
+        // Only tesseract reads the text without sending the screenshot anywhere
+        "capture_screenshot" => {
+            let local = arguments.map_or(true, |a| a.get("ocr").and_then(|v| v.as_str()) == Some("tesseract"));
+            (!local).then_some(NetworkUse::Read)
+        }
+        "web_search" | "deep_research" | "clip_url" | "harvest_github" | "harvest_youtube_transcript"
+        | "harvest_wiki" | "harvest_wiki_category" | "brainstorm_with_grok" | "create_study_guide"
+        | "start_debate" | "invoke_agent" | "consult_agent" | "tkg_search" | "translate" => Some(NetworkUse::Read),
+        _ => None,
+    }
+}
+
+/// The knowledge base paths `tool` would write to
+fn write_targets(tool: &str, arguments: &serde_json::Value) -> Vec<String> {
+    let str_arg = |key: &str| arguments.get(key).and_then(|v| v.as_str());
+    let fixed = |folder: &str| vec![folder.to_string()];
+    match tool {
+        "write_file" => str_arg("path").map(|p| vec![p.to_string()]).unwrap_or_default(),
+        "write_file_batch" => arguments
+            .get("files")
+            .and_then(|f| f.as_array())
+            .map(|files| files.iter().filter_map(|f| f.get("path")?.as_str().map(|p| p.to_string())).collect())
+            .unwrap_or_default(),
+        "log_daily_note" => fixed(crate::daily_notes::JOURNAL_FOLDER),
+        "clip_url" => fixed(crate::clipper::CLIPS_FOLDER),
+        "capture_screenshot" => fixed(crate::screenshot::SCREENSHOTS_FOLDER),
+        "translate" if arguments.get("save").and_then(|v| v.as_bool()).unwrap_or(false) => match (str_arg("path"), str_arg("target_language")) {
+            (Some(path), Some(target)) => vec![crate::translate::translation_key(path, target)],
+            _ => Vec::new(),
+        },
+        "harvest_youtube_transcript" => fixed(crate::youtube::YOUTUBE_FOLDER),
+        "harvest_github" => fixed(crate::github::GITHUB_FOLDER),
+        "deep_research" => fixed(crate::research_notes::RESEARCH_FOLDER),
+        "harvest_wiki" | "harvest_wiki_category" => {
+            crate::mediawiki::WikiSite::resolve(str_arg("wiki").unwrap_or("rs3"), str_arg("lang"), str_arg("community"))
+                .map(|site| vec![site.folder])
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The grants recorded for `session_name`, if any
+pub fn session_grants(app_handle: &AppHandle, session_name: &str) -> Option<ToolGrants> {
+    let state = app_handle.try_state::<AppState>()?;
+    let grants = state.tool_grants.lock().ok()?;
+    grants.get(session_name).cloned()
+}
+
+/// Grant `session_name` these scopes, replacing any it had
+#[tauri::command]
+pub fn set_tool_grants(state: State<'_, AppState>, session_name: String, grants: ToolGrants) -> Result<ToolGrants, String> {
+    tracing::info!("🔐 Tool grants for '{}': network {:?}, writes under {:?}", session_name, grants.network, grants.write_paths);
+    state.tool_grants.lock().map_err(|e| e.to_string())?.insert(session_name, grants.clone());
+    Ok(grants)
+}
+
+#[tauri::command]
+pub fn get_tool_grants(state: State<'_, AppState>, session_name: String) -> Result<Option<ToolGrants>, String> {
+    Ok(state.tool_grants.lock().map_err(|e| e.to_string())?.get(&session_name).cloned())
+}
+
+/// Drop the grants of `session_name`; its tools fall back to the app-wide policy
+#[tauri::command]
+pub fn revoke_tool_grants(state: State<'_, AppState>, session_name: String) -> Result<bool, String> {
+    Ok(state.tool_grants.lock().map_err(|e| e.to_string())?.remove(&session_name).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn research_session() -> ToolGrants {
+        ToolGrants {
+            write_paths: Some(vec!["research".to_string()]),
+            network: NetworkAccess::ReadOnly,
+            ..ToolGrants::default()
+        }
+    }
+
+    fn layers() -> [(ToolGrants, &'static str); 2] {
+        let config = ToolGrants::disabling(&["run_terminal_command".to_string()]);
+        [(config, "in thinkspace.toml"), (research_session(), "in this session")]
+    }
+
+    #[test]
+    fn test_write_paths() {
+        let layers = layers();
+        assert!(check(&layers, "write_file", r#"{"path": "research/notes.md"}"#).is_ok());
+        assert!(check(&layers, "write_file", r#"{"path": "./research/a/b.md"}"#).is_ok());
+        let denied = check(&layers, "write_file", r#"{"path": "researchers/x.md"}"#).unwrap_err();
+        assert!(denied.contains("in this session") && denied.contains("research/"), "{}", denied);
+        assert!(check(&layers, "write_file", r#"{"path": "research/../secrets.md"}"#).is_err());
+        assert!(check(&layers, "write_file_batch", r#"{"files": [{"path": "research/a.md"}, {"path": "dumps/b.md"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_disabled_tool_names_its_layer() {
+        assert!(check(&layers(), "run_terminal_command", "{}").unwrap_err().contains("in thinkspace.toml"));
+    }
+
+    #[test]
+    fn test_read_only_network() {
+        // Read-only network: searching is fine, storing memory is not
+        let layers = layers();
+        assert!(check(&layers, "web_search", r#"{"query": "rust"}"#).is_ok());
+        assert!(check(&layers, "tkg_store", "{}").unwrap_err().contains("network write access"));
+        assert!(check(&layers, "ingest_paper", r#"{"url_or_path": "2401.00001"}"#).is_err());
+        assert!(check(&layers, "ingest_paper", r#"{"url_or_path": "2401.00001", "store_in_tkg": false}"#).is_ok());
+        let session = research_session();
+        assert!(session.allows_tool("ingest_paper") && !session.allows_tool("tkg_store"));
+    }
+
+    #[test]
+    fn test_no_network() {
+        let offline = ToolGrants { network: NetworkAccess::None, ..ToolGrants::default() };
+        assert!(!offline.allows_tool("web_search") && offline.allows_tool("read_file"));
+    }
+
+    #[test]
+    fn test_merge() {
+        // A later layer's tool switches win; unset limits don't loosen earlier ones
+        let session = research_session();
+        let merged = session.clone().merge(ToolGrants::disabling(&["web_search".to_string()]));
+        assert_eq!(merged.write_paths, session.write_paths);
+        assert_eq!(merged.network, NetworkAccess::ReadOnly);
+        assert!(!merged.allows_tool("web_search"));
+    }
+}
@@ -0,0 +1,256 @@
+/// User profiles, each with its own knowledge base, sessions, settings and TKG namespace
+///
+/// The default profile ("guest") keeps everything where it always was: thinkspace.toml and
+/// `sessions/` in app data, the usual knowledge base, and whatever user id the caller passes
+/// to the TKG. Other profiles keep their thinkspace.toml and sessions under
+/// `<app data>/profiles/<id>/`, get a `knowledge-base` folder there (or a root of their
+/// choosing, set in their own thinkspace.toml), and always store and search memory as
+/// `profile:<id>`. Switching profiles reloads the config, which moves the file tools, the
+/// index and the watcher to the profile's knowledge base.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager};
+
+pub const DEFAULT_PROFILE: &str = "guest";
+const PROFILES_FILE: &str = "profiles.json";
+const PROFILES_FOLDER: &str = "profiles";
+const KB_FOLDER: &str = "knowledge-base";
+
+lazy_static::lazy_static! {
+    /// The active profile's id, so the TKG doesn't read profiles.json on every call
+    static ref ACTIVE: RwLock<String> = RwLock::new(load(None).active);
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profiles {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Guest".to_string(),
+                created_at: String::new(),
+            }],
+        }
+    }
+}
+
+impl Profiles {
+    pub fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Add a profile named `name` with an id derived from it
+    pub fn add(&mut self, name: &str, created_at: String) -> Result<Profile, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name can't be empty".to_string());
+        }
+        let slug = crate::research_notes::slugify(name);
+        let mut id = slug.clone();
+        let mut n = 2;
+        while self.get(&id).is_some() {
+            id = format!("{}-{}", slug, n);
+            n += 1;
+        }
+        let profile = Profile { id, name: name.to_string(), created_at };
+        self.profiles.push(profile.clone());
+        Ok(profile)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<Profile, String> {
+        if id == DEFAULT_PROFILE {
+            return Err("The default profile can't be deleted".to_string());
+        }
+        if id == self.active {
+            return Err("Switch to another profile before deleting this one".to_string());
+        }
+        let index = self.profiles.iter().position(|p| p.id == id).ok_or_else(|| format!("No profile '{}'", id))?;
+        Ok(self.profiles.remove(index))
+    }
+}
+
+/// App data, where profiles.json and the default profile's files live
+pub fn app_data_dir(app_handle: Option<&AppHandle>) -> Option<PathBuf> {
+    match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    }
+}
+
+fn profile_dir(app_data: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE {
+        app_data.to_path_buf()
+    } else {
+        app_data.join(PROFILES_FOLDER).join(id)
+    }
+}
+
+fn load(app_handle: Option<&AppHandle>) -> Profiles {
+    let mut profiles: Profiles = app_data_dir(app_handle)
+        .and_then(|dir| std::fs::read_to_string(dir.join(PROFILES_FILE)).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if profiles.get(DEFAULT_PROFILE).is_none() {
+        profiles.profiles.insert(0, Profiles::default().profiles.remove(0));
+    }
+    if profiles.get(&profiles.active).is_none() {
+        profiles.active = DEFAULT_PROFILE.to_string();
+    }
+    profiles
+}
+
+fn save(app_handle: &AppHandle, profiles: &Profiles) -> Result<(), String> {
+    let dir = app_data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(PROFILES_FILE), json).map_err(|e| e.to_string())
+}
+
+pub fn active_id() -> String {
+    ACTIVE.read().map(|id| id.clone()).unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+}
+
+/// Where the active profile keeps its thinkspace.toml and sessions
+pub fn data_dir(app_handle: Option<&AppHandle>) -> Option<PathBuf> {
+    app_data_dir(app_handle).map(|dir| profile_dir(&dir, &active_id()))
+}
+
+/// The knowledge base of a non-default profile whose config doesn't name one, so it never
+/// falls back to the shared one
+pub fn fallback_kb_root() -> Option<PathBuf> {
+    let id = active_id();
+    if id == DEFAULT_PROFILE {
+        return None;
+    }
+    app_data_dir(None).map(|dir| profile_dir(&dir, &id).join(KB_FOLDER))
+}
+
+/// The TKG user id to store and search memory under. The default profile keeps the
+/// caller's id; any other profile always uses its own namespace.
+pub fn tkg_user_id(requested: &str) -> String {
+    namespace(&active_id(), requested)
+}
+
+fn namespace(profile_id: &str, requested: &str) -> String {
+    if profile_id == DEFAULT_PROFILE {
+        requested.to_string()
+    } else {
+        format!("profile:{}", profile_id)
+    }
+}
+
+#[tauri::command]
+pub fn list_profiles(app_handle: AppHandle) -> Result<Profiles, String> {
+    Ok(load(Some(&app_handle)))
+}
+
+/// Create a profile with its own settings and a knowledge base at `knowledge_base_root`
+/// (default: a `knowledge-base` folder in the profile's app data)
+#[tauri::command]
+pub fn create_profile(app_handle: AppHandle, name: String, knowledge_base_root: Option<String>) -> Result<Profile, String> {
+    let mut profiles = load(Some(&app_handle));
+    let profile = profiles.add(&name, crate::scheduler::stamp(chrono::Utc::now()))?;
+
+    let app_data = app_data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+    let dir = profile_dir(&app_data, &profile.id);
+    let kb_root = match knowledge_base_root.map(|r| r.trim().to_string()).filter(|r| !r.is_empty()) {
+        Some(root) => PathBuf::from(root),
+        None => dir.join(KB_FOLDER),
+    };
+    std::fs::create_dir_all(&kb_root).map_err(|e| format!("Failed to create {}: {}", kb_root.display(), e))?;
+
+    let mut config = crate::config::Config::default();
+    config.general.knowledge_base_root = Some(kb_root.to_string_lossy().to_string());
+    crate::config::write_config(&dir.join(crate::config::CONFIG_FILE), &config)?;
+
+    save(&app_handle, &profiles)?;
+    tracing::info!("👤 Created profile '{}' with its knowledge base at {}", profile.name, kb_root.display());
+    Ok(profile)
+}
+
+/// Make `id` the active profile and load its settings and knowledge base
+#[tauri::command]
+pub fn switch_profile(app_handle: AppHandle, id: String) -> Result<Profile, String> {
+    let mut profiles = load(Some(&app_handle));
+    let profile = profiles.get(&id).cloned().ok_or_else(|| format!("No profile '{}'", id))?;
+    profiles.active = id.clone();
+    save(&app_handle, &profiles)?;
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = id;
+    }
+
+    // The profile's thinkspace.toml brings its knowledge base root with it
+    crate::config::init(&app_handle)?;
+    crate::file_watcher::sync_kb_root(&app_handle)?;
+    tracing::info!("👤 Switched to profile '{}'", profile.name);
+    let _ = app_handle.emit_all("profile-changed", &profile);
+    Ok(profile)
+}
+
+/// Remove a profile. Its settings, sessions and default knowledge base folder are only
+/// deleted with `delete_data`; a knowledge base elsewhere is never touched.
+#[tauri::command]
+pub fn delete_profile(app_handle: AppHandle, id: String, delete_data: Option<bool>) -> Result<Profiles, String> {
+    let mut profiles = load(Some(&app_handle));
+    let profile = profiles.remove(&id)?;
+    save(&app_handle, &profiles)?;
+    if delete_data.unwrap_or(false) {
+        let app_data = app_data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+        let dir = profile_dir(&app_data, &profile.id);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete {}: {}", dir.display(), e))?;
+        }
+    }
+    tracing::info!("👤 Deleted profile '{}'", profile.name);
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_picks_unique_ids() {
+        let mut profiles = Profiles::default();
+        let work = profiles.add("Work Notes", String::new()).unwrap();
+        assert_eq!(work.id, "work-notes");
+        assert_eq!(profiles.add("work notes!", String::new()).unwrap().id, "work-notes-2");
+        assert!(profiles.add("  ", String::new()).is_err());
+    }
+
+    #[test]
+    fn test_profile_dir_and_namespace() {
+        let app_data = Path::new("/data");
+        assert_eq!(profile_dir(app_data, DEFAULT_PROFILE), PathBuf::from("/data"));
+        assert_eq!(profile_dir(app_data, "work-notes"), PathBuf::from("/data/profiles/work-notes"));
+        assert_eq!(namespace(DEFAULT_PROFILE, "user-123"), "user-123");
+        assert_eq!(namespace("work-notes", "user-123"), "profile:work-notes");
+    }
+
+    #[test]
+    fn test_remove_keeps_default_and_active_profiles() {
+        let mut profiles = Profiles::default();
+        let work = profiles.add("Work Notes", String::new()).unwrap();
+        assert!(profiles.remove(DEFAULT_PROFILE).is_err());
+        profiles.active = work.id.clone();
+        assert!(profiles.remove(&work.id).is_err());
+        profiles.active = DEFAULT_PROFILE.to_string();
+        assert_eq!(profiles.remove(&work.id).unwrap().name, "Work Notes");
+        assert!(profiles.remove(&work.id).is_err());
+    }
+}
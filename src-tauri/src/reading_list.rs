@@ -0,0 +1,294 @@
+/// Batch ingestion of a reading list
+///
+/// `ingest_reading_list` takes URLs and/or files holding URLs (a text or markdown list, or
+/// exported bookmarks), clips every page in the background a few at a time, tags the clips,
+/// and keeps an index note under `research/reading-lists/` with the status of each item.
+/// `reading-list-progress` is emitted per item and `reading-list-complete` at the end.
+
+use futures_util::stream::{self, StreamExt};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+pub const READING_LISTS_FOLDER: &str = "research/reading-lists";
+const DEFAULT_CONCURRENCY: usize = 3;
+const MAX_CONCURRENCY: usize = 8;
+const MAX_ITEMS: usize = 500;
+
+lazy_static::lazy_static! {
+    static ref URL: Regex = Regex::new(r#"https?://[^\s<>"'`)\]]+"#).unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingListItem {
+    pub url: String,
+    /// "pending", "clipped" or "failed"
+    pub status: String,
+    pub title: Option<String>,
+    /// The clip's note, relative to the knowledge base
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingListJob {
+    pub id: String,
+    /// The index note, relative to the knowledge base
+    pub index_path: String,
+    pub total: usize,
+    /// Entries that were neither a URL nor a readable file of URLs
+    pub skipped: Vec<String>,
+}
+
+/// The http(s) URLs in `text`, without trailing punctuation
+pub fn extract_urls(text: &str) -> Vec<String> {
+    URL.find_iter(text)
+        .map(|m| m.as_str().trim_end_matches(|c| matches!(c, '.' | ',' | ';' | ':' | '!' | '?')).to_string())
+        .collect()
+}
+
+/// URLs from the entries: each is a URL or a file (absolute, or relative to the knowledge
+/// base) whose URLs are read. Duplicates by canonical URL are dropped.
+fn collect_urls(kb_root: &Path, entries: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut urls = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in entries.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        if entry.starts_with("http://") || entry.starts_with("https://") {
+            urls.push(entry.to_string());
+            continue;
+        }
+        let path = Path::new(entry);
+        let path = if path.is_absolute() { path.to_path_buf() } else { kb_root.join(path) };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let found = extract_urls(&text);
+                if found.is_empty() {
+                    skipped.push(format!("{}: no URLs found", entry));
+                }
+                urls.extend(found);
+            }
+            Err(e) => skipped.push(format!("{}: {}", entry, e)),
+        }
+    }
+    let mut seen = HashSet::new();
+    urls.retain(|url| seen.insert(crate::harvests::canonical_url(url)));
+    (urls, skipped)
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+pub fn render_index(title: &str, created: &str, tags: &[String], items: &[ReadingListItem]) -> String {
+    let mut all_tags = vec!["reading-list".to_string()];
+    all_tags.extend(tags.iter().cloned());
+    let frontmatter = serde_yaml::to_string(&serde_json::json!({
+        "title": title,
+        "created": created,
+        "tags": all_tags,
+    }))
+    .unwrap_or_default();
+
+    let count = |status: &str| items.iter().filter(|i| i.status == status).count();
+    let mut body = format!(
+        "# {}\n\n{} items: {} clipped, {} failed, {} pending\n\n| # | Item | Status | Note |\n| --- | --- | --- | --- |\n",
+        title,
+        items.len(),
+        count("clipped"),
+        count("failed"),
+        count("pending")
+    );
+    for (i, item) in items.iter().enumerate() {
+        let label = item.title.as_deref().map(escape_cell).unwrap_or_else(|| escape_cell(&item.url));
+        let status = match (&item.status[..], &item.error) {
+            ("failed", Some(error)) => format!("❌ {}", escape_cell(error)),
+            ("clipped", _) => "✅ clipped".to_string(),
+            (other, _) => other.to_string(),
+        };
+        // Index notes live two folders down from the knowledge base root
+        let note = item.path.as_deref().map(|p| format!("[note](../../{})", p)).unwrap_or_default();
+        body.push_str(&format!("| {} | [{}]({}) | {} | {} |\n", i + 1, label, item.url, status, note));
+    }
+    format!("---\n{}---\n\n{}", frontmatter, body)
+}
+
+fn index_path(kb_root: &Path, title: &str) -> PathBuf {
+    let folder = kb_root.join(READING_LISTS_FOLDER);
+    let slug = crate::research_notes::slugify(title);
+    let mut path = folder.join(format!("{}.md", slug));
+    let mut n = 2;
+    while path.exists() {
+        path = folder.join(format!("{}-{}.md", slug, n));
+        n += 1;
+    }
+    path
+}
+
+fn write_index(app_handle: &tauri::AppHandle, kb_root: &Path, path: &Path, title: &str, created: &str, tags: &[String], items: &[ReadingListItem]) {
+    crate::note_versions::record_before_write(Some(app_handle), kb_root, path, "reading-list");
+    if let Err(e) = std::fs::write(path, render_index(title, created, tags, items)) {
+        tracing::warn!("⚠️ Failed to write reading list index: {}", e);
+        return;
+    }
+    crate::kb_index::apply_watch_changes(app_handle, &[path.to_path_buf()], None);
+    crate::events::emit(app_handle, "content-changed", serde_json::json!({
+        "source": kb_root.join(READING_LISTS_FOLDER),
+        "paths": [crate::kb_index::path_key(kb_root, path)],
+    }));
+}
+
+/// Clip every URL in the list in the background; returns the job and its index note at once
+#[tauri::command]
+pub async fn ingest_reading_list(
+    app_handle: tauri::AppHandle,
+    paths_or_urls: Vec<String>,
+    tags: Option<Vec<String>>,
+    name: Option<String>,
+    concurrency: Option<usize>,
+) -> Result<ReadingListJob, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let (mut urls, skipped) = collect_urls(&kb_root, &paths_or_urls);
+    if urls.is_empty() {
+        return Err(format!("No URLs to ingest{}", if skipped.is_empty() { String::new() } else { format!(": {}", skipped.join("; ")) }));
+    }
+    if urls.len() > MAX_ITEMS {
+        tracing::warn!("⚠️ Reading list has {} URLs, ingesting the first {}", urls.len(), MAX_ITEMS);
+        urls.truncate(MAX_ITEMS);
+    }
+
+    let now = chrono::Local::now();
+    let created = now.format("%Y-%m-%d").to_string();
+    let title = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("Reading list {}", now.format("%Y-%m-%d %H:%M")));
+    let tags: Vec<String> = tags.unwrap_or_default().into_iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect();
+    let concurrency = concurrency.unwrap_or(DEFAULT_CONCURRENCY).clamp(1, MAX_CONCURRENCY);
+
+    let path = index_path(&kb_root, &title);
+    std::fs::create_dir_all(kb_root.join(READING_LISTS_FOLDER)).map_err(|e| e.to_string())?;
+    let mut items: Vec<ReadingListItem> = urls
+        .iter()
+        .map(|url| ReadingListItem { url: url.clone(), status: "pending".to_string(), title: None, path: None, error: None })
+        .collect();
+    write_index(&app_handle, &kb_root, &path, &title, &created, &tags, &items);
+
+    let job = ReadingListJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        index_path: crate::kb_index::path_key(&kb_root, &path),
+        total: items.len(),
+        skipped,
+    };
+    tracing::info!("📚 Ingesting {} URL(s) from '{}' ({} at a time)", job.total, title, concurrency);
+
+    let job_id = job.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let handle = &app_handle;
+        let tags_ref = &tags;
+        let mut clipped = stream::iter(urls.into_iter().enumerate())
+            .map(|(i, url)| async move {
+                let result = crate::clipper::clip(Some(handle), &url, false, tags_ref).await;
+                (i, result)
+            })
+            .buffer_unordered(concurrency);
+
+        let total = items.len();
+        let mut done = 0;
+        while let Some((i, result)) = clipped.next().await {
+            done += 1;
+            let item = &mut items[i];
+            match result {
+                Ok(clip) => {
+                    item.status = "clipped".to_string();
+                    item.title = Some(clip.title);
+                    item.path = Some(clip.path);
+                }
+                Err(e) => {
+                    item.status = "failed".to_string();
+                    item.error = Some(e);
+                }
+            }
+            let _ = handle.emit_all("reading-list-progress", serde_json::json!({
+                "jobId": job_id,
+                "url": item.url,
+                "status": item.status,
+                "path": item.path,
+                "error": item.error,
+                "done": done,
+                "total": total,
+            }));
+        }
+        drop(clipped);
+
+        write_index(handle, &kb_root, &path, &title, &created, &tags, &items);
+        let failed = items.iter().filter(|i| i.status == "failed").count();
+        tracing::info!("📚 Reading list '{}' done: {} clipped, {} failed", title, items.len() - failed, failed);
+        let _ = handle.emit_all("reading-list-complete", serde_json::json!({
+            "jobId": job_id,
+            "indexPath": crate::kb_index::path_key(&kb_root, &path),
+            "clipped": items.len() - failed,
+            "failed": failed,
+        }));
+    });
+
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_index() -> String {
+        let items = vec![
+            ReadingListItem {
+                url: "https://example.com/post".to_string(),
+                status: "clipped".to_string(),
+                title: Some("A | post".to_string()),
+                path: Some("research/clips/a-post.md".to_string()),
+                error: None,
+            },
+            ReadingListItem {
+                url: "https://doc.rust-lang.org/book/ch04-01-what-is-ownership.html".to_string(),
+                status: "failed".to_string(),
+                title: None,
+                path: None,
+                error: Some("HTTP 404".to_string()),
+            },
+        ];
+        render_index("Rust reading", "2026-10-16", &["rust".to_string()], &items)
+    }
+
+    #[test]
+    fn test_collect_urls() {
+        let kb = tempfile::tempdir().unwrap();
+        std::fs::write(
+            kb.path().join("list.md"),
+            "- [Ownership](https://doc.rust-lang.org/book/ch04-01-what-is-ownership.html)\n- https://example.com/post?utm_source=feed.\n",
+        )
+        .unwrap();
+        let entries = vec!["https://example.com/post".to_string(), "list.md".to_string(), "missing.txt".to_string()];
+        let (urls, skipped) = collect_urls(kb.path(), &entries);
+        assert_eq!(urls, vec!["https://example.com/post", "https://doc.rust-lang.org/book/ch04-01-what-is-ownership.html"]);
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_render_index() {
+        let index = sample_index();
+        let (fm, body) = crate::frontmatter::split_frontmatter(&index);
+        assert_eq!(fm.unwrap().tags, vec!["reading-list".to_string(), "rust".to_string()]);
+        assert!(body.contains("2 items: 1 clipped, 1 failed, 0 pending"));
+        assert!(body.contains("| 1 | [A \\| post](https://example.com/post) | ✅ clipped | [note](../../research/clips/a-post.md) |"));
+        assert!(body.contains("| ❌ HTTP 404 |"));
+    }
+
+    #[test]
+    fn test_index_links_to_its_clips() {
+        let index = sample_index();
+        let links = crate::note_links::extract_links("research/reading-lists/rust-reading.md", &index);
+        assert_eq!(links[0].target, "research/clips/a-post.md");
+    }
+}
@@ -0,0 +1,101 @@
+/// Reflection: a critic reviews an agent's draft against a rubric and the agent revises once
+///
+/// Chain steps opt in with `reflect`, chat requests with the `reflect` option. The critic
+/// can reply NO CHANGES NEEDED, in which case the draft stands and no revision is made.
+/// Either way the draft and the critique are returned alongside the final output.
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_RUBRIC: &str = "- Correct: no factual or logical errors\n\
+- Complete: answers every part of the task\n\
+- Specific: concrete details, examples or steps rather than generalities\n\
+- Clear: well organized and no longer than it needs to be";
+
+pub const CRITIC_PROMPT: &str = r#"You are a demanding but fair Critic.
+You review drafts against a rubric and point out exactly what falls short and how to fix it.
+You don't rewrite the draft yourself."#;
+
+/// Critic's reply when the draft needs no revision
+const NO_CHANGES: &str = "NO CHANGES NEEDED";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReflectOptions {
+    /// What the draft is judged against; DEFAULT_RUBRIC when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rubric: Option<String>,
+    /// Registered agent (agents.json) that critiques; a built-in critic otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub critic_id: Option<String>,
+}
+
+impl ReflectOptions {
+    pub fn rubric(&self) -> &str {
+        self.rubric.as_deref().filter(|r| !r.trim().is_empty()).unwrap_or(DEFAULT_RUBRIC)
+    }
+}
+
+/// The draft and critique behind a reflected output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reflection {
+    pub draft: String,
+    pub critique: String,
+    /// False when the critic asked for no changes or the revision failed, so the draft
+    /// is the final output
+    pub revised: bool,
+}
+
+pub fn critique_prompt(task: &str, draft: &str, rubric: &str) -> String {
+    format!(
+        "Review this draft against the rubric.\n\n=== Task ===\n{}\n\n=== Draft ===\n{}\n\n=== Rubric ===\n{}\n\n\
+         For each rubric point, say whether the draft meets it and exactly what to change if not. \
+         If the draft needs no changes at all, reply with only {}.",
+        task.trim(),
+        draft.trim(),
+        rubric.trim(),
+        NO_CHANGES
+    )
+}
+
+/// What the author is asked to revise. `task` and `draft` are included for an author that
+/// doesn't have them in its conversation already.
+pub fn revision_prompt(task: Option<&str>, draft: &str, critique: &str) -> String {
+    let mut prompt = String::new();
+    if let Some(task) = task {
+        prompt.push_str(&format!("{}\n\n=== Your draft ===\n{}\n\n", task.trim(), draft.trim()));
+    }
+    prompt.push_str(&format!(
+        "A reviewer critiqued your draft:\n\n{}\n\nRevise it once to address the critique. \
+         Reply with the complete revised version only.",
+        critique.trim()
+    ));
+    prompt
+}
+
+/// Whether the critic found nothing to change
+pub fn approves(critique: &str) -> bool {
+    critique.to_uppercase().contains(NO_CHANGES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rubric() {
+        assert_eq!(ReflectOptions::default().rubric(), DEFAULT_RUBRIC);
+        let options = ReflectOptions { rubric: Some("- Cites sources".to_string()), critic_id: None };
+        assert!(critique_prompt("Explain WAL mode", "It logs writes.", options.rubric()).contains("=== Rubric ===\n- Cites sources"));
+    }
+
+    #[test]
+    fn test_approval() {
+        assert!(approves("No changes needed."));
+        assert!(!approves("Missing: checkpointing. Add an example."));
+    }
+
+    #[test]
+    fn test_revision_prompt() {
+        assert!(!revision_prompt(None, "draft", "Add an example.").contains("Your draft"));
+        assert!(revision_prompt(Some("Task: Explain WAL mode"), "draft", "Add an example.").starts_with("Task: Explain WAL mode\n\n=== Your draft ===\ndraft"));
+    }
+}
@@ -0,0 +1,613 @@
+/// Reminders and deadlines picked out of what the user asks to have remembered
+///
+/// When a memory goes to the TKG and WAMA's reminder check flags it ("remind me to…",
+/// "deadline…", "by Friday"), `capture` looks for when it's due: a date ("2026-03-14",
+/// "March 14", "Friday", "tomorrow", "end of the month"), a time ("at 5pm", "17:30",
+/// "tonight") or an offset ("in 2 hours"). Text with a due date becomes a reminder in
+/// knowledge_companion.db, stored once per title and time. Dates without a time are due at
+/// `[calendar] all_day_time`.
+///
+/// A background loop announces each reminder once, `lead_minutes` before it's due, with a
+/// `reminder-due` event and a notification in the reminders category; ones that came due
+/// while the app was closed are announced at the next check. The agent's `notify` tool adds
+/// reminders too. Open reminders can be exported as a .ics file or pushed to the CalDAV
+/// calendar set in `[calendar]`.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::{Captures, Regex};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+use crate::config::CalendarConfig;
+use crate::scheduler::stamp;
+
+/// How often due reminders are looked for
+const CHECK_INTERVAL_SECS: u64 = 30;
+const TITLE_CHARS: usize = 120;
+/// Openings dropped from a reminder's title
+const LEADS: [&str; 6] = ["remind me to ", "remind me ", "remember to ", "don't forget to ", "dont forget to ", "todo: "];
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAYS: [&str; 7] = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+const MONTH_NAMES: &str = "january|february|march|april|may|june|july|august|september|october|november|december|jan|feb|mar|apr|jun|jul|aug|sept|sep|oct|nov|dec";
+
+lazy_static::lazy_static! {
+    static ref RELATIVE: Regex = Regex::new(
+        r"\bin\s+(\d+|an?|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve)\s+(minutes?|mins?|hours?|hrs?|days?|weeks?)\b"
+    ).unwrap();
+    static ref ISO_DATE: Regex = Regex::new(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b").unwrap();
+    static ref MONTH_DAY: Regex = Regex::new(&format!(r"\b({})\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?\b(?:,?\s+(\d{{4}}))?", MONTH_NAMES)).unwrap();
+    static ref DAY_MONTH: Regex = Regex::new(&format!(r"\b(\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?({})\b(?:,?\s+(\d{{4}}))?", MONTH_NAMES)).unwrap();
+    static ref NAMED_DAY: Regex = Regex::new(r"\b(today|tonight|tomorrow|next week|end of (?:the )?week|end of (?:the )?month)\b").unwrap();
+    static ref WEEKDAY: Regex = Regex::new(r"\b(?:(this|next)\s+)?(monday|tuesday|wednesday|thursday|friday|saturday|sunday)\b").unwrap();
+    static ref TWELVE_HOUR: Regex = Regex::new(r"\b(\d{1,2})(?::(\d{2}))?\s*(am|pm)\b").unwrap();
+    static ref AT_HOUR: Regex = Regex::new(r"\bat\s+(\d{1,2})(?::(\d{2}))?\b").unwrap();
+    static ref CLOCK: Regex = Regex::new(r"\b([01]?\d|2[0-3]):([0-5]\d)\b").unwrap();
+    static ref PART_OF_DAY: Regex = Regex::new(r"\b(noon|midday|morning|afternoon|evening|tonight)\b").unwrap();
+}
+
+/// When something is due, in local time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Due {
+    pub at: NaiveDateTime,
+    /// Only a date was given; `at` is the configured time of day
+    pub all_day: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub title: String,
+    /// The text the reminder was found in
+    pub source: String,
+    /// "memory", "manual" or "agent"
+    pub origin: String,
+    pub due_at: String,
+    pub all_day: bool,
+    /// When it's announced: `lead_minutes` before it's due
+    pub notify_at: String,
+    pub notified_at: Option<String>,
+    pub done_at: Option<String>,
+    /// When it was last pushed to CalDAV
+    pub pushed_at: Option<String>,
+    pub created_at: String,
+}
+
+fn number(word: &str) -> Option<i64> {
+    let words = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve"];
+    match word {
+        "a" | "an" => Some(1),
+        _ => word.parse().ok().or_else(|| words.iter().position(|w| *w == word).map(|i| i as i64 + 1)),
+    }
+}
+
+/// A month-day match; without a year, the next time that date comes round
+fn month_day(month: &str, day: &str, year: Option<&str>, today: NaiveDate) -> Option<NaiveDate> {
+    let month = MONTHS.iter().position(|m| month.starts_with(m))? as u32 + 1;
+    let day = day.parse().ok()?;
+    match year.and_then(|y| y.parse().ok()) {
+        Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+        None => {
+            let date = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+            if date < today { NaiveDate::from_ymd_opt(today.year() + 1, month, day) } else { Some(date) }
+        }
+    }
+}
+
+fn find_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Some(c) = ISO_DATE.captures(text) {
+        return NaiveDate::from_ymd_opt(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?);
+    }
+    if let Some(c) = MONTH_DAY.captures(text) {
+        return month_day(&c[1], &c[2], c.get(3).map(|m| m.as_str()), today);
+    }
+    if let Some(c) = DAY_MONTH.captures(text) {
+        return month_day(&c[2], &c[1], c.get(3).map(|m| m.as_str()), today);
+    }
+    let weekday = today.weekday().num_days_from_monday() as i64;
+    if let Some(c) = NAMED_DAY.captures(text) {
+        let ahead = match &c[1] {
+            "today" | "tonight" => 0,
+            "tomorrow" => 1,
+            "next week" => 7 - weekday,
+            name if name.ends_with("month") => {
+                let (year, month) = if today.month() == 12 { (today.year() + 1, 1) } else { (today.year(), today.month() + 1) };
+                return NaiveDate::from_ymd_opt(year, month, 1)?.pred_opt();
+            }
+            // End of the week is Friday; at the weekend, the coming one
+            _ => (4 - weekday).rem_euclid(7),
+        };
+        return Some(today + Duration::days(ahead));
+    }
+    if let Some(c) = WEEKDAY.captures(text) {
+        let target = WEEKDAYS.iter().position(|d| *d == &c[2])? as i64;
+        let mut ahead = (target - weekday).rem_euclid(7);
+        if ahead == 0 && c.get(1).map(|m| m.as_str()) != Some("this") {
+            ahead = 7;
+        }
+        return Some(today + Duration::days(ahead));
+    }
+    None
+}
+
+fn clock(c: &Captures, hour: u32) -> Option<NaiveTime> {
+    let minute = c.get(2).map_or(Some(0), |m| m.as_str().parse().ok())?;
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn find_time(text: &str) -> Option<NaiveTime> {
+    if let Some(c) = TWELVE_HOUR.captures(text) {
+        let hour: u32 = c[1].parse().ok().filter(|h| (1..=12).contains(h))?;
+        return clock(&c, hour % 12 + if &c[3] == "pm" { 12 } else { 0 });
+    }
+    if let Some(c) = AT_HOUR.captures(text) {
+        let hour: u32 = c[1].parse().ok()?;
+        // Nobody means "at 3" in the night
+        return clock(&c, if (1..=6).contains(&hour) { hour + 12 } else { hour });
+    }
+    if let Some(c) = CLOCK.captures(text) {
+        return clock(&c, c[1].parse().ok()?);
+    }
+    let hour = match PART_OF_DAY.captures(text)?.get(1)?.as_str() {
+        "morning" => 9,
+        "noon" | "midday" => 12,
+        "afternoon" => 15,
+        "evening" => 18,
+        _ => 20,
+    };
+    NaiveTime::from_hms_opt(hour, 0, 0)
+}
+
+/// When `text` says something is due, relative to `now` (local time)
+pub fn extract_due(text: &str, now: NaiveDateTime, all_day_time: NaiveTime) -> Option<Due> {
+    let text = text.to_lowercase();
+    let today = now.date();
+    let mut date = None;
+    if let Some(c) = RELATIVE.captures(&text) {
+        let n = number(&c[1])?;
+        match c[2].chars().next() {
+            Some('m') => return Some(Due { at: now + Duration::minutes(n), all_day: false }),
+            Some('h') => return Some(Due { at: now + Duration::hours(n), all_day: false }),
+            Some('w') => date = Some(today + Duration::weeks(n)),
+            _ => date = Some(today + Duration::days(n)),
+        }
+    }
+    let date = date.or_else(|| find_date(&text, today));
+    match (date, find_time(&text)) {
+        (Some(date), Some(time)) => Some(Due { at: date.and_time(time), all_day: false }),
+        (Some(date), None) => Some(Due { at: date.and_time(all_day_time), all_day: true }),
+        // A time alone is the next time the clock shows it
+        (None, Some(time)) => {
+            let at = today.and_time(time);
+            Some(Due { at: if at > now { at } else { at + Duration::days(1) }, all_day: false })
+        }
+        (None, None) => None,
+    }
+}
+
+/// The first line of `text` without "remind me to" and the like, capitalized and shortened
+pub fn reminder_title(text: &str) -> String {
+    let line = text.trim().lines().next().unwrap_or_default().trim();
+    let lead = LEADS.iter().find(|lead| line.get(..lead.len()).map_or(false, |start| start.eq_ignore_ascii_case(lead)));
+    let rest = line[lead.map_or(0, |l| l.len())..].trim();
+    let mut chars = rest.chars();
+    let mut title: String = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    if title.chars().count() > TITLE_CHARS {
+        title = title.chars().take(TITLE_CHARS - 1).collect::<String>().trim_end().to_string() + "…";
+    }
+    title
+}
+
+pub fn init_reminder_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reminders (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            source TEXT NOT NULL,
+            origin TEXT NOT NULL,
+            due_at TEXT NOT NULL,
+            all_day INTEGER NOT NULL DEFAULT 0,
+            notify_at TEXT NOT NULL,
+            notified_at TEXT,
+            done_at TEXT,
+            pushed_at TEXT,
+            created_at TEXT NOT NULL,
+            UNIQUE(title, due_at)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_reminders_notify ON reminders(notify_at)", [])?;
+    Ok(())
+}
+
+const REMINDER_COLUMNS: &str = "id, title, source, origin, due_at, all_day, notify_at, notified_at, done_at, pushed_at, created_at";
+
+fn reminder_from_row(row: &rusqlite::Row) -> rusqlite::Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        source: row.get(2)?,
+        origin: row.get(3)?,
+        due_at: row.get(4)?,
+        all_day: row.get(5)?,
+        notify_at: row.get(6)?,
+        notified_at: row.get(7)?,
+        done_at: row.get(8)?,
+        pushed_at: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+/// Store `reminder` unless one with the same title and due time exists; returns whether it was new
+pub fn save_reminder(conn: &Connection, reminder: &Reminder) -> rusqlite::Result<bool> {
+    let inserted = conn.execute(
+        &format!("INSERT OR IGNORE INTO reminders ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)", REMINDER_COLUMNS),
+        params![
+            reminder.id,
+            reminder.title,
+            reminder.source,
+            reminder.origin,
+            reminder.due_at,
+            reminder.all_day,
+            reminder.notify_at,
+            reminder.notified_at,
+            reminder.done_at,
+            reminder.pushed_at,
+            reminder.created_at,
+        ],
+    )?;
+    Ok(inserted > 0)
+}
+
+fn query(conn: &Connection, filter: &str, value: &str) -> rusqlite::Result<Vec<Reminder>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM reminders WHERE {} ORDER BY due_at", REMINDER_COLUMNS, filter))?;
+    let rows = stmt.query_map(params![value], reminder_from_row)?;
+    rows.collect()
+}
+
+/// Open reminders due at or before `until` (a `stamp`), overdue ones included
+pub fn upcoming(conn: &Connection, until: &str) -> rusqlite::Result<Vec<Reminder>> {
+    query(conn, "done_at IS NULL AND due_at <= ?1", until)
+}
+
+/// Open reminders not yet announced whose time to announce has come
+pub fn to_announce(conn: &Connection, now: &str) -> rusqlite::Result<Vec<Reminder>> {
+    query(conn, "done_at IS NULL AND notified_at IS NULL AND notify_at <= ?1", now)
+}
+
+/// Open reminders; with `unpushed_only`, just the ones never pushed to CalDAV
+fn open_reminders(conn: &Connection, unpushed_only: bool) -> rusqlite::Result<Vec<Reminder>> {
+    let filter = if unpushed_only { "done_at IS NULL AND pushed_at IS NULL" } else { "done_at IS NULL" };
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM reminders WHERE {} ORDER BY due_at", REMINDER_COLUMNS, filter))?;
+    let rows = stmt.query_map([], reminder_from_row)?;
+    rows.collect()
+}
+
+fn set_stamp(conn: &Connection, id: &str, column: &str, at: &str) -> Result<(), String> {
+    let updated = conn
+        .execute(&format!("UPDATE reminders SET {} = ?2 WHERE id = ?1", column), params![id, at])
+        .map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Reminder '{}' not found", id));
+    }
+    Ok(())
+}
+
+fn new_reminder(text: &str, origin: &str, due: Due, offset: &FixedOffset, lead_minutes: u32) -> Option<Reminder> {
+    let due_at = offset.from_local_datetime(&due.at).single()?.with_timezone(&Utc);
+    Some(Reminder {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: reminder_title(text),
+        source: text.trim().to_string(),
+        origin: origin.to_string(),
+        due_at: stamp(due_at),
+        all_day: due.all_day,
+        notify_at: stamp(due_at - Duration::minutes(lead_minutes as i64)),
+        notified_at: None,
+        done_at: None,
+        pushed_at: None,
+        created_at: stamp(Utc::now()),
+    })
+}
+
+/// Store the reminder `text` holds, if WAMA takes it for one and it says when it's due.
+/// Returns the reminder when a new one was stored.
+pub fn capture(conn: &Connection, text: &str, origin: &str, now: DateTime<FixedOffset>, calendar: &CalendarConfig) -> rusqlite::Result<Option<Reminder>> {
+    if !crate::tkg::mentions_reminder(text) {
+        return Ok(None);
+    }
+    let due = match extract_due(text, now.naive_local(), calendar.all_day_time()) {
+        // A date that has passed is a mention, not a reminder
+        Some(due) if due.at.date() >= now.date_naive() => due,
+        _ => return Ok(None),
+    };
+    let reminder = match new_reminder(text, origin, due, now.offset(), calendar.lead_minutes) {
+        Some(reminder) if !reminder.title.is_empty() => reminder,
+        _ => return Ok(None),
+    };
+    Ok(if save_reminder(conn, &reminder)? { Some(reminder) } else { None })
+}
+
+/// `capture` for a memory on its way to the TKG; failures are only logged
+pub fn capture_memory(text: &str) {
+    let calendar = crate::config::current().calendar.clone();
+    let captured = crate::minimax_api::open_kc_database(None)
+        .and_then(|conn| capture(&conn, text, "memory", crate::config::now(), &calendar).map_err(|e| e.to_string()));
+    match captured {
+        Ok(Some(reminder)) => tracing::info!("🔔 Reminder '{}' due {}", reminder.title, reminder.due_at),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("⚠️ Failed to store reminder: {}", e),
+    }
+}
+
+fn to_event(reminder: &Reminder, calendar: &CalendarConfig) -> Option<crate::ical::IcsEvent> {
+    let due_at = DateTime::parse_from_rfc3339(&reminder.due_at).ok()?.with_timezone(&Utc);
+    let start = if reminder.all_day {
+        let offset = crate::config::offset_at(&crate::config::current(), due_at);
+        crate::ical::IcsTime::Date(due_at.with_timezone(&offset).date_naive())
+    } else {
+        crate::ical::IcsTime::At(due_at)
+    };
+    Some(crate::ical::IcsEvent {
+        uid: format!("{}@thinkspace", reminder.id),
+        summary: reminder.title.clone(),
+        description: Some(reminder.source.clone()).filter(|s| *s != reminder.title),
+        start,
+        alarm_minutes: Some(calendar.lead_minutes as i64),
+    })
+}
+
+fn announce(app_handle: &tauri::AppHandle, reminder: &Reminder) {
+    let _ = app_handle.emit_all("reminder-due", reminder);
+    crate::notifications::notify(app_handle, crate::notifications::Category::Reminders, "🔔 Reminder", &reminder.title);
+}
+
+fn announce_due(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(Some(app_handle))?;
+    let now = stamp(Utc::now());
+    for reminder in to_announce(&conn, &now).map_err(|e| e.to_string())? {
+        announce(app_handle, &reminder);
+        set_stamp(&conn, &reminder.id, "notified_at", &now)?;
+    }
+    Ok(())
+}
+
+/// Announce reminders as they come due, for as long as the app runs
+pub fn start_background(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = announce_due(&app_handle) {
+                tracing::warn!("⚠️ Failed to check reminders: {}", e);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Open reminders due within `days` (default 7), overdue ones first
+#[tauri::command]
+pub fn list_upcoming_reminders(app_handle: tauri::AppHandle, days: Option<i64>) -> Result<Vec<Reminder>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let until = stamp(Utc::now() + Duration::days(days.unwrap_or(7).max(0)));
+    upcoming(&conn, &until).map_err(|e| e.to_string())
+}
+
+/// When `due` says, or without it `text`: `due` may be an RFC 3339 time, a YYYY-MM-DD date or
+/// words such as "friday at 5pm"
+pub fn parse_due(text: &str, due: Option<&str>) -> Option<Due> {
+    let all_day_time = crate::config::current().calendar.all_day_time();
+    let now = crate::config::now();
+    match due.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(due) => match DateTime::parse_from_rfc3339(due) {
+            Ok(at) => Some(Due { at: at.with_timezone(now.offset()).naive_local(), all_day: false }),
+            Err(_) => match NaiveDate::parse_from_str(due, "%Y-%m-%d") {
+                Ok(date) => Some(Due { at: date.and_time(all_day_time), all_day: true }),
+                Err(_) => extract_due(due, now.naive_local(), all_day_time),
+            },
+        },
+        None => extract_due(text, now.naive_local(), all_day_time),
+    }
+}
+
+/// Store a reminder for `text` at `due`, unless the same one exists
+pub fn add(conn: &Connection, text: &str, due: Due, origin: &str) -> Result<Reminder, String> {
+    let reminder = new_reminder(text, origin, due, crate::config::now().offset(), crate::config::current().calendar.lead_minutes)
+        .filter(|r| !r.title.is_empty())
+        .ok_or("A reminder needs some text")?;
+    if !save_reminder(conn, &reminder).map_err(|e| e.to_string())? {
+        return Err(format!("'{}' is already a reminder at that time", reminder.title));
+    }
+    Ok(reminder)
+}
+
+/// Add a reminder by hand, due when `due` says or, without it, when `text` does
+#[tauri::command]
+pub fn add_reminder(app_handle: tauri::AppHandle, text: String, due: Option<String>) -> Result<Reminder, String> {
+    let parsed = parse_due(&text, due.as_deref());
+    let due = parsed.ok_or_else(|| format!("Couldn't tell when '{}' is due", due.as_deref().unwrap_or(&text)))?;
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    add(&conn, &text, due, "manual")
+}
+
+#[tauri::command]
+pub fn complete_reminder(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    set_stamp(&conn, &id, "done_at", &stamp(Utc::now()))
+}
+
+#[tauri::command]
+pub fn delete_reminder(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    conn.execute("DELETE FROM reminders WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Write the open reminders to `dest`, a .ics path or a folder; returns the file written
+#[tauri::command]
+pub fn export_reminders_ics(app_handle: tauri::AppHandle, dest: String) -> Result<String, String> {
+    let calendar = crate::config::current().calendar.clone();
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let events: Vec<_> = open_reminders(&conn, false)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|r| to_event(r, &calendar))
+        .collect();
+
+    let dest = Path::new(&dest);
+    let path: PathBuf = if dest.extension().map_or(false, |e| e.eq_ignore_ascii_case("ics")) {
+        dest.to_path_buf()
+    } else {
+        dest.join("thinkspace-reminders.ics")
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, crate::ical::calendar(&events, Utc::now())).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    tracing::info!("📅 Exported {} reminders to {}", events.len(), path.display());
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalDavPush {
+    pub pushed: usize,
+    pub errors: Vec<String>,
+}
+
+/// Push open reminders to the CalDAV calendar in `[calendar]`; `all` also re-sends ones
+/// pushed before (to pick up edits), otherwise only new ones go
+#[tauri::command]
+pub async fn push_reminders_to_caldav(app_handle: tauri::AppHandle, all: Option<bool>) -> Result<CalDavPush, String> {
+    let calendar = crate::config::current().calendar.clone();
+    let url = calendar.caldav_url.clone().filter(|u| !u.trim().is_empty()).ok_or("Set caldav_url in [calendar] first")?;
+    let reminders = {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        open_reminders(&conn, !all.unwrap_or(false)).map_err(|e| e.to_string())?
+    };
+
+    let mut report = CalDavPush { pushed: 0, errors: Vec::new() };
+    for reminder in &reminders {
+        let event = match to_event(reminder, &calendar) {
+            Some(event) => event,
+            None => continue,
+        };
+        match crate::ical::put_caldav(&url, calendar.caldav_username.as_deref(), calendar.caldav_password.as_deref(), &event).await {
+            Ok(()) => {
+                let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+                set_stamp(&conn, &reminder.id, "pushed_at", &stamp(Utc::now()))?;
+                report.pushed += 1;
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+    tracing::info!("📅 Pushed {} reminders to CalDAV ({} failed)", report.pushed, report.errors.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    fn nine() -> NaiveTime {
+        NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+    }
+
+    /// Due date found in `text` on Friday 2026-10-16 at 10:00
+    fn due(text: &str) -> Option<(NaiveDateTime, bool)> {
+        extract_due(text, at(10, 16, 10, 0), nine()).map(|d| (d.at, d.all_day))
+    }
+
+    const TEXT: &str = "Remind me to review the Rust guide tonight";
+
+    fn now() -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(2 * 3600).unwrap().from_local_datetime(&at(10, 16, 10, 0)).unwrap()
+    }
+
+    fn calendar() -> CalendarConfig {
+        CalendarConfig { lead_minutes: 15, ..Default::default() }
+    }
+
+    fn reminder_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_reminder_tables(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_extract_due_times() {
+        assert_eq!(due("remind me to review this guide tonight"), Some((at(10, 16, 20, 0), false)));
+        assert_eq!(due("call the bank tomorrow at 5:30pm"), Some((at(10, 17, 17, 30), false)));
+        assert_eq!(due("this friday at 3"), Some((at(10, 16, 15, 0), false)));
+        assert_eq!(due("before 9am"), Some((at(10, 17, 9, 0), false)));
+    }
+
+    #[test]
+    fn test_extract_due_dates() {
+        assert_eq!(due("deadline 2026-11-02"), Some((at(11, 2, 9, 0), true)));
+        assert_eq!(due("submit the report by Monday"), Some((at(10, 19, 9, 0), true)));
+        assert_eq!(due("friday"), Some((at(10, 23, 9, 0), true)));
+        assert_eq!(due("Don't forget the talk on Nov 3rd at 14:00"), Some((at(11, 3, 14, 0), false)));
+        assert_eq!(due("due 3 March"), Some((NaiveDate::from_ymd_opt(2027, 3, 3).unwrap().and_time(nine()), true)));
+    }
+
+    #[test]
+    fn test_extract_due_relative() {
+        assert_eq!(due("remind me in 2 hours"), Some((at(10, 16, 12, 0), false)));
+        assert_eq!(due("in a week"), Some((at(10, 23, 9, 0), true)));
+        assert_eq!(due("by end of the month"), Some((at(10, 31, 9, 0), true)));
+    }
+
+    #[test]
+    fn test_extract_due_ignores_other_numbers() {
+        assert_eq!(due("maybe 3 of them need to be read"), None);
+        assert_eq!(due("I prefer tabs"), None);
+    }
+
+    #[test]
+    fn test_reminder_title() {
+        assert_eq!(reminder_title("Remind me to review the Rust guide tonight\nmore"), "Review the Rust guide tonight");
+        assert_eq!(reminder_title(&"x".repeat(200)).chars().count(), TITLE_CHARS);
+    }
+
+    #[test]
+    fn test_capture_stores_due_and_notify_times() {
+        let conn = reminder_db();
+        let reminder = capture(&conn, TEXT, "memory", now(), &calendar()).unwrap().unwrap();
+        assert_eq!((reminder.due_at.as_str(), reminder.notify_at.as_str()), ("2026-10-16T18:00:00Z", "2026-10-16T17:45:00Z"));
+    }
+
+    #[test]
+    fn test_capture_skips_repeats_and_non_reminders() {
+        // Stored once, and only what WAMA takes for a reminder and has a date
+        let (conn, now, calendar) = (reminder_db(), now(), calendar());
+        assert!(capture(&conn, TEXT, "memory", now, &calendar).unwrap().is_some());
+        assert!(capture(&conn, TEXT, "memory", now, &calendar).unwrap().is_none());
+        assert!(capture(&conn, "I learned about lifetimes tonight", "memory", now, &calendar).unwrap().is_none());
+        assert!(capture(&conn, "remind me to water the plants", "memory", now, &calendar).unwrap().is_none());
+        assert!(capture(&conn, "the deadline was 2026-01-05", "memory", now, &calendar).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_upcoming_and_announcements() {
+        let conn = reminder_db();
+        let reminder = capture(&conn, TEXT, "memory", now(), &calendar()).unwrap().unwrap();
+        assert_eq!(upcoming(&conn, "2026-10-16T23:59:59Z").unwrap().len(), 1);
+        assert!(upcoming(&conn, "2026-10-16T12:00:00Z").unwrap().is_empty());
+        assert!(to_announce(&conn, "2026-10-16T17:44:59Z").unwrap().is_empty());
+        assert_eq!(to_announce(&conn, "2026-10-16T17:45:00Z").unwrap().len(), 1);
+        set_stamp(&conn, &reminder.id, "notified_at", "2026-10-16T17:45:00Z").unwrap();
+        assert!(to_announce(&conn, "2026-10-16T18:00:00Z").unwrap().is_empty());
+        set_stamp(&conn, &reminder.id, "done_at", "2026-10-16T18:05:00Z").unwrap();
+        assert!(upcoming(&conn, "2026-10-17T00:00:00Z").unwrap().is_empty());
+        assert!(set_stamp(&conn, "missing", "done_at", "2026-10-16T18:05:00Z").is_err());
+    }
+}
@@ -1,8 +1,22 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 use ignore::gitignore::GitignoreBuilder;
 use anyhow::{Result, Context};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use crate::symbols;
+
+/// Insert or refresh a file's row, clearing its stale mark
+const UPSERT_FILE: &str = "INSERT INTO repo_files (repo_root, relative_path, size, modified, hash, indexed_at, stale)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0)
+     ON CONFLICT(repo_root, relative_path) DO UPDATE SET
+        size = excluded.size,
+        modified = excluded.modified,
+        hash = excluded.hash,
+        indexed_at = excluded.indexed_at,
+        stale = 0";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -11,6 +25,37 @@ pub struct FileInfo {
     pub extension: Option<String>,
     pub size: u64,
     pub is_text: bool,
+    #[serde(default)]
+    pub modified: i64,          // Unix seconds
+    #[serde(default)]
+    pub hash: Option<String>,   // SHA256 of content (text files only)
+}
+
+/// Summary of what an incremental index run actually had to touch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexDelta {
+    pub added: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// Progress payload emitted while indexing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexProgressEvent {
+    pub phase: String, // "scanning", "hashing", "done"
+    pub processed: usize,
+    pub total: usize,
+    pub current_file: Option<String>,
+}
+
+/// Cached per-file state from a previous index run
+struct CachedFile {
+    size: u64,
+    modified: i64,
+    hash: Option<String>,
+    /// The watcher saw the file change, so the cached state can't be trusted
+    stale: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +116,7 @@ impl RepoIndex {
                 .map(|s| s.to_string());
 
             let is_text = Self::is_likely_text_file(&extension, size);
+            let modified = Self::modified_secs(&metadata);
 
             // Add to index
             let file_info = FileInfo {
@@ -79,6 +125,8 @@ impl RepoIndex {
                 extension,
                 size,
                 is_text,
+                modified,
+                hash: None,
             };
 
             index.total_size += size;
@@ -89,6 +137,384 @@ impl RepoIndex {
         Ok(index)
     }
 
+    /// Index a repository, only re-hashing files whose size or mtime changed since the last run.
+    /// Per-file state is kept in the `repo_files` table of the index database.
+    pub fn index_directory_incremental<F>(
+        repo_path: &Path,
+        conn: &Connection,
+        on_progress: F,
+    ) -> Result<(Self, IndexDelta)>
+    where
+        F: Fn(IndexProgressEvent),
+    {
+        on_progress(IndexProgressEvent {
+            phase: "scanning".to_string(),
+            processed: 0,
+            total: 0,
+            current_file: None,
+        });
+
+        // Walking is cheap (metadata only); content is only read for changed files below
+        let mut index = Self::index_directory(repo_path)?;
+        let root_key = repo_path.to_string_lossy().to_string();
+
+        let mut cached = Self::load_cached_files(conn, &root_key)?;
+        let mut delta = IndexDelta::default();
+        let total = index.files.len();
+
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut upsert = tx.prepare(
+                UPSERT_FILE,
+            )?;
+            let now = chrono::Utc::now().to_rfc3339();
+
+            for (i, file) in index.files.iter_mut().enumerate() {
+                match cached.remove(&file.relative_path) {
+                    Some(prev) if !prev.stale && prev.size == file.size && prev.modified == file.modified => {
+                        file.hash = prev.hash;
+                        delta.unchanged += 1;
+                        continue;
+                    }
+                    Some(_) => delta.changed += 1,
+                    None => delta.added += 1,
+                }
+
+                // Re-read only files whose content may have changed
+                Self::read_contents(&tx, &root_key, file)?;
+
+                upsert.execute(params![
+                    root_key,
+                    file.relative_path,
+                    file.size as i64,
+                    file.modified,
+                    file.hash,
+                    now
+                ])?;
+
+                // Throttle events so large repos don't flood the frontend
+                if i % 50 == 0 {
+                    on_progress(IndexProgressEvent {
+                        phase: "hashing".to_string(),
+                        processed: i,
+                        total,
+                        current_file: Some(file.relative_path.clone()),
+                    });
+                }
+            }
+
+            // Anything left in the cache no longer exists on disk
+            let mut delete = tx.prepare("DELETE FROM repo_files WHERE repo_root = ?1 AND relative_path = ?2")?;
+            for relative_path in cached.keys() {
+                delete.execute(params![root_key, relative_path])?;
+                symbols::delete_file_symbols(&tx, &root_key, relative_path)?;
+                delta.removed += 1;
+            }
+
+            tx.execute(
+                "INSERT INTO repo_indexes (repo_root, total_files, total_size, indexed_at, stale)
+                 VALUES (?1, ?2, ?3, ?4, 0)
+                 ON CONFLICT(repo_root) DO UPDATE SET
+                    total_files = excluded.total_files,
+                    total_size = excluded.total_size,
+                    indexed_at = excluded.indexed_at,
+                    stale = 0",
+                params![root_key, total as i64, index.total_size as i64, now],
+            )?;
+        }
+        tx.commit()?;
+
+        on_progress(IndexProgressEvent {
+            phase: "done".to_string(),
+            processed: total,
+            total,
+            current_file: None,
+        });
+
+        index.total_files = index.files.len();
+        Ok((index, delta))
+    }
+
+    /// Hash a text file and re-extract its symbols
+    fn read_contents(conn: &Connection, root_key: &str, file: &mut FileInfo) -> Result<()> {
+        if !file.is_text {
+            return Ok(());
+        }
+        if let Ok(bytes) = std::fs::read(&file.path) {
+            file.hash = Some(Self::hash_bytes(&bytes));
+
+            if let Some(ext) = file.extension.as_deref().filter(|e| symbols::supports_extension(e)) {
+                let source = String::from_utf8_lossy(&bytes);
+                let file_symbols = symbols::extract_symbols(ext, &file.relative_path, &source);
+                symbols::replace_file_symbols(conn, root_key, &file.relative_path, &file_symbols)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Open (and create if needed) the index database
+    pub fn open_index_db(path: &Path) -> Result<Connection> {
+        let conn = Connection::open(path)?;
+
+        // `stale` marks rows of files the watcher saw change, re-read on the next load
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS repo_files (
+                repo_root TEXT NOT NULL,
+                relative_path TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                modified INTEGER NOT NULL,
+                hash TEXT,
+                indexed_at TEXT NOT NULL,
+                stale INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (repo_root, relative_path)
+            )",
+            [],
+        )?;
+        let has_stale: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('repo_files') WHERE name = 'stale'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_stale == 0 {
+            conn.execute("ALTER TABLE repo_files ADD COLUMN stale INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+
+        // Databases created before symbol extraction existed have file rows but no symbols;
+        // drop the cached rows once so the next run re-parses every file.
+        let has_symbols: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'symbols'",
+            [],
+            |row| row.get(0),
+        )?;
+        symbols::init_symbols_table(&conn)?;
+        if has_symbols == 0 {
+            conn.execute("DELETE FROM repo_files", [])?;
+        }
+
+        // One row per indexed repository; `stale` is set when the watcher sees changes
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS repo_indexes (
+                repo_root TEXT PRIMARY KEY,
+                total_files INTEGER NOT NULL,
+                total_size INTEGER NOT NULL,
+                indexed_at TEXT NOT NULL,
+                stale INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        Ok(conn)
+    }
+
+    /// Rebuild an index from the database without walking the filesystem; only the files the
+    /// watcher marked stale are read again (and dropped if they're gone).
+    /// With no `repo_root`, the most recently indexed repository is loaded.
+    pub fn load_persisted(conn: &Connection, repo_root: Option<&Path>) -> Result<Option<Self>> {
+        let root_key: Option<String> = match repo_root {
+            Some(root) => Some(root.to_string_lossy().to_string()),
+            None => conn
+                .query_row(
+                    "SELECT repo_root FROM repo_indexes ORDER BY indexed_at DESC LIMIT 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?,
+        };
+
+        let root_key = match root_key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+
+        let root_path = PathBuf::from(&root_key);
+        if !root_path.is_dir() {
+            return Ok(None);
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT relative_path, size, modified, hash, stale FROM repo_files
+             WHERE repo_root = ?1 ORDER BY relative_path",
+        )?;
+
+        let rows = stmt.query_map(params![root_key], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)? as u64,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, i64>(4)? != 0,
+            ))
+        })?;
+
+        let mut index = Self::new(root_path.clone());
+        let mut stale = Vec::new();
+        for row in rows {
+            let (relative_path, size, modified, hash, is_stale) = row?;
+            let extension = Path::new(&relative_path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|s| s.to_string());
+            let is_text = Self::is_likely_text_file(&extension, size);
+
+            let file = FileInfo {
+                path: root_path.join(&relative_path),
+                relative_path,
+                extension,
+                size,
+                is_text,
+                modified,
+                hash,
+            };
+            if is_stale {
+                stale.push(file);
+            } else {
+                index.files.push(file);
+            }
+        }
+        drop(stmt);
+
+        if !stale.is_empty() {
+            let refreshed = Self::refresh_stale(conn, &root_key, stale)?;
+            index.files.extend(refreshed);
+            index.files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+        }
+
+        if index.files.is_empty() {
+            return Ok(None);
+        }
+
+        index.total_size = index.files.iter().map(|f| f.size).sum();
+        index.total_files = index.files.len();
+        Ok(Some(index))
+    }
+
+    /// Re-read the files marked stale: changed ones are re-hashed and re-parsed, deleted ones
+    /// are forgotten. Returns the ones still there.
+    fn refresh_stale(conn: &Connection, root_key: &str, stale: Vec<FileInfo>) -> Result<Vec<FileInfo>> {
+        let tx = conn.unchecked_transaction()?;
+        let mut refreshed = Vec::new();
+        {
+            let mut upsert = tx.prepare(UPSERT_FILE)?;
+            let mut delete = tx.prepare("DELETE FROM repo_files WHERE repo_root = ?1 AND relative_path = ?2")?;
+            let now = chrono::Utc::now().to_rfc3339();
+            for mut file in stale {
+                let metadata = match std::fs::metadata(&file.path) {
+                    Ok(metadata) if metadata.is_file() => metadata,
+                    _ => {
+                        delete.execute(params![root_key, file.relative_path])?;
+                        symbols::delete_file_symbols(&tx, root_key, &file.relative_path)?;
+                        continue;
+                    }
+                };
+                file.size = metadata.len();
+                file.modified = Self::modified_secs(&metadata);
+                file.is_text = Self::is_likely_text_file(&file.extension, file.size);
+                file.hash = None;
+                Self::read_contents(&tx, root_key, &mut file)?;
+                upsert.execute(params![root_key, file.relative_path, file.size as i64, file.modified, file.hash, now])?;
+                refreshed.push(file);
+            }
+            // Every change the watcher reported is in the index again
+            tx.execute("UPDATE repo_indexes SET stale = 0 WHERE repo_root = ?1", params![root_key])?;
+        }
+        tx.commit()?;
+        Ok(refreshed)
+    }
+
+    /// Whether the watcher has seen changes since the repository was last indexed
+    pub fn is_stale(conn: &Connection, repo_root: &Path) -> Result<bool> {
+        let stale: Option<i64> = conn
+            .query_row(
+                "SELECT stale FROM repo_indexes WHERE repo_root = ?1",
+                params![repo_root.to_string_lossy()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(stale.map(|s| s != 0).unwrap_or(true))
+    }
+
+    /// Mark specific files stale so the next load or incremental run re-reads them; files the
+    /// index hasn't seen yet get a stale row too, so a load picks them up
+    pub fn invalidate_paths(conn: &Connection, repo_root: &Path, relative_paths: &[String]) -> Result<()> {
+        let root_key = repo_root.to_string_lossy().to_string();
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut mark = tx.prepare(
+                "INSERT INTO repo_files (repo_root, relative_path, size, modified, hash, indexed_at, stale)
+                 VALUES (?1, ?2, 0, 0, NULL, ?3, 1)
+                 ON CONFLICT(repo_root, relative_path) DO UPDATE SET stale = 1",
+            )?;
+            let now = chrono::Utc::now().to_rfc3339();
+            for relative_path in relative_paths {
+                mark.execute(params![root_key, relative_path, now])?;
+            }
+            tx.execute("UPDATE repo_indexes SET stale = 1 WHERE repo_root = ?1", params![root_key])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Drop everything persisted for a repository
+    pub fn invalidate(conn: &Connection, repo_root: &Path) -> Result<()> {
+        let root_key = repo_root.to_string_lossy().to_string();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM repo_files WHERE repo_root = ?1", params![root_key])?;
+        tx.execute("DELETE FROM symbols WHERE repo_root = ?1", params![root_key])?;
+        tx.execute("DELETE FROM repo_indexes WHERE repo_root = ?1", params![root_key])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Location of the index database: the app data dir when a handle is available,
+    /// otherwise the platform data dir under the bundle identifier.
+    pub fn index_db_path(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
+        let data_dir = match app_handle {
+            Some(handle) => handle.path_resolver().app_data_dir(),
+            None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+        }?;
+        Some(data_dir.join("repo_index.db"))
+    }
+
+    fn load_cached_files(conn: &Connection, root_key: &str) -> Result<HashMap<String, CachedFile>> {
+        let mut stmt = conn.prepare(
+            "SELECT relative_path, size, modified, hash, stale FROM repo_files WHERE repo_root = ?1",
+        )?;
+
+        let rows = stmt.query_map(params![root_key], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                CachedFile {
+                    size: row.get::<_, i64>(1)? as u64,
+                    modified: row.get(2)?,
+                    hash: row.get(3)?,
+                    stale: row.get::<_, i64>(4)? != 0,
+                },
+            ))
+        })?;
+
+        let mut cached = HashMap::new();
+        for row in rows {
+            let (path, file) = row?;
+            cached.insert(path, file);
+        }
+        Ok(cached)
+    }
+
+    /// SHA256 of a file's content as lowercase hex
+    fn hash_bytes(bytes: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(bytes);
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn modified_secs(metadata: &std::fs::Metadata) -> i64 {
+        metadata.modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0)
+    }
+
     /// Build gitignore matcher for the repository
     fn build_gitignore(repo_path: &Path) -> Result<ignore::gitignore::Gitignore> {
         let mut builder = GitignoreBuilder::new(repo_path);
@@ -180,4 +606,91 @@ mod tests {
         assert!(!RepoIndex::is_likely_text_file(&Some("exe".to_string()), 1000));
         assert!(!RepoIndex::is_likely_text_file(&Some("rs".to_string()), 3_000_000)); // > 2MB
     }
+
+    #[test]
+    fn test_incremental_index_only_rehashes_changed_files() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("a.rs"), "fn a() {}").unwrap();
+        std::fs::write(repo.path().join("b.md"), "# B").unwrap();
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let conn = RepoIndex::open_index_db(db_file.path()).unwrap();
+
+        let (_, first) = RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+        assert_eq!(first.added, 2);
+
+        std::fs::remove_file(repo.path().join("b.md")).unwrap();
+        let (index, second) = RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+        assert_eq!(second.unchanged, 1);
+        assert_eq!(second.removed, 1);
+        assert!(index.files[0].hash.is_some());
+    }
+
+    #[test]
+    fn test_persisted_index_reloads_and_invalidates() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.path().join("lib.rs"), "pub fn lib() {}").unwrap();
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let conn = RepoIndex::open_index_db(db_file.path()).unwrap();
+        RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+
+        let restored = RepoIndex::load_persisted(&conn, None).unwrap().expect("index should persist");
+        assert_eq!(restored.total_files, 2);
+        assert!(!RepoIndex::is_stale(&conn, repo.path()).unwrap());
+
+        RepoIndex::invalidate_paths(&conn, repo.path(), &["lib.rs".to_string()]).unwrap();
+        assert!(RepoIndex::is_stale(&conn, repo.path()).unwrap());
+        let restored = RepoIndex::load_persisted(&conn, Some(repo.path())).unwrap().unwrap();
+        assert_eq!(restored.total_files, 2);
+        assert!(!RepoIndex::is_stale(&conn, repo.path()).unwrap());
+
+        RepoIndex::invalidate(&conn, repo.path()).unwrap();
+        assert!(RepoIndex::load_persisted(&conn, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_rereads_stale_files() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.path().join("old.rs"), "fn old() {}").unwrap();
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let conn = RepoIndex::open_index_db(db_file.path()).unwrap();
+        RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+        let hash_before = RepoIndex::load_persisted(&conn, None).unwrap().unwrap().files[0].hash.clone();
+
+        // Changed, deleted and created while the app was running
+        std::fs::write(repo.path().join("main.rs"), "fn main() { run(); }").unwrap();
+        std::fs::remove_file(repo.path().join("old.rs")).unwrap();
+        std::fs::write(repo.path().join("new.rs"), "fn new() {}").unwrap();
+        let changed = ["main.rs", "old.rs", "new.rs"].map(String::from);
+        RepoIndex::invalidate_paths(&conn, repo.path(), &changed).unwrap();
+
+        let restored = RepoIndex::load_persisted(&conn, None).unwrap().unwrap();
+        let paths: Vec<_> = restored.files.iter().map(|f| f.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["main.rs", "new.rs"]);
+        assert_ne!(restored.files[0].hash, hash_before);
+        assert_eq!(restored.total_size, "fn main() { run(); }".len() as u64 + "fn new() {}".len() as u64);
+
+        // The refresh was written back, so the next incremental run has nothing to do
+        let (_, delta) = RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+        assert_eq!((delta.unchanged, delta.changed, delta.added, delta.removed), (2, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_stale_rows_are_rehashed_by_incremental_runs() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("a.rs"), "fn a() {}").unwrap();
+
+        let db_file = tempfile::NamedTempFile::new().unwrap();
+        let conn = RepoIndex::open_index_db(db_file.path()).unwrap();
+        RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+
+        // Same size and (to the second) mtime, so only the stale mark reveals the change
+        RepoIndex::invalidate_paths(&conn, repo.path(), &["a.rs".to_string()]).unwrap();
+        let (_, delta) = RepoIndex::index_directory_incremental(repo.path(), &conn, |_| {}).unwrap();
+        assert_eq!((delta.changed, delta.unchanged), (1, 0));
+    }
 }
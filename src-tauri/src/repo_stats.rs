@@ -0,0 +1,152 @@
+/// Structural overview of an indexed repository: languages, lines of code,
+/// largest files and recently modified hot spots.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::repo_indexer::{FileInfo, RepoIndex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStat {
+    pub relative_path: String,
+    pub language: String,
+    pub lines: usize,
+    pub size: u64,
+    pub modified: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoStats {
+    pub root_path: String,
+    pub total_files: usize,
+    pub total_lines: usize,
+    pub total_size: u64,
+    pub languages: Vec<LanguageStats>,   // Sorted by lines, descending
+    pub largest_files: Vec<FileStat>,    // By lines of code
+    pub recently_modified: Vec<FileStat>,
+}
+
+/// Human-readable language name for a file extension
+pub fn language_for_extension(extension: Option<&str>) -> &'static str {
+    match extension.map(|e| e.to_lowercase()).as_deref() {
+        Some("rs") => "Rust",
+        Some("ts") | Some("tsx") | Some("mts") | Some("cts") => "TypeScript",
+        Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => "JavaScript",
+        Some("py") => "Python",
+        Some("go") => "Go",
+        Some("java") => "Java",
+        Some("c") | Some("h") => "C",
+        Some("cpp") | Some("hpp") | Some("cc") => "C++",
+        Some("cs") => "C#",
+        Some("rb") => "Ruby",
+        Some("php") => "PHP",
+        Some("html") => "HTML",
+        Some("css") | Some("scss") => "CSS",
+        Some("vue") => "Vue",
+        Some("svelte") => "Svelte",
+        Some("astro") => "Astro",
+        Some("md") => "Markdown",
+        Some("json") => "JSON",
+        Some("toml") => "TOML",
+        Some("yaml") | Some("yml") => "YAML",
+        Some("sql") => "SQL",
+        Some("sh") | Some("bash") | Some("zsh") | Some("fish") => "Shell",
+        Some(_) => "Other",
+        None => "Other",
+    }
+}
+
+fn count_lines(file: &FileInfo) -> usize {
+    if !file.is_text {
+        return 0;
+    }
+    std::fs::read(&file.path)
+        .map(|bytes| {
+            let text = String::from_utf8_lossy(&bytes);
+            text.lines().filter(|l| !l.trim().is_empty()).count()
+        })
+        .unwrap_or(0)
+}
+
+/// Compute stats for an index. Reads text files to count non-blank lines.
+pub fn compute_repo_stats(index: &RepoIndex, top_n: usize) -> RepoStats {
+    let mut by_language: HashMap<&'static str, LanguageStats> = HashMap::new();
+    let mut file_stats = Vec::with_capacity(index.files.len());
+
+    for file in &index.files {
+        let language = language_for_extension(file.extension.as_deref());
+        let lines = count_lines(file);
+
+        let entry = by_language.entry(language).or_insert_with(|| LanguageStats {
+            language: language.to_string(),
+            files: 0,
+            lines: 0,
+            bytes: 0,
+        });
+        entry.files += 1;
+        entry.lines += lines;
+        entry.bytes += file.size;
+
+        file_stats.push(FileStat {
+            relative_path: file.relative_path.clone(),
+            language: language.to_string(),
+            lines,
+            size: file.size,
+            modified: file.modified,
+        });
+    }
+
+    let mut languages: Vec<LanguageStats> = by_language.into_values().collect();
+    languages.sort_by(|a, b| b.lines.cmp(&a.lines).then(b.files.cmp(&a.files)));
+
+    let total_lines = file_stats.iter().map(|f| f.lines).sum();
+
+    let mut largest_files = file_stats.clone();
+    largest_files.sort_by(|a, b| b.lines.cmp(&a.lines).then(b.size.cmp(&a.size)));
+    largest_files.truncate(top_n);
+
+    let mut recently_modified = file_stats;
+    recently_modified.sort_by(|a, b| b.modified.cmp(&a.modified));
+    recently_modified.truncate(top_n);
+
+    RepoStats {
+        root_path: index.root_path.to_string_lossy().to_string(),
+        total_files: index.total_files,
+        total_lines,
+        total_size: index.total_size,
+        languages,
+        largest_files,
+        recently_modified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_repo_stats_groups_languages() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::write(repo.path().join("main.rs"), "fn main() {\n\n    run();\n}\n").unwrap();
+        std::fs::write(repo.path().join("lib.rs"), "pub fn run() {}\n").unwrap();
+        std::fs::write(repo.path().join("app.ts"), "export const x = 1;\n").unwrap();
+
+        let index = RepoIndex::index_directory(repo.path()).unwrap();
+        let stats = compute_repo_stats(&index, 2);
+
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.total_lines, 5);
+        assert_eq!(stats.languages[0].language, "Rust");
+        assert_eq!(stats.languages[0].files, 2);
+        assert_eq!(stats.largest_files.len(), 2);
+        assert_eq!(stats.largest_files[0].relative_path, "main.rs");
+    }
+}
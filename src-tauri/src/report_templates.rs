@@ -0,0 +1,155 @@
+/// Output templates for deep research reports
+///
+/// A template adds structure instructions to the synthesizer prompt and names the
+/// sections the final markdown must have; `missing_sections` drives the revision pass
+/// when the synthesizer leaves one out. `Standard` keeps the free-form report.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportTemplate {
+    #[default]
+    Standard,
+    ExecutiveBrief,
+    LiteratureReview,
+    ComparisonMatrix,
+    Tutorial,
+}
+
+impl ReportTemplate {
+    pub const ALL: [ReportTemplate; 5] = [
+        ReportTemplate::Standard,
+        ReportTemplate::ExecutiveBrief,
+        ReportTemplate::LiteratureReview,
+        ReportTemplate::ComparisonMatrix,
+        ReportTemplate::Tutorial,
+    ];
+
+    /// Parse a template name; accepts "executive_brief", "Executive Brief", "executive-brief"
+    pub fn from_name(name: &str) -> Option<Self> {
+        let key = name.trim().to_lowercase().replace([' ', '-'], "_");
+        Self::ALL.into_iter().find(|t| t.key() == key)
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ReportTemplate::Standard => "standard",
+            ReportTemplate::ExecutiveBrief => "executive_brief",
+            ReportTemplate::LiteratureReview => "literature_review",
+            ReportTemplate::ComparisonMatrix => "comparison_matrix",
+            ReportTemplate::Tutorial => "tutorial",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportTemplate::Standard => "Standard report",
+            ReportTemplate::ExecutiveBrief => "Executive brief",
+            ReportTemplate::LiteratureReview => "Literature review",
+            ReportTemplate::ComparisonMatrix => "Comparison matrix",
+            ReportTemplate::Tutorial => "Tutorial",
+        }
+    }
+
+    /// `##` sections the report must contain, in order
+    pub fn sections(&self) -> &'static [&'static str] {
+        match self {
+            ReportTemplate::Standard => &[],
+            ReportTemplate::ExecutiveBrief => &["Summary", "Key Findings", "Implications", "Recommendations"],
+            ReportTemplate::LiteratureReview => &["Introduction", "Themes", "Points of Disagreement", "Gaps and Open Questions", "Conclusion"],
+            ReportTemplate::ComparisonMatrix => &["Overview", "Comparison Matrix", "Analysis", "Recommendation"],
+            ReportTemplate::Tutorial => &["Overview", "Prerequisites", "Steps", "Common Pitfalls", "Next Steps"],
+        }
+    }
+
+    fn guidance(&self) -> &'static str {
+        match self {
+            ReportTemplate::Standard => "",
+            ReportTemplate::ExecutiveBrief => "Write for a busy decision maker: at most about 800 words. \
+                Open the Summary with the bottom line in two or three sentences. Use short bullet points under \
+                Key Findings, and make each recommendation concrete and actionable.",
+            ReportTemplate::LiteratureReview => "Review the sources rather than the topic: group them by theme under \
+                Themes (one ### subsection per theme), say which sources agree, and note methods and evidence quality. \
+                Points of Disagreement covers where sources conflict and why.",
+            ReportTemplate::ComparisonMatrix => "Compare the options the research covers. Comparison Matrix must be a \
+                markdown table with one row per option and one column per criterion, with short cited cells. \
+                Analysis explains the trade-offs; Recommendation says which option fits which situation.",
+            ReportTemplate::Tutorial => "Teach the reader to do it themselves. Steps is a numbered list where each step \
+                says what to do and why, with code or commands in fenced blocks where relevant. Keep the tone practical.",
+        }
+    }
+
+    /// Structure instructions added to the synthesizer prompt
+    pub fn instructions(&self) -> String {
+        if *self == ReportTemplate::Standard {
+            return String::new();
+        }
+        let outline: Vec<String> = self.sections().iter().map(|s| format!("## {}", s)).collect();
+        format!(
+            "Format the report as a {}. Start with a `# ` title, then use exactly these sections in this order:\n{}\n{}",
+            self.label().to_lowercase(),
+            outline.join("\n"),
+            self.guidance()
+        )
+    }
+
+    /// Required parts of the template the report doesn't have
+    pub fn missing_sections(&self, report: &str) -> Vec<String> {
+        let headings: Vec<String> = crate::kb_index::split_sections(report)
+            .into_iter()
+            .map(|s| s.heading.to_lowercase())
+            .collect();
+        let mut missing: Vec<String> = self
+            .sections()
+            .iter()
+            .filter(|s| !headings.iter().any(|h| h.contains(&s.to_lowercase())))
+            .map(|s| s.to_string())
+            .collect();
+
+        let has_table = report.lines().any(|l| {
+            let l = l.trim();
+            l.starts_with('|') && l.contains("---")
+        });
+        if *self == ReportTemplate::ComparisonMatrix && !has_table && !missing.iter().any(|s| s == "Comparison Matrix") {
+            missing.push("Comparison Matrix (as a markdown table)".to_string());
+        }
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = "# Databases\n\n## Overview\n\nText.\n\n## Comparison Matrix\n\nPostgres is relational.\n\n## Analysis\n\nText.";
+
+    #[test]
+    fn test_template_names() {
+        assert_eq!(ReportTemplate::from_name("Executive Brief"), Some(ReportTemplate::ExecutiveBrief));
+        assert_eq!(ReportTemplate::from_name("comparison-matrix"), Some(ReportTemplate::ComparisonMatrix));
+        assert_eq!(ReportTemplate::from_name("haiku"), None);
+    }
+
+    #[test]
+    fn test_template_instructions() {
+        assert!(ReportTemplate::Standard.instructions().is_empty());
+        assert!(ReportTemplate::Tutorial.instructions().contains("## Prerequisites\n## Steps"));
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        assert_eq!(
+            ReportTemplate::ComparisonMatrix.missing_sections(REPORT),
+            vec!["Recommendation".to_string(), "Comparison Matrix (as a markdown table)".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_complete_report_has_no_missing_sections() {
+        let with_table = REPORT.replace("Postgres is relational.", "| DB | Model |\n|---|---|\n| Postgres | Relational |")
+            + "\n\n## Recommendations\n\nUse Postgres.";
+        assert!(ReportTemplate::ComparisonMatrix.missing_sections(&with_table).is_empty());
+        assert!(ReportTemplate::Standard.missing_sections("no headings").is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+/// Research run history
+///
+/// Every deep research run is recorded in knowledge_companion.db (topic, sub-topics, papers,
+/// sources, report and usage) so earlier research can be listed, opened, re-run and
+/// compared. `run.json` and the checkpoints in the run folder stay the source of truth for
+/// resuming; this table is the searchable record of what each run produced.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use regex::Regex;
+use crate::citations::Bibliography;
+use crate::deep_research::{ResearchConfig, ResearchRun, ResearchUsage, SearchResult};
+
+lazy_static::lazy_static! {
+    static ref FOOTNOTE_REF: Regex = Regex::new(r"\[\^\d+\]").unwrap();
+}
+
+/// Sections added by the pipeline rather than written from the research
+pub const GENERATED_SECTIONS: [&str; 2] = ["References", "Credibility Notes"];
+
+pub fn init_research_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS research_runs (
+            run_id TEXT PRIMARY KEY,
+            topic TEXT NOT NULL,
+            sub_topics TEXT NOT NULL DEFAULT '[]',
+            papers TEXT NOT NULL DEFAULT '[]',
+            config TEXT NOT NULL,
+            status TEXT NOT NULL,
+            rerun_of TEXT,
+            sources TEXT,
+            report TEXT,
+            bibliography TEXT,
+            usage TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_research_runs_topic ON research_runs(topic)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// One row of the run history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResearchRunSummary {
+    pub run_id: String,
+    pub topic: String,
+    pub sub_topics: Vec<String>,
+    pub status: String,
+    pub rerun_of: Option<String>,
+    pub source_count: usize,
+    pub has_report: bool,
+    pub usage: Option<ResearchUsage>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A run with everything it produced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResearchRunRecord {
+    pub run_id: String,
+    pub topic: String,
+    pub sub_topics: Vec<String>,
+    pub papers: Vec<String>,
+    pub config: ResearchConfig,
+    pub status: String,
+    pub rerun_of: Option<String>,
+    pub sources: Vec<SearchResult>,
+    pub report: Option<String>,
+    pub bibliography: Option<Bibliography>,
+    pub usage: Option<ResearchUsage>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ResearchRunRecord {
+    /// A fresh run of the same research
+    pub fn to_rerun(&self) -> ResearchRun {
+        let mut run = ResearchRun::new(self.topic.clone(), self.sub_topics.clone(), self.config.clone());
+        run.rerun_of = Some(self.run_id.clone());
+        run.papers = self.papers.clone();
+        run
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceRef {
+    pub title: String,
+    pub url: String,
+}
+
+/// What changed between two runs of a topic
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResearchDiff {
+    pub old_run_id: String,
+    pub new_run_id: String,
+    pub added_sources: Vec<SourceRef>,
+    pub removed_sources: Vec<SourceRef>,
+    pub kept_sources: usize,
+    pub added_sections: Vec<String>,
+    pub removed_sections: Vec<String>,
+    pub changed_sections: Vec<String>,
+    pub unchanged_sections: usize,
+}
+
+/// Record a run's current state; results already stored are kept
+pub fn upsert_run(conn: &Connection, run: &ResearchRun) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO research_runs (run_id, topic, sub_topics, papers, config, status, rerun_of, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(run_id) DO UPDATE SET status = excluded.status, updated_at = excluded.updated_at",
+        params![
+            run.run_id,
+            run.topic,
+            serde_json::to_string(&run.sub_topics).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&run.papers).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&run.config).unwrap_or_default(),
+            run.status,
+            run.rerun_of,
+            run.created_at,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Store what a finished run produced
+pub fn store_results(
+    conn: &Connection,
+    run_id: &str,
+    sources: &[SearchResult],
+    report: &str,
+    bibliography: &Bibliography,
+    usage: &ResearchUsage,
+) -> rusqlite::Result<()> {
+    let to_json = |value: serde_json::Result<String>| value.unwrap_or_default();
+    conn.execute(
+        "UPDATE research_runs SET sources = ?2, report = ?3, bibliography = ?4, usage = ?5, updated_at = ?6
+         WHERE run_id = ?1",
+        params![
+            run_id,
+            to_json(serde_json::to_string(sources)),
+            report,
+            to_json(serde_json::to_string(bibliography)),
+            to_json(serde_json::to_string(usage)),
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(text: Option<String>) -> Option<T> {
+    text.and_then(|t| serde_json::from_str(&t).ok())
+}
+
+/// Runs newest first, optionally only those whose topic contains `query`
+pub fn list_runs(conn: &Connection, query: Option<&str>, limit: usize) -> rusqlite::Result<Vec<ResearchRunSummary>> {
+    let pattern = format!("%{}%", query.unwrap_or("").trim());
+    let mut stmt = conn.prepare(
+        "SELECT run_id, topic, sub_topics, status, rerun_of, sources, report IS NOT NULL, usage, created_at, updated_at
+         FROM research_runs WHERE topic LIKE ?1 ORDER BY created_at DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![pattern, limit as i64], |row| {
+        Ok(ResearchRunSummary {
+            run_id: row.get(0)?,
+            topic: row.get(1)?,
+            sub_topics: parse_json(row.get(2)?).unwrap_or_default(),
+            status: row.get(3)?,
+            rerun_of: row.get(4)?,
+            source_count: parse_json::<Vec<serde_json::Value>>(row.get(5)?).map_or(0, |s| s.len()),
+            has_report: row.get(6)?,
+            usage: parse_json(row.get(7)?),
+            created_at: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn get_run(conn: &Connection, run_id: &str) -> rusqlite::Result<Option<ResearchRunRecord>> {
+    conn.query_row(
+        "SELECT run_id, topic, sub_topics, papers, config, status, rerun_of, sources, report, bibliography, usage, created_at, updated_at
+         FROM research_runs WHERE run_id = ?1",
+        params![run_id],
+        |row| {
+            Ok(ResearchRunRecord {
+                run_id: row.get(0)?,
+                topic: row.get(1)?,
+                sub_topics: parse_json(row.get(2)?).unwrap_or_default(),
+                papers: parse_json(row.get(3)?).unwrap_or_default(),
+                config: parse_json(row.get(4)?).unwrap_or_default(),
+                status: row.get(5)?,
+                rerun_of: row.get(6)?,
+                sources: parse_json(row.get(7)?).unwrap_or_default(),
+                report: row.get(8)?,
+                bibliography: parse_json(row.get(9)?),
+                usage: parse_json(row.get(10)?),
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Report sections by heading, with footnote markers dropped since numbering differs between runs
+fn report_sections(report: &str) -> HashMap<String, String> {
+    crate::kb_index::split_sections(report)
+        .into_iter()
+        .filter(|s| !GENERATED_SECTIONS.contains(&s.heading.as_str()))
+        .map(|s| {
+            let body = s.content.lines().skip(if s.heading.is_empty() { 0 } else { 1 }).collect::<Vec<_>>().join("\n");
+            let body = FOOTNOTE_REF.replace_all(&body, "");
+            (s.heading, body.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .filter(|(heading, body)| !heading.is_empty() || !body.is_empty())
+        .collect()
+}
+
+pub fn diff_runs(old: &ResearchRunRecord, new: &ResearchRunRecord) -> ResearchDiff {
+    let old_urls: HashSet<&str> = old.sources.iter().map(|s| s.url.as_str()).collect();
+    let new_urls: HashSet<&str> = new.sources.iter().map(|s| s.url.as_str()).collect();
+    let source_ref = |s: &SearchResult| SourceRef { title: s.title.clone(), url: s.url.clone() };
+
+    let old_sections = report_sections(old.report.as_deref().unwrap_or(""));
+    let new_sections = report_sections(new.report.as_deref().unwrap_or(""));
+    let in_order = |report: &Option<String>| -> Vec<String> {
+        crate::kb_index::split_sections(report.as_deref().unwrap_or("")).into_iter().map(|s| s.heading).collect()
+    };
+
+    let mut diff = ResearchDiff {
+        old_run_id: old.run_id.clone(),
+        new_run_id: new.run_id.clone(),
+        added_sources: new.sources.iter().filter(|s| !old_urls.contains(s.url.as_str())).map(source_ref).collect(),
+        removed_sources: old.sources.iter().filter(|s| !new_urls.contains(s.url.as_str())).map(source_ref).collect(),
+        kept_sources: new_urls.intersection(&old_urls).count(),
+        ..Default::default()
+    };
+    for heading in in_order(&new.report) {
+        match (old_sections.get(&heading), new_sections.get(&heading)) {
+            (None, Some(_)) => diff.added_sections.push(heading),
+            (Some(before), Some(after)) if before != after => diff.changed_sections.push(heading),
+            (Some(_), Some(_)) => diff.unchanged_sections += 1,
+            _ => {}
+        }
+    }
+    diff.removed_sections = in_order(&old.report)
+        .into_iter()
+        .filter(|h| old_sections.contains_key(h) && !new_sections.contains_key(h))
+        .collect();
+    diff
+}
+
+pub fn load_run(conn: &Connection, run_id: &str) -> Result<ResearchRunRecord, String> {
+    get_run(conn, run_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Research run '{}' not found", run_id))
+}
+
+/// Research run history, newest first
+#[tauri::command]
+pub fn list_research_runs(app_handle: tauri::AppHandle, query: Option<String>, limit: Option<usize>) -> Result<Vec<ResearchRunSummary>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_runs(&conn, query.as_deref(), limit.unwrap_or(100)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_research_run(app_handle: tauri::AppHandle, run_id: String) -> Result<ResearchRunRecord, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    load_run(&conn, &run_id)
+}
+
+/// What changed from `old_run_id` to `new_run_id`
+#[tauri::command]
+pub fn compare_research_runs(app_handle: tauri::AppHandle, old_run_id: String, new_run_id: String) -> Result<ResearchDiff, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    Ok(diff_runs(&load_run(&conn, &old_run_id)?, &load_run(&conn, &new_run_id)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(url: &str) -> SearchResult {
+        SearchResult { title: url.to_string(), url: url.to_string(), content: String::new(), score: 0.5, published_date: None }
+    }
+
+    /// A finished run on Rust with two sources, as read back from the database
+    fn finished_run(conn: &Connection) -> ResearchRunRecord {
+        init_research_tables(conn).unwrap();
+        let mut run = ResearchRun::new("Rust".to_string(), vec![], ResearchConfig::default());
+        upsert_run(conn, &run).unwrap();
+        store_results(
+            conn,
+            &run.run_id,
+            &[source("https://a.dev"), source("https://b.dev")],
+            "# Ownership\n\nOne owner[^1].\n\n# Async\n\nFutures are lazy[^2].\n\n## References\n\n[^1]: a",
+            &Bibliography::default(),
+            &ResearchUsage::default(),
+        )
+        .unwrap();
+        run.status = "complete".to_string();
+        upsert_run(conn, &run).unwrap();
+        get_run(conn, &run.run_id).unwrap().unwrap()
+    }
+
+    /// Reruns `old`, which drops a source, finds a new one and adds a section
+    fn record_rerun(conn: &Connection, old: &ResearchRunRecord) -> ResearchRunRecord {
+        let rerun = old.to_rerun();
+        upsert_run(conn, &rerun).unwrap();
+        store_results(
+            conn,
+            &rerun.run_id,
+            &[source("https://b.dev"), source("https://c.dev")],
+            "# Ownership\n\nOne owner[^2].\n\n# Async\n\nFutures are lazy and need an executor[^1].\n\n# Pinning\n\nPin[^1].",
+            &Bibliography::default(),
+            &ResearchUsage::default(),
+        )
+        .unwrap();
+        get_run(conn, &rerun.run_id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        let old = finished_run(&conn);
+        assert_eq!(old.status, "complete");
+        assert_eq!(old.sources.len(), 2);
+    }
+
+    #[test]
+    fn test_list_runs_links_reruns() {
+        let conn = Connection::open_in_memory().unwrap();
+        let run = finished_run(&conn);
+        let rerun = record_rerun(&conn, &run);
+
+        let runs = list_runs(&conn, Some("rus"), 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs.iter().find(|r| r.run_id == rerun.run_id).unwrap().rerun_of.as_deref(), Some(run.run_id.as_str()));
+    }
+
+    #[test]
+    fn test_diff_runs() {
+        let conn = Connection::open_in_memory().unwrap();
+        let old = finished_run(&conn);
+        let diff = diff_runs(&old, &record_rerun(&conn, &old));
+        assert_eq!(diff.added_sources.iter().map(|s| s.url.as_str()).collect::<Vec<_>>(), vec!["https://c.dev"]);
+        assert_eq!(diff.removed_sources.iter().map(|s| s.url.as_str()).collect::<Vec<_>>(), vec!["https://a.dev"]);
+        assert_eq!(diff.kept_sources, 1);
+        assert_eq!(diff.added_sections, vec!["Pinning".to_string()]);
+        assert_eq!(diff.changed_sections, vec!["Async".to_string()]);
+        assert_eq!(diff.unchanged_sections, 1);
+        assert!(diff.removed_sections.is_empty());
+    }
+}
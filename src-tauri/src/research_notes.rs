@@ -0,0 +1,214 @@
+/// Saving finished deep research reports into the knowledge base
+///
+/// Each report is written to `research/<slug>.md` with frontmatter (topic, date, run id,
+/// sources), so it shows up in search and the note graph like any other note. Its
+/// sections are also stored in the TKG as findings, each with the sources it cites.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use crate::citations::Bibliography;
+
+pub const RESEARCH_FOLDER: &str = "research";
+/// Characters of a report section kept per TKG finding
+const MAX_FINDING_CHARS: usize = 2_000;
+/// Shorter sections (intros, one-line summaries) aren't stored as findings
+const MIN_FINDING_CHARS: usize = 200;
+
+lazy_static::lazy_static! {
+    static ref FOOTNOTE_MARKER: Regex = Regex::new(r"\[\^(\d+)\]").unwrap();
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedReport {
+    /// Note path relative to the knowledge base, when it was written
+    pub path: Option<String>,
+    pub findings_stored: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ReportFrontmatter<'a> {
+    title: &'a str,
+    created: String,
+    tags: Vec<&'a str>,
+    topic: &'a str,
+    run_id: &'a str,
+    sources: Vec<&'a str>,
+}
+
+pub fn slugify(topic: &str) -> String {
+    let slug = topic
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(80).collect();
+    if slug.is_empty() { "research".to_string() } else { slug.trim_end_matches('-').to_string() }
+}
+
+/// Where the report on `topic` is saved; reruns of a topic replace the earlier note
+/// (note_versions keeps the previous copy)
+pub fn report_path(kb_root: &Path, topic: &str) -> PathBuf {
+    kb_root.join(RESEARCH_FOLDER).join(format!("{}.md", slugify(topic)))
+}
+
+/// The report as a note, with frontmatter listing the sources it was built from
+pub fn render_note(topic: &str, run_id: &str, date: &str, report: &str, bib: &Bibliography) -> String {
+    let frontmatter = ReportFrontmatter {
+        title: topic,
+        created: date.to_string(),
+        tags: vec!["research"],
+        topic,
+        run_id,
+        sources: bib.entries.iter().map(|e| e.url.as_str()).collect(),
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    let body = if report.trim_start().starts_with("# ") {
+        report.trim().to_string()
+    } else {
+        format!("# {}\n\n{}", topic, report.trim())
+    };
+    format!("---\n{}---\n\n{}\n", yaml, body)
+}
+
+/// Report sections worth remembering, with footnote markers replaced by the cited sources
+pub fn findings(topic: &str, report: &str, bib: &Bibliography) -> Vec<String> {
+    crate::kb_index::split_sections(report)
+        .into_iter()
+        .filter(|s| !crate::research_history::GENERATED_SECTIONS.contains(&s.heading.as_str()))
+        .filter(|s| s.content.len() >= MIN_FINDING_CHARS)
+        .map(|section| {
+            let mut cited: Vec<usize> = FOOTNOTE_MARKER
+                .captures_iter(&section.content)
+                .filter_map(|c| c[1].parse().ok())
+                .filter(|n| bib.contains(*n))
+                .collect();
+            cited.sort_unstable();
+            cited.dedup();
+
+            let text = FOOTNOTE_MARKER.replace_all(&section.content, "");
+            let text: String = text.trim_start_matches('#').trim().chars().take(MAX_FINDING_CHARS).collect();
+            let mut finding = format!("Research on {}: {}", topic, text);
+            if !cited.is_empty() {
+                let sources: Vec<&str> = cited.iter().map(|n| bib.entries[n - 1].url.as_str()).collect();
+                finding.push_str(&format!("\n\n(Sources: {})", sources.join(", ")));
+            }
+            finding
+        })
+        .collect()
+}
+
+/// Write the report into the knowledge base and store its findings in the TKG. Failures
+/// are collected rather than returned so the research result itself is never lost.
+pub async fn save_report(
+    app_handle: Option<&tauri::AppHandle>,
+    topic: &str,
+    run_id: &str,
+    report: &str,
+    bib: &Bibliography,
+    user_id: &str,
+) -> SavedReport {
+    let mut saved = SavedReport { path: None, findings_stored: 0, errors: Vec::new() };
+
+    match crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path() {
+        Ok(kb_root) => match write_note(app_handle, &kb_root, topic, run_id, report, bib) {
+            Ok(path) => {
+                tracing::info!("📝 Saved research report to {}", path);
+                saved.path = Some(path);
+            }
+            Err(e) => saved.errors.push(format!("Failed to save report note: {}", e)),
+        },
+        Err(e) => saved.errors.push(e),
+    }
+
+    for finding in findings(topic, report, bib) {
+        match crate::tkg::tkg_store_knowledge(finding, "FACT".to_string(), 0.6, user_id.to_string()).await {
+            Ok(_) => saved.findings_stored += 1,
+            // Usually the TKG isn't configured; don't try the remaining findings
+            Err(e) => {
+                saved.errors.push(format!("TKG: {}", e));
+                break;
+            }
+        }
+    }
+    if saved.findings_stored > 0 {
+        tracing::info!("🧠 Stored {} research finding(s) in TKG", saved.findings_stored);
+    }
+
+    saved
+}
+
+fn write_note(
+    app_handle: Option<&tauri::AppHandle>,
+    kb_root: &Path,
+    topic: &str,
+    run_id: &str,
+    report: &str,
+    bib: &Bibliography,
+) -> Result<String, String> {
+    let path = report_path(kb_root, topic);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    crate::note_versions::record_before_write(app_handle, kb_root, &path, "research");
+    std::fs::write(&path, render_note(topic, run_id, &date, report, bib)).map_err(|e| e.to_string())?;
+
+    let key = crate::kb_index::path_key(kb_root, &path);
+    if let Some(handle) = app_handle {
+        crate::kb_index::apply_watch_changes(handle, &[path.clone()], None);
+        crate::events::emit(handle, "content-changed", serde_json::json!({
+            "source": kb_root.join(RESEARCH_FOLDER),
+            "paths": [&key],
+        }));
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_bib() -> Bibliography {
+        let mut bib = Bibliography::default();
+        bib.add("The Rust Book", "https://doc.rust-lang.org/book/");
+        bib.add("Rustonomicon", "https://doc.rust-lang.org/nomicon/");
+        bib
+    }
+
+    fn sample_report() -> String {
+        let filler = "Ownership rules are checked at compile time. ".repeat(6);
+        format!(
+            "## Ownership\n\nEach value has one owner[^1][^2]. {filler}\n\n## Summary\n\nShort.\n\n\
+             ## References\n\n[^1]: The Rust Book. <https://doc.rust-lang.org/book/> (accessed 2026-01-01)"
+        )
+    }
+
+    #[test]
+    fn test_report_path() {
+        assert_eq!(report_path(Path::new("/kb"), "Rust: Ownership & Borrowing?"), Path::new("/kb/research/rust-ownership-borrowing.md"));
+    }
+
+    #[test]
+    fn test_note_frontmatter() {
+        let note = render_note("Rust ownership", "run-1", "2026-01-01", &sample_report(), &sample_bib());
+        let (fm, body) = crate::frontmatter::split_frontmatter(&note);
+        let fm = fm.expect("frontmatter should parse");
+        assert_eq!(fm.title.as_deref(), Some("Rust ownership"));
+        assert_eq!(fm.tags, vec!["research".to_string()]);
+        assert_eq!(fm.extra["sources"], serde_json::json!(["https://doc.rust-lang.org/book/", "https://doc.rust-lang.org/nomicon/"]));
+        assert!(body.trim_start().starts_with("# Rust ownership\n\n## Ownership"));
+    }
+
+    #[test]
+    fn test_findings() {
+        let found = findings("Rust ownership", &sample_report(), &sample_bib());
+        assert_eq!(found.len(), 1);
+        assert!(found[0].starts_with("Research on Rust ownership: Ownership\nEach value has one owner. "));
+        assert!(found[0].ends_with("(Sources: https://doc.rust-lang.org/book/, https://doc.rust-lang.org/nomicon/)"));
+    }
+}
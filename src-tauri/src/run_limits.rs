@@ -0,0 +1,158 @@
+/// Per-run caps for agent orchestrations and debates
+///
+/// Limits are checked before every agent call, so a run that reaches one stops between
+/// calls and returns what it has so far. The wall-clock limit also cuts off a call that
+/// is still running when time runs out. Unset limits don't apply.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunLimits {
+    /// Estimated tokens (prompts plus outputs) across the run
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Agent calls across the run
+    #[serde(default)]
+    pub max_api_calls: Option<u32>,
+    /// Wall-clock time for the whole run
+    #[serde(default)]
+    pub max_duration_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitHit {
+    Tokens,
+    ApiCalls,
+    Duration,
+}
+
+impl LimitHit {
+    pub fn message(&self) -> &'static str {
+        match self {
+            LimitHit::Tokens => "Token limit reached",
+            LimitHit::ApiCalls => "API call limit reached",
+            LimitHit::Duration => "Time limit reached",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunUsage {
+    pub estimated_tokens: usize,
+    pub api_calls: u32,
+    pub duration_ms: u64,
+    /// The limit that ended the run early, if any
+    pub limit_hit: Option<LimitHit>,
+}
+
+/// A run's usage against its limits, shared by agents running concurrently
+#[derive(Debug, Clone)]
+pub struct RunBudget {
+    limits: RunLimits,
+    started: Instant,
+    usage: Arc<Mutex<RunUsage>>,
+}
+
+impl RunBudget {
+    pub fn new(limits: RunLimits) -> Self {
+        Self { limits, started: Instant::now(), usage: Arc::new(Mutex::new(RunUsage::default())) }
+    }
+
+    fn reached(&self, usage: &mut RunUsage) -> Option<LimitHit> {
+        if usage.limit_hit.is_none() {
+            usage.limit_hit = if self.remaining() == Some(Duration::ZERO) {
+                Some(LimitHit::Duration)
+            } else if self.limits.max_tokens.map_or(false, |max| usage.estimated_tokens >= max) {
+                Some(LimitHit::Tokens)
+            } else if self.limits.max_api_calls.map_or(false, |max| usage.api_calls >= max) {
+                Some(LimitHit::ApiCalls)
+            } else {
+                None
+            };
+        }
+        usage.limit_hit
+    }
+
+    /// The limit the run has reached, without reserving anything
+    pub fn check(&self) -> Option<LimitHit> {
+        self.reached(&mut self.usage.lock().unwrap())
+    }
+
+    /// Reserve one agent call, or say which limit prevents it
+    pub fn try_call(&self) -> Result<(), LimitHit> {
+        let mut usage = self.usage.lock().unwrap();
+        if let Some(hit) = self.reached(&mut usage) {
+            return Err(hit);
+        }
+        usage.api_calls += 1;
+        Ok(())
+    }
+
+    pub fn record_tokens(&self, tokens: usize) {
+        self.usage.lock().unwrap().estimated_tokens += tokens;
+    }
+
+    /// Time left before the wall-clock limit; None without one
+    pub fn remaining(&self) -> Option<Duration> {
+        self.limits
+            .max_duration_secs
+            .map(|secs| Duration::from_secs(secs).saturating_sub(self.started.elapsed()))
+    }
+
+    /// Await `call` for at most the time left; None if the time limit cut it off
+    pub async fn within_time<F: Future>(&self, call: F) -> Option<F::Output> {
+        match self.remaining() {
+            Some(left) => match tokio::time::timeout(left, call).await {
+                Ok(output) => Some(output),
+                Err(_) => {
+                    self.usage.lock().unwrap().limit_hit = Some(LimitHit::Duration);
+                    None
+                }
+            },
+            None => Some(call.await),
+        }
+    }
+
+    pub fn usage(&self) -> RunUsage {
+        let mut usage = self.usage.lock().unwrap().clone();
+        usage.duration_ms = self.started.elapsed().as_millis() as u64;
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_stops_at_first_limit() {
+        let budget = RunBudget::new(RunLimits { max_tokens: Some(100), max_api_calls: Some(2), max_duration_secs: None });
+        let shared = budget.clone();
+        assert!(budget.try_call().is_ok());
+        shared.record_tokens(60);
+        assert!(shared.try_call().is_ok());
+        assert_eq!(budget.try_call(), Err(LimitHit::ApiCalls));
+        // The first limit reached is the one reported
+        budget.record_tokens(60);
+        assert_eq!(budget.check(), Some(LimitHit::ApiCalls));
+        let usage = budget.usage();
+        assert_eq!((usage.api_calls, usage.estimated_tokens), (2, 120));
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let unlimited = RunBudget::new(RunLimits::default());
+        unlimited.record_tokens(1_000_000);
+        assert!(unlimited.try_call().is_ok());
+        assert_eq!(unlimited.remaining(), None);
+    }
+
+    #[test]
+    fn test_duration_limit() {
+        assert_eq!(RunBudget::new(RunLimits { max_duration_secs: Some(0), ..Default::default() }).check(), Some(LimitHit::Duration));
+    }
+}
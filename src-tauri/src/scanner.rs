@@ -131,7 +131,7 @@ pub async fn scan_codebase(app_handle: tauri::AppHandle, max_depth: Option<usize
     let root_path = std::env::current_dir().map_err(|e| e.to_string())?;
     let depth = max_depth.unwrap_or(5);
 
-    eprintln!("🔍 Scanning codebase at: {:?} (depth: {})", root_path, depth);
+    tracing::info!("🔍 Scanning codebase at: {:?} (depth: {})", root_path, depth);
 
     let structure = build_file_tree(&root_path, 0, depth)
         .ok_or("Failed to build file tree")?;
@@ -0,0 +1,698 @@
+/// Scheduled autonomous agent tasks
+///
+/// A task runs a prompt through `run_autonomous_task` on a cron-like schedule (five fields,
+/// `minute hour day month weekday`, or `@hourly`, `@daily`, `@weekly`, `@monthly`), e.g.
+/// `0 7 * * *` to research the watched topics every morning and write a digest. Each result
+/// is saved as a note under `scheduled/`, and a `scheduled-task-finished` event and a
+/// desktop notification announce it. Tasks and their runs are stored in
+/// knowledge_companion.db.
+///
+/// Nothing runs until the frontend starts the scheduler with API keys, which are kept in
+/// memory only. A task that came due while the app was closed runs once at the next check,
+/// not once per missed time.
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Manager;
+use crate::commands::orchestrate_agents::{parse_provider, ProviderKeys};
+use crate::minimax_enhanced::AIProvider;
+use crate::notifications::Category;
+
+pub const SCHEDULED_FOLDER: &str = "scheduled";
+/// How often due tasks are looked for
+const CHECK_INTERVAL_SECS: u64 = 30;
+/// How far ahead to look for a schedule's next time; covers Feb 29 schedules
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+const TASK_PROMPT: &str = r#"You are ThinkSpace's background assistant, running a scheduled task on your own.
+The user isn't watching, so don't ask questions: make reasonable assumptions and note them.
+Use your tools as needed and finish with the complete result in markdown, ready to be saved as a note."#;
+
+lazy_static::lazy_static! {
+    static ref SCHEDULER_KEYS: Mutex<Option<SchedulerKeys>> = Mutex::new(None);
+}
+static SCHEDULER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// API keys scheduled tasks run with
+#[derive(Debug, Clone)]
+struct SchedulerKeys {
+    keys: ProviderKeys,
+    provider: AIProvider,
+    user_id: String,
+}
+
+/// A parsed cron-like schedule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    /// 0 = Sunday
+    weekdays: Vec<u32>,
+    /// As in cron, when both day and weekday are restricted either one may match
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(|| format!("Invalid step in '{}'", part))?;
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+        let number = |s: &str| s.parse::<u32>().map_err(|_| format!("Invalid value '{}' in '{}'", s, field));
+        let (lo, hi) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((lo, hi)) => (number(lo)?, number(hi)?),
+            None => {
+                // "5/15" means every 15 from 5
+                let value = number(range)?;
+                (value, if step.is_some() { max } else { value })
+            }
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(format!("'{}' is outside {}-{}", part, min, max));
+        }
+        values.extend((lo..=hi).step_by(step.unwrap_or(1) as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("Schedule '{}' needs five fields: minute hour day month weekday", expr.trim()));
+        }
+        // Both 0 and 7 are Sunday
+        let mut weekdays: Vec<u32> = parse_field(fields[4], 0, 7)?.into_iter().map(|d| d % 7).collect();
+        weekdays.sort_unstable();
+        weekdays.dedup();
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days.contains(&date.day());
+        let weekday = self.weekdays.contains(&date.weekday().num_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+
+    /// The first minute after `after` the schedule fires; None if it never does (Feb 30)
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after.date().and_hms_opt(after.hour(), after.minute(), 0)? + chrono::Duration::minutes(1);
+        let limit = time + chrono::Duration::days(MAX_LOOKAHEAD_DAYS);
+        while time <= limit {
+            let date = time.date();
+            if !self.months.contains(&date.month()) {
+                let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(date) {
+                time = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !self.hours.contains(&time.hour()) {
+                time = date.and_hms_opt(time.hour(), 0, 0)? + chrono::Duration::hours(1);
+            } else if !self.minutes.contains(&time.minute()) {
+                time += chrono::Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// The next local time the schedule fires after `after`, skipping times a DST change
+    /// jumps over
+    pub fn next_run(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut time = after.naive_local();
+        loop {
+            time = self.next_after(time)?;
+            if let Some(local) = Local.from_local_datetime(&time).earliest() {
+                return Some(local);
+            }
+        }
+    }
+}
+
+/// Timestamps are stored in one UTC format so they compare as strings
+pub fn stamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn next_run_stamp(schedule: &str, after: DateTime<Local>) -> Result<String, String> {
+    Schedule::parse(schedule)?
+        .next_run(after)
+        .map(|t| stamp(t.with_timezone(&Utc)))
+        .ok_or_else(|| format!("Schedule '{}' never fires", schedule))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub id: String,
+    pub name: String,
+    /// What the agent is asked to do each run
+    pub prompt: String,
+    pub schedule: String,
+    /// "minimax", "grok" or "gemini"; the scheduler's provider when missing
+    pub provider: Option<String>,
+    /// Tools to turn off (false) for this task, as in a chat session; others stay on
+    pub enabled_tools: HashMap<String, bool>,
+    pub enabled: bool,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub created_at: String,
+}
+
+/// One run of a scheduled task; also the `scheduled-task-finished` event payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRun {
+    pub id: String,
+    pub task_id: String,
+    pub task_name: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub success: bool,
+    /// Note the result was saved to, relative to the knowledge base
+    pub note_path: Option<String>,
+    pub output: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduledTaskRequest {
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub enabled_tools: HashMap<String, bool>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn init_scheduler_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduled_tasks (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            prompt TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            provider TEXT,
+            enabled_tools TEXT NOT NULL DEFAULT '{}',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            next_run_at TEXT,
+            last_run_at TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduled_task_runs (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            task_name TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            note_path TEXT,
+            output TEXT NOT NULL,
+            error TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_scheduled_task_runs_task ON scheduled_task_runs(task_id, started_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+const TASK_COLUMNS: &str = "id, name, prompt, schedule, provider, enabled_tools, enabled, next_run_at, last_run_at, created_at";
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<ScheduledTask> {
+    let enabled_tools: String = row.get(5)?;
+    Ok(ScheduledTask {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        prompt: row.get(2)?,
+        schedule: row.get(3)?,
+        provider: row.get(4)?,
+        enabled_tools: serde_json::from_str(&enabled_tools).unwrap_or_default(),
+        enabled: row.get(6)?,
+        next_run_at: row.get(7)?,
+        last_run_at: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+pub fn save_task(conn: &Connection, task: &ScheduledTask) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!("INSERT OR REPLACE INTO scheduled_tasks ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", TASK_COLUMNS),
+        params![
+            task.id,
+            task.name,
+            task.prompt,
+            task.schedule,
+            task.provider,
+            serde_json::to_string(&task.enabled_tools).unwrap_or_else(|_| "{}".to_string()),
+            task.enabled,
+            task.next_run_at,
+            task.last_run_at,
+            task.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_task(conn: &Connection, id: &str) -> rusqlite::Result<Option<ScheduledTask>> {
+    conn.query_row(&format!("SELECT {} FROM scheduled_tasks WHERE id = ?1", TASK_COLUMNS), params![id], task_from_row)
+        .optional()
+}
+
+pub fn list_tasks(conn: &Connection) -> rusqlite::Result<Vec<ScheduledTask>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM scheduled_tasks ORDER BY name", TASK_COLUMNS))?;
+    let rows = stmt.query_map([], task_from_row)?;
+    rows.collect()
+}
+
+/// Enabled tasks whose next run is at or before `now` (a `stamp`), most overdue first
+pub fn due_tasks(conn: &Connection, now: &str) -> rusqlite::Result<Vec<ScheduledTask>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM scheduled_tasks WHERE enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?1 ORDER BY next_run_at",
+        TASK_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now], task_from_row)?;
+    rows.collect()
+}
+
+/// Store a finished run and when the task runs next
+pub fn record_run(conn: &Connection, run: &TaskRun, next_run_at: Option<&str>) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO scheduled_task_runs (id, task_id, task_name, started_at, finished_at, success, note_path, output, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![run.id, run.task_id, run.task_name, run.started_at, run.finished_at, run.success, run.note_path, run.output, run.error],
+    )?;
+    conn.execute(
+        "UPDATE scheduled_tasks SET last_run_at = ?2, next_run_at = ?3 WHERE id = ?1",
+        params![run.task_id, run.started_at, next_run_at],
+    )?;
+    Ok(())
+}
+
+pub fn list_runs(conn: &Connection, task_id: Option<&str>, limit: usize) -> rusqlite::Result<Vec<TaskRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, task_id, task_name, started_at, finished_at, success, note_path, output, error
+         FROM scheduled_task_runs WHERE ?1 IS NULL OR task_id = ?1 ORDER BY started_at DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![task_id, limit as i64], |row| {
+        Ok(TaskRun {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            task_name: row.get(2)?,
+            started_at: row.get(3)?,
+            finished_at: row.get(4)?,
+            success: row.get(5)?,
+            note_path: row.get(6)?,
+            output: row.get(7)?,
+            error: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[derive(Serialize)]
+struct ResultFrontmatter<'a> {
+    title: &'a str,
+    created: String,
+    tags: Vec<&'a str>,
+    task_id: &'a str,
+    schedule: &'a str,
+}
+
+/// The run's result as a note; later runs on the same day replace it (note_versions keeps
+/// the previous copy)
+fn write_result_note(app_handle: &tauri::AppHandle, kb_root: &Path, task: &ScheduledTask, output: &str) -> Result<String, String> {
+    let date = Local::now().format("%Y-%m-%d").to_string();
+    let path = kb_root
+        .join(SCHEDULED_FOLDER)
+        .join(format!("{}-{}.md", crate::research_notes::slugify(&task.name), date));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let title = format!("{} ({})", task.name, date);
+    let frontmatter = ResultFrontmatter {
+        title: &title,
+        created: date.clone(),
+        tags: vec!["scheduled"],
+        task_id: &task.id,
+        schedule: &task.schedule,
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+    let body = if output.trim_start().starts_with("# ") { output.trim().to_string() } else { format!("# {}\n\n{}", title, output.trim()) };
+
+    crate::note_versions::record_before_write(Some(app_handle), kb_root, &path, "scheduled");
+    std::fs::write(&path, format!("---\n{}---\n\n{}\n", yaml, body)).map_err(|e| e.to_string())?;
+
+    let key = crate::kb_index::path_key(kb_root, &path);
+    crate::kb_index::apply_watch_changes(app_handle, &[path.clone()], None);
+    crate::events::emit(app_handle, "content-changed", serde_json::json!({
+        "source": kb_root.join(SCHEDULED_FOLDER),
+        "paths": [&key],
+    }));
+    Ok(key)
+}
+
+fn notify(app_handle: &tauri::AppHandle, run: &TaskRun) {
+    let _ = app_handle.emit_all("scheduled-task-finished", run);
+    let body = match (&run.note_path, &run.error) {
+        (_, Some(error)) => format!("Failed: {}", error),
+        (Some(path), None) => format!("Saved to {}", path),
+        (None, None) => "Finished".to_string(),
+    };
+    let category = if run.error.is_some() { Category::Errors } else { Category::Research };
+    crate::notifications::notify(app_handle, category, &format!("⏰ {}", run.task_name), &body);
+}
+
+/// Run a task now, save its result and schedule its next run
+#[tracing::instrument(name = "scheduled_task", skip_all, fields(task = %task.name))]
+async fn run_task(app_handle: &tauri::AppHandle, task: &ScheduledTask) -> Result<TaskRun, String> {
+    let scheduler = SCHEDULER_KEYS
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or("The scheduler hasn't been started with API keys")?;
+    let provider = scheduler.keys.pick(task.provider.as_deref(), &scheduler.provider);
+    let mut agent = scheduler
+        .keys
+        .agent(provider, TASK_PROMPT.to_string())
+        .with_enabled_tools(task.enabled_tools.clone())
+        .with_app_handle(app_handle.clone())
+        .with_user_id(scheduler.user_id.clone());
+
+    tracing::info!("⏰ Running scheduled task '{}'", task.name);
+    let started_at = Utc::now();
+    let prompt = format!("{}\n\n(Scheduled task \"{}\", running {})", task.prompt, task.name, crate::config::now().format("%A %Y-%m-%d %H:%M"));
+    let result = agent.run_autonomous_task(prompt).await;
+
+    let (output, mut error) = match result {
+        Ok(output) => (output, None),
+        Err(e) => (String::new(), Some(e)),
+    };
+    let mut note_path = None;
+    if error.is_none() {
+        match crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()
+            .and_then(|kb_root| write_result_note(app_handle, &kb_root, task, &output))
+        {
+            Ok(path) => note_path = Some(path),
+            Err(e) => error = Some(format!("Couldn't save the result: {}", e)),
+        }
+    }
+    let run = TaskRun {
+        id: uuid::Uuid::new_v4().to_string(),
+        task_id: task.id.clone(),
+        task_name: task.name.clone(),
+        started_at: stamp(started_at),
+        finished_at: stamp(Utc::now()),
+        success: error.is_none(),
+        note_path,
+        output,
+        error,
+    };
+
+    let next_run_at = next_run_stamp(&task.schedule, Local::now()).ok();
+    let recorded = crate::minimax_api::open_kc_database(Some(app_handle))
+        .and_then(|conn| record_run(&conn, &run, next_run_at.as_deref()).map_err(|e| e.to_string()));
+    if let Err(e) = recorded {
+        tracing::warn!("⚠️ Failed to record run of '{}': {}", task.name, e);
+    }
+    match &run.error {
+        Some(e) => tracing::error!("❌ Scheduled task '{}' failed: {}", task.name, e),
+        None => tracing::info!("✅ Scheduled task '{}' done", task.name),
+    }
+    notify(app_handle, &run);
+    Ok(run)
+}
+
+/// An agent with the keys the scheduler was started with, for other background jobs such
+/// as the email digest; None until the frontend has started the scheduler
+pub fn background_agent(app_handle: &tauri::AppHandle, system_prompt: &str) -> Option<crate::minimax_enhanced::MinimaxAgent> {
+    let scheduler = SCHEDULER_KEYS.lock().unwrap().clone()?;
+    let provider = scheduler.keys.pick(None, &scheduler.provider);
+    Some(
+        scheduler
+            .keys
+            .agent(provider, system_prompt.to_string())
+            .with_app_handle(app_handle.clone())
+            .with_user_id(scheduler.user_id),
+    )
+}
+
+/// The provider, keys and user id the scheduler was started with, for background jobs that
+/// run full agent turns, such as the chat bridge
+pub fn keys() -> Option<(AIProvider, ProviderKeys, String)> {
+    SCHEDULER_KEYS.lock().unwrap().clone().map(|s| (s.provider, s.keys, s.user_id))
+}
+
+/// The user id the scheduler was started with
+pub fn user_id() -> Option<String> {
+    SCHEDULER_KEYS.lock().unwrap().as_ref().map(|s| s.user_id.clone())
+}
+
+fn spawn_scheduler(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let due = crate::minimax_api::open_kc_database(Some(&app_handle))
+                .and_then(|conn| due_tasks(&conn, &stamp(Utc::now())).map_err(|e| e.to_string()));
+            match due {
+                // One at a time, so a slow task delays the others rather than piling up
+                Ok(tasks) => {
+                    for task in tasks {
+                        if let Err(e) = run_task(&app_handle, &task).await {
+                            tracing::warn!("⚠️ Scheduled task '{}' not run: {}", task.name, e);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("⚠️ Failed to check scheduled tasks: {}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Give the scheduler API keys and start it; calling it again only replaces the keys
+#[tauri::command]
+pub fn start_scheduler(
+    app_handle: tauri::AppHandle,
+    provider: Option<String>,
+    api_key: String,
+    tavily_key: Option<String>,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+    user_id: Option<String>,
+) -> Result<(), String> {
+    let provider = parse_provider(provider.as_deref());
+    let keys = ProviderKeys::new(&provider, &api_key, grok_key, gemini_key, tavily_key);
+    *SCHEDULER_KEYS.lock().unwrap() = Some(SchedulerKeys { keys, provider, user_id: user_id.unwrap_or_else(|| "guest".to_string()) });
+
+    if !SCHEDULER_STARTED.swap(true, Ordering::SeqCst) {
+        spawn_scheduler(app_handle);
+        tracing::info!("⏰ Task scheduler started");
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_scheduled_task(app_handle: tauri::AppHandle, request: ScheduledTaskRequest) -> Result<ScheduledTask, String> {
+    if request.name.trim().is_empty() || request.prompt.trim().is_empty() {
+        return Err("A scheduled task needs a name and a prompt".to_string());
+    }
+    let task = ScheduledTask {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: request.name.trim().to_string(),
+        prompt: request.prompt.trim().to_string(),
+        next_run_at: Some(next_run_stamp(&request.schedule, Local::now())?),
+        schedule: request.schedule.trim().to_string(),
+        provider: request.provider,
+        enabled_tools: request.enabled_tools,
+        enabled: request.enabled,
+        last_run_at: None,
+        created_at: stamp(Utc::now()),
+    };
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    save_task(&conn, &task).map_err(|e| e.to_string())?;
+    tracing::info!("⏰ Scheduled '{}' ({}), next run {}", task.name, task.schedule, task.next_run_at.as_deref().unwrap_or(""));
+    Ok(task)
+}
+
+/// Turn a task on or off; turning it on schedules it from now, skipping missed runs
+#[tauri::command]
+pub fn set_scheduled_task_enabled(app_handle: tauri::AppHandle, id: String, enabled: bool) -> Result<ScheduledTask, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut task = get_task(&conn, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Scheduled task '{}' not found", id))?;
+    if enabled && !task.enabled {
+        task.next_run_at = Some(next_run_stamp(&task.schedule, Local::now())?);
+    }
+    task.enabled = enabled;
+    save_task(&conn, &task).map_err(|e| e.to_string())?;
+    Ok(task)
+}
+
+#[tauri::command]
+pub fn delete_scheduled_task(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    conn.execute("DELETE FROM scheduled_tasks WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_scheduled_tasks(app_handle: tauri::AppHandle) -> Result<Vec<ScheduledTask>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_tasks(&conn).map_err(|e| e.to_string())
+}
+
+/// Runs newest first, optionally of one task
+#[tauri::command]
+pub fn list_scheduled_task_runs(app_handle: tauri::AppHandle, task_id: Option<String>, limit: Option<usize>) -> Result<Vec<TaskRun>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_runs(&conn, task_id.as_deref(), limit.unwrap_or(50)).map_err(|e| e.to_string())
+}
+
+/// Run a task right away, outside its schedule
+#[tauri::command]
+pub async fn run_scheduled_task_now(app_handle: tauri::AppHandle, id: String) -> Result<TaskRun, String> {
+    let task = {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        get_task(&conn, &id).map_err(|e| e.to_string())?
+    };
+    let task = task.ok_or_else(|| format!("Scheduled task '{}' not found", id))?;
+    run_task(&app_handle, &task).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+    }
+
+    fn task(id: &str, enabled: bool, next_run_at: &str) -> ScheduledTask {
+        ScheduledTask {
+            id: id.to_string(),
+            name: format!("Task {}", id),
+            prompt: "Research my watched topics and write a digest".to_string(),
+            schedule: "0 7 * * *".to_string(),
+            provider: None,
+            enabled_tools: HashMap::from([("generate_image".to_string(), false)]),
+            enabled,
+            next_run_at: Some(next_run_at.to_string()),
+            last_run_at: None,
+            created_at: "2026-10-01T00:00:00Z".to_string(),
+        }
+    }
+
+    /// A database with a task due at 07:00 on 2026-10-16, one due a day later and one switched off
+    fn task_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_scheduler_tables(&conn).unwrap();
+        save_task(&conn, &task("due", true, "2026-10-16T07:00:00Z")).unwrap();
+        save_task(&conn, &task("later", true, "2026-10-17T07:00:00Z")).unwrap();
+        save_task(&conn, &task("off", false, "2026-10-15T07:00:00Z")).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_weekday_schedule() {
+        // 2026-10-16 is a Friday
+        let weekdays = Schedule::parse("30 7 * * 1-5").unwrap();
+        assert_eq!(weekdays.next_after(at(2026, 10, 16, 8, 0)), Some(at(2026, 10, 19, 7, 30)));
+        assert_eq!(weekdays.next_after(at(2026, 10, 16, 7, 29)), Some(at(2026, 10, 16, 7, 30)));
+    }
+
+    #[test]
+    fn test_step_and_shorthand_schedules() {
+        let quarter_hours = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(quarter_hours.next_after(at(2026, 10, 16, 8, 0)), Some(at(2026, 10, 16, 8, 15)));
+        assert_eq!(quarter_hours.next_after(at(2026, 10, 16, 8, 52)), Some(at(2026, 10, 16, 9, 0)));
+        assert_eq!(Schedule::parse("@daily").unwrap().next_after(at(2026, 12, 31, 23, 59)), Some(at(2027, 1, 1, 0, 0)));
+    }
+
+    #[test]
+    fn test_day_and_weekday_schedules() {
+        // Day and weekday both set: either matches, so Sunday the 1st comes before Monday the 2nd
+        assert_eq!(Schedule::parse("0 9 1 * 1").unwrap().next_after(at(2026, 10, 27, 0, 0)), Some(at(2026, 11, 1, 9, 0)));
+        assert_eq!(Schedule::parse("0 0 30 2 *").unwrap().next_after(at(2026, 1, 1, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_invalid_schedules() {
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(Schedule::parse("0 7 * *").is_err());
+    }
+
+    #[test]
+    fn test_due_tasks() {
+        let conn = task_db();
+        let due = due_tasks(&conn, "2026-10-16T07:00:00Z").unwrap();
+        assert_eq!(due.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), vec!["due"]);
+        assert_eq!(due[0].enabled_tools.get("generate_image"), Some(&false));
+    }
+
+    #[test]
+    fn test_record_run_moves_the_task_on() {
+        let conn = task_db();
+        let run = TaskRun {
+            id: "run-1".to_string(),
+            task_id: "due".to_string(),
+            task_name: "Task due".to_string(),
+            started_at: "2026-10-16T07:00:05Z".to_string(),
+            finished_at: "2026-10-16T07:02:00Z".to_string(),
+            success: true,
+            note_path: Some("scheduled/task-due-2026-10-16.md".to_string()),
+            output: "# Digest".to_string(),
+            error: None,
+        };
+        record_run(&conn, &run, Some("2026-10-17T07:00:00Z")).unwrap();
+        assert!(due_tasks(&conn, "2026-10-16T07:00:00Z").unwrap().is_empty());
+        assert_eq!(get_task(&conn, "due").unwrap().unwrap().last_run_at.as_deref(), Some("2026-10-16T07:00:05Z"));
+        assert_eq!(list_runs(&conn, Some("due"), 10).unwrap()[0].note_path, run.note_path);
+    }
+}
@@ -0,0 +1,412 @@
+/// Screenshots with OCR, saved to the knowledge base
+///
+/// A screenshot of the whole screen, a given region, or one the user drags out is taken with
+/// the platform's own tool (screencapture on macOS; grim/slurp, maim, scrot or ImageMagick on
+/// Linux; PowerShell on Windows). Its text is read with tesseract when it's installed, or by
+/// Grok's vision model otherwise. The image goes to `research/screenshots/assets/` and a note
+/// with the image and its text to `research/screenshots/`, and the text is returned so the
+/// agent can discuss it straight away.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::minimax_enhanced::AIProvider;
+use crate::net::SendChecked;
+
+pub const SCREENSHOTS_FOLDER: &str = "research/screenshots";
+/// Long enough for the user to drag out a region
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(120);
+const OCR_TIMEOUT: Duration = Duration::from_secs(60);
+const VISION_PROMPT: &str = "Transcribe all text in this screenshot exactly as it appears, keeping line breaks, \
+    lists and code indentation. Reply with the text only; if there is none, reply with nothing.";
+
+/// A screen region in pixels from the top-left of the main display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrEngine {
+    /// tesseract if installed, otherwise vision
+    #[default]
+    Auto,
+    Tesseract,
+    Vision,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grabber {
+    ScreenCapture,
+    Grim,
+    Maim,
+    Scrot,
+    Import,
+    PowerShell,
+}
+
+impl Grabber {
+    fn program(&self) -> &'static str {
+        match self {
+            Grabber::ScreenCapture => "screencapture",
+            Grabber::Grim => "grim",
+            Grabber::Maim => "maim",
+            Grabber::Scrot => "scrot",
+            Grabber::Import => "import",
+            Grabber::PowerShell => "powershell",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Screenshot {
+    /// Note path relative to the knowledge base
+    pub path: String,
+    pub image: String,
+    pub text: String,
+    /// "tesseract" or "vision"
+    pub ocr: String,
+}
+
+pub fn on_path(program: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let name = if cfg!(windows) { format!("{}.exe", program) } else { program.to_string() };
+    std::env::split_paths(&path_var).map(|dir| dir.join(&name)).find(|path| path.is_file())
+}
+
+/// The screenshot tool to use here; Wayland sessions need grim
+fn find_grabber() -> Option<Grabber> {
+    if cfg!(target_os = "macos") {
+        return Some(Grabber::ScreenCapture);
+    }
+    if cfg!(windows) {
+        return Some(Grabber::PowerShell);
+    }
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let candidates: &[Grabber] = if wayland { &[Grabber::Grim] } else { &[Grabber::Maim, Grabber::Scrot, Grabber::Import, Grabber::Grim] };
+    candidates.iter().copied().find(|g| on_path(g.program()).is_some())
+}
+
+/// Parse the `x,y wxh` geometry slurp prints
+fn parse_geometry(geometry: &str) -> Option<Region> {
+    let (position, size) = geometry.trim().split_once(' ')?;
+    let (x, y) = position.split_once(',')?;
+    let (width, height) = size.split_once('x')?;
+    let region = Region { x: x.parse().ok()?, y: y.parse().ok()?, width: width.parse().ok()?, height: height.parse().ok()? };
+    (region.width > 0 && region.height > 0).then_some(region)
+}
+
+/// Arguments for `grabber` to save `region` (or the whole screen, or a region the user
+/// selects when `select`) to `output`. grim's selection comes from slurp beforehand.
+fn grab_args(grabber: Grabber, region: Option<Region>, select: bool, output: &Path) -> Vec<String> {
+    let output = output.display().to_string();
+    let geometry = |r: &Region| format!("{}x{}+{}+{}", r.width, r.height, r.x, r.y);
+    let mut args: Vec<String> = match (grabber, region) {
+        (Grabber::ScreenCapture, Some(r)) => vec!["-x".into(), format!("-R{},{},{},{}", r.x, r.y, r.width, r.height)],
+        (Grabber::ScreenCapture, None) if select => vec!["-x".into(), "-i".into()],
+        (Grabber::ScreenCapture, None) => vec!["-x".into()],
+        (Grabber::Grim, Some(r)) => vec!["-g".into(), format!("{},{} {}x{}", r.x, r.y, r.width, r.height)],
+        (Grabber::Grim, None) => Vec::new(),
+        (Grabber::Maim, Some(r)) => vec!["-g".into(), geometry(&r)],
+        (Grabber::Maim, None) if select => vec!["-s".into()],
+        (Grabber::Maim, None) => Vec::new(),
+        (Grabber::Scrot, Some(r)) => vec!["-a".into(), format!("{},{},{},{}", r.x, r.y, r.width, r.height)],
+        (Grabber::Scrot, None) if select => vec!["-s".into()],
+        (Grabber::Scrot, None) => Vec::new(),
+        // import selects with the mouse unless it's told to take the root window
+        (Grabber::Import, Some(r)) => vec!["-window".into(), "root".into(), "-crop".into(), geometry(&r)],
+        (Grabber::Import, None) if select => Vec::new(),
+        (Grabber::Import, None) => vec!["-window".into(), "root".into()],
+        (Grabber::PowerShell, region) => {
+            let bounds = match region {
+                Some(r) => format!("$b = New-Object System.Drawing.Rectangle({}, {}, {}, {})", r.x, r.y, r.width, r.height),
+                None => "$b = [System.Windows.Forms.Screen]::PrimaryScreen.Bounds".to_string(),
+            };
+            let script = format!(
+                "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; {}; \
+                 $bmp = New-Object System.Drawing.Bitmap($b.Width, $b.Height); \
+                 $g = [System.Drawing.Graphics]::FromImage($bmp); \
+                 $g.CopyFromScreen($b.Location, [System.Drawing.Point]::Empty, $b.Size); \
+                 $bmp.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+                bounds,
+                output.replace('\'', "''")
+            );
+            return vec!["-NoProfile".into(), "-NonInteractive".into(), "-Command".into(), script];
+        }
+    };
+    args.push(output);
+    args
+}
+
+/// Run `command` to completion, killing it after `timeout`
+fn run(mut command: Command, timeout: Duration, what: &str) -> Result<(), String> {
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", what, e))?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("{} failed ({})", what, status)),
+            Ok(None) if started.elapsed() > timeout => {
+                let _ = child.kill();
+                return Err(format!("{} didn't finish within {}s", what, timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+/// Save a screenshot to `output` as a PNG
+fn grab(region: Option<Region>, select: bool, output: &Path) -> Result<(), String> {
+    let grabber = find_grabber().ok_or("Screenshots need grim (Wayland), or maim, scrot or ImageMagick (X11) installed")?;
+    if select && region.is_none() && grabber == Grabber::PowerShell {
+        return Err("Selecting a region isn't supported on Windows; pass the region to capture instead".to_string());
+    }
+    let mut region = region;
+    if select && region.is_none() && grabber == Grabber::Grim {
+        let selected = Command::new("slurp").output().map_err(|e| format!("Selecting a region needs slurp installed: {}", e))?;
+        // slurp exits non-zero when the selection is cancelled
+        if !selected.status.success() {
+            return Err("Screenshot cancelled".to_string());
+        }
+        region = Some(parse_geometry(&String::from_utf8_lossy(&selected.stdout)).ok_or("slurp returned no region")?);
+    }
+
+    let mut command = Command::new(grabber.program());
+    command.args(grab_args(grabber, region, select, output));
+    let ran = run(command, CAPTURE_TIMEOUT, grabber.program());
+    // Pressing Escape during a selection leaves no file, with or without an error
+    match ran {
+        _ if select && !output.is_file() => Err("Screenshot cancelled".to_string()),
+        Ok(()) if !output.is_file() => Err(format!("{} saved no screenshot", grabber.program())),
+        ran => ran,
+    }
+}
+
+/// Read the text in `image` with tesseract; `languages` like "eng+deu"
+pub fn tesseract(image: &Path, languages: &str) -> Result<String, String> {
+    let program = on_path("tesseract").ok_or("tesseract is not installed")?;
+    let base = std::env::temp_dir().join(format!("thinkspace-ocr-{}", uuid::Uuid::new_v4().simple()));
+    let mut command = Command::new(program);
+    command.arg(image).arg(&base).arg("-l").arg(languages);
+    let result = run(command, OCR_TIMEOUT, "tesseract");
+    let output = base.with_extension("txt");
+    let text = result.and_then(|_| std::fs::read_to_string(&output).map_err(|e| format!("tesseract wrote no text: {}", e)));
+    let _ = std::fs::remove_file(&output);
+    text
+}
+
+/// An OpenAI-style request asking `model` to transcribe the PNG `image`
+fn vision_payload(model: &str, image: &[u8]) -> serde_json::Value {
+    let data_url = format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(image));
+    serde_json::json!({
+        "model": model,
+        "messages": [{
+            "role": "user",
+            "content": [
+                { "type": "text", "text": VISION_PROMPT },
+                { "type": "image_url", "image_url": { "url": data_url } }
+            ]
+        }],
+        "temperature": 0.0
+    })
+}
+
+/// Read the text in the PNG `image` with Grok's vision model
+async fn vision(api_key: &str, image: &[u8]) -> Result<String, String> {
+    let (base_url, model) = AIProvider::Grok.endpoint();
+    let response = crate::net::client()
+        .post(format!("{}/chat/completions", base_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .json(&vision_payload(&model, image))
+        .send_checked()
+        .await
+        .map_err(|e| format!("Vision request failed: {}", e))?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Vision API error: {}", error_text));
+    }
+    let result: serde_json::Value = response.json().await.map_err(|e| format!("Failed to parse response: {}", e))?;
+    result["choices"][0]["message"]["content"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Missing content in vision response".to_string())
+}
+
+/// The note for a screenshot saved as `image_link`, with its extracted `text`
+fn screenshot_note(title: &str, created: &str, image_link: &str, text: &str, ocr: &str) -> String {
+    let text = text.trim();
+    let body = if text.is_empty() { "_No text found._".to_string() } else { text.to_string() };
+    format!(
+        "---\ntitle: \"{}\"\ncreated: {}\ntags: [screenshot]\nocr: {}\n---\n\n# {}\n\n![{}]({})\n\n## Text\n\n{}\n",
+        title, created, ocr, title, title, image_link, body
+    )
+}
+
+/// Grab the screen into `image` and read its text, with tesseract or the vision model
+async fn grab_and_read(
+    image: &Path,
+    region: Option<Region>,
+    select: bool,
+    use_tesseract: bool,
+    languages: String,
+    vision_key: &str,
+) -> Result<(String, &'static str), String> {
+    let output = image.to_path_buf();
+    let read = tauri::async_runtime::spawn_blocking(move || {
+        grab(region, select, &output)?;
+        Ok::<_, String>(if use_tesseract { Some(tesseract(&output, &languages)) } else { None })
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    match read {
+        Some(text) => Ok((text?, "tesseract")),
+        None => {
+            let bytes = std::fs::read(image).map_err(|e| e.to_string())?;
+            Ok((vision(vision_key, &bytes).await?, "vision"))
+        }
+    }
+}
+
+/// Take a screenshot, read its text and save both; `vision_key` is a Grok key for when
+/// tesseract isn't installed (or `engine` asks for vision)
+pub async fn capture(
+    app_handle: Option<&tauri::AppHandle>,
+    region: Option<Region>,
+    select: bool,
+    engine: OcrEngine,
+    languages: Option<String>,
+    vision_key: Option<&str>,
+) -> Result<Screenshot, String> {
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let folder = kb_root.join(SCREENSHOTS_FOLDER);
+    let assets = folder.join(crate::attachments::ASSETS_DIR);
+    std::fs::create_dir_all(&assets).map_err(|e| e.to_string())?;
+    let taken = crate::config::now();
+    let name = format!("screenshot-{}", taken.format("%Y-%m-%d-%H%M%S"));
+    let image_path = assets.join(format!("{}.png", name));
+
+    let use_tesseract = match engine {
+        OcrEngine::Tesseract => true,
+        OcrEngine::Vision => false,
+        OcrEngine::Auto => on_path("tesseract").is_some(),
+    };
+    // Checked before grabbing, so a missing OCR engine doesn't cost the user a selection
+    if use_tesseract && on_path("tesseract").is_none() {
+        return Err("tesseract is not installed".to_string());
+    }
+    if !use_tesseract && vision_key.map_or(true, str::is_empty) {
+        return Err("Reading screenshot text needs tesseract installed or a Grok API key for its vision model".to_string());
+    }
+
+    let languages = languages.filter(|l| !l.trim().is_empty()).unwrap_or_else(|| "eng".to_string());
+    let read = grab_and_read(&image_path, region, select, use_tesseract, languages, vision_key.unwrap_or_default()).await;
+    let (text, ocr) = match read {
+        Ok(read) => read,
+        Err(e) => {
+            // No note will point at the image, so it isn't kept
+            let _ = std::fs::remove_file(&image_path);
+            return Err(e);
+        }
+    };
+
+    let title = format!("Screenshot {}", taken.format("%Y-%m-%d %H:%M:%S"));
+    let note_path = folder.join(format!("{}.md", name));
+    let image_link = format!("{}/{}.png", crate::attachments::ASSETS_DIR, name);
+    let note = screenshot_note(&title, &taken.format("%Y-%m-%d").to_string(), &image_link, &text, ocr);
+    crate::note_versions::record_before_write(app_handle, &kb_root, &note_path, "screenshot");
+    std::fs::write(&note_path, note).map_err(|e| format!("Failed to save the screenshot note: {}", e))?;
+
+    let key = crate::kb_index::path_key(&kb_root, &note_path);
+    tracing::info!("📸 Screenshot saved to {} ({} chars of text via {})", key, text.trim().chars().count(), ocr);
+    if let Some(handle) = app_handle {
+        crate::kb_index::apply_watch_changes(handle, &[note_path.clone()], None);
+        crate::events::emit(handle, "content-changed", serde_json::json!({
+            "source": folder,
+            "paths": [&key],
+        }));
+    }
+    Ok(Screenshot {
+        path: key,
+        image: crate::kb_index::path_key(&kb_root, &image_path),
+        text: text.trim().to_string(),
+        ocr: ocr.to_string(),
+    })
+}
+
+// ==================== Commands ====================
+
+/// Screenshot `region`, the whole screen, or a region the user selects, and save it with its text
+#[tauri::command]
+pub async fn capture_screenshot(
+    app_handle: tauri::AppHandle,
+    region: Option<Region>,
+    select: Option<bool>,
+    ocr: Option<OcrEngine>,
+    languages: Option<String>,
+    grok_api_key: Option<String>,
+) -> Result<Screenshot, String> {
+    capture(Some(&app_handle), region, select.unwrap_or(false), ocr.unwrap_or_default(), languages, grok_api_key.as_deref()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGION: Region = Region { x: 10, y: -20, width: 300, height: 200 };
+
+    #[test]
+    fn test_parse_geometry() {
+        assert_eq!(parse_geometry("10,-20 300x200\n"), Some(REGION));
+        assert_eq!(parse_geometry("10,20 0x200"), None);
+        assert_eq!(parse_geometry("garbage"), None);
+    }
+
+    #[test]
+    fn test_grab_args_per_grabber() {
+        let out = Path::new("/tmp/shot.png");
+        assert_eq!(grab_args(Grabber::ScreenCapture, Some(REGION), false, out), vec!["-x", "-R10,-20,300,200", "/tmp/shot.png"]);
+        assert_eq!(grab_args(Grabber::ScreenCapture, None, true, out), vec!["-x", "-i", "/tmp/shot.png"]);
+        assert_eq!(grab_args(Grabber::Grim, Some(REGION), true, out), vec!["-g", "10,-20 300x200", "/tmp/shot.png"]);
+        assert_eq!(grab_args(Grabber::Maim, Some(REGION), false, out), vec!["-g", "300x200+10+-20", "/tmp/shot.png"]);
+        assert_eq!(grab_args(Grabber::Import, None, true, out), vec!["/tmp/shot.png"]);
+        assert_eq!(grab_args(Grabber::Import, None, false, out), vec!["-window", "root", "/tmp/shot.png"]);
+    }
+
+    #[test]
+    fn test_powershell_args_quote_the_output_path() {
+        let powershell = grab_args(Grabber::PowerShell, Some(REGION), false, Path::new("C:\\it's\\shot.png"));
+        assert!(powershell[3].contains("Rectangle(10, -20, 300, 200)") && powershell[3].contains("'C:\\it''s\\shot.png'"));
+    }
+
+    #[test]
+    fn test_vision_payload_sends_the_image_as_a_data_url() {
+        let payload = vision_payload("grok-4-1-fast", &[0x89, b'P', b'N', b'G']);
+        assert_eq!(payload["messages"][0]["content"][1]["image_url"]["url"], "data:image/png;base64,iVBORw==");
+    }
+
+    #[test]
+    fn test_screenshot_note_embeds_the_image_and_text() {
+        let note = screenshot_note("Screenshot 2026-10-16 09:30:00", "2026-10-16", "assets/shot.png", "  fn main() {}\n", "tesseract");
+        let (fm, body) = crate::frontmatter::split_frontmatter(&note);
+        assert_eq!(fm.expect("frontmatter should parse").tags, vec!["screenshot".to_string()]);
+        assert!(body.contains("![Screenshot 2026-10-16 09:30:00](assets/shot.png)\n\n## Text\n\nfn main() {}\n"));
+    }
+
+    #[test]
+    fn test_screenshot_note_without_text() {
+        assert!(screenshot_note("S", "2026-10-16", "assets/s.png", " ", "vision").contains("_No text found._"));
+    }
+}
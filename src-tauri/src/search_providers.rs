@@ -0,0 +1,459 @@
+/// Web search backends for research and the web_search tool
+///
+/// Tavily used to be the only option; `SearchProvider` lets Brave Search, a self-hosted
+/// SearXNG instance or DuckDuckGo stand in for it. The choice (plus the Brave key and
+/// SearXNG URL) is persisted in `search_settings.json`; the Tavily key still comes from
+/// the frontend with each request. `Auto` picks the first backend that is configured and
+/// falls back to DuckDuckGo, which needs no key. In local-only mode SearXNG is always used.
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+use url::Url;
+use crate::deep_research::SearchResult;
+use crate::net::SendChecked;
+
+const USER_AGENT: &str = "Mozilla/5.0 (compatible; ThinkSpace-Research/1.0; +https://github.com/oogalieboogalie/ThinkSpace)";
+
+lazy_static::lazy_static! {
+    static ref HTML_TAG: Regex = Regex::new(r"<[^>]+>").unwrap();
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some(USER_AGENT),
+        timeout: Some(Duration::from_secs(20)),
+    })
+}
+
+/// Results of one query, plus a direct answer when the backend provides one
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    pub answer: Option<String>,
+}
+
+#[async_trait]
+pub trait SearchProvider: Send + Sync {
+    /// Display name, used in logs and error messages
+    fn name(&self) -> &str;
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResponse>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProviderKind {
+    #[default]
+    Auto,
+    Tavily,
+    Brave,
+    Searxng,
+    Duckduckgo,
+}
+
+/// Search backend selection, persisted in app data
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchSettings {
+    #[serde(default)]
+    pub provider: SearchProviderKind,
+    #[serde(default)]
+    pub brave_api_key: Option<String>,
+    /// Base URL of a SearXNG instance with the JSON format enabled, e.g. http://localhost:8888
+    #[serde(default)]
+    pub searxng_url: Option<String>,
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+impl SearchSettings {
+    /// Build the selected provider. `tavily_key` is the key the frontend passed along;
+    /// `advanced` asks Tavily for its slower, more thorough search (deep research).
+    pub fn provider(&self, tavily_key: Option<String>, advanced: bool) -> Result<Box<dyn SearchProvider>, String> {
+        let tavily_key = non_empty(&tavily_key);
+        let brave_key = non_empty(&self.brave_api_key);
+        let searxng_url = non_empty(&self.searxng_url);
+
+        let local_only = crate::net::local_only();
+
+        let kind = match self.provider {
+            // SearXNG is the one provider that can run on this machine
+            _ if local_only => SearchProviderKind::Searxng,
+            SearchProviderKind::Auto if tavily_key.is_some() => SearchProviderKind::Tavily,
+            SearchProviderKind::Auto if brave_key.is_some() => SearchProviderKind::Brave,
+            SearchProviderKind::Auto if searxng_url.is_some() => SearchProviderKind::Searxng,
+            SearchProviderKind::Auto => SearchProviderKind::Duckduckgo,
+            kind => kind,
+        };
+
+        let provider: Box<dyn SearchProvider> = match kind {
+            SearchProviderKind::Tavily => Box::new(TavilyProvider {
+                api_key: tavily_key.ok_or("Tavily API key not configured. Please set it in settings or choose another search provider.")?,
+                advanced,
+            }),
+            SearchProviderKind::Brave => Box::new(BraveProvider {
+                api_key: brave_key.ok_or("Brave Search API key not configured. Please set it in settings.")?,
+            }),
+            SearchProviderKind::Searxng => {
+                let mut base = searxng_url.ok_or(if local_only {
+                    "Local-only mode is on: web search needs a local SearXNG instance. Please set its URL in settings."
+                } else {
+                    "SearXNG URL not configured. Please set it in settings."
+                })?;
+                // Keep any path prefix (e.g. /searx) when joining "search"
+                if !base.ends_with('/') {
+                    base.push('/');
+                }
+                Box::new(SearxngProvider {
+                    base_url: Url::parse(&base).map_err(|e| format!("Invalid SearXNG URL '{}': {}", base, e))?,
+                })
+            }
+            SearchProviderKind::Duckduckgo | SearchProviderKind::Auto => Box::new(DuckDuckGoProvider),
+        };
+        Ok(provider)
+    }
+}
+
+fn settings_path(app_handle: Option<&AppHandle>) -> Option<PathBuf> {
+    let dir = match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    };
+    dir.map(|d| d.join("search_settings.json"))
+}
+
+pub fn load_search_settings(app_handle: Option<&AppHandle>) -> SearchSettings {
+    settings_path(app_handle)
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Rank-based score for backends that don't report relevance, so the best-ranked
+/// sources are still the ones research reads in full
+fn rank_score(rank: usize, total: usize) -> f64 {
+    1.0 - rank as f64 / total.max(1) as f64
+}
+
+fn strip_tags(text: &str) -> String {
+    let text = HTML_TAG.replace_all(text, "");
+    text.replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+}
+
+async fn get_json(request: reqwest::RequestBuilder, provider: &str) -> Result<serde_json::Value> {
+    let resp = request.send_checked().await.with_context(|| format!("Failed to connect to {}", provider))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        bail!("{} returned {}: {}", provider, status, body.chars().take(300).collect::<String>());
+    }
+    resp.json().await.with_context(|| format!("Failed to parse {} response", provider))
+}
+
+// ==================== Tavily ====================
+
+pub struct TavilyProvider {
+    api_key: String,
+    advanced: bool,
+}
+
+#[async_trait]
+impl SearchProvider for TavilyProvider {
+    fn name(&self) -> &str {
+        "Tavily"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResponse> {
+        let body = serde_json::json!({
+            "api_key": self.api_key,
+            "query": query,
+            "search_depth": if self.advanced { "advanced" } else { "basic" },
+            "include_answer": true,
+            "include_images": false,
+            "include_raw_content": false,
+            "max_results": max_results
+        });
+        let json = get_json(client().post("https://api.tavily.com/search").json(&body), self.name()).await?;
+
+        let results = json["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .filter_map(|r| {
+                        Some(SearchResult {
+                            title: r["title"].as_str()?.to_string(),
+                            url: r["url"].as_str()?.to_string(),
+                            content: r["content"].as_str().unwrap_or_default().to_string(),
+                            score: r["score"].as_f64().unwrap_or(0.0),
+                            published_date: r["published_date"].as_str().map(str::to_string),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SearchResponse {
+            results,
+            answer: json["answer"].as_str().filter(|a| !a.is_empty()).map(str::to_string),
+        })
+    }
+}
+
+// ==================== Brave Search ====================
+
+pub struct BraveProvider {
+    api_key: String,
+}
+
+#[async_trait]
+impl SearchProvider for BraveProvider {
+    fn name(&self) -> &str {
+        "Brave Search"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResponse> {
+        let count = max_results.min(20).to_string();
+        let request = client()
+            .get("https://api.search.brave.com/res/v1/web/search")
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key)
+            .query(&[("q", query), ("count", count.as_str())]);
+        let json = get_json(request, self.name()).await?;
+
+        let items = json["web"]["results"].as_array().cloned().unwrap_or_default();
+        let total = items.len();
+        let results = items
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, r)| {
+                Some(SearchResult {
+                    title: strip_tags(r["title"].as_str()?),
+                    url: r["url"].as_str()?.to_string(),
+                    content: strip_tags(r["description"].as_str().unwrap_or_default()),
+                    score: rank_score(rank, total),
+                    published_date: r["page_age"].as_str().or_else(|| r["age"].as_str()).map(str::to_string),
+                })
+            })
+            .take(max_results)
+            .collect();
+
+        Ok(SearchResponse { results, answer: None })
+    }
+}
+
+// ==================== SearXNG ====================
+
+pub struct SearxngProvider {
+    base_url: Url,
+}
+
+/// Parse a SearXNG `format=json` response
+pub fn parse_searxng(json: &serde_json::Value, max_results: usize) -> SearchResponse {
+    let items = json["results"].as_array().cloned().unwrap_or_default();
+    let total = items.len();
+    let results = items
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, r)| {
+            Some(SearchResult {
+                title: r["title"].as_str()?.to_string(),
+                url: r["url"].as_str()?.to_string(),
+                content: r["content"].as_str().unwrap_or_default().to_string(),
+                score: rank_score(rank, total),
+                published_date: r["publishedDate"].as_str().map(str::to_string),
+            })
+        })
+        .take(max_results)
+        .collect();
+
+    // Older instances list answers as strings, newer ones as objects
+    let answer = json["answers"]
+        .as_array()
+        .and_then(|answers| answers.first())
+        .and_then(|a| a.as_str().or_else(|| a["answer"].as_str()))
+        .map(str::to_string);
+
+    SearchResponse { results, answer }
+}
+
+#[async_trait]
+impl SearchProvider for SearxngProvider {
+    fn name(&self) -> &str {
+        "SearXNG"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResponse> {
+        let endpoint = self.base_url.join("search").context("Invalid SearXNG URL")?;
+        let request = client()
+            .get(endpoint)
+            .header("Accept", "application/json")
+            .query(&[("q", query), ("format", "json")]);
+        let json = get_json(request, self.name())
+            .await
+            .context("Is the JSON format enabled under search.formats in the instance's settings.yml?")?;
+        Ok(parse_searxng(&json, max_results))
+    }
+}
+
+// ==================== DuckDuckGo ====================
+
+pub struct DuckDuckGoProvider;
+
+/// Result links on the HTML endpoint go through a `/l/?uddg=<target>` redirect
+fn unwrap_duckduckgo_link(href: &str) -> Option<String> {
+    let absolute = if href.starts_with("//") { format!("https:{}", href) } else { href.to_string() };
+    let url = Url::parse(&absolute).ok()?;
+    if url.host_str().map_or(false, |h| h.ends_with("duckduckgo.com")) {
+        return url.query_pairs().find(|(k, _)| k == "uddg").map(|(_, v)| v.into_owned());
+    }
+    Some(absolute)
+}
+
+/// Parse the result list of html.duckduckgo.com, skipping ads
+pub fn parse_duckduckgo_html(html: &str, max_results: usize) -> Vec<SearchResult> {
+    let document = Html::parse_document(html);
+    let result_sel = Selector::parse("div.result").unwrap();
+    let link_sel = Selector::parse("a.result__a").unwrap();
+    let snippet_sel = Selector::parse(".result__snippet").unwrap();
+
+    let mut results: Vec<SearchResult> = document
+        .select(&result_sel)
+        .filter(|r| !r.value().classes().any(|c| c == "result--ad"))
+        .filter_map(|r| {
+            let link = r.select(&link_sel).next()?;
+            let url = unwrap_duckduckgo_link(link.value().attr("href")?)?;
+            let title = link.text().collect::<String>().trim().to_string();
+            let content = r
+                .select(&snippet_sel)
+                .next()
+                .map(|s| s.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default();
+            Some(SearchResult { title, url, content, score: 0.0, published_date: None })
+        })
+        .take(max_results)
+        .collect();
+
+    let total = results.len();
+    for (rank, result) in results.iter_mut().enumerate() {
+        result.score = rank_score(rank, total);
+    }
+    results
+}
+
+#[async_trait]
+impl SearchProvider for DuckDuckGoProvider {
+    fn name(&self) -> &str {
+        "DuckDuckGo"
+    }
+
+    async fn search(&self, query: &str, max_results: usize) -> Result<SearchResponse> {
+        let resp = client()
+            .post("https://html.duckduckgo.com/html/")
+            .form(&[("q", query)])
+            .send_checked()
+            .await
+            .context("Failed to connect to DuckDuckGo")?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("DuckDuckGo returned {}", resp.status()));
+        }
+        let html = resp.text().await.context("Failed to read DuckDuckGo response")?;
+        let results = parse_duckduckgo_html(&html, max_results);
+        if results.is_empty() && html.contains("anomaly") {
+            bail!("DuckDuckGo is rate limiting searches; try again shortly or configure another provider");
+        }
+        Ok(SearchResponse { results, answer: None })
+    }
+}
+
+// ==================== Commands ====================
+
+#[tauri::command]
+pub fn get_search_settings(app_handle: AppHandle) -> Result<SearchSettings, String> {
+    Ok(load_search_settings(Some(&app_handle)))
+}
+
+#[tauri::command]
+pub fn set_search_settings(app_handle: AppHandle, settings: SearchSettings) -> Result<SearchSettings, String> {
+    if let Some(url) = non_empty(&settings.searxng_url) {
+        Url::parse(&url).map_err(|e| format!("Invalid SearXNG URL '{}': {}", url, e))?;
+    }
+    let path = settings_path(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duckduckgo_html() {
+        let html = r#"<div class="results">
+            <div class="result results_links result--ad">
+              <a class="result__a" href="https://duckduckgo.com/y.js?ad_domain=ads.example">Sponsored</a>
+            </div>
+            <div class="result results_links">
+              <h2><a class="result__a" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fdoc.rust-lang.org%2Fbook%2F&amp;rut=abc">The <b>Rust</b> Book</a></h2>
+              <a class="result__snippet" href="#">Learn   <b>Rust</b> from scratch.</a>
+            </div>
+            <div class="result results_links">
+              <a class="result__a" href="https://blog.rust-lang.org/">Rust Blog</a>
+            </div>
+        </div>"#;
+
+        let results = parse_duckduckgo_html(html, 5);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "The Rust Book");
+        assert_eq!(results[0].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(results[0].content, "Learn Rust from scratch.");
+        assert_eq!(results[1].url, "https://blog.rust-lang.org/");
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_auto_provider_selection() {
+        let mut settings = SearchSettings::default();
+        assert_eq!(settings.provider(None, false).unwrap().name(), "DuckDuckGo");
+        assert_eq!(settings.provider(Some(" ".to_string()), false).unwrap().name(), "DuckDuckGo");
+        assert_eq!(settings.provider(Some("tvly-key".to_string()), false).unwrap().name(), "Tavily");
+
+        settings.searxng_url = Some("http://localhost:8888".to_string());
+        assert_eq!(settings.provider(None, false).unwrap().name(), "SearXNG");
+    }
+
+    #[test]
+    fn test_chosen_provider_needs_its_key() {
+        let settings = SearchSettings { provider: SearchProviderKind::Brave, ..SearchSettings::default() };
+        assert!(settings.provider(Some("tvly-key".to_string()), false).is_err());
+    }
+
+    #[test]
+    fn test_parse_searxng() {
+        let parsed = parse_searxng(
+            &serde_json::json!({
+                "results": [{"title": "A", "url": "https://a.dev", "content": "a", "publishedDate": "2024-01-02"}],
+                "answers": [{"answer": "42"}]
+            }),
+            5,
+        );
+        assert_eq!(parsed.results[0].published_date.as_deref(), Some("2024-01-02"));
+        assert_eq!(parsed.answer.as_deref(), Some("42"));
+    }
+}
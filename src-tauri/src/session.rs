@@ -1,8 +1,28 @@
+/// Saved chat sessions
+///
+/// Sessions are stored as gzip-compressed JSON (`<name>.json.gz`) in the active profile's
+/// `sessions/` folder. They're serialized straight into the compressor and deserialized as
+/// they're read, so a long chat is never held as one big JSON string on either side, and saving
+/// and loading run on the blocking pool. Plain `.json` sessions from before still load, and
+/// are replaced the next time they're saved. `export_session` writes a readable JSON copy.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use tauri::command;
 
+use crate::minimax_enhanced::AIProvider;
+
+const SESSION_EXT: &str = ".json.gz";
+/// Sessions saved before compression
+const LEGACY_EXT: &str = ".json";
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VisualData {
     pub type_: String, // "threejs", "url", etc.
@@ -17,70 +37,281 @@ pub struct SessionData {
     pub main_canvas: Option<String>,
     pub left_canvas: Option<String>,
     pub visuals: Option<VisualData>,
+    pub settings: Option<SessionSettings>, // Agent configuration the session was created with
 }
 
-#[command]
-pub fn save_session(app_handle: tauri::AppHandle, data: SessionData) -> Result<String, String> {
-    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app data dir")?;
-    let sessions_dir = app_dir.join("sessions");
+/// Agent configuration persisted with a session so re-opening it restores
+/// the same provider, model, tools and prompt instead of the current UI selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSettings {
+    pub provider: AIProvider,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub enabled_tools: HashMap<String, bool>,
+    #[serde(default)]
+    pub safe_mode: bool,
+    /// File-writing and terminal tools report what they would do instead of doing it
+    #[serde(default)]
+    pub dry_run: bool,
+    pub system_prompt: Option<String>,
+}
 
-    if !sessions_dir.exists() {
-        fs::create_dir_all(&sessions_dir).map_err(|e| e.to_string())?;
-    }
+/// Only the settings of a saved session; the chat and canvases are skipped, not parsed
+#[derive(Deserialize)]
+struct StoredSettings {
+    settings: Option<SessionSettings>,
+}
 
-    // Sanitize filename
-    let safe_name = data.name.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
-    let filename = format!("{}.json", safe_name);
-    let file_path = sessions_dir.join(&filename);
+/// The active profile's sessions
+pub fn sessions_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let profile_dir = crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?;
+    Ok(profile_dir.join("sessions"))
+}
 
-    let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    fs::write(&file_path, json).map_err(|e| e.to_string())?;
+pub fn safe_name(name: &str) -> String {
+    name.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_")
+}
 
-    Ok(format!("Session saved to {}", file_path.display()))
+/// The session's name if `path` is a session file
+fn session_name(path: &Path) -> Option<&str> {
+    let file_name = path.file_name()?.to_str()?;
+    file_name.strip_suffix(SESSION_EXT).or_else(|| file_name.strip_suffix(LEGACY_EXT))
 }
 
-#[command]
-pub fn load_session(app_handle: tauri::AppHandle, name: String) -> Result<SessionData, String> {
-    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app data dir")?;
-    let sessions_dir = app_dir.join("sessions");
-    
-    // Sanitize filename just in case, though usually we'd pass the full filename or safe name
-    let safe_name = name.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "_");
-    // Try with and without extension
-    let mut file_path = sessions_dir.join(&safe_name);
-    if !file_path.exists() {
-        file_path = sessions_dir.join(format!("{}.json", safe_name));
-    }
+/// The stored file for `name`, compressed or (for sessions saved before compression) plain
+pub fn find_session_file(sessions_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let safe_name = safe_name(name);
+    let candidates = [
+        sessions_dir.join(format!("{}{}", safe_name, SESSION_EXT)),
+        sessions_dir.join(format!("{}{}", safe_name, LEGACY_EXT)),
+    ];
+    candidates
+        .into_iter()
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Session file not found: {}", name))
+}
 
-    if !file_path.exists() {
-        return Err(format!("Session file not found: {}", name));
-    }
+/// Serialize `data` straight into a compressed file, replacing the session's old file only
+/// once the new one is complete
+pub fn write_session(sessions_dir: &Path, data: &SessionData) -> Result<PathBuf, String> {
+    fs::create_dir_all(sessions_dir).map_err(|e| e.to_string())?;
+    let safe_name = safe_name(&data.name);
+    let file_path = sessions_dir.join(format!("{}{}", safe_name, SESSION_EXT));
+    let partial = sessions_dir.join(format!("{}{}.partial", safe_name, SESSION_EXT));
 
-    let json = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
-    let data: SessionData = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    let file = File::create(&partial).map_err(|e| e.to_string())?;
+    let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+    let written = serde_json::to_writer(&mut encoder, data)
+        .map_err(|e| e.to_string())
+        .and_then(|_| encoder.finish().and_then(|mut w| w.flush()).map_err(|e| e.to_string()))
+        .and_then(|_| fs::rename(&partial, &file_path).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
 
-    Ok(data)
+    // The plain copy from before compression is superseded
+    let _ = fs::remove_file(sessions_dir.join(format!("{}{}", safe_name, LEGACY_EXT)));
+    Ok(file_path)
 }
 
-#[command]
-pub fn list_sessions(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
-    let app_dir = app_handle.path_resolver().app_data_dir().ok_or("Failed to get app data dir")?;
-    let sessions_dir = app_dir.join("sessions");
+/// Deserialize a session file as it's read, decompressing it if needed
+pub fn read_session<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let file = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    if path.to_string_lossy().ends_with(SESSION_EXT) {
+        serde_json::from_reader(BufReader::new(GzDecoder::new(file))).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_reader(file).map_err(|e| e.to_string())
+    }
+}
 
+pub fn session_names(sessions_dir: &Path) -> Result<Vec<String>, String> {
     if !sessions_dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut sessions = Vec::new();
     for entry in fs::read_dir(sessions_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("json") {
-            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                sessions.push(stem.to_string());
-            }
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if let Some(name) = session_name(&path).filter(|_| path.is_file()) {
+            sessions.push(name.to_string());
         }
     }
-
+    // A session saved both before and after compression is listed once
+    sessions.sort();
+    sessions.dedup();
     Ok(sessions)
 }
+
+#[command]
+pub async fn save_session(app_handle: tauri::AppHandle, data: SessionData) -> Result<String, String> {
+    let sessions_dir = sessions_dir(&app_handle)?;
+    let file_path = crate::db::blocking(move || write_session(&sessions_dir, &data)).await?;
+
+    Ok(format!("Session saved to {}", file_path.display()))
+}
+
+#[command]
+pub async fn load_session(app_handle: tauri::AppHandle, name: String) -> Result<SessionData, String> {
+    let sessions_dir = sessions_dir(&app_handle)?;
+    crate::db::blocking(move || read_session(&find_session_file(&sessions_dir, &name)?)).await
+}
+
+#[command]
+pub fn list_sessions(app_handle: tauri::AppHandle) -> Result<Vec<String>, String> {
+    session_names(&sessions_dir(&app_handle)?)
+}
+
+/// Write a saved session to `path` as readable JSON, for sharing or inspecting it outside the app
+#[command]
+pub async fn export_session(app_handle: tauri::AppHandle, name: String, path: String) -> Result<String, String> {
+    let sessions_dir = sessions_dir(&app_handle)?;
+    crate::db::blocking(move || {
+        let data: SessionData = read_session(&find_session_file(&sessions_dir, &name)?)?;
+        let mut writer = BufWriter::new(File::create(&path).map_err(|e| e.to_string())?);
+        serde_json::to_writer_pretty(&mut writer, &data).map_err(|e| e.to_string())?;
+        writer.flush().map_err(|e| e.to_string())?;
+        Ok(format!("Session exported to {}", path))
+    })
+    .await
+}
+
+/// The persisted agent settings of session `name` in `sessions_dir`; None for a session that
+/// hasn't been saved yet or was saved before settings existed
+fn stored_settings(sessions_dir: &Path, name: &str) -> Result<Option<SessionSettings>, String> {
+    let file_path = match find_session_file(sessions_dir, name) {
+        Ok(file_path) => file_path,
+        Err(_) => return Ok(None),
+    };
+    let stored: StoredSettings = read_session(&file_path)?;
+
+    Ok(stored.settings)
+}
+
+/// Read only the persisted agent settings of a session (see `stored_settings`)
+pub fn load_session_settings(app_handle: &tauri::AppHandle, name: &str) -> Result<Option<SessionSettings>, String> {
+    stored_settings(&sessions_dir(app_handle)?, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_SESSION: &str = r#"{"name":"old","timestamp":"","chat":null,"main_canvas":null,"left_canvas":null,"visuals":null,"settings":null}"#;
+
+    /// A session with a long chat, which compresses well
+    fn long_chat_session() -> SessionData {
+        let chat: Vec<serde_json::Value> = (0..500)
+            .map(|n| serde_json::json!({ "role": "user", "content": format!("Question {} about the borrow checker", n) }))
+            .collect();
+        SessionData {
+            name: "rust study/1".to_string(),
+            timestamp: "2026-10-16T09:00:00Z".to_string(),
+            chat: Some(serde_json::Value::Array(chat)),
+            main_canvas: Some("<h1>Ownership</h1>".to_string()),
+            left_canvas: None,
+            visuals: None,
+            settings: None,
+        }
+    }
+
+    fn grok_settings_session() -> SessionData {
+        let settings = SessionSettings {
+            provider: AIProvider::Grok,
+            model: Some("grok-4".to_string()),
+            enabled_tools: HashMap::from([("web_search".to_string(), false)]),
+            safe_mode: true,
+            dry_run: false,
+            system_prompt: None,
+        };
+        SessionData {
+            name: "rust study".to_string(),
+            timestamp: "2026-10-16T09:00:00Z".to_string(),
+            chat: None,
+            main_canvas: None,
+            left_canvas: None,
+            visuals: None,
+            settings: Some(settings),
+        }
+    }
+
+    #[test]
+    fn test_legacy_json_is_read() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("rust_study_1.json"), LEGACY_SESSION).unwrap();
+        assert_eq!(read_session::<SessionData>(&find_session_file(dir.path(), "rust study/1").unwrap()).unwrap().name, "old");
+    }
+
+    #[test]
+    fn test_write_session_replaces_legacy_json() {
+        // A plain session from before compression is replaced by the compressed one
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("rust_study_1.json"), LEGACY_SESSION).unwrap();
+        let path = write_session(dir.path(), &long_chat_session()).unwrap();
+        assert_eq!(path.file_name().unwrap(), "rust_study_1.json.gz");
+        assert!(!dir.path().join("rust_study_1.json").exists());
+    }
+
+    #[test]
+    fn test_write_session_compresses() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = long_chat_session();
+        let path = write_session(dir.path(), &data).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < serde_json::to_string(&data).unwrap().len() as u64 / 5);
+    }
+
+    #[test]
+    fn test_compressed_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = long_chat_session();
+        let path = write_session(dir.path(), &data).unwrap();
+        let loaded: SessionData = read_session(&find_session_file(dir.path(), "rust_study_1").unwrap()).unwrap();
+        assert_eq!(loaded.chat, data.chat);
+        assert_eq!(loaded.main_canvas.as_deref(), Some("<h1>Ownership</h1>"));
+        assert!(read_session::<StoredSettings>(&path).unwrap().settings.is_none());
+    }
+
+    #[test]
+    fn test_write_session_leaves_no_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        write_session(dir.path(), &long_chat_session()).unwrap();
+        assert!(!dir.path().join("rust_study_1.json.gz.partial").exists());
+        // One left behind by a crash isn't a session
+        fs::write(dir.path().join("crashed.json.gz.partial"), "half").unwrap();
+        assert_eq!(session_names(dir.path()).unwrap(), vec!["rust_study_1"]);
+    }
+
+    #[test]
+    fn test_session_names() {
+        let dir = tempfile::tempdir().unwrap();
+        write_session(dir.path(), &long_chat_session()).unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a session").unwrap();
+        fs::write(dir.path().join("legacy.json"), "{}").unwrap();
+        assert_eq!(session_names(dir.path()).unwrap(), vec!["legacy", "rust_study_1"]);
+        assert!(find_session_file(dir.path(), "notes.txt").is_err());
+    }
+
+    #[test]
+    fn test_stored_settings_of_an_unsaved_session() {
+        // A session the chat has named but never saved
+        let dir = tempfile::tempdir().unwrap();
+        assert!(stored_settings(dir.path(), "chat-1760600000000").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stored_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        write_session(dir.path(), &grok_settings_session()).unwrap();
+        let restored = stored_settings(dir.path(), "rust study").unwrap().unwrap();
+        assert_eq!(restored.model.as_deref(), Some("grok-4"));
+        assert!(restored.safe_mode && !restored.enabled_tools["web_search"]);
+    }
+
+    #[test]
+    fn test_stored_settings_of_a_broken_file() {
+        // A file that exists but can't be read is still an error
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("broken.json"), "not json").unwrap();
+        assert!(stored_settings(dir.path(), "broken").is_err());
+    }
+}
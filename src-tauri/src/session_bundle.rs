@@ -0,0 +1,433 @@
+/// Encrypted bundles for sharing one research session
+///
+/// `export_session_bundle` packs a saved session (its chat, canvases and visuals) together
+/// with the notes it refers to and the local files those notes and canvases link to, and
+/// encrypts the lot with a passphrase, the same way backups are (see backup.rs). A note
+/// counts as referenced when the chat or a canvas links it (`[[Name]]` or a markdown link)
+/// or mentions its path, e.g. in a read_file call. Only those files go in, never anything
+/// outside the knowledge base, so a collaborator gets the session without the rest of the
+/// vault.
+///
+/// `import_session_bundle` reports first: without `confirm` it only decrypts the bundle and
+/// says where each file would go. Notes land at their original paths when those are free (or
+/// already hold the same file); otherwise they go under `shared-sessions/<session>/` so
+/// nothing is overwritten. The session is saved under its own name, or with " (shared)"
+/// added when that's taken.
+///
+/// A bundle is a zip (`manifest.json`, `session.json` and `vault/<path>` per file) before
+/// encryption.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::{Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::session::SessionData;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SESSION_FILE: &str = "session.json";
+const VAULT_DIR: &str = "vault/";
+const BUNDLE_VERSION: u32 = 1;
+/// Where notes whose path is taken are imported to
+const SHARED_FOLDER: &str = "shared-sessions";
+
+lazy_static::lazy_static! {
+    /// Something that looks like a note path, e.g. "research/rust/ownership.md"
+    static ref NOTE_PATH: Regex = Regex::new(r#"[^\s"'`()<>\[\]|*]+\.md\b"#).unwrap();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub session: String,
+    /// Knowledge base paths of the notes in `vault/`
+    pub notes: Vec<String>,
+    /// Knowledge base paths of the other files in `vault/`, such as images
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionBundle {
+    pub path: String,
+    pub bytes: u64,
+    #[serde(flatten)]
+    pub manifest: BundleManifest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BundledFile {
+    /// Its path in the bundle (and the sender's knowledge base)
+    pub path: String,
+    /// Where it goes in this knowledge base
+    pub dest: String,
+    /// "add", "unchanged" (the same file is already there) or "rename" (the path was taken)
+    pub action: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleImport {
+    /// The session's name in the bundle
+    pub original_session: String,
+    /// The name it's saved under
+    pub session: String,
+    pub created_at: String,
+    pub files: Vec<BundledFile>,
+    /// False when this was only a report
+    pub imported: bool,
+}
+
+/// Every string in a session's chat JSON, including tool call arguments
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => out.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, out)),
+        Value::Object(fields) => fields.values().for_each(|field| collect_strings(field, out)),
+        _ => {}
+    }
+}
+
+/// What in `texts` refers to a note: wiki link names, and KB-relative paths from markdown
+/// links and mentions
+fn mentioned_notes(texts: &[&str]) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut names = BTreeSet::new();
+    let mut paths = BTreeSet::new();
+    for text in texts {
+        for link in crate::note_links::extract_links("", text) {
+            if link.kind == "wiki" {
+                names.insert(link.target);
+            } else {
+                paths.insert(link.target);
+            }
+        }
+        for found in NOTE_PATH.find_iter(text) {
+            let path = found.as_str().trim_start_matches("./");
+            let decoded = urlencoding::decode(path).map(|p| p.into_owned()).unwrap_or_else(|_| path.to_string());
+            paths.insert(decoded);
+        }
+    }
+    (names, paths)
+}
+
+/// `path`'s knowledge base key if it's an existing file inside `kb_root`; symlinks and
+/// `..` can't reach outside it
+fn vault_key(kb_root: &Path, path: &Path) -> Option<String> {
+    let root = kb_root.canonicalize().ok()?;
+    let full = if path.is_absolute() { path.to_path_buf() } else { kb_root.join(path) };
+    let resolved = full.canonicalize().ok()?;
+    if !resolved.is_file() {
+        return None;
+    }
+    let key = resolved.strip_prefix(&root).ok()?.to_string_lossy().replace('\\', "/");
+    Some(key).filter(|k| !k.is_empty())
+}
+
+/// A bundle entry name as a safe relative path: no absolute paths and no `..`
+fn safe_relative(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    if name.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    Some(path.to_path_buf())
+}
+
+/// Where a bundled file goes: its own path if that's free or holds the same bytes, otherwise
+/// under the shared folder for `session`
+fn plan_file(kb_root: &Path, key: &str, bytes: &[u8], session: &str) -> BundledFile {
+    let existing = kb_root.join(key);
+    let (dest, action) = match std::fs::read(&existing) {
+        Err(_) if !existing.exists() => (key.to_string(), "add"),
+        Ok(current) if current == bytes => (key.to_string(), "unchanged"),
+        _ => (format!("{}/{}/{}", SHARED_FOLDER, crate::session::safe_name(session), key), "rename"),
+    };
+    BundledFile { path: key.to_string(), dest, action }
+}
+
+/// `name`, or `name (shared)`, `name (shared 2)`, ... when a session already has it
+fn free_session_name(name: &str, taken: &[String]) -> String {
+    let is_taken = |candidate: &str| taken.contains(&crate::session::safe_name(candidate));
+    if !is_taken(name) {
+        return name.to_string();
+    }
+    (1..)
+        .map(|n| if n == 1 { format!("{} (shared)", name) } else { format!("{} (shared {})", name, n) })
+        .find(|candidate| !is_taken(candidate))
+        .unwrap_or_default()
+}
+
+/// The knowledge base files a session refers to: (notes, other files)
+fn referenced_files(session: &SessionData, kb_root: &Path, resolve_name: &dyn Fn(&str) -> Option<String>) -> (Vec<String>, Vec<String>) {
+    let canvases: Vec<&str> = [&session.main_canvas, &session.left_canvas]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .chain(session.visuals.as_ref().map(|v| v.content.as_str()))
+        .collect();
+    let mut texts = canvases.clone();
+    if let Some(chat) = &session.chat {
+        collect_strings(chat, &mut texts);
+    }
+
+    let (names, paths) = mentioned_notes(&texts);
+    let notes: BTreeSet<String> = names
+        .iter()
+        .filter_map(|name| resolve_name(name))
+        .chain(paths)
+        .filter_map(|path| vault_key(kb_root, Path::new(&path)))
+        .filter(|key| key.ends_with(".md"))
+        .collect();
+
+    // Images and other local files the canvases and the notes link to
+    let mut linked: Vec<PathBuf> = canvases.iter().flat_map(|canvas| crate::attachments::referenced_files(canvas, kb_root)).collect();
+    for note in &notes {
+        let path = kb_root.join(note);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            linked.extend(crate::attachments::referenced_files(&content, path.parent().unwrap_or(kb_root)));
+        }
+    }
+    let files: BTreeSet<String> = linked.iter().filter_map(|path| vault_key(kb_root, path)).filter(|key| !notes.contains(key)).collect();
+    (notes.into_iter().collect(), files.into_iter().collect())
+}
+
+/// The unencrypted bundle
+fn write_bundle(session: &SessionData, manifest: &BundleManifest, kb_root: &Path) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())
+    };
+    add(MANIFEST_FILE, &serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?)?;
+    add(SESSION_FILE, &serde_json::to_vec(session).map_err(|e| e.to_string())?)?;
+    for key in manifest.notes.iter().chain(&manifest.files) {
+        let bytes = std::fs::read(kb_root.join(key)).map_err(|e| format!("Failed to read {}: {}", key, e))?;
+        add(&format!("{}{}", VAULT_DIR, key), &bytes)?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+/// What an unencrypted bundle holds: its manifest, the session and each file's bytes
+fn read_bundle(bytes: Vec<u8>) -> Result<(BundleManifest, SessionData, Vec<(String, Vec<u8>)>), String> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Not a ThinkSpace session bundle: {}", e))?;
+    let mut read_entry = |name: &str| -> Result<Vec<u8>, String> {
+        let mut entry = zip.by_name(name).map_err(|_| format!("The bundle has no {}", name))?;
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        Ok(bytes)
+    };
+    let manifest: BundleManifest = serde_json::from_slice(&read_entry(MANIFEST_FILE)?).map_err(|e| format!("Invalid bundle manifest: {}", e))?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(format!("This bundle was made by a newer ThinkSpace ({})", manifest.app_version));
+    }
+    let session: SessionData = serde_json::from_slice(&read_entry(SESSION_FILE)?).map_err(|e| format!("Invalid session in the bundle: {}", e))?;
+    let mut files = Vec::new();
+    for key in manifest.notes.iter().chain(&manifest.files) {
+        safe_relative(key).ok_or_else(|| format!("Unsafe path in the bundle: {}", key))?;
+        files.push((key.clone(), read_entry(&format!("{}{}", VAULT_DIR, key))?));
+    }
+    Ok((manifest, session, files))
+}
+
+fn require_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.trim().is_empty() {
+        return Err("A passphrase is required to encrypt or open a session bundle".to_string());
+    }
+    Ok(())
+}
+
+// ==================== Commands ====================
+
+/// Encrypt saved session `name`, with the notes and files it refers to, into `dest`
+#[tauri::command]
+pub async fn export_session_bundle(app_handle: tauri::AppHandle, name: String, passphrase: String, dest: String) -> Result<SessionBundle, String> {
+    require_passphrase(&passphrase)?;
+    let sessions_dir = crate::session::sessions_dir(&app_handle)?;
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    // Without the index, wiki links can't be resolved; paths still are
+    let index = crate::note_links::open_index(Some(&app_handle)).ok();
+    crate::db::blocking(move || {
+        let session: SessionData = crate::session::read_session(&crate::session::find_session_file(&sessions_dir, &name)?)?;
+        let resolve_name = |note: &str| index.as_ref().and_then(|conn| crate::note_links::resolve_note_name(conn, note).ok().flatten());
+        let (notes, files) = referenced_files(&session, &kb_root, &resolve_name);
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            session: session.name.clone(),
+            notes,
+            files,
+        };
+        let bundle = write_bundle(&session, &manifest, &kb_root)?;
+
+        let dest = PathBuf::from(&dest);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?);
+        crate::backup::encrypt(&mut Cursor::new(bundle), &mut writer, &passphrase)?;
+        let bytes = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+        tracing::info!("📦 Session '{}' bundled with {} note(s) and {} file(s)", manifest.session, manifest.notes.len(), manifest.files.len());
+        Ok(SessionBundle { path: dest.to_string_lossy().to_string(), bytes, manifest })
+    })
+    .await
+}
+
+/// Open a session bundle; without `confirm` only report where its session and files would go
+#[tauri::command]
+pub async fn import_session_bundle(app_handle: tauri::AppHandle, path: String, passphrase: String, confirm: Option<bool>) -> Result<BundleImport, String> {
+    require_passphrase(&passphrase)?;
+    let sessions_dir = crate::session::sessions_dir(&app_handle)?;
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let import = crate::db::blocking(move || {
+        let mut reader = std::io::BufReader::new(std::fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?);
+        let mut bundle = Vec::new();
+        crate::backup::decrypt(&mut reader, &mut bundle, &passphrase)?;
+        let (manifest, mut session, files) = read_bundle(bundle)?;
+
+        let original = session.name.clone();
+        let name = free_session_name(&original, &crate::session::session_names(&sessions_dir)?);
+        let planned: Vec<(BundledFile, Vec<u8>)> = files.into_iter().map(|(key, bytes)| (plan_file(&kb_root, &key, &bytes, &name), bytes)).collect();
+        let imported = confirm.unwrap_or(false);
+        if imported {
+            for (file, bytes) in planned.iter().filter(|(file, _)| file.action != "unchanged") {
+                let dest = kb_root.join(&file.dest);
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                std::fs::write(&dest, bytes).map_err(|e| format!("Failed to write {}: {}", file.dest, e))?;
+            }
+            session.name = name.clone();
+            crate::session::write_session(&sessions_dir, &session)?;
+            tracing::info!("📦 Imported shared session '{}' as '{}' with {} file(s)", original, name, planned.len());
+        }
+        Ok(BundleImport {
+            original_session: original,
+            session: name,
+            created_at: manifest.created_at,
+            files: planned.into_iter().map(|(file, _)| file).collect(),
+            imported,
+        })
+    })
+    .await?;
+    if import.imported && import.files.iter().any(|f| f.action != "unchanged") {
+        let extra_roots = crate::file_watcher::load_watch_config(&app_handle).extra_paths;
+        crate::kb_index::refresh_in_background(app_handle, extra_roots);
+    }
+    Ok(import)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A knowledge base with two shared notes, an image, a private note and a file outside it
+    struct Sample {
+        kb: tempfile::TempDir,
+        _outside: tempfile::TempDir,
+        session: SessionData,
+    }
+
+    fn sample() -> Sample {
+        let kb = tempfile::tempdir().unwrap();
+        let root = kb.path();
+        std::fs::create_dir_all(root.join("rust/assets")).unwrap();
+        std::fs::write(root.join("rust/Ownership.md"), "# Ownership\n\n![diagram](assets/moves.png)").unwrap();
+        std::fs::write(root.join("rust/assets/moves.png"), [137, 80, 78, 71]).unwrap();
+        std::fs::write(root.join("rust/Lifetimes.md"), "# Lifetimes").unwrap();
+        std::fs::write(root.join("private.md"), "# Not shared").unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.md"), "keys").unwrap();
+
+        let session = SessionData {
+            name: "Rust study".to_string(),
+            timestamp: "2026-10-16T09:00:00Z".to_string(),
+            chat: Some(serde_json::json!([
+                { "role": "user", "content": "Summarise [[Ownership]] and ../secret.md" },
+                { "role": "assistant", "content": "", "toolCalls": [{ "function": { "name": "read_file", "arguments": "{\"path\":\"rust/Lifetimes.md\"}" } }] },
+                { "role": "user", "content": format!("and {}", outside.path().join("secret.md").display()) }
+            ])),
+            main_canvas: Some("See [the note](rust/Ownership.md)".to_string()),
+            left_canvas: None,
+            visuals: None,
+            settings: None,
+        };
+        Sample { kb, _outside: outside, session }
+    }
+
+    fn referenced(sample: &Sample) -> (Vec<String>, Vec<String>) {
+        let resolve = |name: &str| (name == "ownership").then(|| "rust/Ownership.md".to_string());
+        referenced_files(&sample.session, sample.kb.path(), &resolve)
+    }
+
+    /// The sample session bundled and encrypted with "correct horse"
+    fn encrypted_bundle(sample: &Sample) -> Vec<u8> {
+        let (notes, files) = referenced(sample);
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION,
+            app_version: "0.1.0".to_string(),
+            created_at: "2026-10-16T09:00:00Z".to_string(),
+            session: sample.session.name.clone(),
+            notes,
+            files,
+        };
+        let mut encrypted = Vec::new();
+        let bundle = write_bundle(&sample.session, &manifest, sample.kb.path()).unwrap();
+        crate::backup::encrypt(&mut Cursor::new(bundle), &mut encrypted, "correct horse").unwrap();
+        encrypted
+    }
+
+    fn open_bundle(encrypted: &[u8]) -> (BundleManifest, SessionData, Vec<(String, Vec<u8>)>) {
+        let mut plain = Vec::new();
+        crate::backup::decrypt(&mut Cursor::new(encrypted), &mut plain, "correct horse").unwrap();
+        read_bundle(plain).unwrap()
+    }
+
+    #[test]
+    fn test_referenced_files_stay_inside_the_kb() {
+        let (notes, files) = referenced(&sample());
+        assert_eq!(notes, ["rust/Lifetimes.md", "rust/Ownership.md"]);
+        assert_eq!(files, ["rust/assets/moves.png"]);
+    }
+
+    #[test]
+    fn test_bundle_needs_the_passphrase() {
+        let encrypted = encrypted_bundle(&sample());
+        assert!(crate::backup::decrypt(&mut Cursor::new(&encrypted), &mut Vec::new(), "wrong").is_err());
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let (read_manifest, read_session, files) = open_bundle(&encrypted_bundle(&sample()));
+        assert_eq!((read_manifest.notes.len(), read_session.name.as_str()), (2, "Rust study"));
+        assert_eq!(files[2], ("rust/assets/moves.png".to_string(), vec![137, 80, 78, 71]));
+    }
+
+    #[test]
+    fn test_plan_file_moves_taken_paths_aside() {
+        // Into another knowledge base: free paths are used, taken ones moved aside
+        let (_, _, files) = open_bundle(&encrypted_bundle(&sample()));
+        let other = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(other.path().join("rust")).unwrap();
+        std::fs::write(other.path().join("rust/Lifetimes.md"), "# Lifetimes").unwrap();
+        std::fs::write(other.path().join("rust/Ownership.md"), "# My own notes").unwrap();
+        let plans: Vec<BundledFile> = files.iter().map(|(key, bytes)| plan_file(other.path(), key, bytes, "Rust study")).collect();
+        assert_eq!(plans.iter().map(|p| p.action).collect::<Vec<_>>(), ["unchanged", "rename", "add"]);
+        assert_eq!(plans[1].dest, "shared-sessions/Rust_study/rust/Ownership.md");
+    }
+
+    #[test]
+    fn test_free_session_name() {
+        assert_eq!(free_session_name("Rust study", &["Rust_study".to_string()]), "Rust study (shared)");
+        assert_eq!(free_session_name("Rust study", &["Rust_study".to_string(), "Rust_study__shared_".to_string()]), "Rust study (shared 2)");
+    }
+
+    #[test]
+    fn test_safe_relative_rejects_escapes() {
+        assert!(safe_relative("../etc/passwd").is_none() && safe_relative("/etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,314 @@
+/// Static HTML export of the knowledge base
+///
+/// Every note is rendered with the `render_markdown` pipeline (embeds, mermaid, inlined
+/// images) into `<output>/<same path>.html`. Pages share a sidebar listing all notes and a
+/// client-side search over `search-index.js`, which is a plain script so the site also
+/// works when opened straight from disk.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use walkdir::WalkDir;
+
+lazy_static::lazy_static! {
+    static ref WIKI_LINK: Regex = Regex::new(r"\[\[([^\]|#]+)(?:#[^\]|]*)?(?:\|([^\]]+))?\]\]").unwrap();
+    static ref MD_LINK: Regex = Regex::new(r"(\[[^\]]*\]\()([^)\s#]+)\.md(#[^)\s]*)?(\))").unwrap();
+}
+
+/// Characters of note text kept per page in the search index
+const SEARCH_TEXT_CHARS: usize = 20_000;
+
+const STYLE_CSS: &str = "body{margin:0;font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;color:#1f2328;display:flex;min-height:100vh}
+nav{width:280px;flex-shrink:0;background:#f6f8fa;border-right:1px solid #d0d7de;padding:16px;box-sizing:border-box;overflow-y:auto;max-height:100vh;position:sticky;top:0}
+nav h2{font-size:13px;text-transform:uppercase;color:#656d76;margin:16px 0 4px}
+nav ul{list-style:none;padding:0;margin:0}nav li a{display:block;padding:2px 0;color:#1f2328;text-decoration:none;font-size:14px}
+nav li a:hover,nav li a.current{color:#0969da}
+#search{width:100%;padding:6px 8px;box-sizing:border-box;border:1px solid #d0d7de;border-radius:6px}
+#results{margin-top:8px}#results a{display:block;padding:4px 0;font-size:14px;color:#0969da}
+main{flex:1;max-width:860px;padding:32px 48px;line-height:1.6}
+pre{background:#f6f8fa;padding:12px;overflow-x:auto;border-radius:6px}code{font-size:90%}
+blockquote{border-left:4px solid #d0d7de;margin:0;padding:0 16px;color:#424a53}
+table{border-collapse:collapse}td,th{border:1px solid #d0d7de;padding:4px 10px}img{max-width:100%}
+";
+
+const SEARCH_JS: &str = "(function(){
+  var input=document.getElementById('search'),out=document.getElementById('results');
+  var root=document.body.getAttribute('data-root')||'';
+  function esc(s){return s.replace(/[&<>\"]/g,function(c){return{'&':'&amp;','<':'&lt;','>':'&gt;','\"':'&quot;'}[c];});}
+  input.addEventListener('input',function(){
+    var terms=input.value.toLowerCase().split(/\\s+/).filter(Boolean);
+    if(!terms.length){out.innerHTML='';return;}
+    var hits=(window.SEARCH_INDEX||[]).map(function(p){
+      var t=p.title.toLowerCase(),b=p.text.toLowerCase(),score=0;
+      for(var i=0;i<terms.length;i++){
+        if(t.indexOf(terms[i])>=0)score+=10;
+        else if(b.indexOf(terms[i])>=0)score+=1;
+        else return null;
+      }
+      return{p:p,score:score};
+    }).filter(Boolean).sort(function(a,b){return b.score-a.score;}).slice(0,20);
+    out.innerHTML=hits.length?hits.map(function(h){return '<a href=\"'+root+h.p.url+'\">'+esc(h.p.title)+'</a>';}).join(''):'<em>No matches</em>';
+  });
+})();
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteExportResult {
+    pub output_dir: String,
+    pub pages: usize,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SearchEntry {
+    title: String,
+    url: String,
+    text: String,
+}
+
+struct Page {
+    key: String,
+    title: String,
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Output location of a note key: `research/a b.md` -> `research/a b.html`
+fn page_url(key: &str) -> String {
+    format!("{}.html", key.trim_end_matches(".md"))
+}
+
+/// `../` prefix from the page for `key` back to the site root
+fn root_prefix(key: &str) -> String {
+    "../".repeat(key.matches('/').count())
+}
+
+/// Point `[[wiki links]]` and relative `.md` links at the exported `.html` pages
+pub fn rewrite_links_for_site(markdown: &str, from_key: &str, by_name: &HashMap<String, String>) -> String {
+    let prefix = root_prefix(from_key);
+
+    let wiki = WIKI_LINK.replace_all(markdown, |caps: &regex::Captures| {
+        let name = caps[1].trim();
+        let label = caps.get(2).map(|l| l.as_str().trim()).unwrap_or(name);
+        match by_name.get(&name.trim_end_matches(".md").to_lowercase()) {
+            Some(key) => format!("[{}](<{}{}>)", label, prefix, page_url(key)),
+            None => label.to_string(),
+        }
+    });
+
+    MD_LINK
+        .replace_all(&wiki, |caps: &regex::Captures| {
+            if caps[2].contains("://") {
+                return caps[0].to_string();
+            }
+            let anchor = caps.get(3).map(|a| a.as_str()).unwrap_or("");
+            format!("{}{}.html{}{}", &caps[1], &caps[2], anchor, &caps[4])
+        })
+        .into_owned()
+}
+
+fn page_list_html(pages: &[Page], current: Option<&str>, prefix: &str) -> String {
+    let mut folders: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+    for page in pages {
+        let folder = page.key.rsplit_once('/').map(|(f, _)| f).unwrap_or("");
+        folders.entry(folder).or_default().push(page);
+    }
+
+    let mut html = String::new();
+    for (folder, pages) in folders {
+        if !folder.is_empty() {
+            html.push_str(&format!("<h2>{}</h2>", html_escape(folder)));
+        }
+        html.push_str("<ul>");
+        for page in pages {
+            let class = if Some(page.key.as_str()) == current { " class=\"current\"" } else { "" };
+            html.push_str(&format!(
+                "<li><a href=\"{}{}\"{}>{}</a></li>",
+                prefix,
+                html_escape(&page_url(&page.key)),
+                class,
+                html_escape(&page.title)
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    html
+}
+
+fn nav_html(pages: &[Page], current: Option<&str>, prefix: &str) -> String {
+    format!(
+        "<input id=\"search\" type=\"search\" placeholder=\"Search...\"><div id=\"results\"></div>\
+         <h2><a href=\"{}index.html\">Home</a></h2>{}",
+        prefix,
+        page_list_html(pages, current, prefix)
+    )
+}
+
+fn page_html(site_title: &str, title: &str, nav: &str, body: &str, prefix: &str, client_mermaid: bool) -> String {
+    let mermaid = if client_mermaid {
+        "<script type=\"module\">import mermaid from 'https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs';mermaid.initialize({startOnLoad:true});</script>"
+    } else {
+        ""
+    };
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{title} - {site}</title><link rel=\"stylesheet\" href=\"{prefix}style.css\"></head>\
+         <body data-root=\"{prefix}\"><nav>{nav}</nav><main>{body}</main>\
+         <script src=\"{prefix}search-index.js\"></script><script src=\"{prefix}search.js\"></script>{mermaid}</body></html>\n",
+        title = html_escape(title),
+        site = html_escape(site_title),
+        prefix = prefix,
+        nav = nav,
+        body = body,
+        mermaid = mermaid,
+    )
+}
+
+/// The notes under `folder` (a note path selects just that note), or the whole knowledge base
+pub fn collect_notes(kb_root: &Path, folder: Option<&str>) -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = match folder {
+        Some(f) => vec![kb_root.join(f)],
+        None => crate::kb_index::KB_FOLDERS.iter().map(|f| kb_root.join(f)).collect(),
+    };
+
+    let mut notes: Vec<PathBuf> = roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).into_iter().filter_map(|e| e.ok()))
+        .map(|e| e.into_path())
+        .filter(|p| p.is_file() && p.extension().map(|e| e == "md").unwrap_or(false))
+        .collect();
+    notes.sort();
+    notes
+}
+
+/// Frontmatter title, else the first `# ` heading, else the file name
+pub fn title_for(key: &str, content: &str) -> String {
+    let (fm, body) = crate::frontmatter::split_frontmatter(content);
+    fm.and_then(|f| f.title)
+        .or_else(|| body.lines().find(|l| l.starts_with("# ")).map(|l| l[2..].trim().to_string()))
+        .unwrap_or_else(|| key.rsplit('/').next().unwrap_or(key).trim_end_matches(".md").to_string())
+}
+
+/// Render the knowledge base (or one folder of it) to a static site in `output_dir`
+#[tauri::command]
+pub async fn export_site(
+    app_handle: tauri::AppHandle,
+    output_dir: String,
+    folder: Option<String>,
+    title: Option<String>,
+) -> Result<SiteExportResult, String> {
+    if let Some(f) = &folder {
+        if Path::new(f).components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err("Folder must be inside the knowledge base".to_string());
+        }
+    }
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let output = PathBuf::from(&output_dir);
+    let site_title = title.unwrap_or_else(|| "ThinkSpace".to_string());
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let note_paths = collect_notes(&kb_root, folder.as_deref());
+        if note_paths.is_empty() {
+            return Err("No notes to export".to_string());
+        }
+        std::fs::create_dir_all(&output).map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+
+        let pages: Vec<Page> = note_paths
+            .iter()
+            .map(|p| {
+                let key = crate::kb_index::path_key(&kb_root, p);
+                let title = title_for(&key, &std::fs::read_to_string(p).unwrap_or_default());
+                Page { key, title }
+            })
+            .collect();
+        let by_name: HashMap<String, String> = pages
+            .iter()
+            .flat_map(|p| {
+                let stem = p.key.rsplit('/').next().unwrap_or(&p.key).trim_end_matches(".md").to_lowercase();
+                [(stem, p.key.clone()), (p.key.trim_end_matches(".md").to_lowercase(), p.key.clone())]
+            })
+            .collect();
+
+        let index = crate::note_links::open_index(Some(&app_handle)).ok();
+        let mut warnings = Vec::new();
+        let mut search = Vec::new();
+
+        for page in &pages {
+            let rewrite = |markdown: &str| rewrite_links_for_site(markdown, &page.key, &by_name);
+            let rendered = match crate::markdown_render::render_note(&kb_root, &page.key, index.as_ref(), Some(&rewrite)) {
+                Ok(r) => r,
+                Err(e) => {
+                    warnings.push(e);
+                    continue;
+                }
+            };
+            warnings.extend(rendered.warnings.iter().map(|w| format!("{}: {}", page.key, w)));
+
+            let prefix = root_prefix(&page.key);
+            let html = page_html(
+                &site_title,
+                &page.title,
+                &nav_html(&pages, Some(&page.key), &prefix),
+                &rendered.html,
+                &prefix,
+                rendered.needs_client_mermaid,
+            );
+            let dest = output.join(page_url(&page.key));
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&dest, html).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+
+            let content = std::fs::read_to_string(kb_root.join(&page.key)).unwrap_or_default();
+            let (_, body) = crate::frontmatter::split_frontmatter(&content);
+            search.push(SearchEntry {
+                title: page.title.clone(),
+                url: page_url(&page.key),
+                text: body.chars().take(SEARCH_TEXT_CHARS).collect(),
+            });
+        }
+
+        let home_body = format!(
+            "<h1>{}</h1><p>{} notes. Use the sidebar or search to browse.</p>{}",
+            html_escape(&site_title),
+            search.len(),
+            page_list_html(&pages, None, "")
+        );
+        std::fs::write(output.join("index.html"), page_html(&site_title, "Home", &nav_html(&pages, None, ""), &home_body, "", false))
+            .map_err(|e| e.to_string())?;
+        std::fs::write(output.join("style.css"), STYLE_CSS).map_err(|e| e.to_string())?;
+        std::fs::write(output.join("search.js"), SEARCH_JS).map_err(|e| e.to_string())?;
+        let index_json = serde_json::to_string(&search).map_err(|e| e.to_string())?;
+        std::fs::write(output.join("search-index.js"), format!("window.SEARCH_INDEX = {};\n", index_json))
+            .map_err(|e| e.to_string())?;
+
+        tracing::info!("🌐 Exported {} pages to {}", search.len(), output.display());
+        Ok(SiteExportResult {
+            output_dir: output.to_string_lossy().to_string(),
+            pages: search.len(),
+            warnings,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_point_at_exported_pages() {
+        let by_name: HashMap<String, String> = [("ownership".to_string(), "research/rust/Ownership.md".to_string())].into();
+        let markdown = "See [[Ownership#Rules|the rules]], [[Unknown]], [intro](../intro.md#top) and [web](https://x.dev/a.md).";
+
+        let rewritten = rewrite_links_for_site(markdown, "research/notes.md", &by_name);
+        assert_eq!(
+            rewritten,
+            "See [the rules](<../research/rust/Ownership.html>), Unknown, [intro](../intro.html#top) and [web](https://x.dev/a.md)."
+        );
+    }
+}
@@ -0,0 +1,210 @@
+/// Deferred startup of the app's subsystems
+///
+/// setup() used to open both databases, start the content watcher, restore the repository
+/// index and refresh the knowledge base index before the window could show. Now only
+/// thinkspace.toml loads there; everything else starts on a background thread right after,
+/// and each subsystem sends `subsystem-ready` ({ subsystem, error, elapsed_ms }) once it's up
+/// or has failed. A window that loads late asks `get_ready_subsystems` for what it missed,
+/// and backend code that can't run without a subsystem awaits `wait_for`.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+lazy_static::lazy_static! {
+    static ref READY: Mutex<Vec<SubsystemStatus>> = Mutex::new(Vec::new());
+    static ref CHANGED: tokio::sync::Notify = tokio::sync::Notify::new();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Subsystem {
+    /// data.db and knowledge_companion.db with their tables
+    Databases,
+    /// The last repository index, restored from repo_index.db
+    RepoIndex,
+    FileWatcher,
+    KbIndex,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubsystemStatus {
+    pub subsystem: Subsystem,
+    /// Why it failed to start; the app runs on without it
+    pub error: Option<String>,
+    /// Since startup began
+    pub elapsed_ms: u64,
+}
+
+/// The deferred subsystems, in the order `start` brings them up: the databases first since
+/// most commands wait for them, and the watcher before the index refresh so changes made
+/// while the index refreshes aren't missed
+const DEFERRED: [Subsystem; 4] = [Subsystem::Databases, Subsystem::RepoIndex, Subsystem::FileWatcher, Subsystem::KbIndex];
+
+/// Start each of `DEFERRED` with `start_one`, in order, and `mark` each as it finishes. A
+/// failed subsystem doesn't hold back the ones after it.
+fn start_in_order(mut start_one: impl FnMut(Subsystem) -> Result<(), String>, mut mark: impl FnMut(Subsystem, Result<(), String>)) {
+    for subsystem in DEFERRED {
+        let result = start_one(subsystem);
+        mark(subsystem, result);
+    }
+}
+
+fn status(subsystem: Subsystem, started: Instant, result: Result<(), String>) -> SubsystemStatus {
+    SubsystemStatus {
+        subsystem,
+        error: result.err(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Record `subsystem` as started (or failed) and tell the frontend
+fn mark_ready(app_handle: Option<&AppHandle>, subsystem: Subsystem, started: Instant, result: Result<(), String>) {
+    let status = status(subsystem, started, result);
+    match &status.error {
+        Some(e) => tracing::warn!("⚠️ {:?} failed to start after {}ms: {}", subsystem, status.elapsed_ms, e),
+        None => tracing::info!("🚀 {:?} ready after {}ms", subsystem, status.elapsed_ms),
+    }
+    READY.lock().unwrap().push(status.clone());
+    CHANGED.notify_waiters();
+    if let Some(app_handle) = app_handle {
+        if let Some(e) = &status.error {
+            let title = format!("⚠️ {:?} failed to start", subsystem);
+            crate::notifications::notify(app_handle, crate::notifications::Category::Errors, &title, e);
+        }
+        let _ = app_handle.emit_all("subsystem-ready", status);
+    }
+}
+
+/// Whether `subsystem` has finished starting, successfully or not
+pub fn is_ready(subsystem: Subsystem) -> bool {
+    READY.lock().unwrap().iter().any(|s| s.subsystem == subsystem)
+}
+
+/// Wait until `subsystem` has finished starting
+pub async fn wait_for(subsystem: Subsystem) {
+    loop {
+        // Registered before the check so a mark_ready in between isn't missed
+        let changed = CHANGED.notified();
+        if is_ready(subsystem) {
+            return;
+        }
+        changed.await;
+    }
+}
+
+fn init_databases(app_data: &Path) -> Result<(), String> {
+    // Through the pool, so the connections commands get later skip creating the tables
+    crate::db::pooled(&app_data.join("data.db"), crate::db::init_db)
+        .and_then(|_| crate::db::pooled(&app_data.join("knowledge_companion.db"), crate::minimax_api::init_kc_database))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Start only the databases, for the CLI: it has no window to watch files or refresh
+/// indexes for
+pub fn start_headless(app_data: &Path) {
+    let started = Instant::now();
+    mark_ready(None, Subsystem::Databases, started, init_databases(app_data));
+}
+
+/// Start everything setup() no longer waits for, in order of how soon the UI needs it
+pub fn start(app_handle: AppHandle, app_data: PathBuf) {
+    let started = Instant::now();
+    std::thread::spawn(move || {
+        start_in_order(
+            |subsystem| match subsystem {
+                Subsystem::Databases => init_databases(&app_data),
+                // Reuse the last repository index instead of re-walking on every launch
+                Subsystem::RepoIndex => {
+                    crate::commands::restore_repo_index(&app_handle);
+                    Ok(())
+                }
+                Subsystem::FileWatcher => crate::file_watcher::setup_file_watcher(&app_handle).map_err(|e| e.to_string()),
+                Subsystem::KbIndex => {
+                    let extra_roots = crate::file_watcher::load_watch_config(&app_handle).extra_paths;
+                    crate::kb_index::refresh(&app_handle, &extra_roots).map(|_| ())
+                }
+            },
+            |subsystem, result| mark_ready(Some(&app_handle), subsystem, started, result),
+        );
+
+        crate::backup::start_background(app_handle.clone());
+        crate::reminders::start_background(app_handle.clone());
+        crate::digest::start_background(app_handle.clone());
+        crate::chat_bridge::start_background(app_handle.clone());
+        crate::openai_compat::start_background(app_handle.clone());
+        crate::clipboard_watch::start_background(app_handle.clone());
+        // After the index, so notes the first sync brings in are indexed as they land
+        crate::vault_sync::start_background(app_handle);
+    });
+}
+
+/// Subsystems that have finished starting so far
+#[tauri::command]
+pub fn get_ready_subsystems() -> Vec<SubsystemStatus> {
+    READY.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for() {
+        let started = Instant::now();
+        let waiting = tokio::spawn(wait_for(Subsystem::Databases));
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        mark_ready(None, Subsystem::FileWatcher, started, Err("No content folders".to_string()));
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        mark_ready(None, Subsystem::Databases, started, Ok(()));
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        // Already started subsystems don't wait, failed ones included
+        wait_for(Subsystem::FileWatcher).await;
+
+        let ready = get_ready_subsystems();
+        assert_eq!(ready.iter().map(|s| s.subsystem).collect::<Vec<_>>(), vec![Subsystem::FileWatcher, Subsystem::Databases]);
+        assert_eq!(ready[0].error.as_deref(), Some("No content folders"));
+        assert!(!is_ready(Subsystem::KbIndex));
+    }
+
+    #[test]
+    fn test_deferred_subsystems_start_in_order() {
+        let mut started = Vec::new();
+        let mut marked = Vec::new();
+        start_in_order(
+            |subsystem| {
+                started.push(subsystem);
+                match subsystem {
+                    Subsystem::FileWatcher => Err("No content folders".to_string()),
+                    _ => Ok(()),
+                }
+            },
+            |subsystem, result| marked.push((subsystem, result)),
+        );
+
+        assert_eq!(started, vec![Subsystem::Databases, Subsystem::RepoIndex, Subsystem::FileWatcher, Subsystem::KbIndex]);
+        // Each is marked as it finishes, and the index refresh still runs after the watcher failed
+        assert_eq!(marked.iter().map(|(s, _)| *s).collect::<Vec<_>>(), started);
+        assert_eq!(marked[2].1, Err("No content folders".to_string()));
+        assert_eq!(marked[3].1, Ok(()));
+    }
+
+    #[test]
+    fn test_ready_event_payload() {
+        let ready = status(Subsystem::RepoIndex, Instant::now(), Ok(()));
+        let payload = serde_json::to_value(&ready).unwrap();
+        assert_eq!(payload["subsystem"], "repo-index");
+        assert!(payload["error"].is_null());
+        assert!(payload["elapsed_ms"].is_u64());
+
+        let failed = status(Subsystem::KbIndex, Instant::now(), Err("locked".to_string()));
+        assert_eq!(serde_json::to_value(&failed).unwrap()["error"], "locked");
+    }
+}
@@ -0,0 +1,346 @@
+/// Study session tracking
+///
+/// One session runs at a time, optionally split into pomodoro focus/break intervals.
+/// While it runs a `study-timer` event is emitted every second and `study-phase` on each
+/// focus/break switch. Ended sessions are stored in `knowledge_companion.db` and their
+/// focus time is added to `progress.hours_learned` (and the daily streak).
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use rusqlite::{params, Connection};
+use tauri::Manager;
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_SESSION: Mutex<Option<ActiveSession>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Pomodoro {
+    pub focus_minutes: u32,
+    pub break_minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveSession {
+    pub id: String,
+    pub topic: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub pomodoro: Option<Pomodoro>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    Focus,
+    Break,
+}
+
+/// Payload of the `study-timer` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimerState {
+    pub session_id: String,
+    pub topic: String,
+    pub elapsed_secs: u64,
+    pub focus_secs: u64,
+    pub phase: Phase,
+    /// Seconds left in the current pomodoro interval (None without pomodoro)
+    pub phase_remaining_secs: Option<u64>,
+    pub pomodoros_completed: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudySession {
+    pub id: String,
+    pub topic: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_secs: i64,
+    pub focus_secs: i64,
+    pub pomodoros_completed: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicTime {
+    pub topic: String,
+    pub sessions: i64,
+    pub focus_secs: i64,
+}
+
+pub fn init_study_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS study_sessions (
+            id TEXT PRIMARY KEY,
+            topic TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            ended_at TEXT,
+            duration_secs INTEGER DEFAULT 0,
+            focus_secs INTEGER DEFAULT 0,
+            pomodoros_completed INTEGER DEFAULT 0,
+            focus_minutes INTEGER,
+            break_minutes INTEGER
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_study_sessions_topic ON study_sessions(topic)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Timer state `elapsed_secs` into a session
+pub fn timer_state(session: &ActiveSession, elapsed_secs: u64) -> TimerState {
+    let (phase, focus_secs, phase_remaining_secs, pomodoros_completed) = match session.pomodoro {
+        Some(p) if p.focus_minutes > 0 => {
+            let focus = p.focus_minutes as u64 * 60;
+            let cycle = focus + p.break_minutes as u64 * 60;
+            let cycles = elapsed_secs / cycle;
+            let into_cycle = elapsed_secs % cycle;
+            if into_cycle < focus {
+                (Phase::Focus, cycles * focus + into_cycle, Some(focus - into_cycle), cycles as u32)
+            } else {
+                (Phase::Break, (cycles + 1) * focus, Some(cycle - into_cycle), cycles as u32 + 1)
+            }
+        }
+        _ => (Phase::Focus, elapsed_secs, None, 0),
+    };
+
+    TimerState {
+        session_id: session.id.clone(),
+        topic: session.topic.clone(),
+        elapsed_secs,
+        focus_secs,
+        phase,
+        phase_remaining_secs,
+        pomodoros_completed,
+    }
+}
+
+fn elapsed_secs(session: &ActiveSession) -> u64 {
+    (chrono::Utc::now() - session.started_at).num_seconds().max(0) as u64
+}
+
+/// Add `focus_secs` to hours_learned and bump the streak on the first activity of a day
+pub fn record_study_time(conn: &Connection, focus_secs: u64, today: chrono::NaiveDate) -> rusqlite::Result<()> {
+    let last_active: Option<String> = conn
+        .query_row("SELECT last_active FROM progress WHERE id = 1", [], |row| row.get(0))
+        .unwrap_or(None);
+    let last_day = last_active.and_then(|d| chrono::NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
+
+    let streak_sql = match last_day {
+        Some(day) if day == today => "streak",
+        Some(day) if day.succ_opt() == Some(today) => "streak + 1",
+        _ => "1",
+    };
+    conn.execute(
+        &format!(
+            "UPDATE progress SET hours_learned = hours_learned + ?1, streak = {}, last_active = ?2 WHERE id = 1",
+            streak_sql
+        ),
+        params![focus_secs as f64 / 3600.0, today.format("%Y-%m-%d").to_string()],
+    )?;
+    Ok(())
+}
+
+fn spawn_timer(app_handle: tauri::AppHandle, session_id: String) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_phase = Phase::Focus;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+
+            let current = ACTIVE_SESSION.lock().unwrap().clone();
+            let session = match current {
+                Some(s) if s.id == session_id => s,
+                _ => break,
+            };
+            let state = timer_state(&session, elapsed_secs(&session));
+            if state.phase != last_phase {
+                last_phase = state.phase;
+                let _ = app_handle.emit_all("study-phase", &state);
+            }
+            let _ = app_handle.emit_all("study-timer", &state);
+        }
+    });
+}
+
+// ==================== Commands ====================
+
+/// Start a session on `topic`; pass both interval lengths to enable pomodoro timing.
+/// Any session still running is ended first.
+#[tauri::command]
+pub async fn start_study_session(
+    app_handle: tauri::AppHandle,
+    topic: String,
+    focus_minutes: Option<u32>,
+    break_minutes: Option<u32>,
+) -> Result<ActiveSession, String> {
+    if topic.trim().is_empty() {
+        return Err("Topic is required".to_string());
+    }
+    let already_running = ACTIVE_SESSION.lock().unwrap().is_some();
+    if already_running {
+        end_study_session(app_handle.clone()).await?;
+    }
+
+    let pomodoro = match (focus_minutes, break_minutes) {
+        (Some(focus_minutes), break_minutes) if focus_minutes > 0 => Some(Pomodoro {
+            focus_minutes,
+            break_minutes: break_minutes.unwrap_or(5),
+        }),
+        _ => None,
+    };
+    let session = ActiveSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        topic: topic.trim().to_string(),
+        started_at: chrono::Utc::now(),
+        pomodoro,
+    };
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    conn.execute(
+        "INSERT INTO study_sessions (id, topic, started_at, focus_minutes, break_minutes) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            session.id,
+            session.topic,
+            session.started_at.to_rfc3339(),
+            pomodoro.map(|p| p.focus_minutes),
+            pomodoro.map(|p| p.break_minutes)
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    *ACTIVE_SESSION.lock().unwrap() = Some(session.clone());
+    spawn_timer(app_handle.clone(), session.id.clone());
+    let _ = app_handle.emit_all("study-session-started", &session);
+    tracing::info!("⏱️ Study session started: {}", session.topic);
+
+    Ok(session)
+}
+
+/// End the running session, store its totals and credit the focus time to progress
+#[tauri::command]
+pub async fn end_study_session(app_handle: tauri::AppHandle) -> Result<StudySession, String> {
+    let session = ACTIVE_SESSION.lock().unwrap().take().ok_or("No study session is running")?;
+    let state = timer_state(&session, elapsed_secs(&session));
+    let ended_at = chrono::Utc::now().to_rfc3339();
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    conn.execute(
+        "UPDATE study_sessions SET ended_at = ?1, duration_secs = ?2, focus_secs = ?3, pomodoros_completed = ?4 WHERE id = ?5",
+        params![ended_at, state.elapsed_secs as i64, state.focus_secs as i64, state.pomodoros_completed, session.id],
+    )
+    .map_err(|e| e.to_string())?;
+    record_study_time(&conn, state.focus_secs, chrono::Local::now().date_naive()).map_err(|e| e.to_string())?;
+
+    let result = StudySession {
+        id: session.id,
+        topic: session.topic,
+        started_at: session.started_at.to_rfc3339(),
+        ended_at: Some(ended_at),
+        duration_secs: state.elapsed_secs as i64,
+        focus_secs: state.focus_secs as i64,
+        pomodoros_completed: state.pomodoros_completed as i64,
+    };
+    let _ = app_handle.emit_all("study-session-ended", &result);
+    tracing::info!("⏱️ Study session ended: {} ({} min focused)", result.topic, result.focus_secs / 60);
+
+    Ok(result)
+}
+
+/// Current timer state, if a session is running
+#[tauri::command]
+pub async fn get_active_study_session() -> Result<Option<TimerState>, String> {
+    Ok(ACTIVE_SESSION.lock().unwrap().as_ref().map(|s| timer_state(s, elapsed_secs(s))))
+}
+
+/// Focus time per topic over the last `days` days (all time if omitted)
+#[tauri::command]
+pub async fn get_study_time_by_topic(app_handle: tauri::AppHandle, days: Option<i64>) -> Result<Vec<TopicTime>, String> {
+    let since = days
+        .map(|d| (chrono::Utc::now() - chrono::Duration::days(d)).to_rfc3339())
+        .unwrap_or_default();
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT topic, COUNT(*), SUM(focus_secs) FROM study_sessions
+             WHERE ended_at IS NOT NULL AND started_at >= ?1
+             GROUP BY topic ORDER BY SUM(focus_secs) DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![since], |row| {
+            Ok(TopicTime { topic: row.get(0)?, sessions: row.get(1)?, focus_secs: row.get(2)? })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Most recent finished sessions
+#[tauri::command]
+pub async fn list_study_sessions(app_handle: tauri::AppHandle, limit: Option<i64>) -> Result<Vec<StudySession>, String> {
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, topic, started_at, ended_at, duration_secs, focus_secs, pomodoros_completed
+             FROM study_sessions WHERE ended_at IS NOT NULL ORDER BY started_at DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit.unwrap_or(50)], |row| {
+            Ok(StudySession {
+                id: row.get(0)?,
+                topic: row.get(1)?,
+                started_at: row.get(2)?,
+                ended_at: row.get(3)?,
+                duration_secs: row.get(4)?,
+                focus_secs: row.get(5)?,
+                pomodoros_completed: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pomodoro_timer_state() {
+        let session = ActiveSession {
+            id: "s".to_string(),
+            topic: "Rust".to_string(),
+            started_at: chrono::Utc::now(),
+            pomodoro: Some(Pomodoro { focus_minutes: 25, break_minutes: 5 }),
+        };
+
+        let focus = timer_state(&session, 10 * 60);
+        assert_eq!((focus.phase, focus.focus_secs, focus.phase_remaining_secs), (Phase::Focus, 600, Some(900)));
+
+        let on_break = timer_state(&session, 27 * 60);
+        assert_eq!((on_break.phase, on_break.focus_secs, on_break.pomodoros_completed), (Phase::Break, 1500, 1));
+
+        let second = timer_state(&session, 40 * 60);
+        assert_eq!((second.phase, second.focus_secs, second.pomodoros_completed), (Phase::Focus, 2100, 1));
+    }
+
+    #[test]
+    fn test_record_study_time_updates_streak() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = crate::minimax_api::init_kc_database(&dir.path().join("kc.db")).unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        record_study_time(&conn, 1800, day).unwrap();
+        record_study_time(&conn, 1800, day).unwrap();
+        record_study_time(&conn, 3600, day.succ_opt().unwrap()).unwrap();
+
+        let (hours, streak): (f64, i32) = conn
+            .query_row("SELECT hours_learned, streak FROM progress WHERE id = 1", [], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert!((hours - 2.0).abs() < 1e-9);
+        assert_eq!(streak, 2);
+    }
+}
@@ -0,0 +1,364 @@
+/// Language-aware symbol extraction for the repo indexer
+///
+/// Parses Rust, TypeScript/JavaScript and Python sources with tree-sitter and
+/// records functions, types and methods in the `symbols` table of the index
+/// database, so "where is X defined" can be answered without reading files.
+
+use serde::{Deserialize, Serialize};
+use rusqlite::{params, Connection};
+use anyhow::{Result, Context};
+use tree_sitter::{Language, Node, Parser};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: String,           // "function", "method", "struct", "enum", "trait", "class", "interface", "type"
+    pub parent: Option<String>, // Enclosing impl/class, e.g. "MinimaxAgent"
+    pub relative_path: String,
+    pub line: usize,            // 1-based
+    pub end_line: usize,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SourceLanguage {
+    Rust,
+    TypeScript,
+    Tsx,
+    JavaScript,
+    Python,
+}
+
+impl SourceLanguage {
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Self::JavaScript),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::language(),
+            Self::TypeScript => tree_sitter_typescript::language_typescript(),
+            Self::Tsx => tree_sitter_typescript::language_tsx(),
+            Self::JavaScript => tree_sitter_javascript::language(),
+            Self::Python => tree_sitter_python::language(),
+        }
+    }
+
+    /// Map a syntax node kind to a symbol kind for this language
+    fn symbol_kind(self, node_kind: &str, in_container: bool) -> Option<&'static str> {
+        match self {
+            Self::Rust => match node_kind {
+                "function_item" if in_container => Some("method"),
+                "function_item" => Some("function"),
+                "struct_item" => Some("struct"),
+                "enum_item" => Some("enum"),
+                "trait_item" => Some("trait"),
+                "type_item" => Some("type"),
+                "mod_item" => Some("module"),
+                _ => None,
+            },
+            Self::TypeScript | Self::Tsx | Self::JavaScript => match node_kind {
+                "function_declaration" | "generator_function_declaration" => Some("function"),
+                "class_declaration" | "abstract_class_declaration" => Some("class"),
+                "method_definition" => Some("method"),
+                "interface_declaration" => Some("interface"),
+                "type_alias_declaration" => Some("type"),
+                "enum_declaration" => Some("enum"),
+                _ => None,
+            },
+            Self::Python => match node_kind {
+                "function_definition" if in_container => Some("method"),
+                "function_definition" => Some("function"),
+                "class_definition" => Some("class"),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Whether symbols can be extracted for files with this extension
+pub fn supports_extension(ext: &str) -> bool {
+    SourceLanguage::from_extension(ext).is_some()
+}
+
+/// Extract top-level and nested symbols from a source file
+pub fn extract_symbols(extension: &str, relative_path: &str, source: &str) -> Vec<Symbol> {
+    let language = match SourceLanguage::from_extension(extension) {
+        Some(lang) => lang,
+        None => return Vec::new(),
+    };
+
+    let mut parser = Parser::new();
+    if parser.set_language(language.grammar()).is_err() {
+        tracing::warn!("⚠️ Failed to load tree-sitter grammar for .{}", extension);
+        return Vec::new();
+    }
+
+    let tree = match parser.parse(source, None) {
+        Some(tree) => tree,
+        None => return Vec::new(),
+    };
+
+    let mut symbols = Vec::new();
+    collect_symbols(tree.root_node(), source.as_bytes(), language, None, relative_path, &mut symbols);
+    symbols
+}
+
+fn collect_symbols(
+    node: Node,
+    source: &[u8],
+    language: SourceLanguage,
+    container: Option<&str>,
+    relative_path: &str,
+    out: &mut Vec<Symbol>,
+) {
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        let kind = child.kind();
+
+        // Containers whose children become methods of a named parent
+        let container_name = match (language, kind) {
+            (SourceLanguage::Rust, "impl_item") => child
+                .child_by_field_name("type")
+                .and_then(|n| n.utf8_text(source).ok())
+                .map(base_type_name),
+            (SourceLanguage::Rust, "trait_item")
+            | (SourceLanguage::Python, "class_definition")
+            | (_, "class_declaration")
+            | (_, "abstract_class_declaration") => child
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source).ok())
+                .map(|s| s.to_string()),
+            _ => None,
+        };
+
+        if let Some(symbol_kind) = language.symbol_kind(kind, container.is_some()) {
+            if let Some(name) = child.child_by_field_name("name").and_then(|n| n.utf8_text(source).ok()) {
+                out.push(Symbol {
+                    name: name.to_string(),
+                    kind: symbol_kind.to_string(),
+                    parent: container.map(|c| c.to_string()),
+                    relative_path: relative_path.to_string(),
+                    line: child.start_position().row + 1,
+                    end_line: child.end_position().row + 1,
+                    signature: signature_line(child, source),
+                });
+            }
+        }
+
+        // Arrow functions assigned to consts: `const handler = () => {}`
+        if matches!(language, SourceLanguage::TypeScript | SourceLanguage::Tsx | SourceLanguage::JavaScript)
+            && kind == "variable_declarator"
+        {
+            let is_function = child
+                .child_by_field_name("value")
+                .map(|v| matches!(v.kind(), "arrow_function" | "function" | "function_expression"))
+                .unwrap_or(false);
+            if is_function {
+                if let Some(name) = child.child_by_field_name("name").and_then(|n| n.utf8_text(source).ok()) {
+                    out.push(Symbol {
+                        name: name.to_string(),
+                        kind: "function".to_string(),
+                        parent: container.map(|c| c.to_string()),
+                        relative_path: relative_path.to_string(),
+                        line: child.start_position().row + 1,
+                        end_line: child.end_position().row + 1,
+                        signature: signature_line(child, source),
+                    });
+                }
+            }
+        }
+
+        let next_container = container_name.as_deref().or(container);
+        collect_symbols(child, source, language, next_container, relative_path, out);
+    }
+}
+
+/// `Foo<T>` / `crate::Foo` -> `Foo`
+fn base_type_name(type_text: &str) -> String {
+    let without_generics = type_text.split('<').next().unwrap_or(type_text);
+    without_generics
+        .rsplit("::")
+        .next()
+        .unwrap_or(without_generics)
+        .trim()
+        .to_string()
+}
+
+fn signature_line(node: Node, source: &[u8]) -> String {
+    let text = node.utf8_text(source).unwrap_or("");
+    let first_line = text.lines().next().unwrap_or("").trim();
+    first_line.chars().take(200).collect()
+}
+
+// ==================== Persistence ====================
+
+pub fn init_symbols_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS symbols (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            repo_root TEXT NOT NULL,
+            relative_path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            parent TEXT,
+            line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            signature TEXT
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols(name)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Replace all symbols recorded for a file
+pub fn replace_file_symbols(conn: &Connection, repo_root: &str, relative_path: &str, symbols: &[Symbol]) -> Result<()> {
+    delete_file_symbols(conn, repo_root, relative_path)?;
+
+    let mut stmt = conn.prepare_cached(
+        "INSERT INTO symbols (repo_root, relative_path, name, kind, parent, line, end_line, signature)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+
+    for symbol in symbols {
+        stmt.execute(params![
+            repo_root,
+            relative_path,
+            symbol.name,
+            symbol.kind,
+            symbol.parent,
+            symbol.line as i64,
+            symbol.end_line as i64,
+            symbol.signature
+        ])?;
+    }
+
+    Ok(())
+}
+
+pub fn delete_file_symbols(conn: &Connection, repo_root: &str, relative_path: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM symbols WHERE repo_root = ?1 AND relative_path = ?2",
+        params![repo_root, relative_path],
+    )?;
+    Ok(())
+}
+
+/// Find symbols by name. Accepts qualified names like `MinimaxAgent::chat` or `Foo.bar`.
+/// Falls back to a substring match when there is no exact hit.
+pub fn find_symbols(conn: &Connection, query: &str, repo_root: Option<&str>, limit: usize) -> Result<Vec<(String, Symbol)>> {
+    let query = query.trim();
+    let (parent, name) = match query.rsplit_once("::").or_else(|| query.rsplit_once('.')) {
+        Some((parent, name)) => (Some(base_type_name(parent)), name.to_string()),
+        None => (None, query.to_string()),
+    };
+
+    let exact = query_symbols(
+        conn,
+        "name = ?1 AND (?2 IS NULL OR parent = ?2) AND (?3 IS NULL OR repo_root = ?3)",
+        &name,
+        parent.as_deref(),
+        repo_root,
+        limit,
+    )?;
+
+    if !exact.is_empty() {
+        return Ok(exact);
+    }
+
+    let pattern = format!("%{}%", name);
+    query_symbols(
+        conn,
+        "name LIKE ?1 AND (?2 IS NULL OR parent = ?2) AND (?3 IS NULL OR repo_root = ?3)",
+        &pattern,
+        parent.as_deref(),
+        repo_root,
+        limit,
+    )
+}
+
+fn query_symbols(
+    conn: &Connection,
+    condition: &str,
+    name: &str,
+    parent: Option<&str>,
+    repo_root: Option<&str>,
+    limit: usize,
+) -> Result<Vec<(String, Symbol)>> {
+    let sql = format!(
+        "SELECT repo_root, relative_path, name, kind, parent, line, end_line, signature
+         FROM symbols WHERE {} ORDER BY length(name), relative_path LIMIT ?4",
+        condition
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare symbol query")?;
+    let rows = stmt.query_map(params![name, parent, repo_root, limit as i64], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            Symbol {
+                relative_path: row.get(1)?,
+                name: row.get(2)?,
+                kind: row.get(3)?,
+                parent: row.get(4)?,
+                line: row.get::<_, i64>(5)? as usize,
+                end_line: row.get::<_, i64>(6)? as usize,
+                signature: row.get::<_, Option<String>>(7)?.unwrap_or_default(),
+            },
+        ))
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_rust_methods_with_impl_parent() {
+        let source = "pub struct MinimaxAgent;\n\nimpl MinimaxAgent {\n    pub async fn chat(&mut self) {}\n}\n\nfn helper() {}\n";
+        let symbols = extract_symbols("rs", "src/agent.rs", source);
+
+        let chat = symbols.iter().find(|s| s.name == "chat").expect("chat should be extracted");
+        assert_eq!(chat.kind, "method");
+        assert_eq!(chat.parent.as_deref(), Some("MinimaxAgent"));
+        assert_eq!(chat.line, 4);
+
+        assert!(symbols.iter().any(|s| s.name == "MinimaxAgent" && s.kind == "struct"));
+        assert!(symbols.iter().any(|s| s.name == "helper" && s.kind == "function" && s.parent.is_none()));
+    }
+
+    #[test]
+    fn test_extract_python_class_methods() {
+        let source = "class Indexer:\n    def run(self):\n        pass\n\ndef main():\n    pass\n";
+        let symbols = extract_symbols("py", "indexer.py", source);
+
+        assert!(symbols.iter().any(|s| s.name == "Indexer" && s.kind == "class"));
+        assert!(symbols.iter().any(|s| s.name == "run" && s.parent.as_deref() == Some("Indexer")));
+        assert!(symbols.iter().any(|s| s.name == "main" && s.kind == "function"));
+    }
+
+    #[test]
+    fn test_base_type_name() {
+        assert_eq!(base_type_name("Foo<T>"), "Foo");
+        assert_eq!(base_type_name("crate::tkg::TemporalKnowledgeGraph"), "TemporalKnowledgeGraph");
+    }
+}
@@ -0,0 +1,228 @@
+/// User-defined note templates
+///
+/// Templates are markdown files in `<app data>/templates/`. `{{name}}` placeholders are
+/// filled from caller-supplied variables plus built-ins (`date`, `time`, `weekday`,
+/// `month`, `year`, `title`); `{{name|fallback}}` supplies a default.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref PLACEHOLDER: Regex = Regex::new(r"\{\{\s*([A-Za-z0-9_\-]+)\s*(?:\|([^}]*))?\}\}").unwrap();
+}
+
+/// Seeded into an empty templates folder so users have something to adapt
+const BUILTIN_TEMPLATES: [(&str, &str); 3] = [
+    (
+        "study-guide",
+        "---\ntitle: \"{{title}}\"\ncreated: {{date}}\ntags: [study-guide]\nstatus: draft\n---\n\n# {{title}}\n\n\
+         ## Overview\n\n## Key Concepts\n\n## Examples\n\n## Practice Questions\n\n## Resources\n",
+    ),
+    (
+        "research-report",
+        "---\ntitle: \"{{title}}\"\ncreated: {{date}}\ntags: [research]\nstatus: draft\n---\n\n# {{title}}\n\n\
+         ## Question\n\n{{question|What are we trying to find out?}}\n\n## Summary\n\n## Findings\n\n## Sources\n\n## Open Questions\n",
+    ),
+    (
+        "project-brief",
+        "---\ntitle: \"{{title}}\"\ncreated: {{date}}\ntags: [project]\nstatus: planning\n---\n\n# {{title}}\n\n\
+         ## Problem\n\n## Goals\n\n## Scope\n\n## Milestones\n\n## Risks\n",
+    ),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub placeholders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedFromTemplate {
+    pub path: String,
+    /// Placeholders with no value or fallback; they were left empty
+    pub missing: Vec<String>,
+}
+
+pub fn templates_dir(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
+    let data_dir = match app_handle {
+        Some(handle) => handle.path_resolver().app_data_dir(),
+        None => dirs::data_dir().map(|d| d.join("com.thinkspace.app")),
+    }?;
+    Some(data_dir.join("templates"))
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ') {
+        return Err(format!("Invalid template name '{}'", name));
+    }
+    Ok(())
+}
+
+fn template_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = name.trim_end_matches(".md");
+    validate_name(name)?;
+    Ok(dir.join(format!("{}.md", name)))
+}
+
+/// Load a template by name, if the user has one
+pub fn load_template(app_handle: Option<&tauri::AppHandle>, name: &str) -> Option<String> {
+    let dir = templates_dir(app_handle)?;
+    std::fs::read_to_string(template_path(&dir, name).ok()?).ok()
+}
+
+/// Distinct placeholder names in order of appearance
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for caps in PLACEHOLDER.captures_iter(template) {
+        if !names.iter().any(|n| n == &caps[1]) {
+            names.push(caps[1].to_string());
+        }
+    }
+    names
+}
+
+/// Date/time variables available to every template
+pub fn builtin_vars(now: chrono::NaiveDateTime) -> HashMap<String, String> {
+    HashMap::from([
+        ("date".to_string(), now.format("%Y-%m-%d").to_string()),
+        ("time".to_string(), now.format("%H:%M").to_string()),
+        ("weekday".to_string(), now.format("%A").to_string()),
+        ("month".to_string(), now.format("%B").to_string()),
+        ("year".to_string(), now.format("%Y").to_string()),
+    ])
+}
+
+/// Substitute placeholders, returning the text and the names that had no value
+pub fn render(template: &str, vars: &HashMap<String, String>) -> (String, Vec<String>) {
+    let mut missing = Vec::new();
+    let rendered = PLACEHOLDER.replace_all(template, |caps: &regex::Captures| {
+        match (vars.get(&caps[1]), caps.get(2)) {
+            (Some(value), _) => value.clone(),
+            (None, Some(fallback)) => fallback.as_str().trim().to_string(),
+            (None, None) => {
+                if !missing.iter().any(|m: &String| m == &caps[1]) {
+                    missing.push(caps[1].to_string());
+                }
+                String::new()
+            }
+        }
+    });
+    (rendered.into_owned(), missing)
+}
+
+fn ensure_seeded(dir: &Path) -> Result<(), String> {
+    let is_empty = std::fs::read_dir(dir).map(|mut d| d.next().is_none()).unwrap_or(true);
+    if !is_empty {
+        return Ok(());
+    }
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    for (name, content) in BUILTIN_TEMPLATES {
+        std::fs::write(dir.join(format!("{}.md", name)), content).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// ==================== Commands ====================
+
+#[tauri::command]
+pub async fn list_templates(app_handle: tauri::AppHandle) -> Result<Vec<TemplateInfo>, String> {
+    let dir = templates_dir(Some(&app_handle)).ok_or("Could not find app data dir")?;
+    ensure_seeded(&dir)?;
+
+    let mut templates: Vec<TemplateInfo> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "md").unwrap_or(false))
+        .filter_map(|p| {
+            let content = std::fs::read_to_string(&p).ok()?;
+            Some(TemplateInfo {
+                name: p.file_stem()?.to_string_lossy().to_string(),
+                placeholders: placeholders(&content),
+            })
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+#[tauri::command]
+pub async fn get_template(app_handle: tauri::AppHandle, name: String) -> Result<String, String> {
+    load_template(Some(&app_handle), &name).ok_or_else(|| format!("Template '{}' not found", name))
+}
+
+#[tauri::command]
+pub async fn save_template(app_handle: tauri::AppHandle, name: String, content: String) -> Result<(), String> {
+    let dir = templates_dir(Some(&app_handle)).ok_or("Could not find app data dir")?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    std::fs::write(template_path(&dir, &name)?, content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_template(app_handle: tauri::AppHandle, name: String) -> Result<(), String> {
+    let dir = templates_dir(Some(&app_handle)).ok_or("Could not find app data dir")?;
+    std::fs::remove_file(template_path(&dir, &name)?).map_err(|e| e.to_string())
+}
+
+/// Render `template` with `variables` into a new note at `path` (relative to the knowledge base)
+#[tauri::command]
+pub async fn create_from_template(
+    app_handle: tauri::AppHandle,
+    template: String,
+    variables: Option<HashMap<String, String>>,
+    path: String,
+) -> Result<CreatedFromTemplate, String> {
+    let relative = Path::new(&path);
+    if relative.is_absolute() || relative.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err("Path must be relative to the knowledge base".to_string());
+    }
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let full_path = kb_root.join(relative);
+    if full_path.exists() {
+        return Err(format!("{} already exists", path));
+    }
+
+    let content = load_template(Some(&app_handle), &template)
+        .ok_or_else(|| format!("Template '{}' not found", template))?;
+
+    let mut vars = builtin_vars(chrono::Local::now().naive_local());
+    if let Some(title) = relative.file_stem() {
+        vars.insert("title".to_string(), title.to_string_lossy().replace(['-', '_'], " "));
+    }
+    vars.extend(variables.unwrap_or_default());
+
+    let (rendered, missing) = render(&content, &vars);
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&full_path, rendered).map_err(|e| e.to_string())?;
+
+    tracing::info!("🧩 Created {} from template '{}'", path, template);
+    Ok(CreatedFromTemplate { path, missing })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "# {{title}}\n{{ question | Why? }} by {{author}} on {{date}} ({{author}})";
+
+    #[test]
+    fn test_render_placeholders() {
+        let mut vars = builtin_vars(
+            chrono::NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(9, 30, 0).unwrap(),
+        );
+        vars.insert("title".to_string(), "Ownership".to_string());
+
+        let (text, missing) = render(TEMPLATE, &vars);
+        assert_eq!(text, "# Ownership\nWhy? by  on 2024-03-01 ()");
+        assert_eq!(missing, vec!["author".to_string()]);
+    }
+
+    #[test]
+    fn test_placeholders_listed_once_in_order() {
+        assert_eq!(placeholders(TEMPLATE), vec!["title", "question", "author", "date"]);
+    }
+}
@@ -3,7 +3,18 @@
 /// Full TKG implementation will be completed after credentials are provided
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Duration;
 use uuid::Uuid;
+use crate::net::{NetError, SendChecked};
+
+/// Points per upsert request
+const UPSERT_BATCH: usize = 256;
+/// Points per scroll page
+const SCROLL_PAGE: usize = 256;
+/// Retries of a Qdrant request that failed to connect, was rate limited or hit a server error
+const QDRANT_RETRIES: u32 = 3;
 
 /// Embedding vector type
 pub type Embedding = Vec<f32>;
@@ -134,16 +145,46 @@ pub struct TKGConfig {
     pub min_trust_threshold: f32,
 }
 
+/// WAMA's reminder intent check: "remind me", "deadline", "by ..." and the like
+pub fn mentions_reminder(text: &str) -> bool {
+    let text = text.to_lowercase();
+    ["remind me", "don't forget", "remember to", "todo", "deadline", "by ", "before ", "need to"]
+        .iter()
+        .any(|pattern| text.contains(pattern))
+}
+
+/// The `expected` vectors of an embeddings response (`[[f32, ...], ...]`, as Cohere and
+/// Ollama both return them)
+fn parse_embeddings(embeddings: &serde_json::Value, expected: usize) -> Result<Vec<Embedding>, String> {
+    let embeddings: Vec<Embedding> = embeddings
+        .as_array()
+        .ok_or("Invalid embedding response format")?
+        .iter()
+        .map(|e| e.as_array().map(|v| v.iter().filter_map(|f| f.as_f64().map(|f| f as f32)).collect()))
+        .collect::<Option<_>>()
+        .ok_or("Invalid embedding response format")?;
+    if embeddings.len() != expected {
+        return Err(format!("Expected {} embeddings, got {}", expected, embeddings.len()));
+    }
+    Ok(embeddings)
+}
+
 /// Main TKG Engine - Simplified
 pub struct TemporalKnowledgeGraph {
     pub config: TKGConfig,
     pub initialized: bool,
+    /// Shared HTTP clients for Qdrant and embedding calls (see net.rs)
+    http: crate::net::HttpClients,
 }
 
 impl TemporalKnowledgeGraph {
     /// Build a normalized Qdrant base URL that always has scheme and port.
     /// Accepts inputs like `localhost`, `localhost:6333`, or `https://host:port`.
     fn qdrant_base_url(&self) -> String {
+        let config = crate::config::current();
+        if let Some(url) = config.local.qdrant_url.as_deref().filter(|_| config.network.local_only) {
+            return url.trim_end_matches('/').to_string();
+        }
         let mut host = self.config.qdrant_host.trim_end_matches('/').to_string();
 
         // Ensure scheme (default to https for cloud endpoints)
@@ -165,34 +206,60 @@ impl TemporalKnowledgeGraph {
         }
     }
 
+    /// The collection memory lives in. Local embeddings don't have Cohere's size, so
+    /// local-only mode keeps them in a collection of their own.
+    fn collection(&self) -> String {
+        if crate::net::local_only() {
+            format!("{}-local", self.config.qdrant_collection)
+        } else {
+            self.config.qdrant_collection.clone()
+        }
+    }
+
+    fn dimension(&self) -> usize {
+        let config = crate::config::current();
+        if config.network.local_only {
+            config.local.embedding_dimension
+        } else {
+            self.config.dimension
+        }
+    }
+
     /// Initialize TKG with configuration
     pub fn new(config: TKGConfig) -> Self {
         Self {
             config,
             initialized: false,
+            http: crate::net::HttpClients::default(),
         }
     }
 
+    /// Use the HTTP clients managed by `app_handle`
+    pub fn with_clients(mut self, app_handle: &tauri::AppHandle) -> Self {
+        self.http = crate::net::HttpClients::of(Some(app_handle));
+        self
+    }
+
     /// Connect to services and create collection if needed
     pub async fn connect_qdrant(&mut self) -> Result<(), String> {
-        eprintln!("🔌 Connecting to Qdrant...");
+        tracing::debug!("🔌 Connecting to Qdrant...");
 
-        let client = reqwest::Client::new();
+        let client = self.http.client();
 
         // Build the base URL
         let base_url = self.qdrant_base_url();
 
         let url = format!("{}/collections", base_url);
 
-        eprintln!("📡 Host: {}", self.config.qdrant_host);
-        eprintln!("📡 Base URL: {}", base_url);
-        eprintln!("📡 Checking collections at: {}", url);
-        eprintln!("📡 Collection: {}", self.config.qdrant_collection);
+        tracing::debug!("📡 Host: {}", self.config.qdrant_host);
+        tracing::debug!("📡 Base URL: {}", base_url);
+        tracing::debug!("📡 Checking collections at: {}", url);
+        tracing::debug!("📡 Collection: {}", self.collection());
 
         // List collections
         let response = client.get(&url)
             .header("Api-Key", &self.config.qdrant_api_key)
-            .send()
+            .send_checked()
             .await
             .map_err(|e| format!("Failed to connect to Qdrant: {}", e))?;
 
@@ -209,24 +276,24 @@ impl TemporalKnowledgeGraph {
             .unwrap_or_else(Vec::new);
 
         let collection_exists = collections.iter()
-            .any(|c| c["name"].as_str() == Some(&self.config.qdrant_collection));
+            .any(|c| c["name"].as_str() == Some(self.collection().as_str()));
 
         if !collection_exists {
-            eprintln!("📦 Creating collection '{}'...", self.config.qdrant_collection);
+            tracing::info!("📦 Creating collection '{}'...", self.collection());
 
             // Create collection
             let base = self.qdrant_base_url();
-            let _create_url = format!("{}/collections/{}/points", base, self.config.qdrant_collection);
-            let create_response = client.put(&format!("{}/collections/{}", base, self.config.qdrant_collection))
+            let _create_url = format!("{}/collections/{}/points", base, self.collection());
+            let create_response = client.put(&format!("{}/collections/{}", base, self.collection()))
                 .header("Api-Key", &self.config.qdrant_api_key)
                 .header("Content-Type", "application/json")
                 .json(&serde_json::json!({
                     "vectors": {
-                        "size": self.config.dimension,
+                        "size": self.dimension(),
                         "distance": "Cosine"
                     }
                 }))
-                .send()
+                .send_checked()
                 .await
                 .map_err(|e| format!("Failed to create collection: {}", e))?;
 
@@ -235,14 +302,14 @@ impl TemporalKnowledgeGraph {
                 return Err(format!("Failed to create collection: {}", error_text));
             }
 
-            eprintln!("✅ Collection '{}' created successfully!", self.config.qdrant_collection);
+            tracing::info!("✅ Collection '{}' created successfully!", self.collection());
         } else {
-            eprintln!("✅ Collection '{}' already exists", self.config.qdrant_collection);
+            tracing::debug!("✅ Collection '{}' already exists", self.collection());
         }
 
         // Ensure payload index for user_id exists
         let base = self.qdrant_base_url();
-        let index_url = format!("{}/collections/{}/index", base, self.config.qdrant_collection);
+        let index_url = format!("{}/collections/{}/index", base, self.collection());
         let index_payload = serde_json::json!({
             "field_name": "user_id",
             "field_schema": "keyword"
@@ -252,86 +319,118 @@ impl TemporalKnowledgeGraph {
             .header("Api-Key", &self.config.qdrant_api_key)
             .header("Content-Type", "application/json")
             .json(&index_payload)
-            .send()
+            .send_checked()
             .await
             .map_err(|e| format!("Failed to create index: {}", e))?;
 
         if !index_response.status().is_success() {
              // It's okay if it fails (e.g. already exists), just log it
-             eprintln!("⚠️ Index creation note: {}", index_response.status());
+             tracing::warn!("⚠️ Index creation note: {}", index_response.status());
         } else {
-             eprintln!("✅ Index for 'user_id' ensured.");
+             tracing::debug!("✅ Index for 'user_id' ensured.");
         }
 
         self.initialized = true;
         Ok(())
     }
 
-    /// Generate embedding for text using Cohere
+    /// Generate embedding for text using Cohere, or the local model in local-only mode
     pub async fn embed_text(&self, text: &str) -> Result<Embedding, String> {
-        eprintln!("🔄 Generating embedding for text: '{}'", text);
+        tracing::debug!("🔄 Generating embedding for text: '{}'", text);
+        let mut embeddings = self.embed_texts(&[text.to_string()]).await?;
+        Ok(embeddings.remove(0))
+    }
+
+    /// Embed several texts in one call, in order (Cohere takes up to 96 at a time)
+    pub async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Embedding>, String> {
+        if crate::net::local_only() {
+            return self.embed_local(texts).await;
+        }
 
-        let client = reqwest::Client::new();
+        let client = self.http.client();
         let url = "https://api.cohere.ai/v1/embed";
 
         let payload = serde_json::json!({
             "model": self.config.embedding_model,
-            "texts": [text],
+            "texts": texts,
             "input_type": "search_document"
         });
 
-        eprintln!("📡 Calling Cohere API with model '{}'...", self.config.embedding_model);
+        tracing::debug!("📡 Calling Cohere API with model '{}' for {} text(s)...", self.config.embedding_model, texts.len());
 
         let response = client
             .post(url)
             .header("Authorization", format!("Bearer {}", self.config.cohere_api_key))
             .header("Content-Type", "application/json")
             .json(&payload)
-            .send()
+            .send_checked()
             .await
             .map_err(|e| {
-                eprintln!("❌ Cohere API connection error: {}", e);
+                tracing::error!("❌ Cohere API connection error: {}", e);
                 format!("Failed to call Cohere API: {}", e)
             })?;
 
-        eprintln!("📥 Cohere response status: {}", response.status());
+        tracing::debug!("📥 Cohere response status: {}", response.status());
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            eprintln!("❌ Cohere API error response: {}", error_text);
+            tracing::error!("❌ Cohere API error response: {}", error_text);
             return Err(format!("Cohere API error: {}", error_text));
         }
 
         let result: serde_json::Value = response.json().await
             .map_err(|e| {
-                eprintln!("❌ Failed to parse Cohere response: {}", e);
+                tracing::error!("❌ Failed to parse Cohere response: {}", e);
                 format!("Failed to parse Cohere response: {}", e)
             })?;
 
-        eprintln!("✅ Cohere response received, extracting embeddings...");
+        tracing::debug!("✅ Cohere response received, extracting embeddings...");
 
-        let embeddings = result["embeddings"]
-            .as_array()
-            .and_then(|arr| arr.get(0))
-            .and_then(|first| first.as_array())
-            .ok_or_else(|| {
-                eprintln!("❌ Invalid embedding response format");
-                "Invalid embedding response format".to_string()
-            })?;
+        let embeddings = parse_embeddings(&result["embeddings"], texts.len()).map_err(|e| {
+            tracing::error!("❌ {}", e);
+            e
+        })?;
 
-        let embedding: Embedding = embeddings
-            .iter()
-            .filter_map(|v| v.as_f64().map(|f| f as f32))
-            .collect();
+        tracing::debug!("✅ {} embedding(s) generated successfully", embeddings.len());
+
+        Ok(embeddings)
+    }
 
-        eprintln!("✅ Embedding generated successfully ({} dimensions)", embedding.len());
+    /// Embed `texts` with the Ollama model from `[local]` in thinkspace.toml
+    async fn embed_local(&self, texts: &[String]) -> Result<Vec<Embedding>, String> {
+        let local = crate::config::current().local.clone();
+        let base_url = local.embedding_base_url.ok_or(
+            "Local-only mode is on and no local embedding model is set up (embedding_base_url in [local])",
+        )?;
+        let url = format!("{}/api/embed", base_url.trim_end_matches('/'));
+        tracing::debug!("📡 Calling local embedding model '{}' for {} text(s)...", local.embedding_model, texts.len());
 
-        Ok(embedding)
+        let response = self.http.client()
+            .post(&url)
+            .json(&serde_json::json!({ "model": local.embedding_model, "input": texts }))
+            .send_checked()
+            .await
+            .map_err(|e| format!("Failed to call the local embedding model: {}", e))?;
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(format!("Local embedding error: {}", error_text));
+        }
+        let result: serde_json::Value = response.json().await
+            .map_err(|e| format!("Failed to parse the local embedding response: {}", e))?;
+
+        let embeddings = parse_embeddings(&result["embeddings"], texts.len())?;
+        if let Some(embedding) = embeddings.iter().find(|e| e.len() != local.embedding_dimension) {
+            return Err(format!(
+                "'{}' returned {} dimensions but embedding_dimension in [local] is {}",
+                local.embedding_model, embedding.len(), local.embedding_dimension
+            ));
+        }
+        Ok(embeddings)
     }
 
     /// Evaluate content using YOUR WAMA algorithm!
     pub fn evaluate_with_wama(&self, content: &str) -> (SaveDecision, f32) {
-        eprintln!("🧠 WAMA evaluating: {}...", &content[..std::cmp::min(content.len(), 60)]);
+        tracing::info!("🧠 WAMA evaluating: {}...", &content[..std::cmp::min(content.len(), 60)]);
 
         let content_lower = content.to_lowercase();
 
@@ -354,17 +453,7 @@ impl TemporalKnowledgeGraph {
             (
                 "Reminders & Deadlines".to_string(),
                 0.95,
-                |text: &str| -> bool {
-                    // Detect reminder intent patterns
-                    text.to_lowercase().contains("remind me")
-                        || text.to_lowercase().contains("don't forget")
-                        || text.to_lowercase().contains("remember to")
-                        || text.to_lowercase().contains("todo")
-                        || text.to_lowercase().contains("deadline")
-                        || text.to_lowercase().contains("by ")
-                        || text.to_lowercase().contains("before ")
-                        || text.to_lowercase().contains("need to")
-                }
+                mentions_reminder
             ),
             // HIGH PRIORITY: Personal Preferences (0.9)
             (
@@ -481,15 +570,29 @@ impl TemporalKnowledgeGraph {
             SaveDecision::LetFade
         };
 
-        eprintln!("   ✅ WAMA Decision: {:?} (score: {:.2})", decision, modified_score);
+        tracing::debug!("   ✅ WAMA Decision: {:?} (score: {:.2})", decision, modified_score);
         if !matched_criteria.is_empty() {
-            eprintln!("   📋 Matched criteria: {}", matched_criteria.join(", "));
+            tracing::debug!("   📋 Matched criteria: {}", matched_criteria.join(", "));
         }
 
         (decision, modified_score)
     }
 }
 
+/// Connect the TKG again with the current config, e.g. after local-only mode was switched,
+/// so the collection it now uses exists
+pub async fn reconnect() -> Result<(), String> {
+    let config = match active_config() {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+    let mut tkg = TemporalKnowledgeGraph::new(config);
+    tkg.connect_qdrant().await?;
+    tracing::info!("✅ TKG reconnected to collection '{}'", tkg.collection());
+    *TKG_INSTANCE.lock().map_err(|e| e.to_string())? = Some(tkg);
+    Ok(())
+}
+
 /// Test Qdrant connection
 #[tauri::command]
 pub async fn tkg_test_connection(
@@ -498,7 +601,7 @@ pub async fn tkg_test_connection(
     qdrant_collection: String,
     qdrant_api_key: String,
 ) -> Result<String, String> {
-    eprintln!("🔌 Testing Qdrant connection...");
+    tracing::debug!("🔌 Testing Qdrant connection...");
 
     let config = TKGConfig {
         qdrant_host,
@@ -521,7 +624,7 @@ pub async fn tkg_test_connection(
             "message": "Qdrant connection successful!",
             "host": tkg.config.qdrant_host,
             "port": tkg.config.qdrant_port,
-            "collection": tkg.config.qdrant_collection
+            "collection": tkg.collection()
         }).to_string()),
         Err(e) => Ok(serde_json::json!({
             "success": false,
@@ -538,7 +641,7 @@ impl TemporalKnowledgeGraph {
         trigger: String,
         config: CascadeConfig,
     ) -> CascadeResult {
-        eprintln!("🌊 RCA Cascade starting: {}...", trigger);
+        tracing::info!("🌊 RCA Cascade starting: {}...", trigger);
 
         let start_time = std::time::Instant::now();
         let mut thoughts = vec![trigger.clone()];
@@ -614,7 +717,7 @@ impl TemporalKnowledgeGraph {
             && !thoughts.is_empty()
         {
             if config.verbose {
-                eprintln!("\n  🔄 Depth {}: Processing {} thought(s)", depth, thoughts.len());
+                tracing::debug!("  🔄 Depth {}: Processing {} thought(s)", depth, thoughts.len());
             }
 
             let mut new_thoughts = Vec::new();
@@ -625,7 +728,7 @@ impl TemporalKnowledgeGraph {
                     let thought_hash = format!("{}-{}", thought, depth);
                     if seen.contains(&thought_hash) {
                         if config.verbose {
-                            eprintln!("    ⏩ Skipping (seen): {}", thought);
+                            tracing::debug!("    ⏩ Skipping (seen): {}", thought);
                         }
                         continue;
                     }
@@ -643,14 +746,14 @@ impl TemporalKnowledgeGraph {
                     satisfaction = satisfaction.max(confidence);
 
                     if config.verbose {
-                        eprintln!("    • {} (confidence: {:.2})", t, confidence);
+                        tracing::debug!("    • {} (confidence: {:.2})", t, confidence);
                     }
 
                     // Pruning check
                     if !config.enable_pruning || confidence >= config.prune_threshold {
                         triggered_with_confidence.push((t.clone(), confidence));
                     } else if config.verbose {
-                        eprintln!("      ✂️ Pruned (low confidence)");
+                        tracing::debug!("      ✂️ Pruned (low confidence)");
                     }
                 }
 
@@ -679,7 +782,7 @@ impl TemporalKnowledgeGraph {
             depth += 1;
 
             if config.verbose {
-                eprintln!("  📊 Satisfaction: {:.2}", satisfaction);
+                tracing::debug!("  📊 Satisfaction: {:.2}", satisfaction);
             }
         }
 
@@ -692,7 +795,7 @@ impl TemporalKnowledgeGraph {
 
         let execution_time = start_time.elapsed();
 
-        eprintln!("✅ Cascade complete! Depth: {}, Thoughts: {}, Satisfaction: {:.2}",
+        tracing::info!("✅ Cascade complete! Depth: {}, Thoughts: {}, Satisfaction: {:.2}",
                   depth, all_thoughts.len(), satisfaction);
 
         let thoughts_count = all_thoughts.len();
@@ -715,39 +818,15 @@ impl TemporalKnowledgeGraph {
         }
     }
 
-    /// Store knowledge in Qdrant
-    pub async fn store_knowledge(
-        &mut self,
-        content: String,
-        node_type: NodeType,
+    /// The Qdrant payload of a node; its `content` is what gets embedded
+    pub fn knowledge_payload(
+        content: &str,
+        node_type: &NodeType,
         importance: f32,
-        user_id: String,
-    ) -> Result<NodeId, String> {
-        // Step 1: WAMA evaluation FIRST (before spending Cohere credits!)
-        let (decision, score) = self.evaluate_with_wama(&content);
-
-        // Handle rejection cases
-        match decision {
-            SaveDecision::LetFade => {
-                eprintln!("   ⚠️  WAMA rejected: Score {:.2} < 0.3 (LET_FADE)", score);
-                return Err(format!("WAMA Decision: LET_FADE (score: {:.2}) - Content not worth saving", score));
-            }
-            SaveDecision::Consider => {
-                eprintln!("   ⚠️  WAMA borderline: Score {:.2} - Consider saving manually", score);
-                // Continue anyway, but log it
-            }
-            _ => {
-                eprintln!("   ✅ WAMA approved: {:?} (score: {:.2})", decision, score);
-            }
-        }
-
-        // Step 2: Generate embedding for the content (only if WAMA says save)
-        let embedding = self.embed_text(&content).await?;
-
-        // Create unique node ID
-        let node_id = Uuid::new_v4();
-
-        // Create payload
+        decision: &SaveDecision,
+        score: f32,
+        user_id: &str,
+    ) -> serde_json::Value {
         let node_type_str = match node_type {
             NodeType::Fact => "FACT",
             NodeType::Concept => "CONCEPT",
@@ -759,13 +838,8 @@ impl TemporalKnowledgeGraph {
             NodeType::AiResponse => "AI_RESPONSE",
         };
 
-        eprintln!("💾 Storing knowledge in Qdrant collection '{}'...", self.config.qdrant_collection);
-
-        let client = reqwest::Client::new();
-        let url = format!("{}/collections/{}/points", self.qdrant_base_url(), self.config.qdrant_collection);
-
-        // Create payload with WAMA data
-        let payload = serde_json::json!({
+        // Payload with WAMA data
+        serde_json::json!({
             "content": content,
             "node_type": node_type_str,
             "importance": importance,
@@ -773,33 +847,33 @@ impl TemporalKnowledgeGraph {
             "wama_decision": format!("{:?}", decision),
             "wama_score": score,
             "user_id": user_id
-        });
+        })
+    }
 
-        // Create point with UUID as string
-        let point = serde_json::json!({
-            "id": node_id.to_string(),
-            "vector": embedding,
-            "payload": payload
-        });
+    /// Embed `nodes` (id and payload) in one call and upsert them to Qdrant in one request
+    pub async fn store_nodes(&self, nodes: &[(String, serde_json::Value)]) -> Result<(), String> {
+        let texts: Vec<String> = nodes
+            .iter()
+            .map(|(_, payload)| payload["content"].as_str().unwrap_or_default().to_string())
+            .collect();
+        let embeddings = self.embed_texts(&texts).await?;
 
-        // Upsert point
-        let response = client.put(&url)
-            .header("Api-Key", &self.config.qdrant_api_key)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "points": [point]
-            }))
-            .send()
-            .await
-            .map_err(|e| format!("Failed to store knowledge: {}", e))?;
+        tracing::info!("💾 Storing {} node(s) in Qdrant collection '{}'...", nodes.len(), self.collection());
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Failed to store knowledge: {}", error_text));
-        }
+        // Points with UUIDs as strings
+        let points: Vec<serde_json::Value> = nodes
+            .iter()
+            .zip(embeddings)
+            .map(|((id, payload), embedding)| serde_json::json!({
+                "id": id,
+                "vector": embedding,
+                "payload": payload
+            }))
+            .collect();
+        self.upsert_points(&points).await?;
 
-        eprintln!("✅ Knowledge stored successfully! Node ID: {}", node_id);
-        Ok(NodeId(node_id.to_string()))
+        tracing::info!("✅ Stored {} node(s)", nodes.len());
+        Ok(())
     }
 
     fn build_search_payload(
@@ -836,31 +910,8 @@ impl TemporalKnowledgeGraph {
         let query_embedding = self.embed_text(query).await?;
 
         // Search in Qdrant
-        let client = reqwest::Client::new();
-        let url = format!(
-            "{}/collections/{}/points/search",
-            self.qdrant_base_url(),
-            self.config.qdrant_collection
-        );
-
         let search_payload = Self::build_search_payload(&query_embedding, limit, &user_id);
-
-        let response = client
-            .post(&url)
-            .header("Api-Key", &self.config.qdrant_api_key)
-            .header("Content-Type", "application/json")
-            .json(&search_payload)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to connect to Qdrant: {}", e))?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Qdrant search error: {}", error_text));
-        }
-
-        let result: serde_json::Value = response.json().await
-            .map_err(|e| format!("Failed to parse Qdrant response: {}", e))?;
+        let result = self.qdrant(reqwest::Method::POST, "points/search", &search_payload, "search knowledge").await?;
 
         let points = result["result"]
             .as_array()
@@ -903,13 +954,19 @@ pub async fn tkg_initialize(
     qdrant_api_key: String,
     cohere_api_key: String,
 ) -> Result<String, String> {
+    // Anything left empty comes from the [tkg] section of thinkspace.toml
+    let defaults = crate::config::current().tkg.clone();
+    let qdrant_host = match qdrant_host.trim() {
+        "" => defaults.qdrant_host.ok_or("No Qdrant host given or configured")?,
+        host => host.to_string(),
+    };
     let config = TKGConfig {
         qdrant_host,
-        qdrant_port,
-        qdrant_collection,
+        qdrant_port: if qdrant_port == 0 { defaults.qdrant_port } else { qdrant_port },
+        qdrant_collection: if qdrant_collection.trim().is_empty() { defaults.qdrant_collection } else { qdrant_collection },
         qdrant_api_key,
         cohere_api_key,
-        embedding_model: "embed-v4.0".to_string(),
+        embedding_model: defaults.embedding_model,
         dimension: 1536,  // ✅ Fixed: Cohere embed-v4.0 generates 1536-dim embeddings
         max_nodes_per_query: 10,
         temporal_decay_factor: 0.95,
@@ -927,6 +984,9 @@ pub async fn tkg_initialize(
     let mut instance = TKG_INSTANCE.lock().map_err(|e| e.to_string())?;
     *instance = Some(tkg);
 
+    // Stores queued before a restart can go out now
+    crate::embed_queue::start_worker();
+
     Ok("TKG initialized successfully".to_string())
 }
 
@@ -938,6 +998,37 @@ pub async fn tkg_store_knowledge(
     importance: f32,
     user_id: String,
 ) -> Result<String, String> {
+    store_knowledge(content, node_type, importance, user_id, None).await
+}
+
+/// Store a section of a knowledge base note, tagged with the note's path and the section's
+/// hash so its point can be found and replaced when the note changes
+pub async fn tkg_store_note_section(content: String, user_id: String, path: &str, section_hash: &str) -> Result<String, String> {
+    let source = serde_json::json!({ "source_path": path, "section_hash": section_hash });
+    store_knowledge(content, "FACT".to_string(), 0.6, user_id, Some(source)).await
+}
+
+/// Forget the stored sections of note `path` whose hash isn't in `keep` (all of them when
+/// `keep` is empty), both in Qdrant and still waiting in the embedding queue
+pub async fn forget_note_sections(user_id: &str, path: &str, keep: &[String]) -> Result<(), String> {
+    let user_id = crate::profiles::tkg_user_id(user_id);
+    let (queue_user, queue_path, queue_keep) = (user_id.clone(), path.to_string(), keep.to_vec());
+    crate::db::blocking(move || crate::embed_queue::drop_outdated_sections(&queue_user, &queue_path, &queue_keep)).await?;
+
+    let config = active_config().ok_or("TKG not initialized")?;
+    TemporalKnowledgeGraph::new(config).delete_note_points(&user_id, path, keep).await
+}
+
+/// `tkg_store_knowledge`, with `source` fields added to the stored payload
+async fn store_knowledge(
+    content: String,
+    node_type: String,
+    importance: f32,
+    user_id: String,
+    source: Option<serde_json::Value>,
+) -> Result<String, String> {
+    // Memory always lands in the active profile's namespace
+    let user_id = crate::profiles::tkg_user_id(&user_id);
     let node_type_normalized = node_type.trim().to_uppercase();
     let node_type_enum = match node_type_normalized.as_str() {
         "FACT" => NodeType::Fact,
@@ -951,6 +1042,11 @@ pub async fn tkg_store_knowledge(
         _ => NodeType::Concept,
     };
 
+    // Reminders are kept whether or not the TKG is set up
+    if matches!(node_type_enum, NodeType::Memory | NodeType::UserInput) {
+        crate::reminders::capture_memory(&content);
+    }
+
     // Get config from global instance (use block to ensure guard is dropped)
     let config = {
         let instance = TKG_INSTANCE.lock().map_err(|e| e.to_string())?;
@@ -964,7 +1060,7 @@ pub async fn tkg_store_knowledge(
     let mut temp_tkg = TemporalKnowledgeGraph::new(config);
     temp_tkg.initialized = true;
 
-    // First evaluate with WAMA
+    // First evaluate with WAMA (before spending Cohere credits!)
     let (decision, score) = temp_tkg.evaluate_with_wama(&content);
 
     // Check if WAMA rejects it
@@ -978,16 +1074,26 @@ pub async fn tkg_store_knowledge(
         }).to_string());
     }
 
-    // Store the knowledge
-    let node_id = temp_tkg.store_knowledge(content, node_type_enum, importance, user_id).await
+    if matches!(decision, SaveDecision::Consider) {
+        tracing::info!("   ⚠️  WAMA borderline: Score {:.2} - saving anyway", score);
+    }
+
+    // Embedding and the Qdrant upsert happen in the background, batched with other stores
+    let node_id = NodeId(Uuid::new_v4().to_string());
+    let mut payload = TemporalKnowledgeGraph::knowledge_payload(&content, &node_type_enum, importance, &decision, score, &user_id);
+    if let (Some(payload), Some(serde_json::Value::Object(source))) = (payload.as_object_mut(), source) {
+        payload.extend(source);
+    }
+    crate::embed_queue::enqueue(&node_id.0, &payload)
         .map_err(|e| format!("Failed to store knowledge: {}", e))?;
 
     Ok(serde_json::json!({
         "success": true,
+        "queued": true,
         "node_id": node_id.0,
         "decision": format!("{:?}", decision),
         "score": score,
-        "message": format!("Knowledge stored successfully in TKG (WAMA: {:?}, score: {:.2})", decision, score)
+        "message": format!("Knowledge queued for the TKG (WAMA: {:?}, score: {:.2})", decision, score)
     }).to_string())
 }
 
@@ -998,6 +1104,7 @@ pub async fn tkg_search_similar(
     limit: u64,
     user_id: String,
 ) -> Result<String, String> {
+    let user_id = crate::profiles::tkg_user_id(&user_id);
     // Get config from global instance (use block to ensure guard is dropped)
     let config = {
         let instance = TKG_INSTANCE.lock().map_err(|e| e.to_string())?;
@@ -1061,24 +1168,50 @@ pub async fn tkg_query_temporal(
     }).to_string())
 }
 
-/// Backup consciousness state (placeholder)
+/// Back up every memory in the collection (or only `user_id`'s) with its vector to a JSON
+/// lines file under `tkg_backups/` in the profile's data folder
 #[tauri::command]
-pub async fn tkg_backup_consciousness() -> Result<String, String> {
+pub async fn tkg_backup_consciousness(app_handle: tauri::AppHandle, user_id: Option<String>) -> Result<String, String> {
+    let config = active_config().ok_or("TKG not initialized. Please configure your Qdrant credentials.")?;
+    let tkg = TemporalKnowledgeGraph::new(config);
+
+    let dir = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?.join("tkg_backups");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let backup_id = Uuid::new_v4().to_string();
+    let path = dir.join(format!("{}-{}.jsonl", tkg.collection(), backup_id));
+
+    let user_id = user_id.map(|id| crate::profiles::tkg_user_id(&id));
+    let count = match tkg.backup_points(&path, user_id.as_deref()).await {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = std::fs::remove_file(&path);
+            return Err(format!("Backup failed: {}", e));
+        }
+    };
+    tracing::info!("🧠 Backed up {} memories to {}", count, path.display());
+
     Ok(serde_json::json!({
         "success": true,
-        "backup_id": Uuid::new_v4().to_string(),
-        "message": "Backup created (placeholder - full features after credentials)"
+        "backup_id": backup_id,
+        "path": path,
+        "points": count,
+        "message": format!("Backed up {} memories", count)
     }).to_string())
 }
 
-/// Get TKG statistics
+/// Get TKG statistics, with point counts when Qdrant is reachable
 #[tauri::command]
 pub async fn tkg_get_stats() -> Result<String, String> {
-    let instance = TKG_INSTANCE.lock().map_err(|e| e.to_string())?;
-    let tkg = instance.as_ref().ok_or("TKG not initialized")?;
+    let (mut stats, config) = {
+        let instance = TKG_INSTANCE.lock().map_err(|e| e.to_string())?;
+        let tkg = instance.as_ref().ok_or("TKG not initialized")?;
+        (tkg.get_consciousness_stats(), tkg.config.clone())
+    };
 
-    let stats = tkg.get_consciousness_stats();
-    drop(instance); // Drop the lock before any async operations
+    match TemporalKnowledgeGraph::new(config).collection_stats().await {
+        Ok(counts) => stats["points"] = serde_json::to_value(counts).unwrap_or_default(),
+        Err(e) => stats["points_error"] = serde_json::Value::String(e),
+    }
     Ok(serde_json::to_string(&stats).unwrap())
 }
 
@@ -1114,20 +1247,20 @@ pub async fn tkg_cascade_brainstorm(
         cascade_config.beam_width = Some(width);
     }
 
-    eprintln!("\n🌊 RCA CASCADE BRAINSTORM STARTED");
-    eprintln!("   Trigger: {}", trigger);
-    eprintln!("   Max Depth: {}", cascade_config.max_depth);
-    eprintln!("   Satisfaction Threshold: {}", cascade_config.satisfaction_threshold);
-    eprintln!("   Beam Width: {:?}", cascade_config.beam_width);
+    tracing::debug!("🌊 RCA CASCADE BRAINSTORM STARTED");
+    tracing::debug!("   Trigger: {}", trigger);
+    tracing::debug!("   Max Depth: {}", cascade_config.max_depth);
+    tracing::debug!("   Satisfaction Threshold: {}", cascade_config.satisfaction_threshold);
+    tracing::debug!("   Beam Width: {:?}", cascade_config.beam_width);
 
     // Execute cascade
     let result = tkg.cascade_brainstorm(trigger.clone(), cascade_config);
 
-    eprintln!("\n✅ Cascade completed!");
-    eprintln!("   Depth explored: {}", result.depths_explored);
-    eprintln!("   Thoughts processed: {}", result.thoughts_processed);
-    eprintln!("   Max satisfaction: {:.2}", result.max_satisfaction);
-    eprintln!("   Termination: {}", result.termination_reason);
+    tracing::debug!("✅ Cascade completed!");
+    tracing::debug!("   Depth explored: {}", result.depths_explored);
+    tracing::debug!("   Thoughts processed: {}", result.thoughts_processed);
+    tracing::debug!("   Max satisfaction: {:.2}", result.max_satisfaction);
+    tracing::debug!("   Termination: {}", result.termination_reason);
 
     Ok(serde_json::json!({
         "success": true,
@@ -1200,16 +1333,169 @@ mod tests {
         assert_eq!(payload_a["filter"]["must"][0]["match"]["value"], "user_a");
         assert_eq!(payload_b["filter"]["must"][0]["match"]["value"], "user_b");
     }
+
+    #[test]
+    fn test_note_points_filter() {
+        let keep = vec!["abc".to_string(), "def".to_string()];
+        let filter = TemporalKnowledgeGraph::note_points_filter("user_a", "research/rust.md", &keep);
+        assert_eq!(filter["must"][0]["match"]["value"], "user_a");
+        assert_eq!(filter["must"][1], serde_json::json!({ "key": "source_path", "match": { "value": "research/rust.md" } }));
+        assert_eq!(filter["must_not"][0]["match"]["any"], serde_json::json!(["abc", "def"]));
+
+        // A removed note loses every point
+        let filter = TemporalKnowledgeGraph::note_points_filter("user_a", "research/rust.md", &[]);
+        assert!(filter.get("must_not").is_none());
+    }
+
+    #[test]
+    fn test_qdrant_batching_and_retries() {
+        assert!(is_retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(reqwest::StatusCode::BAD_REQUEST));
+        assert_eq!(retry_delay(0), Duration::from_millis(500));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+
+        let mut stats = CollectionStats::default();
+        let point = |node_type: &str, user: Option<&str>| serde_json::json!({ "id": 1, "payload": { "node_type": node_type, "user_id": user } });
+        stats.add(&[point("FACT", Some("alice")), point("FACT", Some("bob"))]);
+        stats.add(&[point("INSIGHT", None)]);
+        assert_eq!(stats.total_points, 3);
+        assert_eq!(stats.by_node_type["FACT"], 2);
+        assert_eq!(stats.by_user["(none)"], 1);
+    }
+}
+
+/// Whether a Qdrant response is worth sending the request again for
+fn is_retryable(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// How long to wait before retry number `attempt` (0-based): 0.5s, 1s, 2s, ...
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_millis(500 * 2_u64.pow(attempt.min(6)))
+}
+
+/// Point counts of a collection, overall and by node type and user
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CollectionStats {
+    pub total_points: usize,
+    pub by_node_type: BTreeMap<String, usize>,
+    pub by_user: BTreeMap<String, usize>,
+}
+
+impl CollectionStats {
+    fn add(&mut self, points: &[serde_json::Value]) {
+        for point in points {
+            let key = |field: &str| point["payload"][field].as_str().filter(|v| !v.is_empty()).unwrap_or("(none)").to_string();
+            self.total_points += 1;
+            *self.by_node_type.entry(key("node_type")).or_default() += 1;
+            *self.by_user.entry(key("user_id")).or_default() += 1;
+        }
+    }
 }
 
 impl TemporalKnowledgeGraph {
+    /// Send `body` to `path` under the collection (e.g. "points/scroll") and return the parsed
+    /// response. Connection errors, 429 and 5xx responses are retried with growing delays;
+    /// `action` completes "Failed to ..." in errors.
+    async fn qdrant(&self, method: reqwest::Method, path: &str, body: &serde_json::Value, action: &str) -> Result<serde_json::Value, String> {
+        let url = format!("{}/collections/{}/{}", self.qdrant_base_url(), self.collection(), path);
+        let mut attempt = 0;
+        loop {
+            let sent = self.http.client()
+                .request(method.clone(), &url)
+                .header("Api-Key", &self.config.qdrant_api_key)
+                .json(body)
+                .send_checked()
+                .await;
+            let error = match sent {
+                Ok(response) if response.status().is_success() => {
+                    return response.json().await.map_err(|e| format!("Failed to parse Qdrant response: {}", e));
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    let error = format!("Failed to {}: Qdrant returned {}: {}", action, status, text);
+                    if !is_retryable(status) {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(NetError::Blocked(reason)) => return Err(format!("Failed to {}: {}", action, reason)),
+                Err(e) => format!("Failed to {}: {}", action, e),
+            };
+            if attempt >= QDRANT_RETRIES {
+                return Err(error);
+            }
+            let delay = retry_delay(attempt);
+            tracing::warn!("⚠️ {}; retrying in {}ms", error, delay.as_millis());
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Upsert `points` in requests of `UPSERT_BATCH`
+    pub async fn upsert_points(&self, points: &[serde_json::Value]) -> Result<(), String> {
+        for batch in points.chunks(UPSERT_BATCH) {
+            self.qdrant(reqwest::Method::PUT, "points?wait=true", &serde_json::json!({ "points": batch }), "store knowledge").await?;
+        }
+        Ok(())
+    }
+
+    /// One page of points matching `filter`, starting at `offset`, and where the next page
+    /// starts (None after the last page)
+    async fn scroll_page(
+        &self,
+        filter: Option<&serde_json::Value>,
+        with_vector: bool,
+        offset: Option<serde_json::Value>,
+    ) -> Result<(Vec<serde_json::Value>, Option<serde_json::Value>), String> {
+        let mut body = serde_json::json!({
+            "limit": SCROLL_PAGE,
+            "with_payload": true,
+            "with_vector": with_vector
+        });
+        if let Some(filter) = filter {
+            body["filter"] = filter.clone();
+        }
+        if let Some(offset) = offset {
+            body["offset"] = offset;
+        }
+        let mut result = self.qdrant(reqwest::Method::POST, "points/scroll", &body, "scroll memories").await?;
+        let points = match result["result"]["points"].take() {
+            serde_json::Value::Array(points) => points,
+            _ => return Err("Invalid response format".to_string()),
+        };
+        let next = match result["result"]["next_page_offset"].take() {
+            serde_json::Value::Null => None,
+            next => Some(next),
+        };
+        Ok((points, next))
+    }
+
+    /// Hand every point matching `filter` to `on_page`, a page at a time, so a large
+    /// collection never has to fit in memory. Returns how many points there were.
+    pub async fn scroll_all(
+        &self,
+        filter: Option<&serde_json::Value>,
+        with_vector: bool,
+        mut on_page: impl FnMut(Vec<serde_json::Value>) -> Result<(), String>,
+    ) -> Result<usize, String> {
+        let mut total = 0;
+        let mut offset = None;
+        loop {
+            let (points, next) = self.scroll_page(filter, with_vector, offset).await?;
+            total += points.len();
+            on_page(points)?;
+            match next {
+                Some(next) => offset = Some(next),
+                None => return Ok(total),
+            }
+        }
+    }
+
     pub async fn claim_legacy_data(&mut self, user_id: &str, dry_run: bool) -> Result<usize, String> {
-        let collection_name = &self.config.qdrant_collection;
-        let base_url = self.qdrant_base_url();
-        let client = reqwest::Client::new();
-        
-        // 1. Scroll points where user_id is null, empty, or "guest"
-        // Qdrant filter: should match any of these conditions
+        // Points where user_id is null, empty, or "guest"
         let filter = serde_json::json!({
             "should": [
                 {
@@ -1225,42 +1511,11 @@ impl TemporalKnowledgeGraph {
                 }
             ]
         });
-        
-        let scroll_url = format!("{}/collections/{}/points/scroll", base_url, collection_name);
-        
-        // We will scroll in batches
+
         let mut migrated_count = 0;
-        let mut next_page_offset: Option<serde_json::Value> = None;
-        
+        let mut offset = None;
         loop {
-            let mut scroll_payload = serde_json::json!({
-                "limit": 100,
-                "with_payload": true,
-                "filter": filter
-            });
-            
-            if let Some(offset) = next_page_offset.clone() {
-                scroll_payload["offset"] = offset;
-            }
-            
-            let response = client.post(&scroll_url)
-                .header("Api-Key", &self.config.qdrant_api_key)
-                .header("Content-Type", "application/json")
-                .json(&scroll_payload)
-                .send()
-                .await
-                .map_err(|e| format!("Failed to scroll legacy data: {}", e))?;
-                
-            if !response.status().is_success() {
-                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                return Err(format!("Qdrant scroll error: {}", error_text));
-            }
-            
-            let result: serde_json::Value = response.json().await
-                .map_err(|e| format!("Failed to parse Qdrant response: {}", e))?;
-                
-            let points = result["result"]["points"].as_array().ok_or("Invalid response format")?;
-            
+            let (points, next) = self.scroll_page(Some(&filter), false, offset).await?;
             if points.is_empty() {
                 break;
             }
@@ -1268,55 +1523,118 @@ impl TemporalKnowledgeGraph {
             if dry_run {
                 migrated_count += points.len();
             } else {
-                // Update each point
-                for point in points {
-                    let point_id = point["id"].as_str().or_else(|| point["id"].as_u64().map(|_| "id_is_int")).unwrap_or("unknown");
-
-                    // Preserve the existing payload when claiming legacy data.
-                    // Some Qdrant setups may treat payload updates as overwrites; sending the full payload avoids data loss.
-                    let mut payload_update = point.get("payload").cloned().unwrap_or_else(|| serde_json::json!({}));
-                    if !payload_update.is_object() {
-                        payload_update = serde_json::json!({});
-                    }
-                    if let Some(obj) = payload_update.as_object_mut() {
-                        obj.insert("user_id".to_string(), serde_json::Value::String(user_id.to_string()));
-                    }
-
-                    let update_url = format!("{}/collections/{}/points/payload?wait=true", base_url, collection_name);
-
-                    let update_payload = serde_json::json!({
-                        "points": [point["id"]], // Use the ID from the point object directly to preserve type
-                        "payload": payload_update
-                    });
-
-                    let update_response = client.post(&update_url)
-                        .header("Api-Key", &self.config.qdrant_api_key)
-                        .header("Content-Type", "application/json")
-                        .json(&update_payload)
-                        .send()
-                        .await
-                        .map_err(|e| format!("Failed to update point: {}", e))?;
-
-                    if !update_response.status().is_success() {
-                        eprintln!("Failed to update point {}: {}", point_id, update_response.status());
-                    } else {
-                        migrated_count += 1;
-                    }
-                }
+                // Preserve the existing payload when claiming legacy data. Some Qdrant setups may
+                // treat payload updates as overwrites; sending the full payload avoids data loss.
+                // The whole page goes out as one batch of set_payload operations.
+                let operations: Vec<serde_json::Value> = points
+                    .iter()
+                    .map(|point| {
+                        let mut payload = point.get("payload").cloned().filter(|p| p.is_object()).unwrap_or_else(|| serde_json::json!({}));
+                        payload["user_id"] = serde_json::Value::String(user_id.to_string());
+                        serde_json::json!({
+                            // The ID from the point object directly, to preserve its type
+                            "set_payload": { "points": [point["id"]], "payload": payload }
+                        })
+                    })
+                    .collect();
+                self.qdrant(reqwest::Method::POST, "points/batch?wait=true", &serde_json::json!({ "operations": operations }), "update legacy memories").await?;
+                migrated_count += points.len();
             }
-            
-            // Check for next page
-            let offset_val = result["result"]["next_page_offset"].clone();
-            if offset_val.is_null() {
-                next_page_offset = None;
-                break;
-            } else {
-                next_page_offset = Some(offset_val);
+
+            // Claimed points no longer match the filter, but offsets are point ids, so the
+            // next page still starts in the right place
+            match next {
+                Some(next) => offset = Some(next),
+                None => break,
             }
         }
-        
+
         Ok(migrated_count)
     }
+
+    fn user_filter(user_id: &str) -> serde_json::Value {
+        serde_json::json!({ "must": [{ "key": "user_id", "match": { "value": user_id } }] })
+    }
+
+    /// How many points are stored for `user_id`
+    pub async fn count_points(&self, user_id: &str) -> Result<u64, String> {
+        let body = serde_json::json!({ "filter": Self::user_filter(user_id), "exact": true });
+        let result = self.qdrant(reqwest::Method::POST, "points/count", &body, "count memories").await?;
+        result["result"]["count"].as_u64().ok_or_else(|| "Invalid response format".to_string())
+    }
+
+    /// Points of `user_id` from note `path` whose section hash isn't in `keep`
+    fn note_points_filter(user_id: &str, path: &str, keep: &[String]) -> serde_json::Value {
+        let mut filter = serde_json::json!({
+            "must": [
+                { "key": "user_id", "match": { "value": user_id } },
+                { "key": "source_path", "match": { "value": path } }
+            ]
+        });
+        if !keep.is_empty() {
+            filter["must_not"] = serde_json::json!([{ "key": "section_hash", "match": { "any": keep } }]);
+        }
+        filter
+    }
+
+    /// Delete the points of note `path` whose section hash isn't in `keep`
+    pub async fn delete_note_points(&self, user_id: &str, path: &str, keep: &[String]) -> Result<(), String> {
+        let body = serde_json::json!({ "filter": Self::note_points_filter(user_id, path, keep) });
+        self.qdrant(reqwest::Method::POST, "points/delete?wait=true", &body, "delete note sections").await?;
+        Ok(())
+    }
+
+    /// Delete every point stored for `user_id`
+    pub async fn delete_points(&self, user_id: &str) -> Result<(), String> {
+        let body = serde_json::json!({ "filter": Self::user_filter(user_id) });
+        self.qdrant(reqwest::Method::POST, "points/delete?wait=true", &body, "delete memories").await?;
+        Ok(())
+    }
+
+    /// Every point stored for `user_id`, with payload and vector, for a full data export
+    pub async fn export_points(&self, user_id: &str) -> Result<Vec<serde_json::Value>, String> {
+        let mut points = Vec::new();
+        self.scroll_all(Some(&Self::user_filter(user_id)), true, |page| {
+            points.extend(page);
+            Ok(())
+        })
+        .await?;
+        Ok(points)
+    }
+
+    /// Write every point (of `user_id`, or of everyone) with its vector to `path`, one JSON
+    /// object per line, page by page. Returns how many points were written.
+    pub async fn backup_points(&self, path: &std::path::Path, user_id: Option<&str>) -> Result<usize, String> {
+        let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = std::io::BufWriter::new(file);
+        let filter = user_id.map(Self::user_filter);
+        let count = self
+            .scroll_all(filter.as_ref(), true, |page| {
+                for point in page {
+                    writeln!(writer, "{}", point).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            })
+            .await?;
+        writer.flush().map_err(|e| e.to_string())?;
+        Ok(count)
+    }
+
+    /// Count the collection's points by node type and user, reading payloads only
+    pub async fn collection_stats(&self) -> Result<CollectionStats, String> {
+        let mut stats = CollectionStats::default();
+        self.scroll_all(None, false, |page| {
+            stats.add(&page);
+            Ok(())
+        })
+        .await?;
+        Ok(stats)
+    }
+}
+
+/// The configuration of the initialized TKG, if there is one
+pub fn active_config() -> Option<TKGConfig> {
+    TKG_INSTANCE.lock().ok()?.as_ref().map(|tkg| tkg.config.clone())
 }
 
 #[tauri::command]
@@ -1324,6 +1642,7 @@ pub async fn tkg_claim_legacy_data(
     user_id: String,
     dry_run: Option<bool>,
 ) -> Result<String, String> {
+    let user_id = crate::profiles::tkg_user_id(&user_id);
     let dry_run = dry_run.unwrap_or(false);
     // Get config from global instance
     let config = {
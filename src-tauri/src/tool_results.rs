@@ -0,0 +1,187 @@
+/// Out-of-line storage for large tool results
+///
+/// A full wiki page or a big file read used to go into the conversation history as is, and
+/// was cloned and re-sent with every provider call after it. Results longer than
+/// `INLINE_LIMIT` are now written to `tool_results/` in the profile's data folder, and the
+/// history gets a short stand-in: the result id, its size, an outline of its fields and the
+/// start of its text. The model reads the rest with the `read_tool_result` tool, a window at
+/// a time, only when it needs to. Stored results are removed after `MAX_AGE_DAYS`.
+
+use std::path::{Path, PathBuf};
+
+/// Results up to this many bytes stay in the history
+pub const INLINE_LIMIT: usize = 8_000;
+/// Most characters `read_tool_result` returns at once
+pub const MAX_READ: usize = 6_000;
+const PREVIEW_CHARS: usize = 1_500;
+const MAX_AGE_DAYS: u64 = 7;
+pub const RESULTS_FOLDER: &str = "tool_results";
+
+/// Where stored results live for the active profile
+pub fn results_dir(app_handle: Option<&tauri::AppHandle>) -> Option<PathBuf> {
+    crate::profiles::data_dir(app_handle).map(|dir| dir.join(RESULTS_FOLDER))
+}
+
+/// The first `max` characters of `text`
+fn prefix(text: &str, max: usize) -> &str {
+    text.char_indices().nth(max).map_or(text, |(end, _)| &text[..end])
+}
+
+/// Field names and sizes of a JSON result, so the model can tell what it would be reading
+fn outline(value: &serde_json::Value) -> serde_json::Value {
+    let size = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => format!("text, {} chars", s.chars().count()),
+        serde_json::Value::Array(items) => format!("list of {}", items.len()),
+        serde_json::Value::Object(fields) => format!("object with {} fields", fields.len()),
+        other => other.to_string(),
+    };
+    match value {
+        serde_json::Value::Object(fields) => fields.iter().map(|(k, v)| (k.clone(), serde_json::Value::String(size(v)))).collect(),
+        other => serde_json::Value::String(size(other)),
+    }
+}
+
+/// What goes into the history in place of `result` once it's stored as `id`
+fn stand_in(tool: &str, id: &str, result: &str) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(result).ok();
+    // Preview the longest text field rather than the start of the JSON
+    let longest_text = parsed.as_ref().and_then(|v| v.as_object()).and_then(|fields| {
+        fields.values().filter_map(|v| v.as_str()).max_by_key(|s| s.len())
+    });
+    let preview = prefix(longest_text.unwrap_or(result), PREVIEW_CHARS);
+    serde_json::json!({
+        "success": parsed.as_ref().and_then(|v| v.get("success")).and_then(|v| v.as_bool()).unwrap_or(true),
+        "stored_result": id,
+        "tool": tool,
+        "chars": result.chars().count(),
+        "outline": parsed.as_ref().map(outline),
+        "preview": preview,
+        "message": format!(
+            "This {} result is too long to include and was stored as '{}'. Call read_tool_result with this result_id (and offset) to read more of it.",
+            tool, id
+        )
+    })
+    .to_string()
+}
+
+/// Store `result` under `dir` if it's too long to inline, returning what the history should
+/// hold instead. Results that can't be stored stay inline.
+pub fn offload_in(dir: &Path, tool: &str, result: String) -> String {
+    if result.len() <= INLINE_LIMIT || tool == "read_tool_result" {
+        return result;
+    }
+    prune(dir, std::time::Duration::from_secs(MAX_AGE_DAYS * 24 * 3600));
+    let id = format!("tr-{}", uuid::Uuid::new_v4().simple());
+    let written = std::fs::create_dir_all(dir).and_then(|_| std::fs::write(dir.join(format!("{}.txt", id)), &result));
+    match written {
+        Ok(()) => {
+            tracing::debug!("📦 Stored {} result ({} bytes) as {}", tool, result.len(), id);
+            stand_in(tool, &id, &result)
+        }
+        Err(e) => {
+            tracing::warn!("⚠️ Failed to store {} result out of line: {}", tool, e);
+            result
+        }
+    }
+}
+
+/// `offload_in` for the active profile
+pub fn offload(app_handle: Option<&tauri::AppHandle>, tool: &str, result: String) -> String {
+    match results_dir(app_handle) {
+        Some(dir) => offload_in(&dir, tool, result),
+        None => result,
+    }
+}
+
+/// Up to `length` characters of stored result `id` from character `offset`
+pub fn read_in(dir: &Path, id: &str, offset: usize, length: usize) -> Result<serde_json::Value, String> {
+    if !id.starts_with("tr-") || !id[3..].chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a stored result id", id));
+    }
+    let text = std::fs::read_to_string(dir.join(format!("{}.txt", id)))
+        .map_err(|_| format!("Stored result '{}' not found; it may have expired", id))?;
+    let total = text.chars().count();
+    let content: String = text.chars().skip(offset).take(length.clamp(1, MAX_READ)).collect();
+    let end = offset + content.chars().count();
+    Ok(serde_json::json!({
+        "success": true,
+        "result_id": id,
+        "offset": offset,
+        "content": content,
+        "total_chars": total,
+        "next_offset": if end < total { Some(end) } else { None }
+    }))
+}
+
+/// Remove stored results older than `max_age`
+fn prune(dir: &Path, max_age: std::time::Duration) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let expired = entry.metadata().ok().and_then(|m| m.modified().ok()).and_then(|t| t.elapsed().ok()).map_or(false, |age| age > max_age);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_result() -> String {
+        let page = "é".repeat(5_000) + &"x".repeat(5_000);
+        serde_json::json!({ "success": true, "path": "research/big.md", "content": page }).to_string()
+    }
+
+    /// Offloads the large result, returning its stand-in
+    fn offload_large(dir: &Path) -> serde_json::Value {
+        serde_json::from_str(&offload_in(dir, "read_file", large_result())).unwrap()
+    }
+
+    #[test]
+    fn test_small_results_stay_inline() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = serde_json::json!({ "success": true, "content": "short" }).to_string();
+        assert_eq!(offload_in(dir.path(), "read_file", small.clone()), small);
+    }
+
+    #[test]
+    fn test_large_results_get_a_stand_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let stand_in = offload_large(dir.path());
+        assert!(stand_in["preview"].as_str().unwrap().starts_with("éé"));
+        assert_eq!(stand_in["preview"].as_str().unwrap().chars().count(), PREVIEW_CHARS);
+        assert_eq!(stand_in["outline"]["content"], "text, 10000 chars");
+    }
+
+    #[test]
+    fn test_read_in_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let stand_in = offload_large(dir.path());
+        let id = stand_in["stored_result"].as_str().unwrap();
+
+        let first = read_in(dir.path(), id, 0, 100_000).unwrap();
+        assert_eq!(first["content"].as_str().unwrap().chars().count(), MAX_READ);
+        let next = first["next_offset"].as_u64().unwrap() as usize;
+        let rest = read_in(dir.path(), id, next, 100_000).unwrap();
+        assert!(rest["next_offset"].is_null());
+        assert_eq!(format!("{}{}", first["content"].as_str().unwrap(), rest["content"].as_str().unwrap()), large_result());
+    }
+
+    #[test]
+    fn test_read_in_rejects_bad_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_in(dir.path(), "../secrets", 0, 10).is_err());
+        assert!(read_in(dir.path(), "tr-0123", 0, 10).unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_stored_results_are_not_stored_again() {
+        // Reading a stored result never stores it again
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(offload_in(dir.path(), "read_tool_result", "y".repeat(INLINE_LIMIT + 1)).len(), INLINE_LIMIT + 1);
+    }
+}
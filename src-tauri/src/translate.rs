@@ -0,0 +1,313 @@
+/// Translate text or whole notes with the active provider
+///
+/// Markdown is sent in chunks of whole paragraphs (up to `MAX_CHUNK_CHARS`) so long notes
+/// fit, and the model is told to keep every markdown element as it is and translate only the
+/// prose. Fenced code blocks and frontmatter aren't sent at all. A translated note can be
+/// saved next to the original as `<name>.<language>.md`, with `language` and
+/// `translated_from` in its frontmatter; translating it again replaces that file (note
+/// versions keep the earlier one).
+
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+
+use crate::commands::orchestrate_agents::ProviderKeys;
+use crate::minimax_enhanced::{AIProvider, MinimaxAgent};
+
+/// Characters of markdown sent per request
+const MAX_CHUNK_CHARS: usize = 6_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Translation {
+    pub text: String,
+    pub target_language: String,
+    /// Where the translation was saved, relative to the knowledge base
+    pub saved_to: Option<String>,
+    pub chunks: usize,
+}
+
+/// A run of whole lines of the input; code fences and blank runs aren't translated
+#[derive(Debug, PartialEq)]
+struct Piece {
+    text: String,
+    translate: bool,
+}
+
+fn system_prompt(source: Option<&str>, target: &str) -> String {
+    let from = source.map(|s| format!(" from {}", s)).unwrap_or_default();
+    format!(
+        "You are a translator. Translate the markdown the user sends{} into {}. Keep every markdown \
+         element exactly as it is: headings, list markers, tables, emphasis, link and image targets, \
+         inline code, math, HTML tags and [[wiki links]]. Translate only the human-readable text, \
+         including link labels and image alt text. Reply with the translation only: no preamble, \
+         no notes, and no code fence around it.",
+        from, target
+    )
+}
+
+/// The opening fence of a code block (``` or ~~~, three or more), if `line` is one
+fn fence_of(line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence: String = trimmed.chars().take_while(|c| *c == marker).collect();
+    (fence.len() >= 3).then_some(fence)
+}
+
+/// Split `markdown` into pieces: code blocks as they are, prose in chunks of whole paragraphs
+fn split_pieces(markdown: &str) -> Vec<Piece> {
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut prose = String::new();
+    let mut paragraph = String::new();
+    let mut code: Option<(String, String)> = None;
+
+    // Adds a finished paragraph to the current chunk, starting a new chunk if it's full
+    fn end_paragraph(pieces: &mut Vec<Piece>, prose: &mut String, paragraph: &mut String) {
+        if !prose.is_empty() && prose.len() + paragraph.len() > MAX_CHUNK_CHARS {
+            pieces.push(Piece { text: std::mem::take(prose), translate: true });
+        }
+        prose.push_str(&std::mem::take(paragraph));
+    }
+
+    for line in markdown.split_inclusive('\n') {
+        if let Some((fence, block)) = code.as_mut() {
+            block.push_str(line);
+            let closing = line.trim();
+            if closing.starts_with(fence.as_str()) && closing.chars().all(|c| c == fence.chars().next().unwrap_or('`')) {
+                let (_, block) = code.take().unwrap_or_default();
+                pieces.push(Piece { text: block, translate: false });
+            }
+            continue;
+        }
+        if let Some(fence) = fence_of(line) {
+            end_paragraph(&mut pieces, &mut prose, &mut paragraph);
+            if !prose.is_empty() {
+                pieces.push(Piece { text: std::mem::take(&mut prose), translate: true });
+            }
+            code = Some((fence, line.to_string()));
+            continue;
+        }
+        paragraph.push_str(line);
+        if line.trim().is_empty() {
+            end_paragraph(&mut pieces, &mut prose, &mut paragraph);
+        }
+    }
+    // An unclosed fence runs to the end, as markdown renders it
+    if let Some((_, block)) = code {
+        pieces.push(Piece { text: block, translate: false });
+    }
+    end_paragraph(&mut pieces, &mut prose, &mut paragraph);
+    if !prose.is_empty() {
+        pieces.push(Piece { text: prose, translate: true });
+    }
+    for piece in pieces.iter_mut().filter(|p| p.text.trim().is_empty()) {
+        piece.translate = false;
+    }
+    pieces
+}
+
+/// `translated` with the blank lines around `original` put back, and any code fence the
+/// model wrapped it in removed
+fn reattach(original: &str, translated: &str) -> String {
+    let mut text = translated.trim();
+    if text.starts_with("```") && text.ends_with("```") && !original.trim_start().starts_with("```") {
+        let inner = text.trim_start_matches('`');
+        // Past the fence's info string ("markdown")
+        text = inner.split_once('\n').map_or("", |(_, rest)| rest).trim_end_matches('`').trim();
+    }
+    let leading = &original[..original.len() - original.trim_start().len()];
+    let trailing = &original[original.trim_end().len()..];
+    format!("{}{}{}", leading, text, trailing)
+}
+
+/// Translate `markdown`, asking `agent_for` (given the system prompt) for a fresh agent per chunk
+pub async fn translate_markdown<F>(agent_for: F, markdown: &str, source: Option<&str>, target: &str) -> Result<(String, usize), String>
+where
+    F: Fn(String) -> MinimaxAgent,
+{
+    let prompt = system_prompt(source, target);
+    let mut out = String::new();
+    let mut chunks = 0;
+    for piece in split_pieces(markdown) {
+        if !piece.translate {
+            out.push_str(&piece.text);
+            continue;
+        }
+        let mut agent = agent_for(prompt.clone());
+        let translated = agent.run_autonomous_task(piece.text.clone()).await?;
+        out.push_str(&reattach(&piece.text, &translated));
+        chunks += 1;
+    }
+    Ok((out, chunks))
+}
+
+/// Where the `target` translation of the note at `path` is saved
+pub fn translation_key(path: &str, target: &str) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let file = format!("{}.{}.md", stem, crate::research_notes::slugify(target));
+    path.with_file_name(file).to_string_lossy().replace('\\', "/")
+}
+
+/// The translated note, with the original's frontmatter plus `language` and `translated_from`
+fn translated_note(original: &str, translated_body: &str, path: &str, target: &str) -> String {
+    let body = crate::frontmatter::split_frontmatter(original).1;
+    let fields = format!(
+        "language: {}\ntranslated_from: {}\n",
+        serde_json::to_string(target).unwrap_or_default(),
+        serde_json::to_string(path).unwrap_or_default()
+    );
+    // Kept even when its YAML doesn't parse
+    let block = if body.len() < original.len() {
+        let keep: String = original[..original.len() - body.len()]
+            .trim_end()
+            .trim_end_matches("---")
+            .split_inclusive('\n')
+            .filter(|l| !l.starts_with("language:") && !l.starts_with("translated_from:"))
+            .collect();
+        format!("{}{}---\n", keep, fields)
+    } else {
+        format!("---\n{}---\n\n", fields)
+    };
+    format!("{}{}", block, translated_body)
+}
+
+/// `path` inside the knowledge base, if it's a note that doesn't climb out of it
+fn kb_note_path(kb_root: &Path, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err("Path must be relative to the knowledge base".to_string());
+    }
+    if relative.extension().map_or(true, |e| e != "md") {
+        return Err("Only markdown (.md) notes can be translated".to_string());
+    }
+    Ok(kb_root.join(relative))
+}
+
+/// Translate `text`, or the note at `path` (relative to the knowledge base) and optionally save it
+pub async fn translate<F>(
+    app_handle: Option<&tauri::AppHandle>,
+    agent_for: F,
+    text: Option<String>,
+    path: Option<String>,
+    source: Option<String>,
+    target: &str,
+    save: bool,
+) -> Result<Translation, String>
+where
+    F: Fn(String) -> MinimaxAgent,
+{
+    if target.trim().is_empty() {
+        return Err("Missing target language".to_string());
+    }
+    let source = source.filter(|s| !s.trim().is_empty());
+    let kb_root = MinimaxAgent::get_knowledge_base_path()?;
+    let (original, note) = match (text, path) {
+        (Some(text), _) if !text.trim().is_empty() => (text, None),
+        (_, Some(path)) => {
+            let full_path = kb_note_path(&kb_root, &path)?;
+            let content = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            (content, Some(path))
+        }
+        _ => return Err("Give the text or the path of a note to translate".to_string()),
+    };
+
+    let body = crate::frontmatter::split_frontmatter(&original).1;
+    let (translated, chunks) = translate_markdown(agent_for, body, source.as_deref(), target).await?;
+    tracing::info!("🌐 Translated {} chunk(s) into {}", chunks, target);
+
+    let saved_to = match note.filter(|_| save) {
+        Some(path) => {
+            let key = translation_key(&path, target);
+            let dest = kb_root.join(&key);
+            crate::note_versions::record_before_write(app_handle, &kb_root, &dest, "translate");
+            std::fs::write(&dest, translated_note(&original, &translated, &path, target))
+                .map_err(|e| format!("Failed to save the translation: {}", e))?;
+            if let Some(handle) = app_handle {
+                crate::kb_index::apply_watch_changes(handle, &[dest.clone()], None);
+                crate::events::emit(handle, "content-changed", serde_json::json!({
+                    "source": dest.parent().unwrap_or(&kb_root),
+                    "paths": [&key],
+                }));
+            }
+            Some(key)
+        }
+        None => None,
+    };
+    Ok(Translation { text: translated, target_language: target.to_string(), saved_to, chunks })
+}
+
+// ==================== Commands ====================
+
+/// Translate `text` or the note at `path` into `target_language` with `provider`; `save`
+/// writes a note's translation next to it
+#[tauri::command]
+pub async fn translate_text(
+    app_handle: tauri::AppHandle,
+    provider: AIProvider,
+    api_key: String,
+    grok_key: Option<String>,
+    gemini_key: Option<String>,
+    text: Option<String>,
+    path: Option<String>,
+    source_language: Option<String>,
+    target_language: String,
+    save: Option<bool>,
+) -> Result<Translation, String> {
+    let keys = ProviderKeys::new(&provider, &api_key, grok_key, gemini_key, None);
+    let agent_for = |prompt: String| keys.agent(provider.clone(), prompt);
+    translate(Some(&app_handle), agent_for, text, path, source_language, &target_language, save.unwrap_or(false)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pieces_keeps_code_blocks_apart() {
+        let note = "# Titel\n\nErster Absatz.\n\n```rust\nlet x = 1; // Kommentar\n\n```\n\nLetzter Absatz.\n";
+        let pieces = split_pieces(note);
+        assert_eq!(pieces.iter().map(|p| p.translate).collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(pieces[1].text, "```rust\nlet x = 1; // Kommentar\n\n```\n");
+        assert_eq!(pieces.iter().map(|p| p.text.as_str()).collect::<String>(), note);
+    }
+
+    #[test]
+    fn test_split_pieces_chunks_long_prose() {
+        let long = "Satz. ".repeat(700) + "\n\n";
+        let pieces = split_pieces(&format!("{}{}", long, long));
+        assert_eq!(pieces.len(), 2);
+    }
+
+    #[test]
+    fn test_blank_pieces_are_not_translated() {
+        assert!(split_pieces("\n\n").iter().all(|p| !p.translate));
+    }
+
+    #[test]
+    fn test_reattach() {
+        assert_eq!(reattach("\n# Titel\n\nText\n\n", "# Title\n\nText"), "\n# Title\n\nText\n\n");
+        assert_eq!(reattach("Hallo\n", "```markdown\nHello\n```"), "Hello\n");
+    }
+
+    #[test]
+    fn test_translation_key() {
+        assert_eq!(translation_key("research/Artikel.md", "English"), "research/Artikel.english.md");
+        assert_eq!(translation_key("Notiz.md", "pt-BR"), "Notiz.pt-br.md");
+    }
+
+    #[test]
+    fn test_translated_note_replaces_the_language_fields() {
+        let saved = translated_note("---\ntitle: Artikel\nlanguage: \"German\"\n---\n\n# Titel\n", "# Title\n", "research/Artikel.md", "English");
+        assert_eq!(saved, "---\ntitle: Artikel\nlanguage: \"English\"\ntranslated_from: \"research/Artikel.md\"\n---\n# Title\n");
+    }
+
+    #[test]
+    fn test_translated_note_without_frontmatter() {
+        assert!(translated_note("# Titel\n", "# Title\n", "a.md", "English").starts_with("---\nlanguage: \"English\"\ntranslated_from: \"a.md\"\n---\n\n# Title"));
+    }
+
+    #[test]
+    fn test_kb_note_path() {
+        assert!(kb_note_path(Path::new("/kb"), "../a.md").is_err());
+        assert!(kb_note_path(Path::new("/kb"), "notes/a.pdf").is_err());
+    }
+}
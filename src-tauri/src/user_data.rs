@@ -0,0 +1,882 @@
+/// Everything the app keeps about a user, as one archive
+///
+/// `export_all_data` writes a zip for backup or for moving to another machine:
+/// - `projects/data.db` and `databases/knowledge_companion.db`, snapshotted with `VACUUM INTO`
+///   so a write in progress can't leave a torn copy
+/// - `sessions/`, the active profile's saved sessions
+/// - `tkg/points.jsonl`, the profile's TKG memories with their vectors, when the TKG is set up
+/// - `config/`, thinkspace.toml and the other settings files with API keys and tokens removed
+/// - `knowledge-base/`, the knowledge base folders
+/// - `manifest.json`, what each section holds and anything that was left out
+///
+/// `restore_all_data` puts such an archive back on this machine (backup.rs restores cloud
+/// backups the same way). Like a wipe it reports first, and settings stay as they are since
+/// the archive has them without their secrets.
+///
+/// `wipe_user_data` removes the same data for shared machines. It always reports first: a
+/// call without `confirm` deletes nothing and returns what would go plus a code, and only a
+/// call passing that code back carries out the plan (the code changes if the data does).
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use walkdir::WalkDir;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+/// Settings in app data besides the profile's thinkspace.toml
+const SETTINGS_FILES: [&str; 3] = ["search_settings.json", "watch_paths.json", "profiles.json"];
+/// Settings whose key ends with one of these never leave the machine
+const SECRET_KEYS: [&str; 7] = ["api_key", "apikey", "access_key", "token", "secret", "password", "passphrase"];
+const SKIP_DIRS: [&str; 3] = [".git", ".trash", "node_modules"];
+/// Files SQLite keeps next to a database
+const DB_SIDECARS: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportSection {
+    /// The section's folder in the archive
+    pub name: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub profile: String,
+    pub knowledge_base_root: String,
+    pub tkg_user_id: Option<String>,
+    pub sections: Vec<ExportSection>,
+    /// What was left out, and why
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataExport {
+    pub archive: String,
+    pub manifest: ExportManifest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    /// The archive's own manifest: when, where and from which profile it was made
+    pub manifest: ExportManifest,
+    /// Files written per section (or that would be, in a dry run)
+    pub restored: Vec<ExportSection>,
+    /// Files already the same on this machine
+    pub unchanged: usize,
+    /// TKG memories restored (or that would be)
+    pub tkg_points: usize,
+    /// What was left alone, and why
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WipeScope {
+    /// The active profile's saved sessions and the tool results stored for them
+    Sessions,
+    /// Projects and the Knowledge Companion database
+    Databases,
+    /// The profile's TKG memories
+    Tkg,
+    /// The knowledge base and repository indexes, rebuilt on next use
+    Indexes,
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WipeItem {
+    pub scope: WipeScope,
+    /// A file, or the TKG user id whose memories go
+    pub target: String,
+    /// Bytes of a file, points of the TKG
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WipeReport {
+    pub dry_run: bool,
+    pub items: Vec<WipeItem>,
+    /// Pass this back as `confirm` to carry out exactly this plan
+    pub confirmation: String,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Where the exported data comes from
+struct ExportSources {
+    app_data: PathBuf,
+    profile_dir: PathBuf,
+    kb_root: PathBuf,
+    /// The profile's TKG points, or why there are none
+    tkg_points: Result<Vec<serde_json::Value>, String>,
+}
+
+struct Archive {
+    zip: ZipWriter<File>,
+    options: FileOptions,
+    manifest: ExportManifest,
+}
+
+impl Archive {
+    fn count(&mut self, section: &str, bytes: u64) {
+        match self.manifest.sections.iter_mut().find(|s| s.name == section) {
+            Some(s) => {
+                s.files += 1;
+                s.bytes += bytes;
+            }
+            None => self.manifest.sections.push(ExportSection { name: section.to_string(), files: 1, bytes }),
+        }
+    }
+
+    fn add_bytes(&mut self, section: &str, name: &str, bytes: &[u8]) -> Result<(), String> {
+        self.zip.start_file(format!("{}/{}", section, name), self.options).map_err(|e| e.to_string())?;
+        self.zip.write_all(bytes).map_err(|e| e.to_string())?;
+        self.count(section, bytes.len() as u64);
+        Ok(())
+    }
+
+    /// Copy a file in; one that can't be opened is noted in the manifest instead
+    fn add_file(&mut self, section: &str, name: &str, path: &Path) -> Result<(), String> {
+        let mut file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                self.manifest.skipped.push(format!("{}/{}: {}", section, name, e));
+                return Ok(());
+            }
+        };
+        self.zip.start_file(format!("{}/{}", section, name), self.options).map_err(|e| e.to_string())?;
+        let bytes = std::io::copy(&mut file, &mut self.zip).map_err(|e| format!("Failed to archive {}: {}", path.display(), e))?;
+        self.count(section, bytes);
+        Ok(())
+    }
+
+    fn add_database(&mut self, section: &str, name: &str, db: &Path) -> Result<(), String> {
+        if !db.exists() {
+            self.manifest.skipped.push(format!("{}/{}: not created yet", section, name));
+            return Ok(());
+        }
+        let scratch = std::env::temp_dir().join(format!("thinkspace-export-{}.db", uuid::Uuid::new_v4().simple()));
+        let snapshot = rusqlite::Connection::open(db).and_then(|conn| conn.execute("VACUUM INTO ?1", [scratch.to_string_lossy().to_string()]));
+        let result = match snapshot {
+            Ok(_) => self.add_file(section, name, &scratch),
+            Err(e) => Err(format!("Failed to snapshot {}: {}", db.display(), e)),
+        };
+        let _ = std::fs::remove_file(&scratch);
+        result
+    }
+
+    /// A settings file with its secrets removed; missing files are simply not there to export
+    fn add_settings(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return Ok(()),
+        };
+        let cleaned = if name.ends_with(".toml") {
+            toml::from_str::<serde_json::Value>(&text).map_err(|e| e.to_string()).and_then(|mut value| {
+                strip_secrets(&mut value);
+                toml::to_string_pretty(&value).map_err(|e| e.to_string())
+            })
+        } else {
+            serde_json::from_str::<serde_json::Value>(&text).map_err(|e| e.to_string()).and_then(|mut value| {
+                strip_secrets(&mut value);
+                serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+            })
+        };
+        match cleaned {
+            Ok(text) => self.add_bytes("config", name, text.as_bytes()),
+            // Can't vouch for what an unparseable file holds, so it stays behind
+            Err(e) => {
+                self.manifest.skipped.push(format!("config/{}: unreadable ({})", name, e));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Remove every setting that looks like a credential; returns how many were removed
+pub fn strip_secrets(value: &mut serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Object(map) => {
+            let before = map.len();
+            map.retain(|key, _| {
+                let key = key.to_ascii_lowercase();
+                !SECRET_KEYS.iter().any(|secret| key.ends_with(secret))
+            });
+            let removed = before - map.len();
+            removed + map.values_mut().map(strip_secrets).sum::<usize>()
+        }
+        serde_json::Value::Array(items) => items.iter_mut().map(strip_secrets).sum(),
+        _ => 0,
+    }
+}
+
+/// `dest` itself when it names a .zip, otherwise a dated archive inside the `dest` folder
+fn archive_path(dest: &Path, profile: &str) -> PathBuf {
+    if dest.extension().map_or(false, |e| e.eq_ignore_ascii_case("zip")) {
+        dest.to_path_buf()
+    } else {
+        dest.join(format!("thinkspace-{}-{}.zip", profile, chrono::Local::now().format("%Y%m%d-%H%M%S")))
+    }
+}
+
+fn write_archive(sources: ExportSources, dest: &Path, manifest: ExportManifest) -> Result<ExportManifest, String> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut archive = Archive {
+        zip: ZipWriter::new(file),
+        options: FileOptions::default().compression_method(zip::CompressionMethod::Deflated).large_file(true),
+        manifest,
+    };
+
+    archive.add_database("projects", "data.db", &sources.app_data.join("data.db"))?;
+    archive.add_database("databases", "knowledge_companion.db", &sources.app_data.join("knowledge_companion.db"))?;
+
+    let mut sessions: Vec<PathBuf> = std::fs::read_dir(sources.profile_dir.join("sessions"))
+        .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_file()).collect())
+        .unwrap_or_default();
+    sessions.sort();
+    for path in &sessions {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        archive.add_file("sessions", &name, path)?;
+    }
+
+    match &sources.tkg_points {
+        Ok(points) => {
+            let mut lines = String::new();
+            for point in points {
+                lines.push_str(&point.to_string());
+                lines.push('\n');
+            }
+            archive.add_bytes("tkg", "points.jsonl", lines.as_bytes())?;
+        }
+        Err(reason) => archive.manifest.skipped.push(format!("tkg: {}", reason)),
+    }
+
+    archive.add_settings(crate::config::CONFIG_FILE, &sources.profile_dir.join(crate::config::CONFIG_FILE))?;
+    for name in SETTINGS_FILES {
+        archive.add_settings(name, &sources.app_data.join(name))?;
+    }
+
+    let folders = crate::kb_index::KB_FOLDERS.iter().chain(std::iter::once(&crate::scheduler::SCHEDULED_FOLDER));
+    for folder in folders {
+        let notes = WalkDir::new(sources.kb_root.join(folder))
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| !SKIP_DIRS.iter().any(|skip| e.file_name() == *skip))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.path() != dest);
+        for entry in notes {
+            let key = crate::kb_index::path_key(&sources.kb_root, entry.path());
+            archive.add_file("knowledge-base", &key, entry.path())?;
+        }
+    }
+
+    let manifest_json = serde_json::to_string_pretty(&archive.manifest).map_err(|e| e.to_string())?;
+    archive.zip.start_file(MANIFEST_FILE, archive.options).map_err(|e| e.to_string())?;
+    archive.zip.write_all(manifest_json.as_bytes()).map_err(|e| e.to_string())?;
+    archive.zip.finish().map_err(|e| e.to_string())?;
+    Ok(archive.manifest)
+}
+
+/// Bundle projects, sessions, databases, the profile's TKG memories, settings (without
+/// secrets) and the knowledge base into one zip at `dest` (a .zip path or a folder)
+#[tauri::command]
+pub async fn export_all_data(app_handle: AppHandle, dest: String, user_id: Option<String>) -> Result<DataExport, String> {
+    let archive = archive_path(Path::new(&dest), &crate::profiles::active_id());
+    export_to(&app_handle, archive, user_id.as_deref()).await
+}
+
+/// Write the export archive to exactly `archive`
+pub async fn export_to(app_handle: &AppHandle, archive: PathBuf, user_id: Option<&str>) -> Result<DataExport, String> {
+    let app_data = crate::profiles::app_data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?;
+    let profile_dir = crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?;
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let profile = crate::profiles::active_id();
+
+    let tkg_user_id = crate::profiles::tkg_user_id(user_id.unwrap_or_default());
+    let tkg_points = match crate::tkg::active_config() {
+        _ if tkg_user_id.is_empty() => Err("no user id to export memories for".to_string()),
+        None => Err("the TKG isn't initialized".to_string()),
+        Some(config) => {
+            let mut tkg = crate::tkg::TemporalKnowledgeGraph::new(config).with_clients(app_handle);
+            tkg.initialized = true;
+            tkg.export_points(&tkg_user_id).await
+        }
+    };
+
+    let manifest = ExportManifest {
+        version: MANIFEST_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: crate::scheduler::stamp(chrono::Utc::now()),
+        profile,
+        knowledge_base_root: kb_root.to_string_lossy().to_string(),
+        tkg_user_id: Some(tkg_user_id).filter(|id| !id.is_empty()),
+        ..Default::default()
+    };
+    let sources = ExportSources { app_data, profile_dir, kb_root, tkg_points };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let manifest = write_archive(sources, &archive, manifest)?;
+        let files: usize = manifest.sections.iter().map(|s| s.files).sum();
+        tracing::info!("📦 Exported {} files to {} ({} skipped)", files, archive.display(), manifest.skipped.len());
+        Ok(DataExport { archive: archive.to_string_lossy().to_string(), manifest })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Where a restored archive goes
+struct RestoreTargets {
+    app_data: PathBuf,
+    profile_dir: PathBuf,
+    kb_root: PathBuf,
+}
+
+/// What `restore_files` did, plus what's left for the caller
+struct Restored {
+    report: RestoreReport,
+    /// TKG points from the archive, to upsert once the files are in place
+    points: Vec<serde_json::Value>,
+    /// Knowledge base files written
+    kb_written: Vec<PathBuf>,
+}
+
+/// Replace `dest` with `bytes` in one rename; a database's WAL and journal go first so SQLite
+/// can't replay them onto the restored file
+fn replace_file(dest: &Path, bytes: &[u8], database: bool) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let scratch = dest.with_file_name(format!(".{}.restoring", dest.file_name().unwrap_or_default().to_string_lossy()));
+    std::fs::write(&scratch, bytes).map_err(|e| format!("Failed to write {}: {}", scratch.display(), e))?;
+    if database {
+        // Pooled connections would go on writing to the file being replaced
+        crate::db::evict(dest);
+        for sidecar in DB_SIDECARS {
+            let _ = std::fs::remove_file(format!("{}{}", dest.display(), sidecar));
+        }
+    }
+    std::fs::rename(&scratch, dest).map_err(|e| format!("Failed to replace {}: {}", dest.display(), e))?;
+    if database {
+        // Nor may a connection opened while the file was being swapped
+        crate::db::evict(dest);
+    }
+    Ok(())
+}
+
+/// Put the files of an export archive back, or with `dry_run` only count what would change.
+/// Settings aren't restored: they were exported without their secrets.
+fn restore_files(archive: &Path, targets: &RestoreTargets, dry_run: bool, before_kb_write: &dyn Fn(&Path)) -> Result<Restored, String> {
+    let file = File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a ThinkSpace data export: {}", e))?;
+    let manifest: ExportManifest = {
+        let entry = zip.by_name(MANIFEST_FILE).map_err(|_| "Not a ThinkSpace data export: it has no manifest".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Unreadable export manifest: {}", e))?
+    };
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!("This export was made by a newer ThinkSpace ({})", manifest.app_version));
+    }
+
+    let mut restored = Restored {
+        report: RestoreReport { dry_run, manifest, restored: Vec::new(), unchanged: 0, tkg_points: 0, skipped: Vec::new() },
+        points: Vec::new(),
+        kb_written: Vec::new(),
+    };
+    let report = &mut restored.report;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|e| e.to_string())?;
+        if entry.is_dir() || entry.name() == MANIFEST_FILE {
+            continue;
+        }
+        // Entries that would land outside their folder are never followed
+        let name = match entry.enclosed_name() {
+            Some(name) => name.to_path_buf(),
+            None => {
+                report.skipped.push(format!("{}: unsafe path", entry.name()));
+                continue;
+            }
+        };
+        let mut components = name.components();
+        let section = components.next().map(|c| c.as_os_str().to_string_lossy().to_string()).unwrap_or_default();
+        let rest = components.as_path();
+        let (dest, database) = match (section.as_str(), rest.to_string_lossy().as_ref()) {
+            ("projects", "data.db") => (targets.app_data.join("data.db"), true),
+            ("databases", "knowledge_companion.db") => (targets.app_data.join("knowledge_companion.db"), true),
+            ("sessions", _) if rest.components().count() == 1 => (targets.profile_dir.join("sessions").join(rest), false),
+            ("knowledge-base", _) => (targets.kb_root.join(rest), false),
+            ("tkg", "points.jsonl") => {
+                let mut lines = String::new();
+                entry.read_to_string(&mut lines).map_err(|e| format!("Failed to read {}: {}", name.display(), e))?;
+                restored.points = lines.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+                report.tkg_points = restored.points.len();
+                continue;
+            }
+            ("config", _) => {
+                report.skipped.push(format!("{}: settings are left as they are on this machine", name.display()));
+                continue;
+            }
+            _ => {
+                report.skipped.push(format!("{}: not part of a data export", name.display()));
+                continue;
+            }
+        };
+
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut bytes).map_err(|e| format!("Failed to read {}: {}", name.display(), e))?;
+        if std::fs::read(&dest).map_or(false, |current| current == bytes) {
+            report.unchanged += 1;
+            continue;
+        }
+        match report.restored.iter_mut().find(|s| s.name == section) {
+            Some(s) => {
+                s.files += 1;
+                s.bytes += bytes.len() as u64;
+            }
+            None => report.restored.push(ExportSection { name: section.clone(), files: 1, bytes: bytes.len() as u64 }),
+        }
+        if dry_run {
+            continue;
+        }
+        if section == "knowledge-base" {
+            before_kb_write(&dest);
+            restored.kb_written.push(dest.clone());
+        }
+        replace_file(&dest, &bytes, database)?;
+    }
+    Ok(restored)
+}
+
+/// Restore an export archive onto this machine's active profile: databases, sessions, the
+/// knowledge base and, when the TKG is set up, the archive's memories. With `dry_run`
+/// nothing changes and the report says what would.
+pub async fn restore_archive(app_handle: &AppHandle, archive: PathBuf, dry_run: bool) -> Result<RestoreReport, String> {
+    let targets = RestoreTargets {
+        app_data: crate::profiles::app_data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?,
+        profile_dir: crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?,
+        kb_root: crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?,
+    };
+    let kb_root = targets.kb_root.clone();
+
+    let restored = {
+        // Index once at the end instead of reacting to every restored note
+        let _watch_pause = crate::file_watcher::pause_for_writes(vec![kb_root.clone()]);
+        let handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let before_kb_write = |path: &Path| crate::note_versions::record_before_write(Some(&handle), &targets.kb_root, path, "restore");
+            restore_files(&archive, &targets, dry_run, &before_kb_write)
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+    let mut report = restored.report;
+    if dry_run {
+        return Ok(report);
+    }
+
+    if !restored.points.is_empty() {
+        match crate::tkg::active_config() {
+            Some(config) => {
+                let mut tkg = crate::tkg::TemporalKnowledgeGraph::new(config).with_clients(app_handle);
+                tkg.initialized = true;
+                if let Err(e) = tkg.upsert_points(&restored.points).await {
+                    report.skipped.push(format!("tkg: {}", e));
+                    report.tkg_points = 0;
+                }
+            }
+            None => {
+                report.skipped.push("tkg: the TKG isn't initialized".to_string());
+                report.tkg_points = 0;
+            }
+        }
+    }
+    if !restored.kb_written.is_empty() {
+        crate::kb_index::apply_watch_changes(app_handle, &restored.kb_written, None);
+        let keys: Vec<String> = restored.kb_written.iter().map(|p| crate::kb_index::path_key(&kb_root, p)).collect();
+        crate::events::emit(app_handle, "content-changed", serde_json::json!({
+            "source": "restore",
+            "paths": keys,
+        }));
+    }
+
+    let files: usize = report.restored.iter().map(|s| s.files).sum();
+    tracing::info!("📦 Restored {} files and {} memories ({} unchanged)", files, report.tkg_points, report.unchanged);
+    let _ = app_handle.emit_all("user-data-restored", &report);
+    Ok(report)
+}
+
+/// Restore an archive written by `export_all_data`. Without `confirm` nothing changes: the
+/// report lists what the archive holds and what it would overwrite.
+#[tauri::command]
+pub async fn restore_all_data(app_handle: AppHandle, archive: String, confirm: Option<bool>) -> Result<RestoreReport, String> {
+    restore_archive(&app_handle, PathBuf::from(archive), !confirm.unwrap_or(false)).await
+}
+
+/// The files a wipe of `scopes` removes, as they are now
+fn wipe_files(app_data: &Path, profile_dir: &Path, scopes: &[WipeScope]) -> Vec<WipeItem> {
+    let wants = |scope: WipeScope| scopes.contains(&scope) || scopes.contains(&WipeScope::All);
+    let mut files = Vec::new();
+    if wants(WipeScope::Sessions) {
+        // Stored tool results belong to the conversations that produced them
+        let mut sessions: Vec<PathBuf> = ["sessions", crate::tool_results::RESULTS_FOLDER]
+            .iter()
+            .filter_map(|folder| std::fs::read_dir(profile_dir.join(folder)).ok())
+            .flat_map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())))
+            .collect();
+        sessions.sort();
+        files.extend(sessions.into_iter().map(|path| (WipeScope::Sessions, path)));
+    }
+    let databases = [
+        (WipeScope::Databases, "data.db"),
+        (WipeScope::Databases, "knowledge_companion.db"),
+        (WipeScope::Indexes, "kb_index.db"),
+        (WipeScope::Indexes, "repo_index.db"),
+    ];
+    for (scope, name) in databases.into_iter().filter(|(scope, _)| wants(*scope)) {
+        files.push((scope, app_data.join(name)));
+        files.extend(DB_SIDECARS.iter().map(|sidecar| (scope, app_data.join(format!("{}{}", name, sidecar)))));
+    }
+
+    files
+        .into_iter()
+        .filter_map(|(scope, path)| {
+            let meta = std::fs::metadata(&path).ok().filter(|m| m.is_file())?;
+            Some(WipeItem { scope, target: path.to_string_lossy().to_string(), size: meta.len() })
+        })
+        .collect()
+}
+
+/// A short code for a plan; any change to what would be wiped changes it
+fn confirmation_code(items: &[WipeItem]) -> String {
+    let mut hasher = Sha256::new();
+    for item in items {
+        hasher.update(format!("{:?}\t{}\t{}\n", item.scope, item.target, item.size));
+    }
+    format!("{:x}", hasher.finalize())[..8].to_string()
+}
+
+/// Overwrite a file with zeros before removing it, so undeleting doesn't bring it back.
+/// SSDs and copy-on-write filesystems may still hold old blocks.
+fn shred(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 64 * 1024];
+    let mut left = len;
+    while left > 0 {
+        let chunk = left.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        left -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)
+}
+
+/// Delete the active profile's sessions, the databases, the profile's TKG memories and the
+/// cached indexes (`scope` picks which). Without `confirm` nothing is deleted: the report
+/// lists what would be and carries the code to pass back as `confirm`.
+#[tauri::command]
+pub async fn wipe_user_data(
+    app_handle: AppHandle,
+    scope: Vec<WipeScope>,
+    confirm: Option<String>,
+    user_id: Option<String>,
+) -> Result<WipeReport, String> {
+    if scope.is_empty() {
+        return Err("Choose what to wipe: sessions, databases, tkg, indexes or all".to_string());
+    }
+    let app_data = crate::profiles::app_data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+    let profile_dir = crate::profiles::data_dir(Some(&app_handle)).ok_or("Failed to get app data dir")?;
+
+    let mut items = wipe_files(&app_data, &profile_dir, &scope);
+    let mut skipped = Vec::new();
+    let mut tkg = None;
+    if scope.contains(&WipeScope::Tkg) || scope.contains(&WipeScope::All) {
+        let tkg_user_id = crate::profiles::tkg_user_id(user_id.as_deref().unwrap_or_default());
+        match crate::tkg::active_config() {
+            _ if tkg_user_id.is_empty() => skipped.push("tkg: no user id to wipe memories for".to_string()),
+            None => skipped.push("tkg: the TKG isn't initialized".to_string()),
+            Some(config) => {
+                let mut graph = crate::tkg::TemporalKnowledgeGraph::new(config).with_clients(&app_handle);
+                graph.initialized = true;
+                let points = graph.count_points(&tkg_user_id).await?;
+                items.push(WipeItem { scope: WipeScope::Tkg, target: tkg_user_id.clone(), size: points });
+                tkg = Some((graph, tkg_user_id));
+            }
+        }
+    }
+
+    let confirmation = confirmation_code(&items);
+    let mut report = WipeReport { dry_run: true, items, confirmation, skipped, errors: Vec::new() };
+    match confirm.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        None => return Ok(report),
+        Some(code) if code != report.confirmation => {
+            return Err("The confirmation code doesn't match what would be wiped now; run the dry run again".to_string());
+        }
+        Some(_) => report.dry_run = false,
+    }
+
+    let files: Vec<WipeItem> = report.items.iter().filter(|i| i.scope != WipeScope::Tkg).cloned().collect();
+    let errors = tauri::async_runtime::spawn_blocking(move || {
+        // Pooled connections would go on writing to the deleted databases
+        for item in &files {
+            crate::db::evict(Path::new(&item.target));
+        }
+        files
+            .iter()
+            .filter_map(|item| shred(Path::new(&item.target)).err().map(|e| format!("{}: {}", item.target, e)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    report.errors.extend(errors);
+    if let Some((graph, tkg_user_id)) = tkg {
+        if let Err(e) = graph.delete_points(&tkg_user_id).await {
+            report.errors.push(format!("tkg: {}", e));
+        }
+    }
+
+    tracing::warn!("🧹 Wiped {} item(s) of user data ({} failed)", report.items.len(), report.errors.len());
+    let _ = app_handle.emit_all("user-data-wiped", &report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An export of a profile with a project, a session, two config files holding secrets and a
+    /// knowledge base with a git folder, while the TKG is down
+    struct Exported {
+        _app_data: tempfile::TempDir,
+        _kb: tempfile::TempDir,
+        out: tempfile::TempDir,
+        dest: PathBuf,
+        manifest: ExportManifest,
+    }
+
+    fn exported() -> Exported {
+        let app_data = tempfile::tempdir().unwrap();
+        let kb = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+
+        let conn = rusqlite::Connection::open(app_data.path().join("data.db")).unwrap();
+        conn.execute_batch("CREATE TABLE projects (name TEXT); INSERT INTO projects VALUES ('Orbit');").unwrap();
+        drop(conn);
+        std::fs::create_dir_all(app_data.path().join("sessions")).unwrap();
+        std::fs::write(app_data.path().join("sessions/monday.json"), r#"{"name":"monday"}"#).unwrap();
+        std::fs::write(
+            app_data.path().join("search_settings.json"),
+            r#"{"provider":"brave","brave_api_key":"sk-123","searxng_url":null}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            app_data.path().join("thinkspace.toml"),
+            "[general]\ntimezone = \"utc\"\n\n[providers.grok]\nmodel = \"grok-3\"\napi_key = \"xai-456\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(kb.path().join("research/.git")).unwrap();
+        std::fs::write(kb.path().join("research/rust.md"), "# Rust\n").unwrap();
+        std::fs::write(kb.path().join("research/.git/HEAD"), "ref").unwrap();
+
+        let sources = ExportSources {
+            app_data: app_data.path().to_path_buf(),
+            profile_dir: app_data.path().to_path_buf(),
+            kb_root: kb.path().to_path_buf(),
+            tkg_points: Err("the TKG isn't initialized".to_string()),
+        };
+        let dest = archive_path(out.path(), "guest");
+        let manifest = write_archive(sources, &dest, ExportManifest { version: MANIFEST_VERSION, ..Default::default() }).unwrap();
+        Exported { _app_data: app_data, _kb: kb, out, dest, manifest }
+    }
+
+    fn read_entry(archive: &Path, name: &str) -> Vec<u8> {
+        let mut zip = zip::ZipArchive::new(File::open(archive).unwrap()).unwrap();
+        let mut bytes = Vec::new();
+        zip.by_name(name).unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_export_sections() {
+        let manifest = exported().manifest;
+        let files = |name: &str| manifest.sections.iter().find(|s| s.name == name).map_or(0, |s| s.files);
+        assert_eq!((files("projects"), files("sessions"), files("config"), files("knowledge-base")), (1, 1, 2, 1));
+        assert_eq!(files("databases"), 0);
+        assert!(manifest.skipped.iter().any(|s| s.starts_with("databases/knowledge_companion.db")));
+        assert!(manifest.skipped.iter().any(|s| s.starts_with("tkg:")));
+    }
+
+    #[test]
+    fn test_export_leaves_out_secrets() {
+        let export = exported();
+        let search = String::from_utf8(read_entry(&export.dest, "config/search_settings.json")).unwrap();
+        assert!(search.contains("brave") && !search.contains("sk-123"));
+        let config = String::from_utf8(read_entry(&export.dest, "config/thinkspace.toml")).unwrap();
+        assert!(config.contains("grok-3") && !config.contains("xai-456"));
+    }
+
+    #[test]
+    fn test_export_knowledge_base_without_git() {
+        let export = exported();
+        assert_eq!(read_entry(&export.dest, "knowledge-base/research/rust.md"), b"# Rust\n");
+        let mut zip = zip::ZipArchive::new(File::open(&export.dest).unwrap()).unwrap();
+        assert!(zip.by_name("knowledge-base/research/.git/HEAD").is_err());
+    }
+
+    #[test]
+    fn test_export_saves_its_manifest() {
+        let export = exported();
+        let saved: ExportManifest = serde_json::from_slice(&read_entry(&export.dest, MANIFEST_FILE)).unwrap();
+        assert_eq!(saved.sections, export.manifest.sections);
+    }
+
+    #[test]
+    fn test_exported_database_opens_on_its_own() {
+        let export = exported();
+        let projects = export.out.path().join("projects.db");
+        std::fs::write(&projects, read_entry(&export.dest, "projects/data.db")).unwrap();
+        let name: String = rusqlite::Connection::open(&projects).unwrap().query_row("SELECT name FROM projects", [], |r| r.get(0)).unwrap();
+        assert_eq!(name, "Orbit");
+    }
+
+    /// An archive with a project, a session, search settings, a note and a TKG point, and a
+    /// profile to restore it into that has an older copy of the note and a stale WAL file
+    struct Restore {
+        _from: tempfile::TempDir,
+        to: tempfile::TempDir,
+        archive: PathBuf,
+        targets: RestoreTargets,
+    }
+
+    fn restore_case() -> Restore {
+        let from = tempfile::tempdir().unwrap();
+        let to = tempfile::tempdir().unwrap();
+        let conn = rusqlite::Connection::open(from.path().join("data.db")).unwrap();
+        conn.execute_batch("CREATE TABLE projects (name TEXT); INSERT INTO projects VALUES ('Orbit');").unwrap();
+        drop(conn);
+        std::fs::create_dir_all(from.path().join("sessions")).unwrap();
+        std::fs::write(from.path().join("sessions/monday.json"), r#"{"name":"monday"}"#).unwrap();
+        std::fs::write(from.path().join("search_settings.json"), r#"{"provider":"brave"}"#).unwrap();
+        std::fs::create_dir_all(from.path().join("kb/research")).unwrap();
+        std::fs::write(from.path().join("kb/research/rust.md"), "# Rust\n").unwrap();
+
+        let sources = ExportSources {
+            app_data: from.path().to_path_buf(),
+            profile_dir: from.path().to_path_buf(),
+            kb_root: from.path().join("kb"),
+            tkg_points: Ok(vec![serde_json::json!({ "id": "p1", "payload": { "user_id": "sam" } })]),
+        };
+        let archive = from.path().join("export.zip");
+        write_archive(sources, &archive, ExportManifest { version: MANIFEST_VERSION, ..Default::default() }).unwrap();
+
+        let targets = RestoreTargets {
+            app_data: to.path().join("app"),
+            profile_dir: to.path().join("app"),
+            kb_root: to.path().join("kb"),
+        };
+        std::fs::create_dir_all(to.path().join("kb/research")).unwrap();
+        std::fs::write(to.path().join("kb/research/rust.md"), "# Old\n").unwrap();
+        std::fs::create_dir_all(to.path().join("app")).unwrap();
+        std::fs::write(to.path().join("app/data.db-wal"), "stale").unwrap();
+        Restore { _from: from, to, archive, targets }
+    }
+
+    fn restored_sections(restored: &Restored) -> Vec<(String, usize)> {
+        restored.report.restored.iter().map(|s| (s.name.clone(), s.files)).collect()
+    }
+
+    fn archived_sections() -> Vec<(String, usize)> {
+        vec![("projects".to_string(), 1), ("sessions".to_string(), 1), ("knowledge-base".to_string(), 1)]
+    }
+
+    #[test]
+    fn test_restore_dry_run_changes_nothing() {
+        let case = restore_case();
+        let dry = restore_files(&case.archive, &case.targets, true, &|_: &Path| {}).unwrap();
+        assert_eq!(restored_sections(&dry), archived_sections());
+        assert_eq!(dry.report.tkg_points, 1);
+        assert!(dry.report.skipped.iter().any(|s| s.starts_with("config/search_settings.json")));
+        assert_eq!(std::fs::read_to_string(case.to.path().join("kb/research/rust.md")).unwrap(), "# Old\n");
+        assert!(!case.to.path().join("app/sessions").exists());
+    }
+
+    #[test]
+    fn test_restore_archive() {
+        let case = restore_case();
+        let to = case.to.path();
+        let snapshots = std::cell::RefCell::new(Vec::new());
+        let done = restore_files(&case.archive, &case.targets, false, &|path: &Path| snapshots.borrow_mut().push(path.to_path_buf())).unwrap();
+        assert_eq!(restored_sections(&done), archived_sections());
+        assert_eq!(done.points[0]["id"], "p1");
+        assert_eq!(snapshots.into_inner(), vec![to.join("kb/research/rust.md")]);
+        assert_eq!(std::fs::read_to_string(to.join("kb/research/rust.md")).unwrap(), "# Rust\n");
+        assert!(to.join("app/sessions/monday.json").exists());
+        assert!(!to.join("app/data.db-wal").exists());
+        let name: String = rusqlite::Connection::open(to.join("app/data.db")).unwrap().query_row("SELECT name FROM projects", [], |r| r.get(0)).unwrap();
+        assert_eq!(name, "Orbit");
+    }
+
+    #[test]
+    fn test_restore_again_finds_everything_in_place() {
+        let case = restore_case();
+        restore_files(&case.archive, &case.targets, false, &|_: &Path| {}).unwrap();
+        let again = restore_files(&case.archive, &case.targets, false, &|_: &Path| {}).unwrap();
+        assert!(again.report.restored.is_empty());
+        assert_eq!(again.report.unchanged, 3);
+    }
+
+    fn wipe_app_data() -> tempfile::TempDir {
+        let app_data = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(app_data.path().join("sessions")).unwrap();
+        std::fs::write(app_data.path().join("sessions/monday.json"), "{}").unwrap();
+        std::fs::write(app_data.path().join("data.db"), "projects").unwrap();
+        std::fs::write(app_data.path().join("data.db-wal"), "wal").unwrap();
+        std::fs::write(app_data.path().join("kb_index.db"), "index").unwrap();
+        std::fs::write(app_data.path().join("profiles.json"), "{}").unwrap();
+        app_data
+    }
+
+    #[test]
+    fn test_wipe_plan() {
+        let app_data = wipe_app_data();
+        let targets = |scopes: &[WipeScope]| {
+            wipe_files(app_data.path(), app_data.path(), scopes)
+                .into_iter()
+                .map(|i| Path::new(&i.target).file_name().unwrap().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(targets(&[WipeScope::Sessions]), vec!["monday.json"]);
+        assert_eq!(targets(&[WipeScope::Databases, WipeScope::Indexes]), vec!["data.db", "data.db-wal", "kb_index.db"]);
+        assert_eq!(targets(&[WipeScope::All]).len(), 4);
+    }
+
+    #[test]
+    fn test_confirmation_code_matches_its_plan() {
+        // The code only confirms the plan it was issued for
+        let app_data = wipe_app_data();
+        let plan = wipe_files(app_data.path(), app_data.path(), &[WipeScope::All]);
+        let code = confirmation_code(&plan);
+        std::fs::write(app_data.path().join("sessions/tuesday.json"), "{}").unwrap();
+        assert_ne!(confirmation_code(&wipe_files(app_data.path(), app_data.path(), &[WipeScope::All])), code);
+    }
+
+    #[test]
+    fn test_shred() {
+        let app_data = wipe_app_data();
+        shred(&app_data.path().join("data.db")).unwrap();
+        assert!(!app_data.path().join("data.db").exists());
+        assert!(app_data.path().join("profiles.json").exists());
+    }
+}
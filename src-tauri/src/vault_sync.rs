@@ -0,0 +1,559 @@
+/// Two-way sync between an Obsidian vault and a knowledge base folder
+///
+/// `[obsidian] vault` in thinkspace.toml names the vault; its notes and attachments are mirrored
+/// into `[obsidian] folder` (collections/obsidian by default), so notes written in Obsidian show
+/// up in ThinkSpace search and notes written here show up in Obsidian. `.obsidian/`, `.trash/`
+/// and `.git/` are never synced in either direction.
+///
+/// Each sync compares both sides with the content hashes recorded by the previous one (kept in
+/// the profile's `obsidian_sync.json`): a file that changed on one side only is copied to the
+/// other, or deleted there when it was deleted. A synced file that disappeared from one side
+/// while its exact content turned up under a new path on that side was renamed, and the other
+/// side's copy is moved to match, edits included. A file changed on both sides is a conflict
+/// settled by `[obsidian] conflict`; an edit always wins over a deletion. Knowledge base notes
+/// are snapshotted into note history before a sync overwrites or deletes them.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+const STATE_FILE: &str = "obsidian_sync.json";
+/// How often the background loop looks at the config, so a newly set vault starts syncing
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    /// The background sync and the command never run at once
+    static ref SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+/// Which copy wins when a file changed on both sides since the last sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictStrategy {
+    /// The vault's copy wins; the knowledge base's is kept beside it as "<name> (ThinkSpace conflict <date>)"
+    #[default]
+    KeepBoth,
+    /// The copy modified last wins
+    Newest,
+    Vault,
+    KnowledgeBase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Side {
+    Vault,
+    KnowledgeBase,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Renamed {
+    /// The side the rename was made on
+    pub side: Side,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SyncConflict {
+    pub path: String,
+    /// Whose copy is now at `path` on both sides
+    pub kept: Side,
+    /// Where the losing copy was kept, with keep-both
+    pub conflict_copy: Option<String>,
+}
+
+/// Paths are relative to the vault and the synced folder, with `/` separators
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncReport {
+    pub to_knowledge_base: Vec<String>,
+    pub to_vault: Vec<String>,
+    pub deleted_from_knowledge_base: Vec<String>,
+    pub deleted_from_vault: Vec<String>,
+    pub renamed: Vec<Renamed>,
+    pub conflicts: Vec<SyncConflict>,
+    /// Knowledge base files written, moved or deleted, for the index
+    #[serde(skip)]
+    pub kb_changed: Vec<PathBuf>,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.kb_changed.is_empty() && self.to_vault.is_empty() && self.deleted_from_vault.is_empty() && self.renamed.is_empty()
+    }
+}
+
+/// What the last sync left on both sides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SyncState {
+    vault: PathBuf,
+    folder: PathBuf,
+    /// Relative path -> content hash
+    files: BTreeMap<String, String>,
+}
+
+impl SyncState {
+    /// The saved state for this vault and folder; a different pair starts over
+    fn load(path: &Path, vault: &Path, folder: &Path) -> Self {
+        let saved: Option<SyncState> = std::fs::read_to_string(path).ok().and_then(|text| serde_json::from_str(&text).ok());
+        match saved {
+            Some(state) if state.vault == vault && state.folder == folder => state,
+            _ => SyncState { vault: vault.to_path_buf(), folder: folder.to_path_buf(), files: BTreeMap::new() },
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| format!("Failed to save sync state: {}", e))
+    }
+}
+
+fn file_hash(path: &Path) -> Option<String> {
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&bytes))[..16].to_string())
+}
+
+/// Every syncable file under `root`, by relative path
+fn scan(root: &Path) -> BTreeMap<String, String> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_str().map_or(false, |n| crate::kb_import::SKIP_DIRS.contains(&n)))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, file_hash(e.path())?))
+        })
+        .collect()
+}
+
+/// The two folders being synced, with what's on each side right now
+struct Sides<'a> {
+    vault: &'a Path,
+    kb: &'a Path,
+    vault_files: BTreeMap<String, String>,
+    kb_files: BTreeMap<String, String>,
+    /// Called with a knowledge base file before it's overwritten or deleted
+    before_kb_change: &'a dyn Fn(&Path),
+}
+
+impl Sides<'_> {
+    fn root(&self, side: Side) -> &Path {
+        match side {
+            Side::Vault => self.vault,
+            Side::KnowledgeBase => self.kb,
+        }
+    }
+
+    fn files(&mut self, side: Side) -> &mut BTreeMap<String, String> {
+        match side {
+            Side::Vault => &mut self.vault_files,
+            Side::KnowledgeBase => &mut self.kb_files,
+        }
+    }
+
+    fn touch(&self, report: &mut SyncReport, side: Side, path: &Path) {
+        if side == Side::KnowledgeBase {
+            (self.before_kb_change)(path);
+            report.kb_changed.push(path.to_path_buf());
+        }
+    }
+
+    /// Copy `from`'s file at `source` to `target` on the other side
+    fn copy(&mut self, report: &mut SyncReport, from: Side, source: &str, target: &str) -> Result<String, String> {
+        let to = other(from);
+        let (src, dest) = (self.root(from).join(source), self.root(to).join(target));
+        self.touch(report, to, &dest);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::copy(&src, &dest).map_err(|e| format!("Failed to copy {} to {}: {}", src.display(), dest.display(), e))?;
+        let hash = self.files(from).get(source).cloned().unwrap_or_default();
+        self.files(to).insert(target.to_string(), hash.clone());
+        match to {
+            Side::Vault => report.to_vault.push(target.to_string()),
+            Side::KnowledgeBase => report.to_knowledge_base.push(target.to_string()),
+        }
+        Ok(hash)
+    }
+
+    fn delete(&mut self, report: &mut SyncReport, side: Side, path: &str) -> Result<(), String> {
+        let full = self.root(side).join(path);
+        self.touch(report, side, &full);
+        std::fs::remove_file(&full).map_err(|e| format!("Failed to delete {}: {}", full.display(), e))?;
+        self.files(side).remove(path);
+        match side {
+            Side::Vault => report.deleted_from_vault.push(path.to_string()),
+            Side::KnowledgeBase => report.deleted_from_knowledge_base.push(path.to_string()),
+        }
+        Ok(())
+    }
+
+    fn rename(&mut self, report: &mut SyncReport, side: Side, from: &str, to: &str) -> Result<(), String> {
+        let (src, dest) = (self.root(side).join(from), self.root(side).join(to));
+        self.touch(report, side, &src);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::rename(&src, &dest).map_err(|e| format!("Failed to move {} to {}: {}", src.display(), dest.display(), e))?;
+        if side == Side::KnowledgeBase {
+            report.kb_changed.push(dest);
+        }
+        if let Some(hash) = self.files(side).remove(from) {
+            self.files(side).insert(to.to_string(), hash);
+        }
+        Ok(())
+    }
+
+    fn modified(&self, side: Side, path: &str) -> Option<std::time::SystemTime> {
+        std::fs::metadata(self.root(side).join(path)).and_then(|m| m.modified()).ok()
+    }
+}
+
+fn other(side: Side) -> Side {
+    match side {
+        Side::Vault => Side::KnowledgeBase,
+        Side::KnowledgeBase => Side::Vault,
+    }
+}
+
+/// "Notes/Idea.md" -> "Notes/Idea (ThinkSpace conflict 2026-10-16).md", numbered if taken
+fn conflict_name(path: &str, date: &str, taken: impl Fn(&str) -> bool) -> String {
+    let (dir, name) = path.rsplit_once('/').map_or(("", path), |(d, n)| (d, n));
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    let mut candidate = format!("{}{} (ThinkSpace conflict {}){}", prefix, stem, date, ext);
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{}{} (ThinkSpace conflict {} {}){}", prefix, stem, date, n, ext);
+        n += 1;
+    }
+    candidate
+}
+
+/// Synced files missing from `side` whose content reappeared there under a path that's new
+/// to both sides, as (old, new) pairs
+fn detect_renames(state: &BTreeMap<String, String>, files: &BTreeMap<String, String>, others: &BTreeMap<String, String>) -> Vec<(String, String)> {
+    let mut claimed = BTreeSet::new();
+    let mut renames = Vec::new();
+    for (old, hash) in state.iter().filter(|(path, _)| !files.contains_key(*path)) {
+        let new = files.iter().find(|(path, h)| {
+            *h == hash && !state.contains_key(*path) && !others.contains_key(*path) && !claimed.contains(*path)
+        });
+        if let Some((new, _)) = new {
+            claimed.insert(new.clone());
+            renames.push((old.clone(), new.clone()));
+        }
+    }
+    renames
+}
+
+/// Sync `vault` and `kb`, given the hashes the last sync left in `state`, and update `state`
+fn sync_dirs(
+    vault: &Path,
+    kb: &Path,
+    state: &mut BTreeMap<String, String>,
+    strategy: ConflictStrategy,
+    today: &str,
+    before_kb_change: &dyn Fn(&Path),
+) -> Result<SyncReport, String> {
+    let mut sides = Sides { vault, kb, vault_files: scan(vault), kb_files: scan(kb), before_kb_change };
+    let mut report = SyncReport::default();
+
+    for side in [Side::Vault, Side::KnowledgeBase] {
+        let (files, others) = match side {
+            Side::Vault => (&sides.vault_files, &sides.kb_files),
+            Side::KnowledgeBase => (&sides.kb_files, &sides.vault_files),
+        };
+        for (from, to) in detect_renames(state, files, others) {
+            if sides.files(other(side)).contains_key(&from) {
+                sides.rename(&mut report, other(side), &from, &to)?;
+            }
+            if let Some(hash) = state.remove(&from) {
+                state.insert(to.clone(), hash);
+            }
+            report.renamed.push(Renamed { side, from, to });
+        }
+    }
+
+    let paths: BTreeSet<String> = sides.vault_files.keys().chain(sides.kb_files.keys()).chain(state.keys()).cloned().collect();
+    for path in paths {
+        let vault_hash = sides.vault_files.get(&path).cloned();
+        let kb_hash = sides.kb_files.get(&path).cloned();
+        let base = state.get(&path).cloned();
+        // The side whose copy goes to the other, or None when a deletion goes across
+        let winner = if vault_hash == kb_hash {
+            match vault_hash {
+                Some(hash) => state.insert(path.clone(), hash),
+                None => state.remove(&path),
+            };
+            continue;
+        } else if kb_hash == base {
+            Some(Side::Vault)
+        } else if vault_hash == base {
+            Some(Side::KnowledgeBase)
+        } else {
+            None
+        };
+
+        let side = match winner {
+            Some(side) => side,
+            None => {
+                // Changed on both sides: an edit beats a deletion, otherwise the strategy decides
+                let kept = match (vault_hash.is_some(), kb_hash.is_some(), strategy) {
+                    (true, false, _) => Side::Vault,
+                    (false, true, _) => Side::KnowledgeBase,
+                    (_, _, ConflictStrategy::Vault | ConflictStrategy::KeepBoth) => Side::Vault,
+                    (_, _, ConflictStrategy::KnowledgeBase) => Side::KnowledgeBase,
+                    (_, _, ConflictStrategy::Newest) => {
+                        if sides.modified(Side::KnowledgeBase, &path) > sides.modified(Side::Vault, &path) {
+                            Side::KnowledgeBase
+                        } else {
+                            Side::Vault
+                        }
+                    }
+                };
+                let mut conflict_copy = None;
+                if strategy == ConflictStrategy::KeepBoth && vault_hash.is_some() && kb_hash.is_some() {
+                    let copy = conflict_name(&path, today, |p| sides.vault_files.contains_key(p) || sides.kb_files.contains_key(p));
+                    let hash = sides.copy(&mut report, Side::KnowledgeBase, &path, &copy)?;
+                    std::fs::copy(kb.join(&path), kb.join(&copy)).map_err(|e| format!("Failed to keep {}: {}", copy, e))?;
+                    sides.kb_files.insert(copy.clone(), hash.clone());
+                    report.kb_changed.push(kb.join(&copy));
+                    state.insert(copy.clone(), hash);
+                    conflict_copy = Some(copy);
+                }
+                report.conflicts.push(SyncConflict { path: path.clone(), kept, conflict_copy });
+                kept
+            }
+        };
+
+        let source_hash = match side {
+            Side::Vault => &vault_hash,
+            Side::KnowledgeBase => &kb_hash,
+        };
+        if source_hash.is_some() {
+            let hash = sides.copy(&mut report, side, &path, &path)?;
+            state.insert(path, hash);
+        } else {
+            sides.delete(&mut report, other(side), &path)?;
+            state.remove(&path);
+        }
+    }
+    Ok(report)
+}
+
+/// Sync the configured vault now
+pub async fn sync(app_handle: &tauri::AppHandle) -> Result<SyncReport, String> {
+    let config = crate::config::current().obsidian.clone();
+    let vault = config
+        .vault
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(crate::config::expand_home)
+        .ok_or("No Obsidian vault configured; set [obsidian] vault in thinkspace.toml")?;
+    if !vault.is_dir() {
+        return Err(format!("Obsidian vault not found: {}", vault.display()));
+    }
+    crate::kb_import::check_target_folder(&config.folder)?;
+    let kb_root = crate::minimax_enhanced::MinimaxAgent::get_knowledge_base_path()?;
+    let kb_dir = kb_root.join(&config.folder);
+    let state_path = crate::profiles::data_dir(Some(app_handle)).ok_or("Failed to get app data dir")?.join(STATE_FILE);
+
+    let _running = SYNC_LOCK.lock().await;
+    let report = {
+        // Index once at the end instead of reacting to every synced file
+        let _watch_pause = crate::file_watcher::pause_for_writes(vec![kb_dir.clone()]);
+        let handle = app_handle.clone();
+        let (kb_root, kb_dir) = (kb_root.clone(), kb_dir.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            std::fs::create_dir_all(&kb_dir).map_err(|e| format!("Failed to create {}: {}", kb_dir.display(), e))?;
+            let mut state = SyncState::load(&state_path, &vault, Path::new(&config.folder));
+            let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+            let before_kb_change = |path: &Path| crate::note_versions::record_before_write(Some(&handle), &kb_root, path, "obsidian-sync");
+            let result = sync_dirs(&vault, &kb_dir, &mut state.files, config.conflict, &today, &before_kb_change);
+            // Whatever was synced before a failure is recorded, so it isn't mistaken for an edit next time
+            state.save(&state_path)?;
+            result
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+
+    if !report.is_empty() {
+        crate::kb_index::apply_watch_changes(app_handle, &report.kb_changed, None);
+        let keys: Vec<String> = report.kb_changed.iter().map(|p| crate::kb_index::path_key(&kb_root, p)).collect();
+        crate::events::emit(app_handle, "content-changed", serde_json::json!({
+            "source": kb_dir,
+            "paths": keys,
+        }));
+        tracing::info!(
+            "🔁 Obsidian sync: {} to the knowledge base, {} to the vault, {} renamed, {} conflict(s)",
+            report.to_knowledge_base.len(),
+            report.to_vault.len(),
+            report.renamed.len(),
+            report.conflicts.len()
+        );
+    }
+    Ok(report)
+}
+
+/// Sync every `[obsidian] interval_minutes` while a vault is configured
+pub fn start_background(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_sync: Option<Instant> = None;
+        loop {
+            let config = crate::config::current().obsidian.clone();
+            let interval = Duration::from_secs(60 * config.interval_minutes);
+            let due = last_sync.map_or(true, |at| at.elapsed() >= interval);
+            if config.vault.as_deref().map_or(false, |v| !v.trim().is_empty()) && config.interval_minutes > 0 && due {
+                last_sync = Some(Instant::now());
+                if let Err(e) = sync(&app_handle).await {
+                    tracing::warn!("⚠️ Obsidian sync failed: {}", e);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Sync the configured Obsidian vault with its knowledge base folder now
+#[tauri::command]
+pub async fn sync_obsidian_vault(app_handle: tauri::AppHandle) -> Result<SyncReport, String> {
+    sync(&app_handle).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::fs::write(full, content).unwrap();
+    }
+
+    fn read(root: &Path, path: &str) -> Option<String> {
+        std::fs::read_to_string(root.join(path)).ok()
+    }
+
+    /// A vault and a knowledge base after their first sync: `Rust/Lifetimes.md` came from the
+    /// vault and `Async.md` from the knowledge base
+    struct Synced {
+        vault: tempfile::TempDir,
+        kb: tempfile::TempDir,
+        state: BTreeMap<String, String>,
+    }
+
+    impl Synced {
+        fn v(&self) -> &Path {
+            self.vault.path()
+        }
+
+        fn k(&self) -> &Path {
+            self.kb.path()
+        }
+
+        fn run(&mut self, strategy: ConflictStrategy) -> SyncReport {
+            sync_dirs(self.vault.path(), self.kb.path(), &mut self.state, strategy, "2026-10-16", &|_: &Path| {}).unwrap()
+        }
+    }
+
+    fn unsynced() -> Synced {
+        let synced = Synced { vault: tempfile::tempdir().unwrap(), kb: tempfile::tempdir().unwrap(), state: BTreeMap::new() };
+        write(synced.v(), "Rust/Lifetimes.md", "# Lifetimes");
+        write(synced.v(), ".obsidian/workspace.json", "{}");
+        write(synced.k(), "Async.md", "# Async");
+        synced
+    }
+
+    fn synced() -> Synced {
+        let mut synced = unsynced();
+        synced.run(ConflictStrategy::KeepBoth);
+        synced
+    }
+
+    /// `synced`, plus `Rust/Borrowing.md` written in the vault and synced
+    fn with_borrowing() -> Synced {
+        let mut synced = synced();
+        write(synced.v(), "Rust/Borrowing.md", "# Borrowing");
+        synced.run(ConflictStrategy::KeepBoth);
+        synced
+    }
+
+    #[test]
+    fn test_first_sync_copies_both_ways() {
+        let mut synced = unsynced();
+        let first = synced.run(ConflictStrategy::KeepBoth);
+        assert_eq!((first.to_knowledge_base, first.to_vault), (vec!["Rust/Lifetimes.md".to_string()], vec!["Async.md".to_string()]));
+        assert!(read(synced.k(), ".obsidian/workspace.json").is_none());
+        assert!(synced.run(ConflictStrategy::KeepBoth).is_empty());
+    }
+
+    #[test]
+    fn test_rename_carries_the_edit() {
+        // Renamed in Obsidian while edited here: the edit moves with the rename
+        let mut synced = synced();
+        let (v, k) = (synced.v().to_path_buf(), synced.k().to_path_buf());
+        std::fs::rename(v.join("Rust/Lifetimes.md"), v.join("Rust/Borrowing.md")).unwrap();
+        write(&k, "Rust/Lifetimes.md", "# Lifetimes\nEdited in ThinkSpace");
+        let renamed = synced.run(ConflictStrategy::KeepBoth);
+        assert_eq!(renamed.renamed, vec![Renamed { side: Side::Vault, from: "Rust/Lifetimes.md".to_string(), to: "Rust/Borrowing.md".to_string() }]);
+        assert_eq!(read(&v, "Rust/Borrowing.md").unwrap(), "# Lifetimes\nEdited in ThinkSpace");
+        assert!(read(&k, "Rust/Lifetimes.md").is_none());
+    }
+
+    #[test]
+    fn test_edit_wins_over_deletion() {
+        // Deleted here, edited in Obsidian: the edit comes back
+        let mut synced = synced();
+        std::fs::remove_file(synced.k().join("Async.md")).unwrap();
+        write(synced.v(), "Async.md", "# Async\nMore");
+        let restored = synced.run(ConflictStrategy::KnowledgeBase);
+        assert_eq!(restored.conflicts[0].kept, Side::Vault);
+        assert_eq!(read(synced.k(), "Async.md").unwrap(), "# Async\nMore");
+    }
+
+    #[test]
+    fn test_deletion_on_one_side() {
+        let mut synced = synced();
+        std::fs::remove_file(synced.v().join("Async.md")).unwrap();
+        assert_eq!(synced.run(ConflictStrategy::KeepBoth).deleted_from_knowledge_base, vec!["Async.md".to_string()]);
+    }
+
+    #[test]
+    fn test_conflict_keeps_both() {
+        // Edited on both sides
+        let mut synced = with_borrowing();
+        let (v, k) = (synced.v().to_path_buf(), synced.k().to_path_buf());
+        write(&v, "Rust/Borrowing.md", "vault");
+        write(&k, "Rust/Borrowing.md", "thinkspace");
+        let conflict = synced.run(ConflictStrategy::KeepBoth);
+        let copy = "Rust/Borrowing (ThinkSpace conflict 2026-10-16).md";
+        assert_eq!(conflict.conflicts[0].conflict_copy.as_deref(), Some(copy));
+        assert_eq!((read(&k, "Rust/Borrowing.md").unwrap(), read(&v, copy).unwrap()), ("vault".to_string(), "thinkspace".to_string()));
+        assert_eq!(read(&k, copy).unwrap(), "thinkspace");
+        assert!(synced.run(ConflictStrategy::KeepBoth).is_empty());
+    }
+
+    #[test]
+    fn test_conflict_knowledge_base_wins() {
+        let mut synced = with_borrowing();
+        write(synced.v(), "Rust/Borrowing.md", "vault again");
+        write(synced.k(), "Rust/Borrowing.md", "thinkspace again");
+        synced.run(ConflictStrategy::KnowledgeBase);
+        assert_eq!(read(synced.v(), "Rust/Borrowing.md").unwrap(), "thinkspace again");
+    }
+
+    #[test]
+    fn test_conflict_name_skips_taken_copies() {
+        assert_eq!(conflict_name("a.md", "d", |p| p == "a (ThinkSpace conflict d).md"), "a (ThinkSpace conflict d 2).md");
+    }
+}
@@ -0,0 +1,621 @@
+/// Full-page content extraction for research
+///
+/// Fetches a page, picks the main content (readability-style: `<article>`/`<main>`, else the
+/// element holding the most paragraph text) and converts it to markdown. Fetches respect
+/// robots.txt and wait at least `MIN_DOMAIN_DELAY` (or the site's Crawl-delay) between
+/// requests to the same host; that state is global so parallel research agents share it.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ego_tree::NodeRef;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+use crate::net::SendChecked;
+
+const USER_AGENT: &str = "ThinkSpace-Research/1.0 (+https://github.com/oogalieboogalie/ThinkSpace)";
+/// Token matched against robots.txt User-agent lines
+const ROBOTS_AGENT: &str = "thinkspace-research";
+const MIN_DOMAIN_DELAY: Duration = Duration::from_secs(1);
+const MAX_CRAWL_DELAY: Duration = Duration::from_secs(10);
+const MAX_PAGE_BYTES: usize = 3 * 1024 * 1024;
+const SKIP_TAGS: [&str; 13] = [
+    "script", "style", "noscript", "nav", "header", "footer", "aside", "form", "svg", "iframe", "button", "template", "head",
+];
+/// Page furniture, mostly from MediaWiki: navigation boxes, edit links, tables of contents
+const SKIP_CLASSES: [&str; 6] = ["navbox", "mw-editsection", "noprint", "toc", "mw-empty-elt", "metadata"];
+
+lazy_static::lazy_static! {
+    static ref WHITESPACE: Regex = Regex::new(r"\s+").unwrap();
+    static ref BLANK_LINES: Regex = Regex::new(r"\n{3,}").unwrap();
+    static ref ROBOTS_CACHE: Mutex<HashMap<String, Arc<RobotsRules>>> = Mutex::new(HashMap::new());
+    /// Earliest time the next request to each host may start
+    static ref NEXT_SLOT: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some(USER_AGENT),
+        timeout: Some(Duration::from_secs(15)),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedPage {
+    pub url: String,
+    pub title: Option<String>,
+    pub markdown: String,
+}
+
+// ==================== robots.txt ====================
+
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    /// (allow, path pattern)
+    rules: Vec<(bool, String)>,
+    pub crawl_delay: Option<Duration>,
+}
+
+fn pattern_matches(pattern: &str, path: &str) -> Option<usize> {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if !path.starts_with(parts[0]) {
+        return None;
+    }
+    let mut pos = parts[0].len();
+    for part in &parts[1..] {
+        match path[pos..].find(part) {
+            Some(i) => pos += i + part.len(),
+            None => return None,
+        }
+    }
+    if anchored && pos != path.len() && !pattern.ends_with('*') {
+        return None;
+    }
+    Some(pattern.len())
+}
+
+impl RobotsRules {
+    /// Rules for `agent` from a robots.txt body, falling back to the `*` group
+    pub fn parse(text: &str, agent: &str) -> Self {
+        let agent = agent.to_lowercase();
+        let mut specific = RobotsRules::default();
+        let mut wildcard = RobotsRules::default();
+        let mut has_specific = false;
+
+        // Agents of the group being read, and whether its rule lines have started
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let (key, value) = match line.split_once(':') {
+                Some((k, v)) => (k.trim().to_lowercase(), v.trim()),
+                None => continue,
+            };
+
+            if key == "user-agent" {
+                if in_rules {
+                    group_agents.clear();
+                    in_rules = false;
+                }
+                group_agents.push(value.to_lowercase());
+                continue;
+            }
+            in_rules = true;
+
+            let matches_specific = group_agents.iter().any(|a| a != "*" && agent.contains(a.as_str()));
+            let matches_wildcard = group_agents.iter().any(|a| a == "*");
+            let targets: Vec<&mut RobotsRules> = match (matches_specific, matches_wildcard) {
+                (true, _) => {
+                    has_specific = true;
+                    vec![&mut specific]
+                }
+                (false, true) => vec![&mut wildcard],
+                _ => continue,
+            };
+
+            for target in targets {
+                match key.as_str() {
+                    "allow" if !value.is_empty() => target.rules.push((true, value.to_string())),
+                    "disallow" if !value.is_empty() => target.rules.push((false, value.to_string())),
+                    "crawl-delay" => {
+                        target.crawl_delay = value.parse::<f64>().ok().map(|s| Duration::from_secs_f64(s.max(0.0)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if has_specific { specific } else { wildcard }
+    }
+
+    /// Longest matching rule wins; Allow wins ties
+    pub fn allows(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+        for (allow, pattern) in &self.rules {
+            if let Some(len) = pattern_matches(pattern, path) {
+                let better = match best {
+                    None => true,
+                    Some((best_len, best_allow)) => len > best_len || (len == best_len && *allow && !best_allow),
+                };
+                if better {
+                    best = Some((len, *allow));
+                }
+            }
+        }
+        best.map(|(_, allow)| allow).unwrap_or(true)
+    }
+}
+
+async fn robots_for(url: &Url) -> Arc<RobotsRules> {
+    let origin = url.origin().ascii_serialization();
+    if let Some(rules) = ROBOTS_CACHE.lock().await.get(&origin) {
+        return rules.clone();
+    }
+
+    // Missing or unreachable robots.txt means no restrictions
+    let rules = match client().get(format!("{}/robots.txt", origin)).send_checked().await {
+        Ok(resp) if resp.status().is_success() => {
+            let text = resp.text().await.unwrap_or_default();
+            RobotsRules::parse(&text, ROBOTS_AGENT)
+        }
+        _ => RobotsRules::default(),
+    };
+    let rules = Arc::new(rules);
+    ROBOTS_CACHE.lock().await.insert(origin, rules.clone());
+    rules
+}
+
+/// Reserve the next request slot for `host` and sleep until it arrives
+pub async fn wait_for_slot(host: &str, delay: Duration) {
+    let start = {
+        let mut slots = NEXT_SLOT.lock().await;
+        let now = Instant::now();
+        let start = slots.get(host).copied().filter(|t| *t > now).unwrap_or(now);
+        slots.insert(host.to_string(), start + delay);
+        start
+    };
+    tokio::time::sleep_until(tokio::time::Instant::from_std(start)).await;
+}
+
+// ==================== HTML to markdown ====================
+
+/// Blank line before the next block, without doubling up
+fn push_block(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+    if out.is_empty() || out.ends_with("\n\n") {
+        return;
+    }
+    out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+}
+
+fn inline(node: NodeRef<Node>, base: Option<&Url>) -> String {
+    let mut out = String::new();
+    for child in node.children() {
+        render(child, &mut out, base, 0);
+    }
+    out.trim().replace('\n', " ")
+}
+
+fn resolve_url(href: &str, base: Option<&Url>) -> Option<String> {
+    if href.starts_with('#') || href.starts_with("javascript:") || href.starts_with("mailto:") {
+        return None;
+    }
+    match base {
+        Some(base) => base.join(href).ok().map(|u| u.to_string()),
+        None => Url::parse(href).ok().map(|u| u.to_string()),
+    }
+}
+
+fn render(node: NodeRef<Node>, out: &mut String, base: Option<&Url>, list_depth: usize) {
+    let element = match node.value() {
+        Node::Text(text) => {
+            let collapsed = WHITESPACE.replace_all(text, " ");
+            let collapsed: &str = &collapsed;
+            let at_line_start = out.is_empty() || out.ends_with('\n') || out.ends_with(' ');
+            out.push_str(if at_line_start { collapsed.trim_start() } else { collapsed });
+            return;
+        }
+        Node::Element(element) => element,
+        _ => return,
+    };
+    let name = element.name();
+    if SKIP_TAGS.contains(&name) || element.classes().any(|c| SKIP_CLASSES.contains(&c)) {
+        return;
+    }
+    // Footnote markers like [1]
+    if name == "sup" && element.classes().any(|c| c == "reference") {
+        return;
+    }
+
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let text = inline(node, base);
+            if !text.is_empty() {
+                push_block(out);
+                let level = name[1..].parse::<usize>().unwrap_or(2);
+                out.push_str(&format!("{} {}", "#".repeat(level), text));
+                push_block(out);
+            }
+        }
+        "br" => out.push('\n'),
+        "hr" => {
+            push_block(out);
+            out.push_str("---");
+            push_block(out);
+        }
+        "pre" => {
+            let code: String = ElementRef::wrap(node).map(|e| e.text().collect()).unwrap_or_default();
+            push_block(out);
+            out.push_str(&format!("```\n{}\n```", code.trim_end_matches('\n')));
+            push_block(out);
+        }
+        "code" => {
+            let code: String = ElementRef::wrap(node).map(|e| e.text().collect()).unwrap_or_default();
+            if !code.trim().is_empty() {
+                out.push_str(&format!("`{}`", code.trim()));
+            }
+        }
+        "strong" | "b" | "em" | "i" => {
+            let text = inline(node, base);
+            if !text.is_empty() {
+                let marker = if name == "strong" || name == "b" { "**" } else { "*" };
+                out.push_str(&format!("{}{}{}", marker, text, marker));
+            }
+        }
+        "a" => {
+            let text = inline(node, base);
+            match element.attr("href").and_then(|h| resolve_url(h, base)) {
+                Some(href) if !text.is_empty() => out.push_str(&format!("[{}]({})", text, href)),
+                _ => out.push_str(&text),
+            }
+        }
+        "img" => {
+            if let Some(src) = element.attr("src").and_then(|s| resolve_url(s, base)) {
+                out.push_str(&format!("![{}]({})", element.attr("alt").unwrap_or("").trim(), src));
+            }
+        }
+        "ul" | "ol" => {
+            if list_depth == 0 {
+                push_block(out);
+            } else if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            let mut index = 0;
+            for child in node.children() {
+                let is_item = matches!(child.value(), Node::Element(e) if e.name() == "li");
+                if !is_item {
+                    continue;
+                }
+                index += 1;
+                let marker = if name == "ol" { format!("{}.", index) } else { "-".to_string() };
+                out.push_str(&format!("{}{} ", "  ".repeat(list_depth), marker));
+                for grandchild in child.children() {
+                    render(grandchild, out, base, list_depth + 1);
+                }
+                let trimmed = out.trim_end().len();
+                out.truncate(trimmed);
+                out.push('\n');
+            }
+            if list_depth == 0 {
+                push_block(out);
+            }
+        }
+        "blockquote" => {
+            let mut inner = String::new();
+            for child in node.children() {
+                render(child, &mut inner, base, list_depth);
+            }
+            push_block(out);
+            let quoted: Vec<String> = inner.trim().lines().map(|l| format!("> {}", l).trim_end().to_string()).collect();
+            out.push_str(&quoted.join("\n"));
+            push_block(out);
+        }
+        "table" => {
+            push_block(out);
+            match table_markdown(node, base) {
+                Some(table) => out.push_str(&table),
+                // Layout tables: one column of content
+                None => {
+                    for child in node.children() {
+                        render(child, out, base, list_depth);
+                    }
+                }
+            }
+            push_block(out);
+        }
+        "p" | "div" | "section" | "article" | "main" | "figure" | "figcaption" | "dl" | "dt" | "dd" => {
+            push_block(out);
+            for child in node.children() {
+                render(child, out, base, list_depth);
+            }
+            push_block(out);
+        }
+        _ => {
+            for child in node.children() {
+                render(child, out, base, list_depth);
+            }
+        }
+    }
+}
+
+fn is_element(node: NodeRef<Node>, names: &[&str]) -> bool {
+    matches!(node.value(), Node::Element(e) if names.contains(&e.name()))
+}
+
+/// The rows of a table as (cells, all header cells), not descending into nested tables.
+/// Cells spanning several columns are repeated as empty cells.
+fn table_rows(table: NodeRef<Node>, base: Option<&Url>) -> Vec<(Vec<String>, bool)> {
+    let mut rows = Vec::new();
+    let sections = table.children().filter(|c| is_element(*c, &["thead", "tbody", "tfoot"]));
+    let trs = table.children().chain(sections.flat_map(|s| s.children())).filter(|c| is_element(*c, &["tr"]));
+    for tr in trs {
+        let mut cells = Vec::new();
+        let mut all_headers = true;
+        for cell in tr.children().filter(|c| is_element(*c, &["td", "th"])) {
+            let span = match cell.value() {
+                Node::Element(e) => {
+                    all_headers &= e.name() == "th";
+                    e.attr("colspan").and_then(|n| n.trim().parse::<usize>().ok()).unwrap_or(1).clamp(1, 20)
+                }
+                _ => 1,
+            };
+            cells.push(inline(cell, base).replace('|', "\\|"));
+            cells.resize(cells.len() + span - 1, String::new());
+        }
+        if cells.iter().any(|c| !c.is_empty()) {
+            rows.push((cells, all_headers));
+        }
+    }
+    rows
+}
+
+fn table_line(cells: &[String], width: usize) -> String {
+    let padded: Vec<&str> = (0..width).map(|i| cells.get(i).map_or("", String::as_str)).collect();
+    format!("| {} |", padded.join(" | "))
+}
+
+/// A table as a markdown table; infoboxes become a Field/Value table under their title.
+/// None for layout tables with a single column.
+fn table_markdown(table: NodeRef<Node>, base: Option<&Url>) -> Option<String> {
+    let infobox = matches!(table.value(), Node::Element(e) if e.classes().any(|c| c.contains("infobox")));
+    let caption = table.children().find(|c| is_element(*c, &["caption"])).map(|c| inline(c, base)).filter(|c| !c.is_empty());
+    let rows = table_rows(table, base);
+
+    let mut lines = Vec::new();
+    if infobox {
+        // Full-width rows are the box's title and section labels; the rest are label/value pairs
+        let mut title = caption;
+        let mut fields = Vec::new();
+        for (cells, _) in rows {
+            let filled: Vec<String> = cells.into_iter().filter(|c| !c.is_empty()).collect();
+            match filled.len() {
+                0 => {}
+                1 if title.is_none() && fields.is_empty() => title = filled.into_iter().next(),
+                1 => fields.push(vec![format!("**{}**", filled[0]), String::new()]),
+                _ => fields.push(vec![filled[0].clone(), filled[1..].join(" · ")]),
+            }
+        }
+        if fields.is_empty() {
+            return None;
+        }
+        if let Some(title) = title {
+            lines.push(format!("**{}**", title));
+            lines.push(String::new());
+        }
+        lines.push("| Field | Value |".to_string());
+        lines.push("| --- | --- |".to_string());
+        lines.extend(fields.iter().map(|f| table_line(f, 2)));
+        return Some(lines.join("\n"));
+    }
+
+    let width = rows.iter().map(|(cells, _)| cells.len()).max().unwrap_or(0);
+    if width < 2 {
+        return None;
+    }
+    if let Some(caption) = caption {
+        lines.push(format!("*{}*", caption));
+        lines.push(String::new());
+    }
+    // Markdown tables need a header row: the first row, or empty headers when it's data
+    let mut rows = rows.into_iter().peekable();
+    let header = match rows.peek() {
+        Some((_, true)) => rows.next().map(|(cells, _)| cells).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    lines.push(table_line(&header, width));
+    lines.push(format!("|{}", " --- |".repeat(width)));
+    lines.extend(rows.map(|(cells, _)| table_line(&cells, width)));
+    Some(lines.join("\n"))
+}
+
+/// Element most likely to hold the article: `<article>`/`<main>` with real text, else the
+/// parent of the most paragraph text
+fn main_content(doc: &Html) -> Option<NodeRef<'_, Node>> {
+    for selector in ["article", "main", "[role=main]"] {
+        let selector = Selector::parse(selector).ok()?;
+        if let Some(el) = doc.select(&selector).max_by_key(|e| e.text().map(str::len).sum::<usize>()) {
+            if el.text().map(str::len).sum::<usize>() > 500 {
+                return Some(*el);
+            }
+        }
+    }
+
+    let paragraphs = Selector::parse("p").ok()?;
+    let mut scores: HashMap<ego_tree::NodeId, usize> = HashMap::new();
+    for p in doc.select(&paragraphs) {
+        if let Some(parent) = p.parent() {
+            *scores.entry(parent.id()).or_default() += p.text().map(str::len).sum::<usize>();
+        }
+    }
+    if let Some((id, _)) = scores.into_iter().max_by_key(|(_, score)| *score) {
+        return doc.tree.get(id);
+    }
+
+    let body = Selector::parse("body").ok()?;
+    doc.select(&body).next().map(|e| *e)
+}
+
+/// Title and main content of an HTML document as markdown
+pub fn html_to_markdown(html: &str, base: Option<&Url>) -> (Option<String>, String) {
+    let doc = Html::parse_document(html);
+    let title = Selector::parse("title")
+        .ok()
+        .and_then(|s| doc.select(&s).next())
+        .map(|t| WHITESPACE.replace_all(&t.text().collect::<String>(), " ").trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    let mut out = String::new();
+    if let Some(root) = main_content(&doc) {
+        render(root, &mut out, base, 0);
+    }
+    let markdown = BLANK_LINES.replace_all(out.trim(), "\n\n").into_owned();
+    (title, markdown)
+}
+
+/// An HTML fragment, such as a wiki's rendered article, as markdown. Unlike
+/// `html_to_markdown` all of it is kept rather than just the main content.
+pub fn fragment_to_markdown(html: &str, base: Option<&Url>) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut out = String::new();
+    for child in fragment.tree.root().children() {
+        render(child, &mut out, base, 0);
+    }
+    BLANK_LINES.replace_all(out.trim(), "\n\n").into_owned()
+}
+
+// ==================== Fetching ====================
+
+/// GET `url` if robots.txt allows it, after waiting for the host's request slot
+pub async fn fetch_politely(url: &str) -> Result<reqwest::Response> {
+    let parsed = Url::parse(url).context("Invalid URL")?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        bail!("Unsupported URL scheme: {}", parsed.scheme());
+    }
+    let host = parsed.host_str().ok_or_else(|| anyhow!("URL has no host"))?.to_string();
+
+    let robots = robots_for(&parsed).await;
+    let path = match parsed.query() {
+        Some(q) => format!("{}?{}", parsed.path(), q),
+        None => parsed.path().to_string(),
+    };
+    if !robots.allows(&path) {
+        bail!("Disallowed by robots.txt: {}", url);
+    }
+
+    let delay = robots.crawl_delay.map(|d| d.min(MAX_CRAWL_DELAY)).unwrap_or_default().max(MIN_DOMAIN_DELAY);
+    wait_for_slot(&host, delay).await;
+
+    let resp = client().get(parsed).send_checked().await.context("Failed to fetch page")?;
+    if !resp.status().is_success() {
+        bail!("HTTP {} for {}", resp.status(), url);
+    }
+    Ok(resp)
+}
+
+/// Fetch `url` politely and return its main content as markdown
+pub async fn fetch_page(url: &str) -> Result<ExtractedPage> {
+    let resp = fetch_politely(url).await?;
+    let is_html = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| ct.contains("html"))
+        .unwrap_or(true);
+    if !is_html {
+        bail!("Not an HTML page: {}", url);
+    }
+    if resp.content_length().map(|len| len as usize > MAX_PAGE_BYTES).unwrap_or(false) {
+        bail!("Page too large: {}", url);
+    }
+
+    // Resolve relative links against where redirects ended up
+    let final_url = resp.url().clone();
+    let bytes = resp.bytes().await.context("Failed to read page")?;
+    let html = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_PAGE_BYTES)]);
+    let (title, markdown) = html_to_markdown(&html, Some(&final_url));
+
+    Ok(ExtractedPage { url: url.to_string(), title, markdown })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "User-agent: *\nDisallow: /private/\nCrawl-delay: 3\n\n\
+                          User-agent: ThinkSpace-Research\nUser-agent: other\nDisallow: /drafts\nAllow: /drafts/public\nDisallow: /*.pdf$\n";
+
+    #[test]
+    fn test_robots_rules_for_our_agent() {
+        let ours = RobotsRules::parse(ROBOTS, ROBOTS_AGENT);
+        assert!(!ours.allows("/drafts/x"));
+        assert!(ours.allows("/drafts/public/x"));
+        assert!(!ours.allows("/files/a.pdf"));
+        assert!(ours.allows("/files/a.pdf?v=1"));
+        assert!(ours.allows("/private/x"));
+    }
+
+    #[test]
+    fn test_robots_rules_for_other_agents() {
+        let others = RobotsRules::parse(ROBOTS, "somebot");
+        assert!(!others.allows("/private/x"));
+        assert_eq!(others.crawl_delay, Some(Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_html_to_markdown_keeps_main_content() {
+        let html = r#"<html><head><title> Ownership
+            in Rust </title><script>var x = 1;</script></head><body>
+            <nav><a href="/">Home</a></nav>
+            <div class="content"><h2>Rules</h2>
+              <p>Each value has <strong>one</strong> owner. See <a href="/borrowing">borrowing</a>.</p>
+              <ul><li>Move</li><li>Copy<ul><li>Clone</li></ul></li></ul>
+              <pre><code>let s = String::new();</code></pre>
+            </div><footer>Copyright</footer></body></html>"#;
+        let base = Url::parse("https://example.com/book/ownership").unwrap();
+
+        let (title, markdown) = html_to_markdown(html, Some(&base));
+        assert_eq!(title.as_deref(), Some("Ownership in Rust"));
+        assert_eq!(
+            markdown,
+            "## Rules\n\nEach value has **one** owner. See [borrowing](https://example.com/borrowing).\n\n\
+             - Move\n- Copy\n  - Clone\n\n```\nlet s = String::new();\n```"
+        );
+    }
+
+    #[test]
+    fn test_tables_and_infoboxes() {
+        let html = r##"<div class="mw-parser-output">
+            <table class="infobox"><tbody>
+              <tr><th colspan="2">Zulrah</th></tr>
+              <tr><th>Combat level</th><td>725</td></tr>
+              <tr><th colspan="2">Combat info</th></tr>
+              <tr><th>Weakness</th><td>Ranged | Magic</td></tr>
+            </tbody></table>
+            <p>Zulrah is a boss<sup class="reference"><a href="#cite-1">[1]</a></sup>.</p>
+            <table class="wikitable"><caption>Drops</caption>
+              <tr><th>Item</th><th>Quantity</th></tr>
+              <tr><td>Zulrah's scales</td><td>100–299</td></tr>
+              <tr><td colspan="2">Rare drop table</td></tr>
+            </table>
+            <table><tr><td><p>Layout only</p></td></tr></table>
+            <div class="navbox">Bosses navigation</div></div>"##;
+
+        assert_eq!(
+            fragment_to_markdown(html, None),
+            "**Zulrah**\n\n| Field | Value |\n| --- | --- |\n| Combat level | 725 |\n| **Combat info** |  |\n| Weakness | Ranged \\| Magic |\n\n\
+             Zulrah is a boss.\n\n\
+             *Drops*\n\n| Item | Quantity |\n| --- | --- |\n| Zulrah's scales | 100–299 |\n| Rare drop table |  |\n\n\
+             Layout only"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+/// YouTube transcript harvesting
+///
+/// Captions are read from the video's watch page (the caption tracks listed in its player
+/// response), preferring human-written captions in the requested language over
+/// auto-generated ones. The transcript is saved to `research/youtube/<title>.md` in
+/// paragraphs of about a minute, each starting with a timestamp that links to that point
+/// in the video.
+
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::net::SendChecked;
+
+pub const YOUTUBE_FOLDER: &str = "research/youtube";
+/// Seconds of captions per transcript paragraph
+const CHUNK_SECS: f64 = 60.0;
+
+lazy_static::lazy_static! {
+    static ref VIDEO_ID: Regex = Regex::new(r"^[A-Za-z0-9_-]{11}$").unwrap();
+    // srv1 (`<text start="1.2" dur="3">`) and srv3 (`<p t="1200" d="3000">`) caption formats
+    static ref CUE: Regex = Regex::new(r#"(?s)<text[^>]*\bstart="([\d.]+)"[^>]*>(.*?)</text>|<p[^>]*\bt="(\d+)"[^>]*>(.*?)</p>"#).unwrap();
+    static ref TAG: Regex = Regex::new(r"<[^>]+>").unwrap();
+    static ref WHITESPACE: Regex = Regex::new(r"\s+").unwrap();
+}
+
+fn client() -> reqwest::Client {
+    crate::net::client_with(crate::net::ClientOptions {
+        user_agent: Some("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36"),
+        timeout: Some(Duration::from_secs(20)),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Cue {
+    /// Seconds from the start of the video
+    pub start: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub video_id: String,
+    pub title: String,
+    pub language: String,
+    pub auto_generated: bool,
+    pub cues: Vec<Cue>,
+}
+
+impl Transcript {
+    pub fn url(&self) -> String {
+        format!("https://www.youtube.com/watch?v={}", self.video_id)
+    }
+
+    pub fn text(&self) -> String {
+        self.cues.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[derive(Serialize)]
+struct TranscriptFrontmatter<'a> {
+    title: &'a str,
+    created: String,
+    tags: Vec<&'a str>,
+    source: String,
+    video_id: &'a str,
+    language: &'a str,
+}
+
+/// The video id in a YouTube URL (watch, youtu.be, shorts, embed, live) or a bare id
+pub fn video_id(input: &str) -> Option<String> {
+    let input = input.trim();
+    if VIDEO_ID.is_match(input) {
+        return Some(input.to_string());
+    }
+    let url = url::Url::parse(input).ok()?;
+    let host = url.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    let id = match host {
+        "youtu.be" => url.path_segments()?.next().map(str::to_string),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            let segments: Vec<&str> = url.path_segments()?.collect();
+            match segments.as_slice() {
+                ["watch"] => url.query_pairs().find(|(k, _)| k == "v").map(|(_, v)| v.into_owned()),
+                ["shorts" | "embed" | "live" | "v", id, ..] => Some(id.to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }?;
+    if VIDEO_ID.is_match(&id) { Some(id) } else { None }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Caption cues from a timedtext document
+pub fn parse_timedtext(xml: &str) -> Vec<Cue> {
+    CUE.captures_iter(xml)
+        .filter_map(|c| {
+            let (start, raw) = match (c.get(1), c.get(2), c.get(3), c.get(4)) {
+                (Some(secs), Some(raw), _, _) => (secs.as_str().parse::<f64>().ok()?, raw.as_str()),
+                (_, _, Some(millis), Some(raw)) => (millis.as_str().parse::<f64>().ok()? / 1000.0, raw.as_str()),
+                _ => return None,
+            };
+            // Caption text is often escaped twice (`&amp;#39;`)
+            let text = decode_entities(&decode_entities(&TAG.replace_all(raw, "")));
+            let text = WHITESPACE.replace_all(&text, " ").trim().to_string();
+            if text.is_empty() { None } else { Some(Cue { start, text }) }
+        })
+        .collect()
+}
+
+/// `1:02:03` or `2:03`
+pub fn timestamp(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 { format!("{}:{:02}:{:02}", h, m, s) } else { format!("{}:{:02}", m, s) }
+}
+
+/// Cues grouped into paragraphs of about CHUNK_SECS each
+pub fn chunk(cues: &[Cue]) -> Vec<Cue> {
+    let mut chunks: Vec<Cue> = Vec::new();
+    for cue in cues {
+        match chunks.last_mut() {
+            Some(last) if cue.start - last.start < CHUNK_SECS => {
+                last.text.push(' ');
+                last.text.push_str(&cue.text);
+            }
+            _ => chunks.push(cue.clone()),
+        }
+    }
+    chunks
+}
+
+/// The JSON value that follows `"key":` in `page`
+fn json_after(page: &str, key: &str) -> Option<serde_json::Value> {
+    let start = page.find(&format!("\"{}\":", key))? + key.len() + 3;
+    serde_json::Deserializer::from_str(&page[start..]).into_iter::<serde_json::Value>().next()?.ok()
+}
+
+/// The caption track to use: the requested language first, human-written before auto-generated
+fn pick_track<'a>(tracks: &'a [serde_json::Value], lang: &str) -> Option<&'a serde_json::Value> {
+    tracks.iter().filter(|t| t["baseUrl"].is_string()).min_by_key(|t| {
+        let code = t["languageCode"].as_str().unwrap_or_default();
+        let other_language = !(code == lang || code.starts_with(&format!("{}-", lang)));
+        let auto = t["kind"].as_str() == Some("asr");
+        (other_language, auto)
+    })
+}
+
+/// Fetch a video's transcript in `lang` (or whatever language it has captions in)
+pub async fn fetch_transcript(video_id: &str, lang: &str) -> Result<Transcript, String> {
+    let watch_url = format!("https://www.youtube.com/watch?v={}&hl=en", video_id);
+    let page = client()
+        .get(&watch_url)
+        // Skip the cookie consent interstitial
+        .header(reqwest::header::COOKIE, "CONSENT=YES+1")
+        .send_checked()
+        .await
+        .map_err(|e| format!("Failed to fetch video page: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read video page: {}", e))?;
+
+    let title = json_after(&page, "videoDetails")
+        .and_then(|details| details["title"].as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("YouTube video {}", video_id));
+    let tracks = match json_after(&page, "captionTracks") {
+        Some(serde_json::Value::Array(tracks)) => tracks,
+        _ => return Err(format!("'{}' has no captions", title)),
+    };
+    let track = pick_track(&tracks, lang).ok_or_else(|| format!("'{}' has no captions", title))?;
+    let base_url = track["baseUrl"].as_str().unwrap_or_default();
+
+    let xml = client()
+        .get(base_url)
+        .send_checked()
+        .await
+        .map_err(|e| format!("Failed to fetch captions: {}", e))?
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read captions: {}", e))?;
+    let cues = parse_timedtext(&xml);
+    if cues.is_empty() {
+        return Err(format!("The captions for '{}' were empty", title));
+    }
+
+    Ok(Transcript {
+        video_id: video_id.to_string(),
+        title,
+        language: track["languageCode"].as_str().unwrap_or(lang).to_string(),
+        auto_generated: track["kind"].as_str() == Some("asr"),
+        cues,
+    })
+}
+
+pub fn render_note(transcript: &Transcript, summary: Option<&str>, date: &str) -> String {
+    let frontmatter = TranscriptFrontmatter {
+        title: &transcript.title,
+        created: date.to_string(),
+        tags: vec!["youtube", "transcript"],
+        source: transcript.url(),
+        video_id: &transcript.video_id,
+        language: &transcript.language,
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).unwrap_or_default();
+
+    let mut body = format!("# {}\n\n", transcript.title);
+    if let Some(summary) = summary.map(str::trim).filter(|s| !s.is_empty()) {
+        body.push_str(&format!("## Summary\n\n{}\n\n", summary));
+    }
+    body.push_str("## Transcript\n\n");
+    if transcript.auto_generated {
+        body.push_str("*Auto-generated captions*\n\n");
+    }
+    for paragraph in chunk(&transcript.cues) {
+        body.push_str(&format!(
+            "[{}](https://youtu.be/{}?t={}) {}\n\n",
+            timestamp(paragraph.start),
+            transcript.video_id,
+            paragraph.start as u64,
+            paragraph.text
+        ));
+    }
+    format!("---\n{}---\n\n{}\n", yaml, body.trim_end())
+}
+
+/// The note of an earlier harvest of the same video, else a new one named after its title
+pub fn note_path(kb_root: &Path, transcript: &Transcript) -> PathBuf {
+    let folder = kb_root.join(YOUTUBE_FOLDER);
+    crate::harvests::note_path(&folder, &folder, &crate::research_notes::slugify(&transcript.title), &transcript.url())
+}
+
+/// Save the transcript note; returns its path relative to the knowledge base
+pub fn save(app_handle: Option<&tauri::AppHandle>, kb_root: &Path, transcript: &Transcript, summary: Option<&str>) -> Result<String, String> {
+    let path = note_path(kb_root, transcript);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    crate::note_versions::record_before_write(app_handle, kb_root, &path, "youtube");
+    std::fs::write(&path, render_note(transcript, summary, &date)).map_err(|e| format!("Failed to save transcript: {}", e))?;
+
+    let key = crate::kb_index::path_key(kb_root, &path);
+    if let Some(handle) = app_handle {
+        crate::kb_index::apply_watch_changes(handle, &[path.clone()], None);
+        crate::events::emit(handle, "content-changed", serde_json::json!({
+            "source": kb_root.join(YOUTUBE_FOLDER),
+            "paths": [&key],
+        }));
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_cues() -> Vec<Cue> {
+        let xml = r#"<transcript><text start="0.5" dur="2">Ownership &amp;amp; borrowing</text>
+            <text start="30" dur="2">it&amp;#39;s   checked</text><text start="61.2" dur="1"><font color="#fff">at compile time</font></text></transcript>"#;
+        parse_timedtext(xml)
+    }
+
+    #[test]
+    fn test_video_id() {
+        assert_eq!(video_id("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://youtu.be/dQw4w9WgXcQ?si=abc").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://youtube.com/shorts/dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("dQw4w9WgXcQ").as_deref(), Some("dQw4w9WgXcQ"));
+        assert_eq!(video_id("https://vimeo.com/12345"), None);
+    }
+
+    #[test]
+    fn test_parse_timedtext() {
+        let cues = sample_cues();
+        assert_eq!(cues[1], Cue { start: 30.0, text: "it's checked".to_string() });
+        assert_eq!(parse_timedtext(r#"<timedtext><body><p t="3723000" d="900">Hi</p></body></timedtext>"#)[0].start, 3723.0);
+        assert_eq!(timestamp(3723.0), "1:02:03");
+    }
+
+    #[test]
+    fn test_chunk_into_paragraphs() {
+        let paragraphs = chunk(&sample_cues());
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(paragraphs[0].text, "Ownership & borrowing it's checked");
+    }
+
+    #[test]
+    fn test_pick_track() {
+        let tracks = serde_json::json!([
+            {"baseUrl": "a", "languageCode": "en", "kind": "asr"},
+            {"baseUrl": "b", "languageCode": "de"},
+            {"baseUrl": "c", "languageCode": "en-GB"}
+        ]);
+        let tracks = tracks.as_array().unwrap();
+        assert_eq!(pick_track(tracks, "en").unwrap()["baseUrl"], "c");
+        assert_eq!(pick_track(tracks, "fr").unwrap()["baseUrl"], "b");
+    }
+
+    #[test]
+    fn test_render_note() {
+        let transcript = Transcript {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            title: "Rust Ownership".to_string(),
+            language: "en".to_string(),
+            auto_generated: false,
+            cues: sample_cues(),
+        };
+        let note = render_note(&transcript, Some("Covers ownership."), "2026-01-01");
+        let (fm, body) = crate::frontmatter::split_frontmatter(&note);
+        assert_eq!(fm.unwrap().extra["video_id"], serde_json::json!("dQw4w9WgXcQ"));
+        assert!(body.contains("## Summary\n\nCovers ownership.\n\n## Transcript\n\n[0:00](https://youtu.be/dQw4w9WgXcQ?t=0) Ownership & borrowing"));
+        assert!(body.contains("[1:01](https://youtu.be/dQw4w9WgXcQ?t=61) at compile time"));
+    }
+}
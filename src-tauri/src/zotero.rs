@@ -0,0 +1,610 @@
+/// Zotero sync: pull library metadata in, push research citations out
+///
+/// `pull_zotero_library` copies the metadata of a Zotero library's top-level items (title,
+/// creators, date, URL, DOI, abstract, tags, collections) into knowledge_companion.db. It
+/// asks only for what changed since the library version of the last pull, and drops items
+/// deleted in Zotero, so pulling again is cheap.
+///
+/// `push_research_to_zotero` files a deep research run's cited sources into a collection,
+/// named after the topic unless another is given, creating it if needed. arXiv sources
+/// become preprints and the rest web pages. A source that's already in the pulled library
+/// (same URL) is added to the collection instead of duplicated, and sources pushed to that
+/// collection before are skipped, so pushing a run again only adds what's new.
+///
+/// The library is a user id (as shown on zotero.org/settings/keys), "users/<id>" or
+/// "groups/<id>"; the API key needs write access for pushing.
+
+use reqwest::Method;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use crate::citations::BibEntry;
+use crate::net::SendChecked;
+use crate::papers::PaperSource;
+
+const API: &str = "https://api.zotero.org";
+const API_VERSION: &str = "3";
+/// Items per page when reading, the API's maximum
+const PAGE_SIZE: usize = 100;
+/// Objects per write request, the API's maximum
+const WRITE_BATCH: usize = 50;
+const MAX_RETRIES: usize = 3;
+/// Tag on every item pushed from ThinkSpace
+const PUSH_TAG: &str = "thinkspace";
+
+pub fn init_zotero_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS zotero_items (
+            library TEXT NOT NULL,
+            item_key TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            item_type TEXT NOT NULL,
+            title TEXT NOT NULL,
+            creators TEXT NOT NULL DEFAULT '[]',
+            date TEXT,
+            url TEXT,
+            doi TEXT,
+            abstract TEXT,
+            tags TEXT NOT NULL DEFAULT '[]',
+            collections TEXT NOT NULL DEFAULT '[]',
+            PRIMARY KEY (library, item_key)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_zotero_items_url ON zotero_items(library, url)", [])?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS zotero_sync (
+            library TEXT PRIMARY KEY,
+            version INTEGER NOT NULL,
+            synced_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS zotero_pushes (
+            library TEXT NOT NULL,
+            collection_key TEXT NOT NULL,
+            url TEXT NOT NULL,
+            item_key TEXT NOT NULL,
+            run_id TEXT NOT NULL,
+            pushed_at TEXT NOT NULL,
+            PRIMARY KEY (library, collection_key, url)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// A Zotero item's metadata as pulled
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZoteroItem {
+    pub library: String,
+    pub key: String,
+    pub version: i64,
+    pub item_type: String,
+    pub title: String,
+    /// "First Last" or the single name Zotero has
+    pub creators: Vec<String>,
+    pub date: Option<String>,
+    pub url: Option<String>,
+    pub doi: Option<String>,
+    pub abstract_text: Option<String>,
+    pub tags: Vec<String>,
+    /// Keys of the collections it's in
+    pub collections: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoteroPullReport {
+    pub library: String,
+    /// Library version now stored
+    pub version: i64,
+    pub updated: usize,
+    pub deleted: usize,
+    /// Items stored for the library after the pull
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ZoteroPushReport {
+    pub collection_key: String,
+    pub collection_name: String,
+    pub created: usize,
+    /// Items already in the library that were added to the collection
+    pub added: usize,
+    /// Sources pushed to the collection before
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+/// The API path prefix for `library`: a bare user id, "users/<id>" or "groups/<id>"
+pub fn library_path(library: &str) -> Result<String, String> {
+    let library = library.trim().trim_matches('/');
+    let (kind, id) = library.split_once('/').unwrap_or(("users", library));
+    if !matches!(kind, "users" | "groups") || id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid Zotero library '{}': use your numeric user id or \"groups/<id>\"", library));
+    }
+    Ok(format!("{}/{}", kind, id))
+}
+
+/// Zotero object keys are eight characters of digits and capitals
+fn is_object_key(text: &str) -> bool {
+    text.len() == 8 && text.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+}
+
+fn text_field(data: &Value, field: &str) -> Option<String> {
+    data.get(field).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// An item from the API, or None for notes and attachments
+fn parse_item(library: &str, item: &Value) -> Option<ZoteroItem> {
+    let data = item.get("data")?;
+    let item_type = data.get("itemType")?.as_str()?;
+    if matches!(item_type, "note" | "attachment" | "annotation") {
+        return None;
+    }
+    let creators = data
+        .get("creators")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|creator| {
+            text_field(creator, "name").or_else(|| {
+                let name = format!("{} {}", creator["firstName"].as_str().unwrap_or(""), creator["lastName"].as_str().unwrap_or(""));
+                Some(name.trim().to_string()).filter(|n| !n.is_empty())
+            })
+        })
+        .collect();
+    let strings = |field: &str, inner: Option<&str>| -> Vec<String> {
+        data.get(field)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| inner.map_or(Some(v), |key| v.get(key)).and_then(|v| v.as_str()).map(str::to_string))
+            .collect()
+    };
+    Some(ZoteroItem {
+        library: library.to_string(),
+        key: item.get("key")?.as_str()?.to_string(),
+        version: item.get("version").and_then(|v| v.as_i64()).unwrap_or(0),
+        item_type: item_type.to_string(),
+        title: text_field(data, "title").unwrap_or_else(|| "(untitled)".to_string()),
+        creators,
+        date: text_field(data, "date"),
+        url: text_field(data, "url"),
+        doi: text_field(data, "DOI"),
+        abstract_text: text_field(data, "abstractNote"),
+        tags: strings("tags", Some("tag")),
+        collections: strings("collections", None),
+    })
+}
+
+/// A new Zotero item for a cited source, filed in `collection_key`
+fn new_item(entry: &BibEntry, topic: &str, collection_key: &str) -> Value {
+    let mut item = json!({
+        "itemType": "webpage",
+        "title": entry.title,
+        "url": entry.url,
+        "accessDate": entry.accessed,
+        "extra": format!("Cited in ThinkSpace research: {}", topic),
+        "tags": [{ "tag": PUSH_TAG }],
+        "collections": [collection_key],
+    });
+    if let PaperSource::Arxiv(id) = crate::papers::parse_source(&entry.url) {
+        item["itemType"] = json!("preprint");
+        item["repository"] = json!("arXiv");
+        item["archiveID"] = json!(format!("arXiv:{}", id));
+    }
+    item
+}
+
+/// Keys a write request stored, by the index of the object sent, and why others failed
+fn write_results(response: &Value, count: usize) -> (Vec<Option<String>>, Vec<String>) {
+    let keys = (0..count)
+        .map(|i| response["success"][i.to_string()].as_str().or_else(|| response["unchanged"][i.to_string()].as_str()).map(str::to_string))
+        .collect();
+    let failures = response["failed"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(i, failure)| format!("Object {}: {}", i, failure["message"].as_str().unwrap_or("failed")))
+        .collect();
+    (keys, failures)
+}
+
+fn save_item(conn: &Connection, item: &ZoteroItem) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO zotero_items (library, item_key, version, item_type, title, creators, date, url, doi, abstract, tags, collections)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            item.library,
+            item.key,
+            item.version,
+            item.item_type,
+            item.title,
+            serde_json::to_string(&item.creators).unwrap_or_else(|_| "[]".to_string()),
+            item.date,
+            item.url,
+            item.doi,
+            item.abstract_text,
+            serde_json::to_string(&item.tags).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&item.collections).unwrap_or_else(|_| "[]".to_string()),
+        ],
+    )?;
+    Ok(())
+}
+
+const ITEM_COLUMNS: &str = "library, item_key, version, item_type, title, creators, date, url, doi, abstract, tags, collections";
+
+fn item_from_row(row: &rusqlite::Row) -> rusqlite::Result<ZoteroItem> {
+    let list = |text: String| serde_json::from_str(&text).unwrap_or_default();
+    Ok(ZoteroItem {
+        library: row.get(0)?,
+        key: row.get(1)?,
+        version: row.get(2)?,
+        item_type: row.get(3)?,
+        title: row.get(4)?,
+        creators: list(row.get(5)?),
+        date: row.get(6)?,
+        url: row.get(7)?,
+        doi: row.get(8)?,
+        abstract_text: row.get(9)?,
+        tags: list(row.get(10)?),
+        collections: list(row.get(11)?),
+    })
+}
+
+/// Stored items, optionally of one library and matching `query` in title, creators or tags
+pub fn list_items(conn: &Connection, library: Option<&str>, query: Option<&str>, limit: usize) -> rusqlite::Result<Vec<ZoteroItem>> {
+    let pattern = query.map(|q| format!("%{}%", q.trim()));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM zotero_items
+         WHERE (?1 IS NULL OR library = ?1)
+           AND (?2 IS NULL OR title LIKE ?2 OR creators LIKE ?2 OR tags LIKE ?2)
+         ORDER BY title COLLATE NOCASE LIMIT ?3",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![library, pattern, limit as i64], item_from_row)?;
+    rows.collect()
+}
+
+fn item_by_url(conn: &Connection, library: &str, url: &str) -> rusqlite::Result<Option<ZoteroItem>> {
+    conn.query_row(
+        &format!("SELECT {} FROM zotero_items WHERE library = ?1 AND url = ?2 LIMIT 1", ITEM_COLUMNS),
+        params![library, url],
+        item_from_row,
+    )
+    .optional()
+}
+
+fn synced_version(conn: &Connection, library: &str) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT version FROM zotero_sync WHERE library = ?1", params![library], |row| row.get(0))
+        .optional()
+        .map(|v| v.unwrap_or(0))
+}
+
+fn pushed(conn: &Connection, library: &str, collection_key: &str, url: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT 1 FROM zotero_pushes WHERE library = ?1 AND collection_key = ?2 AND url = ?3",
+        params![library, collection_key, url],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|found| found.is_some())
+}
+
+fn record_push(conn: &Connection, library: &str, collection_key: &str, url: &str, item_key: &str, run_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO zotero_pushes (library, collection_key, url, item_key, run_id, pushed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![library, collection_key, url, item_key, run_id, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// A response's body with the library version and result count Zotero sends as headers
+struct ApiResponse {
+    body: Value,
+    version: Option<i64>,
+    total: Option<usize>,
+}
+
+/// One Zotero API call, retried when Zotero asks to back off
+async fn call(api_key: &str, method: Method, path: &str, body: Option<&Value>, headers: &[(&str, String)]) -> Result<ApiResponse, String> {
+    let url = format!("{}/{}", API, path);
+    for attempt in 0..=MAX_RETRIES {
+        let mut request = crate::net::client()
+            .request(method.clone(), &url)
+            .header("Zotero-API-Key", api_key.trim())
+            .header("Zotero-API-Version", API_VERSION);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send_checked().await.map_err(|e| format!("Zotero request failed: {}", e))?;
+        let status = response.status();
+        let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string());
+        if matches!(status.as_u16(), 429 | 503) && attempt < MAX_RETRIES {
+            let wait = header("retry-after").and_then(|s| s.parse::<u64>().ok()).unwrap_or(5);
+            tracing::info!("⏳ Zotero asked to wait {}s", wait);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            continue;
+        }
+        let version = header("last-modified-version").and_then(|v| v.parse().ok());
+        let total = header("total-results").and_then(|v| v.parse().ok());
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(match status.as_u16() {
+                403 => "Zotero refused the API key; check it has access to this library (and write access to push)".to_string(),
+                412 => format!("{} changed in Zotero since the last pull; pull the library and try again", path),
+                _ => format!("Zotero returned HTTP {} for {}: {}", status, path, text.chars().take(200).collect::<String>()),
+            });
+        }
+        let body = serde_json::from_str(&text).unwrap_or(Value::Null);
+        return Ok(ApiResponse { body, version, total });
+    }
+    Err("Zotero is rate limiting requests; try again later".to_string())
+}
+
+/// Every page of a listing under `path` (which already has its query string)
+async fn list_all(api_key: &str, path: &str) -> Result<(Vec<Value>, Option<i64>), String> {
+    let mut objects = Vec::new();
+    let mut version = None;
+    loop {
+        let page = call(api_key, Method::GET, &format!("{}&limit={}&start={}", path, PAGE_SIZE, objects.len()), None, &[]).await?;
+        version = version.or(page.version);
+        let batch = page.body.as_array().cloned().unwrap_or_default();
+        let done = batch.len() < PAGE_SIZE || page.total.map_or(false, |total| objects.len() + batch.len() >= total);
+        objects.extend(batch);
+        if done {
+            return Ok((objects, version));
+        }
+    }
+}
+
+/// The key of the collection called `name`, creating it when there's none
+async fn collection_key(api_key: &str, library: &str, name: &str) -> Result<String, String> {
+    let (collections, _) = list_all(api_key, &format!("{}/collections?format=json", library)).await?;
+    let existing = collections.iter().find(|c| c["data"]["name"].as_str().map_or(false, |n| n.trim().eq_ignore_ascii_case(name.trim())));
+    if let Some(key) = existing.and_then(|c| c["key"].as_str()) {
+        return Ok(key.to_string());
+    }
+    let body = json!([{ "name": name.trim(), "parentCollection": false }]);
+    let write_token = [("Zotero-Write-Token", uuid::Uuid::new_v4().simple().to_string())];
+    let created = call(api_key, Method::POST, &format!("{}/collections", library), Some(&body), &write_token).await?;
+    let (keys, failures) = write_results(&created.body, 1);
+    keys.into_iter().next().flatten().ok_or_else(|| format!("Couldn't create the Zotero collection '{}': {}", name, failures.join("; ")))
+}
+
+// ==================== Commands ====================
+
+/// Pull the metadata of a Zotero library's items, only what changed since the last pull
+#[tauri::command]
+pub async fn pull_zotero_library(app_handle: tauri::AppHandle, zotero_key: String, library: String) -> Result<ZoteroPullReport, String> {
+    let library = library_path(&library)?;
+    let since = {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        synced_version(&conn, &library).map_err(|e| e.to_string())?
+    };
+    tracing::info!("📚 Pulling Zotero library {} (since version {})", library, since);
+    let (objects, version) = list_all(&zotero_key, &format!("{}/items/top?format=json&since={}", library, since)).await?;
+    let deleted = call(&zotero_key, Method::GET, &format!("{}/deleted?since={}", library, since), None, &[]).await?;
+    let deleted_keys: Vec<String> = deleted.body["items"].as_array().into_iter().flatten().filter_map(|k| k.as_str().map(str::to_string)).collect();
+
+    let items: Vec<ZoteroItem> = objects.iter().filter_map(|item| parse_item(&library, item)).collect();
+    let version = version.or(deleted.version).unwrap_or(since);
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    for item in &items {
+        save_item(&conn, item).map_err(|e| e.to_string())?;
+    }
+    let mut removed = 0;
+    for key in &deleted_keys {
+        removed += conn
+            .execute("DELETE FROM zotero_items WHERE library = ?1 AND item_key = ?2", params![library, key])
+            .map_err(|e| e.to_string())?;
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO zotero_sync (library, version, synced_at) VALUES (?1, ?2, ?3)",
+        params![library, version, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| e.to_string())?;
+    let total = conn
+        .query_row("SELECT COUNT(*) FROM zotero_items WHERE library = ?1", params![library], |row| row.get::<_, i64>(0))
+        .map_err(|e| e.to_string())? as usize;
+    tracing::info!("✅ Zotero library {}: {} updated, {} deleted, {} stored", library, items.len(), removed, total);
+    Ok(ZoteroPullReport { library, version, updated: items.len(), deleted: removed, total })
+}
+
+/// Pulled Zotero items, optionally filtered by library and a search of title, creators and tags
+#[tauri::command]
+pub fn list_zotero_items(app_handle: tauri::AppHandle, library: Option<String>, query: Option<String>, limit: Option<usize>) -> Result<Vec<ZoteroItem>, String> {
+    let library = library.as_deref().map(library_path).transpose()?;
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    list_items(&conn, library.as_deref(), query.as_deref().filter(|q| !q.trim().is_empty()), limit.unwrap_or(200)).map_err(|e| e.to_string())
+}
+
+/// File a research run's cited sources into a Zotero collection: `collection` is a collection
+/// key or name, defaulting to the run's topic
+#[tauri::command]
+pub async fn push_research_to_zotero(
+    app_handle: tauri::AppHandle,
+    zotero_key: String,
+    library: String,
+    run_id: String,
+    collection: Option<String>,
+) -> Result<ZoteroPushReport, String> {
+    let library = library_path(&library)?;
+    let run = {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        crate::research_history::load_run(&conn, &run_id)?
+    };
+    let bibliography = run.bibliography.clone().filter(|b| !b.entries.is_empty()).ok_or_else(|| format!("Research run '{}' has no sources to push yet", run.topic))?;
+    // Only what the report cites, when there's a report to tell
+    let cited = run.report.as_deref().map(|report| crate::citations::check_citations(report, &bibliography).cited).filter(|cited| !cited.is_empty());
+    let entries: Vec<&BibEntry> = bibliography.entries.iter().filter(|e| cited.as_ref().map_or(true, |c| c.contains(&e.number))).collect();
+
+    let requested = collection.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let (collection_key, collection_name) = match requested {
+        Some(key) if is_object_key(key) => (key.to_string(), key.to_string()),
+        Some(name) => (collection_key(&zotero_key, &library, name).await?, name.to_string()),
+        None => (collection_key(&zotero_key, &library, &run.topic).await?, run.topic.clone()),
+    };
+    let mut report = ZoteroPushReport { collection_key: collection_key.clone(), collection_name, ..Default::default() };
+
+    // Sort the sources into already pushed, already in the library and new
+    let mut existing = Vec::new();
+    let mut new = Vec::new();
+    {
+        let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+        for entry in entries {
+            if pushed(&conn, &library, &collection_key, &entry.url).map_err(|e| e.to_string())? {
+                report.skipped += 1;
+            } else if let Some(item) = item_by_url(&conn, &library, &entry.url).map_err(|e| e.to_string())? {
+                existing.push((entry, item));
+            } else {
+                new.push(entry);
+            }
+        }
+    }
+    tracing::info!("📤 Pushing {} source(s) of '{}' to Zotero", existing.len() + new.len(), run.topic);
+
+    let mut stored: Vec<(String, String)> = Vec::new();
+    for (entry, item) in existing {
+        if item.collections.contains(&collection_key) {
+            stored.push((entry.url.clone(), item.key));
+            report.added += 1;
+            continue;
+        }
+        let mut collections = item.collections.clone();
+        collections.push(collection_key.clone());
+        let body = json!({ "collections": collections });
+        let version = [("If-Unmodified-Since-Version", item.version.to_string())];
+        match call(&zotero_key, Method::PATCH, &format!("{}/items/{}", library, item.key), Some(&body), &version).await {
+            Ok(_) => {
+                stored.push((entry.url.clone(), item.key));
+                report.added += 1;
+            }
+            Err(e) => report.errors.push(format!("{}: {}", entry.title, e)),
+        }
+    }
+    for batch in new.chunks(WRITE_BATCH) {
+        let body = Value::Array(batch.iter().map(|entry| new_item(entry, &run.topic, &collection_key)).collect());
+        let write_token = [("Zotero-Write-Token", uuid::Uuid::new_v4().simple().to_string())];
+        match call(&zotero_key, Method::POST, &format!("{}/items", library), Some(&body), &write_token).await {
+            Ok(response) => {
+                let (keys, failures) = write_results(&response.body, batch.len());
+                for (entry, key) in batch.iter().zip(keys) {
+                    if let Some(key) = key {
+                        stored.push((entry.url.clone(), key));
+                        report.created += 1;
+                    }
+                }
+                report.errors.extend(failures);
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+
+    let conn = crate::minimax_api::open_kc_database(Some(&app_handle))?;
+    for (url, item_key) in &stored {
+        record_push(&conn, &library, &collection_key, url, item_key, &run_id).map_err(|e| e.to_string())?;
+    }
+    tracing::info!("✅ Zotero: {} created, {} added, {} skipped, {} failed", report.created, report.added, report.skipped, report.errors.len());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_item() -> ZoteroItem {
+        let item = json!({
+            "key": "ABCD2345",
+            "version": 12,
+            "data": {
+                "itemType": "journalArticle",
+                "title": "Attention Is All You Need",
+                "creators": [
+                    { "creatorType": "author", "firstName": "Ashish", "lastName": "Vaswani" },
+                    { "creatorType": "author", "name": "Google Brain" }
+                ],
+                "date": "2017",
+                "url": "https://arxiv.org/abs/1706.03762",
+                "DOI": "",
+                "tags": [{ "tag": "transformers" }],
+                "collections": ["COLL0001"]
+            }
+        });
+        parse_item("users/1", &item).unwrap()
+    }
+
+    fn zotero_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_zotero_tables(&conn).unwrap();
+        conn
+    }
+
+    fn entry(url: &str) -> BibEntry {
+        BibEntry { number: 1, title: "Source".to_string(), url: url.to_string(), accessed: "2026-10-16".to_string(), credibility: None }
+    }
+
+    #[test]
+    fn test_library_path() {
+        assert_eq!(library_path("1234567").unwrap(), "users/1234567");
+        assert_eq!(library_path("/groups/42/").unwrap(), "groups/42");
+        assert!(library_path("teams/42").is_err() && library_path("me").is_err());
+    }
+
+    #[test]
+    fn test_is_object_key() {
+        assert!(is_object_key("ABCD2345") && !is_object_key("Reading list"));
+    }
+
+    #[test]
+    fn test_parse_item() {
+        let item = sample_item();
+        assert_eq!(item.creators, ["Ashish Vaswani", "Google Brain"]);
+        assert_eq!((item.doi.as_deref(), item.tags.as_slice(), item.version), (None, &["transformers".to_string()][..], 12));
+    }
+
+    #[test]
+    fn test_parse_item_skips_notes() {
+        assert!(parse_item("users/1", &json!({ "key": "NOTE0001", "data": { "itemType": "note" } })).is_none());
+    }
+
+    #[test]
+    fn test_saved_items() {
+        let (conn, item) = (zotero_db(), sample_item());
+        save_item(&conn, &item).unwrap();
+        assert_eq!(list_items(&conn, Some("users/1"), Some("vaswani"), 10).unwrap(), vec![item.clone()]);
+        assert!(list_items(&conn, Some("groups/2"), None, 10).unwrap().is_empty());
+        assert_eq!(item_by_url(&conn, "users/1", "https://arxiv.org/abs/1706.03762").unwrap().map(|i| i.key), Some("ABCD2345".to_string()));
+        assert_eq!(synced_version(&conn, "users/1").unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pushes_are_recorded_per_collection() {
+        let conn = zotero_db();
+        record_push(&conn, "users/1", "COLL0002", "https://example.com", "ITEM0001", "run-1").unwrap();
+        assert!(pushed(&conn, "users/1", "COLL0002", "https://example.com").unwrap());
+        assert!(!pushed(&conn, "users/1", "COLL0001", "https://example.com").unwrap());
+    }
+
+    #[test]
+    fn test_new_item_types() {
+        let paper = new_item(&entry("https://arxiv.org/abs/1706.03762v5"), "Transformers", "COLL0002");
+        assert_eq!((paper["itemType"].as_str(), paper["archiveID"].as_str()), (Some("preprint"), Some("arXiv:1706.03762v5")));
+        let page = new_item(&entry("https://example.com/post"), "Transformers", "COLL0002");
+        assert_eq!((page["itemType"].as_str(), page["collections"][0].as_str()), (Some("webpage"), Some("COLL0002")));
+    }
+
+    #[test]
+    fn test_write_results() {
+        let response = json!({ "success": { "0": "NEWKEY01" }, "unchanged": {}, "failed": { "1": { "code": 400, "message": "Invalid URL" } } });
+        let (keys, failures) = write_results(&response, 2);
+        assert_eq!(keys, vec![Some("NEWKEY01".to_string()), None]);
+        assert_eq!(failures, vec!["Object 1: Invalid URL".to_string()]);
+    }
+}
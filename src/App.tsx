@@ -5,6 +5,7 @@ import { X, CheckCircle, AlertCircle, Save, Upload, Download, Loader2 } from 'lu
 import SaveSessionModal from './components/SaveSessionModal';
 import LoadSessionModal from './components/LoadSessionModal';
 import { invoke } from '@tauri-apps/api/tauri';
+import { getSearchSettings, setSearchSettings, SearchSettings } from './lib/tauri-bridge';
 import AIChatEnhanced from './components/AIChatEnhanced';
 import ThemeSwitcher from './components/ThemeSwitcher';
 import { ThemeProvider, useTheme } from './contexts/ThemeContext';
@@ -64,11 +65,13 @@ function AppContent() {
   const [showSettings, setShowSettings] = useState(false);
   const [tempApiKey, setTempApiKey] = useState('');
   const [tempTavilyApiKey, setTempTavilyApiKey] = useState('');
+  const [tempSearchSettings, setTempSearchSettings] = useState<SearchSettings>({ provider: 'auto' });
   const [tempGrokApiKey, setTempGrokApiKey] = useState('');
   const [tempQdrantApiKey, setTempQdrantApiKey] = useState('');
   const [tempQdrantHost, setTempQdrantHost] = useState('');
   const [tempQdrantCollection, setTempQdrantCollection] = useState('TheDojoKnowledge');
   const [tempCohereApiKey, setTempCohereApiKey] = useState('');
+  const [tempGithubToken, setTempGithubToken] = useState('');
   const [testStatus, setTestStatus] = useState<'idle' | 'testing' | 'success' | 'error'>('idle');
   const [testMessage, setTestMessage] = useState('');
   const [qdrantTestStatus, setQdrantTestStatus] = useState<'idle' | 'testing' | 'success' | 'error'>('idle');
@@ -215,6 +218,10 @@ function AppContent() {
       setTempTavilyApiKey(tavilySaved);
     }
 
+    getSearchSettings()
+      .then(setTempSearchSettings)
+      .catch((error) => console.error('Failed to load search settings:', error));
+
     const grokSaved = localStorage.getItem('grok_api_key');
     if (grokSaved) {
       setTempGrokApiKey(grokSaved);
@@ -240,6 +247,14 @@ function AppContent() {
       setTempCohereApiKey(cohereSaved);
     }
 
+    // The backend only keeps the GitHub token in memory, so hand it over on every start
+    const githubTokenSaved = localStorage.getItem('github_token');
+    if (githubTokenSaved) {
+      setTempGithubToken(githubTokenSaved);
+      invoke('set_github_token', { token: githubTokenSaved })
+        .catch((error) => console.error('Failed to set GitHub token:', error));
+    }
+
     const initializeTKG = async () => {
       const qdrantKey = localStorage.getItem('qdrant_api_key');
       const qdrantHost = localStorage.getItem('qdrant_host');
@@ -269,6 +284,25 @@ function AppContent() {
     initializeTKG();
   }, []);
 
+  // Let scheduled agent tasks run in the background with the saved keys
+  useEffect(() => {
+    if (!apiKey) return;
+    invoke('start_scheduler', {
+      apiKey,
+      tavilyKey: localStorage.getItem('tavily_api_key'),
+      grokKey: localStorage.getItem('grok_api_key'),
+      userId: user?.id || 'guest',
+    }).catch((error) => console.error('Failed to start task scheduler:', error));
+  }, [apiKey, user]);
+
+  // Poll registered feeds; the key is only used for item summaries
+  useEffect(() => {
+    invoke('start_feed_poller', {
+      apiKey: apiKey || null,
+      grokKey: localStorage.getItem('grok_api_key'),
+    }).catch((error) => console.error('Failed to start feed poller:', error));
+  }, [apiKey]);
+
   // Fetch Cloud Config on Login
   useEffect(() => {
     const fetchCloudConfig = async () => {
@@ -386,6 +420,7 @@ Use this space for quick notes.`);
         main_canvas: null,
         left_canvas: null,
         visuals: null,
+        settings: null,
       };
 
       if (options.chat) {
@@ -394,6 +429,19 @@ Use this space for quick notes.`);
         if (savedChat) {
           sessionData.chat = JSON.parse(savedChat);
         }
+
+        // The agent configuration, so reopening the session restores it: the live session's own
+        // when it was restored from a file, otherwise what the chat panel is set to now
+        const chatSession = localStorage.getItem('chat_session_name');
+        const live = chatSession ? await api.getAgentSession(chatSession) : null;
+        sessionData.settings = live?.settings ?? {
+          provider: localStorage.getItem('selected_ai_provider') || 'minimax',
+          model: null,
+          enabled_tools: JSON.parse(localStorage.getItem('enabled_tools') || '{}'),
+          safe_mode: false,
+          dry_run: false,
+          system_prompt: null,
+        };
       }
 
       if (options.mainCanvas) {
@@ -430,6 +478,12 @@ Use this space for quick notes.`);
         const chatStorageKey = user?.id ? `chat_messages_${user.id}` : 'chat_messages_guest';
         localStorage.setItem(chatStorageKey, JSON.stringify(data.chat));
         localStorage.setItem('chat_messages_enhanced', JSON.stringify(data.chat));
+        // Continue in the saved session, whose settings the backend restores on the first turn
+        localStorage.setItem('chat_session_name', data.name);
+        if (data.settings) {
+          localStorage.setItem('selected_ai_provider', data.settings.provider);
+          localStorage.setItem('enabled_tools', JSON.stringify(data.settings.enabled_tools || {}));
+        }
         // Force reload chat component or notify it? 
         // For now, a simple window reload might be cleanest to reset all state, 
         // but let's try to do it gracefully if possible.
@@ -670,6 +724,10 @@ Use this space for quick notes.`);
         localStorage.setItem('tavily_api_key', tempTavilyApiKey);
       }
 
+      setSearchSettings(tempSearchSettings).catch((error) => {
+        console.error('Failed to save search settings:', error);
+      });
+
       if (tempGrokApiKey) {
         localStorage.setItem('grok_api_key', tempGrokApiKey);
       }
@@ -690,6 +748,14 @@ Use this space for quick notes.`);
         localStorage.setItem('cohere_api_key', tempCohereApiKey);
       }
 
+      if (tempGithubToken) {
+        localStorage.setItem('github_token', tempGithubToken);
+      } else {
+        localStorage.removeItem('github_token');
+      }
+      invoke('set_github_token', { token: tempGithubToken || null })
+        .catch((error) => console.error('Failed to set GitHub token:', error));
+
       // Initialize TKG with credentials
       if (tempQdrantApiKey && tempQdrantHost && tempCohereApiKey) {
         // Extract port from host URL if present, otherwise use default 6334
@@ -928,6 +994,64 @@ Use this space for quick notes.`);
                       </p>
                     </div>
 
+                    {/* GitHub Token Section */}
+                    <div>
+                      <label className="text-sm mb-2 block text-foreground">
+                        GitHub Personal Access Token (optional)
+                      </label>
+                      <input
+                        type="password"
+                        value={tempGithubToken}
+                        onChange={(e) => {
+                          setTempGithubToken(e.target.value);
+                        }}
+                        placeholder="ghp_..."
+                        className="w-full px-4 py-2 bg-input border border-border rounded-lg focus:outline-none focus:ring-2 focus:ring-primary text-foreground"
+                      />
+                      <p className="text-xs mt-1 text-muted-foreground">
+                        Lets the GitHub harvester read private repositories and raises its rate limit
+                      </p>
+                    </div>
+
+                    {/* Search Provider Section */}
+                    <div>
+                      <label className="text-sm mb-2 block text-foreground">
+                        Web Search Provider
+                      </label>
+                      <select
+                        value={tempSearchSettings.provider}
+                        onChange={(e) => setTempSearchSettings({ ...tempSearchSettings, provider: e.target.value as SearchSettings['provider'] })}
+                        className="w-full px-4 py-2 bg-input border border-border rounded-lg focus:outline-none focus:ring-2 focus:ring-primary text-foreground"
+                      >
+                        <option value="auto">Auto (first configured, else DuckDuckGo)</option>
+                        <option value="tavily">Tavily</option>
+                        <option value="brave">Brave Search</option>
+                        <option value="searxng">SearXNG (self-hosted)</option>
+                        <option value="duckduckgo">DuckDuckGo (no key needed)</option>
+                      </select>
+                      {(tempSearchSettings.provider === 'brave' || tempSearchSettings.provider === 'auto') && (
+                        <input
+                          type="password"
+                          value={tempSearchSettings.brave_api_key ?? ''}
+                          onChange={(e) => setTempSearchSettings({ ...tempSearchSettings, brave_api_key: e.target.value })}
+                          placeholder="Brave Search API key (optional)..."
+                          className="w-full mt-2 px-4 py-2 bg-input border border-border rounded-lg focus:outline-none focus:ring-2 focus:ring-primary text-foreground"
+                        />
+                      )}
+                      {(tempSearchSettings.provider === 'searxng' || tempSearchSettings.provider === 'auto') && (
+                        <input
+                          type="text"
+                          value={tempSearchSettings.searxng_url ?? ''}
+                          onChange={(e) => setTempSearchSettings({ ...tempSearchSettings, searxng_url: e.target.value })}
+                          placeholder="SearXNG URL, e.g. http://localhost:8888 (optional)..."
+                          className="w-full mt-2 px-4 py-2 bg-input border border-border rounded-lg focus:outline-none focus:ring-2 focus:ring-primary text-foreground"
+                        />
+                      )}
+                      <p className="text-xs mt-1 text-muted-foreground">
+                        Used by web search and deep research. SearXNG needs the JSON format enabled in settings.yml.
+                      </p>
+                    </div>
+
                     {/* Grok API Key Section */}
                     <div>
                       <label className="text-sm mb-2 block text-foreground">
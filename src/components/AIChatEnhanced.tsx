@@ -1,6 +1,7 @@
 import React, { useState, useRef, useEffect, useCallback, memo } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import { emitEvent as emit, listenEvent as listen } from '../lib/events';
+import { api } from '../lib/api';
 import { Brain, Send, Sparkles, Bot, Zap, ChevronDown, Loader2, RotateCcw, Wrench, Lightbulb, Pin, Paperclip, X } from 'lucide-react';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
@@ -10,7 +11,8 @@ import { Button } from './ui/button';
 import genesisAvatar from '../assets/genesis-avatar.png';
 import userAvatar from '../assets/user-avatar.png';
 import hawkeyeLogo from '../assets/hawkeye-logo.png';
-import { DeepResearchPreview, ResearchStep } from './DeepResearchPreview';
+import { DeepResearchPreview, ResearchProgress } from './DeepResearchPreview';
+import { DebatePreview, DebateTurnEvent } from './DebatePreview';
 
 // Theme-specific code block colors
 const getCodeBlockColors = (theme: Theme) => {
@@ -135,12 +137,13 @@ const createMarkdownComponents = (theme: Theme) => ({
 });
 
 // Memoized message component to prevent unnecessary re-renders
-const MessageItem = memo(({ message, streamingMessageId, currentStreamedContent, theme, researchSteps, isLatest, textScale }: {
+const MessageItem = memo(({ message, streamingMessageId, currentStreamedContent, theme, researchSteps, debateTurns, isLatest, textScale }: {
   message: Message;
   streamingMessageId: string | null;
   currentStreamedContent: string;
   theme: Theme;
-  researchSteps?: ResearchStep[];
+  researchSteps?: ResearchProgress[];
+  debateTurns?: DebateTurnEvent[];
   isLatest?: boolean;
   textScale: number;
 }) => {
@@ -266,7 +269,12 @@ const MessageItem = memo(({ message, streamingMessageId, currentStreamedContent,
               ))}
               {message.tool_calls.some(t => t.function.name === 'deep_research') && isLatest && researchSteps && (
                 <div className="mt-2">
-                  <DeepResearchPreview steps={researchSteps} isComplete={false} />
+                  <DeepResearchPreview events={researchSteps} isComplete={false} />
+                </div>
+              )}
+              {message.tool_calls.some(t => t.function.name === 'start_debate') && isLatest && debateTurns && (
+                <div className="mt-2">
+                  <DebatePreview turns={debateTurns} isComplete={!isStreaming} />
                 </div>
               )}
             </div>
@@ -366,7 +374,7 @@ I can now:
 - Search your knowledge base
 - Generate study guides
 - List available resources
-- **Web search** (with Tavily API)
+- **Web search** (Tavily, Brave, SearXNG or DuckDuckGo)
 - **Write files** to your knowledge base!
 - **Create 3D CAD models** (using Manifold)! 🧊
 
@@ -420,11 +428,17 @@ What would you like to explore today?`,
   const [currentStreamedContent, setCurrentStreamedContent] = useState('');
   const streamingMessageIdRef = useRef<string | null>(null);
   const currentStreamedContentRef = useRef<string>('');
+  // The backend agent session this chat runs in; it keeps tool calls and settings between turns
+  const [sessionName, setSessionName] = useState<string>(() => {
+    return localStorage.getItem('chat_session_name') || `chat-${Date.now()}`;
+  });
+  const sessionNameRef = useRef(sessionName);
   const messagesEndRef = useRef<HTMLDivElement>(null);
   const [, setIsComposing] = useState(false);
   const [lastStudyGuide, setLastStudyGuide] = useState<string>('');
   const [, setLastBrainstorm] = useState<string>('');
-  const [researchSteps, setResearchSteps] = useState<ResearchStep[]>([]);
+  const [researchSteps, setResearchSteps] = useState<ResearchProgress[]>([]);
+  const [debateTurns, setDebateTurns] = useState<DebateTurnEvent[]>([]);
   const [attachedFiles, setAttachedFiles] = useState<AttachedFile[]>([]);
   const [canvasSnippet, setCanvasSnippet] = useState<CanvasSnippetContext | null>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
@@ -487,20 +501,35 @@ What would you like to explore today?`,
     const defaults = {
       'calculate': true,
       'search_knowledge': true,
+      'get_note_links': true,
       'read_file': true,
       'list_markdown_files': true,
       'create_study_guide': true,
+      'create_flashcards': true,
+      'notify': true,
+      'log_daily_note': true,
       'write_file': true,
       'web_search': true,
       'brainstorm_with_grok': true,
       'consult_agent': true,
       'scan_codebase': true,
+      'find_symbol': true,
+      'grep_codebase': true,
+      'get_repo_stats': true,
       'start_debate': true,
       'write_file_batch': true,
       'run_terminal_command': true,
       'tkg_search': true,
       'tkg_store': true,
       'deep_research': true,
+      'ingest_paper': true,
+      'read_pdf': true,
+      'capture_screenshot': true,
+      'read_tool_result': true,
+      'clip_url': true,
+      'harvest_youtube_transcript': true,
+      'harvest_github': true,
+      'translate': true,
     };
 
     const saved = localStorage.getItem('enabled_tools');
@@ -528,6 +557,11 @@ What would you like to explore today?`,
     streamingMessageIdRef.current = streamingMessageId;
   }, [streamingMessageId]);
 
+  useEffect(() => {
+    sessionNameRef.current = sessionName;
+    localStorage.setItem('chat_session_name', sessionName);
+  }, [sessionName]);
+
   // Update ref synchronously with state
   const setCurrentStreamedContentSync = (updater: string | ((prev: string) => string)) => {
     const newValue = typeof updater === 'function' ? (updater as (prev: string) => string)(currentStreamedContentRef.current) : updater;
@@ -536,7 +570,14 @@ What would you like to explore today?`,
   };
 
   const handleStop = async () => {
-    await emit('stop-generation');
+    if (import.meta.env.VITE_AI_GATEWAY_URL) {
+      await emit('stop-generation');
+    } else {
+      // Only this chat's turn; other sessions keep streaming
+      await api.stopAgentSession(sessionNameRef.current);
+    }
+    // A debate tool call keeps going until its own stop signal
+    await emit('stop-debate');
     setLoading(false);
     setStreamingMessageId(null);
   };
@@ -601,8 +642,11 @@ What would you like to explore today?`,
       is_thinking: boolean;
       done: boolean;
       tool_calls?: ToolCall[];
+      session?: string | null;
     }>('chat-stream', (event) => {
       const chunk = event.payload;
+      // Chunks of another session's turn
+      if (chunk.session && chunk.session !== sessionNameRef.current) return;
 
       if (chunk.content) {
         setCurrentStreamedContentSync((prev) => prev + chunk.content);
@@ -678,12 +722,20 @@ What would you like to explore today?`,
 
   // Listen for deep research progress
   useEffect(() => {
-    const unlistenPromise = listen<ResearchStep>('research-progress', (event) => {
+    const unlistenPromise = listen<ResearchProgress>('research-progress', (event) => {
       setResearchSteps(prev => [...prev, event.payload]);
     });
     return () => { unlistenPromise.then(f => f()); };
   }, []);
 
+  // Listen for debate turns as they happen
+  useEffect(() => {
+    const unlistenPromise = listen<DebateTurnEvent>('debate-turn', (event) => {
+      setDebateTurns(prev => [...prev, event.payload]);
+    });
+    return () => { unlistenPromise.then(f => f()); };
+  }, []);
+
   // Helper to process canvas updates (reused by JSON parser and native tool)
   const processCanvasUpdate = useCallback((update: any) => {
     let mediaUrl: string | null = null;
@@ -931,6 +983,7 @@ What would you like to explore today?`,
     setLoading(true);
     setCurrentThinking([]);
     setResearchSteps([]);
+    setDebateTurns([]);
     setToolCallsCount(0);
     setIterationsCount(0);
 
@@ -986,6 +1039,7 @@ What would you like to explore today?`,
               timestamp: m.timestamp ? new Date(m.timestamp).toISOString() : undefined,
             })),
           maxIterations: 50,
+          sessionName,
         });
       } else {
         // Web browser fallback - non-streaming
@@ -1057,6 +1111,9 @@ What would you like to explore today?`,
     setCanvasSnippet(null);
     const storageKey = user?.id ? `chat_messages_${user.id}` : 'chat_messages_guest';
     localStorage.setItem(storageKey, JSON.stringify([newDefaultMessage]));
+    // A cleared chat starts a new agent session
+    api.closeAgentSession(sessionNameRef.current).catch(() => {});
+    setSessionName(`chat-${Date.now()}`);
   }, [defaultMessage, user?.id]);
 
   const quickPrompts = [
@@ -1182,6 +1239,7 @@ What would you like to explore today?`,
               currentStreamedContent={currentStreamedContent}
               theme={theme}
               researchSteps={researchSteps}
+              debateTurns={debateTurns}
               isLatest={index === messages.length - 1}
               textScale={textScale}
             />
@@ -0,0 +1,112 @@
+import React, { useMemo } from 'react';
+import {
+    LineChart, Line, BarChart, Bar, AreaChart, Area, PieChart, Pie, Cell,
+    XAxis, YAxis, CartesianGrid, Tooltip, Legend, ResponsiveContainer,
+} from 'recharts';
+
+// A `chart` block as the backend normalizes it (see canvas_blocks.rs)
+interface ChartSpec {
+    type: 'line' | 'bar' | 'area' | 'pie';
+    title?: string;
+    labels: string[];
+    series: { name: string; values: number[] }[];
+}
+
+interface ChartBlockProps {
+    code: string;
+}
+
+const COLORS = ['#3b82f6', '#10b981', '#f59e0b', '#ef4444', '#8b5cf6', '#06b6d4', '#ec4899', '#84cc16', '#f97316', '#6366f1', '#14b8a6', '#a855f7'];
+
+const tooltipStyle = {
+    backgroundColor: '#1e293b',
+    border: '1px solid #334155',
+    borderRadius: '8px',
+};
+
+const ChartBlock: React.FC<ChartBlockProps> = ({ code }) => {
+    const spec = useMemo<ChartSpec | null>(() => {
+        try {
+            const parsed = JSON.parse(code);
+            return parsed && Array.isArray(parsed.labels) && Array.isArray(parsed.series) ? parsed : null;
+        } catch {
+            return null;
+        }
+    }, [code]);
+
+    if (!spec) {
+        return (
+            <div className="p-4 border border-red-500/20 bg-red-500/10 rounded-lg text-red-500 text-sm">
+                Invalid chart data
+                <pre className="mt-2 text-xs opacity-70 overflow-x-auto">{code}</pre>
+            </div>
+        );
+    }
+
+    // Recharts wants one row per label with a key per series
+    const rows = spec.labels.map((label, i) => {
+        const row: Record<string, string | number> = { label };
+        spec.series.forEach((s) => { row[s.name] = s.values[i]; });
+        return row;
+    });
+
+    const axes = (
+        <>
+            <CartesianGrid strokeDasharray="3 3" stroke="#334155" />
+            <XAxis dataKey="label" stroke="#94a3b8" />
+            <YAxis stroke="#94a3b8" />
+            <Tooltip contentStyle={tooltipStyle} />
+            {spec.series.length > 1 && <Legend />}
+        </>
+    );
+
+    let chart: React.ReactElement;
+    if (spec.type === 'pie') {
+        const slices = spec.labels.map((label, i) => ({ label, value: spec.series[0]?.values[i] ?? 0 }));
+        chart = (
+            <PieChart>
+                <Pie data={slices} dataKey="value" nameKey="label" outerRadius={110} label>
+                    {slices.map((_, i) => <Cell key={i} fill={COLORS[i % COLORS.length]} />)}
+                </Pie>
+                <Tooltip contentStyle={tooltipStyle} />
+                <Legend />
+            </PieChart>
+        );
+    } else if (spec.type === 'bar') {
+        chart = (
+            <BarChart data={rows}>
+                {axes}
+                {spec.series.map((s, i) => <Bar key={s.name} dataKey={s.name} fill={COLORS[i % COLORS.length]} />)}
+            </BarChart>
+        );
+    } else if (spec.type === 'area') {
+        chart = (
+            <AreaChart data={rows}>
+                {axes}
+                {spec.series.map((s, i) => (
+                    <Area key={s.name} type="monotone" dataKey={s.name} stroke={COLORS[i % COLORS.length]} fill={COLORS[i % COLORS.length]} fillOpacity={0.25} />
+                ))}
+            </AreaChart>
+        );
+    } else {
+        chart = (
+            <LineChart data={rows}>
+                {axes}
+                {spec.series.map((s, i) => (
+                    <Line key={s.name} type="monotone" dataKey={s.name} stroke={COLORS[i % COLORS.length]} strokeWidth={2} dot={{ r: 3 }} />
+                ))}
+            </LineChart>
+        );
+    }
+
+    return (
+        <div className="my-4 p-4 bg-card rounded-lg border border-border">
+            {spec.title && <h4 className="text-md font-semibold text-foreground mb-3">{spec.title}</h4>}
+            <ResponsiveContainer width="100%" height={300}>
+                {chart}
+            </ResponsiveContainer>
+        </div>
+    );
+};
+
+export default ChartBlock;
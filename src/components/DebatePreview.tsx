@@ -0,0 +1,82 @@
+import React, { useEffect, useMemo, useRef } from 'react';
+import { MessagesSquare, Gavel, Square, Loader2 } from 'lucide-react';
+import { emitEvent as emit } from '../lib/events';
+
+/** Payload of the `debate-turn` event */
+export interface DebateTurnEvent {
+    debate_id: string;
+    turn_index: number;
+    speaker: string;
+    content: string;
+    timestamp: number;
+    round: number;
+    role: 'participant' | 'moderator' | 'final';
+}
+
+interface DebatePreviewProps {
+    turns: DebateTurnEvent[];
+    isComplete: boolean;
+}
+
+export const DebatePreview: React.FC<DebatePreviewProps> = ({ turns, isComplete }) => {
+    const scrollRef = useRef<HTMLDivElement>(null);
+
+    useEffect(() => {
+        if (scrollRef.current) {
+            scrollRef.current.scrollTop = scrollRef.current.scrollHeight;
+        }
+    }, [turns]);
+
+    // Only the latest debate's turns, in transcript order
+    const debateId = turns[turns.length - 1]?.debate_id;
+    const current = useMemo(
+        () => turns.filter(t => t.debate_id === debateId).sort((a, b) => a.turn_index - b.turn_index),
+        [turns, debateId]
+    );
+    const done = isComplete || current.some(t => t.role === 'final');
+    const round = current[current.length - 1]?.round ?? 1;
+
+    const handleStop = () => {
+        if (debateId) emit('stop-debate', { debate_id: debateId });
+    };
+
+    return (
+        <div className="bg-card/50 border border-border rounded-lg p-4 my-2 max-w-2xl w-full font-sans">
+            <div className="flex items-center gap-2 mb-2 border-b border-border pb-2">
+                <MessagesSquare className="w-5 h-5 text-primary" />
+                <h3 className="font-semibold text-sm">Debate</h3>
+                <span className="text-xs text-muted-foreground">Round {round}</span>
+                {done ? (
+                    <span className="text-xs bg-green-500/10 text-green-500 px-2 py-0.5 rounded-full ml-auto">Completed</span>
+                ) : (
+                    <button
+                        onClick={handleStop}
+                        disabled={!debateId}
+                        className="flex items-center gap-1 text-xs bg-red-500/10 text-red-500 hover:bg-red-500/20 px-2 py-0.5 rounded-full ml-auto disabled:opacity-50"
+                    >
+                        <Square className="w-3 h-3" /> Stop
+                    </button>
+                )}
+            </div>
+
+            <div ref={scrollRef} className="space-y-3 max-h-72 overflow-y-auto pr-2 custom-scrollbar">
+                {current.map(turn => (
+                    <div key={turn.turn_index} className="text-sm animate-in fade-in slide-in-from-left-2 duration-300">
+                        <div className="flex items-center gap-1.5 font-semibold">
+                            {turn.role !== 'participant' && <Gavel className="w-3.5 h-3.5 text-orange-500" />}
+                            <span>{turn.speaker}</span>
+                        </div>
+                        <p className="text-muted-foreground whitespace-pre-wrap line-clamp-6">{turn.content}</p>
+                    </div>
+                ))}
+
+                {!done && (
+                    <div className="flex items-center gap-2 text-muted-foreground text-sm italic">
+                        <Loader2 className="w-4 h-4 animate-spin" />
+                        {current.length === 0 ? 'Opening the debate...' : 'Next speaker is thinking...'}
+                    </div>
+                )}
+            </div>
+        </div>
+    );
+};
@@ -1,74 +1,162 @@
-import React, { useEffect, useRef } from 'react';
-import { CheckCircle2, Circle, Loader2, Search, FileText, Brain } from 'lucide-react';
+import React, { useEffect, useMemo, useRef } from 'react';
+import { CheckCircle2, Circle, Loader2, Search, FileText, Brain, Gauge, XCircle, Link2 } from 'lucide-react';
 
-export interface ResearchStep {
-    step_type: 'planning' | 'searching' | 'analyzing' | 'synthesizing';
-    description: string;
-    details?: string;
+export type ResearchPhase =
+    | 'planning'
+    | 'searching'
+    | 'reading'
+    | 'extracting'
+    | 'budget'
+    | 'synthesizing'
+    | 'revising'
+    | 'complete'
+    | 'failed';
+
+/** Payload of the `research-progress` event */
+export interface ResearchProgress {
+    run_id: string;
+    phase: ResearchPhase;
+    sub_topic?: string | null;
+    source_url?: string | null;
+    message: string;
+    details?: string | null;
+    tokens_used: number;
+    percent_complete: number;
 }
 
 interface DeepResearchPreviewProps {
-    steps: ResearchStep[];
+    events: ResearchProgress[];
     isComplete: boolean;
 }
 
-export const DeepResearchPreview: React.FC<DeepResearchPreviewProps> = ({ steps, isComplete }) => {
+interface TopicNode {
+    label: string | null;
+    events: ResearchProgress[];
+}
+
+/** Group events into run-level steps and one branch per sub-topic, in arrival order */
+const buildTree = (events: ResearchProgress[]) => {
+    const runLevel: ResearchProgress[] = [];
+    const topics: TopicNode[] = [];
+    for (const event of events) {
+        if (!event.sub_topic) {
+            runLevel.push(event);
+            continue;
+        }
+        let node = topics.find(t => t.label === event.sub_topic);
+        if (!node) {
+            node = { label: event.sub_topic, events: [] };
+            topics.push(node);
+        }
+        node.events.push(event);
+    }
+    return { runLevel, topics };
+};
+
+const getIcon = (phase: ResearchPhase, active: boolean) => {
+    if (phase === 'complete') return <CheckCircle2 className="w-4 h-4 text-green-500" />;
+    if (phase === 'failed') return <XCircle className="w-4 h-4 text-red-500" />;
+    if (active) return <Loader2 className="w-4 h-4 text-blue-500 animate-spin" />;
+
+    switch (phase) {
+        case 'planning': return <Brain className="w-4 h-4 text-purple-500" />;
+        case 'searching': return <Search className="w-4 h-4 text-blue-500" />;
+        case 'reading':
+        case 'extracting': return <FileText className="w-4 h-4 text-yellow-500" />;
+        case 'synthesizing':
+        case 'revising': return <Brain className="w-4 h-4 text-green-500" />;
+        case 'budget': return <Gauge className="w-4 h-4 text-orange-500" />;
+        default: return <Circle className="w-4 h-4 text-gray-400" />;
+    }
+};
+
+const StepRow: React.FC<{ event: ResearchProgress; active: boolean }> = ({ event, active }) => (
+    <div className="flex gap-2 text-sm animate-in fade-in slide-in-from-left-2 duration-300">
+        <div className="flex-shrink-0 mt-0.5">{getIcon(event.phase, active)}</div>
+        <div className="flex-1 min-w-0">
+            <p className={`font-medium ${active ? 'text-foreground' : 'text-muted-foreground'}`}>{event.message}</p>
+            {event.source_url && (
+                <p className="flex items-center gap-1 text-xs text-muted-foreground/70 mt-0.5 truncate font-mono">
+                    <Link2 className="w-3 h-3 flex-shrink-0" />
+                    <span className="truncate">{event.source_url}</span>
+                </p>
+            )}
+            {event.details && (
+                <p className="text-xs text-muted-foreground/70 mt-0.5 truncate font-mono bg-muted/30 p-1 rounded">
+                    {event.details}
+                </p>
+            )}
+        </div>
+    </div>
+);
+
+export const DeepResearchPreview: React.FC<DeepResearchPreviewProps> = ({ events, isComplete }) => {
     const scrollRef = useRef<HTMLDivElement>(null);
 
     useEffect(() => {
         if (scrollRef.current) {
             scrollRef.current.scrollTop = scrollRef.current.scrollHeight;
         }
-    }, [steps]);
-
-    const getIcon = (type: string, isLast: boolean, isComplete: boolean) => {
-        if (isComplete) return <CheckCircle2 className="w-5 h-5 text-green-500" />;
-        if (isLast) return <Loader2 className="w-5 h-5 text-blue-500 animate-spin" />;
-
-        switch (type) {
-            case 'planning': return <Brain className="w-5 h-5 text-purple-500" />;
-            case 'searching': return <Search className="w-5 h-5 text-blue-500" />;
-            case 'analyzing': return <FileText className="w-5 h-5 text-yellow-500" />;
-            case 'synthesizing': return <Brain className="w-5 h-5 text-green-500" />;
-            default: return <Circle className="w-5 h-5 text-gray-400" />;
-        }
-    };
+    }, [events]);
+
+    const { runLevel, topics } = useMemo(() => buildTree(events), [events]);
+    const latest = events[events.length - 1];
+    const percent = latest ? Math.min(100, latest.percent_complete) : 0;
+    const done = isComplete || (latest?.phase === 'complete' && !latest.sub_topic);
+    const failed = latest?.phase === 'failed' && !latest.sub_topic;
+    const lastEvent = done || failed ? undefined : latest;
 
     return (
         <div className="bg-card/50 border border-border rounded-lg p-4 my-2 max-w-2xl w-full font-sans">
-            <div className="flex items-center gap-2 mb-3 border-b border-border pb-2">
+            <div className="flex items-center gap-2 mb-2 border-b border-border pb-2">
                 <Brain className="w-5 h-5 text-primary" />
                 <h3 className="font-semibold text-sm">Deep Research Agent</h3>
-                {isComplete ? (
+                {failed ? (
+                    <span className="text-xs bg-red-500/10 text-red-500 px-2 py-0.5 rounded-full ml-auto">Failed</span>
+                ) : done ? (
                     <span className="text-xs bg-green-500/10 text-green-500 px-2 py-0.5 rounded-full ml-auto">Completed</span>
                 ) : (
                     <span className="text-xs bg-blue-500/10 text-blue-500 px-2 py-0.5 rounded-full ml-auto animate-pulse">Active</span>
                 )}
             </div>
 
+            <div className="mb-3">
+                <div className="h-1.5 w-full bg-muted rounded-full overflow-hidden">
+                    <div
+                        className={`h-full transition-all duration-500 ${failed ? 'bg-red-500' : 'bg-primary'}`}
+                        style={{ width: `${percent}%` }}
+                    />
+                </div>
+                <div className="flex justify-between text-xs text-muted-foreground mt-1">
+                    <span>{percent}%</span>
+                    {latest && <span>~{latest.tokens_used.toLocaleString()} tokens</span>}
+                </div>
+            </div>
+
             <div ref={scrollRef} className="space-y-3 max-h-60 overflow-y-auto pr-2 custom-scrollbar">
-                {steps.map((step, index) => {
-                    const isLast = index === steps.length - 1;
+                {topics.map(topic => {
+                    const last = topic.events[topic.events.length - 1];
+                    const finished = last.phase === 'complete' || last.phase === 'failed';
                     return (
-                        <div key={index} className="flex gap-3 text-sm animate-in fade-in slide-in-from-left-2 duration-300">
-                            <div className="flex-shrink-0 mt-0.5">
-                                {getIcon(step.step_type, isLast && !isComplete, isComplete && index < steps.length - 1)}
+                        <div key={topic.label ?? ''}>
+                            <div className="flex items-center gap-2 text-sm font-semibold">
+                                {getIcon(finished ? last.phase : 'searching', !finished)}
+                                <span className="truncate">{topic.label}</span>
                             </div>
-                            <div className="flex-1 min-w-0">
-                                <p className={`font-medium ${isLast && !isComplete ? 'text-foreground' : 'text-muted-foreground'}`}>
-                                    {step.description}
-                                </p>
-                                {step.details && (
-                                    <p className="text-xs text-muted-foreground/70 mt-0.5 truncate font-mono bg-muted/30 p-1 rounded">
-                                        {step.details}
-                                    </p>
-                                )}
+                            <div className="ml-2 mt-1 pl-3 border-l border-border space-y-1.5">
+                                {topic.events.map((event, index) => (
+                                    <StepRow key={index} event={event} active={event === lastEvent} />
+                                ))}
                             </div>
                         </div>
                     );
                 })}
 
-                {steps.length === 0 && (
+                {runLevel.map((event, index) => (
+                    <StepRow key={index} event={event} active={event === lastEvent} />
+                ))}
+
+                {events.length === 0 && (
                     <div className="flex items-center gap-2 text-muted-foreground text-sm italic">
                         <Loader2 className="w-4 h-4 animate-spin" />
                         Initializing agent...
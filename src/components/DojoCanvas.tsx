@@ -1,6 +1,6 @@
 import React, { useRef, useState } from 'react';
 import { emitEvent as emit, listenEvent as listen } from '../lib/events';
-import { saveMarkdownFile, readMarkdownFile, openExternal, openMediaWindow } from '../lib/tauri-bridge';
+import { saveMarkdownFile, readMarkdownWithEtag, openExternal, openMediaWindow } from '../lib/tauri-bridge';
 import { Copy, Check, Maximize2, Trash2, Save, Loader2, FolderOpen, X } from 'lucide-react';
 import MarkdownRenderer from './MarkdownRenderer';
 import { useTheme } from '../contexts/ThemeContext';
@@ -134,6 +134,8 @@ const DojoCanvas: React.FC<DojoCanvasProps> = ({
   const [saveStatus, setSaveStatus] = useState<'idle' | 'success' | 'error'>('idle');
   const [notebookMode, setNotebookMode] = useState(true);
   const [showFilePicker, setShowFilePicker] = useState(false);
+  // The note opened from disk and its etag as read, so saving it back can't overwrite newer edits
+  const [openNote, setOpenNote] = useState<{ path: string; etag?: string } | null>(null);
   const [mediaPane, setMediaPane] = useState<{ type: 'url' | 'threejs' | 'manifold' | 'html' | 'youtube'; content: string } | null>(null);
   const mediaIframeRef = useRef<HTMLIFrameElement | null>(null);
   const [copied, setCopied] = useState(false);
@@ -344,30 +346,56 @@ const DojoCanvas: React.FC<DojoCanvasProps> = ({
     if (initialContent !== DEFAULT_CONTENT) {
       console.log('?? Overwriting local content with prop content');
       setContent(initialContent);
+      setOpenNote(null);
     }
   }, [initialContent]);
 
   const handleSave = async () => {
     if (!content || content === DEFAULT_CONTENT) return;
 
-    const filename = window.prompt('Enter a filename for this guide (e.g., "rust-basics"):');
-    if (!filename) return;
+    // An opened note is saved back in place unless the user asks for a new guide
+    const noteName = openNote?.path.split(/[\\/]/).pop();
+    const target = openNote && window.confirm(`Save your changes to ${noteName}? Cancel to save them as a new guide.`)
+      ? openNote
+      : null;
 
-    // Ensure filename ends with .md
-    const safeFilename = filename.endsWith('.md') ? filename : `${filename}.md`;
-    const path = `generated-guides/${safeFilename}`;
+    let path = target?.path;
+    if (!path) {
+      const filename = window.prompt('Enter a filename for this guide (e.g., "rust-basics"):');
+      if (!filename) return;
+
+      // Ensure filename ends with .md
+      const safeFilename = filename.endsWith('.md') ? filename : `${filename}.md`;
+      path = `generated-guides/${safeFilename}`;
+    }
 
     setIsSaving(true);
     setSaveStatus('idle');
 
     try {
-      await saveMarkdownFile(path, content);
+      const etag = await saveMarkdownFile(path, content, target?.etag);
+      if (target) setOpenNote({ path, etag });
       setSaveStatus('success');
       setTimeout(() => setSaveStatus('idle'), 2000);
     } catch (error) {
       console.error('Failed to save file:', error);
       setSaveStatus('error');
-      alert('Failed to save file. Please try again.');
+      const message = String(error);
+      if (target && message.startsWith('merge_required')) {
+        // Someone (e.g. the agent) changed the note since it was opened
+        if (window.confirm(`${message}\n\nOverwrite the note with your version? Cancel keeps your edits here so you can copy them and re-open the note.`)) {
+          const etag = await saveMarkdownFile(path, content).catch((e) => {
+            alert('Failed to save file: ' + e);
+            return undefined;
+          });
+          if (etag !== undefined) {
+            setOpenNote({ path, etag });
+            setSaveStatus('success');
+          }
+        }
+      } else {
+        alert('Failed to save file. Please try again.');
+      }
     } finally {
       setIsSaving(false);
     }
@@ -375,8 +403,9 @@ const DojoCanvas: React.FC<DojoCanvasProps> = ({
 
   const handleLoadFile = async (path: string) => {
     try {
-      const fileContent = await readMarkdownFile(path);
-      setContent(fileContent);
+      const note = await readMarkdownWithEtag(path);
+      setContent(note.content);
+      setOpenNote({ path, etag: note.etag });
     } catch (error) {
       console.error('Failed to load file:', error);
       alert('Failed to load file. Please try again.');
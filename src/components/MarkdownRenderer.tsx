@@ -5,6 +5,7 @@ import { emitEvent as emit } from '../lib/events';
 import { openExternal } from '../lib/tauri-bridge';
 import { useTheme } from '../contexts/ThemeContext';
 import MermaidRenderer from './MermaidRenderer';
+import ChartBlock from './ChartBlock';
 import { ChevronDown, ChevronUp } from 'lucide-react';
 
 // Collapsible wrapper for long code blocks
@@ -179,6 +180,10 @@ const MarkdownRenderer: React.FC<MarkdownRendererProps> = ({
               return <MermaidRenderer code={codeContent} />;
             }
 
+            if (!inline && lang === 'chart') {
+              return <ChartBlock code={codeContent} />;
+            }
+
             // Languages that can be previewed in canvas
             const previewableLanguages = ['html', 'htm', 'javascript', 'js', 'css', 'svg'];
 
@@ -0,0 +1,64 @@
+import React, { useEffect, useRef, useState } from 'react';
+import { api } from '../lib/api';
+import { useAuth } from '../contexts/AuthContext';
+
+// The small window the global capture hotkey opens (see capture.rs).
+// Enter saves to the inbox note, Shift+Enter adds a line, Escape closes.
+const QuickCapture: React.FC = () => {
+    const { user } = useAuth();
+    const [text, setText] = useState('');
+    const [status, setStatus] = useState<'idle' | 'saving' | 'error'>('idle');
+    const [error, setError] = useState('');
+    const inputRef = useRef<HTMLTextAreaElement>(null);
+
+    useEffect(() => {
+        inputRef.current?.focus();
+        // Pressing the hotkey again while the window is open brings it back to the front
+        const onFocus = () => inputRef.current?.focus();
+        window.addEventListener('focus', onFocus);
+        return () => window.removeEventListener('focus', onFocus);
+    }, []);
+
+    const save = async () => {
+        if (!text.trim() || status === 'saving') return;
+        setStatus('saving');
+        try {
+            await api.quickCapture(text, user?.id || 'guest');
+            setText('');
+            setStatus('idle');
+            await api.closeQuickCapture();
+        } catch (e) {
+            setStatus('error');
+            setError(String(e));
+        }
+    };
+
+    const onKeyDown = (e: React.KeyboardEvent<HTMLTextAreaElement>) => {
+        if (e.key === 'Enter' && !e.shiftKey) {
+            e.preventDefault();
+            save();
+        } else if (e.key === 'Escape') {
+            e.preventDefault();
+            api.closeQuickCapture();
+        }
+    };
+
+    return (
+        <div className="h-screen w-screen p-3 bg-card border border-border rounded-xl flex flex-col gap-2" data-tauri-drag-region>
+            <textarea
+                ref={inputRef}
+                value={text}
+                onChange={(e) => setText(e.target.value)}
+                onKeyDown={onKeyDown}
+                placeholder="Capture a thought, link or to-do…"
+                className="flex-1 w-full resize-none bg-transparent text-foreground placeholder:text-muted-foreground outline-none text-sm"
+            />
+            <div className="flex items-center justify-between text-xs text-muted-foreground" data-tauri-drag-region>
+                <span>{status === 'error' ? <span className="text-red-500">{error}</span> : 'Enter to save to the inbox · Esc to close'}</span>
+                {status === 'saving' && <span>Saving…</span>}
+            </div>
+        </div>
+    );
+};
+
+export default QuickCapture;
@@ -4,7 +4,7 @@
  */
 
 import React, { useState } from 'react';
-import { Calculator, Search, BookOpen, FileText, Globe, PenTool, MessageSquare, Database, Wrench, ChevronDown, ChevronUp, FolderTree, Bot } from 'lucide-react';
+import { Calculator, Search, BookOpen, FileText, Globe, PenTool, MessageSquare, Database, Wrench, ChevronDown, ChevronUp, FolderTree, Bot, Bell, Camera, Scissors, Youtube, Github, Languages } from 'lucide-react';
 import { motion, AnimatePresence } from 'framer-motion';
 
 interface Tool {
@@ -49,6 +49,14 @@ const ToolToolbar: React.FC<ToolToolbarProps> = ({
       costLevel: 'low',
       enabled: enabledTools.search_knowledge || false
     },
+    {
+      id: 'get_note_links',
+      name: 'Note Links',
+      description: 'Follow links and backlinks between notes',
+      icon: Search,
+      costLevel: 'low',
+      enabled: enabledTools.get_note_links || false
+    },
     {
       id: 'read_file',
       name: 'File Reader',
@@ -73,6 +81,30 @@ const ToolToolbar: React.FC<ToolToolbarProps> = ({
       costLevel: 'low',
       enabled: enabledTools.scan_codebase || false
     },
+    {
+      id: 'get_repo_stats',
+      name: 'Repo Stats',
+      description: 'Languages, LOC and hot spots',
+      icon: FolderTree,
+      costLevel: 'low',
+      enabled: enabledTools.get_repo_stats || false
+    },
+    {
+      id: 'grep_codebase',
+      name: 'Grep Code',
+      description: 'Regex search file contents',
+      icon: Search,
+      costLevel: 'low',
+      enabled: enabledTools.grep_codebase || false
+    },
+    {
+      id: 'find_symbol',
+      name: 'Find Symbol',
+      description: 'Locate function/class definitions',
+      icon: Search,
+      costLevel: 'low',
+      enabled: enabledTools.find_symbol || false
+    },
     {
       id: 'start_debate',
       name: 'Start Debate',
@@ -121,6 +153,30 @@ const ToolToolbar: React.FC<ToolToolbarProps> = ({
       costLevel: 'medium',
       enabled: enabledTools.create_study_guide || false
     },
+    {
+      id: 'create_flashcards',
+      name: 'Flashcards',
+      description: 'Add cards to your review deck',
+      icon: BookOpen,
+      costLevel: 'low',
+      enabled: enabledTools.create_flashcards || false
+    },
+    {
+      id: 'notify',
+      name: 'Notify',
+      description: 'Send desktop reminders',
+      icon: Bell,
+      costLevel: 'low',
+      enabled: enabledTools.notify || false
+    },
+    {
+      id: 'log_daily_note',
+      name: 'Daily Log',
+      description: "Append to today's journal",
+      icon: BookOpen,
+      costLevel: 'low',
+      enabled: enabledTools.log_daily_note || false
+    },
     {
       id: 'write_file',
       name: 'Write File',
@@ -132,7 +188,7 @@ const ToolToolbar: React.FC<ToolToolbarProps> = ({
     {
       id: 'web_search',
       name: 'Web Search',
-      description: 'Search the web (Tavily)',
+      description: 'Search the web',
       icon: Globe,
       costLevel: 'high',
       enabled: enabledTools.web_search || false
@@ -145,6 +201,70 @@ const ToolToolbar: React.FC<ToolToolbarProps> = ({
       costLevel: 'high',
       enabled: enabledTools.deep_research || false
     },
+    {
+      id: 'ingest_paper',
+      name: 'Read Paper',
+      description: 'Read arXiv/PDF papers',
+      icon: BookOpen,
+      costLevel: 'medium',
+      enabled: enabledTools.ingest_paper || false
+    },
+    {
+      id: 'read_pdf',
+      name: 'Read PDF',
+      description: 'Extract text from PDF files',
+      icon: FileText,
+      costLevel: 'low',
+      enabled: enabledTools.read_pdf || false
+    },
+    {
+      id: 'capture_screenshot',
+      name: 'Screenshot',
+      description: 'Capture and OCR the screen',
+      icon: Camera,
+      costLevel: 'medium',
+      enabled: enabledTools.capture_screenshot || false
+    },
+    {
+      id: 'read_tool_result',
+      name: 'Tool Results',
+      description: 'Page through large tool outputs',
+      icon: Database,
+      costLevel: 'low',
+      enabled: enabledTools.read_tool_result || false
+    },
+    {
+      id: 'clip_url',
+      name: 'Web Clipper',
+      description: 'Save a web page as a note',
+      icon: Scissors,
+      costLevel: 'medium',
+      enabled: enabledTools.clip_url || false
+    },
+    {
+      id: 'harvest_youtube_transcript',
+      name: 'YouTube Transcript',
+      description: 'Save a video transcript as a note',
+      icon: Youtube,
+      costLevel: 'medium',
+      enabled: enabledTools.harvest_youtube_transcript || false
+    },
+    {
+      id: 'harvest_github',
+      name: 'GitHub Harvest',
+      description: "Save a repository's docs as notes",
+      icon: Github,
+      costLevel: 'medium',
+      enabled: enabledTools.harvest_github || false
+    },
+    {
+      id: 'translate',
+      name: 'Translate',
+      description: 'Translate text or notes',
+      icon: Languages,
+      costLevel: 'medium',
+      enabled: enabledTools.translate || false
+    },
     {
       id: 'consult_agent',
       name: 'Consult Agent',
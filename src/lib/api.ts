@@ -50,11 +50,11 @@ export const api = {
     },
 
     /**
-     * Get content structure (knowledge base files)
+     * Get content structure (knowledge base files); cached by the backend unless forceRefresh
      */
-    getContentStructure: async (): Promise<ContentItem[]> => {
+    getContentStructure: async (forceRefresh = false): Promise<ContentItem[]> => {
         if (isTauri()) {
-            return await invoke<ContentItem[]>('get_content_structure');
+            return await invoke<ContentItem[]>('get_content_structure', { forceRefresh });
         } else {
             return await webApi.getContentStructure();
         }
@@ -116,6 +116,423 @@ export const api = {
         }
     },
 
+    /**
+     * Export a saved session as readable JSON (desktop only)
+     */
+    exportSession: async (name: string, path: string): Promise<string> => {
+        if (isTauri()) {
+            return await invoke<string>('export_session', { name, path });
+        }
+        throw new Error('Session export is only available in the desktop app');
+    },
+
+    /**
+     * Encrypt a saved session with the notes and files it refers to, for sharing (desktop only)
+     */
+    exportSessionBundle: async (name: string, passphrase: string, dest: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('export_session_bundle', { name, passphrase, dest });
+        }
+        throw new Error('Session bundles are only available in the desktop app');
+    },
+
+    /**
+     * Open a shared session bundle; without confirm it only reports where things would go (desktop only)
+     */
+    importSessionBundle: async (path: string, passphrase: string, confirm = false): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('import_session_bundle', { path, passphrase, confirm });
+        }
+        throw new Error('Session bundles are only available in the desktop app');
+    },
+
+    /**
+     * Render a saved session's canvases as a standalone HTML page, PDF or PNG; `dest` defaults to the exports folder (desktop only)
+     */
+    exportCanvas: async (sessionId: string, format: 'html' | 'pdf' | 'png' = 'html', dest?: string): Promise<{ path: string; format: string; warnings: string[] }> => {
+        if (isTauri()) {
+            return await invoke('export_canvas', { sessionId, format, dest });
+        }
+        throw new Error('Canvas export is only available in the desktop app');
+    },
+
+    /**
+     * Sync the Obsidian vault set in thinkspace.toml with its knowledge base folder (desktop only)
+     */
+    syncObsidianVault: async (): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('sync_obsidian_vault');
+        }
+        throw new Error('Obsidian sync is only available in the desktop app');
+    },
+
+    /**
+     * Export a note or folder as pages under a Notion page; exporting again updates them (desktop only)
+     */
+    exportToNotion: async (folderOrNote: string, notionToken: string, parentPage: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('export_to_notion', { folderOrNote, notionToken, parentPage });
+        }
+        throw new Error('Notion export is only available in the desktop app');
+    },
+
+    /**
+     * Pull a Zotero library's item metadata, only what changed since the last pull (desktop only)
+     */
+    pullZoteroLibrary: async (zoteroKey: string, library: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('pull_zotero_library', { zoteroKey, library });
+        }
+        throw new Error('Zotero sync is only available in the desktop app');
+    },
+
+    /**
+     * Pulled Zotero items, optionally of one library and matching a search (desktop only)
+     */
+    listZoteroItems: async (library?: string, query?: string, limit?: number): Promise<any[]> => {
+        if (isTauri()) {
+            return await invoke('list_zotero_items', { library, query, limit });
+        }
+        return [];
+    },
+
+    /**
+     * File a research run's cited sources into a Zotero collection, the topic's unless one is given (desktop only)
+     */
+    pushResearchToZotero: async (zoteroKey: string, library: string, runId: string, collection?: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('push_research_to_zotero', { zoteroKey, library, runId, collection });
+        }
+        throw new Error('Zotero sync is only available in the desktop app');
+    },
+
+    /**
+     * Restore an archive made by the data export; without confirm it only reports what would change (desktop only)
+     */
+    restoreAllData: async (archive: string, confirm = false): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('restore_all_data', { archive, confirm });
+        }
+        throw new Error('Restoring data is only available in the desktop app');
+    },
+
+    /**
+     * Back up to the cloud target set in thinkspace.toml now (desktop only)
+     */
+    backupNow: async (): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('backup_now');
+        }
+        throw new Error('Cloud backups are only available in the desktop app');
+    },
+
+    /**
+     * Backups at the configured target, newest first (desktop only)
+     */
+    listBackups: async (): Promise<{ id: string; name: string; size: number; modified: string }[]> => {
+        if (isTauri()) {
+            return await invoke('list_backups');
+        }
+        return [];
+    },
+
+    /**
+     * Download, decrypt and restore a backup; without confirm it only reports what would change (desktop only)
+     */
+    restoreBackup: async (id: string, passphrase?: string, confirm = false): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('restore_backup', { id, passphrase, confirm });
+        }
+        throw new Error('Cloud backups are only available in the desktop app');
+    },
+
+    /**
+     * Open reminders due within `days` (default 7), overdue ones included (desktop only)
+     */
+    listUpcomingReminders: async (days?: number): Promise<any[]> => {
+        if (isTauri()) {
+            return await invoke('list_upcoming_reminders', { days });
+        }
+        return [];
+    },
+
+    /**
+     * Add a reminder; `due` is an ISO time or date or words like "friday at 5pm", else it's read from the text (desktop only)
+     */
+    addReminder: async (text: string, due?: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('add_reminder', { text, due });
+        }
+        throw new Error('Reminders are only available in the desktop app');
+    },
+
+    /**
+     * Mark a reminder done (desktop only)
+     */
+    completeReminder: async (id: string): Promise<void> => {
+        if (isTauri()) {
+            await invoke('complete_reminder', { id });
+            return;
+        }
+        throw new Error('Reminders are only available in the desktop app');
+    },
+
+    /**
+     * Write open reminders to a .ics file, or thinkspace-reminders.ics in a folder (desktop only)
+     */
+    exportRemindersIcs: async (dest: string): Promise<string> => {
+        if (isTauri()) {
+            return await invoke<string>('export_reminders_ics', { dest });
+        }
+        throw new Error('Reminders are only available in the desktop app');
+    },
+
+    /**
+     * Push open reminders to the CalDAV calendar in thinkspace.toml; `all` re-sends ones pushed before (desktop only)
+     */
+    pushRemindersToCaldav: async (all = false): Promise<{ pushed: number; errors: string[] }> => {
+        if (isTauri()) {
+            return await invoke('push_reminders_to_caldav', { all });
+        }
+        throw new Error('Reminders are only available in the desktop app');
+    },
+
+    /**
+     * A project's milestones and deadlines, or every project's, by date (desktop only)
+     */
+    getProjectMilestones: async (projectId?: number, includeDone = true): Promise<any[]> => {
+        if (isTauri()) {
+            return await invoke('get_project_milestones', { projectId, includeDone });
+        }
+        return [];
+    },
+
+    /**
+     * Add a milestone or deadline to a project, or update it when it has an id (desktop only)
+     */
+    saveProjectMilestone: async (milestone: {
+        id?: number;
+        project_id: number;
+        title: string;
+        kind?: 'milestone' | 'deadline';
+        due_date: string;
+        due_time?: string;
+        notes?: string;
+        done?: boolean;
+    }): Promise<number> => {
+        if (isTauri()) {
+            return await invoke<number>('save_project_milestone', { milestone });
+        }
+        throw new Error('Project milestones are only available in the desktop app');
+    },
+
+    /**
+     * Delete a project milestone (desktop only)
+     */
+    deleteProjectMilestone: async (id: number): Promise<void> => {
+        if (isTauri()) {
+            await invoke('delete_project_milestone', { id });
+            return;
+        }
+        throw new Error('Project milestones are only available in the desktop app');
+    },
+
+    /**
+     * Write open milestones, of one project or all, to a .ics file or into a folder (desktop only)
+     */
+    exportMilestonesIcs: async (dest: string, projectId?: number, includeDone = false): Promise<string> => {
+        if (isTauri()) {
+            return await invoke<string>('export_ical', { dest, projectId, includeDone });
+        }
+        throw new Error('Project milestones are only available in the desktop app');
+    },
+
+    /**
+     * Notification categories (reminders, research, backup, errors) and whether each is shown (desktop only)
+     */
+    getNotificationPreferences: async (): Promise<{ category: string; label: string; enabled: boolean }[]> => {
+        if (isTauri()) {
+            return await invoke('get_notification_preferences');
+        }
+        return [];
+    },
+
+    /**
+     * Switch one notification category on or off (desktop only)
+     */
+    setNotificationPreference: async (category: string, enabled: boolean): Promise<{ category: string; label: string; enabled: boolean }[]> => {
+        if (isTauri()) {
+            return await invoke('set_notification_preference', { category, enabled });
+        }
+        throw new Error('Desktop notifications are only available in the desktop app');
+    },
+
+    /**
+     * Show a desktop notification unless its category is switched off (desktop only)
+     */
+    sendNotification: async (category: string, title: string, body: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('send_notification', { category, title, body });
+        }
+        throw new Error('Desktop notifications are only available in the desktop app');
+    },
+
+    /**
+     * Email digest settings, when the next one goes out and how the last one went (desktop only)
+     */
+    getDigestStatus: async (): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('get_digest_status');
+        }
+        throw new Error('The email digest is only available in the desktop app');
+    },
+
+    /**
+     * Send the email digest now; with preview it's only composed and returned (desktop only)
+     */
+    sendDigestNow: async (preview = false): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('send_digest_now', { preview });
+        }
+        throw new Error('The email digest is only available in the desktop app');
+    },
+
+    /**
+     * Discord/Slack channels the agent answers in, with their reply counts and last errors (desktop only)
+     */
+    getChatBridgeStatus: async (): Promise<any[]> => {
+        if (isTauri()) {
+            return await invoke('get_chat_bridge_status');
+        }
+        return [];
+    },
+
+    /**
+     * Append text to the inbox note and offer it to the TKG; `store` overrides the [capture] setting (desktop only)
+     */
+    quickCapture: async (text: string, userId?: string, store?: boolean): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('quick_capture', { text, userId, store });
+        }
+        throw new Error('Quick capture is only available in the desktop app');
+    },
+
+    /**
+     * Close the quick-capture window (desktop only)
+     */
+    closeQuickCapture: async (): Promise<void> => {
+        if (isTauri()) {
+            await invoke('close_quick_capture');
+        }
+    },
+
+    /**
+     * Copied links and long text the clipboard watcher offers to save, newest first (desktop only)
+     */
+    getClipboardOffers: async (): Promise<{ id: string; kind: 'url' | 'text'; content: string; preview: string; host: string | null; at: string }[]> => {
+        if (isTauri()) {
+            return await invoke('get_clipboard_offers');
+        }
+        return [];
+    },
+
+    /**
+     * Switch the clipboard watcher on or off; saved to thinkspace.toml (desktop only)
+     */
+    setClipboardWatch: async (enabled: boolean): Promise<boolean> => {
+        if (isTauri()) {
+            return await invoke<boolean>('set_clipboard_watch', { enabled });
+        }
+        throw new Error('The clipboard watcher is only available in the desktop app');
+    },
+
+    /**
+     * Save a clipboard offer: links are clipped, text goes to the capture inbox (desktop only)
+     */
+    acceptClipboardOffer: async (id: string, userId?: string): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('accept_clipboard_offer', { id, userId });
+        }
+        throw new Error('The clipboard watcher is only available in the desktop app');
+    },
+
+    /**
+     * Drop a clipboard offer without saving it (desktop only)
+     */
+    dismissClipboardOffer: async (id: string): Promise<boolean> => {
+        if (isTauri()) {
+            return await invoke<boolean>('dismiss_clipboard_offer', { id });
+        }
+        return false;
+    },
+
+    /**
+     * Screenshot a region (or the whole screen, or one the user selects), read its text and
+     * save both under research/screenshots/ (desktop only)
+     */
+    captureScreenshot: async (options: {
+        region?: { x: number; y: number; width: number; height: number };
+        select?: boolean;
+        ocr?: 'auto' | 'tesseract' | 'vision';
+        languages?: string;
+        grokApiKey?: string;
+    } = {}): Promise<{ path: string; image: string; text: string; ocr: string }> => {
+        if (isTauri()) {
+            return await invoke('capture_screenshot', options);
+        }
+        throw new Error('Screenshots are only available in the desktop app');
+    },
+
+    /**
+     * Save files to the knowledge base as if they'd been dropped on the window (desktop only)
+     */
+    ingestDroppedFiles: async (paths: string[]): Promise<{
+        folder: string;
+        ingested: { source: string; kind: 'markdown' | 'pdf' | 'image' | 'code'; path: string }[];
+        skipped: { source: string; reason: string }[];
+    }> => {
+        if (isTauri()) {
+            return await invoke('ingest_dropped_files', { paths });
+        }
+        throw new Error('File ingestion is only available in the desktop app');
+    },
+
+    /**
+     * Text of a PDF's pages, e.g. pageRange "2-5"; path is absolute or relative to the knowledge base (desktop only)
+     */
+    readPdf: async (path: string, pageRange?: string): Promise<{
+        path: string;
+        page_count: number;
+        pages: { number: number; text: string }[];
+        next_page: number | null;
+    }> => {
+        if (isTauri()) {
+            return await invoke('read_pdf', { path, pageRange });
+        }
+        throw new Error('Reading PDFs is only available in the desktop app');
+    },
+
+    /**
+     * Translate text, or a knowledge base note by path, keeping its markdown; save writes a
+     * note's translation next to it as <name>.<language>.md (desktop only)
+     */
+    translate: async (request: {
+        provider: 'minimax' | 'grok' | 'gemini';
+        apiKey: string;
+        grokKey?: string;
+        geminiKey?: string;
+        text?: string;
+        path?: string;
+        sourceLanguage?: string;
+        targetLanguage: string;
+        save?: boolean;
+    }): Promise<{ text: string; target_language: string; saved_to: string | null; chunks: number }> => {
+        if (isTauri()) {
+            return await invoke('translate_text', request);
+        }
+        throw new Error('Translation is only available in the desktop app');
+    },
+
     /**
      * Store knowledge (TKG on Tauri, Supabase on web)
      */
@@ -138,6 +555,56 @@ export const api = {
         }
     },
 
+    /**
+     * Backend subsystems that have finished starting (desktop only; the web app has none)
+     */
+    getReadySubsystems: async (): Promise<{ subsystem: string; error: string | null; elapsed_ms: number }[]> => {
+        if (isTauri()) {
+            return await invoke('get_ready_subsystems');
+        }
+        return [];
+    },
+
+    /**
+     * Stop the agent turn running in one chat session (desktop only)
+     */
+    stopAgentSession: async (name: string): Promise<boolean> => {
+        if (isTauri()) {
+            return await invoke<boolean>('stop_agent_session', { name });
+        }
+        return false;
+    },
+
+    /**
+     * Forget the live state of a chat session; its saved file is untouched (desktop only)
+     */
+    closeAgentSession: async (name: string): Promise<boolean> => {
+        if (isTauri()) {
+            return await invoke<boolean>('close_agent_session', { name });
+        }
+        return false;
+    },
+
+    /**
+     * Live history and settings of a chat session, with tool calls included (desktop only)
+     */
+    getAgentSession: async (name: string): Promise<any | null> => {
+        if (isTauri()) {
+            return await invoke('get_agent_session', { name });
+        }
+        return null;
+    },
+
+    /**
+     * Where agent turns spent their time: provider calls, tools, searches and DB work (desktop only)
+     */
+    getPerformanceReport: async (reset = false): Promise<any> => {
+        if (isTauri()) {
+            return await invoke('get_performance_report', { reset });
+        }
+        return { timings: [], recent_turns: [] };
+    },
+
     /**
      * Download image (Tauri only - web uses right-click save)
      */
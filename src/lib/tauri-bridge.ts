@@ -53,13 +53,14 @@ export async function getContentStructure(): Promise<ContentItem[]> {
 }
 
 /**
- * Save markdown content to a file
+ * Save markdown content to a file and return its new etag (desktop only).
+ * Pass the etag from readMarkdownWithEtag to have the save rejected ("merge_required")
+ * if the note changed on disk in the meantime.
  */
-export async function saveMarkdownFile(path: string, content: string): Promise<void> {
+export async function saveMarkdownFile(path: string, content: string, expectedEtag?: string): Promise<string | undefined> {
     if (isTauri) {
         const { invoke } = await import('@tauri-apps/api/tauri');
-        await invoke('save_markdown_file', { path, content });
-        return;
+        return await invoke<string>('save_markdown_file', { path, content, expectedEtag });
     }
 
     // Web mode: save to localStorage
@@ -78,6 +79,7 @@ export async function saveMarkdownFile(path: string, content: string): Promise<v
     }
 
     console.log(`📝 [Web] Saved guide "${filename}" to localStorage`);
+    return undefined;
 }
 
 /**
@@ -102,6 +104,67 @@ export async function readMarkdownFile(path: string): Promise<string> {
     return content;
 }
 
+/**
+ * Read markdown content together with its etag for conflict-checked saves
+ */
+export async function readMarkdownWithEtag(path: string): Promise<{ content: string; etag?: string }> {
+    if (isTauri) {
+        const { invoke } = await import('@tauri-apps/api/tauri');
+        return await invoke<{ content: string; etag: string }>('read_markdown_with_etag', { path });
+    }
+
+    // Web mode has a single writer, so no etag is needed
+    return { content: await readMarkdownFile(path) };
+}
+
+export interface RenderedNote {
+    path: string;
+    title?: string;
+    html: string;
+    warnings: string[];
+    needs_client_mermaid: boolean;
+}
+
+/**
+ * Render a note to display-ready HTML (embeds expanded, mermaid as SVG, images inlined).
+ * Returns null in web mode, where the canvas renders markdown itself.
+ */
+export async function renderMarkdown(path: string): Promise<RenderedNote | null> {
+    if (isTauri) {
+        const { invoke } = await import('@tauri-apps/api/tauri');
+        return await invoke<RenderedNote>('render_markdown', { path });
+    }
+    return null;
+}
+
+export type SearchProviderKind = 'auto' | 'tavily' | 'brave' | 'searxng' | 'duckduckgo';
+
+export interface SearchSettings {
+    provider: SearchProviderKind;
+    brave_api_key?: string | null;
+    searxng_url?: string | null;
+}
+
+/**
+ * Search backend used by web_search and deep research. 'auto' uses Tavily when a key is set,
+ * then Brave, then SearXNG, and falls back to DuckDuckGo.
+ */
+export async function getSearchSettings(): Promise<SearchSettings> {
+    if (isTauri) {
+        const { invoke } = await import('@tauri-apps/api/tauri');
+        return await invoke<SearchSettings>('get_search_settings');
+    }
+    return { provider: 'auto' };
+}
+
+export async function setSearchSettings(settings: SearchSettings): Promise<SearchSettings> {
+    if (isTauri) {
+        const { invoke } = await import('@tauri-apps/api/tauri');
+        return await invoke<SearchSettings>('set_search_settings', { settings });
+    }
+    return settings;
+}
+
 /**
  * Open URL in external browser
  */
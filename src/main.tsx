@@ -7,15 +7,26 @@ import { ThemeProvider } from './contexts/ThemeContext';
 import './styles/index.css';
 
 const App = React.lazy(() => import('./App'));
+const QuickCapture = React.lazy(() => import('./components/QuickCapture'));
 
 // Only show the app in Tauri (desktop)
 // Web users see the landing page with download links
 const isTauri = typeof window !== 'undefined' && (window as any).__TAURI__;
+// The window the global capture hotkey opens
+const isQuickCapture = isTauri && window.location.hash === '#quick-capture';
 
 
 ReactDOM.createRoot(document.getElementById('root') as HTMLElement).render(
   <React.StrictMode>
-    {isTauri ? (
+    {isQuickCapture ? (
+      <ThemeProvider>
+        <AuthProvider>
+          <Suspense fallback={null}>
+            <QuickCapture />
+          </Suspense>
+        </AuthProvider>
+      </ThemeProvider>
+    ) : isTauri ? (
       <AuthProvider>
         <AuthWrapper>
           <Suspense fallback={null}>